@@ -324,6 +324,24 @@ pub fn build(b: *std.Build) void {
     const run_step = b.step("run", "Run Paw compiler");
     run_step.dependOn(&run_cmd.step);
     
+    // 🆕 编译器性能基准：zig build bench [-- --baseline benches/baseline.json]
+    const bench_cmd = b.addRunArtifact(exe);
+    bench_cmd.addArgs(&[_][]const u8{ "self-bench", "benches" });
+    if (b.args) |args| {
+        bench_cmd.addArgs(args);
+    }
+    const bench_step = b.step("bench", "Benchmark the compiler on the benches/ corpus");
+    bench_step.dependOn(&bench_cmd.step);
+    
+    // 🆕 语法文档：zig build grammar 重新生成 docs/GRAMMAR.ebnf 和 docs/grammar.html
+    const grammar_ebnf_cmd = b.addRunArtifact(exe);
+    grammar_ebnf_cmd.addArgs(&[_][]const u8{ "grammar", "--ebnf", "-o", "docs/GRAMMAR.ebnf" });
+    const grammar_html_cmd = b.addRunArtifact(exe);
+    grammar_html_cmd.addArgs(&[_][]const u8{ "grammar", "--html", "-o", "docs/grammar.html" });
+    const grammar_step = b.step("grammar", "Regenerate docs/GRAMMAR.ebnf and docs/grammar.html");
+    grammar_step.dependOn(&grammar_ebnf_cmd.step);
+    grammar_step.dependOn(&grammar_html_cmd.step);
+    
     // 🆕 LLVM 编译流程步骤
     const llvm_example = b.option([]const u8, "llvm-example", "Compile and run a .paw file with LLVM") orelse "examples/llvm_demo.paw";
     
@@ -0,0 +1,243 @@
+//! AbiTest - 编译器与运行时之间的 golden ABI 测试（pawc abitest <dir>）
+//!
+//! pawc abitest <dir> [--target <triple>]... [--update]
+//!
+//! 对目录下（递归）每个 .paw 程序和每个目标：
+//!   1. pawc <file> --backend=c -o <tmp>                生成 C 代码
+//!   2. zig cc [-target <triple>] <tmp>.c -o <exe>       为目标编译并链接 libc
+//!   3. 运行：本机目标直接运行，其他 Linux 架构通过 qemu-<arch>，Windows 通过 wine
+//!      PAW_LOG_LEVEL 固定为 warn（与未设置时相同），日志输出不随调用者的环境变化
+//!   4. stdout / stderr 与 <file>.stdout / <file>.stderr 逐字节比较（没有 .stderr
+//!      文件时期望为空），退出码与源文件头部的 "期望退出码：N" 比较（默认 0）
+//!
+//! 用于在用户之前发现生成代码与运行时（prelude 的 extern 函数、生成的 C 运行时、
+//! libc）之间的 ABI 偏差。没有可用运行器的目标被跳过。
+//! --update 用本机运行的结果重写 golden 文件。
+//!
+//! 注意：@link_name 按编译器所在系统解析，交叉到其他操作系统时使用的是本机的符号名。
+
+const std = @import("std");
+const builtin = @import("builtin");
+const selftest = @import("selftest.zig");
+const toolchain = @import("toolchain.zig");
+
+/// 编译产物的临时目录（结束后删除）
+const WORK_DIR = ".paw-abitest";
+/// 源文件头部的期望退出码标记
+const EXIT_CODE_MARKER = "期望退出码：";
+/// 🆕 运行程序时固定的 PAW_LOG_LEVEL（paw_log 的输出在 golden stderr 中，不随调用者的环境变化）
+const PINNED_LOG_LEVEL = "warn";
+
+pub const Options = struct {
+    targets: []const []const u8 = &.{"native"},  // zig 目标三元组，"native" 为本机
+    update: bool = false,                         // 重写 golden 文件
+};
+
+/// 运行目标程序的方式
+const Runner = union(enum) {
+    direct,
+    emulator: []const u8,  // qemu-aarch64、wine 等（由 allocator 分配）
+};
+
+/// 运行目录中的所有程序，全部通过（或跳过）时返回 true
+pub fn run(allocator: std.mem.Allocator, dir_path: []const u8, options: Options) !bool {
+    const self_exe = try std.fs.selfExePathAlloc(allocator);
+    defer allocator.free(self_exe);
+
+    var files = std.ArrayList([]const u8){};
+    defer {
+        for (files.items) |file| allocator.free(file);
+        files.deinit(allocator);
+    }
+    try selftest.collectPrograms(allocator, dir_path, &files);
+    std.mem.sort([]const u8, files.items, {}, selftest.lessThan);
+
+    if (files.items.len == 0) {
+        std.debug.print("No .paw files found in {s}\n", .{dir_path});
+        return true;
+    }
+
+    // 🆕 为各目标编译需要 zig cc：缺少时给出安装方法，而不是在第一个程序上报出原始错误
+    if (try toolchain.probe(allocator, .zig)) |version| {
+        allocator.free(version);
+    } else {
+        std.debug.print("❌ Error: abitest needs `zig cc` to compile for each target, but zig was not found\n", .{});
+        std.debug.print("💡 To install it: {s}\n", .{toolchain.Linker.zig.installHint()});
+        return false;
+    }
+
+    try std.fs.cwd().makePath(WORK_DIR);
+    defer std.fs.cwd().deleteTree(WORK_DIR) catch {};
+
+    var passed: usize = 0;
+    var failed: usize = 0;
+    var skipped: usize = 0;
+    for (options.targets) |target| {
+        const runner = try findRunner(allocator, target) orelse {
+            std.debug.print("SKIP  {s}: no way to run programs for this target (install qemu-user or wine)\n", .{target});
+            skipped += files.items.len;
+            continue;
+        };
+        defer if (runner == .emulator) allocator.free(runner.emulator);
+        if (options.update and runner != .direct) continue;  // golden 文件只由本机结果生成
+
+        for (files.items) |file| {
+            switch (try checkProgram(allocator, self_exe, file, target, runner, options.update)) {
+                .pass => passed += 1,
+                .fail => failed += 1,
+                .skip => skipped += 1,
+            }
+        }
+    }
+
+    std.debug.print("\nabitest: {d} passed, {d} failed, {d} skipped\n", .{ passed, failed, skipped });
+    return failed == 0;
+}
+
+/// 选择运行目标程序的方式（没有可用运行器时返回 null）
+fn findRunner(allocator: std.mem.Allocator, target: []const u8) !?Runner {
+    if (std.mem.eql(u8, target, "native")) return .direct;
+
+    var parts = std.mem.splitScalar(u8, target, '-');
+    const arch = parts.next() orelse return null;
+    const os = parts.next() orelse return null;
+    const host_arch = @tagName(builtin.cpu.arch);
+    const host_os = @tagName(builtin.os.tag);
+
+    if (std.mem.eql(u8, os, host_os) and std.mem.eql(u8, arch, host_arch)) return .direct;
+    if (std.mem.eql(u8, os, "linux") and builtin.os.tag == .linux) {
+        const qemu = try std.fmt.allocPrint(allocator, "qemu-{s}", .{arch});
+        if (isAvailable(allocator, qemu)) return Runner{ .emulator = qemu };
+        allocator.free(qemu);
+        return null;
+    }
+    if (std.mem.eql(u8, os, "windows") and isAvailable(allocator, "wine")) {
+        return Runner{ .emulator = try allocator.dupe(u8, "wine") };
+    }
+    return null;
+}
+
+fn isAvailable(allocator: std.mem.Allocator, program: []const u8) bool {
+    const result = std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = &.{ program, "--version" },
+    }) catch return false;
+    allocator.free(result.stdout);
+    allocator.free(result.stderr);
+    return true;
+}
+
+/// 编译、运行单个程序并与 golden 文件比较
+fn checkProgram(
+    allocator: std.mem.Allocator,
+    self_exe: []const u8,
+    file: []const u8,
+    target: []const u8,
+    runner: Runner,
+    update: bool,
+) !selftest.Outcome {
+    const is_windows = std.mem.indexOf(u8, target, "windows") != null;
+    const base = try std.fmt.allocPrint(allocator, "{s}/{s}", .{ WORK_DIR, std.fs.path.stem(file) });
+    defer allocator.free(base);
+    const c_path = try std.fmt.allocPrint(allocator, "{s}.c", .{base});
+    defer allocator.free(c_path);
+    const exe_path = try std.fmt.allocPrint(allocator, "{s}{s}", .{ base, if (is_windows) ".exe" else "" });
+    defer allocator.free(exe_path);
+
+    // 1. 生成 C 代码
+    const generate = try selftest.observe(allocator, &.{ self_exe, file, "--backend=c", "-o", base });
+    defer generate.deinit(allocator);
+    std.fs.cwd().access(c_path, .{}) catch {
+        std.debug.print("FAIL  {s} [{s}] (does not compile)\n{s}\n", .{ file, target, generate.stderr });
+        return .fail;
+    };
+    defer std.fs.cwd().deleteFile(c_path) catch {};
+
+    // 2. 为目标编译
+    var cc_argv = std.ArrayList([]const u8){};
+    defer cc_argv.deinit(allocator);
+    try cc_argv.appendSlice(allocator, &.{ "zig", "cc" });
+    if (!std.mem.eql(u8, target, "native")) try cc_argv.appendSlice(allocator, &.{ "-target", target });
+    try cc_argv.appendSlice(allocator, &.{ c_path, "-o", exe_path });
+    const cc = try selftest.observe(allocator, cc_argv.items);
+    defer cc.deinit(allocator);
+    if (cc.exit_code != 0) {
+        std.debug.print("FAIL  {s} [{s}] (C compiler failed)\n{s}\n", .{ file, target, cc.stderr });
+        return .fail;
+    }
+    defer std.fs.cwd().deleteFile(exe_path) catch {};
+
+    // 3. 运行
+    const run_path = try std.fmt.allocPrint(allocator, "./{s}", .{exe_path});
+    defer allocator.free(run_path);
+    var env_map = try std.process.getEnvMap(allocator);
+    defer env_map.deinit();
+    try env_map.put("PAW_LOG_LEVEL", PINNED_LOG_LEVEL);
+    const actual = switch (runner) {
+        .direct => try selftest.observeWithEnv(allocator, &.{run_path}, &env_map),
+        .emulator => |emulator| try selftest.observeWithEnv(allocator, &.{ emulator, run_path }, &env_map),
+    };
+    defer actual.deinit(allocator);
+
+    const stdout_path = try std.fmt.allocPrint(allocator, "{s}.stdout", .{file[0 .. file.len - ".paw".len]});
+    defer allocator.free(stdout_path);
+    const stderr_path = try std.fmt.allocPrint(allocator, "{s}.stderr", .{file[0 .. file.len - ".paw".len]});
+    defer allocator.free(stderr_path);
+
+    if (update) {
+        try std.fs.cwd().writeFile(.{ .sub_path = stdout_path, .data = actual.stdout });
+        if (actual.stderr.len > 0) {
+            try std.fs.cwd().writeFile(.{ .sub_path = stderr_path, .data = actual.stderr });
+        } else {
+            std.fs.cwd().deleteFile(stderr_path) catch {};
+        }
+        std.debug.print("UPDATE {s}\n", .{file});
+        return .pass;
+    }
+
+    // 4. 比较
+    const expected_stdout = readGolden(allocator, stdout_path) orelse {
+        std.debug.print("FAIL  {s} [{s}] (missing {s}; run with --update to create it)\n", .{ file, target, stdout_path });
+        return .fail;
+    };
+    defer allocator.free(expected_stdout);
+    const expected_stderr = readGolden(allocator, stderr_path) orelse try allocator.dupe(u8, "");
+    defer allocator.free(expected_stderr);
+    const expected_exit = try expectedExitCode(allocator, file);
+
+    const same_stdout = std.mem.eql(u8, actual.stdout, expected_stdout);
+    const same_stderr = std.mem.eql(u8, actual.stderr, expected_stderr);
+    const same_exit = actual.exit_code == expected_exit;
+    if (same_stdout and same_stderr and same_exit) {
+        std.debug.print("PASS  {s} [{s}]\n", .{ file, target });
+        return .pass;
+    }
+
+    std.debug.print("FAIL  {s} [{s}]\n", .{ file, target });
+    if (!same_exit) std.debug.print("      exit code: expected {d}, got {?d}\n", .{ expected_exit, actual.exit_code });
+    if (!same_stdout) reportFirstDifference("stdout", expected_stdout, actual.stdout);
+    if (!same_stderr) reportFirstDifference("stderr", expected_stderr, actual.stderr);
+    return .fail;
+}
+
+fn readGolden(allocator: std.mem.Allocator, path: []const u8) ?[]u8 {
+    return std.fs.cwd().readFileAlloc(allocator, path, 1024 * 1024) catch null;
+}
+
+/// 源文件头部注释中的 "期望退出码：N"（没有时为 0）
+fn expectedExitCode(allocator: std.mem.Allocator, file: []const u8) !u8 {
+    const source = try std.fs.cwd().readFileAlloc(allocator, file, 1024 * 1024);
+    defer allocator.free(source);
+    const start = (std.mem.indexOf(u8, source, EXIT_CODE_MARKER) orelse return 0) + EXIT_CODE_MARKER.len;
+    var end = start;
+    while (end < source.len and std.ascii.isDigit(source[end])) : (end += 1) {}
+    return std.fmt.parseInt(u8, source[start..end], 10) catch 0;
+}
+
+/// 打印第一处不同的字节（ABI 偏差常常只是一个字节：换行、符号、截断）
+fn reportFirstDifference(stream: []const u8, expected: []const u8, actual: []const u8) void {
+    const index = std.mem.indexOfDiff(u8, expected, actual) orelse return;
+    std.debug.print("      {s} differs at byte {d}:\n", .{ stream, index });
+    std.debug.print("        expected: {f}\n", .{std.zig.fmtString(expected[index..@min(expected.len, index + 40)])});
+    std.debug.print("        actual:   {f}\n", .{std.zig.fmtString(actual[index..@min(actual.len, index + 40)])});
+}
@@ -0,0 +1,532 @@
+//! API - 公共 API 元数据（.pawm）与版本间差异报告
+//!
+//! 用法：
+//!   pawc api lib.paw -o lib.pawm          生成模块的公共 API 元数据
+//!   pawc api-diff old.pawm new.pawm       比较两次构建的公共 API
+//!
+//! .pawm 格式（纯文本，每行一个公共项，按键排序）：
+//!   # pawm 1
+//!   fn add = fn add(a: i32, b: i32) -> i32
+//!   type Point = type Point = struct { x: i32, y: i32 }
+//!   method Point.len = fn len(self) -> i32
+//!   trait Show = trait Show { fn show(self) -> string }
+//!   impl Show for Point = impl Show for Point { fn show(self) -> string }
+//!   inline fn add = (a + b)
+//!
+//! 只有 pub 声明会被记录；删除或签名变化的项被视为破坏性变更。
+//!
+//! 🆕 可内联函数（见 isInlinable）另外记录函数体（inline fn 行），发布构建（-O）时
+//! 调用方的编译单元直接得到函数体，可以跨模块内联。函数体变化不是破坏性变更，
+//! 但依赖它的模块需要重新编译，api-diff 单独报告。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+
+pub const METADATA_HEADER = "# pawm 1";
+
+/// 公共 API 项：键（种类 + 名称）与完整签名
+pub const ApiItem = struct {
+    key: []const u8,
+    signature: []const u8,
+};
+
+/// 模块的公共 API 表
+pub const ApiTable = struct {
+    allocator: std.mem.Allocator,
+    items: std.StringHashMap([]const u8),
+
+    pub fn init(allocator: std.mem.Allocator) ApiTable {
+        return ApiTable{
+            .allocator = allocator,
+            .items = std.StringHashMap([]const u8).init(allocator),
+        };
+    }
+
+    pub fn deinit(self: *ApiTable) void {
+        var it = self.items.iterator();
+        while (it.next()) |entry| {
+            self.allocator.free(entry.key_ptr.*);
+            self.allocator.free(entry.value_ptr.*);
+        }
+        self.items.deinit();
+    }
+
+    /// 添加一项（键和签名的所有权转移给表）
+    fn put(self: *ApiTable, key: []const u8, signature: []const u8) !void {
+        if (try self.items.fetchPut(key, signature)) |old| {
+            self.allocator.free(old.key);
+            self.allocator.free(old.value);
+        }
+    }
+
+    /// 从 AST 收集所有 pub 声明
+    pub fn fromProgram(allocator: std.mem.Allocator, program: ast.Program) !ApiTable {
+        var table = ApiTable.init(allocator);
+        errdefer table.deinit();
+
+        for (program.declarations) |decl| {
+            switch (decl) {
+                .function => |func| {
+                    if (!func.is_public) continue;
+                    try table.put(
+                        try std.fmt.allocPrint(allocator, "fn {s}", .{func.name}),
+                        try formatFunction(allocator, func.name, func.type_params, func.params, func.return_type),
+                    );
+                    if (isInlinable(func)) {
+                        var body = std.ArrayList(u8){};
+                        errdefer body.deinit(allocator);
+                        try appendInlineExpr(&body, allocator, inlineBody(func).?);
+                        const key = try std.fmt.allocPrint(allocator, "{s}fn {s}", .{ INLINE_PREFIX, func.name });
+                        errdefer allocator.free(key);
+                        try table.put(key, try body.toOwnedSlice(allocator));
+                    }
+                },
+                .type_decl => |td| {
+                    if (!td.is_public) continue;
+                    try table.addTypeDecl(td);
+                },
+                .impl_decl => |impl| {
+                    var sig = std.ArrayList(u8){};
+                    errdefer sig.deinit(allocator);
+                    try sig.appendSlice(allocator, "impl ");
+                    try sig.appendSlice(allocator, impl.trait_name);
+                    try sig.appendSlice(allocator, " for ");
+                    try appendType(&sig, allocator, impl.target_type);
+                    const key = try std.fmt.allocPrint(allocator, "{s}", .{sig.items});
+                    errdefer allocator.free(key);
+                    try sig.appendSlice(allocator, " {");
+                    for (impl.methods, 0..) |method, i| {
+                        try sig.appendSlice(allocator, if (i == 0) " " else "; ");
+                        const method_sig = try formatFunction(allocator, method.name, method.type_params, method.params, method.return_type);
+                        defer allocator.free(method_sig);
+                        try sig.appendSlice(allocator, method_sig);
+                    }
+                    try sig.appendSlice(allocator, " }");
+                    try table.put(key, try sig.toOwnedSlice(allocator));
+                },
+                else => {},
+            }
+        }
+
+        return table;
+    }
+
+    fn addTypeDecl(self: *ApiTable, td: ast.TypeDecl) !void {
+        const allocator = self.allocator;
+        var sig = std.ArrayList(u8){};
+        errdefer sig.deinit(allocator);
+
+        switch (td.kind) {
+            .struct_type => |st| {
+                try sig.appendSlice(allocator, "type ");
+                try appendTypeHead(&sig, allocator, td.name, td.type_params);
+                try sig.appendSlice(allocator, " = struct {");
+                var first = true;
+                for (st.fields) |field| {
+                    if (!field.is_public) continue;
+                    try sig.appendSlice(allocator, if (first) " " else ", ");
+                    first = false;
+                    try sig.appendSlice(allocator, field.name);
+                    try sig.appendSlice(allocator, ": ");
+                    try appendType(&sig, allocator, field.type);
+                }
+                try sig.appendSlice(allocator, " }");
+                try self.addMethods(td.name, st.methods);
+            },
+            .enum_type => |et| {
+                try sig.appendSlice(allocator, "type ");
+                try appendTypeHead(&sig, allocator, td.name, td.type_params);
+                try sig.appendSlice(allocator, " = enum {");
+                for (et.variants, 0..) |variant, i| {
+                    try sig.appendSlice(allocator, if (i == 0) " " else ", ");
+                    try sig.appendSlice(allocator, variant.name);
+                    if (variant.fields.len > 0) {
+                        try sig.appendSlice(allocator, "(");
+                        for (variant.fields, 0..) |field_type, j| {
+                            if (j > 0) try sig.appendSlice(allocator, ", ");
+                            try appendType(&sig, allocator, field_type);
+                        }
+                        try sig.appendSlice(allocator, ")");
+                    }
+                }
+                try sig.appendSlice(allocator, " }");
+                try self.addMethods(td.name, et.methods);
+            },
+            .trait_type => |tt| {
+                try sig.appendSlice(allocator, "trait ");
+                try appendTypeHead(&sig, allocator, td.name, td.type_params);
+                try sig.appendSlice(allocator, " {");
+                for (tt.methods, 0..) |method, i| {
+                    try sig.appendSlice(allocator, if (i == 0) " " else "; ");
+                    const no_type_params = [_][]const u8{};
+                    const method_sig = try formatFunction(allocator, method.name, &no_type_params, method.params, method.return_type);
+                    defer allocator.free(method_sig);
+                    try sig.appendSlice(allocator, method_sig);
+                }
+                try sig.appendSlice(allocator, " }");
+                const key = try std.fmt.allocPrint(allocator, "trait {s}", .{td.name});
+                errdefer allocator.free(key);
+                try self.put(key, try sig.toOwnedSlice(allocator));
+                return;
+            },
+            .alias => |target| {
+                try sig.appendSlice(allocator, "type ");
+                try appendTypeHead(&sig, allocator, td.name, td.type_params);
+                try sig.appendSlice(allocator, " = ");
+                try appendType(&sig, allocator, target);
+            },
+        }
+
+        const key = try std.fmt.allocPrint(allocator, "type {s}", .{td.name});
+        errdefer allocator.free(key);
+        try self.put(key, try sig.toOwnedSlice(allocator));
+    }
+
+    fn addMethods(self: *ApiTable, type_name: []const u8, methods: []ast.FunctionDecl) !void {
+        for (methods) |method| {
+            if (!method.is_public) continue;
+            const key = try std.fmt.allocPrint(self.allocator, "method {s}.{s}", .{ type_name, method.name });
+            errdefer self.allocator.free(key);
+            try self.put(
+                key,
+                try formatFunction(self.allocator, method.name, method.type_params, method.params, method.return_type),
+            );
+        }
+    }
+
+    /// 序列化为 .pawm 文本（键排序，保证输出稳定）
+    pub fn serialize(self: *ApiTable, allocator: std.mem.Allocator) ![]const u8 {
+        const keys = try self.sortedKeys(allocator);
+        defer allocator.free(keys);
+
+        var out = std.ArrayList(u8){};
+        errdefer out.deinit(allocator);
+        try out.appendSlice(allocator, METADATA_HEADER);
+        try out.append(allocator, '\n');
+        for (keys) |key| {
+            try out.writer(allocator).print("{s} = {s}\n", .{ key, self.items.get(key).? });
+        }
+        return out.toOwnedSlice(allocator);
+    }
+
+    /// 从 .pawm 文本解析
+    pub fn parse(allocator: std.mem.Allocator, text: []const u8) !ApiTable {
+        var table = ApiTable.init(allocator);
+        errdefer table.deinit();
+
+        var lines = std.mem.splitScalar(u8, text, '\n');
+        const header = lines.next() orelse return error.InvalidMetadata;
+        if (!std.mem.eql(u8, std.mem.trimRight(u8, header, "\r"), METADATA_HEADER)) {
+            return error.InvalidMetadata;
+        }
+
+        while (lines.next()) |raw_line| {
+            const line = std.mem.trimRight(u8, raw_line, "\r");
+            if (line.len == 0 or line[0] == '#') continue;
+            const sep = std.mem.indexOf(u8, line, " = ") orelse return error.InvalidMetadata;
+            const key = try allocator.dupe(u8, line[0..sep]);
+            errdefer allocator.free(key);
+            const signature = try allocator.dupe(u8, line[sep + 3 ..]);
+            errdefer allocator.free(signature);
+            try table.put(key, signature);
+        }
+
+        return table;
+    }
+
+    fn sortedKeys(self: *ApiTable, allocator: std.mem.Allocator) ![][]const u8 {
+        var keys = std.ArrayList([]const u8){};
+        errdefer keys.deinit(allocator);
+        var it = self.items.keyIterator();
+        while (it.next()) |key| {
+            try keys.append(allocator, key.*);
+        }
+        std.mem.sort([]const u8, keys.items, {}, lessThan);
+        return keys.toOwnedSlice(allocator);
+    }
+};
+
+fn lessThan(_: void, a: []const u8, b: []const u8) bool {
+    return std.mem.order(u8, a, b) == .lt;
+}
+
+/// 差异统计
+pub const DiffSummary = struct {
+    added: usize = 0,
+    removed: usize = 0,
+    changed: usize = 0,
+    inline_changed: usize = 0,  // 🆕 只有可内联函数体变化（依赖方需要重新编译）
+
+    pub fn isBreaking(self: DiffSummary) bool {
+        return self.removed > 0 or self.changed > 0;
+    }
+};
+
+/// 比较两个 API 表并打印报告
+pub fn diff(allocator: std.mem.Allocator, old: *ApiTable, new: *ApiTable) !DiffSummary {
+    var summary = DiffSummary{};
+
+    const old_keys = try old.sortedKeys(allocator);
+    defer allocator.free(old_keys);
+    const new_keys = try new.sortedKeys(allocator);
+    defer allocator.free(new_keys);
+
+    for (old_keys) |key| {
+        const old_sig = old.items.get(key).?;
+        if (new.items.get(key)) |new_sig| {
+            if (!std.mem.eql(u8, old_sig, new_sig)) {
+                if (std.mem.startsWith(u8, key, INLINE_PREFIX)) {
+                    summary.inline_changed += 1;
+                    std.debug.print("\x1b[1;36m~ inline\x1b[0m  {s} (body changed; dependents must be rebuilt)\n", .{key});
+                    continue;
+                }
+                summary.changed += 1;
+                std.debug.print("\x1b[1;33m~ changed\x1b[0m {s}\n", .{key});
+                std.debug.print("    - {s}\n", .{old_sig});
+                std.debug.print("    + {s}\n", .{new_sig});
+            }
+        } else if (std.mem.startsWith(u8, key, INLINE_PREFIX)) {
+            // 函数不再可内联：调用方改为调用导出的符号，签名由 fn 行比较
+            summary.inline_changed += 1;
+            std.debug.print("\x1b[1;36m~ inline\x1b[0m  {s} (no longer inlinable)\n", .{key});
+        } else {
+            summary.removed += 1;
+            std.debug.print("\x1b[1;31m- removed\x1b[0m {s}\n", .{old_sig});
+        }
+    }
+
+    for (new_keys) |key| {
+        if (!old.items.contains(key)) {
+            if (std.mem.startsWith(u8, key, INLINE_PREFIX)) {
+                summary.inline_changed += 1;
+                std.debug.print("\x1b[1;36m~ inline\x1b[0m  {s} (now inlinable)\n", .{key});
+                continue;
+            }
+            summary.added += 1;
+            std.debug.print("\x1b[1;32m+ added\x1b[0m   {s}\n", .{new.items.get(key).?});
+        }
+    }
+
+    return summary;
+}
+
+// ============================================================================
+// 🆕 跨模块内联
+// ============================================================================
+
+pub const INLINE_PREFIX = "inline ";
+
+/// 可内联函数体的最大节点数
+pub const INLINE_MAX_NODES = 16;
+
+/// 函数是否可以跨模块内联：pub、非泛型的普通函数，函数体只有一个表达式
+/// （或 return 表达式），表达式不超过 INLINE_MAX_NODES 个节点，且只由
+/// 参数、整数 / 布尔字面量、运算符、字段访问、结构体字面量和 if 组成。
+///
+/// 不允许调用和对参数以外名字的引用：调用方的编译单元看不到模块的私有函数和常量，
+/// 这样函数体在任何导入它的单元中都能原样生成。
+pub fn isInlinable(func: ast.FunctionDecl) bool {
+    if (!func.is_public or func.type_params.len > 0 or func.is_extern or func.is_async) return false;
+    if (func.asm_bodies.len > 0 or func.isEntryPoint()) return false;
+    if (func.return_type == .void or func.return_type == .never) return false;
+    const body = inlineBody(func) orelse return false;
+    const cost = inlineCost(body, func.params) orelse return false;
+    return cost <= INLINE_MAX_NODES;
+}
+
+/// 只有一条语句的函数体中的表达式
+pub fn inlineBody(func: ast.FunctionDecl) ?ast.Expr {
+    if (func.body.len != 1) return null;
+    return switch (func.body[0]) {
+        .expr => |expr| expr,
+        .return_stmt => |ret| ret,
+        else => null,
+    };
+}
+
+/// 表达式的节点数；含有不能内联的表达式时返回 null
+fn inlineCost(expr: ast.Expr, params: []const ast.Param) ?usize {
+    return switch (expr) {
+        .int_literal, .bool_literal => 1,
+        .identifier => |name| for (params) |param| {
+            if (std.mem.eql(u8, param.name, name)) break 1;
+        } else null,
+        .binary => |bin| 1 + (inlineCost(bin.left.*, params) orelse return null) +
+            (inlineCost(bin.right.*, params) orelse return null),
+        .unary => |un| 1 + (inlineCost(un.operand.*, params) orelse return null),
+        .field_access => |fa| 1 + (inlineCost(fa.object.*, params) orelse return null),
+        .struct_init => |si| blk: {
+            if (si.type_args.len > 0) break :blk null;
+            var cost: usize = 1;
+            for (si.fields) |field| cost += inlineCost(field.value, params) orelse break :blk null;
+            break :blk cost;
+        },
+        .if_expr => |if_expr| blk: {
+            const else_branch = if_expr.else_branch orelse break :blk null;
+            break :blk 1 + (inlineCost(if_expr.condition.*, params) orelse break :blk null) +
+                (inlineCost(if_expr.then_branch.*, params) orelse break :blk null) +
+                (inlineCost(else_branch.*, params) orelse break :blk null);
+        },
+        // if 的分支：只有一个表达式的块
+        .block => |stmts| if (stmts.len == 1 and stmts[0] == .expr) inlineCost(stmts[0].expr, params) else null,
+        else => null,
+    };
+}
+
+/// 将可内联的表达式写成 Paw 源码形式（只处理 inlineCost 接受的表达式）
+pub fn appendInlineExpr(buf: *std.ArrayList(u8), allocator: std.mem.Allocator, expr: ast.Expr) !void {
+    switch (expr) {
+        .int_literal => |literal| try buf.writer(allocator).print("{d}", .{literal.value}),
+        .bool_literal => |value| try buf.appendSlice(allocator, if (value) "true" else "false"),
+        .identifier => |name| try buf.appendSlice(allocator, name),
+        .binary => |bin| {
+            try buf.append(allocator, '(');
+            try appendInlineExpr(buf, allocator, bin.left.*);
+            try buf.writer(allocator).print(" {s} ", .{binaryOpSymbol(bin.op)});
+            try appendInlineExpr(buf, allocator, bin.right.*);
+            try buf.append(allocator, ')');
+        },
+        .unary => |un| {
+            try buf.appendSlice(allocator, if (un.op == .neg) "-" else "!");
+            try appendInlineExpr(buf, allocator, un.operand.*);
+        },
+        .field_access => |fa| {
+            try appendInlineExpr(buf, allocator, fa.object.*);
+            try buf.append(allocator, '.');
+            try buf.appendSlice(allocator, fa.field);
+        },
+        .struct_init => |si| {
+            try buf.appendSlice(allocator, si.type_name);
+            try buf.appendSlice(allocator, " {");
+            for (si.fields, 0..) |field, i| {
+                try buf.appendSlice(allocator, if (i == 0) " " else ", ");
+                try buf.appendSlice(allocator, field.name);
+                try buf.appendSlice(allocator, ": ");
+                try appendInlineExpr(buf, allocator, field.value);
+            }
+            try buf.appendSlice(allocator, " }");
+        },
+        .if_expr => |if_expr| {
+            try buf.appendSlice(allocator, "if ");
+            try appendInlineExpr(buf, allocator, if_expr.condition.*);
+            try buf.append(allocator, ' ');
+            try appendInlineExpr(buf, allocator, if_expr.then_branch.*);
+            try buf.appendSlice(allocator, " else ");
+            try appendInlineExpr(buf, allocator, if_expr.else_branch.?.*);
+        },
+        .block => |stmts| {
+            try buf.appendSlice(allocator, "{ ");
+            try appendInlineExpr(buf, allocator, stmts[0].expr);
+            try buf.appendSlice(allocator, " }");
+        },
+        else => try buf.appendSlice(allocator, @tagName(expr)),
+    }
+}
+
+fn binaryOpSymbol(op: ast.BinaryOp) []const u8 {
+    return switch (op) {
+        .add => "+", .sub => "-", .mul => "*", .div => "/", .mod => "%",
+        .eq => "==", .ne => "!=", .lt => "<", .le => "<=", .gt => ">", .ge => ">=",
+        .and_op => "&&", .or_op => "||",
+    };
+}
+
+// ============================================================================
+// 签名格式化
+// ============================================================================
+
+pub fn formatFunction(
+    allocator: std.mem.Allocator,
+    name: []const u8,
+    type_params: []const []const u8,
+    params: []const ast.Param,
+    return_type: ast.Type,
+) ![]const u8 {
+    var sig = std.ArrayList(u8){};
+    errdefer sig.deinit(allocator);
+
+    try sig.appendSlice(allocator, "fn ");
+    try appendTypeHead(&sig, allocator, name, type_params);
+    try sig.append(allocator, '(');
+    for (params, 0..) |param, i| {
+        if (i > 0) try sig.appendSlice(allocator, ", ");
+        if (param.is_mut) try sig.appendSlice(allocator, "mut ");
+        try sig.appendSlice(allocator, param.name);
+        if (!std.mem.eql(u8, param.name, "self")) {
+            try sig.appendSlice(allocator, ": ");
+            try appendType(&sig, allocator, param.type);
+        }
+    }
+    try sig.append(allocator, ')');
+    if (return_type != .void) {
+        try sig.appendSlice(allocator, " -> ");
+        try appendType(&sig, allocator, return_type);
+    }
+    return sig.toOwnedSlice(allocator);
+}
+
+fn appendTypeHead(
+    buf: *std.ArrayList(u8),
+    allocator: std.mem.Allocator,
+    name: []const u8,
+    type_params: []const []const u8,
+) !void {
+    try buf.appendSlice(allocator, name);
+    if (type_params.len > 0) {
+        try buf.append(allocator, '<');
+        for (type_params, 0..) |param, i| {
+            if (i > 0) try buf.appendSlice(allocator, ", ");
+            try buf.appendSlice(allocator, param);
+        }
+        try buf.append(allocator, '>');
+    }
+}
+
+/// 将类型写成 Paw 源码形式
+pub fn appendType(buf: *std.ArrayList(u8), allocator: std.mem.Allocator, t: ast.Type) !void {
+    switch (t) {
+        .generic => |name| try buf.appendSlice(allocator, name),
+        .named => |name| try buf.appendSlice(allocator, name),
+        .pointer => |inner| {
+            try buf.append(allocator, '*');
+            try appendType(buf, allocator, inner.*);
+        },
+        .array => |arr| {
+            try buf.append(allocator, '[');
+            try appendType(buf, allocator, arr.element.*);
+            if (arr.size) |size| {
+                try buf.writer(allocator).print("; {d}", .{size});
+            }
+            try buf.append(allocator, ']');
+        },
+        .function => |func| {
+            try buf.appendSlice(allocator, "fn(");
+            for (func.params, 0..) |param, i| {
+                if (i > 0) try buf.appendSlice(allocator, ", ");
+                try appendType(buf, allocator, param);
+            }
+            try buf.appendSlice(allocator, ") -> ");
+            try appendType(buf, allocator, func.return_type.*);
+        },
+        .generic_instance => |gi| {
+            try buf.appendSlice(allocator, gi.name);
+            try buf.append(allocator, '<');
+            for (gi.type_args, 0..) |arg, i| {
+                if (i > 0) try buf.appendSlice(allocator, ", ");
+                try appendType(buf, allocator, arg);
+            }
+            try buf.append(allocator, '>');
+        },
+        .projection => |proj| {
+            if (proj.trait_name) |trait_name| {
+                try buf.append(allocator, '<');
+                try appendType(buf, allocator, proj.base.*);
+                try buf.writer(allocator).print(" as {s}>", .{trait_name});
+            } else {
+                try appendType(buf, allocator, proj.base.*);
+            }
+            try buf.writer(allocator).print("::{s}", .{proj.name});
+        },
+        else => try buf.appendSlice(allocator, @tagName(t)),
+    }
+}
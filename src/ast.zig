@@ -1,4 +1,5 @@
 const std = @import("std");
+const FileId = @import("diagnostic.zig").FileId;
 
 pub const Type = union(enum) {
     // 有符号整数类型（支持到 128 位）
@@ -24,6 +25,7 @@ pub const Type = union(enum) {
     char,
     string,
     void,
+    never,  // 🆕 不会产生值的表达式（break / continue / return）的类型，可以当作任何类型
     
     // 复杂类型
     generic: []const u8, // T
@@ -32,6 +34,7 @@ pub const Type = union(enum) {
     array: struct {
         element: *Type,
         size: ?usize,
+        size_expr: ?*Expr = null,  // 🆕 [T; expr]：编译期求值后写入 size
     },
     function: struct {
         params: []Type,
@@ -41,6 +44,13 @@ pub const Type = union(enum) {
         name: []const u8,
         type_args: []Type,
     },
+    // 🆕 关联类型投影：T::Item 或 <T as Iter>::Item
+    // 具体类型上的投影在解析后展开（见 type_alias.zig），类型参数上的投影在单态化时确定
+    projection: struct {
+        base: *Type,
+        trait_name: ?[]const u8 = null,
+        name: []const u8,
+    },
 
     pub fn eql(self: Type, other: Type) bool {
         if (@intFromEnum(self) != @intFromEnum(other)) return false;
@@ -49,7 +59,7 @@ pub const Type = union(enum) {
             .i8, .i16, .i32, .i64, .i128,
             .u8, .u16, .u32, .u64, .u128,
             .f32, .f64,
-            .bool, .char, .string, .void => true,
+            .bool, .char, .string, .void, .never => true,
             .generic => |name| std.mem.eql(u8, name, other.generic),
             .named => |name| std.mem.eql(u8, name, other.named),
             .pointer => |ptr| ptr.eql(other.pointer.*),
@@ -85,6 +95,7 @@ pub const Type = union(enum) {
                 }
                 return true;
             },
+            .projection => |proj| std.mem.eql(u8, proj.name, other.projection.name) and proj.base.eql(other.projection.base.*),
         };
     }
     
@@ -105,17 +116,91 @@ pub const Type = union(enum) {
     }
 };
 
+// 🆕 char 是 Unicode 标量值：0..=0x10FFFF，且不是代理项 0xD800..=0xDFFF
+pub const MAX_CHAR: i128 = 0x10FFFF;
+
+pub fn isValidChar(code: i128) bool {
+    return (code >= 0 and code < 0xD800) or (code > 0xDFFF and code <= MAX_CHAR);
+}
+
+/// 🆕 整数字面量 token：数字部分和类型后缀（42u32 -> "42", u32；没有后缀时 suffix 为 null）
+/// 带后缀的字面量解析为 `42 as u32`，字面量按后缀的类型检查范围
+pub const IntLiteralText = struct {
+    digits: []const u8,
+    suffix: ?Type,
+};
+
+pub fn splitIntLiteral(lexeme: []const u8) IntLiteralText {
+    var end: usize = 0;
+    while (end < lexeme.len and std.ascii.isDigit(lexeme[end])) : (end += 1) {}
+    const int_types = [_]Type{ .i8, .i16, .i32, .i64, .i128, .u8, .u16, .u32, .u64, .u128 };
+    for (int_types) |t| {
+        if (std.mem.eql(u8, lexeme[end..], @tagName(t))) return .{ .digits = lexeme[0..end], .suffix = t };
+    }
+    return .{ .digits = lexeme, .suffix = null };
+}
+
+/// 🆕 `n as char` 的 n 不是合法标量值时，各后端和解释器 panic 的消息（退出码 101）
+pub const INVALID_CHAR_PANIC = "invalid char value: not a Unicode scalar value";
+
+/// 🆕 数组索引越界时 C 后端 panic 的消息（printf 格式：长度、索引；退出码 101）
+pub const INDEX_OUT_OF_BOUNDS_PANIC = "index out of bounds: the length is %lld but the index is %lld";
+
+/// 🆕 分配 lambda 环境失败时各后端 panic 的消息（退出码 101）
+pub const OUT_OF_MEMORY_PANIC = "out of memory";
+
+/// 🆕 生成的程序栈溢出时 panic 消息的前缀，后接溢出时所在的函数名（退出码 101）
+pub const STACK_OVERFLOW_PANIC = "stack overflow in function ";
+
+/// 🆕 dispatch<T in [...]>(tag) 的 tag 不是列表中的下标时 panic 的消息（退出码 101）
+pub const DISPATCH_TAG_PANIC = "dispatch tag does not select a type in the list";
+
+/// 🆕 From 转换（prelude 中的 From<T> trait）：类型 B 的静态方法 `fn from(value: A) -> B`
+/// 使 `a as B` 和 `let b: B = into(a);` 成立，两者都调用 B::from(a)
+pub const FROM_METHOD = "from";
+pub const INTO_BUILTIN = "into";
+
+/// 🆕 @track_caller 函数的隐藏参数：调用处的文件名（string）和行号（i32）
+pub const CALLER_FILE_PARAM = "__caller_file";
+pub const CALLER_LINE_PARAM = "__caller_line";
+
+/// 🆕 __FILE__ 的值：源文件路径作为字符串字面量（转义 \ 和 "）
+pub fn fileLiteral(allocator: std.mem.Allocator, file: FileId) ![]const u8 {
+    var escaped = std.ArrayList(u8){};
+    for (file.path()) |c| {
+        if (c == '\\' or c == '"') try escaped.append(allocator, '\\');
+        try escaped.append(allocator, c);
+    }
+    return escaped.toOwnedSlice(allocator);
+}
+
+/// 🆕 into(x)（类型检查器已标记 resolved_into）要调用的 from 方法所在的类型名；
+/// 源类型与目标类型相同时返回 null，into(x) 就是 x
+pub fn intoTarget(call: anytype) ?[]const u8 {
+    if (!call.resolved_into.*) return null;
+    const types = call.resolved_type_args.*;
+    if (types[0].eql(types[1]) or types[1] != .named) return null;
+    return types[1].named;
+}
+
 pub const Expr = union(enum) {
-    int_literal: i64,
+    int_literal: struct {
+        value: i64,
+        location: ?SourceLocation = null,  // 🆕 字面量的位置（越界、非法 char 值等诊断）
+    },
     float_literal: f64,
     string_literal: []const u8,
     char_literal: u32,
+    byte_literal: u8,                // 🆕 b'A'（类型 u8 / Byte）
+    byte_string_literal: []const u8, // 🆕 b"..."（已解码的字节，类型 [u8; N]）
     bool_literal: bool,
     identifier: []const u8,
     binary: struct {
         left: *Expr,
         op: BinaryOp,
         right: *Expr,
+        resolved_operator: *?[]const u8,  // 🆕 由类型检查器填写：运算符由该用户类型（左操作数的类型）的方法实现
+        location: ?SourceLocation = null,  // 🆕 运算符的位置（运算符重载的诊断）
     },
     unary: struct {
         op: UnaryOp,
@@ -125,6 +210,10 @@ pub const Expr = union(enum) {
         callee: *Expr,
         args: []Expr,
         type_args: []Type,
+        resolved_type_args: *[]Type,  // 🆕 由类型检查器填写（泛型函数调用推导出的类型实参）
+        resolved_into: *bool,  // 🆕 由类型检查器填写：true 表示 into(x) 是 From 转换（resolved_type_args 为 [源类型, 目标类型]）
+        resolved_receiver: *?[]const u8,  // 🆕 由类型检查器填写：name(x, ...) 解析为 x 的类型上的 trait 方法时为该类型名
+        location: ?SourceLocation = null,  // 🆕 调用处的位置（调用 @track_caller 函数时传入，见 track_caller.zig）
     },
     // 🆕 静态方法调用：Type<T>::method()
     static_method_call: struct {
@@ -152,6 +241,7 @@ pub const Expr = union(enum) {
         condition: *Expr,
         then_branch: *Expr,
         else_branch: ?*Expr,
+        dispatch_instance: ?[]const u8 = null,  // 🆕 dispatch 展开的分支："T = i32"（分支中的类型检查诊断注明）
     },
     // 新增：is 表达式（模式匹配）
     is_expr: struct {
@@ -167,6 +257,7 @@ pub const Expr = union(enum) {
     as_expr: struct {
         value: *Expr,
         target_type: Type,
+        resolved_from: *bool,  // 🆕 由类型检查器填写：true 表示调用目标类型的 from 方法（From 转换）
     },
     // 新增：await 表达式
     await_expr: *Expr,
@@ -176,6 +267,7 @@ pub const Expr = union(enum) {
     array_index: struct {
         array: *Expr,
         index: *Expr,
+        length: *?usize,  // 🆕 由类型检查器填写：固定大小数组的长度（两个后端据此检查越界）
     },
     // 🆕 范围表达式
     range: struct {
@@ -189,6 +281,96 @@ pub const Expr = union(enum) {
     },
     // 🆕 错误传播 (expr?)
     try_expr: *Expr,
+    // 🆕 编译期布局查询：size_of<T>() / align_of<T>()
+    type_query: struct {
+        kind: TypeQueryKind,
+        type: Type,
+    },
+    // 🆕 loop 表达式：let x = loop { ... break value; };
+    loop_expr: struct {
+        label: ?[]const u8,
+        body: []Stmt,
+        result_type: *Type,  // 由类型检查器填写（break 值的统一类型）
+    },
+    // 🆕 lambda 表达式：|x: i32| -> i32 { x + n }（类型为 fn(i32) -> i32）
+    lambda: Lambda,
+    
+    pub const TypeQueryKind = enum {
+        size_of,
+        align_of,
+        
+        pub fn fromName(name: []const u8) ?TypeQueryKind {
+            if (std.mem.eql(u8, name, "size_of")) return .size_of;
+            if (std.mem.eql(u8, name, "align_of")) return .align_of;
+            return null;
+        }
+    };
+    
+    /// 🆕 源码位置常量：__FILE__ / __LINE__ / __FUNCTION__
+    ///
+    /// 解析时展开为所在位置的字面量（string / i32 / string）；
+    /// @track_caller 函数中的 __FILE__ / __LINE__ 展开为调用处传入的隐藏参数
+    pub const SourceMeta = enum {
+        file,
+        line,
+        function,
+        
+        pub fn fromName(name: []const u8) ?SourceMeta {
+            if (std.mem.eql(u8, name, "__FILE__")) return .file;
+            if (std.mem.eql(u8, name, "__LINE__")) return .line;
+            if (std.mem.eql(u8, name, "__FUNCTION__")) return .function;
+            return null;
+        }
+    };
+    
+    /// 🆕 表达式是否一定不会正常结束（块中有 break / continue / return，
+    /// 或 if 的两个分支都不会正常结束）。与类型检查中的 never 类型对应
+    pub fn diverges(self: Expr) bool {
+        return switch (self) {
+            .block => |stmts| blk: {
+                for (stmts) |stmt| {
+                    if (stmt.diverges()) break :blk true;
+                }
+                break :blk false;
+            },
+            .if_expr => |if_expr| blk: {
+                if (if_expr.condition.diverges()) break :blk true;
+                const else_branch = if_expr.else_branch orelse break :blk false;
+                break :blk if_expr.then_branch.diverges() and else_branch.diverges();
+            },
+            else => false,
+        };
+    }
+    
+    /// 🆕 `_ = expr;` 的赋值目标：显式丢弃表达式的值
+    pub fn isDiscardTarget(self: Expr) bool {
+        return self == .identifier and std.mem.eql(u8, self.identifier, "_");
+    }
+    
+    /// 🆕 表达式中是否有可能跳出外层循环的 break
+    /// （嵌套循环内只有带标签的 break 才可能跳出外层循环）
+    pub fn containsBreak(self: Expr, nested: bool) bool {
+        return switch (self) {
+            .block => |stmts| Stmt.anyContainsBreak(stmts, nested),
+            .if_expr => |if_expr| if_expr.condition.containsBreak(nested) or
+                if_expr.then_branch.containsBreak(nested) or
+                (if_expr.else_branch != null and if_expr.else_branch.?.containsBreak(nested)),
+            .is_expr => |is_match| blk: {
+                for (is_match.arms) |arm| {
+                    if (arm.body.containsBreak(nested)) break :blk true;
+                }
+                break :blk false;
+            },
+            .match_expr => |match| blk: {
+                for (match.arms) |arm| {
+                    if (arm.body.containsBreak(nested)) break :blk true;
+                }
+                break :blk false;
+            },
+            .loop_expr => |loop| Stmt.anyContainsBreak(loop.body, true),
+            else => false,
+        };
+    }
     
     /// 递归释放表达式及其子表达式
     pub fn deinit(self: Expr, allocator: std.mem.Allocator) void {
@@ -198,6 +380,7 @@ pub const Expr = union(enum) {
                 allocator.destroy(bin.left);
                 bin.right.deinit(allocator);
                 allocator.destroy(bin.right);
+                allocator.destroy(bin.resolved_operator);
             },
             .unary => |un| {
                 un.operand.deinit(allocator);
@@ -211,6 +394,9 @@ pub const Expr = union(enum) {
                 }
                 allocator.free(call.args);
                 allocator.free(call.type_args);
+                allocator.destroy(call.resolved_type_args);
+                allocator.destroy(call.resolved_into);
+                allocator.destroy(call.resolved_receiver);
             },
             .static_method_call => |smc| {
                 for (smc.args) |arg| {
@@ -239,6 +425,22 @@ pub const Expr = union(enum) {
                 }
                 allocator.free(stmts);
             },
+            .loop_expr => |le| {
+                for (le.body) |stmt| {
+                    stmt.deinit(allocator);
+                }
+                allocator.free(le.body);
+                allocator.destroy(le.result_type);
+            },
+            .lambda => |lambda| {
+                for (lambda.body) |stmt| {
+                    stmt.deinit(allocator);
+                }
+                allocator.free(lambda.body);
+                allocator.free(lambda.params);
+                allocator.destroy(lambda.captures);
+                allocator.destroy(lambda.lifted_name);
+            },
             .if_expr => |ie| {
                 ie.condition.deinit(allocator);
                 allocator.destroy(ie.condition);
@@ -262,6 +464,7 @@ pub const Expr = union(enum) {
             .as_expr => |ae| {
                 ae.value.deinit(allocator);
                 allocator.destroy(ae.value);
+                allocator.destroy(ae.resolved_from);
             },
             .await_expr => |ae| {
                 ae.deinit(allocator);
@@ -278,6 +481,7 @@ pub const Expr = union(enum) {
                 allocator.destroy(ai.array);
                 ai.index.deinit(allocator);
                 allocator.destroy(ai.index);
+                allocator.destroy(ai.length);
             },
             .range => |rng| {
                 rng.start.deinit(allocator);
@@ -317,6 +521,37 @@ pub const BinaryOp = enum {
     ge,
     and_op,
     or_op,
+    
+    /// 🆕 运算符重载：左操作数是用户类型时，实现运算符的方法（prelude 中对应的 trait 见 overloadTrait）
+    ///   + - * / % → add / sub / mul / div / rem，返回值是运算的结果
+    ///   == !=     → eq(self, other) -> bool（!= 取反）
+    ///   < <= > >= → cmp(self, other) -> i32（负数、0、正数分别表示小于、等于、大于）
+    pub fn overloadMethod(self: BinaryOp) ?[]const u8 {
+        return switch (self) {
+            .add => "add",
+            .sub => "sub",
+            .mul => "mul",
+            .div => "div",
+            .mod => "rem",
+            .eq, .ne => "eq",
+            .lt, .le, .gt, .ge => "cmp",
+            .and_op, .or_op => null,
+        };
+    }
+    
+    /// 🆕 声明 overloadMethod 的 prelude trait
+    pub fn overloadTrait(self: BinaryOp) ?[]const u8 {
+        return switch (self) {
+            .add => "Add",
+            .sub => "Sub",
+            .mul => "Mul",
+            .div => "Div",
+            .mod => "Rem",
+            .eq, .ne => "Equal",
+            .lt, .le, .gt, .ge => "Compare",
+            .and_op, .or_op => null,
+        };
+    }
 };
 
 pub const UnaryOp = enum {
@@ -331,6 +566,17 @@ pub const CompoundAssignOp = enum {
     mul_assign,  // *=
     div_assign,  // /=
     mod_assign,  // %=
+    
+    /// 🆕 x op= v 等价于 x = x op v
+    pub fn binaryOp(self: CompoundAssignOp) BinaryOp {
+        return switch (self) {
+            .add_assign => .add,
+            .sub_assign => .sub,
+            .mul_assign => .mul,
+            .div_assign => .div,
+            .mod_assign => .mod,
+        };
+    }
 };
 
 pub const StructFieldInit = struct {
@@ -348,6 +594,7 @@ pub const IsArm = struct {
     pattern: Pattern,
     guard: ?Expr,  // 可选的 if 条件
     body: Expr,
+    location: ?SourceLocation = null,  // 🆕 模式的第一个 token 的位置
 };
 
 pub const Pattern = union(enum) {
@@ -358,6 +605,14 @@ pub const Pattern = union(enum) {
     },
     literal: Expr,
     wildcard,
+    // 🆕 范围模式：1..=5 / 'a'..'z'（端点是整数或字符字面量）
+    range: struct {
+        start: Expr,
+        end: Expr,
+        inclusive: bool,
+    },
+    // 🆕 或模式：1 | 2 | 3（任一子模式匹配即可；子模式不能绑定变量）
+    alternatives: []Pattern,
 };
 
 pub const Stmt = union(enum) {
@@ -380,10 +635,14 @@ pub const Stmt = union(enum) {
         value: Expr,
     },
     return_stmt: ?Expr,
-    break_stmt: ?Expr,  // loop 可以返回值
-    continue_stmt,
+    break_stmt: struct {
+        label: ?[]const u8,  // 🆕 break 'outer（不含 '）
+        value: ?Expr,        // loop 可以返回值
+    },
+    continue_stmt: ?[]const u8,  // 🆕 continue 'outer 的标签
     // 新增：统一的 loop 语句
     loop_stmt: struct {
+        label: ?[]const u8 = null,  // 🆕 'outer: loop { }
         condition: ?Expr,      // loop if condition
         iterator: ?LoopIterator,  // loop for item in iter
         body: []Stmt,
@@ -400,6 +659,34 @@ pub const Stmt = union(enum) {
         body: []Stmt,
     },
     
+    /// 🆕 语句执行后是否一定不会继续执行下一条语句
+    pub fn diverges(self: Stmt) bool {
+        return switch (self) {
+            .return_stmt, .break_stmt, .continue_stmt => true,
+            .expr => |expr| expr.diverges(),
+            // 没有条件、没有 break 的 loop { } 是死循环
+            .loop_stmt => |loop| loop.condition == null and loop.iterator == null and !anyContainsBreak(loop.body, false),
+            else => false,
+        };
+    }
+    
+    /// 🆕 语句序列中是否有可能跳出外层循环的 break（见 Expr.containsBreak）
+    pub fn anyContainsBreak(stmts: []const Stmt, nested: bool) bool {
+        for (stmts) |stmt| {
+            const found = switch (stmt) {
+                .break_stmt => |brk| !nested or brk.label != null,
+                .expr => |expr| expr.containsBreak(nested),
+                .let_decl => |decl| decl.init != null and decl.init.?.containsBreak(nested),
+                .loop_stmt => |loop| anyContainsBreak(loop.body, true),
+                .while_loop => |loop| anyContainsBreak(loop.body, true),
+                .for_loop => |loop| anyContainsBreak(loop.body, true),
+                else => false,
+            };
+            if (found) return true;
+        }
+        return false;
+    }
+    
     /// 释放语句及其子表达式
     pub fn deinit(self: Stmt, allocator: std.mem.Allocator) void {
         switch (self) {
@@ -423,7 +710,7 @@ pub const Stmt = union(enum) {
                 }
             },
             .break_stmt => |brk| {
-                if (brk) |expr| {
+                if (brk.value) |expr| {
                     expr.deinit(allocator);
                 }
             },
@@ -479,6 +766,85 @@ pub const Param = struct {
     is_mut: bool,  // 🆕 v0.1.6: 参数是否可变 (用于 mut self)
 };
 
+/// 🆕 lambda 捕获的外层局部变量：创建闭包时按值复制到环境中
+pub const Capture = struct {
+    name: []const u8,
+    type: Type,
+};
+
+/// 🆕 lambda 表达式（省略 -> 时返回 void）
+pub const Lambda = struct {
+    params: []Param,
+    return_type: Type,
+    body: []Stmt,
+    captures: *[]Capture,  // 由类型检查器填写（按值捕获的外层局部变量）
+    lifted_name: *[]const u8,  // 由闭包转换填写（提升后的顶层函数名，见 closure.zig）
+
+    /// lambda 作为值的类型：fn(参数类型...) -> 返回类型
+    pub fn valueType(self: Lambda, allocator: std.mem.Allocator) !Type {
+        const params = try allocator.alloc(Type, self.params.len);
+        for (self.params, 0..) |param, i| params[i] = param.type;
+        const return_type = try allocator.create(Type);
+        return_type.* = self.return_type;
+        return Type{ .function = .{ .params = params, .return_type = return_type } };
+    }
+};
+
+/// 🆕 声明在源码中的位置（column 为起始列，均从 1 开始）
+pub const SourceLocation = struct {
+    file: FileId,
+    line: usize,
+    column: usize,
+};
+
+/// 🆕 编译器内置的类型参数约束
+///
+/// Num 允许算术运算，Ord 允许大小比较，Eq 允许 == / !=。
+/// Num 和 Ord 都蕴含 Eq；数值类型同时满足 Ord。
+pub const BuiltinBound = enum {
+    Num,
+    Ord,
+    Eq,
+    Display,  // 🆕 可以格式化为文本（println 等）
+
+    pub fn fromName(name: []const u8) ?BuiltinBound {
+        return std.meta.stringToEnum(BuiltinBound, name);
+    }
+
+    /// bound 是否蕴含 other（T: Num 时也可以使用 < 和 ==）
+    pub fn implies(self: BuiltinBound, other: BuiltinBound) bool {
+        if (self == other) return true;
+        return switch (self) {
+            .Num => true,
+            .Ord => other == .Eq or other == .Display,
+            .Eq, .Display => false,
+        };
+    }
+
+    /// 具体类型是否满足约束（单态化时验证）
+    pub fn satisfiedBy(self: BuiltinBound, t: Type) bool {
+        return switch (t) {
+            .i8, .i16, .i32, .i64, .i128, .u8, .u16, .u32, .u64, .u128, .f32, .f64 => true,
+            .char => self != .Num,
+            .bool, .string => self == .Eq or self == .Display,
+            else => false,
+        };
+    }
+};
+
+/// 🆕 类型参数约束：<T: Num> 或 where T: Num
+pub const TypeBound = struct {
+    param: []const u8,
+    bound: BuiltinBound,
+};
+
+/// 🆕 用户 trait 约束：where T: Show
+/// 只能约束泛型类型的参数（方法的 where 子句和 impl 块），类型实参在结构上实现了该 trait 时满足
+pub const TraitBound = struct {
+    param: []const u8,
+    trait_name: []const u8,
+};
+
 pub const FunctionDecl = struct {
     name: []const u8,
     type_params: [][]const u8,
@@ -487,7 +853,64 @@ pub const FunctionDecl = struct {
     body: []Stmt,
     is_public: bool,
     is_async: bool,  // 新增：是否异步
-    
+    location: ?SourceLocation = null,  // 🆕 函数名在源码中的位置（用于后端诊断）
+    bounds: []const TypeBound = &[_]TypeBound{},  // 🆕 类型参数约束
+    trait_bounds: []const TraitBound = &[_]TraitBound{},  // 🆕 类型的参数上的 trait 约束（where T: Show）
+    is_const: bool = false,  // 🆕 const fn：可以在编译期求值
+    is_extern: bool = false,  // 🆕 extern fn：只有签名，由外部库（libc 等）提供
+    link_name: ?LinkName = null,  // 🆕 @link_name：外部函数在目标文件中的符号名
+    allow: LintSet = LintSet.initEmpty(),  // 🆕 @allow(...) 关闭的检查
+    asm_bodies: []const AsmBody = &[_]AsmBody{},  // 🆕 @asm：按目标架构给出的汇编函数体（没有 Paw 函数体）
+    captures: ?[]const Capture = null,  // 🆕 由 lambda 提升而来的函数：环境中的变量（见 closure.zig）
+    test_attr: ?TestAttr = null,  // 🆕 @test：pawc test 运行的测试函数
+    track_caller: bool = false,  // 🆕 @track_caller：函数中的 __FILE__ / __LINE__ 是调用处的位置
+    source_name: ?[]const u8 = null,  // 🆕 命名空间导入改名前的名字（分离编译时定义它的单元中的符号名）
+    namespace: ?[]const u8 = null,  // 🆕 命名空间导入的副本：导入时的别名（别名下的私有项只能在同一别名的副本中引用）
+
+    /// 🆕 程序入口：后端把它生成为 ENTRY_SYMBOL，由生成的 C ABI main 包装函数调用
+    pub fn isEntryPoint(self: FunctionDecl) bool {
+        return std.mem.eql(u8, self.name, "main") and
+            self.type_params.len == 0 and self.params.len == 0 and !self.is_extern;
+    }
+
+    /// 🆕 由 lambda 提升而来的函数（只在本编译单元中使用）
+    pub fn isLambda(self: FunctionDecl) bool {
+        return self.captures != null;
+    }
+
+    /// 🆕 有捕获的 lambda：第一个参数是环境指针
+    pub fn hasEnv(self: FunctionDecl) bool {
+        return if (self.captures) |captures| captures.len > 0 else false;
+    }
+
+    /// 🆕 泛型类型的方法可以用 where 约束类型的参数（`fn show(self) -> string where T: Display`），
+    /// 这样的方法只在满足约束的实例上存在。type_params 是类型的参数，type_args 是实例的具体类型；
+    /// 返回第一个不满足的约束（全部满足时返回 null）
+    pub fn unmetReceiverBound(self: FunctionDecl, type_params: []const []const u8, type_args: []const Type) ?TypeBound {
+        for (self.bounds) |b| {
+            const actual = typeArgFor(type_params, type_args, b.param) orelse continue;
+            if (!b.bound.satisfiedBy(actual)) return b;
+        }
+        return null;
+    }
+
+    /// 🆕 @asm 函数在目标架构（std.Target.Cpu.Arch 的名字）上的汇编文本
+    pub fn asmFor(self: FunctionDecl, arch: []const u8) ?[]const u8 {
+        for (self.asm_bodies) |body| {
+            if (std.mem.eql(u8, body.arch, arch)) return body.text;
+        }
+        return null;
+    }
+
+    /// 🆕 把函数当作值使用时的类型：fn(参数类型...) -> 返回类型（只用于非泛型函数）
+    pub fn valueType(self: FunctionDecl, allocator: std.mem.Allocator) !Type {
+        const params = try allocator.alloc(Type, self.params.len);
+        for (self.params, 0..) |param, i| params[i] = param.type;
+        const return_type = try allocator.create(Type);
+        return_type.* = self.return_type;
+        return Type{ .function = .{ .params = params, .return_type = return_type } };
+    }
+
     pub fn deinit(self: FunctionDecl, allocator: std.mem.Allocator) void {
         allocator.free(self.type_params);
         allocator.free(self.params);
@@ -499,6 +922,161 @@ pub const FunctionDecl = struct {
     }
 };
 
+/// 🆕 @asm("x86_64", "...")：一个目标架构上的函数体
+///
+/// 文本按目标的默认汇编语法（x86 为 AT&T）逐字输出，转义已解码；
+/// 函数是 naked 的：参数按 C 调用约定在寄存器中，汇编自己负责返回（ret）。
+/// 原始字节用汇编指令写：.byte 0x0f, 0x05
+pub const AsmBody = struct {
+    arch: []const u8,
+    text: []const u8,
+};
+
+/// 🆕 @test / @test(should_panic = "msg") / @property(runs = N) 以及 @skip("reason")
+///
+/// 测试函数没有参数、返回 void；正常返回即通过，panic（退出码 101）即失败。
+pub const TestAttr = struct {
+    /// 期望 panic：panic 消息必须包含该文本（@test(should_panic) 为 ""，任意 panic 都通过）
+    should_panic: ?[]const u8 = null,
+    /// @skip("reason")：不运行，在汇总中计入跳过
+    skip: ?[]const u8 = null,
+    /// 🆕 @property：以不同的种子运行的次数（函数体用 gen_int / gen_string 生成输入）
+    runs: ?u32 = null,
+};
+
+/// 🆕 @property 未指定 runs 时的运行次数
+pub const DEFAULT_PROPERTY_RUNS: u32 = 100;
+/// 🆕 property 测试 panic 时运行时在 stderr 最后输出的一行："<前缀><种子>"
+pub const PROPERTY_SEED_REPORT = "property failed with seed ";
+/// 🆕 gen_string 生成的字符
+pub const GEN_STRING_ALPHABET = "abcdefghijklmnopqrstuvwxyz0123456789";
+
+/// 🆕 gen_int / gen_string 的伪随机数（splitmix64；生成的 C 运行时实现相同的算法，
+/// 同一个种子在原生程序和解释器中产生相同的输入）
+pub fn nextRandom(state: *u64) u64 {
+    state.* +%= 0x9E3779B97F4A7C15;
+    var z = state.*;
+    z = (z ^ (z >> 30)) *% 0xBF58476D1CE4E5B9;
+    z = (z ^ (z >> 27)) *% 0x94D049BB133111EB;
+    return z ^ (z >> 31);
+}
+
+/// 🆕 gen_int(lo, hi)：[lo, hi] 中的整数（hi < lo 时返回 lo）
+pub fn randomInRange(state: *u64, lo: i64, hi: i64) i64 {
+    if (hi < lo) return lo;
+    const span: u64 = @intCast(hi - lo + 1);
+    return lo + @as(i64, @intCast(nextRandom(state) % span));
+}
+
+/// 🆕 用户 main 在目标文件中的符号名
+/// 真正的 `main` 是后端生成的包装函数：paw_rt_init → paw_main → paw_rt_shutdown
+pub const ENTRY_SYMBOL = "paw_main";
+
+/// 🆕 运行时 stdout 缓冲区大小（生成的 C 运行时和解释器一致）
+pub const STDOUT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 🆕 paw_log 的日志级别（下标即级别），PAW_LOG_LEVEL 可以是名称或数字
+pub const LOG_LEVEL_NAMES = [_][]const u8{ "off", "error", "warn", "info", "debug", "trace" };
+/// 🆕 未设置 PAW_LOG_LEVEL（或无法识别）时输出 error 和 warn
+pub const DEFAULT_LOG_LEVEL: i64 = 2;
+
+/// 🆕 解析 PAW_LOG_LEVEL（生成的 C 运行时实现相同的规则）
+pub fn parseLogLevel(value: ?[]const u8) i64 {
+    const text = value orelse return DEFAULT_LOG_LEVEL;
+    if (text.len == 0) return DEFAULT_LOG_LEVEL;
+    if (text[0] >= '0' and text[0] <= '9') {
+        return std.fmt.parseInt(i64, text, 10) catch DEFAULT_LOG_LEVEL;
+    }
+    for (LOG_LEVEL_NAMES, 0..) |name, level| {
+        if (std.mem.eql(u8, text, name)) return @intCast(level);
+    }
+    return DEFAULT_LOG_LEVEL;
+}
+
+/// 🆕 浮点数的输出格式（paw_write_f64 的 style 参数）
+pub const FloatStyle = enum(i32) {
+    shortest = 0,    // 能精确读回原值的最短十进制表示（println / 字符串插值）
+    fixed = 1,       // 定点，digits 位小数
+    scientific = 2,  // 科学计数法，尾数 digits 位小数
+};
+
+/// 🆕 shortest 格式中十进制指数在 [MIN, MAX) 内用定点表示，之外用科学计数法
+pub const FLOAT_FIXED_MIN_EXP: i32 = -5;
+pub const FLOAT_FIXED_MAX_EXP: i32 = 17;
+/// 🆕 fixed / scientific 的 digits 上限
+pub const FLOAT_MAX_DIGITS: i32 = 40;
+
+/// 🆕 浮点数的文本形式（生成的 C 运行时和 LLVM 运行时实现相同的规则）
+///
+///   - nan / inf / -inf
+///   - shortest：3 → "3"，0.1 + 0.2 → "0.30000000000000004"，1e20 → "1e20"，1e-7 → "1e-7"
+///   - 科学计数法的指数不带 "+" 和前导零，与 C 库无关
+pub fn formatFloat(writer: anytype, value: f64, style: FloatStyle, digits: i64) !void {
+    if (std.math.isNan(value)) return writer.writeAll("nan");
+    if (std.math.isInf(value)) return writer.writeAll(if (value < 0) "-inf" else "inf");
+    const precision: usize = @intCast(std.math.clamp(digits, 0, FLOAT_MAX_DIGITS));
+
+    switch (style) {
+        .fixed => try writer.print("{[value]d:.[precision]}", .{ .value = value, .precision = precision }),
+        .scientific => try writer.print("{[value]e:.[precision]}", .{ .value = value, .precision = precision }),
+        .shortest => {
+            // 最短往返的尾数和指数，如 "-1.25e3"
+            var buf: [64]u8 = undefined;
+            const sci = std.fmt.bufPrint(&buf, "{e}", .{value}) catch unreachable;
+            const e_index = std.mem.lastIndexOfScalar(u8, sci, 'e').?;
+            const exp10 = std.fmt.parseInt(i32, sci[e_index + 1 ..], 10) catch unreachable;
+            if (exp10 < FLOAT_FIXED_MIN_EXP or exp10 >= FLOAT_FIXED_MAX_EXP) return writer.writeAll(sci);
+
+            var significant: i32 = 0;
+            for (sci[0..e_index]) |c| {
+                if (std.ascii.isDigit(c)) significant += 1;
+            }
+            const decimals: usize = @intCast(@max(significant - 1 - exp10, 0));
+            try writer.print("{[value]d:.[precision]}", .{ .value = value, .precision = decimals });
+        },
+    }
+}
+
+/// 🆕 @link_name("symbol", macos: "_symbol", windows: "...")
+///
+/// 名称是目标文件中的原始符号（后端不再添加平台前缀）；
+/// 按目标系统的覆盖优先，其次是默认名称
+pub const LinkName = struct {
+    default: ?[]const u8,
+    overrides: []const TargetLinkName,
+
+    pub fn resolve(self: LinkName, os: []const u8) ?[]const u8 {
+        for (self.overrides) |o| {
+            if (std.mem.eql(u8, o.os, os)) return o.name;
+        }
+        return self.default;
+    }
+};
+
+pub const TargetLinkName = struct {
+    os: []const u8,  // std.Target.Os.Tag 的名称：linux、macos、windows……
+    name: []const u8,
+};
+
+/// 🆕 可以用 @allow(...) 对单个声明关闭的检查
+pub const Lint = enum {
+    unused_pub,  // 没有被其他公开项或测试用到的 pub 项（pawc unused-pub）
+};
+
+pub const LintSet = std.EnumSet(Lint);
+
+/// 🆕 全局常量：const NAME: T = expr;
+/// value 在解析结束后由 consteval 求值并替换为字面量
+pub const ConstDecl = struct {
+    name: []const u8,
+    type: ?Type,
+    value: *Expr,
+    is_public: bool,
+    location: ?SourceLocation = null,
+    allow: LintSet = LintSet.initEmpty(),  // 🆕 @allow(...) 关闭的检查
+    namespace: ?[]const u8 = null,  // 🆕 命名空间导入的副本：导入时的别名（见 FunctionDecl.namespace）
+};
+
 pub const StructDecl = struct {
     name: []const u8,
     type_params: [][]const u8,
@@ -514,6 +1092,12 @@ pub const StructField = struct {
     is_mut: bool,     // 新增：字段可变性
 };
 
+/// 🆕 类型中的关联类型：type Item = i32;（实现 trait 中声明的 `type Item;`）
+pub const AssocType = struct {
+    name: []const u8,
+    type: Type,
+};
+
 pub const EnumVariant = struct {
     name: []const u8,
     fields: []Type, // 数据变体的字段类型
@@ -545,15 +1129,27 @@ pub const ImplDecl = struct {
     type_args: []Type,
     target_type: Type,
     methods: []FunctionDecl,
+    type_params: [][]const u8 = &.{},  // 🆕 impl<T> Show for Box<T>：与目标类型的参数相同
+    location: ?SourceLocation = null,  // 🆕 impl 关键字的位置
 };
 
+/// 🆕 泛型类型的参数 param 在实例中的类型实参（type_params 是类型的参数，type_args 是实例的类型实参）
+pub fn typeArgFor(type_params: []const []const u8, type_args: []const Type, param: []const u8) ?Type {
+    for (type_params, 0..) |tp, i| {
+        if (i < type_args.len and std.mem.eql(u8, tp, param)) return type_args[i];
+    }
+    return null;
+}
+
 pub const ImportDecl = struct {
     module_path: []const u8,      // math.add -> "math" (需要释放)
     items: ImportItems,           // 🆕 支持多项导入
+    is_public: bool = false,      // 🆕 pub import：重导出给本模块的导入者
     
     pub const ImportItems = union(enum) {
         single: []const u8,       // import math.add
         multiple: [][]const u8,   // import math.{add, sub} (需要释放slice)
+        namespace: []const u8,    // 🆕 import math as m（别名，通过 m.add 访问）
     };
     
     pub fn deinit(self: ImportDecl, allocator: std.mem.Allocator) void {
@@ -564,19 +1160,37 @@ pub const ImportDecl = struct {
     }
 };
 
+/// 🆕 命名空间导入的符号名：import math as m 后，m.add 解析为 m__add
+pub fn namespacedName(allocator: std.mem.Allocator, alias: []const u8, item: []const u8) ![]const u8 {
+    return std.fmt.allocPrint(allocator, "{s}__{s}", .{ alias, item });
+}
+
+/// 🆕 对 trait 的引用：Eq 或 Eq<T>（supertrait）
+pub const TraitRef = struct {
+    name: []const u8,
+    type_args: []Type,
+};
+
 // 新增：统一的类型定义类型
 pub const TypeDeclKind = union(enum) {
     struct_type: struct {
         fields: []StructField,
         methods: []FunctionDecl,
+        assoc_types: []AssocType = &.{},  // 🆕 type Item = i32;
     },
     enum_type: struct {
         variants: []EnumVariant,
         methods: []FunctionDecl,
+        assoc_types: []AssocType = &.{},  // 🆕 type Item = i32;
     },
     trait_type: struct {
         methods: []FunctionSignature,
+        supertraits: []TraitRef = &.{},  // 🆕 type Ord = trait: Eq { ... }
+        assoc_types: [][]const u8 = &.{},  // 🆕 关联类型声明：type Item;
     },
+    // 🆕 类型别名：type Meters = f64; / type Grid<T> = [[T; 3]; 3];
+    // 解析后由 alias.zig 展开，之后的阶段忽略别名声明
+    alias: Type,
 };
 
 // 新增：统一的 type 声明
@@ -585,6 +1199,30 @@ pub const TypeDecl = struct {
     type_params: [][]const u8,
     kind: TypeDeclKind,
     is_public: bool,
+    location: ?SourceLocation = null,  // 🆕 类型名在源码中的位置
+    allow: LintSet = LintSet.initEmpty(),  // 🆕 @allow(...) 关闭的检查
+    
+    /// 🆕 struct / enum 中名为 name 的关联类型。type_args 是泛型类型实例的类型实参：
+    /// 关联类型是类型参数本身时（type Item = T;）替换为对应的实参
+    pub fn assocType(self: TypeDecl, name: []const u8, type_args: []const Type) ?Type {
+        const assoc_types = switch (self.kind) {
+            .struct_type => |st| st.assoc_types,
+            .enum_type => |et| et.assoc_types,
+            .trait_type, .alias => return null,
+        };
+        for (assoc_types) |assoc| {
+            if (!std.mem.eql(u8, assoc.name, name)) continue;
+            const param = switch (assoc.type) {
+                .named, .generic => |n| n,
+                else => return assoc.type,
+            };
+            for (self.type_params, 0..) |tp, i| {
+                if (std.mem.eql(u8, tp, param) and i < type_args.len) return type_args[i];
+            }
+            return assoc.type;
+        }
+        return null;
+    }
     
     pub fn deinit(self: TypeDecl, allocator: std.mem.Allocator) void {
         allocator.free(self.type_params);
@@ -595,6 +1233,7 @@ pub const TypeDecl = struct {
                     method.deinit(allocator);
                 }
                 allocator.free(st.methods);
+                allocator.free(st.assoc_types);
             },
             .enum_type => |et| {
                 // 释放每个变体的字段
@@ -606,10 +1245,14 @@ pub const TypeDecl = struct {
                     method.deinit(allocator);
                 }
                 allocator.free(et.methods);
+                allocator.free(et.assoc_types);
             },
             .trait_type => |tt| {
                 allocator.free(tt.methods);
+                allocator.free(tt.supertraits);
+                allocator.free(tt.assoc_types);
             },
+            .alias => {},
         }
     }
 };
@@ -624,6 +1267,7 @@ pub const TopLevelDecl = union(enum) {
     trait_decl: TraitDecl,
     impl_decl: ImplDecl,
     import_decl: ImportDecl,
+    const_decl: ConstDecl,  // 🆕 const NAME = expr;
 };
 
 pub const Program = struct {
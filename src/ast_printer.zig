@@ -0,0 +1,699 @@
+//! AstPrinter - 以缩进树打印语法树（pawc ast <file> [--typed] [--spans]）
+//!
+//! 每个节点一行，子节点缩进两格，比 {any} 的调试输出容易读：
+//! 学习语言时可以看到源码被解析成了什么，维护解析器时可以直接检查脱糖的结果。
+//!   --typed   先做类型检查，在表达式和 let 绑定后面附上推导出的类型（"  : i32"）
+//!   --spans   在声明后面附上源码位置（"  @ 3:4"；目前只有声明记录了位置）
+//!
+//! 只打印用户文件中的声明，省略 prelude。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const api = @import("api.zig");
+const query = @import("query.zig");
+const Lexer = @import("lexer.zig").Lexer;
+const Parser = @import("parser.zig").Parser;
+const consteval = @import("consteval.zig");
+const TypeChecker = @import("typechecker.zig").TypeChecker;
+const SourceMap = @import("source_map.zig").SourceMap;
+
+pub const Options = struct {
+    typed: bool = false,  // --typed
+    spans: bool = false,  // --spans
+};
+
+/// 类型检查时记录的表达式类型（--typed）
+///
+/// 表达式按值传递，没有 id；这里用节点独有的地址作为键：
+/// 标识符的源码切片、子表达式的堆指针、由类型检查器填写的字段等（见 exprKey）。
+pub const ExprTypes = struct {
+    types: std.AutoHashMap(usize, ast.Type),
+
+    pub fn init(allocator: std.mem.Allocator) ExprTypes {
+        return ExprTypes{ .types = std.AutoHashMap(usize, ast.Type).init(allocator) };
+    }
+
+    pub fn deinit(self: *ExprTypes) void {
+        self.types.deinit();
+    }
+
+    pub fn record(self: *ExprTypes, expr: ast.Expr, t: ast.Type) !void {
+        const key = exprKey(expr) orelse return;
+        try self.types.put(key, t);
+    }
+
+    /// let 绑定的类型（声明的或推导的），以变量名 token 的地址为键
+    pub fn recordBinding(self: *ExprTypes, name: []const u8, t: ast.Type) !void {
+        try self.types.put(@intFromPtr(name.ptr), t);
+    }
+
+    fn get(self: *const ExprTypes, expr: ast.Expr) ?ast.Type {
+        return self.types.get(exprKey(expr) orelse return null);
+    }
+
+    fn getBinding(self: *const ExprTypes, name: []const u8) ?ast.Type {
+        return self.types.get(@intFromPtr(name.ptr));
+    }
+};
+
+/// 表达式的唯一地址；字面量没有（它们的类型由上下文决定，打印时省略）
+fn exprKey(expr: ast.Expr) ?usize {
+    return switch (expr) {
+        // 解析器为 self 生成的是同一个字符串常量，没有唯一地址
+        .identifier => |name| if (std.mem.eql(u8, name, "self")) null else @intFromPtr(name.ptr),
+        .binary => |bin| @intFromPtr(bin.left),
+        .unary => |un| @intFromPtr(un.operand),
+        .call => |call| @intFromPtr(call.resolved_type_args),
+        .static_method_call => |smc| @intFromPtr(smc.method_name.ptr),
+        .field_access => |fa| @intFromPtr(fa.object),
+        .struct_init => |si| @intFromPtr(si.type_name.ptr),
+        .enum_variant => |ev| @intFromPtr(ev.variant.ptr),
+        .block => |stmts| if (stmts.len > 0) @intFromPtr(stmts.ptr) else null,
+        .if_expr => |ie| @intFromPtr(ie.condition),
+        .is_expr => |is_match| @intFromPtr(is_match.value),
+        .match_expr => |match| @intFromPtr(match.value),
+        .as_expr => |ae| @intFromPtr(ae.resolved_from),
+        .await_expr => |inner| @intFromPtr(inner),
+        .array_literal => |elems| if (elems.len > 0) @intFromPtr(elems.ptr) else null,
+        .array_index => |ai| @intFromPtr(ai.array),
+        .range => |rng| @intFromPtr(rng.start),
+        .string_interp => |si| if (si.parts.len > 0) @intFromPtr(si.parts.ptr) else null,
+        .try_expr => |inner| @intFromPtr(inner),
+        .loop_expr => |le| @intFromPtr(le.result_type),
+        .lambda => |lambda| @intFromPtr(lambda.captures),
+        else => null,
+    };
+}
+
+/// 解析（--typed 时再做类型检查）并把用户文件的语法树打印到 stdout
+pub fn run(allocator: std.mem.Allocator, source_file: []const u8, options: Options) !void {
+    const source = std.fs.cwd().readFileAlloc(allocator, source_file, 10 * 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read file {s}: {any}\n", .{ source_file, err });
+        return error.InvalidInput;
+    };
+    defer allocator.free(source);
+
+    // 与编译时相同：prelude 在前，行号减去 prelude 的行数
+    const prelude_source = @embedFile("prelude/prelude.paw");
+    const prelude_lines = std.mem.count(u8, prelude_source, "\n") + 2;
+    const combined_source = try std.fmt.allocPrint(allocator, "{s}\n\n{s}", .{ prelude_source, source });
+    defer allocator.free(combined_source);
+    const user_start = @intFromPtr(combined_source.ptr) + prelude_source.len + 2;
+
+    var lexer = Lexer.init(allocator, combined_source, source_file);
+    lexer.setLineOffset(prelude_lines);
+    defer lexer.deinit();
+    const tokens = try lexer.tokenize();
+
+    var parser = Parser.init(allocator, tokens);
+    defer parser.deinit();
+    const program = try parser.parse();
+    try consteval.evaluateProgram(parser.arena.allocator(), program, consteval.MAX_STEPS);  // 与编译时相同：常量已折叠
+
+    var expr_types = ExprTypes.init(allocator);
+    defer expr_types.deinit();
+    var type_checker = TypeChecker.init(allocator, tokens);
+    defer type_checker.deinit();
+    var source_map = SourceMap.init(allocator);
+    defer source_map.deinit();
+    if (options.typed) {
+        _ = try source_map.addFile(source_file, source);
+        type_checker.source_map = &source_map;
+        type_checker.expr_types = &expr_types;
+        try type_checker.check(program);  // 诊断已经打印
+    }
+
+    var printer = Printer{
+        .allocator = allocator,
+        .out = std.ArrayList(u8){},
+        .types = if (options.typed) &expr_types else null,
+        .spans = options.spans,
+    };
+    defer printer.out.deinit(allocator);
+
+    try printer.out.writer(allocator).print("Program {s}\n", .{source_file});
+    for (program.declarations) |decl| {
+        // 声明名是 combined_source 的切片：位于用户部分的才是用户声明（导入没有名字，总是打印）
+        const name: ?[]const u8 = switch (decl) {
+            .function => |func| func.name,
+            .type_decl => |td| td.name,
+            .struct_decl => |sd| sd.name,
+            .enum_decl => |ed| ed.name,
+            .trait_decl => |trait| trait.name,
+            .impl_decl => |impl| impl.trait_name,
+            .const_decl => |cd| cd.name,
+            .import_decl => null,
+        };
+        if (name) |n| {
+            if (@intFromPtr(n.ptr) < user_start) continue;
+        }
+        try printer.printDecl(decl, 1);
+    }
+
+    try std.fs.File.stdout().writeAll(printer.out.items);
+}
+
+const Error = std.mem.Allocator.Error;
+
+const Printer = struct {
+    allocator: std.mem.Allocator,
+    out: std.ArrayList(u8),
+    types: ?*const ExprTypes,
+    spans: bool,
+
+    fn writer(self: *Printer) @TypeOf(self.out.writer(self.allocator)) {
+        return self.out.writer(self.allocator);
+    }
+
+    fn indent(self: *Printer, depth: usize) Error!void {
+        try self.out.appendNTimes(self.allocator, ' ', depth * 2);
+    }
+
+    /// 一整行：缩进 + 文本 + 换行
+    fn line(self: *Printer, depth: usize, comptime fmt: []const u8, args: anytype) Error!void {
+        try self.indent(depth);
+        try self.writer().print(fmt ++ "\n", args);
+    }
+
+    fn appendType(self: *Printer, t: ast.Type) Error!void {
+        try api.appendType(&self.out, self.allocator, t);
+    }
+
+    fn appendTypeSuffix(self: *Printer, t: ?ast.Type) Error!void {
+        const found = t orelse return;
+        try self.out.appendSlice(self.allocator, "  : ");
+        try self.appendType(found);
+    }
+
+    fn appendSpan(self: *Printer, location: ?ast.SourceLocation) Error!void {
+        if (!self.spans) return;
+        const loc = location orelse return;
+        try self.writer().print("  @ {d}:{d}", .{ loc.line, loc.column });
+    }
+
+    fn appendTypeParams(self: *Printer, type_params: []const []const u8) Error!void {
+        if (type_params.len == 0) return;
+        try self.out.append(self.allocator, '<');
+        for (type_params, 0..) |param, i| {
+            if (i > 0) try self.out.appendSlice(self.allocator, ", ");
+            try self.out.appendSlice(self.allocator, param);
+        }
+        try self.out.append(self.allocator, '>');
+    }
+
+    fn appendTypeArgs(self: *Printer, type_args: []const ast.Type) Error!void {
+        if (type_args.len == 0) return;
+        try self.out.append(self.allocator, '<');
+        for (type_args, 0..) |arg, i| {
+            if (i > 0) try self.out.appendSlice(self.allocator, ", ");
+            try self.appendType(arg);
+        }
+        try self.out.append(self.allocator, '>');
+    }
+
+    // ========================================================================
+    // 声明
+    // ========================================================================
+
+    fn printDecl(self: *Printer, decl: ast.TopLevelDecl, depth: usize) Error!void {
+        switch (decl) {
+            .function => |func| try self.printFunction(func, depth),
+            .type_decl => |td| {
+                try self.indent(depth);
+                if (td.is_public) try self.out.appendSlice(self.allocator, "pub ");
+                const kind = switch (td.kind) {
+                    .struct_type => "Struct",
+                    .enum_type => "Enum",
+                    .trait_type => "Trait",
+                    .alias => "Alias",
+                };
+                try self.writer().print("{s} {s}", .{ kind, td.name });
+                try self.appendTypeParams(td.type_params);
+                if (td.kind == .alias) {
+                    try self.out.appendSlice(self.allocator, " = ");
+                    try self.appendType(td.kind.alias);
+                }
+                try self.appendSpan(td.location);
+                try self.out.append(self.allocator, '\n');
+                switch (td.kind) {
+                    .struct_type => |st| {
+                        try self.printFields(st.fields, depth + 1);
+                        for (st.methods) |method| try self.printFunction(method, depth + 1);
+                    },
+                    .enum_type => |et| {
+                        try self.printVariants(et.variants, depth + 1);
+                        for (et.methods) |method| try self.printFunction(method, depth + 1);
+                    },
+                    .trait_type => |tt| try self.printSignatures(tt.methods, depth + 1),
+                    .alias => {},
+                }
+            },
+            .struct_decl => |sd| {
+                try self.line(depth, "Struct {s}", .{sd.name});
+                try self.printFields(sd.fields, depth + 1);
+                for (sd.methods) |method| try self.printFunction(method, depth + 1);
+            },
+            .enum_decl => |ed| {
+                try self.line(depth, "Enum {s}", .{ed.name});
+                try self.printVariants(ed.variants, depth + 1);
+                for (ed.methods) |method| try self.printFunction(method, depth + 1);
+            },
+            .trait_decl => |trait| {
+                try self.line(depth, "Trait {s}", .{trait.name});
+                try self.printSignatures(trait.methods, depth + 1);
+            },
+            .impl_decl => |impl| {
+                try self.indent(depth);
+                try self.writer().print("Impl {s}", .{impl.trait_name});
+                try self.appendTypeArgs(impl.type_args);
+                try self.out.appendSlice(self.allocator, " for ");
+                try self.appendType(impl.target_type);
+                try self.out.append(self.allocator, '\n');
+                for (impl.methods) |method| try self.printFunction(method, depth + 1);
+            },
+            .const_decl => |cd| {
+                try self.indent(depth);
+                if (cd.is_public) try self.out.appendSlice(self.allocator, "pub ");
+                try self.writer().print("Const {s}", .{cd.name});
+                if (cd.type) |t| {
+                    try self.out.appendSlice(self.allocator, ": ");
+                    try self.appendType(t);
+                }
+                try self.appendSpan(cd.location);
+                try self.out.append(self.allocator, '\n');
+                try self.printExpr(cd.value.*, depth + 1);
+            },
+            .import_decl => |import| {
+                try self.indent(depth);
+                if (import.is_public) try self.out.appendSlice(self.allocator, "pub ");
+                try self.writer().print("Import {s}", .{import.module_path});
+                switch (import.items) {
+                    .single => |item| try self.writer().print(".{s}", .{item}),
+                    .multiple => |items| {
+                        try self.out.appendSlice(self.allocator, ".{");
+                        for (items, 0..) |item, i| {
+                            if (i > 0) try self.out.appendSlice(self.allocator, ", ");
+                            try self.out.appendSlice(self.allocator, item);
+                        }
+                        try self.out.append(self.allocator, '}');
+                    },
+                    .namespace => |alias| try self.writer().print(" as {s}", .{alias}),
+                }
+                try self.out.append(self.allocator, '\n');
+            },
+        }
+    }
+
+    fn printFunction(self: *Printer, func: ast.FunctionDecl, depth: usize) Error!void {
+        try self.indent(depth);
+        if (func.is_public) try self.out.appendSlice(self.allocator, "pub ");
+        if (func.is_const) try self.out.appendSlice(self.allocator, "const ");
+        if (func.is_async) try self.out.appendSlice(self.allocator, "async ");
+        if (func.is_extern) try self.out.appendSlice(self.allocator, "extern ");
+        // formatFunction 的结果以 "fn " 开头
+        const signature = try api.formatFunction(self.allocator, func.name, func.type_params, func.params, func.return_type);
+        defer self.allocator.free(signature);
+        try self.writer().print("Function {s}", .{signature[3..]});
+        for (func.bounds, 0..) |bound, i| {
+            try self.out.appendSlice(self.allocator, if (i == 0) " where " else ", ");
+            try self.writer().print("{s}: {s}", .{ bound.param, @tagName(bound.bound) });
+        }
+        try self.appendSpan(func.location);
+        try self.out.append(self.allocator, '\n');
+
+        for (func.asm_bodies) |body| {
+            try self.indent(depth + 1);
+            try self.writer().print("Asm {s} ", .{body.arch});
+            try query.appendJsonString(&self.out, self.allocator, body.text);
+            try self.out.append(self.allocator, '\n');
+        }
+        for (func.body) |stmt| try self.printStmt(stmt, depth + 1);
+    }
+
+    fn printFields(self: *Printer, fields: []const ast.StructField, depth: usize) Error!void {
+        for (fields) |field| {
+            try self.indent(depth);
+            try self.out.appendSlice(self.allocator, "Field ");
+            if (field.is_public) try self.out.appendSlice(self.allocator, "pub ");
+            if (field.is_mut) try self.out.appendSlice(self.allocator, "mut ");
+            try self.writer().print("{s}: ", .{field.name});
+            try self.appendType(field.type);
+            try self.out.append(self.allocator, '\n');
+        }
+    }
+
+    fn printVariants(self: *Printer, variants: []const ast.EnumVariant, depth: usize) Error!void {
+        for (variants) |variant| {
+            try self.indent(depth);
+            try self.writer().print("Variant {s}", .{variant.name});
+            if (variant.fields.len > 0) {
+                try self.out.append(self.allocator, '(');
+                for (variant.fields, 0..) |field, i| {
+                    if (i > 0) try self.out.appendSlice(self.allocator, ", ");
+                    try self.appendType(field);
+                }
+                try self.out.append(self.allocator, ')');
+            }
+            try self.out.append(self.allocator, '\n');
+        }
+    }
+
+    fn printSignatures(self: *Printer, methods: []const ast.FunctionSignature, depth: usize) Error!void {
+        for (methods) |method| {
+            const signature = try api.formatFunction(self.allocator, method.name, &[_][]const u8{}, method.params, method.return_type);
+            defer self.allocator.free(signature);
+            try self.line(depth, "Method {s}", .{signature[3..]});
+        }
+    }
+
+    // ========================================================================
+    // 语句
+    // ========================================================================
+
+    fn printStmt(self: *Printer, stmt: ast.Stmt, depth: usize) Error!void {
+        switch (stmt) {
+            .expr => |expr| try self.printExpr(expr, depth),
+            .let_decl => |let| {
+                try self.indent(depth);
+                try self.writer().print("Let {s}{s}", .{ if (let.is_mut) "mut " else "", let.name });
+                if (let.type) |t| {
+                    try self.out.appendSlice(self.allocator, ": ");
+                    try self.appendType(t);
+                } else if (self.types) |types| {
+                    try self.appendTypeSuffix(types.getBinding(let.name));
+                }
+                try self.out.append(self.allocator, '\n');
+                if (let.init) |init| try self.printExpr(init, depth + 1);
+            },
+            .assign => |assign| {
+                try self.line(depth, "Assign", .{});
+                try self.printExpr(assign.target, depth + 1);
+                try self.printExpr(assign.value, depth + 1);
+            },
+            .compound_assign => |ca| {
+                try self.line(depth, "CompoundAssign {s}", .{compoundOpSymbol(ca.op)});
+                try self.printExpr(ca.target, depth + 1);
+                try self.printExpr(ca.value, depth + 1);
+            },
+            .return_stmt => |ret| {
+                try self.line(depth, "Return", .{});
+                if (ret) |value| try self.printExpr(value, depth + 1);
+            },
+            .break_stmt => |brk| {
+                try self.indent(depth);
+                try self.out.appendSlice(self.allocator, "Break");
+                if (brk.label) |label| try self.writer().print(" '{s}", .{label});
+                try self.out.append(self.allocator, '\n');
+                if (brk.value) |value| try self.printExpr(value, depth + 1);
+            },
+            .continue_stmt => |label| {
+                if (label) |l| {
+                    try self.line(depth, "Continue '{s}", .{l});
+                } else {
+                    try self.line(depth, "Continue", .{});
+                }
+            },
+            .loop_stmt => |loop| {
+                try self.indent(depth);
+                try self.out.appendSlice(self.allocator, "Loop");
+                if (loop.label) |label| try self.writer().print(" '{s}", .{label});
+                try self.out.append(self.allocator, '\n');
+                if (loop.condition) |cond| {
+                    try self.line(depth + 1, "condition:", .{});
+                    try self.printExpr(cond, depth + 2);
+                }
+                if (loop.iterator) |iter| {
+                    try self.line(depth + 1, "for {s} in:", .{iter.binding});
+                    try self.printExpr(iter.iterable, depth + 2);
+                }
+                try self.printBody(loop.body, depth + 1);
+            },
+            .while_loop => |loop| {
+                try self.line(depth, "While", .{});
+                try self.line(depth + 1, "condition:", .{});
+                try self.printExpr(loop.condition, depth + 2);
+                try self.printBody(loop.body, depth + 1);
+            },
+            .for_loop => |loop| {
+                try self.line(depth, "For", .{});
+                if (loop.init) |init| {
+                    try self.line(depth + 1, "init:", .{});
+                    try self.printStmt(init.*, depth + 2);
+                }
+                if (loop.condition) |cond| {
+                    try self.line(depth + 1, "condition:", .{});
+                    try self.printExpr(cond, depth + 2);
+                }
+                if (loop.step) |step| {
+                    try self.line(depth + 1, "step:", .{});
+                    try self.printExpr(step, depth + 2);
+                }
+                try self.printBody(loop.body, depth + 1);
+            },
+        }
+    }
+
+    fn printBody(self: *Printer, body: []const ast.Stmt, depth: usize) Error!void {
+        try self.line(depth, "body:", .{});
+        for (body) |stmt| try self.printStmt(stmt, depth + 1);
+    }
+
+    // ========================================================================
+    // 表达式：先打印节点本身一行，再打印子节点
+    // ========================================================================
+
+    fn printExpr(self: *Printer, expr: ast.Expr, depth: usize) Error!void {
+        try self.indent(depth);
+        const w = self.writer();
+        switch (expr) {
+            .int_literal => |literal| try w.print("Int {d}", .{literal.value}),
+            .float_literal => |v| {
+                try w.writeAll("Float ");
+                try ast.formatFloat(w, v, .shortest, 0);
+            },
+            .string_literal => |s| {
+                try w.writeAll("String ");
+                try query.appendJsonString(&self.out, self.allocator, s);
+            },
+            .char_literal => |c| {
+                if (c >= 0x20 and c < 0x7f and c != '\'' and c != '\\') {
+                    try w.print("Char '{c}'", .{@as(u8, @intCast(c))});
+                } else {
+                    try w.print("Char '\\u{{{X}}}'", .{c});
+                }
+            },
+            .byte_literal => |b| try w.print("Byte {d}", .{b}),
+            .byte_string_literal => |bytes| {
+                try w.writeAll("ByteString ");
+                try query.appendJsonString(&self.out, self.allocator, bytes);
+            },
+            .bool_literal => |b| try w.print("Bool {s}", .{if (b) "true" else "false"}),
+            .identifier => |name| try w.print("Identifier {s}", .{name}),
+            .binary => |bin| {
+                try w.print("Binary {s}", .{binaryOpSymbol(bin.op)});
+                if (self.types != null) {
+                    if (bin.resolved_operator.*) |type_name| try w.print(" ({s}.{s})", .{ type_name, bin.op.overloadMethod().? });
+                }
+            },
+            .unary => |un| try w.print("Unary {s}", .{if (un.op == .neg) "-" else "!"}),
+            .call => |call| {
+                try w.writeAll("Call");
+                if (call.callee.* == .identifier) try w.print(" {s}", .{call.callee.identifier});
+                try self.appendTypeArgs(call.type_args);
+                // 类型检查器推导出的泛型实参（into 的是 [源类型, 目标类型]）
+                if (self.types != null and call.type_args.len == 0 and call.resolved_type_args.*.len > 0) {
+                    try w.writeAll(if (call.resolved_into.*) " (From " else " (inferred ");
+                    try self.appendTypeArgs(call.resolved_type_args.*);
+                    try w.writeAll(")");
+                }
+            },
+            .static_method_call => |smc| {
+                try w.print("StaticCall {s}", .{smc.type_name});
+                try self.appendTypeArgs(smc.type_args);
+                try w.print("::{s}", .{smc.method_name});
+            },
+            .field_access => |fa| try w.print("Field .{s}", .{fa.field}),
+            .struct_init => |si| {
+                try w.print("StructInit {s}", .{si.type_name});
+                try self.appendTypeArgs(si.type_args);
+            },
+            .enum_variant => |ev| try w.print("EnumVariant {s}::{s}", .{ ev.enum_name, ev.variant }),
+            .block => try w.writeAll("Block"),
+            .if_expr => try w.writeAll("If"),
+            .is_expr => try w.writeAll("Is"),
+            .match_expr => try w.writeAll("Match"),
+            .as_expr => |ae| {
+                try w.writeAll("As ");
+                try self.appendType(ae.target_type);
+                if (self.types != null and ae.resolved_from.*) try w.writeAll(" (From)");
+            },
+            .await_expr => try w.writeAll("Await"),
+            .array_literal => try w.writeAll("Array"),
+            .array_index => try w.writeAll("Index"),
+            .range => |rng| try w.print("Range {s}", .{if (rng.inclusive) "..=" else ".."}),
+            .string_interp => try w.writeAll("StringInterp"),
+            .try_expr => try w.writeAll("Try ?"),
+            .type_query => |tq| {
+                try w.print("{s}<", .{@tagName(tq.kind)});
+                try self.appendType(tq.type);
+                try w.writeAll(">()");
+            },
+            .loop_expr => |le| {
+                try w.writeAll("LoopExpr");
+                if (le.label) |label| try w.print(" '{s}", .{label});
+            },
+            .lambda => |lambda| {
+                // formatFunction 的结果以 "fn " 开头（名字为空）
+                const signature = try api.formatFunction(self.allocator, "", &[_][]const u8{}, lambda.params, lambda.return_type);
+                defer self.allocator.free(signature);
+                try w.print("Lambda {s}", .{signature[3..]});
+                for (lambda.captures.*, 0..) |capture, i| {
+                    try w.print("{s}{s}", .{ if (i == 0) " captures " else ", ", capture.name });
+                }
+            },
+        }
+        if (self.types) |types| try self.appendTypeSuffix(types.get(expr));
+        try self.out.append(self.allocator, '\n');
+
+        const child = depth + 1;
+        switch (expr) {
+            .binary => |bin| {
+                try self.printExpr(bin.left.*, child);
+                try self.printExpr(bin.right.*, child);
+            },
+            .unary => |un| try self.printExpr(un.operand.*, child),
+            .call => |call| {
+                if (call.callee.* != .identifier) {
+                    try self.line(child, "callee:", .{});
+                    try self.printExpr(call.callee.*, child + 1);
+                }
+                for (call.args) |arg| try self.printExpr(arg, child);
+            },
+            .static_method_call => |smc| for (smc.args) |arg| try self.printExpr(arg, child),
+            .field_access => |fa| try self.printExpr(fa.object.*, child),
+            .struct_init => |si| for (si.fields) |field| {
+                try self.line(child, "{s}:", .{field.name});
+                try self.printExpr(field.value, child + 1);
+            },
+            .enum_variant => |ev| for (ev.args) |arg| try self.printExpr(arg, child),
+            .block => |stmts| for (stmts) |stmt| try self.printStmt(stmt, child),
+            .if_expr => |ie| {
+                try self.line(child, "condition:", .{});
+                try self.printExpr(ie.condition.*, child + 1);
+                try self.line(child, "then:", .{});
+                try self.printExpr(ie.then_branch.*, child + 1);
+                if (ie.else_branch) |else_branch| {
+                    try self.line(child, "else:", .{});
+                    try self.printExpr(else_branch.*, child + 1);
+                }
+            },
+            .is_expr => |is_match| {
+                try self.printExpr(is_match.value.*, child);
+                for (is_match.arms) |arm| {
+                    try self.printArm(arm.pattern, child);
+                    if (arm.guard) |guard| {
+                        try self.line(child + 1, "if:", .{});
+                        try self.printExpr(guard, child + 2);
+                    }
+                    try self.printExpr(arm.body, child + 1);
+                }
+            },
+            .match_expr => |match| {
+                try self.printExpr(match.value.*, child);
+                for (match.arms) |arm| {
+                    try self.printArm(arm.pattern, child);
+                    try self.printExpr(arm.body, child + 1);
+                }
+            },
+            .as_expr => |ae| try self.printExpr(ae.value.*, child),
+            .await_expr => |inner| try self.printExpr(inner.*, child),
+            .array_literal => |elems| for (elems) |elem| try self.printExpr(elem, child),
+            .array_index => |ai| {
+                try self.printExpr(ai.array.*, child);
+                try self.printExpr(ai.index.*, child);
+            },
+            .range => |rng| {
+                try self.printExpr(rng.start.*, child);
+                try self.printExpr(rng.end.*, child);
+            },
+            .string_interp => |si| for (si.parts) |part| switch (part) {
+                .literal => |text| {
+                    try self.indent(child);
+                    try self.out.appendSlice(self.allocator, "Text ");
+                    try query.appendJsonString(&self.out, self.allocator, text);
+                    try self.out.append(self.allocator, '\n');
+                },
+                .expr => |e| try self.printExpr(e, child),
+            },
+            .try_expr => |inner| try self.printExpr(inner.*, child),
+            .loop_expr => |le| for (le.body) |stmt| try self.printStmt(stmt, child),
+            .lambda => |lambda| for (lambda.body) |stmt| try self.printStmt(stmt, child),
+            else => {},
+        }
+    }
+
+    /// 分支的模式：Arm Some(x) / Arm _ / Arm 42 / Arm 1..=5 / Arm 1 | 2
+    fn printArm(self: *Printer, pattern: ast.Pattern, depth: usize) Error!void {
+        try self.indent(depth);
+        try self.out.appendSlice(self.allocator, "Arm ");
+        try self.printPattern(pattern);
+        try self.out.append(self.allocator, '\n');
+    }
+
+    fn printPattern(self: *Printer, pattern: ast.Pattern) Error!void {
+        switch (pattern) {
+            .identifier => |name| try self.out.appendSlice(self.allocator, name),
+            .wildcard => try self.out.append(self.allocator, '_'),
+            .variant => |v| {
+                try self.out.appendSlice(self.allocator, v.name);
+                if (v.bindings.len > 0) {
+                    try self.out.append(self.allocator, '(');
+                    for (v.bindings, 0..) |binding, i| {
+                        if (i > 0) try self.out.appendSlice(self.allocator, ", ");
+                        try self.out.appendSlice(self.allocator, binding);
+                    }
+                    try self.out.append(self.allocator, ')');
+                }
+            },
+            .literal => |lit| try self.printPatternLiteral(lit),
+            .range => |r| {
+                try self.printPatternLiteral(r.start);
+                try self.out.appendSlice(self.allocator, if (r.inclusive) "..=" else "..");
+                try self.printPatternLiteral(r.end);
+            },
+            .alternatives => |alts| for (alts, 0..) |alt, i| {
+                if (i > 0) try self.out.appendSlice(self.allocator, " | ");
+                try self.printPattern(alt);
+            },
+        }
+    }
+
+    fn printPatternLiteral(self: *Printer, lit: ast.Expr) Error!void {
+        switch (lit) {
+            .int_literal => |literal| try self.writer().print("{d}", .{literal.value}),
+            .unary => |un| if (un.op == .neg and un.operand.* == .int_literal) {
+                try self.writer().print("-{d}", .{un.operand.int_literal.value});
+            } else {
+                try self.out.appendSlice(self.allocator, "<expr>");
+            },
+            .string_literal => |text| try query.appendJsonString(&self.out, self.allocator, text),
+            .char_literal => |c| try self.writer().print("U+{X:0>4}", .{c}),
+            .bool_literal => |b| try self.out.appendSlice(self.allocator, if (b) "true" else "false"),
+            else => try self.out.appendSlice(self.allocator, "<expr>"),
+        }
+    }
+};
+
+fn binaryOpSymbol(op: ast.BinaryOp) []const u8 {
+    return switch (op) {
+        .add => "+", .sub => "-", .mul => "*", .div => "/", .mod => "%",
+        .eq => "==", .ne => "!=", .lt => "<", .le => "<=", .gt => ">", .ge => ">=",
+        .and_op => "&&", .or_op => "||",
+    };
+}
+
+fn compoundOpSymbol(op: ast.CompoundAssignOp) []const u8 {
+    return switch (op) {
+        .add_assign => "+=", .sub_assign => "-=", .mul_assign => "*=", .div_assign => "/=", .mod_assign => "%=",
+    };
+}
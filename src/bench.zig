@@ -0,0 +1,489 @@
+//! Bench - 编译器性能基准（pawc self-bench [dir]）
+//!
+//! 对目录下（递归）每个 .paw 程序，在进程内重复运行前端和 C 代码生成：
+//!   lex → parse → typecheck → codegen
+//! 每个阶段记录耗时的中位数，Linux 上还记录用户态指令数（perf_event_open）。
+//! 指令数几乎不受机器负载影响，适合在 CI 中发现性能回退。
+//!
+//!   --save <file.json>       保存结果作为基线
+//!   --baseline <file.json>   与基线比较，任一阶段变慢超过阈值即失败
+//!   --threshold <percent>    回退阈值（默认 10%）
+//!   --generate               重新生成基准语料（benches/ 中的程序）
+//!
+//! 语料由 generateCorpus 确定性地生成，不提交到仓库：目录中没有 .paw 程序时先生成语料，
+//! 修改生成器后用 --generate 重新生成。
+
+const std = @import("std");
+const builtin = @import("builtin");
+const Lexer = @import("lexer.zig").Lexer;
+const Parser = @import("parser.zig").Parser;
+const consteval = @import("consteval.zig");
+const TypeChecker = @import("typechecker.zig").TypeChecker;
+const CodeGen = @import("codegen.zig").CodeGen;
+const selftest = @import("selftest.zig");
+
+pub const Phase = enum { lex, parse, typecheck, codegen };
+const PHASE_COUNT = @typeInfo(Phase).@"enum".fields.len;
+
+pub const Options = struct {
+    iterations: usize = 5,
+    save_path: ?[]const u8 = null,
+    baseline_path: ?[]const u8 = null,
+    threshold_percent: f64 = 10.0,
+};
+
+/// 一个阶段的测量结果（均为各次迭代的中位数）
+pub const Measurement = struct {
+    bench: []const u8,
+    phase: []const u8,
+    ns: u64,
+    instructions: ?u64 = null,  // null = 当前平台无法计数
+};
+
+/// 运行目录中的所有基准；与基线比较出现回退时返回 false
+pub fn run(allocator: std.mem.Allocator, dir_path: []const u8, options: Options) !bool {
+    var arena_state = std.heap.ArenaAllocator.init(allocator);
+    defer arena_state.deinit();
+    const arena = arena_state.allocator();
+
+    var files = std.ArrayList([]const u8){};
+    selftest.collectPrograms(arena, dir_path, &files) catch |err| switch (err) {
+        error.FileNotFound => {},
+        else => return err,
+    };
+    if (files.items.len == 0) {
+        // 🆕 语料按需生成（确定性的，与之前生成的语料相同，基线仍可比较）
+        try generateCorpus(allocator, dir_path);
+        try selftest.collectPrograms(arena, dir_path, &files);
+    }
+    std.mem.sort([]const u8, files.items, {}, selftest.lessThan);
+
+    var counter = InstructionCounter.open();
+    defer InstructionCounter.close(&counter);
+    if (counter == null) {
+        std.debug.print("ℹ️  Instruction counts are not available on this platform; comparing wall time only\n", .{});
+    }
+
+    var results = std.ArrayList(Measurement){};
+    std.debug.print("{s:<28} {s:<10} {s:>12} {s:>16}\n", .{ "bench", "phase", "time", "instructions" });
+    for (files.items) |file| {
+        const name = std.fs.path.stem(file);
+        const samples = try measureProgram(allocator, arena, file, options.iterations, &counter);
+        for (samples, 0..) |phase_samples, i| {
+            const measurement = Measurement{
+                .bench = name,
+                .phase = @tagName(@as(Phase, @enumFromInt(i))),
+                .ns = median(phase_samples.ns),
+                .instructions = if (counter != null) median(phase_samples.instructions) else null,
+            };
+            printMeasurement(measurement);
+            try results.append(arena, measurement);
+        }
+    }
+
+    if (options.save_path) |path| {
+        try saveResults(arena, path, results.items);
+        std.debug.print("\n💾 Saved {d} measurement(s) to {s}\n", .{ results.items.len, path });
+    }
+
+    if (options.baseline_path) |path| {
+        return compareWithBaseline(arena, path, results.items, options.threshold_percent);
+    }
+    return true;
+}
+
+// ============================================================================
+// 测量
+// ============================================================================
+
+const PhaseSamples = struct {
+    ns: []u64,
+    instructions: []u64,
+};
+
+/// 完整运行 iterations 次前端和代码生成，返回每个阶段的样本
+fn measureProgram(
+    allocator: std.mem.Allocator,
+    arena: std.mem.Allocator,
+    path: []const u8,
+    iterations: usize,
+    counter: *?InstructionCounter,
+) ![PHASE_COUNT]PhaseSamples {
+    const source = std.fs.cwd().readFileAlloc(arena, path, 64 * 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read file {s}: {any}\n", .{ path, err });
+        return err;
+    };
+
+    // 与正常编译一样先合并 prelude
+    const prelude_source = @embedFile("prelude/prelude.paw");
+    var prelude_lines: usize = 0;
+    for (prelude_source) |c| {
+        if (c == '\n') prelude_lines += 1;
+    }
+    prelude_lines += 2;
+    const combined_source = try std.fmt.allocPrint(arena, "{s}\n\n{s}", .{ prelude_source, source });
+
+    var samples: [PHASE_COUNT]PhaseSamples = undefined;
+    for (&samples) |*phase_samples| {
+        phase_samples.* = .{
+            .ns = try arena.alloc(u64, iterations),
+            .instructions = try arena.alloc(u64, iterations),
+        };
+    }
+
+    for (0..iterations) |iteration| {
+        // 1. lex
+        var probe = Probe.start(counter);
+        var lexer = Lexer.init(allocator, combined_source, path);
+        lexer.setLineOffset(prelude_lines);
+        defer lexer.deinit();
+        const tokens = try lexer.tokenize();
+        probe.finish(&samples[@intFromEnum(Phase.lex)], iteration);
+
+        // 2. parse
+        probe = Probe.start(counter);
+        var parser = Parser.init(allocator, tokens);
+        defer parser.deinit();
+        const program = try parser.parse();
+        try consteval.evaluateProgram(parser.arena.allocator(), program, consteval.MAX_STEPS);
+        probe.finish(&samples[@intFromEnum(Phase.parse)], iteration);
+
+        // 3. typecheck（语料不使用 import，不经过模块加载）
+        probe = Probe.start(counter);
+        var type_checker = TypeChecker.init(allocator, tokens);
+        defer type_checker.deinit();
+        try type_checker.check(program);
+        probe.finish(&samples[@intFromEnum(Phase.typecheck)], iteration);
+
+        // 4. codegen（C 后端）
+        probe = Probe.start(counter);
+        var codegen = CodeGen.init(allocator);
+        defer codegen.deinit();
+        const code = try codegen.generate(program);
+        probe.finish(&samples[@intFromEnum(Phase.codegen)], iteration);
+        allocator.free(code);
+    }
+    return samples;
+}
+
+/// 一个阶段的计时与计数
+const Probe = struct {
+    timer: std.time.Timer,
+    counter: *?InstructionCounter,
+    instructions_start: u64,
+
+    fn start(counter: *?InstructionCounter) Probe {
+        const instructions_start = if (counter.*) |*c| c.read() else 0;
+        return Probe{
+            .timer = std.time.Timer.start() catch unreachable,
+            .counter = counter,
+            .instructions_start = instructions_start,
+        };
+    }
+
+    fn finish(self: *Probe, samples: *PhaseSamples, iteration: usize) void {
+        samples.ns[iteration] = self.timer.read();
+        samples.instructions[iteration] = if (self.counter.*) |*c| c.read() -% self.instructions_start else 0;
+    }
+};
+
+/// 用户态指令计数器（Linux perf_event_open；其他平台不可用）
+const InstructionCounter = struct {
+    fd: std.posix.fd_t,
+
+    fn open() ?InstructionCounter {
+        if (builtin.os.tag != .linux) return null;
+        const linux = std.os.linux;
+
+        var attr = std.mem.zeroes(linux.perf_event_attr);
+        attr.type = linux.PERF.TYPE.HARDWARE;
+        attr.size = @sizeOf(linux.perf_event_attr);
+        attr.config = @intFromEnum(linux.PERF.COUNT.HW.INSTRUCTIONS);
+        attr.flags.disabled = true;
+        attr.flags.exclude_kernel = true;
+        attr.flags.exclude_hv = true;
+
+        // 容器或 perf_event_paranoid 较高时会失败，退化为只计时
+        const rc = linux.perf_event_open(&attr, 0, -1, -1, 0);
+        if (linux.E.init(rc) != .SUCCESS) return null;
+        const fd: std.posix.fd_t = @intCast(rc);
+        _ = linux.ioctl(fd, linux.PERF.EVENT_IOC.RESET, 0);
+        _ = linux.ioctl(fd, linux.PERF.EVENT_IOC.ENABLE, 0);
+        return InstructionCounter{ .fd = fd };
+    }
+
+    fn read(self: *InstructionCounter) u64 {
+        var count: u64 = 0;
+        _ = std.posix.read(self.fd, std.mem.asBytes(&count)) catch return 0;
+        return count;
+    }
+
+    fn close(counter: *?InstructionCounter) void {
+        if (counter.*) |c| std.posix.close(c.fd);
+        counter.* = null;
+    }
+};
+
+fn median(samples: []u64) u64 {
+    if (samples.len == 0) return 0;
+    std.mem.sort(u64, samples, {}, std.sort.asc(u64));
+    return samples[samples.len / 2];
+}
+
+fn printMeasurement(m: Measurement) void {
+    const ms = @as(f64, @floatFromInt(m.ns)) / 1_000_000.0;
+    if (m.instructions) |count| {
+        std.debug.print("{s:<28} {s:<10} {d:>10.2}ms {d:>16}\n", .{ m.bench, m.phase, ms, count });
+    } else {
+        std.debug.print("{s:<28} {s:<10} {d:>10.2}ms {s:>16}\n", .{ m.bench, m.phase, ms, "n/a" });
+    }
+}
+
+// ============================================================================
+// 基线
+// ============================================================================
+
+/// 保存为 JSON 数组：[{"bench":..,"phase":..,"ns":..,"instructions":..}, ...]
+fn saveResults(allocator: std.mem.Allocator, path: []const u8, results: []const Measurement) !void {
+    var buf = std.ArrayList(u8){};
+    defer buf.deinit(allocator);
+
+    try buf.appendSlice(allocator, "[\n");
+    for (results, 0..) |m, i| {
+        try buf.writer(allocator).print("  {{\"bench\":\"{s}\",\"phase\":\"{s}\",\"ns\":{d},\"instructions\":", .{ m.bench, m.phase, m.ns });
+        if (m.instructions) |count| {
+            try buf.writer(allocator).print("{d}", .{count});
+        } else {
+            try buf.appendSlice(allocator, "null");
+        }
+        try buf.appendSlice(allocator, if (i + 1 < results.len) "},\n" else "}\n");
+    }
+    try buf.appendSlice(allocator, "]\n");
+
+    try std.fs.cwd().writeFile(.{ .sub_path = path, .data = buf.items });
+}
+
+/// 与基线逐项比较。两边都有指令数时比较指令数，否则比较耗时
+fn compareWithBaseline(
+    allocator: std.mem.Allocator,
+    path: []const u8,
+    results: []const Measurement,
+    threshold_percent: f64,
+) !bool {
+    const data = std.fs.cwd().readFileAlloc(allocator, path, 16 * 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read baseline {s}: {any}\n", .{ path, err });
+        return err;
+    };
+    const parsed = std.json.parseFromSlice([]Measurement, allocator, data, .{}) catch |err| {
+        std.debug.print("Error: Invalid baseline {s}: {any}\n", .{ path, err });
+        return err;
+    };
+    const baseline = parsed.value;
+
+    std.debug.print("\nComparing with baseline {s} (threshold {d:.1}%)\n", .{ path, threshold_percent });
+    var regressions: usize = 0;
+    for (results) |current| {
+        const base = findMeasurement(baseline, current.bench, current.phase) orelse {
+            std.debug.print("  {s}/{s}: new (no baseline)\n", .{ current.bench, current.phase });
+            continue;
+        };
+
+        const use_instructions = current.instructions != null and base.instructions != null;
+        const before: u64 = if (use_instructions) base.instructions.? else base.ns;
+        const after: u64 = if (use_instructions) current.instructions.? else current.ns;
+        if (before == 0) continue;
+
+        const change = (@as(f64, @floatFromInt(after)) - @as(f64, @floatFromInt(before))) /
+            @as(f64, @floatFromInt(before)) * 100.0;
+        const metric = if (use_instructions) "instructions" else "time";
+        if (change > threshold_percent) {
+            regressions += 1;
+            std.debug.print("  \x1b[1;31m✗\x1b[0m {s}/{s}: {s} +{d:.1}% ({d} → {d})\n", .{ current.bench, current.phase, metric, change, before, after });
+        } else {
+            std.debug.print("  ✓ {s}/{s}: {s} {d:.1}%\n", .{ current.bench, current.phase, metric, change });
+        }
+    }
+
+    if (regressions > 0) {
+        std.debug.print("\n\x1b[1;31merror\x1b[0m: {d} phase(s) regressed by more than {d:.1}%\n", .{ regressions, threshold_percent });
+        return false;
+    }
+    std.debug.print("\n✅ No performance regressions\n", .{});
+    return true;
+}
+
+fn findMeasurement(measurements: []const Measurement, bench: []const u8, phase: []const u8) ?Measurement {
+    for (measurements) |m| {
+        if (std.mem.eql(u8, m.bench, bench) and std.mem.eql(u8, m.phase, phase)) return m;
+    }
+    return null;
+}
+
+// ============================================================================
+// 语料生成
+// ============================================================================
+
+/// 长调用链中的函数个数
+const FUNCTION_COUNT = 2000;
+/// 泛型函数/结构体的组数（每组在 main 中以多种类型实例化）
+const GENERIC_COUNT = 200;
+/// 深表达式函数的个数、线性嵌套深度和平衡树高度
+const DEEP_FUNCTION_COUNT = 100;
+const DEEP_NESTING = 48;
+const DEEP_TREE_HEIGHT = 6;
+
+const HEADER =
+    \\// 基准语料（由 pawc self-bench --generate 生成，请勿手工修改）
+    \\
+;
+
+/// 在 dir_path 中（重新）生成基准语料
+pub fn generateCorpus(allocator: std.mem.Allocator, dir_path: []const u8) !void {
+    try std.fs.cwd().makePath(dir_path);
+    var dir = try std.fs.cwd().openDir(dir_path, .{});
+    defer dir.close();
+
+    const generators = [_]struct { name: []const u8, generate: *const fn (std.mem.Allocator, *std.ArrayList(u8)) anyerror!void }{
+        .{ .name = "many_functions.paw", .generate = generateManyFunctions },
+        .{ .name = "generics.paw", .generate = generateGenerics },
+        .{ .name = "deep_expressions.paw", .generate = generateDeepExpressions },
+    };
+    for (generators) |generator| {
+        var buf = std.ArrayList(u8){};
+        defer buf.deinit(allocator);
+        try buf.appendSlice(allocator, HEADER);
+        try generator.generate(allocator, &buf);
+        try dir.writeFile(.{ .sub_path = generator.name, .data = buf.items });
+        std.debug.print("📝 Generated {s}/{s} ({d} bytes)\n", .{ dir_path, generator.name, buf.items.len });
+    }
+}
+
+/// 大量函数：每个函数调用前一个，形成一条长调用链
+fn generateManyFunctions(allocator: std.mem.Allocator, buf: *std.ArrayList(u8)) anyerror!void {
+    const w = buf.writer(allocator);
+    try w.print("// {d} 个函数，每个调用前一个\n\n", .{FUNCTION_COUNT});
+    try w.writeAll(
+        \\fn f0(x: i32) -> i32 {
+        \\    return x + 1;
+        \\}
+        \\
+        \\
+    );
+    for (1..FUNCTION_COUNT) |i| {
+        try w.print(
+            \\fn f{d}(x: i32) -> i32 {{
+            \\    let a = f{d}(x);
+            \\    let b = a * {d} - x;
+            \\    if b > 1000 {{
+            \\        return b % 1000;
+            \\    }}
+            \\    return b + {d};
+            \\}}
+            \\
+            \\
+        , .{ i, i - 1, i % 7 + 1, i % 13 });
+    }
+    try w.print(
+        \\fn main() -> i32 {{
+        \\    return f{d}(1) % 100;
+        \\}}
+        \\
+    , .{FUNCTION_COUNT - 1});
+}
+
+/// 重度泛型：每组一个泛型结构体和两个带约束的泛型函数，以 i32 / f64 / char 实例化
+fn generateGenerics(allocator: std.mem.Allocator, buf: *std.ArrayList(u8)) anyerror!void {
+    const w = buf.writer(allocator);
+    try w.print("// {d} 组泛型结构体和泛型函数，每组以多种类型实例化\n\n", .{GENERIC_COUNT});
+    for (0..GENERIC_COUNT) |i| {
+        try w.print(
+            \\type Box{d}<T> = struct {{
+            \\    value: T,
+            \\}}
+            \\
+            \\fn pick{d}<T: Ord>(a: T, b: T) -> T {{
+            \\    if a > b {{
+            \\        return a;
+            \\    }}
+            \\    return b;
+            \\}}
+            \\
+            \\fn sum{d}<T>(a: T, b: T, c: T) -> T where T: Num {{
+            \\    return a + b + c;
+            \\}}
+            \\
+            \\
+        , .{ i, i, i });
+    }
+    try w.writeAll(
+        \\fn main() -> i32 {
+        \\    let mut total: i32 = 0;
+        \\
+    );
+    for (0..GENERIC_COUNT) |i| {
+        try w.print(
+            \\    let b{d} = Box{d} {{ value: sum{d}({d}, 1, 2) }};
+            \\    let f{d} = pick{d}(1.5, sum{d}(0.5, 0.25, {d}.0));
+            \\    let c{d} = pick{d}('a', 'z');
+            \\    total = total + pick{d}(b{d}.value, {d});
+            \\
+        , .{ i, i, i, i, i, i, i, i, i, i, i, i, i % 10 });
+    }
+    try w.writeAll(
+        \\    return total % 100;
+        \\}
+        \\
+    );
+}
+
+const OPERATORS = [_][]const u8{ "+", "-", "*" };
+
+/// 深表达式：每个函数一个深度线性嵌套的表达式和一个平衡的二叉表达式树
+fn generateDeepExpressions(allocator: std.mem.Allocator, buf: *std.ArrayList(u8)) anyerror!void {
+    const w = buf.writer(allocator);
+    try w.print("// {d} 个函数，每个包含嵌套 {d} 层的表达式和高度 {d} 的表达式树\n\n", .{ DEEP_FUNCTION_COUNT, DEEP_NESTING, DEEP_TREE_HEIGHT });
+    for (0..DEEP_FUNCTION_COUNT) |i| {
+        try w.print("fn e{d}(x: i32, y: i32) -> i32 {{\n    let t = ", .{i});
+        try writeTree(w, DEEP_TREE_HEIGHT, 1);
+        try w.writeAll(";\n    return ");
+        try writeNested(w, i, DEEP_NESTING);
+        try w.writeAll(" + t;\n}\n\n");
+    }
+    try w.writeAll(
+        \\fn main() -> i32 {
+        \\    let mut total: i32 = 0;
+        \\
+    );
+    for (0..DEEP_FUNCTION_COUNT) |i| {
+        try w.print("    total = total + e{d}({d}, 3);\n", .{ i, i });
+    }
+    try w.writeAll(
+        \\    return total % 100;
+        \\}
+        \\
+    );
+}
+
+/// (((x op k) op k) op y) ...，嵌套 depth 层
+fn writeNested(w: anytype, function_index: usize, depth: usize) !void {
+    if (depth == 0) return w.writeAll("x");
+    try w.writeAll("(");
+    try writeNested(w, function_index, depth - 1);
+    try w.print(" {s} ", .{OPERATORS[(function_index + depth) % OPERATORS.len]});
+    if (depth % 5 == 0) {
+        try w.writeAll("y");
+    } else {
+        try w.print("{d}", .{depth % 9 + 1});
+    }
+    try w.writeAll(")");
+}
+
+/// 高度为 height 的平衡二叉表达式树，叶子交替为 x 和 y
+fn writeTree(w: anytype, height: usize, index: usize) !void {
+    if (height == 0) return w.writeAll(if (index % 2 == 0) "x" else "y");
+    try w.writeAll("(");
+    try writeTree(w, height - 1, index * 2);
+    try w.print(" {s} ", .{OPERATORS[height % OPERATORS.len]});
+    try writeTree(w, height - 1, index * 2 + 1);
+    try w.writeAll(")");
+}
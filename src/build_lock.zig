@@ -0,0 +1,75 @@
+//! BuildLock - 并发构建的目录锁与原子输出
+//!
+//! 同一项目中同时运行的两个构建（或 pawc dev / 编辑器的后台构建）会争用
+//! target/cache 中的目标文件、缓存键和最终的可执行文件：
+//!
+//! 1. acquire() 对 target/.pawc-lock 加排他锁（类似 cargo 的构建目录锁），
+//!    另一个构建持有锁时等待它完成；进程退出时锁由操作系统自动释放
+//! 2. 所有输出（.c、.o、缓存键、可执行文件）先写到同一目录下唯一的临时文件，
+//!    完成后 rename 到最终路径：读者要么看到旧文件，要么看到完整的新文件
+//!
+//! 运行生成的程序之前调用 release()，程序运行期间不阻塞其他构建。
+
+const std = @import("std");
+
+const TARGET_DIR = "target";
+pub const LOCK_FILE = TARGET_DIR ++ "/.pawc-lock";
+
+/// 当前进程持有的锁
+var held: ?std.fs.File = null;
+
+/// 加构建目录锁（已持有时直接返回）
+pub fn acquire() !void {
+    if (held != null) return;
+    try std.fs.cwd().makePath(TARGET_DIR);
+    const file = try std.fs.cwd().createFile(LOCK_FILE, .{ .truncate = false });
+    errdefer file.close();
+    if (!try file.tryLock(.exclusive)) {
+        std.debug.print("⏳ Blocking waiting for file lock on {s} (another pawc build is running)\n", .{LOCK_FILE});
+        try file.lock(.exclusive);
+    }
+    held = file;
+}
+
+/// 释放构建目录锁（未持有时什么也不做）
+pub fn release() void {
+    const file = held orelse return;
+    file.unlock();
+    file.close();
+    held = null;
+}
+
+/// 与 final_path 同一目录下的唯一临时路径（调用者释放）：
+///   target/cache/main.o  ->  target/cache/.main.<random>.tmp.o
+/// 保留扩展名：C 编译器按扩展名判断输入文件的语言
+pub fn tempPath(allocator: std.mem.Allocator, final_path: []const u8) ![]u8 {
+    const name = std.fs.path.basename(final_path);
+    const ext = std.fs.path.extension(name);
+    const stem = name[0 .. name.len - ext.len];
+    const nonce = std.crypto.random.int(u64);
+    if (std.fs.path.dirname(final_path)) |dir| {
+        return std.fmt.allocPrint(allocator, "{s}/.{s}.{x:0>16}.tmp{s}", .{ dir, stem, nonce, ext });
+    }
+    return std.fmt.allocPrint(allocator, ".{s}.{x:0>16}.tmp{s}", .{ stem, nonce, ext });
+}
+
+/// 把临时文件移动到最终路径（同一目录内 rename 是原子的）；失败时删除临时文件
+pub fn commit(tmp_path: []const u8, final_path: []const u8) !void {
+    std.fs.cwd().rename(tmp_path, final_path) catch |err| {
+        std.fs.cwd().deleteFile(tmp_path) catch {};
+        return err;
+    };
+}
+
+/// 原子地写入文件：parts 依次写入临时文件，再 rename 到 path
+pub fn writeFileAtomic(allocator: std.mem.Allocator, path: []const u8, parts: []const []const u8) !void {
+    const tmp_path = try tempPath(allocator, path);
+    defer allocator.free(tmp_path);
+    {
+        errdefer std.fs.cwd().deleteFile(tmp_path) catch {};
+        const file = try std.fs.cwd().createFile(tmp_path, .{});
+        defer file.close();
+        for (parts) |part| try file.writeAll(part);
+    }
+    try commit(tmp_path, path);
+}
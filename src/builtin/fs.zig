@@ -3,6 +3,7 @@
 // 提供跨平台的文件 I/O 操作
 
 const std = @import("std");
+const string = @import("string.zig");
 
 // 全局分配器（用于文件内容缓冲区）
 var gpa = std.heap.GeneralPurposeAllocator(.{}){};
@@ -12,43 +13,53 @@ const allocator = gpa.allocator();
 // 文件读取
 // ============================================================================
 
-/// 读取文件内容
+/// 读取文件内容（作为 string）
 /// @param path_ptr 文件路径的指针
 /// @param path_len 路径长度
-/// @return 文件内容的指针（i64 编码），失败返回 0
+/// @return 文件内容的指针（i64 编码），失败或内容不是合法的 UTF-8 时返回 0
 export fn paw_read_file(path_ptr: [*]const u8, path_len: usize) i64 {
-    // 转换路径为 slice
-    const path_slice = path_ptr[0..path_len];
+    const buffer = readWholeFile(path_ptr[0..path_len]) orelse return 0;
+    
+    // 🆕 string 总是 UTF-8：非法内容不能作为 string 返回
+    if (!string.isValidUtf8(buffer)) {
+        allocator.free(buffer);
+        return 0;
+    }
     
+    // 返回缓冲区指针（编码为 i64）
+    return @intCast(@intFromPtr(buffer.ptr));
+}
+
+/// 读取整个文件，失败返回 null
+fn readWholeFile(path_slice: []const u8) ?[]u8 {
     // 打开文件
     const file = std.fs.cwd().openFile(path_slice, .{}) catch {
-        return 0; // 文件不存在或无法打开
+        return null; // 文件不存在或无法打开
     };
     defer file.close();
     
     // 读取文件大小
     const file_size = file.getEndPos() catch {
-        return 0;
+        return null;
     };
     
     // 分配缓冲区
     const buffer = allocator.alloc(u8, file_size) catch {
-        return 0;
+        return null;
     };
     
     // 读取内容
     const bytes_read = file.readAll(buffer) catch {
         allocator.free(buffer);
-        return 0;
+        return null;
     };
     
     if (bytes_read != file_size) {
         allocator.free(buffer);
-        return 0;
+        return null;
     }
     
-    // 返回缓冲区指针（编码为 i64）
-    return @intCast(@intFromPtr(buffer.ptr));
+    return buffer;
 }
 
 /// 获取读取的文件大小
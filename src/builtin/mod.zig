@@ -4,7 +4,9 @@
 // 此模块导出所有内置函数，包括：
 // - 内存管理 (memory.zig)
 // - 文件系统 (fs.zig)
+// - 字符串 / UTF-8 (string.zig)
 
 pub const memory = @import("memory.zig");
 pub const fs = @import("fs.zig");
+pub const string = @import("string.zig");
 
@@ -0,0 +1,24 @@
+//! Built-in String Functions for PawLang
+//!
+//! PawLang 的 string 总是合法的 UTF-8：
+//!   - 源码中的字符串字面量由解析器检查
+//!   - 从外部读入的数据（文件等）在变成 string 之前由这里的函数检查
+//! 不保证是 UTF-8 的原始数据使用 Bytes（[u8]）。
+
+const std = @import("std");
+
+// ============================================================================
+// UTF-8 检查 - C ABI 导出
+// ============================================================================
+
+/// 检查一段字节是否是合法的 UTF-8
+/// 参数: ptr, len - 字节的指针和长度
+/// 返回: 合法返回 1，否则返回 0
+export fn paw_str_is_valid_utf8(ptr: [*]const u8, len: usize) i32 {
+    return @intFromBool(isValidUtf8(ptr[0..len]));
+}
+
+/// 供其他内置函数使用（如 paw_read_file）
+pub fn isValidUtf8(bytes: []const u8) bool {
+    return std.unicode.utf8ValidateSlice(bytes);
+}
@@ -1,114 +1,232 @@
 const std = @import("std");
+const builtin = @import("builtin");
 const ast = @import("ast.zig");
 const CodeGen = @import("codegen.zig").CodeGen;
+const build_lock = @import("build_lock.zig");
+const toolchain = @import("toolchain.zig");
 
 /// C Backend - Compiles and executes C code using GCC
 /// Generates portable C code that can be compiled with any C compiler
 pub const CBackend = struct {
     allocator: std.mem.Allocator,
+    /// 🆕 --stack-size: main thread stack size in bytes (passed to the linker)
+    stack_size: ?u64,
+    /// 🆕 Directory for per-unit .c/.o files (null: next to the output)
+    object_dir: ?[]const u8,
+    /// 🆕 -O: compile units with optimization (enables cross-module inlining)
+    optimize: bool,
+    /// 🆕 C compiler already selected by the driver (--linker or auto-detected up front)
+    linker: ?toolchain.Linker,
     
     pub fn init(allocator: std.mem.Allocator) CBackend {
         return CBackend{
             .allocator = allocator,
+            .stack_size = null,
+            .object_dir = null,
+            .optimize = false,
+            .linker = null,
         };
     }
     
     /// Compile C code to executable using GCC
     /// Falls back to clang if GCC is not available
+    ///
+    /// 🆕 The C file and the executable are first written under unique temporary
+    /// names and renamed into place, so concurrent builds never see half-written output
     pub fn compile(
         self: *CBackend,
         c_code: []const u8,
         output_file: []const u8,
     ) !void {
-        // 1. Write C code to temporary file
-        const temp_c_file = try std.fmt.allocPrint(
+        // 1. Write C code to a temporary file
+        const c_path = try std.fmt.allocPrint(
             self.allocator,
             "{s}.c",
             .{output_file},
         );
+        defer self.allocator.free(c_path);
+        const temp_c_file = try build_lock.tempPath(self.allocator, c_path);
         defer self.allocator.free(temp_c_file);
+        errdefer std.fs.cwd().deleteFile(temp_c_file) catch {};
+        {
+            const c_file = try std.fs.cwd().createFile(temp_c_file, .{});
+            defer c_file.close();
+            try c_file.writeAll(c_code);
+        }
         
-        const c_file = try std.fs.cwd().createFile(temp_c_file, .{});
-        defer c_file.close();
-        _ = try c_file.write(c_code);
+        // 2. Compile with GCC (or clang as fallback) to a temporary executable
+        const temp_output = try build_lock.tempPath(self.allocator, output_file);
+        defer self.allocator.free(temp_output);
+        errdefer std.fs.cwd().deleteFile(temp_output) catch {};
+        const compiler = try self.compileWithGcc(temp_c_file, temp_output);
         
-        // 2. Compile with GCC (or clang as fallback)
-        try self.compileWithGcc(temp_c_file, output_file);
+        // 3. Move both into place
+        try build_lock.commit(temp_output, output_file);
+        try build_lock.commit(temp_c_file, c_path);
+        std.debug.print("✅ Compilation successful (using {s}): {s}\n", .{ compiler, output_file });
+    }
+    
+    /// Find an available C compiler (Zig CC -> GCC -> Clang -> cc)
+    /// Returns the argv prefix used to invoke it
+    fn findCompiler(self: *CBackend) ![]const []const u8 {
+        const linker = self.linker orelse
+            (try toolchain.select(self.allocator, null)) orelse
+            return error.NoCompilerFound;
+        switch (linker) {
+            .zig => std.debug.print("🚀 Compiling with Zig CC (Clang 20.1.2, best performance)...\n", .{}),
+            else => std.debug.print("🔧 Compiling with {s}...\n", .{linker.displayName()}),
+        }
+        return linker.argv();
     }
     
-           /// Compile using system C compiler (Zig CC -> GCC -> Clang)
-           fn compileWithGcc(
-               self: *CBackend,
-               c_file: []const u8,
-               output_file: []const u8,
-           ) !void {
-               // Try to find available C compiler
-               // Priority: Zig CC (if building from source) -> GCC -> Clang
-               var compiler: []const u8 = "gcc";
-               var use_zig_cc = false;
-               
-               // 1. Try Zig CC first (for users who build from source)
-               if (std.process.Child.run(.{
-                   .allocator = self.allocator,
-                   .argv = &[_][]const u8{ "zig", "cc", "--version" },
-               })) |zig_result| {
-                   self.allocator.free(zig_result.stdout);
-                   self.allocator.free(zig_result.stderr);
-                   compiler = "zig";
-                   use_zig_cc = true;
-                   std.debug.print("🚀 Compiling with Zig CC (Clang 20.1.2, best performance)...\n", .{});
-               } else |_| {
-                   // 2. Try gcc
-                   if (std.process.Child.run(.{
-                       .allocator = self.allocator,
-                       .argv = &[_][]const u8{ "gcc", "--version" },
-                   })) |gcc_result| {
-                       self.allocator.free(gcc_result.stdout);
-                       self.allocator.free(gcc_result.stderr);
-                       compiler = "gcc";
-                       std.debug.print("🔧 Compiling with GCC...\n", .{});
-                   } else |_| {
-                       // 3. Try clang as fallback
-                       if (std.process.Child.run(.{
-                           .allocator = self.allocator,
-                           .argv = &[_][]const u8{ "clang", "--version" },
-                       })) |clang_result| {
-                           self.allocator.free(clang_result.stdout);
-                           self.allocator.free(clang_result.stderr);
-                           compiler = "clang";
-                           std.debug.print("🔧 Compiling with Clang...\n", .{});
-                       } else |_| {
-                           std.debug.print("❌ No C compiler found (zig/gcc/clang)\n", .{});
-                           std.debug.print("💡 Please install a C compiler:\n", .{});
-                           std.debug.print("   • Zig (recommended): Already available if you built from source\n", .{});
-                           std.debug.print("   • Linux:   sudo apt-get install gcc\n", .{});
-                           std.debug.print("   • macOS:   brew install gcc or xcode-select --install\n", .{});
-                           std.debug.print("   • Windows: Install MinGW or MSVC\n", .{});
-                           return error.NoCompilerFound;
-                       }
-                   }
-               }
-               
-               // Build command line arguments
-               const argv = if (use_zig_cc) &[_][]const u8{
-                   "zig", "cc", "-o", output_file, c_file,
-               } else &[_][]const u8{
-                   compiler, "-o", output_file, c_file,
-               };
+    /// Run the C compiler with the given arguments appended to the compiler prefix
+    fn runCompiler(
+        self: *CBackend,
+        compiler: []const []const u8,
+        args: []const []const u8,
+    ) !void {
+        var argv = std.ArrayList([]const u8){};
+        defer argv.deinit(self.allocator);
+        try argv.appendSlice(self.allocator, compiler);
+        try argv.appendSlice(self.allocator, args);
         
         const compile_result = try std.process.Child.run(.{
             .allocator = self.allocator,
-            .argv = argv,
+            .argv = argv.items,
         });
         defer self.allocator.free(compile_result.stdout);
         defer self.allocator.free(compile_result.stderr);
         
         if (compile_result.term.Exited != 0) {
-            std.debug.print("❌ {s} compilation failed:\n{s}\n", .{ compiler, compile_result.stderr });
+            std.debug.print("❌ {s} compilation failed:\n{s}\n", .{ compiler[0], compile_result.stderr });
             return error.CompilationFailed;
         }
+    }
+    
+    /// Compile using system C compiler (Zig CC -> GCC -> Clang)
+    /// Returns the name of the compiler that was used
+    fn compileWithGcc(
+        self: *CBackend,
+        c_file: []const u8,
+        output_file: []const u8,
+    ) ![]const u8 {
+        const compiler = try self.findCompiler();
         
-        std.debug.print("✅ Compilation successful (using {s}): {s}\n", .{ compiler, output_file });
+        var args = std.ArrayList([]const u8){};
+        defer args.deinit(self.allocator);
+        try args.appendSlice(self.allocator, &[_][]const u8{ "-o", output_file, c_file });
+        try appendStackProbeFlags(self.allocator, &args);
+        var stack_flag_buf: [64]u8 = undefined;
+        try appendStackSizeFlag(self.allocator, &args, self.stack_size, &stack_flag_buf);
+        try self.runCompiler(compiler, args.items);
+        return compiler[0];
+    }
+    
+    /// 🆕 A separately compiled C translation unit (one per Paw module)
+    pub const Unit = struct {
+        name: []const u8,
+        code: []const u8,
+        /// 🆕 Up to date in the build cache: link the existing object as is
+        cached: bool = false,
+    };
+    
+    /// 🆕 Compile each unit to its own object file, then link them together
+    ///
+    /// Objects are kept in `object_dir` (or next to the output): when a unit's
+    /// C source is unchanged since the last build and its object exists, it is reused.
+    /// 🆕 Each object is compiled under a temporary name and renamed into place before
+    /// its C source, so a failed compile never leaves a new source next to an old object.
+    pub fn compileUnits(
+        self: *CBackend,
+        units: []const Unit,
+        output_file: []const u8,
+    ) !void {
+        const compiler = try self.findCompiler();
+        
+        var obj_files = std.ArrayList([]const u8){};
+        defer {
+            for (obj_files.items) |obj| self.allocator.free(obj);
+            obj_files.deinit(self.allocator);
+        }
+        
+        if (self.object_dir) |dir| try std.fs.cwd().makePath(dir);
+        
+        for (units) |unit| {
+            const c_path = try self.unitPath(output_file, unit.name, "c");
+            defer self.allocator.free(c_path);
+            const obj_path = try self.unitPath(output_file, unit.name, "o");
+            try obj_files.append(self.allocator, obj_path);
+            
+            if (unit.cached) {
+                std.debug.print("♻️  Up to date: {s}\n", .{obj_path});
+                continue;
+            }
+            const banner = self.unitBanner();
+            if (self.isUnitCached(c_path, obj_path, banner, unit.code)) {
+                std.debug.print("♻️  Reusing object: {s}\n", .{obj_path});
+                continue;
+            }
+            
+            const temp_c_path = try build_lock.tempPath(self.allocator, c_path);
+            defer self.allocator.free(temp_c_path);
+            errdefer std.fs.cwd().deleteFile(temp_c_path) catch {};
+            {
+                const c_file = try std.fs.cwd().createFile(temp_c_path, .{});
+                defer c_file.close();
+                try c_file.writeAll(banner);
+                try c_file.writeAll(unit.code);
+            }
+            const temp_obj_path = try build_lock.tempPath(self.allocator, obj_path);
+            defer self.allocator.free(temp_obj_path);
+            errdefer std.fs.cwd().deleteFile(temp_obj_path) catch {};
+            
+            var compile_args = std.ArrayList([]const u8){};
+            defer compile_args.deinit(self.allocator);
+            try compile_args.appendSlice(self.allocator, &[_][]const u8{ "-c", "-o", temp_obj_path, temp_c_path });
+            if (self.optimize) try compile_args.append(self.allocator, "-O2");
+            try appendStackProbeFlags(self.allocator, &compile_args);
+            try self.runCompiler(compiler, compile_args.items);
+            try build_lock.commit(temp_obj_path, obj_path);
+            try build_lock.commit(temp_c_path, c_path);
+        }
+        
+        // Link all objects (🆕 into a temporary executable, then rename it into place)
+        const temp_output = try build_lock.tempPath(self.allocator, output_file);
+        defer self.allocator.free(temp_output);
+        errdefer std.fs.cwd().deleteFile(temp_output) catch {};
+        var link_args = std.ArrayList([]const u8){};
+        defer link_args.deinit(self.allocator);
+        try link_args.append(self.allocator, "-o");
+        try link_args.append(self.allocator, temp_output);
+        try link_args.appendSlice(self.allocator, obj_files.items);
+        var stack_flag_buf: [64]u8 = undefined;
+        try appendStackSizeFlag(self.allocator, &link_args, self.stack_size, &stack_flag_buf);
+        try self.runCompiler(compiler, link_args.items);
+        try build_lock.commit(temp_output, output_file);
+        
+        std.debug.print("✅ Linked {d} object(s) (using {s}): {s}\n", .{ obj_files.items.len, compiler[0], output_file });
+    }
+    
+    /// Path of a unit's .c/.o file
+    fn unitPath(self: *CBackend, output_file: []const u8, unit_name: []const u8, ext: []const u8) ![]const u8 {
+        if (self.object_dir) |dir| {
+            return std.fmt.allocPrint(self.allocator, "{s}/{s}.{s}", .{ dir, unit_name, ext });
+        }
+        return std.fmt.allocPrint(self.allocator, "{s}_{s}.{s}", .{ output_file, unit_name, ext });
+    }
+    
+    /// Check whether a unit's previous C source matches and its object exists
+    fn isUnitCached(self: *CBackend, c_path: []const u8, obj_path: []const u8, banner: []const u8, code: []const u8) bool {
+        std.fs.cwd().access(obj_path, .{}) catch return false;
+        const previous = std.fs.cwd().readFileAlloc(self.allocator, c_path, 10 * 1024 * 1024) catch return false;
+        defer self.allocator.free(previous);
+        return std.mem.startsWith(u8, previous, banner) and std.mem.eql(u8, previous[banner.len..], code);
+    }
+    
+    /// 🆕 First line of each unit's .c file: records the optimization level,
+    /// so switching -O on or off does not reuse objects built the other way
+    fn unitBanner(self: *const CBackend) []const u8 {
+        return if (self.optimize) "/* pawc -O */\n" else "";
     }
     
     /// Compile and run (for REPL or quick testing)
@@ -116,8 +234,11 @@ pub const CBackend = struct {
         self: *CBackend,
         c_code: []const u8,
     ) !void {
-        const temp_output = "temp_paw_output";
+        // 🆕 Unique name: concurrent `--run` builds do not overwrite each other's program
+        const temp_output = try build_lock.tempPath(self.allocator, "temp_paw_output");
+        defer self.allocator.free(temp_output);
         try self.compile(c_code, temp_output);
+        build_lock.release();  // the program may run for a long time
         
         // Run program
         std.debug.print("\n🚀 Running program:\n", .{});
@@ -152,3 +273,32 @@ pub const CBackend = struct {
         std.fs.cwd().deleteFile(temp_c_output) catch {};
     }
 };
+
+/// 🆕 Compiler flags for stack probes
+///
+/// With probes every large frame touches the guard page before moving past it,
+/// so an overflow always faults there and is reported by the runtime handler.
+/// Windows already probes with __chkstk; macOS clang does not support the flag.
+pub fn appendStackProbeFlags(allocator: std.mem.Allocator, args: *std.ArrayList([]const u8)) !void {
+    if (builtin.os.tag == .linux) try args.append(allocator, "-fstack-clash-protection");
+}
+
+/// 🆕 Linker flag for --stack-size (formatted into `buf`)
+///
+/// On Linux this only sets PT_GNU_STACK's size; the generated runtime raises
+/// RLIMIT_STACK at startup so the main thread gets the requested size too.
+pub fn appendStackSizeFlag(
+    allocator: std.mem.Allocator,
+    args: *std.ArrayList([]const u8),
+    stack_size: ?u64,
+    buf: []u8,
+) !void {
+    const size = stack_size orelse return;
+    const flag = switch (builtin.os.tag) {
+        .windows => try std.fmt.bufPrint(buf, "-Wl,--stack,{d}", .{size}),
+        // ld64 requires a multiple of the page size (16 KiB on Apple silicon)
+        .macos => try std.fmt.bufPrint(buf, "-Wl,-stack_size,0x{x}", .{std.mem.alignForward(u64, size, 0x4000)}),
+        else => try std.fmt.bufPrint(buf, "-Wl,-z,stack-size={d}", .{size}),
+    };
+    try args.append(allocator, flag);
+}
@@ -0,0 +1,209 @@
+//! Cache - 增量编译缓存（--split-modules，目标文件位于 target/cache）
+//!
+//! 每个编译单元的键是 SHA-256(编译器 + 命令行选项 + Paw.toml + 单元依赖的源文件)：
+//!   主单元      入口文件 + 所有导入模块（泛型实例需要模块的完整源码）
+//!   模块单元    模块自身 + 入口文件（入口文件的声明在每个单元中生成原型和常量）
+//! 键与 target/cache/<unit>.key 相同且 <unit>.o 存在时，跳过该单元的代码生成和 C 编译；
+//! 所有单元都未变化时连类型检查也跳过，只重新链接。--no-cache 关闭缓存。
+
+const std = @import("std");
+const Sha256 = std.crypto.hash.sha2.Sha256;
+const package = @import("package.zig");
+const ModuleLoader = @import("module.zig").ModuleLoader;
+const build_lock = @import("build_lock.zig");
+
+pub const CACHE_DIR = "target/cache";
+
+/// 单元键（十六进制 SHA-256）
+pub const Key = [Sha256.digest_length * 2]u8;
+
+/// 单元的一个输入（路径参与哈希，重命名文件也会使缓存失效）
+pub const Input = struct {
+    path: []const u8,
+    source: []const u8,
+};
+
+pub const BuildCache = struct {
+    allocator: std.mem.Allocator,
+    /// 编译器、选项和 Paw.toml 的指纹：任何一项变化都使所有单元失效
+    fingerprint: [Sha256.digest_length]u8,
+
+    pub fn init(allocator: std.mem.Allocator, options: []const [:0]const u8) !BuildCache {
+        var hasher = Sha256.init(.{});
+
+        // 编译器本身：以可执行文件的大小和修改时间代表（重新构建 pawc 即失效）
+        const self_exe = try std.fs.selfExePathAlloc(allocator);
+        defer allocator.free(self_exe);
+        if (std.fs.cwd().statFile(self_exe)) |stat| {
+            hasher.update(std.mem.asBytes(&stat.size));
+            hasher.update(std.mem.asBytes(&stat.mtime));
+        } else |_| {
+            hasher.update(self_exe);
+        }
+
+        for (options) |option| {
+            hasher.update(option);
+            hasher.update(&[_]u8{0});
+        }
+
+        // [limits] 等项目设置也会影响生成的代码
+        if (std.fs.cwd().readFileAlloc(allocator, package.MANIFEST_FILE, 1024 * 1024)) |manifest| {
+            defer allocator.free(manifest);
+            hasher.update(manifest);
+        } else |_| {}
+
+        var cache = BuildCache{ .allocator = allocator, .fingerprint = undefined };
+        hasher.final(&cache.fingerprint);
+        return cache;
+    }
+
+    /// 计算单元键（输入按路径排序，与模块的加载顺序无关）
+    pub fn unitKey(self: *const BuildCache, inputs: []Input) Key {
+        std.mem.sort(Input, inputs, {}, struct {
+            fn lessThan(_: void, a: Input, b: Input) bool {
+                return std.mem.order(u8, a.path, b.path) == .lt;
+            }
+        }.lessThan);
+
+        var hasher = Sha256.init(.{});
+        hasher.update(&self.fingerprint);
+        for (inputs) |input| {
+            hasher.update(input.path);
+            hasher.update(&[_]u8{0});
+            hasher.update(input.source);
+            hasher.update(&[_]u8{0});
+        }
+        var digest: [Sha256.digest_length]u8 = undefined;
+        hasher.final(&digest);
+        return std.fmt.bytesToHex(digest, .lower);
+    }
+
+    /// 单元的键与上次构建相同且目标文件存在
+    pub fn isFresh(self: *const BuildCache, unit_name: []const u8, key: Key) bool {
+        const obj_path = self.path(unit_name, "o") catch return false;
+        defer self.allocator.free(obj_path);
+        std.fs.cwd().access(obj_path, .{}) catch return false;
+
+        const key_path = self.path(unit_name, "key") catch return false;
+        defer self.allocator.free(key_path);
+        var buf: [Key.len]u8 = undefined;
+        const previous = std.fs.cwd().readFile(key_path, &buf) catch return false;
+        return std.mem.eql(u8, previous, &key);
+    }
+
+    /// 单元编译成功后记录键（🆕 原子写入：并发构建不会读到写了一半的键）
+    pub fn store(self: *const BuildCache, unit_name: []const u8, key: Key) !void {
+        const key_path = try self.path(unit_name, "key");
+        defer self.allocator.free(key_path);
+        try build_lock.writeFileAtomic(self.allocator, key_path, &.{&key});
+    }
+
+    /// 重新生成单元之前删除旧键（构建中途失败时不会误用旧目标文件）
+    pub fn invalidate(self: *const BuildCache, unit_name: []const u8) void {
+        const key_path = self.path(unit_name, "key") catch return;
+        defer self.allocator.free(key_path);
+        std.fs.cwd().deleteFile(key_path) catch {};
+    }
+
+    /// target/cache/<unit>.<ext>
+    pub fn path(self: *const BuildCache, unit_name: []const u8, ext: []const u8) ![]u8 {
+        return std.fmt.allocPrint(self.allocator, "{s}/{s}.{s}", .{ CACHE_DIR, unit_name, ext });
+    }
+};
+
+/// 计划中的一个编译单元
+pub const PlannedUnit = struct {
+    name: []const u8,
+    key: Key,
+    fresh: bool,
+};
+
+/// 一次分离编译的计划（units[0] 是主单元）
+pub const Plan = struct {
+    allocator: std.mem.Allocator,
+    cache: BuildCache,
+    units: std.ArrayList(PlannedUnit),
+
+    pub fn deinit(self: *Plan) void {
+        for (self.units.items) |unit| self.allocator.free(unit.name);
+        self.units.deinit(self.allocator);
+    }
+
+    /// 所有单元都可以复用：不需要类型检查和代码生成，只需链接
+    pub fn allFresh(self: *const Plan) bool {
+        for (self.units.items) |unit| {
+            if (!unit.fresh) return false;
+        }
+        return true;
+    }
+
+    pub fn find(self: *const Plan, name: []const u8) ?PlannedUnit {
+        for (self.units.items) |unit| {
+            if (std.mem.eql(u8, unit.name, name)) return unit;
+        }
+        return null;
+    }
+
+    fn addUnit(self: *Plan, name: []u8, inputs: []Input) !void {
+        errdefer self.allocator.free(name);
+        const key = self.cache.unitKey(inputs);
+        const fresh = self.cache.isFresh(name, key);
+        // 需要重新生成的单元先删除旧键，构建失败时不会误用旧目标文件
+        if (!fresh) self.cache.invalidate(name);
+        try self.units.append(self.allocator, .{ .name = name, .key = key, .fresh = fresh });
+    }
+
+    /// 链接成功后记录重新生成的单元
+    pub fn commit(self: *const Plan) !void {
+        for (self.units.items) |unit| {
+            if (!unit.fresh) try self.cache.store(unit.name, unit.key);
+        }
+    }
+};
+
+/// 编译单元名：模块路径中的 '/' 和 '.' 替换为 '_'（调用者释放）
+pub fn unitName(allocator: std.mem.Allocator, module_path: []const u8) ![]u8 {
+    const name = try allocator.dupe(u8, module_path);
+    for (name) |*c| {
+        if (c.* == '/' or c.* == '.') c.* = '_';
+    }
+    return name;
+}
+
+/// 计算主单元和每个模块单元的键，并检查哪些单元可以复用
+pub fn plan(
+    allocator: std.mem.Allocator,
+    cache: BuildCache,
+    entry: Input,
+    loader: *ModuleLoader,
+) !Plan {
+    var result = Plan{ .allocator = allocator, .cache = cache, .units = std.ArrayList(PlannedUnit){} };
+    errdefer result.deinit();
+
+    // 主单元：入口文件 + 所有模块
+    var inputs = std.ArrayList(Input){};
+    defer inputs.deinit(allocator);
+    try inputs.append(allocator, entry);
+    var it = loader.modules.iterator();
+    while (it.next()) |module_entry| {
+        const module = module_entry.value_ptr;
+        try inputs.append(allocator, .{ .path = module.source_file, .source = module.source });
+    }
+    try result.addUnit(try allocator.dupe(u8, "main"), inputs.items);
+
+    // 模块单元：模块自身 + 入口文件 + 模块导入的模块（其中的声明生成为原型）
+    it = loader.modules.iterator();
+    while (it.next()) |module_entry| {
+        const module = module_entry.value_ptr;
+        inputs.clearRetainingCapacity();
+        try inputs.append(allocator, .{ .path = module.source_file, .source = module.source });
+        try inputs.append(allocator, entry);
+        for (module.declarations) |decl| {
+            if (decl != .import_decl) continue;
+            const imported = loader.modules.getPtr(decl.import_decl.module_path) orelse continue;
+            try inputs.append(allocator, .{ .path = imported.source_file, .source = imported.source });
+        }
+        try result.addUnit(try unitName(allocator, module.path), inputs.items);
+    }
+    return result;
+}
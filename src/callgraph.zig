@@ -0,0 +1,359 @@
+//! CallGraph - 无条件递归检测
+//!
+//! 构建"无条件调用图"：边 f → g 表示 f 的每条执行路径在返回之前都会调用 g。
+//! 图中的环意味着一旦调用环上的任何函数，程序就会无限递归（运行时栈溢出），
+//! 类型检查结束后对每个环给出警告。
+//!
+//! 节点是顶层函数（按名字）和类型的方法（Type::method）。泛型函数的所有
+//! 单态化实例共用同一个函数体，所以在声明上检测就覆盖了单态化后的调用图。
+//! 按名字调用 trait 方法（show(x)）时，只有恰好一个类型提供该方法才能确定目标。
+//!
+//! 只统计一定会执行的调用：if / is 的分支、有条件的循环体、&& / || 的右侧
+//! 都视为可能不执行；某条语句中可能有 return / break / `?` 时，其后的语句
+//! 也视为可能不执行。因此这里只会漏报，不会误报。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+
+/// 一个无条件递归的环
+pub const Cycle = struct {
+    /// 环上的函数，按调用顺序排列；最后一个调用第一个
+    functions: []const []const u8,
+    /// 环上第一个函数的位置
+    location: ?ast.SourceLocation,
+};
+
+const Node = struct {
+    location: ?ast.SourceLocation,
+    callees: std.ArrayList([]const u8),
+};
+
+/// 查找程序中所有无条件递归的环（结果分配在 arena 中）
+pub fn findUnconditionalCycles(arena: std.mem.Allocator, program: ast.Program) ![]Cycle {
+    var graph = Graph{
+        .arena = arena,
+        .nodes = std.StringArrayHashMap(Node).init(arena),
+        .method_owners = std.StringHashMap(?[]const u8).init(arena),
+        .never_functions = std.StringHashMap(void).init(arena),
+    };
+    try graph.build(program);
+    return try graph.findCycles();
+}
+
+const Graph = struct {
+    arena: std.mem.Allocator,
+    nodes: std.StringArrayHashMap(Node),
+    /// 方法名 -> 唯一提供它的类型（多个类型都有时为 null）
+    method_owners: std.StringHashMap(?[]const u8),
+    /// 声明为 `-> !` 的函数（panic、paw_exit 等），调用它们会离开当前函数
+    never_functions: std.StringHashMap(void),
+
+    // ------------------------------------------------------------------------
+    // 构建
+    // ------------------------------------------------------------------------
+
+    fn build(self: *Graph, program: ast.Program) !void {
+        // 1. 收集节点
+        for (program.declarations) |decl| {
+            switch (decl) {
+                .function => |func| {
+                    try self.addNode(func.name, func);
+                    if (func.return_type == .never) try self.never_functions.put(func.name, {});
+                },
+                // impl 块的方法在解析时已并入目标类型
+                .type_decl => |td| for (typeMethods(td)) |method| try self.addMethod(td.name, method),
+                else => {},
+            }
+        }
+
+        // 2. 收集每个函数无条件调用的函数
+        for (program.declarations) |decl| {
+            switch (decl) {
+                .function => |func| try self.scanFunction(func.name, null, func),
+                .type_decl => |td| for (typeMethods(td)) |method| {
+                    try self.scanFunction(try methodKey(self.arena, td.name, method.name), td.name, method);
+                },
+                else => {},
+            }
+        }
+    }
+
+    fn typeMethods(td: ast.TypeDecl) []ast.FunctionDecl {
+        return switch (td.kind) {
+            .struct_type => |st| st.methods,
+            .enum_type => |et| et.methods,
+            .trait_type, .alias => &[_]ast.FunctionDecl{},
+        };
+    }
+
+    fn methodKey(arena: std.mem.Allocator, type_name: []const u8, method_name: []const u8) ![]const u8 {
+        return std.fmt.allocPrint(arena, "{s}::{s}", .{ type_name, method_name });
+    }
+
+    fn addNode(self: *Graph, key: []const u8, func: ast.FunctionDecl) !void {
+        const entry = try self.nodes.getOrPut(key);
+        if (entry.found_existing) return;
+        entry.value_ptr.* = .{ .location = func.location, .callees = std.ArrayList([]const u8){} };
+    }
+
+    fn addMethod(self: *Graph, type_name: []const u8, method: ast.FunctionDecl) !void {
+        try self.addNode(try methodKey(self.arena, type_name, method.name), method);
+        const owner = try self.method_owners.getOrPut(method.name);
+        if (!owner.found_existing) {
+            owner.value_ptr.* = type_name;
+        } else if (owner.value_ptr.*) |existing| {
+            if (!std.mem.eql(u8, existing, type_name)) owner.value_ptr.* = null;
+        }
+    }
+
+    fn scanFunction(self: *Graph, key: []const u8, owner: ?[]const u8, func: ast.FunctionDecl) !void {
+        const node = self.nodes.getPtr(key) orelse return;
+        var scanner = Scanner{ .graph = self, .owner = owner, .callees = &node.callees };
+        _ = try scanner.scanStmts(func.body);
+    }
+
+    // ------------------------------------------------------------------------
+    // 找环
+    // ------------------------------------------------------------------------
+
+    fn findCycles(self: *Graph) ![]Cycle {
+        var cycles = std.ArrayList(Cycle){};
+        var reported = std.StringHashMap(void).init(self.arena);
+
+        for (self.nodes.keys()) |start| {
+            if (reported.contains(start)) continue;
+            const path = try self.pathBackTo(start) orelse continue;
+            for (path) |name| try reported.put(name, {});
+            try cycles.append(self.arena, .{
+                .functions = path,
+                .location = self.nodes.get(start).?.location,
+            });
+        }
+        return cycles.toOwnedSlice(self.arena);
+    }
+
+    /// 广度优先搜索从 start 出发回到 start 的最短路径
+    fn pathBackTo(self: *Graph, start: []const u8) !?[]const []const u8 {
+        var parent = std.StringHashMap([]const u8).init(self.arena);
+        var queue = std.ArrayList([]const u8){};
+        try queue.append(self.arena, start);
+
+        var head: usize = 0;
+        while (head < queue.items.len) : (head += 1) {
+            const current = queue.items[head];
+            for (self.nodes.get(current).?.callees.items) |callee| {
+                if (std.mem.eql(u8, callee, start)) {
+                    // 沿 parent 回溯得到 start → ... → current
+                    var path = std.ArrayList([]const u8){};
+                    var name = current;
+                    while (!std.mem.eql(u8, name, start)) : (name = parent.get(name).?) {
+                        try path.append(self.arena, name);
+                    }
+                    try path.append(self.arena, start);
+                    std.mem.reverse([]const u8, path.items);
+                    return try path.toOwnedSlice(self.arena);
+                }
+                if (parent.contains(callee)) continue;
+                try parent.put(callee, current);
+                try queue.append(self.arena, callee);
+            }
+        }
+        return null;
+    }
+};
+
+/// 扫描函数体中一定会执行的调用
+const Scanner = struct {
+    graph: *Graph,
+    owner: ?[]const u8,  // 正在扫描的方法所属的类型（self.method() 的目标）
+    callees: *std.ArrayList([]const u8),
+
+    /// 按顺序扫描语句，遇到一定会离开当前位置的语句（return / break / ...）时停止；
+    /// 返回是否停止
+    fn scanStmts(self: *Scanner, stmts: []const ast.Stmt) std.mem.Allocator.Error!bool {
+        for (stmts) |stmt| {
+            switch (stmt) {
+                .expr => |expr| try self.scanExpr(expr),
+                .let_decl => |let| if (let.init) |init| try self.scanExpr(init),
+                .assign => |assign| try self.scanExpr(assign.value),
+                .compound_assign => |ca| try self.scanExpr(ca.value),
+                .return_stmt => |ret| if (ret) |value| try self.scanExpr(value),
+                .break_stmt => |brk| if (brk.value) |value| try self.scanExpr(value),
+                .continue_stmt => {},
+                .loop_stmt => |loop| {
+                    if (loop.condition) |cond| {
+                        try self.scanExpr(cond);
+                    } else if (loop.iterator) |iter| {
+                        try self.scanExpr(iter.iterable);
+                    } else {
+                        // 无条件的 loop 至少执行一次循环体
+                        _ = try self.scanStmts(loop.body);
+                    }
+                },
+                .while_loop => |loop| try self.scanExpr(loop.condition),
+                .for_loop => |loop| {
+                    if (loop.init) |init| _ = try self.scanStmts(&[_]ast.Stmt{init.*});
+                    if (loop.condition) |cond| try self.scanExpr(cond);
+                },
+            }
+            // 之后的语句可能不执行（例如递归的基本情况 `if n == 0 { return 1; }`）
+            if (stmt.diverges() or self.stmtMayJump(stmt)) return true;
+        }
+        return false;
+    }
+
+    fn scanExpr(self: *Scanner, expr: ast.Expr) std.mem.Allocator.Error!void {
+        switch (expr) {
+            .call => |call| {
+                for (call.args) |arg| try self.scanExpr(arg);
+                // 求值参数时可能已经离开（如参数中的 `?`）
+                for (call.args) |arg| {
+                    if (self.exprMayJump(arg)) return;
+                }
+                switch (call.callee.*) {
+                    .identifier => |name| if (call.resolved_into.*) {
+                        if (ast.intoTarget(call)) |target| try self.addCall(try Graph.methodKey(self.graph.arena, target, ast.FROM_METHOD));
+                    } else {
+                        try self.addCallByName(name);
+                    },
+                    .field_access => |fa| {
+                        try self.scanExpr(fa.object.*);
+                        // 只有 self.method() 能确定接收者的类型
+                        if (fa.object.* == .identifier and std.mem.eql(u8, fa.object.identifier, "self")) {
+                            if (self.owner) |owner| try self.addCall(try Graph.methodKey(self.graph.arena, owner, fa.field));
+                        }
+                    },
+                    else => {},
+                }
+            },
+            .static_method_call => |smc| {
+                for (smc.args) |arg| try self.scanExpr(arg);
+                try self.addCall(try Graph.methodKey(self.graph.arena, smc.type_name, smc.method_name));
+            },
+            .binary => |bin| {
+                try self.scanExpr(bin.left.*);
+                // && / || 的右侧可能不执行
+                if (bin.op != .and_op and bin.op != .or_op) try self.scanExpr(bin.right.*);
+                // 用户类型上的运算符调用左操作数类型的方法
+                if (bin.resolved_operator.*) |type_name| {
+                    try self.addCall(try Graph.methodKey(self.graph.arena, type_name, bin.op.overloadMethod().?));
+                }
+            },
+            .unary => |un| try self.scanExpr(un.operand.*),
+            .field_access => |fa| try self.scanExpr(fa.object.*),
+            .array_index => |ai| {
+                try self.scanExpr(ai.array.*);
+                try self.scanExpr(ai.index.*);
+            },
+            .as_expr => |as_cast| {
+                try self.scanExpr(as_cast.value.*);
+                // From 转换调用目标类型的 from 方法
+                if (as_cast.resolved_from.* and as_cast.target_type == .named) {
+                    try self.addCall(try Graph.methodKey(self.graph.arena, as_cast.target_type.named, ast.FROM_METHOD));
+                }
+            },
+            .struct_init => |si| for (si.fields) |field| try self.scanExpr(field.value),
+            .enum_variant => |ev| for (ev.args) |arg| try self.scanExpr(arg),
+            .array_literal => |elements| for (elements) |elem| try self.scanExpr(elem),
+            .string_interp => |si| for (si.parts) |part| {
+                if (part == .expr) try self.scanExpr(part.expr);
+            },
+            .try_expr, .await_expr => |inner| try self.scanExpr(inner.*),
+            // 分支可能不执行，只有条件 / 被匹配的值一定会求值
+            .if_expr => |if_expr| try self.scanExpr(if_expr.condition.*),
+            .is_expr => |is_match| try self.scanExpr(is_match.value.*),
+            .match_expr => |match| try self.scanExpr(match.value.*),
+            .block => |stmts| _ = try self.scanStmts(stmts),
+            .loop_expr => |loop| _ = try self.scanStmts(loop.body),
+            else => {},
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // 可能离开当前位置的语句
+    // ------------------------------------------------------------------------
+
+    /// 语句中是否（在任何分支里）有 return / break / continue / `?`，或调用了不返回的函数
+    fn stmtMayJump(self: *Scanner, stmt: ast.Stmt) bool {
+        return switch (stmt) {
+            .return_stmt, .break_stmt, .continue_stmt => true,
+            .expr => |expr| self.exprMayJump(expr),
+            .let_decl => |let| let.init != null and self.exprMayJump(let.init.?),
+            .assign => |assign| self.exprMayJump(assign.value),
+            .compound_assign => |ca| self.exprMayJump(ca.value),
+            .loop_stmt => |loop| (loop.condition != null and self.exprMayJump(loop.condition.?)) or
+                (loop.iterator != null and self.exprMayJump(loop.iterator.?.iterable)) or
+                self.anyMayJump(loop.body),
+            .while_loop => |loop| self.exprMayJump(loop.condition) or self.anyMayJump(loop.body),
+            .for_loop => |loop| (loop.init != null and self.stmtMayJump(loop.init.?.*)) or
+                (loop.condition != null and self.exprMayJump(loop.condition.?)) or
+                self.anyMayJump(loop.body),
+        };
+    }
+
+    fn anyMayJump(self: *Scanner, stmts: []const ast.Stmt) bool {
+        for (stmts) |stmt| {
+            if (self.stmtMayJump(stmt)) return true;
+        }
+        return false;
+    }
+
+    fn exprMayJump(self: *Scanner, expr: ast.Expr) bool {
+        return switch (expr) {
+            .try_expr => true,
+            .call => |call| blk: {
+                if (call.callee.* == .identifier and self.graph.never_functions.contains(call.callee.identifier)) break :blk true;
+                for (call.args) |arg| {
+                    if (self.exprMayJump(arg)) break :blk true;
+                }
+                break :blk self.exprMayJump(call.callee.*);
+            },
+            .static_method_call => |smc| blk: {
+                for (smc.args) |arg| {
+                    if (self.exprMayJump(arg)) break :blk true;
+                }
+                break :blk false;
+            },
+            .binary => |bin| self.exprMayJump(bin.left.*) or self.exprMayJump(bin.right.*),
+            .unary => |un| self.exprMayJump(un.operand.*),
+            .field_access => |fa| self.exprMayJump(fa.object.*),
+            .array_index => |ai| self.exprMayJump(ai.array.*) or self.exprMayJump(ai.index.*),
+            .as_expr => |as_cast| self.exprMayJump(as_cast.value.*),
+            .await_expr => |inner| self.exprMayJump(inner.*),
+            .if_expr => |if_expr| self.exprMayJump(if_expr.condition.*) or self.exprMayJump(if_expr.then_branch.*) or
+                (if_expr.else_branch != null and self.exprMayJump(if_expr.else_branch.?.*)),
+            .is_expr => |is_match| blk: {
+                if (self.exprMayJump(is_match.value.*)) break :blk true;
+                for (is_match.arms) |arm| {
+                    if (self.exprMayJump(arm.body)) break :blk true;
+                }
+                break :blk false;
+            },
+            .match_expr => |match| blk: {
+                if (self.exprMayJump(match.value.*)) break :blk true;
+                for (match.arms) |arm| {
+                    if (self.exprMayJump(arm.body)) break :blk true;
+                }
+                break :blk false;
+            },
+            .block => |stmts| self.anyMayJump(stmts),
+            .loop_expr => |loop| self.anyMayJump(loop.body),
+            else => false,
+        };
+    }
+
+    fn addCallByName(self: *Scanner, name: []const u8) !void {
+        if (self.graph.nodes.contains(name)) return self.addCall(name);
+        // 按名字调用方法（show(x)）：只有一个类型提供时才能确定目标
+        const owner = self.graph.method_owners.get(name) orelse return;
+        if (owner) |type_name| try self.addCall(try Graph.methodKey(self.graph.arena, type_name, name));
+    }
+
+    fn addCall(self: *Scanner, key: []const u8) !void {
+        if (!self.graph.nodes.contains(key)) return;
+        for (self.callees.items) |existing| {
+            if (std.mem.eql(u8, existing, key)) return;
+        }
+        try self.callees.append(self.graph.arena, key);
+    }
+};
@@ -0,0 +1,58 @@
+//! Cancel - 编译取消令牌
+//!
+//! 为长时间运行的工具（watch 模式、LSP）设计：用户再次输入时，正在进行的
+//! 编译应当尽快放弃，而不是继续完成并与新的请求竞争（产生过期的诊断）。
+//!
+//! 解析、类型检查和代码生成在每个顶层声明 / 函数之间调用 `check()`，
+//! 令牌被取消或超过截止时间（看门狗）时返回 error.Cancelled。
+//!
+//! 用法：
+//!   var token = CancelToken.init();
+//!   parser.cancel_token = &token;
+//!   type_checker.cancel_token = &token;
+//!   ...
+//!   token.cancel();  // 可以在其他线程调用
+
+const std = @import("std");
+
+pub const Error = error{Cancelled};
+
+pub const CancelToken = struct {
+    cancelled: std.atomic.Value(bool),
+    deadline_ns: ?i128,  // 看门狗：超过该时间点（nanoTimestamp）视为已取消
+
+    pub fn init() CancelToken {
+        return CancelToken{
+            .cancelled = std.atomic.Value(bool).init(false),
+            .deadline_ns = null,
+        };
+    }
+
+    /// 带看门狗的令牌：从现在起 timeout_ms 毫秒后自动取消
+    pub fn withTimeout(timeout_ms: u64) CancelToken {
+        var token = CancelToken.init();
+        token.deadline_ns = std.time.nanoTimestamp() + @as(i128, timeout_ms) * std.time.ns_per_ms;
+        return token;
+    }
+
+    /// 取消编译（线程安全）
+    pub fn cancel(self: *CancelToken) void {
+        self.cancelled.store(true, .release);
+    }
+
+    pub fn isCancelled(self: *const CancelToken) bool {
+        if (self.cancelled.load(.acquire)) return true;
+        if (self.deadline_ns) |deadline| return std.time.nanoTimestamp() >= deadline;
+        return false;
+    }
+
+    /// 已取消时返回 error.Cancelled
+    pub fn check(self: *const CancelToken) Error!void {
+        if (self.isCancelled()) return error.Cancelled;
+    }
+};
+
+/// 可选令牌的检查（各阶段的 cancel_token 字段默认为 null）
+pub fn checkOptional(token: ?*const CancelToken) Error!void {
+    if (token) |t| try t.check();
+}
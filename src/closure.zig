@@ -0,0 +1,370 @@
+//! Closure - lambda 的捕获分析与闭包转换
+//!
+//! lambda 表达式 `|x: i32| -> i32 { x + n }` 分两步处理：
+//!
+//!   1. 类型检查时，freeVariables 找出 lambda 体中引用的外层局部变量，
+//!      写入 lambda.captures（按值捕获：创建闭包时复制，之后外层变量的修改不可见）
+//!   2. 代码生成之前，convert 把每个 lambda 提升为顶层函数 `<外层函数>__lambda_<n>`：
+//!      参数与 lambda 相同，FunctionDecl.captures 记录环境中的变量，
+//!      lambda 表达式记录提升后的函数名（方法中的外层函数名为 Type_method，
+//!      嵌套的 lambda 属于外层 lambda 提升后的函数）
+//!
+//! 后端把所有函数值表示为 (env, fn) 对：
+//!   - 函数名作为值、没有捕获的 lambda：env 为 null，调用 fn(args...)
+//!   - 有捕获的 lambda：env 指向运行时分配的 { 捕获1, 捕获2, ... }，
+//!     调用 fn(env, args...)，提升后的函数在入口把捕获的变量复制到局部变量
+//!
+//! 环境与程序同生命周期（不释放）。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+
+/// 提升出的 lambda 函数
+pub const Lifted = struct {
+    owner: []const u8,  // 直接包含 lambda 的函数（后端在它之前声明提升出的函数）
+    func: ast.FunctionDecl,
+};
+
+/// lambda 体中引用的外层局部变量（outer 是 lambda 所在位置的作用域），按第一次引用的顺序
+pub fn freeVariables(
+    allocator: std.mem.Allocator,
+    params: []const ast.Param,
+    body: []const ast.Stmt,
+    outer: *const std.StringHashMap(ast.Type),
+) ![]ast.Capture {
+    var walker = FreeVariables{ .allocator = allocator, .outer = outer };
+    defer walker.bound.deinit(allocator);
+    errdefer walker.captures.deinit(allocator);
+    for (params) |param| try walker.bound.append(allocator, param.name);
+    try walker.stmts(body);
+    return walker.captures.toOwnedSlice(allocator);
+}
+
+const FreeVariables = struct {
+    allocator: std.mem.Allocator,
+    outer: *const std.StringHashMap(ast.Type),
+    bound: std.ArrayList([]const u8) = .{},  // lambda 内声明的名字（离开块时弹出）
+    captures: std.ArrayList(ast.Capture) = .{},
+
+    const Error = std.mem.Allocator.Error;
+
+    fn isBound(self: *FreeVariables, name: []const u8) bool {
+        for (self.bound.items) |bound| {
+            if (std.mem.eql(u8, bound, name)) return true;
+        }
+        return false;
+    }
+
+    fn use(self: *FreeVariables, name: []const u8) Error!void {
+        if (self.isBound(name)) return;
+        // 函数名、常量等不在局部作用域中，不需要捕获
+        const var_type = self.outer.get(name) orelse return;
+        for (self.captures.items) |capture| {
+            if (std.mem.eql(u8, capture.name, name)) return;
+        }
+        try self.captures.append(self.allocator, .{ .name = name, .type = var_type });
+    }
+
+    fn bind(self: *FreeVariables, name: []const u8) Error!void {
+        try self.bound.append(self.allocator, name);
+    }
+
+    fn bindPattern(self: *FreeVariables, pattern: ast.Pattern) Error!void {
+        switch (pattern) {
+            .identifier => |name| try self.bind(name),
+            .variant => |v| for (v.bindings) |name| try self.bind(name),
+            .literal, .wildcard, .range, .alternatives => {},
+        }
+    }
+
+    fn block(self: *FreeVariables, body: []const ast.Stmt) Error!void {
+        const mark = self.bound.items.len;
+        defer self.bound.shrinkRetainingCapacity(mark);
+        try self.stmts(body);
+    }
+
+    fn stmts(self: *FreeVariables, body: []const ast.Stmt) Error!void {
+        for (body) |stmt| try self.stmt(stmt);
+    }
+
+    fn stmt(self: *FreeVariables, s: ast.Stmt) Error!void {
+        switch (s) {
+            .expr => |e| try self.expr(e),
+            .let_decl => |decl| {
+                if (decl.init) |init| try self.expr(init);
+                try self.bind(decl.name);
+            },
+            .assign => |assign| {
+                try self.expr(assign.target);
+                try self.expr(assign.value);
+            },
+            .compound_assign => |assign| {
+                try self.expr(assign.target);
+                try self.expr(assign.value);
+            },
+            .return_stmt => |ret| if (ret) |value| try self.expr(value),
+            .break_stmt => |brk| if (brk.value) |value| try self.expr(value),
+            .continue_stmt => {},
+            .loop_stmt => |loop| {
+                if (loop.condition) |cond| try self.expr(cond);
+                const mark = self.bound.items.len;
+                defer self.bound.shrinkRetainingCapacity(mark);
+                if (loop.iterator) |iter| {
+                    try self.expr(iter.iterable);
+                    try self.bind(iter.binding);
+                }
+                try self.stmts(loop.body);
+            },
+            .while_loop => |loop| {
+                try self.expr(loop.condition);
+                try self.block(loop.body);
+            },
+            .for_loop => |loop| {
+                const mark = self.bound.items.len;
+                defer self.bound.shrinkRetainingCapacity(mark);
+                if (loop.init) |init| try self.stmt(init.*);
+                if (loop.condition) |cond| try self.expr(cond);
+                if (loop.step) |step| try self.expr(step);
+                try self.stmts(loop.body);
+            },
+        }
+    }
+
+    fn expr(self: *FreeVariables, e: ast.Expr) Error!void {
+        switch (e) {
+            .identifier => |name| try self.use(name),
+            .binary => |bin| {
+                try self.expr(bin.left.*);
+                try self.expr(bin.right.*);
+            },
+            .unary => |un| try self.expr(un.operand.*),
+            .call => |call| {
+                try self.expr(call.callee.*);
+                for (call.args) |arg| try self.expr(arg);
+            },
+            .static_method_call => |smc| for (smc.args) |arg| try self.expr(arg),
+            .field_access => |fa| try self.expr(fa.object.*),
+            .struct_init => |si| for (si.fields) |field| try self.expr(field.value),
+            .enum_variant => |ev| for (ev.args) |arg| try self.expr(arg),
+            .block => |body| try self.block(body),
+            .if_expr => |if_expr| {
+                try self.expr(if_expr.condition.*);
+                try self.expr(if_expr.then_branch.*);
+                if (if_expr.else_branch) |else_branch| try self.expr(else_branch.*);
+            },
+            .is_expr => |is_match| {
+                try self.expr(is_match.value.*);
+                for (is_match.arms) |arm| {
+                    const mark = self.bound.items.len;
+                    defer self.bound.shrinkRetainingCapacity(mark);
+                    try self.bindPattern(arm.pattern);
+                    if (arm.guard) |guard| try self.expr(guard);
+                    try self.expr(arm.body);
+                }
+            },
+            .match_expr => |match| {
+                try self.expr(match.value.*);
+                for (match.arms) |arm| {
+                    const mark = self.bound.items.len;
+                    defer self.bound.shrinkRetainingCapacity(mark);
+                    try self.bindPattern(arm.pattern);
+                    try self.expr(arm.body);
+                }
+            },
+            .as_expr => |as_expr| try self.expr(as_expr.value.*),
+            .await_expr, .try_expr => |inner| try self.expr(inner.*),
+            .array_literal => |elems| for (elems) |elem| try self.expr(elem),
+            .array_index => |index| {
+                try self.expr(index.array.*);
+                try self.expr(index.index.*);
+            },
+            .range => |range| {
+                try self.expr(range.start.*);
+                try self.expr(range.end.*);
+            },
+            .string_interp => |interp| for (interp.parts) |part| switch (part) {
+                .expr => |part_expr| try self.expr(part_expr),
+                .literal => {},
+            },
+            .loop_expr => |loop| try self.block(loop.body),
+            // 嵌套 lambda 引用的外层变量也要经过这一层的环境
+            .lambda => |lambda| {
+                const mark = self.bound.items.len;
+                defer self.bound.shrinkRetainingCapacity(mark);
+                for (lambda.params) |param| try self.bind(param.name);
+                try self.stmts(lambda.body);
+            },
+            .int_literal, .float_literal, .string_literal, .char_literal,
+            .byte_literal, .byte_string_literal, .bool_literal, .type_query => {},
+        }
+    }
+};
+
+/// 把声明中的所有 lambda 提升为顶层函数，并在 lambda 表达式中记录提升后的函数名
+pub fn lift(allocator: std.mem.Allocator, decls: []const ast.TopLevelDecl) ![]Lifted {
+    var lifter = Lifter{ .allocator = allocator };
+    errdefer lifter.lifted.deinit(allocator);
+    for (decls) |decl| {
+        switch (decl) {
+            .function => |func| try lifter.liftBody(func.name, func.body),
+            .type_decl => |type_decl| {
+                const methods = switch (type_decl.kind) {
+                    .struct_type => |st| st.methods,
+                    .enum_type => |et| et.methods,
+                    else => continue,
+                };
+                for (methods) |method| {
+                    const owner = try std.fmt.allocPrint(allocator, "{s}_{s}", .{ type_decl.name, method.name });
+                    try lifter.liftBody(owner, method.body);
+                }
+            },
+            else => {},
+        }
+    }
+    return lifter.lifted.toOwnedSlice(allocator);
+}
+
+/// 闭包转换的结果
+pub const Converted = struct {
+    program: ast.Program,  // 原来的声明之后追加提升出的函数（后端像普通函数一样生成它们）
+    lifted: []const Lifted,
+};
+
+/// 闭包转换：提升程序中的 lambda
+pub fn convert(allocator: std.mem.Allocator, program: ast.Program) !Converted {
+    const lifted = try lift(allocator, program.declarations);
+    const decls = try allocator.alloc(ast.TopLevelDecl, program.declarations.len + lifted.len);
+    @memcpy(decls[0..program.declarations.len], program.declarations);
+    for (lifted, program.declarations.len..) |l, i| decls[i] = .{ .function = l.func };
+    return .{ .program = .{ .declarations = decls }, .lifted = lifted };
+}
+
+const Lifter = struct {
+    allocator: std.mem.Allocator,
+    lifted: std.ArrayList(Lifted) = .{},
+    owner: []const u8 = "",
+    count: usize = 0,  // owner 中已提升的 lambda 数
+
+    const Error = std.mem.Allocator.Error;
+
+    fn liftBody(self: *Lifter, owner: []const u8, body: []const ast.Stmt) Error!void {
+        const saved_owner = self.owner;
+        const saved_count = self.count;
+        self.owner = owner;
+        self.count = 0;
+        defer {
+            self.owner = saved_owner;
+            self.count = saved_count;
+        }
+        try self.stmts(body);
+    }
+
+    fn stmts(self: *Lifter, body: []const ast.Stmt) Error!void {
+        for (body) |stmt| try self.stmt(stmt);
+    }
+
+    fn stmt(self: *Lifter, s: ast.Stmt) Error!void {
+        switch (s) {
+            .expr => |e| try self.expr(e),
+            .let_decl => |decl| if (decl.init) |init| try self.expr(init),
+            .assign => |assign| {
+                try self.expr(assign.target);
+                try self.expr(assign.value);
+            },
+            .compound_assign => |assign| {
+                try self.expr(assign.target);
+                try self.expr(assign.value);
+            },
+            .return_stmt => |ret| if (ret) |value| try self.expr(value),
+            .break_stmt => |brk| if (brk.value) |value| try self.expr(value),
+            .continue_stmt => {},
+            .loop_stmt => |loop| {
+                if (loop.condition) |cond| try self.expr(cond);
+                if (loop.iterator) |iter| try self.expr(iter.iterable);
+                try self.stmts(loop.body);
+            },
+            .while_loop => |loop| {
+                try self.expr(loop.condition);
+                try self.stmts(loop.body);
+            },
+            .for_loop => |loop| {
+                if (loop.init) |init| try self.stmt(init.*);
+                if (loop.condition) |cond| try self.expr(cond);
+                if (loop.step) |step| try self.expr(step);
+                try self.stmts(loop.body);
+            },
+        }
+    }
+
+    fn expr(self: *Lifter, e: ast.Expr) Error!void {
+        switch (e) {
+            .binary => |bin| {
+                try self.expr(bin.left.*);
+                try self.expr(bin.right.*);
+            },
+            .unary => |un| try self.expr(un.operand.*),
+            .call => |call| {
+                try self.expr(call.callee.*);
+                for (call.args) |arg| try self.expr(arg);
+            },
+            .static_method_call => |smc| for (smc.args) |arg| try self.expr(arg),
+            .field_access => |fa| try self.expr(fa.object.*),
+            .struct_init => |si| for (si.fields) |field| try self.expr(field.value),
+            .enum_variant => |ev| for (ev.args) |arg| try self.expr(arg),
+            .block => |body| try self.stmts(body),
+            .if_expr => |if_expr| {
+                try self.expr(if_expr.condition.*);
+                try self.expr(if_expr.then_branch.*);
+                if (if_expr.else_branch) |else_branch| try self.expr(else_branch.*);
+            },
+            .is_expr => |is_match| {
+                try self.expr(is_match.value.*);
+                for (is_match.arms) |arm| {
+                    if (arm.guard) |guard| try self.expr(guard);
+                    try self.expr(arm.body);
+                }
+            },
+            .match_expr => |match| {
+                try self.expr(match.value.*);
+                for (match.arms) |arm| try self.expr(arm.body);
+            },
+            .as_expr => |as_expr| try self.expr(as_expr.value.*),
+            .await_expr, .try_expr => |inner| try self.expr(inner.*),
+            .array_literal => |elems| for (elems) |elem| try self.expr(elem),
+            .array_index => |index| {
+                try self.expr(index.array.*);
+                try self.expr(index.index.*);
+            },
+            .range => |range| {
+                try self.expr(range.start.*);
+                try self.expr(range.end.*);
+            },
+            .string_interp => |interp| for (interp.parts) |part| switch (part) {
+                .expr => |part_expr| try self.expr(part_expr),
+                .literal => {},
+            },
+            .loop_expr => |loop| try self.stmts(loop.body),
+            .lambda => |lambda| {
+                const name = try std.fmt.allocPrint(self.allocator, "{s}__lambda_{d}", .{ self.owner, self.count });
+                self.count += 1;
+                lambda.lifted_name.* = name;
+                try self.lifted.append(self.allocator, .{
+                    .owner = self.owner,
+                    .func = .{
+                        .name = name,
+                        .type_params = &[_][]const u8{},
+                        .params = lambda.params,
+                        .return_type = lambda.return_type,
+                        .body = lambda.body,
+                        .is_public = false,
+                        .is_async = false,
+                        .captures = lambda.captures.*,
+                    },
+                });
+                try self.liftBody(name, lambda.body);
+            },
+            .identifier, .int_literal, .float_literal, .string_literal, .char_literal,
+            .byte_literal, .byte_string_literal, .bool_literal, .type_query => {},
+        }
+    }
+};
@@ -19,11 +19,26 @@
 const std = @import("std");
 const ast = @import("ast.zig");
 const generics = @import("generics.zig");
+const layout = @import("layout.zig");
+const diagnostic = @import("diagnostic.zig");
+const api = @import("api.zig");
+const trace = @import("trace.zig");  // 🆕 --instrument=trace 的跟踪文件格式
+const cancel = @import("cancel.zig");
+const Limits = @import("limits.zig").Limits;  // 🆕 Paw.toml [limits]
+const closure = @import("closure.zig");  // 🆕 lambda 提升
+const builtin = @import("builtin");
 
 // ============================================================================
 // CodeGen Structure
 // ============================================================================
 
+/// 🆕 正在生成的循环（id 用于生成唯一的 C 跳转标签）
+const LoopLabel = struct {
+    name: ?[]const u8,
+    id: usize,
+    is_expr: bool = false,  // loop 表达式：break 值写入 paw_loop_value_{id}
+};
+
 /// C Code Generator
 pub const CodeGen = struct {
     allocator: std.mem.Allocator,
@@ -32,6 +47,12 @@ pub const CodeGen = struct {
     output: std.ArrayList(u8),
     // 🆕 类型表：变量名 -> 类型名
     var_types: std.StringHashMap([]const u8),
+    // 🆕 窄整数（i8 / u8 / i16 / u16）变量的类型：这些类型的算术结果按位宽回绕
+    narrow_var_types: std.StringHashMap(ast.Type),
+    // 🆕 当前函数中已知类型的参数和局部变量（字符串插值按类型格式化）
+    local_types: std.StringHashMap(ast.Type),
+    // 🆕 当前函数中长度在编译期已知的数组变量（索引时做越界检查）
+    struct_constants: std.ArrayList(ast.ConstDecl),  // 🆕 结构体常量（随结构体定义生成）
     // 🆕 类型定义表：类型名 -> TypeDecl
     type_decls: std.StringHashMap(ast.TypeDecl),
     // 🆕 enum variant表：variant名 -> enum类型名
@@ -47,6 +68,39 @@ pub const CodeGen = struct {
         type_params: [][]const u8,    // 类型参数 ([T])
         type_args: []ast.Type,        // 具体类型 ([i32])
     },
+    // 🆕 当前单态化函数的类型参数绑定（size_of<T>() 等需要）
+    current_function_bindings: ?layout.Bindings,
+    // 🆕 分离编译：为 true 时只生成函数/方法原型（实现在其他编译单元）
+    declarations_only: bool,
+    // 🆕 分离编译：为 true 时非 pub 函数和单态化实例使用 static 链接
+    internal_linkage: bool,
+    // 🆕 分离编译（-O）：外部的可内联函数生成 static inline 定义而不是原型（见 api.isInlinable）
+    inline_external: bool,
+    // 🆕 分离编译：本单元是否定义 main（null 为整个程序一个单元）。
+    // 所有单元共用的运行时状态（paw_rt_current_fn）定义在含 main 的单元中，其他单元声明为 extern
+    unit_has_main: ?bool,
+    // 🆕 生成失败的函数（生成结束后统一报告）
+    codegen_errors: diagnostic.CodegenErrors,
+    // 🆕 外层循环栈（带标签的 break/continue 用 goto 跳转）
+    loop_labels: std.ArrayList(LoopLabel),
+    next_label_id: usize,
+    // 🆕 取消令牌：每个声明之间检查
+    cancel_token: ?*const cancel.CancelToken,
+    // 🆕 --stack-size：主线程栈大小（字节），Linux 上由运行时提升 RLIMIT_STACK
+    stack_size: ?u64,
+    // 🆕 资源限制：实例总数、单个函数的生成时间
+    limits: Limits,
+    // 🆕 当前函数的生成截止时间（function_codegen_ms），每条语句检查
+    function_deadline_ns: ?i128,
+    // 🆕 --instrument=trace：每个函数的进入 / 退出写入运行时跟踪文件（见 trace.zig）
+    instrument_trace: bool,
+    // 🆕 跟踪的函数名表（下标即 fn_id）
+    trace_names: std.ArrayList([]const u8),
+    // 🆕 从本单元提升出的 lambda（见 closure.zig），以及已经声明了环境和原型的 lambda
+    lifted_lambdas: []const closure.Lifted,
+    declared_lambdas: std.StringHashMap(void),
+    // 🆕 --test：main 按 argv[1] 运行一个 @test 函数，而不是调用用户的 main（见 testrunner.zig）
+    test_harness: bool,
 
     pub fn init(allocator: std.mem.Allocator) CodeGen {
         var output = std.ArrayList(u8){};
@@ -58,64 +112,79 @@ pub const CodeGen = struct {
             .arena = std.heap.ArenaAllocator.init(allocator),
             .output = output,
             .var_types = std.StringHashMap([]const u8).init(allocator),
+            .narrow_var_types = std.StringHashMap(ast.Type).init(allocator),
+            .local_types = std.StringHashMap(ast.Type).init(allocator),
+            .struct_constants = std.ArrayList(ast.ConstDecl){},
             .type_decls = std.StringHashMap(ast.TypeDecl).init(allocator),
             .enum_variants = std.StringHashMap([]const u8).init(allocator),
             .generic_context = generics.GenericContext.init(allocator),
             .function_table = std.StringHashMap(ast.FunctionDecl).init(allocator),
             .current_method_context = null,
+            .current_function_bindings = null,
+            .declarations_only = false,
+            .internal_linkage = false,
+            .inline_external = false,
+            .unit_has_main = null,
+            .codegen_errors = diagnostic.CodegenErrors.init(allocator),
+            .loop_labels = std.ArrayList(LoopLabel){},
+            .next_label_id = 0,
+            .cancel_token = null,
+            .stack_size = null,
+            .limits = .{},
+            .function_deadline_ns = null,
+            .instrument_trace = false,
+            .trace_names = std.ArrayList([]const u8){},
+            .lifted_lambdas = &[_]closure.Lifted{},
+            .declared_lambdas = std.StringHashMap(void).init(allocator),
+            .test_harness = false,
         };
     }
 
     pub fn deinit(self: *CodeGen) void {
         self.output.deinit(self.allocator);
         self.var_types.deinit();
+        self.narrow_var_types.deinit();
+        self.local_types.deinit();
+        self.struct_constants.deinit(self.allocator);
         self.type_decls.deinit();
         self.enum_variants.deinit();
         self.generic_context.deinit();
         self.function_table.deinit();
+        self.codegen_errors.deinit();
+        self.loop_labels.deinit(self.allocator);
+        self.trace_names.deinit(self.allocator);
+        self.declared_lambdas.deinit();
         self.arena.deinit();  // 🆕 释放所有arena分配
     }
     
-    pub fn generate(self: *CodeGen, program: ast.Program) ![]const u8 {
+    pub fn generate(self: *CodeGen, source_program: ast.Program) ![]const u8 {
+        const program = try self.convertClosures(source_program);
+        
         // 🆕 第一遍：收集类型定义、函数和enum variants
         for (program.declarations) |decl| {
-            if (decl == .type_decl) {
-                try self.type_decls.put(decl.type_decl.name, decl.type_decl);
-                
-                // 收集enum variants
-                if (decl.type_decl.kind == .enum_type) {
-                    const enum_type = decl.type_decl.kind.enum_type;
-                    for (enum_type.variants) |variant| {
-                        try self.enum_variants.put(variant.name, decl.type_decl.name);
-                    }
-                }
-            } else if (decl == .function) {
-                // 🆕 收集函数定义（用于泛型实例化）
-                try self.function_table.put(decl.function.name, decl.function);
-            }
+            try self.collectDecl(decl);
         }
         
         // 🆕 设置泛型上下文的函数表引用
         self.generic_context.function_table = &self.function_table;
+        self.generic_context.monomorphizer.max_instances = self.limits.generic_instances;
         
         // 🆕 第二遍：收集所有泛型函数调用和泛型结构体实例
         try self.generic_context.collectGenericCalls(program);
         try self.collectGenericStructInstances(program);
         
         // 生成 C 代码头部
-        try self.output.appendSlice(self.allocator, "#include <stdio.h>\n");
-        try self.output.appendSlice(self.allocator, "#include <stdlib.h>\n");
-        try self.output.appendSlice(self.allocator, "#include <stdint.h>\n");
-        try self.output.appendSlice(self.allocator, "#include <stdbool.h>\n");
-        try self.output.appendSlice(self.allocator, "#include <string.h>\n");  // For string interpolation
-        try self.output.appendSlice(self.allocator, "\n");
-        try self.output.appendSlice(self.allocator, "// Generic function forward declarations\n");
+        try self.generateHeader();
+        
+        // 🆕 全局常量在所有函数之前生成
+        try self.generateConstants(program.declarations);
         
         // 🆕 第三遍：生成单态化函数的前向声明和泛型结构体定义
         try self.generateMonomorphizedDeclarations();
         
         // 第四遍：生成所有声明
         for (program.declarations) |decl| {
+            try cancel.checkOptional(self.cancel_token);
             try self.generateDecl(decl);
             try self.output.appendSlice(self.allocator, "\n");
         }
@@ -123,17 +192,672 @@ pub const CodeGen = struct {
         // 🆕 第五遍：生成泛型实例化的函数实现
         try self.generateMonomorphizedFunctions();
         
+        // 🆕 C ABI 入口
+        try self.generateMainWrapper(program.declarations);
+        
+        // 🆕 跟踪文件中的函数名表（所有函数生成之后才知道）
+        if (self.instrument_trace) try self.generateTraceNames();
+        
+        try self.checkCodegenErrors();
+        
         // 🔧 v0.1.4: Return a copy to avoid use-after-free
         return try self.allocator.dupe(u8, self.output.items);
     }
     
+    /// 🆕 分离编译：为单个模块生成独立的 C 编译单元
+    ///
+    /// `external` 中的声明由其他编译单元提供：类型定义照常生成，
+    /// 函数和方法只生成原型，链接时由对应的目标文件解析。
+    /// 单态化实例在每个单元内使用 static 链接，避免重复符号。
+    /// inline_external 为 true 时，可内联的外部函数生成 static inline 定义。
+    /// 与本单元的声明同名的外部声明被跳过（本单元的定义优先，static 定义之前不能有非 static 原型），
+    /// 重复的外部声明只保留第一个。
+    pub fn generateUnit(
+        self: *CodeGen,
+        unit_program: ast.Program,
+        all_external: []const ast.TopLevelDecl,
+        private_static: bool,
+    ) ![]const u8 {
+        const external = try self.filterExternal(unit_program.declarations, all_external);
+        // 🆕 只提升本单元的 lambda（外部声明只生成原型）
+        const program = try self.convertClosures(unit_program);
+        for (external) |decl| {
+            try self.collectDecl(decl);
+        }
+        for (program.declarations) |decl| {
+            try self.collectDecl(decl);
+        }
+        
+        self.generic_context.function_table = &self.function_table;
+        self.generic_context.monomorphizer.max_instances = self.limits.generic_instances;
+        try self.generic_context.collectGenericCalls(program);
+        try self.collectGenericStructInstances(program);
+        
+        self.unit_has_main = for (program.declarations) |decl| {
+            if (decl == .function and decl.function.isEntryPoint()) break true;
+        } else false;
+        try self.generateHeader();
+        
+        // 🆕 常量使用 static 链接，每个单元各自一份
+        try self.generateConstants(external);
+        try self.generateConstants(program.declarations);
+        
+        self.internal_linkage = true;
+        try self.generateMonomorphizedDeclarations();
+        
+        // 外部声明：只生成原型
+        self.internal_linkage = false;
+        self.declarations_only = true;
+        for (external) |decl| {
+            // 命名空间导入改名的函数（m__name）在定义它的单元中仍叫 name
+            if (decl == .function and !decl.function.is_extern and decl.function.source_name != null) {
+                try self.output.writer(self.allocator).print("#define {s} {s}\n", .{ decl.function.name, decl.function.source_name.? });
+            }
+            if (self.inline_external and decl == .function and api.isInlinable(decl.function)) continue;
+            try self.generateDecl(decl);
+            try self.output.appendSlice(self.allocator, "\n");
+        }
+        self.declarations_only = false;
+        
+        // 🆕 跨模块内联：可内联的外部函数在所有类型定义之后生成 static inline 定义，
+        // 函数体在本单元可见，C 编译器可以内联
+        if (self.inline_external) {
+            for (external) |decl| {
+                if (decl != .function or !api.isInlinable(decl.function)) continue;
+                try self.output.appendSlice(self.allocator, "static inline ");
+                try self.generateDecl(decl);
+                try self.output.appendSlice(self.allocator, "\n");
+            }
+        }
+        
+        // 本单元声明：非 pub 函数按需使用 static 链接
+        self.internal_linkage = private_static;
+        for (program.declarations) |decl| {
+            try cancel.checkOptional(self.cancel_token);
+            try self.generateDecl(decl);
+            try self.output.appendSlice(self.allocator, "\n");
+        }
+        
+        self.internal_linkage = true;
+        try self.generateMonomorphizedFunctions();
+        self.internal_linkage = false;
+        
+        // 🆕 只有定义了 main 的单元生成 C ABI 入口
+        try self.generateMainWrapper(program.declarations);
+        
+        try self.checkCodegenErrors();
+        
+        return try self.allocator.dupe(u8, self.output.items);
+    }
+    
+    /// 🆕 分离编译：去掉与本单元声明同名的外部声明、重复的外部声明，
+    /// 以及命名空间导入的模块私有函数（只在模块自己的单元中调用，那里是 static 的）
+    fn filterExternal(self: *CodeGen, unit_decls: []const ast.TopLevelDecl, external: []const ast.TopLevelDecl) ![]const ast.TopLevelDecl {
+        var seen = std.StringHashMap(void).init(self.allocator);
+        defer seen.deinit();
+        for (unit_decls) |decl| {
+            if (unitDeclName(decl)) |name| try seen.put(name, {});
+        }
+        var kept = std.ArrayList(ast.TopLevelDecl){};
+        for (external) |decl| {
+            if (decl == .function and decl.function.source_name != null and !decl.function.is_public) continue;
+            if (unitDeclName(decl)) |name| {
+                const entry = try seen.getOrPut(name);
+                if (entry.found_existing) continue;
+            }
+            try kept.append(self.arena.allocator(), decl);
+        }
+        return kept.items;
+    }
+    
+    fn unitDeclName(decl: ast.TopLevelDecl) ?[]const u8 {
+        return switch (decl) {
+            .function => |f| f.name,
+            .type_decl => |td| td.name,
+            .const_decl => |cd| cd.name,
+            else => null,
+        };
+    }
+    
+    /// 🆕 闭包转换：lambda 提升为顶层函数，追加在声明之后
+    fn convertClosures(self: *CodeGen, program: ast.Program) !ast.Program {
+        const converted = try closure.convert(self.arena.allocator(), program);
+        self.lifted_lambdas = converted.lifted;
+        return converted.program;
+    }
+    
+    /// 🆕 在函数之前声明从它提升出的 lambda（C 要求先声明后使用，lambda 的定义在所有声明之后）
+    fn declareLambdas(self: *CodeGen, owner: []const u8) !void {
+        for (self.lifted_lambdas) |lifted| {
+            if (std.mem.eql(u8, lifted.owner, owner)) try self.declareLambda(lifted.func);
+        }
+    }
+    
+    /// 🆕 lambda 的环境结构体 {name}_env 和 static 原型（每个 lambda 只声明一次）
+    fn declareLambda(self: *CodeGen, func: ast.FunctionDecl) !void {
+        const entry = try self.declared_lambdas.getOrPut(func.name);
+        if (entry.found_existing) return;
+        const w = self.output.writer(self.allocator);
+        if (func.hasEnv()) {
+            try self.output.appendSlice(self.allocator, "typedef struct {\n");
+            for (func.captures.?) |capture| try w.print("    {s} {s};\n", .{ self.typeToC(capture.type), capture.name });
+            try w.print("}} {s}_env;\n", .{func.name});
+        }
+        try self.generateLambdaSignature(func);
+        try self.output.appendSlice(self.allocator, ";\n");
+    }
+    
+    /// 🆕 static R name(void* paw_env, P1 p1, ...)：有捕获的 lambda 第一个参数是环境指针
+    fn generateLambdaSignature(self: *CodeGen, func: ast.FunctionDecl) !void {
+        const w = self.output.writer(self.allocator);
+        try w.print("static {s} {s}(", .{ self.typeToC(func.return_type), func.name });
+        if (func.hasEnv()) try self.output.appendSlice(self.allocator, "void* paw_env");
+        for (func.params, 0..) |param, i| {
+            if (i > 0 or func.hasEnv()) try self.output.appendSlice(self.allocator, ", ");
+            try w.print("{s} {s}", .{ self.typeToC(param.type), param.name });
+        }
+        if (func.params.len == 0 and !func.hasEnv()) try self.output.appendSlice(self.allocator, "void");
+        try self.output.appendSlice(self.allocator, ")");
+    }
+    
+    /// 🆕 提升出的 lambda 的定义：入口把捕获的变量从环境复制到同名的局部变量
+    fn generateLambdaFunction(self: *CodeGen, func: ast.FunctionDecl) !void {
+        try self.declareLambda(func);
+        
+        self.narrow_var_types.clearRetainingCapacity();
+        for (func.params) |param| {
+            if (isNarrowInt(param.type)) try self.narrow_var_types.put(param.name, param.type);
+        }
+        try self.recordParamTypes(func.params, layout.Bindings.empty);
+        for (func.captures.?) |capture| {
+            try self.local_types.put(capture.name, capture.type);
+            if (isNarrowInt(capture.type)) try self.narrow_var_types.put(capture.name, capture.type);
+        }
+        
+        try self.generateLambdaSignature(func);
+        try self.output.appendSlice(self.allocator, " {\n");
+        try self.generateFrameMarker(null, func.name);
+        for (func.captures.?) |capture| {
+            try self.output.writer(self.allocator).print("    {s} {s} = (({s}_env*)paw_env)->{s};\n", .{ self.typeToC(capture.type), capture.name, func.name, capture.name });
+        }
+        try self.generateFunctionBody(func);
+        try self.output.appendSlice(self.allocator, "}\n");
+    }
+    
+    /// 🆕 生成 C ABI 的 main 包装函数
+    ///
+    /// 用户的 main 以 ast.ENTRY_SYMBOL 生成；真正的 main 在调用它之前执行 paw_rt_init
+    /// （保存 argc/argv），返回之后执行 paw_rt_shutdown（刷新输出等运行时清理）。
+    fn generateMainWrapper(self: *CodeGen, decls: []const ast.TopLevelDecl) !void {
+        if (self.test_harness) return self.generateTestMain(decls);
+        const entry = for (decls) |decl| {
+            if (decl == .function and decl.function.isEntryPoint()) break decl.function;
+        } else return;
+        
+        try self.output.appendSlice(self.allocator, "int main(int argc, char** argv) {\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_init(argc, argv);\n");
+        switch (entry.return_type) {
+            .void, .never => {
+                try self.output.appendSlice(self.allocator, "    " ++ ast.ENTRY_SYMBOL ++ "();\n");
+                try self.output.appendSlice(self.allocator, "    int32_t paw_exit_code = 0;\n");
+            },
+            else => try self.output.appendSlice(self.allocator, "    int32_t paw_exit_code = (int32_t)" ++ ast.ENTRY_SYMBOL ++ "();\n"),
+        }
+        try self.output.appendSlice(self.allocator, "    paw_rt_shutdown();\n");
+        try self.output.appendSlice(self.allocator, "    return paw_exit_code;\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+    }
+    
+    /// 🆕 测试可执行文件的 main：`<exe> <test> [seed]` 运行名为 test 的 @test 函数
+    ///
+    /// 测试正常返回时退出码为 0；panic 与普通程序一样输出到 stderr 并以 101 退出，
+    /// 由测试运行器判断是否符合 should_panic。未知的测试名以 2 退出。
+    /// @property 函数从 seed（默认 0）开始运行 runs 次，见 generateRandomRuntime。
+    fn generateTestMain(self: *CodeGen, decls: []const ast.TopLevelDecl) !void {
+        const w = self.output.writer(self.allocator);
+        try self.output.appendSlice(self.allocator, "int main(int argc, char** argv) {\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_init(argc, argv);\n");
+        try self.output.appendSlice(self.allocator, "    const char* paw_test = argc > 1 ? argv[1] : \"\";\n");
+        try self.output.appendSlice(self.allocator, "    uint64_t paw_seed = argc > 2 ? strtoull(argv[2], NULL, 10) : 0;\n");
+        try self.output.appendSlice(self.allocator, "    (void)paw_seed;\n");
+        for (decls) |decl| {
+            if (decl != .function or decl.function.test_attr == null) continue;
+            const func = decl.function;
+            const symbol = if (func.isEntryPoint()) ast.ENTRY_SYMBOL else func.name;
+            if (func.test_attr.?.runs) |runs| {
+                try w.print("    if (strcmp(paw_test, \"{s}\") == 0) {{ paw_rt_property({s}, paw_seed, {d}); return 0; }}\n", .{ func.name, symbol, runs });
+            } else {
+                try w.print("    if (strcmp(paw_test, \"{s}\") == 0) {{ {s}(); return 0; }}\n", .{ func.name, symbol });
+            }
+        }
+        try self.output.appendSlice(self.allocator, "    fprintf(stderr, \"unknown test '%s'\\n\", paw_test);\n");
+        try self.output.appendSlice(self.allocator, "    return 2;\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+    }
+    
+    /// 🆕 有函数生成失败时报告所有错误并使构建失败
+    fn checkCodegenErrors(self: *CodeGen) !void {
+        if (self.codegen_errors.count() == 0) return;
+        self.codegen_errors.report();
+        return error.CodegenFailed;
+    }
+    
+    /// 🆕 记录函数生成失败：丢弃它已输出的部分代码，继续生成其余函数
+    fn recordFunctionError(
+        self: *CodeGen,
+        name: []const u8,
+        location: ?ast.SourceLocation,
+        output_mark: usize,
+        err: anyerror,
+    ) !void {
+        if (err == error.OutOfMemory) return err;
+        self.output.shrinkRetainingCapacity(output_mark);
+        self.current_function_bindings = null;
+        self.current_method_context = null;
+        
+        const span: ?diagnostic.Span = if (location) |loc|
+            diagnostic.Span.fromPosition(loc.file, loc.line, loc.column)
+        else
+            null;
+        if (err == error.CodegenTimeLimit) {
+            return self.codegen_errors.recordTimeLimit(name, span, self.limits.function_codegen_ms);
+        }
+        try self.codegen_errors.record(name, span, err);
+    }
+    
+    /// 🆕 开始生成一个函数：此后每条语句检查是否超过 function_codegen_ms
+    fn startFunctionClock(self: *CodeGen) void {
+        self.function_deadline_ns = std.time.nanoTimestamp() + @as(i128, self.limits.function_codegen_ms) * std.time.ns_per_ms;
+    }
+    
+    fn checkFunctionClock(self: *CodeGen) error{CodegenTimeLimit}!void {
+        const deadline = self.function_deadline_ns orelse return;
+        if (std.time.nanoTimestamp() > deadline) return error.CodegenTimeLimit;
+    }
+    
+    /// 收集类型定义、函数和enum variants
+    fn collectDecl(self: *CodeGen, decl: ast.TopLevelDecl) !void {
+        if (decl == .type_decl) {
+            try self.type_decls.put(decl.type_decl.name, decl.type_decl);
+            
+            // 收集enum variants
+            if (decl.type_decl.kind == .enum_type) {
+                const enum_type = decl.type_decl.kind.enum_type;
+                for (enum_type.variants) |variant| {
+                    try self.enum_variants.put(variant.name, decl.type_decl.name);
+                }
+            }
+        } else if (decl == .function) {
+            // 🆕 收集函数定义（用于泛型实例化）
+            try self.function_table.put(decl.function.name, decl.function);
+        }
+    }
+    
+    /// 生成 C 代码头部
+    fn generateHeader(self: *CodeGen) !void {
+        try self.output.appendSlice(self.allocator, "#include <stdio.h>\n");
+        try self.output.appendSlice(self.allocator, "#include <stdlib.h>\n");
+        try self.output.appendSlice(self.allocator, "#include <stdint.h>\n");
+        try self.output.appendSlice(self.allocator, "#include <stdbool.h>\n");
+        try self.output.appendSlice(self.allocator, "#include <string.h>\n");  // For string interpolation
+        try self.output.appendSlice(self.allocator, "#include <float.h>\n");  // 🆕 DBL_MAX（浮点数格式化）
+        // 🆕 isatty：stdout 是终端时按行缓冲
+        try self.output.appendSlice(self.allocator, "#if defined(_WIN32)\n");
+        try self.output.appendSlice(self.allocator, "#include <io.h>\n");
+        try self.output.appendSlice(self.allocator, "#define paw_isatty(fd) _isatty(fd)\n");
+        try self.output.appendSlice(self.allocator, "#else\n");
+        try self.output.appendSlice(self.allocator, "#include <unistd.h>\n");
+        try self.output.appendSlice(self.allocator, "#define paw_isatty(fd) isatty(fd)\n");
+        try self.output.appendSlice(self.allocator, "#endif\n");
+        try self.output.appendSlice(self.allocator, "\n");
+        // 🆕 `n as char` 的运行时检查：n 必须是 Unicode 标量值，否则与 panic 一样以 101 退出
+        try self.output.appendSlice(self.allocator, "static inline int64_t paw_char_check(int64_t v) {\n");
+        try self.output.appendSlice(self.allocator, "    if (v < 0 || (v >= 0xD800 && v <= 0xDFFF) || v > 0x10FFFF) {\n");
+        try self.output.appendSlice(self.allocator, "        fputs(\"PANIC:\\n" ++ ast.INVALID_CHAR_PANIC ++ "\\n\", stderr);\n");
+        try self.output.appendSlice(self.allocator, "        exit(101);\n");
+        try self.output.appendSlice(self.allocator, "    }\n");
+        try self.output.appendSlice(self.allocator, "    return v;\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+        // 🆕 数组索引的越界检查（长度在编译期已知的数组），越界与 panic 一样以 101 退出
+        try self.output.appendSlice(self.allocator, "static inline int64_t paw_index_check(int64_t index, int64_t len) {\n");
+        try self.output.appendSlice(self.allocator, "    if (index < 0 || index >= len) {\n");
+        try self.output.appendSlice(self.allocator, "        fprintf(stderr, \"PANIC:\\n" ++ ast.INDEX_OUT_OF_BOUNDS_PANIC ++ "\\n\", (long long)len, (long long)index);\n");
+        try self.output.appendSlice(self.allocator, "        exit(101);\n");
+        try self.output.appendSlice(self.allocator, "    }\n");
+        try self.output.appendSlice(self.allocator, "    return index;\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+        // 🆕 字符串模式：按内容比较
+        try self.output.appendSlice(self.allocator, "static inline bool paw_str_eq(const char* a, const char* b) {\n");
+        try self.output.appendSlice(self.allocator, "    return a == b || strcmp(a, b) == 0;\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+        // 🆕 函数值：(环境, 函数) 对，见 closure.zig；lambda 的环境分配后不释放
+        try self.output.appendSlice(self.allocator, "typedef struct { void* env; void* fn; } PawFn;\n");
+        try self.output.appendSlice(self.allocator, "static inline void* paw_rt_alloc(size_t size) {\n");
+        try self.output.appendSlice(self.allocator, "    void* p = malloc(size);\n");
+        try self.output.appendSlice(self.allocator, "    if (p == NULL) {\n");
+        try self.output.appendSlice(self.allocator, "        fputs(\"PANIC:\\n" ++ ast.OUT_OF_MEMORY_PANIC ++ "\\n\", stderr);\n");
+        try self.output.appendSlice(self.allocator, "        exit(101);\n");
+        try self.output.appendSlice(self.allocator, "    }\n");
+        try self.output.appendSlice(self.allocator, "    return p;\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+        // 🆕 运行时初始化 / 清理钩子：由生成的 main 包装函数调用
+        // shutdown 同时注册到 atexit，exit()/panic 退出的程序也会执行清理（只执行一次）
+        try self.generateStackRuntime();
+        if (self.instrument_trace) try self.generateTraceRuntime();
+        try self.output.appendSlice(self.allocator, "static int paw_rt_argc = 0;\n");
+        try self.output.appendSlice(self.allocator, "static char** paw_rt_argv = NULL;\n");
+        try self.output.appendSlice(self.allocator, "static bool paw_rt_done = false;\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_rt_shutdown(void) {\n");
+        try self.output.appendSlice(self.allocator, "    if (paw_rt_done) return;\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_done = true;\n");
+        if (self.instrument_trace) try self.output.appendSlice(self.allocator, "    paw_trace_finish();\n");
+        try self.output.appendSlice(self.allocator, "    fflush(NULL);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_rt_init(int argc, char** argv) {\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_argc = argc;\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_argv = argv;\n");
+        // 🆕 stdout 缓冲：终端按行写出，文件 / 管道缓冲区满才写出（paw_flush 立即写出）
+        try self.output.appendSlice(self.allocator, "    setvbuf(stdout, NULL, paw_isatty(1) ? _IOLBF : _IOFBF, " ++ std.fmt.comptimePrint("{d}", .{ast.STDOUT_BUFFER_SIZE}) ++ ");\n");
+        try self.output.appendSlice(self.allocator, "    atexit(paw_rt_shutdown);\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_stack_init();\n");
+        if (self.instrument_trace) try self.output.appendSlice(self.allocator, "    paw_trace_start();\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+        // 🆕 paw_write：println 等 prelude 函数的输出原语（fd 2 为 stderr，其余为 stdout）
+        try self.output.appendSlice(self.allocator, "static inline int32_t paw_rt_write(int32_t fd, const char* text) {\n");
+        try self.output.appendSlice(self.allocator, "    return fputs(text, fd == 2 ? stderr : stdout) < 0 ? -1 : 0;\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+        try self.generateFloatRuntime();
+        try self.generateLogRuntime();
+        try self.generateRandomRuntime();
+        try self.output.appendSlice(self.allocator, "// Generic function forward declarations\n");
+    }
+    
+    /// 🆕 栈溢出检测的运行时
+    ///
+    /// 每个函数入口把自己的名字写入 paw_rt_current_fn；溢出时报告最近进入的函数。
+    /// 分离编译时 paw_rt_current_fn 只在含 main 的单元中定义，其他单元的函数写入同一个变量。
+    /// - POSIX：在备用信号栈上处理 SIGSEGV / SIGBUS，出错地址落在主线程栈的
+    ///   范围内（含保护页）就视为栈溢出，其余错误交回默认处理
+    /// - Windows：向量化异常处理 EXCEPTION_STACK_OVERFLOW，SetThreadStackGuarantee
+    ///   为处理函数保留栈空间
+    /// 两者都与 panic 一样输出 "PANIC:\n<msg>" 到 stderr 并以 101 退出。
+    /// Linux 主线程的栈大小由 RLIMIT_STACK 决定（链接器参数只影响 PE / Mach-O），
+    /// 指定了 --stack-size 时在启动时提升软限制。
+    fn generateStackRuntime(self: *CodeGen) !void {
+        const w = self.output.writer(self.allocator);
+        try w.print("#define PAW_STACK_SIZE {d}ULL\n", .{self.stack_size orelse 0});
+        try self.output.appendSlice(self.allocator, if (self.unit_has_main) |has_main|
+            (if (has_main) "const char* volatile paw_rt_current_fn = \"main\";\n" else "extern const char* volatile paw_rt_current_fn;\n")
+        else
+            "static const char* volatile paw_rt_current_fn = \"main\";\n");
+        try self.output.appendSlice(self.allocator, "static const char paw_rt_overflow_msg[] = \"PANIC:\\n" ++ ast.STACK_OVERFLOW_PANIC ++ "\";\n");
+        try self.output.appendSlice(self.allocator, "#if defined(_WIN32)\n");
+        try self.output.appendSlice(self.allocator, "#define WIN32_LEAN_AND_MEAN\n");
+        try self.output.appendSlice(self.allocator, "#define NOMINMAX\n");
+        try self.output.appendSlice(self.allocator, "#include <windows.h>\n");
+        try self.output.appendSlice(self.allocator, "static LONG WINAPI paw_rt_stack_handler(PEXCEPTION_POINTERS info) {\n");
+        try self.output.appendSlice(self.allocator, "    if (info->ExceptionRecord->ExceptionCode != EXCEPTION_STACK_OVERFLOW) return EXCEPTION_CONTINUE_SEARCH;\n");
+        try self.output.appendSlice(self.allocator, "    _write(2, paw_rt_overflow_msg, sizeof(paw_rt_overflow_msg) - 1);\n");
+        try self.output.appendSlice(self.allocator, "    _write(2, paw_rt_current_fn, (unsigned)strlen(paw_rt_current_fn));\n");
+        try self.output.appendSlice(self.allocator, "    _write(2, \"\\n\", 1);\n");
+        try self.output.appendSlice(self.allocator, "    ExitProcess(101);\n");
+        try self.output.appendSlice(self.allocator, "    return EXCEPTION_CONTINUE_SEARCH;\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_rt_stack_init(void) {\n");
+        try self.output.appendSlice(self.allocator, "    ULONG guarantee = 64 * 1024;\n");
+        try self.output.appendSlice(self.allocator, "    SetThreadStackGuarantee(&guarantee);\n");
+        try self.output.appendSlice(self.allocator, "    AddVectoredExceptionHandler(1, paw_rt_stack_handler);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "#elif defined(__unix__) || defined(__APPLE__)\n");
+        try self.output.appendSlice(self.allocator, "#include <signal.h>\n");
+        try self.output.appendSlice(self.allocator, "#include <sys/resource.h>\n");
+        try self.output.appendSlice(self.allocator, "static char* paw_rt_stack_top = NULL;\n");
+        try self.output.appendSlice(self.allocator, "static uint64_t paw_rt_stack_span = 0;\n");
+        try self.output.appendSlice(self.allocator, "static void paw_rt_stack_handler(int sig, siginfo_t* info, void* context) {\n");
+        try self.output.appendSlice(self.allocator, "    (void)context;\n");
+        try self.output.appendSlice(self.allocator, "    char* addr = (char*)info->si_addr;\n");
+        try self.output.appendSlice(self.allocator, "    if (addr < paw_rt_stack_top && (uint64_t)(paw_rt_stack_top - addr) <= paw_rt_stack_span) {\n");
+        try self.output.appendSlice(self.allocator, "        ssize_t ignored = write(2, paw_rt_overflow_msg, sizeof(paw_rt_overflow_msg) - 1);\n");
+        try self.output.appendSlice(self.allocator, "        ignored = write(2, paw_rt_current_fn, strlen(paw_rt_current_fn));\n");
+        try self.output.appendSlice(self.allocator, "        ignored = write(2, \"\\n\", 1);\n");
+        try self.output.appendSlice(self.allocator, "        (void)ignored;\n");
+        try self.output.appendSlice(self.allocator, "        _exit(101);\n");
+        try self.output.appendSlice(self.allocator, "    }\n");
+        // 不是栈溢出：恢复默认处理，返回后重新执行出错的指令
+        try self.output.appendSlice(self.allocator, "    signal(sig, SIG_DFL);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_rt_stack_init(void) {\n");
+        try self.output.appendSlice(self.allocator, "    char marker;\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_stack_top = &marker;\n");
+        try self.output.appendSlice(self.allocator, "    struct rlimit rl;\n");
+        try self.output.appendSlice(self.allocator, "    if (getrlimit(RLIMIT_STACK, &rl) != 0) rl.rlim_cur = RLIM_INFINITY;\n");
+        try self.output.appendSlice(self.allocator, "#if defined(__linux__)\n");
+        try self.output.appendSlice(self.allocator, "    if (PAW_STACK_SIZE > 0 && rl.rlim_cur != RLIM_INFINITY && rl.rlim_cur < PAW_STACK_SIZE) {\n");
+        try self.output.appendSlice(self.allocator, "        rl.rlim_cur = (rl.rlim_max == RLIM_INFINITY || rl.rlim_max >= PAW_STACK_SIZE) ? (rlim_t)PAW_STACK_SIZE : rl.rlim_max;\n");
+        try self.output.appendSlice(self.allocator, "        setrlimit(RLIMIT_STACK, &rl);\n");
+        try self.output.appendSlice(self.allocator, "    }\n");
+        try self.output.appendSlice(self.allocator, "#endif\n");
+        // 栈的范围：软限制（无限制时按 4 GiB 算）再加 1 MiB 的保护区
+        try self.output.appendSlice(self.allocator, "    uint64_t limit = (rl.rlim_cur == RLIM_INFINITY || rl.rlim_cur > (1ULL << 32)) ? (1ULL << 32) : (uint64_t)rl.rlim_cur;\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_stack_span = limit + (1ULL << 20);\n");
+        try self.output.appendSlice(self.allocator, "    static char alt_stack[64 * 1024];\n");
+        try self.output.appendSlice(self.allocator, "    stack_t ss;\n");
+        try self.output.appendSlice(self.allocator, "    memset(&ss, 0, sizeof(ss));\n");
+        try self.output.appendSlice(self.allocator, "    ss.ss_sp = alt_stack;\n");
+        try self.output.appendSlice(self.allocator, "    ss.ss_size = sizeof(alt_stack);\n");
+        try self.output.appendSlice(self.allocator, "    if (sigaltstack(&ss, NULL) != 0) return;\n");
+        try self.output.appendSlice(self.allocator, "    struct sigaction sa;\n");
+        try self.output.appendSlice(self.allocator, "    memset(&sa, 0, sizeof(sa));\n");
+        try self.output.appendSlice(self.allocator, "    sa.sa_sigaction = paw_rt_stack_handler;\n");
+        try self.output.appendSlice(self.allocator, "    sa.sa_flags = SA_SIGINFO | SA_ONSTACK;\n");
+        try self.output.appendSlice(self.allocator, "    sigemptyset(&sa.sa_mask);\n");
+        try self.output.appendSlice(self.allocator, "    sigaction(SIGSEGV, &sa, NULL);\n");
+        try self.output.appendSlice(self.allocator, "    sigaction(SIGBUS, &sa, NULL);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "#else\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_rt_stack_init(void) {}\n");
+        try self.output.appendSlice(self.allocator, "#endif\n\n");
+    }
+    
+    /// 🆕 函数入口：记录当前函数名，栈溢出时报告（方法记为 Type::method）
+    fn generateFrameMarker(self: *CodeGen, type_name: ?[]const u8, name: []const u8) !void {
+        try self.output.appendSlice(self.allocator, "    paw_rt_current_fn = \"");
+        if (type_name) |t| {
+            try self.output.appendSlice(self.allocator, t);
+            try self.output.appendSlice(self.allocator, "::");
+        }
+        try self.output.appendSlice(self.allocator, name);
+        try self.output.appendSlice(self.allocator, "\";\n");
+        
+        // 🆕 --instrument=trace：进入时记录，离开作用域（任何 return）时由 cleanup 记录退出
+        if (self.instrument_trace) {
+            const display = if (type_name) |t| try std.fmt.allocPrint(self.arena.allocator(), "{s}::{s}", .{ t, name }) else name;
+            const fn_id = self.trace_names.items.len;
+            try self.trace_names.append(self.allocator, display);
+            try self.output.writer(self.allocator).print("    uint32_t paw_trace_frame __attribute__((cleanup(paw_trace_exit))) = paw_trace_enter({d});\n", .{fn_id});
+        }
+    }
+    
+    /// 🆕 --instrument=trace 的运行时：把函数进入 / 退出事件写入 PAW_TRACE_FILE
+    /// （默认 trace.DEFAULT_FILE），格式见 trace.zig；pawc trace view 转换为折叠栈。
+    /// 函数名表在程序结束时由 paw_trace_write_names 追加（定义在所有函数之后）。
+    fn generateTraceRuntime(self: *CodeGen) !void {
+        const w = self.output.writer(self.allocator);
+        try self.output.appendSlice(self.allocator, "#include <time.h>\n");
+        try self.output.appendSlice(self.allocator, "static FILE* paw_trace_out = NULL;\n");
+        try self.output.appendSlice(self.allocator, "static void paw_trace_write_names(void);\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_trace_u32(uint32_t v) {\n");
+        try self.output.appendSlice(self.allocator, "    for (int i = 0; i < 4; i++) fputc((int)((v >> (8 * i)) & 0xFF), paw_trace_out);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_trace_u64(uint64_t v) {\n");
+        try self.output.appendSlice(self.allocator, "    for (int i = 0; i < 8; i++) fputc((int)((v >> (8 * i)) & 0xFF), paw_trace_out);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_trace_event(int kind, uint32_t fn_id) {\n");
+        try self.output.appendSlice(self.allocator, "    if (!paw_trace_out) return;\n");
+        try self.output.appendSlice(self.allocator, "    struct timespec ts;\n");
+        try self.output.appendSlice(self.allocator, "    timespec_get(&ts, TIME_UTC);\n");
+        try self.output.appendSlice(self.allocator, "    fputc(kind, paw_trace_out);\n");
+        try self.output.appendSlice(self.allocator, "    paw_trace_u32(fn_id);\n");
+        try self.output.appendSlice(self.allocator, "    paw_trace_u64((uint64_t)ts.tv_sec * 1000000000ULL + (uint64_t)ts.tv_nsec);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try w.print("static inline uint32_t paw_trace_enter(uint32_t fn_id) {{ paw_trace_event({d}, fn_id); return fn_id; }}\n", .{@intFromEnum(trace.EventKind.enter)});
+        try w.print("static inline void paw_trace_exit(uint32_t* fn_id) {{ paw_trace_event({d}, *fn_id); }}\n", .{@intFromEnum(trace.EventKind.exit)});
+        try self.output.appendSlice(self.allocator, "static inline void paw_trace_start(void) {\n");
+        try self.output.appendSlice(self.allocator, "    const char* path = getenv(\"" ++ trace.FILE_ENV ++ "\");\n");
+        try self.output.appendSlice(self.allocator, "    paw_trace_out = fopen(path && *path ? path : \"" ++ trace.DEFAULT_FILE ++ "\", \"wb\");\n");
+        try self.output.appendSlice(self.allocator, "    if (!paw_trace_out) return;\n");
+        try self.output.appendSlice(self.allocator, "    fwrite(\"" ++ trace.MAGIC ++ "\", 1, " ++ std.fmt.comptimePrint("{d}", .{trace.MAGIC.len}) ++ ", paw_trace_out);\n");
+        try w.print("    paw_trace_u32({d});\n", .{trace.FORMAT_VERSION});
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_trace_finish(void) {\n");
+        try self.output.appendSlice(self.allocator, "    if (!paw_trace_out) return;\n");
+        try self.output.appendSlice(self.allocator, "    paw_trace_write_names();\n");
+        try self.output.appendSlice(self.allocator, "    fclose(paw_trace_out);\n");
+        try self.output.appendSlice(self.allocator, "    paw_trace_out = NULL;\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+    }
+    
+    /// 🆕 跟踪文件的函数名记录：每个 fn_id 一条（kind、fn_id、名字长度、名字）
+    fn generateTraceNames(self: *CodeGen) !void {
+        const w = self.output.writer(self.allocator);
+        try self.output.appendSlice(self.allocator, "static void paw_trace_write_names(void) {\n");
+        for (self.trace_names.items, 0..) |name, fn_id| {
+            try w.print("    fputc({d}, paw_trace_out); paw_trace_u32({d}); paw_trace_u32({d}); fwrite(\"{s}\", 1, {d}, paw_trace_out);\n", .{
+                @intFromEnum(trace.EventKind.name), fn_id, name.len, name, name.len,
+            });
+        }
+        try self.output.appendSlice(self.allocator, "}\n");
+    }
+    
+    /// 🆕 paw_log 的运行时：按 PAW_LOG_LEVEL（名称或数字，规则同 ast.parseLogLevel）过滤，
+    /// 输出 "[LEVEL] msg" 到 stderr
+    /// 🆕 浮点数格式化的运行时（规则见 ast.formatFloat，解释器使用同一实现）
+    ///
+    /// 不直接使用 %g：它只保留 6 位有效数字，指数的写法（e+20 / e+020）也随 C 库而变。
+    /// shortest 从 1 位有效数字开始增加，直到 strtod 能读回原值（最多 17 位），
+    /// 再按十进制指数选择定点或科学计数法；科学计数法的指数统一写成 e20 / e-7。
+    fn generateFloatRuntime(self: *CodeGen) !void {
+        const w = self.output.writer(self.allocator);
+        try self.output.appendSlice(self.allocator, "static inline char* paw_rt_format_f64(char* buf, size_t size, double v, int32_t style, int32_t digits) {\n");
+        try self.output.appendSlice(self.allocator, "    if (v != v) { snprintf(buf, size, \"nan\"); return buf; }\n");
+        try self.output.appendSlice(self.allocator, "    if (v > DBL_MAX || v < -DBL_MAX) { snprintf(buf, size, \"%s\", v < 0 ? \"-inf\" : \"inf\"); return buf; }\n");
+        try w.print("    if (digits < 0) digits = 0;\n    if (digits > {d}) digits = {d};\n", .{ ast.FLOAT_MAX_DIGITS, ast.FLOAT_MAX_DIGITS });
+        try w.print("    if (style == {d}) {{ snprintf(buf, size, \"%.*f\", (int)digits, v); return buf; }}\n", .{@intFromEnum(ast.FloatStyle.fixed)});
+        try self.output.appendSlice(self.allocator, "    char tmp[64];\n");
+        try self.output.appendSlice(self.allocator, "    int p = (int)digits;\n");
+        try w.print("    if (style == {d}) {{\n", .{@intFromEnum(ast.FloatStyle.shortest)});
+        try self.output.appendSlice(self.allocator, "        for (p = 0; p < 16; p++) {\n");
+        try self.output.appendSlice(self.allocator, "            snprintf(tmp, sizeof(tmp), \"%.*e\", p, v);\n");
+        try self.output.appendSlice(self.allocator, "            if (strtod(tmp, NULL) == v) break;\n");
+        try self.output.appendSlice(self.allocator, "        }\n");
+        try self.output.appendSlice(self.allocator, "    }\n");
+        try self.output.appendSlice(self.allocator, "    snprintf(tmp, sizeof(tmp), \"%.*e\", p, v);\n");
+        try self.output.appendSlice(self.allocator, "    char* e = strchr(tmp, 'e');\n");
+        try self.output.appendSlice(self.allocator, "    int exp10 = atoi(e + 1);\n");
+        try w.print("    if (style == {d} && exp10 >= {d} && exp10 < {d}) {{\n", .{ @intFromEnum(ast.FloatStyle.shortest), ast.FLOAT_FIXED_MIN_EXP, ast.FLOAT_FIXED_MAX_EXP });
+        try self.output.appendSlice(self.allocator, "        snprintf(buf, size, \"%.*f\", p > exp10 ? p - exp10 : 0, v);\n");
+        try self.output.appendSlice(self.allocator, "    } else {\n");
+        try self.output.appendSlice(self.allocator, "        *e = '\\0';\n");
+        try self.output.appendSlice(self.allocator, "        snprintf(buf, size, \"%se%d\", tmp, exp10);\n");
+        try self.output.appendSlice(self.allocator, "    }\n");
+        try self.output.appendSlice(self.allocator, "    return buf;\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline int32_t paw_rt_write_f64(int32_t fd, double v, int32_t style, int32_t digits) {\n");
+        try self.output.appendSlice(self.allocator, "    char buf[512];\n");
+        try self.output.appendSlice(self.allocator, "    return paw_rt_write(fd, paw_rt_format_f64(buf, sizeof(buf), v, style, digits));\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+    }
+    
+    fn generateLogRuntime(self: *CodeGen) !void {
+        const w = self.output.writer(self.allocator);
+        try self.output.appendSlice(self.allocator, "static int paw_rt_log_level = -1;\n");
+        try self.output.appendSlice(self.allocator, "static inline int paw_rt_parse_log_level(const char* s) {\n");
+        try w.print("    if (s == NULL || s[0] == '\\0') return {d};\n", .{ast.DEFAULT_LOG_LEVEL});
+        try self.output.appendSlice(self.allocator, "    if (s[0] >= '0' && s[0] <= '9') {\n");
+        try self.output.appendSlice(self.allocator, "        char* end;\n");
+        try self.output.appendSlice(self.allocator, "        long level = strtol(s, &end, 10);\n");
+        try w.print("        return *end == '\\0' ? (int)level : {d};\n", .{ast.DEFAULT_LOG_LEVEL});
+        try self.output.appendSlice(self.allocator, "    }\n");
+        for (ast.LOG_LEVEL_NAMES, 0..) |name, level| {
+            try w.print("    if (strcmp(s, \"{s}\") == 0) return {d};\n", .{ name, level });
+        }
+        try w.print("    return {d};\n", .{ast.DEFAULT_LOG_LEVEL});
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline int32_t paw_rt_log(int32_t level, const char* msg) {\n");
+        try self.output.appendSlice(self.allocator, "    static const char* const names[] = {");
+        for (ast.LOG_LEVEL_NAMES, 0..) |name, level| {
+            var upper: [16]u8 = undefined;
+            if (level > 0) try self.output.appendSlice(self.allocator, ", ");
+            try w.print("\"{s}\"", .{std.ascii.upperString(&upper, name)});
+        }
+        try self.output.appendSlice(self.allocator, "};\n");
+        try self.output.appendSlice(self.allocator, "    if (paw_rt_log_level < 0) paw_rt_log_level = paw_rt_parse_log_level(getenv(\"PAW_LOG_LEVEL\"));\n");
+        try self.output.appendSlice(self.allocator, "    if (level < 1 || level > paw_rt_log_level) return 0;\n");
+        try w.print("    fprintf(stderr, \"[%s] %s\\n\", names[level > {d} ? {d} : level], msg);\n", .{ ast.LOG_LEVEL_NAMES.len - 1, ast.LOG_LEVEL_NAMES.len - 1 });
+        try self.output.appendSlice(self.allocator, "    return 0;\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+    }
+    
+    /// 🆕 gen_int / gen_string 的 PRNG（与 ast.nextRandom 相同的 splitmix64）以及 @property 的运行循环
+    ///
+    /// paw_rt_property 以 seed, seed+1, ... 作为每次运行的种子；某次运行 panic 时，
+    /// 运行时在 PANIC 消息之后（atexit）输出 ast.PROPERTY_SEED_REPORT 和这次的种子。
+    fn generateRandomRuntime(self: *CodeGen) !void {
+        try self.output.appendSlice(self.allocator, "static uint64_t paw_rt_rng_state = 0;\n");
+        try self.output.appendSlice(self.allocator, "static inline uint64_t paw_rt_next_random(void) {\n");
+        try self.output.appendSlice(self.allocator, "    uint64_t z = (paw_rt_rng_state += 0x9E3779B97F4A7C15ULL);\n");
+        try self.output.appendSlice(self.allocator, "    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ULL;\n");
+        try self.output.appendSlice(self.allocator, "    z = (z ^ (z >> 27)) * 0x94D049BB133111EBULL;\n");
+        try self.output.appendSlice(self.allocator, "    return z ^ (z >> 31);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline int32_t paw_rt_gen_int(int32_t lo, int32_t hi) {\n");
+        try self.output.appendSlice(self.allocator, "    if (hi < lo) return lo;\n");
+        try self.output.appendSlice(self.allocator, "    uint64_t span = (uint64_t)((int64_t)hi - (int64_t)lo + 1);\n");
+        try self.output.appendSlice(self.allocator, "    return (int32_t)((int64_t)lo + (int64_t)(paw_rt_next_random() % span));\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline const char* paw_rt_gen_string(int32_t len) {\n");
+        try self.output.appendSlice(self.allocator, "    static const char alphabet[] = \"" ++ ast.GEN_STRING_ALPHABET ++ "\";\n");
+        try self.output.appendSlice(self.allocator, "    if (len < 0) len = 0;\n");
+        try self.output.appendSlice(self.allocator, "    char* s = (char*)paw_rt_alloc((size_t)len + 1);\n");
+        try self.output.appendSlice(self.allocator, "    for (int32_t i = 0; i < len; i++) s[i] = alphabet[paw_rt_next_random() % (sizeof(alphabet) - 1)];\n");
+        try self.output.appendSlice(self.allocator, "    s[len] = '\\0';\n");
+        try self.output.appendSlice(self.allocator, "    return s;\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        if (!self.test_harness) {
+            try self.output.appendSlice(self.allocator, "\n");
+            return;
+        }
+        try self.output.appendSlice(self.allocator, "static bool paw_rt_property_running = false;\n");
+        try self.output.appendSlice(self.allocator, "static uint64_t paw_rt_property_seed = 0;\n");
+        try self.output.appendSlice(self.allocator, "static void paw_rt_property_report(void) {\n");
+        try self.output.appendSlice(self.allocator, "    if (paw_rt_property_running) fprintf(stderr, \"" ++ ast.PROPERTY_SEED_REPORT ++ "%llu\\n\", (unsigned long long)paw_rt_property_seed);\n");
+        try self.output.appendSlice(self.allocator, "}\n");
+        try self.output.appendSlice(self.allocator, "static inline void paw_rt_property(void (*property)(void), uint64_t seed, uint32_t runs) {\n");
+        try self.output.appendSlice(self.allocator, "    atexit(paw_rt_property_report);\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_property_running = true;\n");
+        try self.output.appendSlice(self.allocator, "    for (uint32_t i = 0; i < runs; i++) {\n");
+        try self.output.appendSlice(self.allocator, "        paw_rt_property_seed = seed + i;\n");
+        try self.output.appendSlice(self.allocator, "        paw_rt_rng_state = paw_rt_property_seed;\n");
+        try self.output.appendSlice(self.allocator, "        property();\n");
+        try self.output.appendSlice(self.allocator, "    }\n");
+        try self.output.appendSlice(self.allocator, "    paw_rt_property_running = false;\n");
+        try self.output.appendSlice(self.allocator, "}\n\n");
+    }
+    
     // ============================================================================
     // Declaration Generation
     // ============================================================================
     
     fn generateDecl(self: *CodeGen, decl: ast.TopLevelDecl) !void {
             switch (decl) {
-            .function => |func| try self.generateFunction(func),
+            .function => |func| {
+                const mark = self.output.items.len;
+                self.startFunctionClock();
+                defer self.function_deadline_ns = null;
+                self.generateFunction(func) catch |err| {
+                    try self.recordFunctionError(func.name, func.location, mark, err);
+                };
+            },
             .type_decl => |type_decl| try self.generateTypeDecl(type_decl),
             .struct_decl => |struct_decl| try self.generateStructDecl(struct_decl),
             .enum_decl => |enum_decl| try self.generateEnumDecl(enum_decl),
@@ -149,6 +873,104 @@ pub const CodeGen = struct {
                 // TODO: 处理 impl 声明
                 _ = impl_decl;
             },
+            // 🆕 常量已在 generateConstants 中生成
+            .const_decl => {},
+        }
+    }
+    
+    /// 🆕 生成全局常量：static const T NAME = value;
+    fn generateConstants(self: *CodeGen, decls: []const ast.TopLevelDecl) !void {
+        for (decls) |decl| {
+            if (decl != .const_decl) continue;
+            const cd = decl.const_decl;
+            const const_type: ast.Type = cd.type orelse switch (cd.value.*) {
+                .float_literal => ast.Type.f64,
+                .bool_literal => ast.Type.bool,
+                .char_literal => ast.Type.char,
+                .string_literal => ast.Type.string,
+                .struct_init => |si| ast.Type{ .named = si.type_name },  // 🆕 结构体常量
+                else => ast.Type.i32,
+            };
+            // 🆕 数组 / 结构体常量：元素已在编译期求值，生成静态初始化的只读数据（不需要运行时初始化）
+            if (cd.value.* == .array_literal or cd.value.* == .struct_init) {
+                // 结构体（数组）常量要等到结构体定义之后（见 generateStructConstants）
+                if (self.constantStructType(const_type) != null) {
+                    try self.struct_constants.append(self.allocator, cd);
+                } else {
+                    try self.generateAggregateConstant(cd.name, const_type, cd.value.*);
+                }
+                continue;
+            }
+            try self.output.appendSlice(self.allocator, "static const ");
+            try self.output.appendSlice(self.allocator, self.typeToC(const_type));
+            try self.output.appendSlice(self.allocator, " ");
+            try self.output.appendSlice(self.allocator, cd.name);
+            try self.output.appendSlice(self.allocator, " = ");
+            try self.generateExpr(cd.value.*);
+            try self.output.appendSlice(self.allocator, ";\n");
+        }
+    }
+    
+    /// 🆕 元素是结构体的常量：返回结构体名
+    fn constantStructType(self: *CodeGen, const_type: ast.Type) ?[]const u8 {
+        var element_type = const_type;
+        while (element_type == .array) element_type = element_type.array.element.*;
+        if (element_type != .named) return null;
+        const type_decl = self.type_decls.get(element_type.named) orelse return null;
+        return if (type_decl.kind == .struct_type) element_type.named else null;
+    }
+    
+    /// 🆕 在结构体定义之后、方法实现之前生成它的常量
+    fn generateStructConstants(self: *CodeGen, struct_name: []const u8) !void {
+        for (self.struct_constants.items) |cd| {
+            const const_type = cd.type orelse ast.Type{ .named = cd.value.struct_init.type_name };
+            const name = self.constantStructType(const_type) orelse continue;
+            if (!std.mem.eql(u8, name, struct_name)) continue;
+            try self.generateAggregateConstant(cd.name, const_type, cd.value.*);
+        }
+    }
+    
+    /// 🆕 static const T NAME[N][M] = { ... };（多维数组的长度逐层写出）
+    fn generateAggregateConstant(self: *CodeGen, name: []const u8, const_type: ast.Type, value: ast.Expr) !void {
+        var element_type = const_type;
+        var dims = std.ArrayList(u8){};
+        defer dims.deinit(self.allocator);
+        while (element_type == .array) {
+            const size = element_type.array.size orelse 0;
+            try dims.writer(self.allocator).print("[{d}]", .{size});
+            element_type = element_type.array.element.*;
+        }
+        try self.output.appendSlice(self.allocator, "static const ");
+        try self.output.appendSlice(self.allocator, self.typeToC(element_type));
+        try self.output.appendSlice(self.allocator, " ");
+        try self.output.appendSlice(self.allocator, name);
+        try self.output.appendSlice(self.allocator, dims.items);
+        try self.output.appendSlice(self.allocator, " = ");
+        try self.generateConstInitializer(value);
+        try self.output.appendSlice(self.allocator, ";\n");
+    }
+    
+    /// 🆕 常量初始化器：嵌套的数组和结构体都写成花括号形式（C 的静态初始化不能使用复合字面量）
+    fn generateConstInitializer(self: *CodeGen, value: ast.Expr) !void {
+        switch (value) {
+            .array_literal => |elements| {
+                try self.output.appendSlice(self.allocator, "{ ");
+                for (elements, 0..) |element, i| {
+                    if (i > 0) try self.output.appendSlice(self.allocator, ", ");
+                    try self.generateConstInitializer(element);
+                }
+                try self.output.appendSlice(self.allocator, " }");
+            },
+            .struct_init => |si| {
+                try self.output.appendSlice(self.allocator, "{ ");
+                for (si.fields, 0..) |field, i| {
+                    if (i > 0) try self.output.appendSlice(self.allocator, ", ");
+                    try self.output.writer(self.allocator).print(".{s} = ", .{field.name});
+                    try self.generateConstInitializer(field.value);
+                }
+                try self.output.appendSlice(self.allocator, " }");
+            },
+            else => try self.generateExpr(value),
         }
     }
     
@@ -170,6 +992,12 @@ pub const CodeGen = struct {
             try self.output.writer(self.allocator).print("{d}", .{i});
         }
         
+        // 🆕 分离编译：外部类型只需要构造器原型
+        if (self.declarations_only) {
+            try self.output.appendSlice(self.allocator, ");\n\n");
+            return;
+        }
+        
         try self.output.appendSlice(self.allocator, ") {\n");
         try self.output.appendSlice(self.allocator, "    ");
         try self.output.appendSlice(self.allocator, enum_name);
@@ -232,6 +1060,9 @@ pub const CodeGen = struct {
     
     // 🆕 生成方法实现
     fn generateMethodImpl(self: *CodeGen, type_name: []const u8, method: ast.FunctionDecl) !void {
+        // 🆕 方法中的 lambda 在方法之前声明（提升时的外层函数名为 TypeName_methodName）
+        try self.declareLambdas(try std.fmt.allocPrint(self.arena.allocator(), "{s}_{s}", .{ type_name, method.name }));
+        
         // 返回类型
         try self.output.appendSlice(self.allocator, self.typeToC(method.return_type));
         try self.output.appendSlice(self.allocator, " ");
@@ -258,12 +1089,14 @@ pub const CodeGen = struct {
         }
         
         try self.output.appendSlice(self.allocator, ") {\n");
+        try self.generateFrameMarker(type_name, method.name);
+        try self.recordParamTypes(method.params, layout.Bindings.empty);
         
         // 生成方法体
         // 🆕 v0.1.6: 特殊处理最后一个表达式语句 - 应该生成 return
         for (method.body, 0..) |stmt, i| {
             const is_last = (i == method.body.len - 1);
-            const is_non_void = method.return_type != .void;
+            const is_non_void = method.return_type != .void and method.return_type != .never;
             
             // 如果是最后一个语句，且是表达式语句，且方法返回非void，生成return
             if (is_last and stmt == .expr and is_non_void) {
@@ -285,12 +1118,37 @@ pub const CodeGen = struct {
             return;
         }
         
+        // 🆕 extern fn：只生成原型
+        if (func.is_extern) return self.generateExternFunction(func);
+        
+        // 🆕 从这个函数提升出的 lambda 在它之前声明
+        if (!self.declarations_only) try self.declareLambdas(func.name);
+        if (func.isLambda()) return self.generateLambdaFunction(func);
+        
+        // 🆕 分离编译：模块私有函数不导出符号
+        if (self.internal_linkage and !func.is_public) {
+            try self.output.appendSlice(self.allocator, "static ");
+        }
+        
+        // 🆕 @asm 函数：函数体是目标架构的汇编（类型检查已确认存在）
+        if (func.asmFor(@tagName(builtin.cpu.arch))) |text| return self.generateAsmFunction(func, text);
+        
         // 生成函数签名
+        if (func.return_type == .never) {
+            try self.output.appendSlice(self.allocator, "_Noreturn ");
+        }
         try self.output.appendSlice(self.allocator, self.typeToC(func.return_type));
         try self.output.appendSlice(self.allocator, " ");
-        try self.output.appendSlice(self.allocator, func.name);
+        try self.output.appendSlice(self.allocator, if (func.isEntryPoint()) ast.ENTRY_SYMBOL else func.name);
         try self.output.appendSlice(self.allocator, "(");
         
+        // 🆕 参数中的窄整数变量
+        self.narrow_var_types.clearRetainingCapacity();
+        for (func.params) |param| {
+            if (isNarrowInt(param.type)) try self.narrow_var_types.put(param.name, param.type);
+        }
+        try self.recordParamTypes(func.params, layout.Bindings.empty);
+        
         // 生成参数
         for (func.params, 0..) |param, i| {
             if (i > 0) try self.output.appendSlice(self.allocator, ", ");
@@ -299,13 +1157,24 @@ pub const CodeGen = struct {
             try self.output.appendSlice(self.allocator, param.name);
         }
         
-        try self.output.appendSlice(self.allocator, ") {\n");
+        // 🆕 分离编译：外部函数只生成原型
+        if (self.declarations_only) {
+            try self.output.appendSlice(self.allocator, ");\n");
+            return;
+        }
         
-        // 生成函数体
-        // 🆕 v0.1.6: 特殊处理最后一个表达式语句 - 应该生成 return
+        try self.output.appendSlice(self.allocator, ") {\n");
+        try self.generateFrameMarker(null, func.name);
+        try self.generateFunctionBody(func);
+        try self.output.appendSlice(self.allocator, "}\n");
+    }
+    
+    /// 生成函数体
+    /// 🆕 v0.1.6: 特殊处理最后一个表达式语句 - 应该生成 return
+    fn generateFunctionBody(self: *CodeGen, func: ast.FunctionDecl) !void {
         for (func.body, 0..) |stmt, i| {
             const is_last = (i == func.body.len - 1);
-            const is_non_void = func.return_type != .void;
+            const is_non_void = func.return_type != .void and func.return_type != .never;
             
             // 如果是最后一个语句，且是表达式语句，且函数返回非void，生成return
             if (is_last and stmt == .expr and is_non_void) {
@@ -316,8 +1185,71 @@ pub const CodeGen = struct {
                 try self.generateStmt(stmt);
             }
         }
-
-        try self.output.appendSlice(self.allocator, "}\n");
+    }
+    
+    /// 🆕 @asm 函数：naked 函数，只包含一条基本汇编语句
+    ///
+    ///   __attribute__((naked, noinline)) int64_t sys_write(int32_t fd, ...) {
+    ///       __asm__ volatile("\x6d\x6f...");
+    ///   }
+    ///
+    /// 参数按 C 调用约定在寄存器中，汇编自己返回；文本逐字节转义，不受 C 字符串语法影响
+    fn generateAsmFunction(self: *CodeGen, func: ast.FunctionDecl, text: []const u8) !void {
+        if (!self.declarations_only) {
+            try self.output.appendSlice(self.allocator, "__attribute__((naked, noinline)) ");
+        }
+        if (func.return_type == .never) {
+            try self.output.appendSlice(self.allocator, "_Noreturn ");
+        }
+        try self.output.appendSlice(self.allocator, self.typeToC(func.return_type));
+        try self.output.appendSlice(self.allocator, " ");
+        try self.output.appendSlice(self.allocator, func.name);
+        try self.output.appendSlice(self.allocator, "(");
+        for (func.params, 0..) |param, i| {
+            if (i > 0) try self.output.appendSlice(self.allocator, ", ");
+            try self.output.appendSlice(self.allocator, self.typeToC(param.type));
+            try self.output.appendSlice(self.allocator, " ");
+            try self.output.appendSlice(self.allocator, param.name);
+        }
+        if (func.params.len == 0) try self.output.appendSlice(self.allocator, "void");
+        
+        if (self.declarations_only) {
+            try self.output.appendSlice(self.allocator, ");\n");
+            return;
+        }
+        try self.output.appendSlice(self.allocator, ") {\n    __asm__ volatile(\"");
+        for (text) |b| {
+            try self.output.writer(self.allocator).print("\\x{x:0>2}", .{b});
+        }
+        try self.output.appendSlice(self.allocator, "\");\n}\n");
+    }
+    
+    /// 🆕 extern fn 的原型
+    /// @link_name 通过汇编标签绑定到原始符号名（C 编译器不再添加平台前缀），
+    /// 因此 Paw 中的函数名可以与 C 头文件中的声明不同而互不冲突
+    fn generateExternFunction(self: *CodeGen, func: ast.FunctionDecl) !void {
+        if (func.return_type == .never) {
+            try self.output.appendSlice(self.allocator, "_Noreturn ");
+        }
+        try self.output.appendSlice(self.allocator, "extern ");
+        try self.output.appendSlice(self.allocator, self.typeToC(func.return_type));
+        try self.output.appendSlice(self.allocator, " ");
+        try self.output.appendSlice(self.allocator, func.name);
+        try self.output.appendSlice(self.allocator, "(");
+        for (func.params, 0..) |param, i| {
+            if (i > 0) try self.output.appendSlice(self.allocator, ", ");
+            try self.output.appendSlice(self.allocator, self.typeToC(param.type));
+            try self.output.appendSlice(self.allocator, " ");
+            try self.output.appendSlice(self.allocator, param.name);
+        }
+        if (func.params.len == 0) try self.output.appendSlice(self.allocator, "void");
+        try self.output.appendSlice(self.allocator, ")");
+        if (func.link_name) |link_name| {
+            if (link_name.resolve(@tagName(builtin.os.tag))) |symbol| {
+                try self.output.writer(self.allocator).print(" __asm__(\"{s}\")", .{symbol});
+            }
+        }
+        try self.output.appendSlice(self.allocator, ";\n");
     }
     
     fn generateTypeDecl(self: *CodeGen, type_decl: ast.TypeDecl) !void {
@@ -352,10 +1284,19 @@ pub const CodeGen = struct {
                     try self.output.appendSlice(self.allocator, ";\n");
                 }
                 try self.output.appendSlice(self.allocator, "};\n\n");
+                try self.generateStructConstants(type_decl.name);
                 
-                // 生成方法实现
-                for (st.methods) |method| {
-                    try self.generateMethodImpl(type_decl.name, method);
+                // 生成方法实现（外部类型的方法实现在其他编译单元）
+                if (!self.declarations_only) {
+                    for (st.methods) |method| {
+                        const mark = self.output.items.len;
+                        self.startFunctionClock();
+                        defer self.function_deadline_ns = null;
+                        self.generateMethodImpl(type_decl.name, method) catch |err| {
+                            const name = try std.fmt.allocPrint(self.arena.allocator(), "{s}.{s}", .{ type_decl.name, method.name });
+                            try self.recordFunctionError(name, method.location, mark, err);
+                        };
+                    }
                 }
             },
             .enum_type => |et| {
@@ -437,6 +1378,9 @@ pub const CodeGen = struct {
             .trait_type => {
                 // C 中没有 trait 概念，跳过
             },
+            .alias => {
+                // 🆕 别名在解析后已展开，不生成 typedef
+            },
         }
     }
     
@@ -472,6 +1416,7 @@ pub const CodeGen = struct {
     // ============================================================================
     
     fn generateStmt(self: *CodeGen, stmt: ast.Stmt) !void {
+        try self.checkFunctionClock();
         switch (stmt) {
             .expr => |expr| {
                 // 🆕 v0.2.0: 特殊处理 if 表达式作为语句
@@ -515,6 +1460,13 @@ pub const CodeGen = struct {
             },
             // 🆕 赋值语句
             .assign => |assign| {
+                // 🆕 `_ = expr;`：只求值，不赋值
+                if (assign.target.isDiscardTarget()) {
+                    try self.output.appendSlice(self.allocator, "(void)(");
+                    _ = try self.generateExpr(assign.value);
+                    try self.output.appendSlice(self.allocator, ");\n");
+                    return;
+                }
                 _ = try self.generateExpr(assign.target);
                 try self.output.appendSlice(self.allocator, " = ");
                 _ = try self.generateExpr(assign.value);
@@ -538,6 +1490,13 @@ pub const CodeGen = struct {
             },
             .let_decl => |let| {
                 var type_name: ?[]const u8 = null;
+                // 🆕 窄整数变量：显式标注的类型，或初始化表达式的窄整数类型
+                const narrow_type: ?ast.Type = if (let.type) |t|
+                    (if (isNarrowInt(t)) t else null)
+                else if (let.init) |init_expr|
+                    self.narrowIntType(init_expr)
+                else
+                    null;
                 var is_array = false;
                 var array_size: ?usize = null;
                 
@@ -606,6 +1565,16 @@ pub const CodeGen = struct {
                     } else if (init_expr == .string_interp) {
                         // 🆕 字符串插值返回 char*
                         try self.output.appendSlice(self.allocator, "char*");
+                    } else if (init_expr == .loop_expr) {
+                        // 🆕 loop 表达式：类型检查器记录的 break 值类型
+                        try self.output.appendSlice(self.allocator, self.typeToC(init_expr.loop_expr.result_type.*));
+                    } else if (init_expr == .byte_literal) {
+                        try self.output.appendSlice(self.allocator, "uint8_t");
+                    } else if (init_expr == .byte_string_literal) {
+                        // 🆕 字节串变量：uint8_t name[N]（sizeof 和越界检查需要长度）
+                        is_array = true;
+                        array_size = init_expr.byte_string_literal.len;
+                        try self.output.appendSlice(self.allocator, "uint8_t");
                     } else if (init_expr == .static_method_call) {
                         // 🆕 静态方法调用：Vec<i32>::new() → Vec_i32
                         const smc = init_expr.static_method_call;
@@ -624,17 +1593,37 @@ pub const CodeGen = struct {
                             try self.output.appendSlice(self.allocator, smc.type_name);
                             type_name = smc.type_name;
                         }
+                    } else if (init_expr == .as_expr and init_expr.as_expr.resolved_from.*) {
+                        // 🆕 From 转换：变量是目标类型
+                        type_name = init_expr.as_expr.target_type.named;
+                        try self.output.appendSlice(self.allocator, type_name.?);
+                    } else if (self.operatorResultType(init_expr)) |result_type| {
+                        // 🆕 用户类型上的运算符：运算符方法的结果类型
+                        if (result_type == .named) type_name = result_type.named;
+                        try self.output.appendSlice(self.allocator, self.typeToC(result_type));
+                    } else if (self.functionValueType(init_expr)) |func_type| {
+                        // 🆕 函数值（函数名、lambda）：PawFn
+                        try self.output.appendSlice(self.allocator, self.typeToC(func_type));
                     } else if (init_expr == .call and init_expr.call.callee.* == .identifier) {
                         // 🆕 检查是否是enum构造器调用
                         const callee_name = init_expr.call.callee.identifier;
+                        const callee_type = self.local_types.get(callee_name);
                         if (self.enum_variants.get(callee_name)) |enum_name| {
                             // 是enum构造器，使用enum类型
                             try self.output.appendSlice(self.allocator, enum_name);
                             type_name = enum_name;
+                        } else if (callee_type != null and callee_type.? == .function) {
+                            // 🆕 通过函数值调用：函数类型的返回类型
+                            try self.output.appendSlice(self.allocator, self.typeToC(callee_type.?.function.return_type.*));
+                        } else if (self.returnedFunctionType(init_expr)) |func_type| {
+                            // 🆕 返回函数值的函数：pick(true)
+                            try self.output.appendSlice(self.allocator, self.typeToC(func_type));
                         } else {
                             // 普通函数调用，默认int32_t
                             try self.output.appendSlice(self.allocator, "int32_t");
                         }
+                    } else if (narrow_type) |t| {
+                        try self.output.appendSlice(self.allocator, self.typeToC(t));
                     } else {
                         try self.output.appendSlice(self.allocator, "int32_t");
                     }
@@ -652,6 +1641,8 @@ pub const CodeGen = struct {
                 if (let.init) |init_expr| {
                         if (init_expr == .array_literal) {
                             actual_size = init_expr.array_literal.len;
+                        } else if (init_expr == .byte_string_literal) {
+                            actual_size = init_expr.byte_string_literal.len;
                         }
                     }
                     
@@ -665,7 +1656,17 @@ pub const CodeGen = struct {
                 
                 if (let.init) |init_expr| {
                     try self.output.appendSlice(self.allocator, " = ");
-                    _ = try self.generateExpr(init_expr);
+                    if (is_array and init_expr == .byte_string_literal) {
+                        // 🆕 数组的初始化器：不带指针转换的字符串字面量（长度正好是 N，不追加 NUL）
+                        const bytes = init_expr.byte_string_literal;
+                        if (bytes.len == 0) {
+                            try self.output.appendSlice(self.allocator, "{}");
+                        } else {
+                            try self.generateByteString(bytes);
+                        }
+                    } else {
+                        _ = try self.generateExpr(init_expr);
+                    }
                     
                     // 记录struct类型
                     if (init_expr == .struct_init) {
@@ -678,8 +1679,35 @@ pub const CodeGen = struct {
                 if (type_name) |tn| {
                     try self.var_types.put(let.name, tn);
                 }
+                // 🆕 与生成的 C 声明一致的变量类型（未知时按 i32 处理）
+                const value_type: ?ast.Type = if (let.type) |t|
+                    (if (t == .array) null else layout.resolve(t, self.current_function_bindings orelse layout.Bindings.empty))
+                else if (let.init) |init_expr| switch (init_expr) {
+                    .string_literal, .string_interp => ast.Type.string,
+                    .loop_expr => |le| le.result_type.*,
+                    .byte_literal => ast.Type.u8,
+                    .identifier, .lambda => self.functionValueType(init_expr) orelse narrow_type,
+                    .call => self.returnedFunctionType(init_expr) orelse narrow_type,
+                    .binary => self.operatorResultType(init_expr) orelse narrow_type,
+                    else => narrow_type,
+                } else null;
+                if (value_type) |t| {
+                    try self.local_types.put(let.name, t);
+                } else {
+                    _ = self.local_types.remove(let.name);
+                }
+                if (narrow_type) |t| {
+                    try self.narrow_var_types.put(let.name, t);
+                } else {
+                    _ = self.narrow_var_types.remove(let.name);
+                }
             },
             .loop_stmt => |loop| {
+                const label_id = self.next_label_id;
+                self.next_label_id += 1;
+                try self.loop_labels.append(self.allocator, .{ .name = loop.label, .id = label_id });
+                defer _ = self.loop_labels.pop();
+                
                 if (loop.iterator) |iter| {
                     // 🆕 loop i in collection { }
                     try self.generateLoopIterator(iter, loop.body);
@@ -688,24 +1716,43 @@ pub const CodeGen = struct {
                     try self.output.appendSlice(self.allocator, "while (");
                     try self.generateExpr(condition);
                     try self.output.appendSlice(self.allocator, ") {\n");
-                    for (loop.body) |body_stmt| {
-                        try self.generateStmt(body_stmt);
-                    }
+                    try self.generateLoopBody(loop.body);
                     try self.output.appendSlice(self.allocator, "}\n");
                 } else {
                     // loop { }
                     try self.output.appendSlice(self.allocator, "for (;;) {\n");
-                    for (loop.body) |body_stmt| {
-                        try self.generateStmt(body_stmt);
-                    }
+                    try self.generateLoopBody(loop.body);
                     try self.output.appendSlice(self.allocator, "}\n");
                 }
+                
+                // 🆕 break 'label 的跳转目标
+                if (loop.label != null) {
+                    try self.output.writer(self.allocator).print("paw_break_{d}: ;\n", .{label_id});
+                }
             },
-            .break_stmt => {
-                try self.output.appendSlice(self.allocator, "break;\n");
+            .break_stmt => |brk| {
+                const target = self.findLoop(brk.label);
+                if (target != null and (target.?.name != null or target.?.is_expr)) {
+                    const loop = target.?;
+                    if (loop.is_expr) {
+                        if (brk.value) |value| {
+                            try self.output.writer(self.allocator).print("paw_loop_value_{d} = ", .{loop.id});
+                            try self.generateExpr(value);
+                            try self.output.appendSlice(self.allocator, ";\n");
+                        }
+                    }
+                    try self.output.writer(self.allocator).print("goto paw_break_{d};\n", .{loop.id});
+                } else {
+                    try self.output.appendSlice(self.allocator, "break;\n");
+                }
             },
-            .continue_stmt => {
-                try self.output.appendSlice(self.allocator, "continue;\n");
+            .continue_stmt => |label| {
+                const target = self.findLoop(label);
+                if (target != null and target.?.name != null) {
+                    try self.output.writer(self.allocator).print("goto paw_continue_{d};\n", .{target.?.id});
+                } else {
+                    try self.output.appendSlice(self.allocator, "continue;\n");
+                }
             },
             .while_loop => |while_loop| {
                 try self.output.appendSlice(self.allocator, "while (");
@@ -740,8 +1787,8 @@ pub const CodeGen = struct {
 
     fn generateExpr(self: *CodeGen, expr: ast.Expr) !void {
         switch (expr) {
-            .int_literal => |i| {
-                const str = try std.fmt.allocPrint(self.allocator, "{d}", .{i});
+            .int_literal => |literal| {
+                const str = try std.fmt.allocPrint(self.allocator, "{d}", .{literal.value});
                 defer self.allocator.free(str);
                 try self.output.appendSlice(self.allocator, str);
             },
@@ -771,9 +1818,34 @@ pub const CodeGen = struct {
                 defer self.allocator.free(str);
                 try self.output.appendSlice(self.allocator, str);
             },
+            .byte_literal => |b| {
+                // 🆕 b'A' -> ((uint8_t)65)
+                try self.output.writer(self.allocator).print("((uint8_t){d})", .{b});
+            },
+            .byte_string_literal => |bytes| {
+                // 🆕 b"..." -> 指向静态数据的 uint8_t*；赋给变量时变量是 uint8_t name[N]（见 let）
+                try self.output.appendSlice(self.allocator, "((uint8_t*)");
+                try self.generateByteString(bytes);
+                try self.output.appendSlice(self.allocator, ")");
+            },
             .bool_literal => |b| try self.output.appendSlice(self.allocator, if (b) "true" else "false"),
-            .identifier => |id| try self.output.appendSlice(self.allocator, id),
+            .identifier => |id| {
+                // 🆕 函数名作为值：没有环境的 PawFn，程序入口以 ENTRY_SYMBOL 生成
+                if (self.functionValueType(expr) != null) {
+                    const func = self.function_table.get(id).?;
+                    try self.output.writer(self.allocator).print("((PawFn){{ NULL, (void*){s} }})", .{if (func.isEntryPoint()) ast.ENTRY_SYMBOL else id});
+                    return;
+                }
+                try self.output.appendSlice(self.allocator, id);
+            },
+            .lambda => |lambda| try self.generateLambdaValue(lambda),
             .binary => |bin| {
+                // 🆕 用户类型上的运算符：调用左操作数类型的方法
+                if (bin.resolved_operator.*) |type_name| return self.generateOperatorCall(type_name, bin);
+                // 🆕 C 会把窄整数提升为 int 再运算，结果需要截回原类型才能按位宽回绕
+                // （例如 Byte 的 250 + 10 == 4，与 LLVM 后端的 i8 运算一致）
+                const narrow = self.narrowIntType(expr);
+                if (narrow) |t| try self.output.writer(self.allocator).print("(({s})", .{self.typeToC(t)});
                 try self.output.appendSlice(self.allocator, "(");
                 _ = try self.generateExpr(bin.left.*);
                 try self.output.appendSlice(self.allocator, " ");
@@ -781,14 +1853,24 @@ pub const CodeGen = struct {
                 try self.output.appendSlice(self.allocator, " ");
                 _ = try self.generateExpr(bin.right.*);
                 try self.output.appendSlice(self.allocator, ")");
+                if (narrow != null) try self.output.appendSlice(self.allocator, ")");
             },
             .unary => |un| {
+                const narrow = self.narrowIntType(expr);
+                if (narrow) |t| try self.output.writer(self.allocator).print("(({s})", .{self.typeToC(t)});
                 try self.output.appendSlice(self.allocator, "(");
                 try self.output.appendSlice(self.allocator, self.unaryOpToC(un.op));
                 _ = try self.generateExpr(un.operand.*);
                 try self.output.appendSlice(self.allocator, ")");
+                if (narrow != null) try self.output.appendSlice(self.allocator, ")");
             },
             .call => |call| {
+                // 🆕 into(x)：From 转换调用 Target_from(x)，同类型时就是 x
+                if (call.resolved_into.*) {
+                    if (ast.intoTarget(call)) |target| return self.generateFromCall(target, call.args[0]);
+                    _ = try self.generateExpr(call.args[0]);
+                    return;
+                }
                 // 🆕 检查是否是方法调用 (obj.method 形式)
                 if (call.callee.* == .field_access) {
                     const field = call.callee.field_access;
@@ -838,65 +1920,64 @@ pub const CodeGen = struct {
                             _ = try self.generateExpr(arg);
                         }
                         try self.output.appendSlice(self.allocator, ")");
-                    } else if (std.mem.eql(u8, func_name, "println")) {
-                        // 🆕 内置函数 println
-                        try self.output.appendSlice(self.allocator, "printf(\"%s\\n\", ");
-                        if (call.args.len > 0) {
-                            _ = try self.generateExpr(call.args[0]);
-                        } else {
-                            try self.output.appendSlice(self.allocator, "\"\"");
-                        }
+                    } else if (std.mem.eql(u8, func_name, "paw_write") and call.args.len == 2) {
+                        // 🆕 内置函数 paw_write(fd, text)：println 等 prelude 函数的输出原语
+                        try self.output.appendSlice(self.allocator, "paw_rt_write(");
+                        _ = try self.generateExpr(call.args[0]);
+                        try self.output.appendSlice(self.allocator, ", ");
+                        _ = try self.generateExpr(call.args[1]);
                         try self.output.appendSlice(self.allocator, ")");
-                    } else if (std.mem.eql(u8, func_name, "print")) {
-                        // 🆕 内置函数 print
-                        try self.output.appendSlice(self.allocator, "printf(\"%s\", ");
-                        if (call.args.len > 0) {
-                            _ = try self.generateExpr(call.args[0]);
-                        } else {
-                            try self.output.appendSlice(self.allocator, "\"\"");
+                    } else if (std.mem.eql(u8, func_name, "paw_write_f64") and call.args.len == 4) {
+                        // 🆕 内置函数 paw_write_f64(fd, value, style, digits)：按指定格式输出浮点数
+                        try self.output.appendSlice(self.allocator, "paw_rt_write_f64(");
+                        for (call.args, 0..) |arg, i| {
+                            if (i > 0) try self.output.appendSlice(self.allocator, ", ");
+                            _ = try self.generateExpr(arg);
                         }
                         try self.output.appendSlice(self.allocator, ")");
-                    } else if (std.mem.eql(u8, func_name, "eprintln")) {
-                        // 🆕 内置函数 eprintln
-                        try self.output.appendSlice(self.allocator, "fprintf(stderr, \"%s\\n\", ");
-                        if (call.args.len > 0) {
-                            _ = try self.generateExpr(call.args[0]);
-                        } else {
-                            try self.output.appendSlice(self.allocator, "\"\"");
-                        }
+                    } else if (std.mem.eql(u8, func_name, "paw_log") and call.args.len == 2) {
+                        // 🆕 内置函数 paw_log(level, msg)
+                        try self.output.appendSlice(self.allocator, "paw_rt_log(");
+                        _ = try self.generateExpr(call.args[0]);
+                        try self.output.appendSlice(self.allocator, ", ");
+                        _ = try self.generateExpr(call.args[1]);
                         try self.output.appendSlice(self.allocator, ")");
-                    } else if (std.mem.eql(u8, func_name, "eprint")) {
-                        // 🆕 内置函数 eprint
-                        try self.output.appendSlice(self.allocator, "fprintf(stderr, \"%s\", ");
-                        if (call.args.len > 0) {
-                            _ = try self.generateExpr(call.args[0]);
-                        } else {
-                            try self.output.appendSlice(self.allocator, "\"\"");
-                        }
+                    } else if (std.mem.eql(u8, func_name, "gen_int") and call.args.len == 2) {
+                        // 🆕 内置函数 gen_int(lo, hi)
+                        try self.output.appendSlice(self.allocator, "paw_rt_gen_int(");
+                        _ = try self.generateExpr(call.args[0]);
+                        try self.output.appendSlice(self.allocator, ", ");
+                        _ = try self.generateExpr(call.args[1]);
                         try self.output.appendSlice(self.allocator, ")");
+                    } else if (std.mem.eql(u8, func_name, "gen_string") and call.args.len == 1) {
+                        // 🆕 内置函数 gen_string(len)
+                        try self.output.appendSlice(self.allocator, "paw_rt_gen_string(");
+                        _ = try self.generateExpr(call.args[0]);
+                        try self.output.appendSlice(self.allocator, ")");
+                    } else if (call.resolved_receiver.*) |type_name| {
+                        // 🆕 直接调用 trait 方法：show(p) → TypeName_show(&p, ...)
+                        try self.generateDirectMethodCall(type_name, func_name, call.args);
+                    } else if (self.calleeFunctionType(call.callee.*)) |func_type| {
+                        // 🆕 通过函数类型的变量调用
+                        try self.generateIndirectCall(call.callee.*, func_type, call.args);
                     } else {
                         // 普通函数调用（可能是泛型）
                         // 🆕 检查是否是泛型函数
                         const actual_func_name = blk: {
                             if (self.function_table.get(func_name)) |func| {
-                                if (func.type_params.len > 0) {
-                                    // 泛型函数：收集参数类型并获取修饰后的名称
-                                    var arg_types = std.ArrayList(ast.Type){};
-                                    defer arg_types.deinit(self.allocator);
-                                    
-                                    // 🆕 从实际参数推导类型
-                                    for (call.args) |arg| {
-                                        const arg_type = self.inferExprType(arg);
-                                        try arg_types.append(self.allocator, arg_type);
-                                    }
-                                    
-                                    const mangled = self.generic_context.inferGenericInstance(
-                                        func_name,
-                                        try arg_types.toOwnedSlice(self.allocator),
-                                    ) catch func_name;
-                                    
-                                    break :blk mangled;
+                                if (func.type_params.len > 0) {
+                                    // 泛型函数：类型实参（类型检查器推导）对应的实例名
+                                    // 🆕 单态化函数体中按当前实例的绑定替换类型参数
+                                    const type_args = try self.generic_context.callTypeArgs(
+                                        func,
+                                        call.args,
+                                        call.resolved_type_args.*,
+                                        self.current_function_bindings orelse layout.Bindings.empty,
+                                    ) orelse break :blk func_name;
+                                    break :blk try self.generic_context.monomorphizer.recordInstance(func_name, type_args);
                                 }
+                                // 🆕 递归调用 main
+                                if (func.isEntryPoint()) break :blk ast.ENTRY_SYMBOL;
                             }
                             break :blk func_name;
                         };
@@ -909,6 +1990,9 @@ pub const CodeGen = struct {
                         }
                         try self.output.appendSlice(self.allocator, ")");
                     }
+                } else if (self.calleeFunctionType(call.callee.*)) |func_type| {
+                    // 🆕 调用表达式的值：make_adder(1)(x)、(|x: i32| -> i32 { x })(1)
+                    try self.generateIndirectCall(call.callee.*, func_type, call.args);
                 } else {
                     // 其他形式的调用
                     _ = try self.generateExpr(call.callee.*);
@@ -953,6 +2037,11 @@ pub const CodeGen = struct {
                 try self.output.appendSlice(self.allocator, field.field);
             },
             .if_expr => |if_expr| {
+                // 🆕 有分支不会产生值（break / continue / return）时不能用三元运算符
+                if (if_expr.then_branch.diverges() or (if_expr.else_branch != null and if_expr.else_branch.?.diverges())) {
+                    try self.generateDivergingIfExpr(if_expr);
+                    return;
+                }
                 try self.output.appendSlice(self.allocator, "(");
                 _ = try self.generateExpr(if_expr.condition.*);
                 try self.output.appendSlice(self.allocator, " ? ");
@@ -1065,24 +2154,35 @@ pub const CodeGen = struct {
                 // 🆕 生成数组索引
                 _ = try self.generateExpr(ai.array.*);
                 try self.output.appendSlice(self.allocator, "[");
+                // 🆕 长度已知的数组（类型检查器按作用域中的类型记录）：越界时 panic（读取和元素赋值都检查）
+                const length = ai.length.*;
+                if (length != null) try self.output.appendSlice(self.allocator, "paw_index_check((int64_t)(");
                 _ = try self.generateExpr(ai.index.*);
+                if (length) |len| try self.output.writer(self.allocator).print("), {d})", .{len});
                 try self.output.appendSlice(self.allocator, "]");
             },
             .block => |stmts| {
                 // 🆕 实现 block 表达式
-                // 简化实现：只返回最后一个表达式的值
-                // 注意：不支持 block 中的变量声明（需要 GCC/Clang 扩展）
-                if (stmts.len > 0) {
+                // 只有尾表达式时直接生成它；否则使用 GNU 语句表达式执行前面的语句
+                if (stmts.len == 0) {
+                    try self.output.appendSlice(self.allocator, "0");
+                } else if (stmts.len == 1 and stmts[0] == .expr) {
+                    try self.generateExpr(stmts[0].expr);
+                } else {
+                    try self.output.appendSlice(self.allocator, "({\n");
+                    for (stmts[0 .. stmts.len - 1]) |stmt| {
+                        try self.generateStmt(stmt);
+                    }
                     const last_stmt = stmts[stmts.len - 1];
                     if (last_stmt == .expr) {
-                        // 返回最后一个表达式
                         try self.generateExpr(last_stmt.expr);
+                        try self.output.appendSlice(self.allocator, ";\n");
                     } else {
-                        // 如果最后不是表达式，返回 0
-                        try self.output.appendSlice(self.allocator, "0");
+                        // 最后不是表达式时块的值为 0
+                        try self.generateStmt(last_stmt);
+                        try self.output.appendSlice(self.allocator, "0;\n");
                     }
-                } else {
-                    try self.output.appendSlice(self.allocator, "0");
+                    try self.output.appendSlice(self.allocator, "})");
                 }
             },
             // 🆕 is 表达式（模式匹配）
@@ -1104,14 +2204,57 @@ pub const CodeGen = struct {
             .try_expr => |inner| {
                 try self.generateTryExpr(inner.*);
             },
+            // 🆕 loop 表达式：GNU 语句表达式 + goto，break 值写入临时变量
+            .loop_expr => |loop| {
+                const id = self.next_label_id;
+                self.next_label_id += 1;
+                try self.loop_labels.append(self.allocator, .{ .name = loop.label, .id = id, .is_expr = true });
+                defer _ = self.loop_labels.pop();
+                
+                const has_value = loop.result_type.* != .void;
+                try self.output.appendSlice(self.allocator, "({\n");
+                if (has_value) {
+                    try self.output.writer(self.allocator).print("{s} paw_loop_value_{d};\n", .{ self.typeToC(loop.result_type.*), id });
+                }
+                try self.output.appendSlice(self.allocator, "for (;;) {\n");
+                try self.generateLoopBody(loop.body);
+                try self.output.writer(self.allocator).print("}}\npaw_break_{d}: ;\n", .{id});
+                if (has_value) {
+                    try self.output.writer(self.allocator).print("paw_loop_value_{d};\n", .{id});
+                }
+                try self.output.appendSlice(self.allocator, "})");
+            },
             // 🆕 v0.1.7: as 类型转换
             .as_expr => |as_cast| {
+                // 🆕 From 转换：调用目标类型的 from 方法
+                if (as_cast.resolved_from.*) return self.generateFromCall(as_cast.target_type.named, as_cast.value.*);
                 try self.output.appendSlice(self.allocator, "((");
                 try self.output.appendSlice(self.allocator, self.typeToC(as_cast.target_type));
                 try self.output.appendSlice(self.allocator, ")(");
+                // 🆕 转换为 char 时检查值是否是 Unicode 标量值
+                const check_char = as_cast.target_type == .char and self.needsCharCheck(as_cast.value.*);
+                if (check_char) try self.output.appendSlice(self.allocator, "paw_char_check((int64_t)(");
                 _ = try self.generateExpr(as_cast.value.*);
+                if (check_char) try self.output.appendSlice(self.allocator, "))");
                 try self.output.appendSlice(self.allocator, "))");
             },
+            .type_query => |tq| {
+                // 🆕 size_of<T>() / align_of<T>()：使用共享布局规则折叠为常量
+                const bindings: layout.Bindings = if (self.current_method_context) |ctx|
+                    .{ .type_params = ctx.type_params, .type_args = ctx.type_args }
+                else
+                    self.current_function_bindings orelse layout.Bindings.empty;
+                
+                if (layout.ofType(tq.type, &self.type_decls, bindings)) |l| {
+                    const value = if (tq.kind == .size_of) l.size else l.alignment;
+                    try self.output.writer(self.allocator).print("{d}", .{value});
+                } else |_| {
+                    // 无法静态确定时交给 C 编译器
+                    try self.output.appendSlice(self.allocator, if (tq.kind == .size_of) "(int32_t)sizeof(" else "(int32_t)_Alignof(");
+                    try self.output.appendSlice(self.allocator, self.typeToC(tq.type));
+                    try self.output.appendSlice(self.allocator, ")");
+                }
+            },
             else => {
                 // 其他表达式暂时生成 0
                 try self.output.appendSlice(self.allocator, "0");
@@ -1119,6 +2262,147 @@ pub const CodeGen = struct {
         }
     }
     
+    /// 🆕 From 转换 `value as Target` / into(value)：Target_from(value)
+    fn generateFromCall(self: *CodeGen, target: []const u8, value: ast.Expr) (std.mem.Allocator.Error)!void {
+        try self.output.writer(self.allocator).print("{s}_{s}(", .{ target, ast.FROM_METHOD });
+        _ = try self.generateExpr(value);
+        try self.output.appendSlice(self.allocator, ")");
+    }
+    
+    /// 🆕 直接调用 trait 方法（类型检查器已确认只有一个 trait 提供该方法）。
+    /// 方法接收 self 的指针，不是变量的接收者先求值到临时变量：
+    ///   show(p)             ->  Point_show(&p)
+    ///   show(make_point())  ->  ({ Point paw_recv_3 = make_point(); Point_show(&paw_recv_3); })
+    fn generateDirectMethodCall(self: *CodeGen, type_name: []const u8, name: []const u8, args: []ast.Expr) (std.mem.Allocator.Error)!void {
+        const w = self.output.writer(self.allocator);
+        const temp_id: ?usize = if (args[0] == .identifier) null else blk: {
+            const id = self.next_label_id;
+            self.next_label_id += 1;
+            break :blk id;
+        };
+        if (temp_id) |id| {
+            try w.print("({{ {s} paw_recv_{d} = ", .{ type_name, id });
+            _ = try self.generateExpr(args[0]);
+            try w.print("; {s}_{s}(&paw_recv_{d}", .{ type_name, name, id });
+        } else {
+            try w.print("{s}_{s}(&{s}", .{ type_name, name, args[0].identifier });
+        }
+        for (args[1..]) |arg| {
+            try self.output.appendSlice(self.allocator, ", ");
+            _ = try self.generateExpr(arg);
+        }
+        try self.output.appendSlice(self.allocator, if (temp_id != null) "); })" else ")");
+    }
+    
+    /// 🆕 用户类型上的运算符：在临时变量上调用运算符方法（方法接收 self 的指针）
+    ///   a + b   ->  ({ Type paw_lhs_3 = a; Type_add(&paw_lhs_3, b); })
+    ///   a != b  ->  ({ Type paw_lhs_3 = a; !Type_eq(&paw_lhs_3, b); })
+    ///   a < b   ->  ({ Type paw_lhs_3 = a; Type_cmp(&paw_lhs_3, b) < 0; })
+    fn generateOperatorCall(self: *CodeGen, type_name: []const u8, bin: anytype) (std.mem.Allocator.Error)!void {
+        const id = self.next_label_id;
+        self.next_label_id += 1;
+        try self.output.writer(self.allocator).print("({{ {s} paw_lhs_{d} = ", .{ type_name, id });
+        _ = try self.generateExpr(bin.left.*);
+        try self.output.writer(self.allocator).print("; {s}{s}_{s}(&paw_lhs_{d}, ", .{
+            if (bin.op == .ne) "!" else "",
+            type_name,
+            bin.op.overloadMethod().?,
+            id,
+        });
+        _ = try self.generateExpr(bin.right.*);
+        try self.output.appendSlice(self.allocator, ")");
+        switch (bin.op) {
+            .lt, .le, .gt, .ge => try self.output.writer(self.allocator).print(" {s} 0", .{self.binaryOpToC(bin.op)}),
+            else => {},
+        }
+        try self.output.appendSlice(self.allocator, "; })");
+    }
+    
+    /// 🆕 用户类型上的运算符表达式的结果类型：比较为 bool，其余为运算符方法的返回类型
+    fn operatorResultType(self: *CodeGen, expr: ast.Expr) ?ast.Type {
+        if (expr != .binary) return null;
+        const bin = expr.binary;
+        const type_name = bin.resolved_operator.* orelse return null;
+        switch (bin.op) {
+            .eq, .ne, .lt, .le, .gt, .ge => return ast.Type.bool,
+            else => {},
+        }
+        const type_decl = self.type_decls.get(type_name) orelse return null;
+        const methods = switch (type_decl.kind) {
+            .struct_type => |st| st.methods,
+            .enum_type => |et| et.methods,
+            else => return null,
+        };
+        const method_name = bin.op.overloadMethod() orelse return null;
+        for (methods) |method| {
+            if (std.mem.eql(u8, method.name, method_name)) return method.return_type;
+        }
+        return null;
+    }
+    
+    /// 🆕 生成含有不会产生值的分支的 if 表达式，例如 `if c { break; } else { 1 }`
+    /// 
+    /// 不产生值的分支作为普通语句执行，另一个分支提供整个表达式的值：
+    ///   ({ if (c) { break; } 1; })
+    fn generateDivergingIfExpr(self: *CodeGen, if_expr: anytype) (std.mem.Allocator.Error)!void {
+        try self.output.appendSlice(self.allocator, "({\n");
+        const then_diverges = if_expr.then_branch.diverges();
+        if (then_diverges or if_expr.else_branch == null) {
+            try self.output.appendSlice(self.allocator, "if (");
+        } else {
+            try self.output.appendSlice(self.allocator, "if (!(");
+        }
+        _ = try self.generateExpr(if_expr.condition.*);
+        try self.output.appendSlice(self.allocator, if (then_diverges or if_expr.else_branch == null) ") {\n" else ")) {\n");
+        
+        const else_branch = if_expr.else_branch orelse {
+            // 没有 else：条件为假时继续执行，值为 0
+            try self.generateBranchStmts(if_expr.then_branch.*);
+            try self.output.appendSlice(self.allocator, "}\n0;\n})");
+            return;
+        };
+        
+        if (!then_diverges) {
+            // else 分支不产生值，then 分支提供值
+            try self.generateBranchStmts(else_branch.*);
+            try self.output.appendSlice(self.allocator, "}\n");
+            try self.generateExpr(if_expr.then_branch.*);
+        } else if (!else_branch.diverges()) {
+            // then 分支不产生值，else 分支提供值
+            try self.generateBranchStmts(if_expr.then_branch.*);
+            try self.output.appendSlice(self.allocator, "}\n");
+            try self.generateExpr(else_branch.*);
+        } else {
+            // 两个分支都不产生值：值永远不会被使用
+            try self.generateBranchStmts(if_expr.then_branch.*);
+            try self.output.appendSlice(self.allocator, "} else {\n");
+            try self.generateBranchStmts(else_branch.*);
+            try self.output.appendSlice(self.allocator, "}\n0");
+        }
+        try self.output.appendSlice(self.allocator, ";\n})");
+    }
+    
+    /// 🆕 字节串的 C 字符串字面量：每个字节都转义以保证二进制安全
+    fn generateByteString(self: *CodeGen, bytes: []const u8) !void {
+        try self.output.append(self.allocator, '"');
+        for (bytes) |b| {
+            try self.output.writer(self.allocator).print("\\x{x:0>2}", .{b});
+        }
+        try self.output.append(self.allocator, '"');
+    }
+    
+    /// 🆕 将分支作为语句序列生成（值被丢弃）
+    fn generateBranchStmts(self: *CodeGen, branch: ast.Expr) (std.mem.Allocator.Error)!void {
+        if (branch == .block) {
+            for (branch.block) |stmt| {
+                try self.generateStmt(stmt);
+            }
+        } else {
+            _ = try self.generateExpr(branch);
+            try self.output.appendSlice(self.allocator, ";\n");
+        }
+    }
+    
     // 🆕 生成错误传播代码
     // 策略：使用 statement expression 检查 Result，如果是 Err 则提前返回
     fn generateTryExpr(self: *CodeGen, inner: ast.Expr) (std.mem.Allocator.Error)!void {
@@ -1143,8 +2427,13 @@ pub const CodeGen = struct {
     }
     
     // 🆕 生成字符串插值代码
-    // 策略：使用 sprintf 拼接字符串
+    // 策略：按表达式类型 snprintf 到临时缓冲区，再拼接字符串
     fn generateStringInterpolation(self: *CodeGen, parts: []ast.StringInterpPart) (std.mem.Allocator.Error)!void {
+        // 🆕 只有一个 string 表达式（"$s"）：直接使用原字符串，不受缓冲区长度限制
+        if (parts.len == 1 and parts[0] == .expr and self.interpType(parts[0].expr) == .string) {
+            return self.generateExpr(parts[0].expr);
+        }
+        
         // 简化实现：生成立即执行的代码块，返回拼接后的字符串
         try self.output.appendSlice(self.allocator, "({\n");
         try self.output.appendSlice(self.allocator, "    static char __str_buf__[1024];\n");
@@ -1155,19 +2444,44 @@ pub const CodeGen = struct {
             switch (part) {
                 .literal => |lit| {
                     if (lit.len > 0) {
-                        try self.output.appendSlice(self.allocator, "    strcat(__str_buf__, \"");
+                        try self.output.appendSlice(self.allocator, "    strncat(__str_buf__, \"");
                         try self.output.appendSlice(self.allocator, lit);
-                        try self.output.appendSlice(self.allocator, "\");\n");
+                        try self.output.appendSlice(self.allocator, "\", sizeof(__str_buf__) - strlen(__str_buf__) - 1);\n");
                     }
                 },
                 .expr => |expr| {
-                    // 将表达式转换为字符串并拼接
+                    // 🆕 string 直接拼接，bool 拼接 true / false，其他类型按 printf 格式转换
+                    const expr_type = self.interpType(expr);
+                    if (expr_type == .string or expr_type == .bool) {
+                        try self.output.appendSlice(self.allocator, "    strncat(__str_buf__, ");
+                        if (expr_type == .bool) try self.output.appendSlice(self.allocator, "(");
+                        try self.generateExpr(expr);
+                        if (expr_type == .bool) try self.output.appendSlice(self.allocator, ") ? \"true\" : \"false\"");
+                        try self.output.appendSlice(self.allocator, ", sizeof(__str_buf__) - strlen(__str_buf__) - 1);\n");
+                        continue;
+                    }
+                    // 🆕 浮点数使用运行时的最短往返格式（与解释器一致），不用 %g
+                    if (expr_type == .f32 or expr_type == .f64) {
+                        try self.output.appendSlice(self.allocator, "    {\n");
+                        try self.output.appendSlice(self.allocator, "        size_t __len__ = strlen(__str_buf__);\n");
+                        try self.output.appendSlice(self.allocator, "        paw_rt_format_f64(__str_buf__ + __len__, sizeof(__str_buf__) - __len__, (double)(");
+                        try self.generateExpr(expr);
+                        try self.output.writer(self.allocator).print("), {d}, 0);\n", .{@intFromEnum(ast.FloatStyle.shortest)});
+                        try self.output.appendSlice(self.allocator, "    }\n");
+                        continue;
+                    }
+                    const format: struct { spec: []const u8, cast: []const u8 } = switch (expr_type) {
+                        .i64 => .{ .spec = "%lld", .cast = "(long long)" },
+                        .u64 => .{ .spec = "%llu", .cast = "(unsigned long long)" },
+                        .u8, .u16, .u32 => .{ .spec = "%u", .cast = "(unsigned)" },
+                        .char => .{ .spec = "%c", .cast = "(char)" },
+                        else => .{ .spec = "%d", .cast = "(int)" },
+                    };
                     try self.output.appendSlice(self.allocator, "    {\n");
-                    try self.output.appendSlice(self.allocator, "        char __tmp__[64];\n");
-                    try self.output.appendSlice(self.allocator, "        sprintf(__tmp__, \"%d\", ");
+                    try self.output.appendSlice(self.allocator, "        size_t __len__ = strlen(__str_buf__);\n");
+                    try self.output.writer(self.allocator).print("        snprintf(__str_buf__ + __len__, sizeof(__str_buf__) - __len__, \"{s}\", {s}(", .{ format.spec, format.cast });
                     try self.generateExpr(expr);
-                    try self.output.appendSlice(self.allocator, ");\n");
-                    try self.output.appendSlice(self.allocator, "        strcat(__str_buf__, __tmp__);\n");
+                    try self.output.appendSlice(self.allocator, "));\n");
                     try self.output.appendSlice(self.allocator, "    }\n");
                 },
             }
@@ -1177,6 +2491,151 @@ pub const CodeGen = struct {
         try self.output.appendSlice(self.allocator, "})");
     }
     
+    /// 🆕 插值表达式的类型（决定格式化方式；未知时按 i32 处理）
+    fn interpType(self: *CodeGen, expr: ast.Expr) ast.Type {
+        const bindings = self.current_function_bindings orelse layout.Bindings.empty;
+        return switch (expr) {
+            .identifier => |name| if (self.local_types.get(name)) |t| layout.resolve(t, bindings) else ast.Type.i32,
+            .string_interp => ast.Type.string,
+            .as_expr => |as_cast| layout.resolve(as_cast.target_type, bindings),
+            .call => |call| blk: {
+                if (call.callee.* != .identifier) break :blk ast.Type.i32;
+                const func = self.function_table.get(call.callee.identifier) orelse break :blk ast.Type.i32;
+                break :blk if (func.type_params.len == 0) func.return_type else ast.Type.i32;
+            },
+            else => self.inferExprType(expr),
+        };
+    }
+    
+    /// 🆕 把函数名当作值使用（不是同名的局部变量）时的函数类型，其他表达式返回 null
+    fn functionValueType(self: *CodeGen, expr: ast.Expr) ?ast.Type {
+        if (expr == .lambda) return expr.lambda.valueType(self.arena.allocator()) catch null;
+        if (expr != .identifier or self.isLocal(expr.identifier)) return null;
+        const func = self.function_table.get(expr.identifier) orelse return null;
+        if (func.type_params.len > 0) return null;
+        return func.valueType(self.arena.allocator()) catch null;
+    }
+    
+    /// 🆕 返回函数值的调用（pick(true)、f(x)）：结果的函数类型
+    fn returnedFunctionType(self: *CodeGen, expr: ast.Expr) ?ast.Type {
+        if (expr != .call or expr.call.callee.* != .identifier) return null;
+        const name = expr.call.callee.identifier;
+        const return_type = if (self.local_types.get(name)) |t|
+            (if (t == .function) t.function.return_type.* else return null)
+        else if (self.function_table.get(name)) |func|
+            (if (func.type_params.len == 0) func.return_type else return null)
+        else
+            return null;
+        return if (return_type == .function) return_type else null;
+    }
+    
+    /// 🆕 调用的目标是函数值（而不是函数名）时，它的函数类型
+    fn calleeFunctionType(self: *CodeGen, callee: ast.Expr) ?ast.Type {
+        return switch (callee) {
+            .identifier => |name| if (self.local_types.get(name)) |t| (if (t == .function) t else null) else null,
+            .lambda => self.functionValueType(callee),
+            .call => self.returnedFunctionType(callee),
+            else => null,
+        };
+    }
+    
+    /// 🆕 通过函数值调用：实参先求值到临时变量，有环境时作为第一个实参传入
+    ///
+    ///   ({ PawFn paw_fn_3 = f; int32_t paw_fn_3_a0 = (x);
+    ///      paw_fn_3.env ? ((int32_t (*)(void*, int32_t))paw_fn_3.fn)(paw_fn_3.env, paw_fn_3_a0)
+    ///                   : ((int32_t (*)(int32_t))paw_fn_3.fn)(paw_fn_3_a0); })
+    fn generateIndirectCall(self: *CodeGen, callee: ast.Expr, func_type: ast.Type, args: []ast.Expr) (std.mem.Allocator.Error)!void {
+        const func = func_type.function;
+        const id = self.next_label_id;
+        self.next_label_id += 1;
+        const w = self.output.writer(self.allocator);
+        const return_c = self.typeToC(func.return_type.*);
+        
+        try w.print("({{ PawFn paw_fn_{d} = ", .{id});
+        _ = try self.generateExpr(callee);
+        try self.output.appendSlice(self.allocator, "; ");
+        for (args, func.params, 0..) |arg, param_type, i| {
+            try w.print("{s} paw_fn_{d}_a{d} = (", .{ self.typeToC(param_type), id, i });
+            _ = try self.generateExpr(arg);
+            try self.output.appendSlice(self.allocator, "); ");
+        }
+        for ([_]bool{ true, false }) |with_env| {
+            if (with_env) {
+                try w.print("paw_fn_{d}.env ? (({s} (*)(void*", .{ id, return_c });
+            } else {
+                try w.print(" : (({s} (*)(", .{return_c});
+            }
+            for (func.params, 0..) |param_type, i| {
+                if (i > 0 or with_env) try self.output.appendSlice(self.allocator, ", ");
+                try self.output.appendSlice(self.allocator, self.typeToC(param_type));
+            }
+            if (!with_env and func.params.len == 0) try self.output.appendSlice(self.allocator, "void");
+            try w.print("))paw_fn_{d}.fn)(", .{id});
+            if (with_env) try w.print("paw_fn_{d}.env", .{id});
+            for (0..args.len) |i| {
+                if (i > 0 or with_env) try self.output.appendSlice(self.allocator, ", ");
+                try w.print("paw_fn_{d}_a{d}", .{ id, i });
+            }
+            try self.output.appendSlice(self.allocator, ")");
+        }
+        try self.output.appendSlice(self.allocator, "; })");
+    }
+    
+    /// 🆕 lambda 的值：没有捕获时环境为 NULL，否则在运行时分配环境并复制捕获的变量
+    fn generateLambdaValue(self: *CodeGen, lambda: ast.Lambda) (std.mem.Allocator.Error)!void {
+        const name = lambda.lifted_name.*;
+        const w = self.output.writer(self.allocator);
+        if (lambda.captures.*.len == 0) {
+            try w.print("((PawFn){{ NULL, (void*){s} }})", .{name});
+            return;
+        }
+        const id = self.next_label_id;
+        self.next_label_id += 1;
+        try w.print("({{ {s}_env* paw_env_{d} = ({s}_env*)paw_rt_alloc(sizeof({s}_env)); ", .{ name, id, name, name });
+        for (lambda.captures.*) |capture| {
+            try w.print("paw_env_{d}->{s} = {s}; ", .{ id, capture.name, capture.name });
+        }
+        try w.print("(PawFn){{ paw_env_{d}, (void*){s} }}; }})", .{ id, name });
+    }
+    
+    fn isLocal(self: *CodeGen, name: []const u8) bool {
+        return self.local_types.contains(name) or self.var_types.contains(name);
+    }
+    
+    /// 🆕 函数开始：记录参数类型（类型参数按 bindings 替换），清除上一个函数的局部变量
+    fn recordParamTypes(self: *CodeGen, params: []const ast.Param, bindings: layout.Bindings) !void {
+        self.local_types.clearRetainingCapacity();
+        for (params) |param| {
+            try self.local_types.put(param.name, self.resolveProjection(param.type, bindings));
+        }
+    }
+    
+    /// 🆕 生成循环体；带标签的循环在末尾放置 continue 'label 的跳转目标
+    fn generateLoopBody(self: *CodeGen, body: []ast.Stmt) (std.mem.Allocator.Error)!void {
+        for (body) |stmt| {
+            try self.generateStmt(stmt);
+        }
+        
+        if (self.loop_labels.items.len == 0) return;
+        const loop = self.loop_labels.items[self.loop_labels.items.len - 1];
+        if (loop.name != null) {
+            try self.output.writer(self.allocator).print("paw_continue_{d}: ;\n", .{loop.id});
+        }
+    }
+    
+    /// 🆕 查找 break/continue 的目标循环（无标签时为最内层循环）
+    fn findLoop(self: *CodeGen, label: ?[]const u8) ?LoopLabel {
+        const items = self.loop_labels.items;
+        if (items.len == 0) return null;
+        const name = label orelse return items[items.len - 1];
+        var i = items.len;
+        while (i > 0) {
+            i -= 1;
+            if (items[i].name != null and std.mem.eql(u8, items[i].name.?, name)) return items[i];
+        }
+        return null;
+    }
+    
     // 🆕 生成 loop iterator (loop i in collection)
     fn generateLoopIterator(self: *CodeGen, iter: ast.LoopIterator, body: []ast.Stmt) (std.mem.Allocator.Error)!void {
         // 检查 iterable 是否是范围表达式
@@ -1204,9 +2663,7 @@ pub const CodeGen = struct {
             try self.output.appendSlice(self.allocator, iter.binding);
             try self.output.appendSlice(self.allocator, "++) {\n");
             
-            for (body) |stmt| {
-                    try self.generateStmt(stmt);
-                }
+            try self.generateLoopBody(body);
                 
             try self.output.appendSlice(self.allocator, "}\n");
         } else if (iter.iterable == .array_literal) {
@@ -1249,10 +2706,7 @@ pub const CodeGen = struct {
             try self.output.appendSlice(self.allocator, "];\n");
             
             // 生成循环体
-            for (body) |stmt| {
-                try self.output.appendSlice(self.allocator, "        ");
-                try self.generateStmt(stmt);
-            }
+            try self.generateLoopBody(body);
             
             try self.output.appendSlice(self.allocator, "    }\n");
             try self.output.appendSlice(self.allocator, "}\n");
@@ -1293,10 +2747,7 @@ pub const CodeGen = struct {
             try self.output.appendSlice(self.allocator, "];\n");
             
             // 生成循环体
-            for (body) |stmt| {
-                try self.output.appendSlice(self.allocator, "        ");
-                try self.generateStmt(stmt);
-            }
+            try self.generateLoopBody(body);
             
             try self.output.appendSlice(self.allocator, "    }\n");
             try self.output.appendSlice(self.allocator, "}\n");
@@ -1329,6 +2780,8 @@ pub const CodeGen = struct {
         
         if (use_switch) {
             try self.generateIsExprSwitch(is_match);
+        } else if (try self.isDenseLiteralMatch(is_match)) {
+            try self.generateIsExprJumpTable(is_match);
         } else {
             try self.generateIsExprIfElse(is_match);
         }
@@ -1341,9 +2794,13 @@ pub const CodeGen = struct {
     // 判断是否应该使用 switch（enum 模式匹配）
     fn shouldUseSwitch(self: *CodeGen, is_match: anytype) bool {
         _ = self;
-        // 简单策略：如果第一个 arm 是 variant 模式，使用 switch
+        // 简单策略：如果第一个 arm 是 variant 模式（🆕 或由变体组成的或模式），使用 switch
         if (is_match.arms.len > 0) {
-            return is_match.arms[0].pattern == .variant;
+            return switch (is_match.arms[0].pattern) {
+                .variant => true,
+                .alternatives => |alts| alts[0] == .variant,
+                else => false,
+            };
         }
         return false;
     }
@@ -1352,34 +2809,46 @@ pub const CodeGen = struct {
     fn generateIsExprSwitch(self: *CodeGen, is_match: anytype) (std.mem.Allocator.Error)!void {
         try self.output.appendSlice(self.allocator, "    switch (__match_value__.tag) {\n");
         
+        // 🆕 不可达的分支（类型检查已警告）不生成：重复的 case / default 在 C 中不合法
+        var covered = std.StringHashMap(void).init(self.allocator);
+        defer covered.deinit();
+        var has_default = false;
+        
         for (is_match.arms) |arm| {
-            if (arm.pattern == .variant) {
-                const variant = arm.pattern.variant;
+            if (has_default) break;
+            if (arm.pattern == .variant or arm.pattern == .alternatives) {
+                // case EnumName_TAG_VariantName:（🆕 或模式的每个变体各一个 case 标签）
+                const labels: []const ast.Pattern = if (arm.pattern == .alternatives) arm.pattern.alternatives else (&arm.pattern)[0..1];
+                const reachable = for (labels) |label| {
+                    if (label == .variant and !covered.contains(label.variant.name)) break true;
+                } else false;
+                if (!reachable) continue;
+                try self.output.appendSlice(self.allocator, "       ");
+                for (labels) |label| {
+                    if (label != .variant or covered.contains(label.variant.name)) continue;
+                    if (arm.guard == null) try covered.put(label.variant.name, {});
+                    // 需要找到enum类型名
+                    const label_enum = self.enum_variants.get(label.variant.name) orelse "Unknown";
+                    try self.output.writer(self.allocator).print(" case {s}_TAG_{s}:", .{ label_enum, label.variant.name });
+                }
+                try self.output.appendSlice(self.allocator, " {\n");
                 
-                // 需要找到enum类型名
+                const variant = if (arm.pattern == .variant) arm.pattern.variant else labels[0].variant;
                 const enum_name = self.enum_variants.get(variant.name) orelse "Unknown";
                 
-                // case EnumName_TAG_VariantName:
-                try self.output.appendSlice(self.allocator, "        case ");
-                try self.output.appendSlice(self.allocator, enum_name);
-                try self.output.appendSlice(self.allocator, "_TAG_");
-                try self.output.appendSlice(self.allocator, variant.name);
-                try self.output.appendSlice(self.allocator, ": {\n");
-                
-                // 🆕 绑定变量（如果有）
-                if (variant.bindings.len > 0) {
+                // 🆕 绑定变量（如果有；或模式中的变体不绑定）
+                if (arm.pattern == .variant and variant.bindings.len > 0) {
                     // 单个参数: Type binding = __match_value__.data.VariantName_value;
+                    const fields = self.variantFields(enum_name, variant.name);
                     if (variant.bindings.len == 1) {
-                        try self.output.appendSlice(self.allocator, "            int32_t ");
-                        try self.output.appendSlice(self.allocator, variant.bindings[0]);
+                        try self.appendBindingDecl(fields, 0, variant.bindings[0]);
                         try self.output.appendSlice(self.allocator, " = __match_value__.data.");
                         try self.output.appendSlice(self.allocator, variant.name);
                         try self.output.appendSlice(self.allocator, "_value;\n");
                     } else {
                         // 多个参数: 从 struct 中提取
                         for (variant.bindings, 0..) |binding, i| {
-                            try self.output.appendSlice(self.allocator, "            int32_t ");
-                            try self.output.appendSlice(self.allocator, binding);
+                            try self.appendBindingDecl(fields, i, binding);
                             try self.output.appendSlice(self.allocator, " = __match_value__.data.");
                             try self.output.appendSlice(self.allocator, variant.name);
                             const field_ref = try std.fmt.allocPrint(self.allocator, "_value.field{d};\n", .{i});
@@ -1407,9 +2876,13 @@ pub const CodeGen = struct {
                 
                 try self.output.appendSlice(self.allocator, "            break;\n");
                 try self.output.appendSlice(self.allocator, "        }\n");
-            } else if (arm.pattern == .wildcard) {
-                // default case
+            } else if (arm.pattern == .wildcard or arm.pattern == .identifier) {
+                // default case（🆕 标识符模式绑定整个值）
+                has_default = true;
                 try self.output.appendSlice(self.allocator, "        default: {\n");
+                if (arm.pattern == .identifier) {
+                    try self.output.writer(self.allocator).print("            typeof(__match_value__) {s} = __match_value__;\n", .{arm.pattern.identifier});
+                }
                 try self.output.appendSlice(self.allocator, "            __match_result__ = ");
                 try self.generateExpr(arm.body);
                 try self.output.appendSlice(self.allocator, ";\n");
@@ -1421,6 +2894,135 @@ pub const CodeGen = struct {
         try self.output.appendSlice(self.allocator, "    }\n");
     }
     
+    /// 🆕 值模式的匹配条件（与 __match_value__ 比较）
+    ///   "foo"     paw_str_eq(__match_value__, "foo")（字符串按内容比较）
+    ///   1..=5     (__match_value__ >= 1 && __match_value__ <= 5)
+    ///   1 | 2     (__match_value__ == 1 || __match_value__ == 2)
+    fn generatePatternCondition(self: *CodeGen, pattern: ast.Pattern) (std.mem.Allocator.Error)!void {
+        switch (pattern) {
+            .literal => |lit| if (lit == .string_literal) {
+                try self.output.appendSlice(self.allocator, "paw_str_eq(__match_value__, ");
+                try self.generateExpr(lit);
+                try self.output.appendSlice(self.allocator, ")");
+            } else {
+                try self.output.appendSlice(self.allocator, "__match_value__ == ");
+                try self.generateExpr(lit);
+            },
+            .range => |r| {
+                try self.output.appendSlice(self.allocator, "(__match_value__ >= ");
+                try self.generateExpr(r.start);
+                try self.output.appendSlice(self.allocator, if (r.inclusive) " && __match_value__ <= " else " && __match_value__ < ");
+                try self.generateExpr(r.end);
+                try self.output.appendSlice(self.allocator, ")");
+            },
+            .alternatives => |alts| {
+                try self.output.appendSlice(self.allocator, "(");
+                for (alts, 0..) |alt, i| {
+                    if (i > 0) try self.output.appendSlice(self.allocator, " || ");
+                    try self.generatePatternCondition(alt);
+                }
+                try self.output.appendSlice(self.allocator, ")");
+            },
+            // 类型检查保证值模式中没有绑定和通配符
+            .identifier, .variant, .wildcard => try self.output.appendSlice(self.allocator, "1"),
+        }
+    }
+    
+    /// 🆕 枚举变体的负载类型（找不到声明或是泛型枚举时为空，绑定按 int32_t 生成）
+    fn variantFields(self: *CodeGen, enum_name: []const u8, variant_name: []const u8) []const ast.Type {
+        const type_decl = self.type_decls.get(enum_name) orelse return &[_]ast.Type{};
+        if (type_decl.kind != .enum_type or type_decl.type_params.len > 0) return &[_]ast.Type{};
+        for (type_decl.kind.enum_type.variants) |v| {
+            if (std.mem.eql(u8, v.name, variant_name)) return v.fields;
+        }
+        return &[_]ast.Type{};
+    }
+    
+    /// 🆕 变体模式绑定的声明（"    T binding"），类型取负载的第 index 个字段
+    fn appendBindingDecl(self: *CodeGen, fields: []const ast.Type, index: usize, binding: []const u8) !void {
+        const c_type = if (index < fields.len) self.typeToC(fields[index]) else "int32_t";
+        try self.output.writer(self.allocator).print("            {s} {s}", .{ c_type, binding });
+        if (index < fields.len) {
+            try self.local_types.put(binding, fields[index]);
+        } else {
+            _ = self.local_types.remove(binding);
+        }
+    }
+    
+    /// 🆕 生成跳转表所需的最少字面量分支数
+    const MIN_JUMP_TABLE_CASES: usize = 4;
+    
+    /// 🆕 整数 / 字符字面量模式的值
+    fn literalCaseValue(expr: ast.Expr) ?i64 {
+        return switch (expr) {
+            .int_literal => |literal| literal.value,
+            .char_literal => |v| v,
+            .byte_literal => |v| v,
+            .unary => |un| if (un.op == .neg and un.operand.* == .int_literal) -un.operand.int_literal.value else null,
+            else => null,
+        };
+    }
+    
+    /// 🆕 判断 is 表达式是否是密集的整数字面量匹配：
+    /// 所有分支（直到第一个兜底分支）都是不带 guard 的整数/字符字面量，
+    /// 值互不相同，且覆盖范围不超过分支数的两倍
+    fn isDenseLiteralMatch(self: *CodeGen, is_match: anytype) (std.mem.Allocator.Error)!bool {
+        var values = std.ArrayList(i64){};
+        defer values.deinit(self.allocator);
+        
+        for (is_match.arms) |arm| {
+            switch (arm.pattern) {
+                .literal => |lit| {
+                    if (arm.guard != null) return false;
+                    try values.append(self.allocator, literalCaseValue(lit) orelse return false);
+                },
+                .wildcard => break,
+                .identifier => if (arm.guard == null) break else return false,
+                .variant, .range, .alternatives => return false,
+            }
+        }
+        if (values.items.len < MIN_JUMP_TABLE_CASES) return false;
+        
+        std.mem.sort(i64, values.items, {}, std.sort.asc(i64));
+        for (values.items[1..], 0..) |v, i| {
+            if (v == values.items[i]) return false;  // 重复的 case 在 C 中不合法
+        }
+        const span = @as(i128, values.items[values.items.len - 1]) - values.items[0] + 1;
+        return span <= values.items.len * 2;
+    }
+    
+    /// 🆕 密集的整数字面量匹配生成 C switch：
+    /// C 编译器会把它编译成跳转表（带范围检查和 default），而不是逐个比较
+    fn generateIsExprJumpTable(self: *CodeGen, is_match: anytype) (std.mem.Allocator.Error)!void {
+        try self.output.appendSlice(self.allocator, "    switch (__match_value__) {\n");
+        
+        for (is_match.arms) |arm| {
+            switch (arm.pattern) {
+                .literal => |lit| {
+                    try self.output.writer(self.allocator).print("        case {d}: {{\n", .{literalCaseValue(lit).?});
+                },
+                .wildcard => try self.output.appendSlice(self.allocator, "        default: {\n"),
+                .identifier => |name| {
+                    try self.output.appendSlice(self.allocator, "        default: {\n");
+                    try self.output.appendSlice(self.allocator, "            int32_t ");
+                    try self.output.appendSlice(self.allocator, name);
+                    try self.output.appendSlice(self.allocator, " = __match_value__;\n");
+                },
+                .variant, .range, .alternatives => unreachable,
+            }
+            try self.output.appendSlice(self.allocator, "            __match_result__ = ");
+            try self.generateExpr(arm.body);
+            try self.output.appendSlice(self.allocator, ";\n");
+            try self.output.appendSlice(self.allocator, "            break;\n");
+            try self.output.appendSlice(self.allocator, "        }\n");
+            
+            // 兜底分支之后的分支不可达
+            if (arm.pattern != .literal) break;
+        }
+        
+        try self.output.appendSlice(self.allocator, "    }\n");
+    }
+    
     // 使用 if-else 生成 is 表达式（常量/标识符模式）
     fn generateIsExprIfElse(self: *CodeGen, is_match: anytype) (std.mem.Allocator.Error)!void {
         for (is_match.arms, 0..) |arm, i| {
@@ -1439,14 +3041,12 @@ pub const CodeGen = struct {
                 try self.generateExpr(arm.body);
                 try self.output.appendSlice(self.allocator, ";\n");
                 try self.output.appendSlice(self.allocator, "    }\n");
-            } else if (arm.pattern == .literal) {
-                // 字面量模式：比较值
-                if (needs_else) {
-                    try self.output.appendSlice(self.allocator, "    else if (__match_value__ == ");
-                } else {
-                    try self.output.appendSlice(self.allocator, "    if (__match_value__ == ");
-                }
-                try self.generateExpr(arm.pattern.literal);
+                // 🆕 后面的分支不可达（类型检查器已警告），不再生成
+                break;
+            } else if (arm.pattern == .literal or arm.pattern == .range or arm.pattern == .alternatives) {
+                // 字面量模式：比较值（🆕 范围和或模式展开为比较链）
+                try self.output.appendSlice(self.allocator, if (needs_else) "    else if (" else "    if (");
+                try self.generatePatternCondition(arm.pattern);
                 try self.output.appendSlice(self.allocator, ")");
                 
                 // guard
@@ -1464,31 +3064,26 @@ pub const CodeGen = struct {
                 try self.output.appendSlice(self.allocator, "    }\n");
             } else if (arm.pattern == .identifier) {
                 // 标识符模式：绑定并总是匹配
-                if (needs_else) {
+                if (arm.guard) |guard| {
+                    // 🆕 有 guard 时是链中的一个条件（绑定后求值 guard），后面的分支接着 else if
+                    try self.output.appendSlice(self.allocator, if (needs_else) "    else if (({ " else "    if (({ ");
+                    try self.output.writer(self.allocator).print("typeof(__match_value__) {s} = __match_value__; ", .{arm.pattern.identifier});
+                    try self.generateExpr(guard);
+                    try self.output.appendSlice(self.allocator, "; })) {\n");
+                } else if (needs_else) {
                     try self.output.appendSlice(self.allocator, "    else {\n");
                 } else {
                     try self.output.appendSlice(self.allocator, "    {\n");
                 }
-                try self.output.appendSlice(self.allocator, "        int32_t ");
+                try self.output.appendSlice(self.allocator, "        typeof(__match_value__) ");
                 try self.output.appendSlice(self.allocator, arm.pattern.identifier);
                 try self.output.appendSlice(self.allocator, " = __match_value__;\n");
-                
-                // guard
-                if (arm.guard) |guard| {
-                    try self.output.appendSlice(self.allocator, "        if (");
-                    try self.generateExpr(guard);
-                    try self.output.appendSlice(self.allocator, ") {\n");
-                    try self.output.appendSlice(self.allocator, "            __match_result__ = ");
-                    try self.generateExpr(arm.body);
-                    try self.output.appendSlice(self.allocator, ";\n");
-                    try self.output.appendSlice(self.allocator, "        }\n");
-                } else {
-                    // 没有 guard，直接赋值
-                    try self.output.appendSlice(self.allocator, "        __match_result__ = ");
-                    try self.generateExpr(arm.body);
-                    try self.output.appendSlice(self.allocator, ";\n");
-                }
+                try self.output.appendSlice(self.allocator, "        __match_result__ = ");
+                try self.generateExpr(arm.body);
+                try self.output.appendSlice(self.allocator, ";\n");
                 try self.output.appendSlice(self.allocator, "    }\n");
+                // 🆕 没有 guard 的绑定匹配所有值：后面的分支不可达，不再生成
+                if (arm.guard == null) break;
             }
         }
     }
@@ -1542,6 +3137,8 @@ pub const CodeGen = struct {
             .char => "char",
             .string => "char*",
             .void => "void",
+            // 🆕 不返回的函数声明为 _Noreturn int32_t，调用可以出现在任何需要值的位置
+            .never => "int32_t",
             .generic => |name| name,  // 🆕 泛型类型：直接使用类型参数名（T, U, etc）
             .named => |name| name,
             .pointer => |ptr| {
@@ -1563,10 +3160,17 @@ pub const CodeGen = struct {
                     return self.typeToC(arr.element.*);  // 简化：返回元素类型
                 }
             },
-            .function => |func| {
-                // TODO: 处理函数类型
-                _ = func;
-                return "void*";
+            // 🆕 函数值：(环境, 函数) 对，调用时按函数类型转换函数指针（见 generateIndirectCall）
+            .function => "PawFn",
+            // 🆕 T::Item：按当前实例的绑定取关联类型
+            .projection => |proj| {
+                const resolved = self.resolveProjection(paw_type, self.current_function_bindings orelse layout.Bindings.empty);
+                if (resolved == .projection) {
+                    // 🆕 记录编译器错误（生成结束后构建失败），输出的类型只是占位
+                    self.codegen_errors.recordUnresolvedProjection(self.getSimpleTypeName(proj.base.*), proj.name) catch {};
+                    return "int32_t";
+                }
+                return self.typeToC(resolved);
             },
             .generic_instance => |gi| {
                 // 🆕 处理泛型实例：Vec<i32> -> Vec_i32
@@ -1632,6 +3236,7 @@ pub const CodeGen = struct {
             .float_literal => ast.Type.f64,
             .string_literal => ast.Type.string,
             .char_literal => ast.Type.char,
+            .byte_literal => ast.Type.u8,
             .bool_literal => ast.Type.bool,
             .identifier => |name| blk: {
                 // 查询变量类型
@@ -1640,7 +3245,8 @@ pub const CodeGen = struct {
                 }
                 break :blk ast.Type.i32;  // 默认
             },
-            .call => ast.Type.i32,  // 简化：函数调用返回 i32
+            .call => |call| if (call.resolved_into.*) call.resolved_type_args.*[1] else ast.Type.i32,  // 简化：函数调用返回 i32
+            .as_expr => |as_cast| as_cast.target_type,
             .binary => ast.Type.i32,
             .unary => |un| self.inferExprType(un.operand.*),
             .field_access => ast.Type.i32,
@@ -1650,11 +3256,93 @@ pub const CodeGen = struct {
             else => ast.Type.i32,
         };
     }
+    
+    /// 🆕 算术表达式的窄整数类型（i8 / u8 / i16 / u16），其他类型返回 null
+    /// 
+    /// 未标注类型的整数字面量跟随另一侧的类型；两侧类型不同时返回 null。
+    fn narrowIntType(self: *CodeGen, expr: ast.Expr) ?ast.Type {
+        return switch (expr) {
+            .byte_literal => ast.Type.u8,
+            .identifier => |name| self.narrow_var_types.get(name),
+            .as_expr => |as_cast| if (isNarrowInt(as_cast.target_type)) as_cast.target_type else null,
+            .unary => |un| if (un.op == .neg) self.narrowIntType(un.operand.*) else null,
+            .binary => |bin| switch (bin.op) {
+                .add, .sub, .mul, .div, .mod => blk: {
+                    const left = self.narrowIntType(bin.left.*);
+                    const right = self.narrowIntType(bin.right.*);
+                    if (left) |l| {
+                        if (right) |r| break :blk if (l.eql(r)) l else null;
+                        break :blk if (bin.right.* == .int_literal) l else null;
+                    }
+                    if (right) |r| break :blk if (bin.left.* == .int_literal) r else null;
+                    break :blk null;
+                },
+                else => null,
+            },
+            else => null,
+        };
+    }
+    
+    /// 🆕 `expr as char` 是否需要运行时检查
+    /// 字符、字节和 u8 的值一定是合法的标量值；整数字面量已由类型检查器在编译期检查
+    fn needsCharCheck(self: *CodeGen, expr: ast.Expr) bool {
+        return switch (expr) {
+            .char_literal, .byte_literal, .int_literal => false,
+            .as_expr => |as_cast| as_cast.target_type != .char and as_cast.target_type != .u8,
+            else => if (self.narrowIntType(expr)) |t| t != .u8 else true,
+        };
+    }
+    
+    fn isNarrowInt(t: ast.Type) bool {
+        return switch (t) {
+            .i8, .u8, .i16, .u16 => true,
+            else => false,
+        };
+    }
 
     // ============================================================================
     // 🆕 泛型单态化函数生成
     // ============================================================================
 
+    /// 🆕 泛型类型实例上的方法的 where 约束（内建约束和 trait 约束）是否被类型实参满足
+    fn receiverBoundsMet(self: *CodeGen, method: ast.FunctionDecl, type_decl: ast.TypeDecl, type_args: []const ast.Type) bool {
+        if (method.unmetReceiverBound(type_decl.type_params, type_args) != null) return false;
+        for (method.trait_bounds) |tb| {
+            const actual = ast.typeArgFor(type_decl.type_params, type_args, tb.param) orelse continue;
+            if (!self.implementsTrait(actual, tb.trait_name)) return false;
+        }
+        return true;
+    }
+    
+    /// 🆕 类型（结构上）实现 trait：trait 及 supertrait 的每个方法在该类型（实例）上存在
+    fn implementsTrait(self: *CodeGen, t: ast.Type, trait_name: []const u8) bool {
+        const trait_decl = self.type_decls.get(trait_name) orelse return false;
+        if (trait_decl.kind != .trait_type) return false;
+        const trait_type = trait_decl.kind.trait_type;
+        for (trait_type.supertraits) |super| {
+            if (!self.implementsTrait(t, super.name)) return false;
+        }
+        
+        const type_name, const type_args: []const ast.Type = switch (t) {
+            .named => |n| .{ n, &.{} },
+            .generic_instance => |gi| .{ gi.name, gi.type_args },
+            else => return false,
+        };
+        const type_decl = self.type_decls.get(type_name) orelse return false;
+        const methods = switch (type_decl.kind) {
+            .struct_type => |st| st.methods,
+            .enum_type => |et| et.methods,
+            .trait_type, .alias => return false,
+        };
+        for (trait_type.methods) |sig| {
+            const method = for (methods) |m| {
+                if (std.mem.eql(u8, m.name, sig.name)) break m;
+            } else return false;
+            if (!self.receiverBoundsMet(method, type_decl, type_args)) return false;
+        }
+        return true;
+    }
+    
     /// 生成单态化函数的前向声明
     fn generateMonomorphizedDeclarations(self: *CodeGen) !void {
         // 🆕 1. 生成泛型结构体定义，并自动记录所有实例方法
@@ -1665,7 +3353,9 @@ pub const CodeGen = struct {
                     const st = type_decl.kind.struct_type;
                     
                     // 🆕 自动记录该struct的所有实例方法
+                    // （where 约束了类型参数的方法只在满足约束的实例上生成）
                     for (st.methods) |method| {
+                        if (!self.receiverBoundsMet(method, type_decl, instance.type_args)) continue;
                         if (method.params.len > 0 and 
                             std.mem.eql(u8, method.params[0].name, "self")) {
                             // 这是实例方法
@@ -1736,31 +3426,10 @@ pub const CodeGen = struct {
         for (instances) |instance| {
             if (self.function_table.get(instance.generic_name)) |generic_func| {
                 if (generic_func.type_params.len > 0 and instance.type_args.len > 0) {
-                    // 🆕 返回类型：使用第一个类型参数（简化）
-                    const return_type = instance.type_args[0];
-                    
                     // 生成前向声明
-                    try self.output.appendSlice(self.allocator, self.typeToC(return_type));
-                    try self.output.appendSlice(self.allocator, " ");
-                    try self.output.appendSlice(self.allocator, instance.mangled_name);
-                    try self.output.appendSlice(self.allocator, "(");
-                    
-                    // 🆕 参数类型：使用对应的类型参数
-                    for (generic_func.params, 0..) |param, i| {
-                        if (i > 0) try self.output.appendSlice(self.allocator, ", ");
-                        
-                        // 如果有足够的类型参数，使用对应的类型
-                        const param_type = if (i < instance.type_args.len)
-                            instance.type_args[i]
-                        else
-                            instance.type_args[0];  // 降级：重复使用第一个
-                        
-                        try self.output.appendSlice(self.allocator, self.typeToC(param_type));
-                        try self.output.appendSlice(self.allocator, " ");
-                        try self.output.appendSlice(self.allocator, param.name);
-                    }
-                    
-                    try self.output.appendSlice(self.allocator, ");\n");
+                    if (self.internal_linkage) try self.output.appendSlice(self.allocator, "static ");
+                    try self.generateInstanceSignature(generic_func, instance);
+                    try self.output.appendSlice(self.allocator, ";\n");
                 }
             }
         }
@@ -1784,6 +3453,7 @@ pub const CodeGen = struct {
                                 type_decl.type_params,
                                 method_instance.type_args,
                             );
+                            if (self.internal_linkage) try self.output.appendSlice(self.allocator, "static ");
                             try self.output.appendSlice(self.allocator, self.typeToC(return_type));
                             try self.output.appendSlice(self.allocator, " ");
                             
@@ -1826,6 +3496,22 @@ pub const CodeGen = struct {
         try self.output.appendSlice(self.allocator, "\n");
     }
 
+    /// 🆕 base 类型定义的关联类型 name
+    fn projectType(self: *CodeGen, base: ast.Type, name: []const u8) ?ast.Type {
+        return switch (base) {
+            .named => |type_name| (self.type_decls.get(type_name) orelse return null).assocType(name, &.{}),
+            .generic_instance => |gi| (self.type_decls.get(gi.name) orelse return null).assocType(name, gi.type_args),
+            else => null,
+        };
+    }
+    
+    /// 🆕 把外层绑定中的类型参数替换为具体类型，投影 T::Item 替换为绑定类型的关联类型
+    fn resolveProjection(self: *CodeGen, ty: ast.Type, bindings: layout.Bindings) ast.Type {
+        if (ty != .projection) return layout.resolve(ty, bindings);
+        const base = layout.resolve(ty.projection.base.*, bindings);
+        return self.projectType(base, ty.projection.name) orelse ty;
+    }
+
     /// 替换类型中的泛型参数
     fn substituteGenericType(
         self: *CodeGen,
@@ -1868,10 +3554,36 @@ pub const CodeGen = struct {
                     },
                 };
             },
+            .projection => |proj| {
+                // 🆕 T::Item：T 替换后取具体类型的关联类型
+                const base = try self.substituteGenericType(proj.base.*, type_params, type_args);
+                if (self.projectType(base, proj.name)) |target| return target;
+                const new_base = try self.arena.allocator().create(ast.Type);
+                new_base.* = base;
+                return ast.Type{ .projection = .{ .base = new_base, .trait_name = proj.trait_name, .name = proj.name } };
+            },
             else => return ty,
         }
     }
 
+    /// 🆕 单态化函数的签名：参数和返回类型中的类型参数替换为实例的类型实参
+    fn generateInstanceSignature(self: *CodeGen, generic_func: ast.FunctionDecl, instance: generics.GenericInstance) !void {
+        const return_type = try self.substituteGenericType(generic_func.return_type, generic_func.type_params, instance.type_args);
+        try self.output.appendSlice(self.allocator, self.typeToC(return_type));
+        try self.output.appendSlice(self.allocator, " ");
+        try self.output.appendSlice(self.allocator, instance.mangled_name);
+        try self.output.appendSlice(self.allocator, "(");
+        for (generic_func.params, 0..) |param, i| {
+            if (i > 0) try self.output.appendSlice(self.allocator, ", ");
+            const param_type = try self.substituteGenericType(param.type, generic_func.type_params, instance.type_args);
+            try self.output.appendSlice(self.allocator, self.typeToC(param_type));
+            try self.output.appendSlice(self.allocator, " ");
+            try self.output.appendSlice(self.allocator, param.name);
+        }
+        if (generic_func.params.len == 0) try self.output.appendSlice(self.allocator, "void");
+        try self.output.appendSlice(self.allocator, ")");
+    }
+
     /// 生成所有单态化的泛型函数
     fn generateMonomorphizedFunctions(self: *CodeGen) !void {
         const instances = self.generic_context.monomorphizer.instances.items;
@@ -1880,36 +3592,33 @@ pub const CodeGen = struct {
             // 获取原始泛型函数
             if (self.function_table.get(instance.generic_name)) |generic_func| {
                 if (generic_func.type_params.len > 0 and instance.type_args.len > 0) {
-                    // 🆕 返回类型：使用第一个类型参数
-                    const return_type = instance.type_args[0];
+                    const mark = self.output.items.len;
                     
                     // 生成函数签名
-                    try self.output.appendSlice(self.allocator, self.typeToC(return_type));
-                    try self.output.appendSlice(self.allocator, " ");
-                    try self.output.appendSlice(self.allocator, instance.mangled_name);
-                    try self.output.appendSlice(self.allocator, "(");
-                    
-                    // 🆕 生成参数：使用对应的类型参数
-                    for (generic_func.params, 0..) |param, i| {
-                        if (i > 0) try self.output.appendSlice(self.allocator, ", ");
-                        
-                        // 如果有足够的类型参数，使用对应的类型
-                        const param_type = if (i < instance.type_args.len)
-                            instance.type_args[i]
-                        else
-                            instance.type_args[0];  // 降级：重复使用第一个
-                        
-                        try self.output.appendSlice(self.allocator, self.typeToC(param_type));
-                        try self.output.appendSlice(self.allocator, " ");
-                        try self.output.appendSlice(self.allocator, param.name);
-                    }
-                    
-                    try self.output.appendSlice(self.allocator, ") {\n");
+                    if (self.internal_linkage) try self.output.appendSlice(self.allocator, "static ");
+                    try self.generateInstanceSignature(generic_func, instance);
+                    try self.output.appendSlice(self.allocator, " {\n");
+                    try self.generateFrameMarker(null, generic_func.name);
                     
                     // 生成函数体
-                    for (generic_func.body) |stmt| {
-                        try self.generateStmt(stmt);
-                    }
+                    self.current_function_bindings = .{
+                        .type_params = generic_func.type_params,
+                        .type_args = instance.type_args,
+                    };
+                    try self.recordParamTypes(generic_func.params, self.current_function_bindings.?);
+                    self.startFunctionClock();
+                    defer self.function_deadline_ns = null;
+                    const body_ok = blk: {
+                        for (generic_func.body) |stmt| {
+                            self.generateStmt(stmt) catch |err| {
+                                try self.recordFunctionError(instance.mangled_name, generic_func.location, mark, err);
+                                break :blk false;
+                            };
+                        }
+                        break :blk true;
+                    };
+                    self.current_function_bindings = null;
+                    if (!body_ok) continue;
                     
                     try self.output.appendSlice(self.allocator, "}\n\n");
                 }
@@ -1934,6 +3643,7 @@ pub const CodeGen = struct {
                                 type_decl.type_params,
                                 method_instance.type_args,
                             );
+                            if (self.internal_linkage) try self.output.appendSlice(self.allocator, "static ");
                             try self.output.appendSlice(self.allocator, self.typeToC(return_type));
                             try self.output.appendSlice(self.allocator, " ");
                             
@@ -1967,6 +3677,9 @@ pub const CodeGen = struct {
                             }
                             
                             try self.output.appendSlice(self.allocator, ") {\n");
+                            try self.generateFrameMarker(method_instance.struct_name, method.name);
+                            
+                            try self.recordParamTypes(&[_]ast.Param{}, layout.Bindings.empty);
                             
                             // 🆕 设置方法上下文，用于方法体生成时的类型替换
                             self.current_method_context = .{
@@ -2100,6 +3813,7 @@ pub const CodeGen = struct {
                         if (type_decl.kind == .struct_type) {
                             const st = type_decl.kind.struct_type;
                             for (st.methods) |method| {
+                                if (!self.receiverBoundsMet(method, type_decl, saved_type_args.items)) continue;
                                 // 检查是否有self参数（实例方法）
                                 if (method.params.len > 0 and 
                                     std.mem.eql(u8, method.params[0].name, "self")) {
@@ -0,0 +1,248 @@
+//! Codes - 诊断代码注册表
+//!
+//! 编译器报告的每一种诊断都有一个稳定的代码（error[E0004]: literal out of range ...），
+//! 全部在这里登记，供 pawc explain 和诊断文档的生成使用：
+//!   - 代码由类别前缀和 4 位数字组成：
+//!       P 语法分析   E 类型检查等语义错误   W 警告   I 编译器内部错误（代码生成失败）
+//!   - 代码一旦发布就不再改变含义；诊断删除后代码也不再分配给别的诊断
+//!   - 诊断通过 Code 枚举引用代码：没有登记的代码无法通过编译
+//!   - 编译期检查：代码唯一、格式正确、前缀与类别一致、说明不为空
+//!
+//! 用法：
+//!   Diagnostic.init(.Error, msg, span, notes, help).withCode(.literal_out_of_range)
+
+const std = @import("std");
+
+pub const Category = enum {
+    parse,
+    semantic,
+    warning,
+    internal,
+
+    pub fn prefix(self: Category) u8 {
+        return switch (self) {
+            .parse => 'P',
+            .semantic => 'E',
+            .warning => 'W',
+            .internal => 'I',
+        };
+    }
+};
+
+pub const Info = struct {
+    id: []const u8,          // "E0004"
+    category: Category,
+    summary: []const u8,     // 一句话说明（英文，与诊断消息一致）
+};
+
+pub const Code = enum {
+    // P：语法分析
+    unknown_attribute,
+    misplaced_attribute,
+    duplicate_attribute,
+    unknown_lint,
+    invalid_asm_declaration,
+    generic_foreign_function,
+    invalid_link_name,
+    unknown_where_parameter,
+    integer_literal_too_large,
+    invalid_utf8_string,
+    invalid_byte_literal,
+    type_query_arity,
+    embed_file_failed,
+    unknown_bound,
+    invalid_attribute_argument,
+    function_name_outside_function,
+    empty_dispatch,
+    invalid_impl,
+    expected_declaration,
+    unexpected_token,
+    expected_expression,
+    expected_type,
+    expected_pattern,
+    unknown_character,
+    unterminated_literal,
+
+    // E：语义错误
+    undefined_variable,
+    unknown_function,
+    mismatched_types,
+    literal_out_of_range,
+    invalid_char_value,
+    index_out_of_bounds,
+    never_returns,
+    missing_bound,
+    ambiguous_call,
+    cannot_infer_into,
+    invalid_conversion,
+    asm_not_enabled,
+    asm_missing_target,
+    asm_invalid_signature,
+    unknown_layout,
+    unsigned_negation,
+    shadowed_label,
+    break_outside_loop,
+    undeclared_label,
+    break_value_outside_loop_expr,
+    non_exhaustive_match,
+    invalid_pattern,
+    unknown_variant,
+    pattern_arity,
+    empty_range_pattern,
+    const_eval_failed,
+    invalid_type_alias,
+    limit_exceeded,
+    generic_function_value,
+    not_callable,
+    unsupported_lambda,
+    assign_to_capture,
+    invalid_test_function,
+    unknown_supertrait,
+    cyclic_supertrait,
+    track_caller_function_value,
+    missing_operator_method,
+    unknown_associated_type,
+    missing_impl_method,
+    unknown_trait,
+    wrong_argument_count,
+    missing_main,
+    assign_to_immutable,
+    invalid_assignment_target,
+    conflicting_type_argument,
+    await_outside_async,
+    not_indexable,
+    private_item,
+
+    // W：警告
+    infinite_recursion,
+    unused_value,
+    unreachable_match_arm,
+    unused_pub,
+
+    // I：编译器内部错误
+    codegen_failed,
+    codegen_time_limit,
+    invalid_ir,
+
+    pub fn info(self: Code) Info {
+        return switch (self) {
+            .unknown_attribute => .{ .id = "P0001", .category = .parse, .summary = "unknown attribute" },
+            .misplaced_attribute => .{ .id = "P0002", .category = .parse, .summary = "attribute applied to a declaration that does not accept it" },
+            .duplicate_attribute => .{ .id = "P0003", .category = .parse, .summary = "attribute or @asm body given more than once" },
+            .unknown_lint => .{ .id = "P0004", .category = .parse, .summary = "unknown lint in @allow" },
+            .invalid_asm_declaration => .{ .id = "P0005", .category = .parse, .summary = "malformed @asm function" },
+            .generic_foreign_function => .{ .id = "P0006", .category = .parse, .summary = "extern or @asm function declared generic" },
+            .invalid_link_name => .{ .id = "P0007", .category = .parse, .summary = "malformed @link_name attribute" },
+            .unknown_where_parameter => .{ .id = "P0008", .category = .parse, .summary = "where clause names an undeclared type parameter" },
+            .integer_literal_too_large => .{ .id = "P0009", .category = .parse, .summary = "integer literal larger than i64 max" },
+            .invalid_utf8_string => .{ .id = "P0010", .category = .parse, .summary = "string literal is not valid UTF-8" },
+            .invalid_byte_literal => .{ .id = "P0011", .category = .parse, .summary = "malformed byte literal or byte escape" },
+            .type_query_arity => .{ .id = "P0012", .category = .parse, .summary = "size_of/align_of without exactly one type argument" },
+            .embed_file_failed => .{ .id = "P0013", .category = .parse, .summary = "embed_file cannot read the file" },
+            .unknown_bound => .{ .id = "P0014", .category = .parse, .summary = "unknown type parameter bound" },
+            .invalid_attribute_argument => .{ .id = "P0015", .category = .parse, .summary = "attribute argument has the wrong form" },
+            .function_name_outside_function => .{ .id = "P0016", .category = .parse, .summary = "__FUNCTION__ used outside of a function body" },
+            .empty_dispatch => .{ .id = "P0017", .category = .parse, .summary = "dispatch lists no types" },
+            .invalid_impl => .{ .id = "P0018", .category = .parse, .summary = "impl block has the wrong form or targets a type not declared in the file" },
+            .expected_declaration => .{ .id = "P0019", .category = .parse, .summary = "token cannot start a top-level declaration" },
+            .unexpected_token => .{ .id = "P0020", .category = .parse, .summary = "token differs from the one the grammar requires here" },
+            .expected_expression => .{ .id = "P0021", .category = .parse, .summary = "token cannot start an expression" },
+            .expected_type => .{ .id = "P0022", .category = .parse, .summary = "token cannot start a type" },
+            .expected_pattern => .{ .id = "P0023", .category = .parse, .summary = "token cannot start a pattern" },
+            .unknown_character => .{ .id = "P0024", .category = .parse, .summary = "character that cannot start a token" },
+            .unterminated_literal => .{ .id = "P0025", .category = .parse, .summary = "string, char or byte literal without its closing quote" },
+
+            .undefined_variable => .{ .id = "E0001", .category = .semantic, .summary = "use of an undeclared variable" },
+            .unknown_function => .{ .id = "E0002", .category = .semantic, .summary = "call to a function that is not in scope" },
+            .mismatched_types => .{ .id = "E0003", .category = .semantic, .summary = "a value's type differs from the type required here" },
+            .literal_out_of_range => .{ .id = "E0004", .category = .semantic, .summary = "integer literal does not fit its type" },
+            .invalid_char_value => .{ .id = "E0005", .category = .semantic, .summary = "constant converted to char is not a Unicode scalar value" },
+            .index_out_of_bounds => .{ .id = "E0006", .category = .semantic, .summary = "constant index outside a fixed-size array" },
+            .never_returns => .{ .id = "E0007", .category = .semantic, .summary = "function declared '!' can return" },
+            .missing_bound => .{ .id = "E0008", .category = .semantic, .summary = "type parameter lacks a bound its use requires" },
+            .ambiguous_call => .{ .id = "E0009", .category = .semantic, .summary = "call resolves to methods of several traits" },
+            .cannot_infer_into => .{ .id = "E0010", .category = .semantic, .summary = "target type of into() cannot be inferred" },
+            .invalid_conversion => .{ .id = "E0011", .category = .semantic, .summary = "no conversion between the two types" },
+            .asm_not_enabled => .{ .id = "E0012", .category = .semantic, .summary = "@asm function without --allow-asm" },
+            .asm_missing_target => .{ .id = "E0013", .category = .semantic, .summary = "@asm function has no body for the target architecture" },
+            .asm_invalid_signature => .{ .id = "E0014", .category = .semantic, .summary = "@asm parameter or return type cannot be passed in registers" },
+            .unknown_layout => .{ .id = "E0015", .category = .semantic, .summary = "size_of/align_of of a type without a layout" },
+            .unsigned_negation => .{ .id = "E0016", .category = .semantic, .summary = "negation of an unsigned value" },
+            .shadowed_label => .{ .id = "E0017", .category = .semantic, .summary = "loop label shadows an enclosing loop's label" },
+            .break_outside_loop => .{ .id = "E0018", .category = .semantic, .summary = "break or continue outside of a loop" },
+            .undeclared_label => .{ .id = "E0019", .category = .semantic, .summary = "use of an undeclared loop label" },
+            .break_value_outside_loop_expr => .{ .id = "E0020", .category = .semantic, .summary = "break with a value outside a loop expression" },
+            .non_exhaustive_match => .{ .id = "E0021", .category = .semantic, .summary = "match does not cover every value" },
+            .invalid_pattern => .{ .id = "E0022", .category = .semantic, .summary = "pattern cannot match a value of this type" },
+            .unknown_variant => .{ .id = "E0023", .category = .semantic, .summary = "pattern names a variant the enum does not have" },
+            .pattern_arity => .{ .id = "E0024", .category = .semantic, .summary = "pattern binds a different number of values than the variant carries" },
+            .empty_range_pattern => .{ .id = "E0025", .category = .semantic, .summary = "range pattern matches no value" },
+            .const_eval_failed => .{ .id = "E0026", .category = .semantic, .summary = "compile-time evaluation failed" },
+            .invalid_type_alias => .{ .id = "E0027", .category = .semantic, .summary = "type alias cannot be expanded" },
+            .limit_exceeded => .{ .id = "E0028", .category = .semantic, .summary = "compilation exceeded a Paw.toml [limits] setting" },
+            .generic_function_value => .{ .id = "E0029", .category = .semantic, .summary = "generic function used as a value" },
+            .not_callable => .{ .id = "E0030", .category = .semantic, .summary = "call of a value that is not a function" },
+            .unsupported_lambda => .{ .id = "E0031", .category = .semantic, .summary = "lambda cannot be used in this context" },
+            .assign_to_capture => .{ .id = "E0032", .category = .semantic, .summary = "assignment to a variable captured by a lambda" },
+            .invalid_test_function => .{ .id = "E0033", .category = .semantic, .summary = "@test function has parameters, type parameters or a return value" },
+            .unknown_supertrait => .{ .id = "E0034", .category = .semantic, .summary = "supertrait is not a trait or has the wrong number of type arguments" },
+            .cyclic_supertrait => .{ .id = "E0035", .category = .semantic, .summary = "trait is its own supertrait" },
+            .track_caller_function_value => .{ .id = "E0036", .category = .semantic, .summary = "@track_caller function used as a value" },
+            .missing_operator_method => .{ .id = "E0037", .category = .semantic, .summary = "operator used on a type that does not implement it" },
+            .unknown_associated_type => .{ .id = "E0038", .category = .semantic, .summary = "associated type is not declared by the trait or defined by the type" },
+            .missing_impl_method => .{ .id = "E0039", .category = .semantic, .summary = "impl block does not define every method of its trait" },
+            .unknown_trait => .{ .id = "E0040", .category = .semantic, .summary = "impl or bound names a trait that is not declared" },
+            .wrong_argument_count => .{ .id = "E0041", .category = .semantic, .summary = "function or function value called with the wrong number of arguments" },
+            .missing_main => .{ .id = "E0042", .category = .semantic, .summary = "program has no main function" },
+            .assign_to_immutable => .{ .id = "E0043", .category = .semantic, .summary = "assignment to an immutable variable or an element of one" },
+            .invalid_assignment_target => .{ .id = "E0044", .category = .semantic, .summary = "left side of an assignment is not a variable, field or element" },
+            .conflicting_type_argument => .{ .id = "E0045", .category = .semantic, .summary = "type parameter inferred as two different types" },
+            .await_outside_async => .{ .id = "E0046", .category = .semantic, .summary = "await outside of an async function" },
+            .not_indexable => .{ .id = "E0047", .category = .semantic, .summary = "index on a value that is not an array or string" },
+            .private_item => .{ .id = "E0048", .category = .semantic, .summary = "non-pub item of a module used through a namespace alias" },
+
+            .infinite_recursion => .{ .id = "W0001", .category = .warning, .summary = "functions call each other on every path" },
+            .unused_value => .{ .id = "W0002", .category = .warning, .summary = "value of an expression statement is discarded" },
+            .unreachable_match_arm => .{ .id = "W0003", .category = .warning, .summary = "match arm can never be taken" },
+            .unused_pub => .{ .id = "W0004", .category = .warning, .summary = "pub item is not used outside its module" },
+
+            .codegen_failed => .{ .id = "I0001", .category = .internal, .summary = "backend could not generate code for a function" },
+            .codegen_time_limit => .{ .id = "I0002", .category = .internal, .summary = "code generation for a function exceeded its time limit" },
+            .invalid_ir => .{ .id = "I0003", .category = .internal, .summary = "backend generated invalid code" },
+        };
+    }
+
+    pub fn id(self: Code) []const u8 {
+        return self.info().id;
+    }
+
+    /// "E0004"（不区分大小写）对应的代码
+    pub fn fromId(text: []const u8) ?Code {
+        for (std.enums.values(Code)) |code| {
+            if (std.ascii.eqlIgnoreCase(code.id(), text)) return code;
+        }
+        return null;
+    }
+};
+
+// 代码唯一、格式为前缀 + 4 位数字、前缀与类别一致
+comptime {
+    @setEvalBranchQuota(100_000);
+    const all = std.enums.values(Code);
+    for (all, 0..) |code, i| {
+        const entry = code.info();
+        if (entry.id.len != 5) @compileError("diagnostic code '" ++ entry.id ++ "' is not a prefix and 4 digits");
+        if (entry.id[0] != entry.category.prefix()) {
+            @compileError("diagnostic code '" ++ entry.id ++ "' (" ++ @tagName(code) ++ ") does not match its category " ++ @tagName(entry.category));
+        }
+        for (entry.id[1..]) |c| {
+            if (!std.ascii.isDigit(c)) @compileError("diagnostic code '" ++ entry.id ++ "' is not a prefix and 4 digits");
+        }
+        if (entry.summary.len == 0) @compileError("diagnostic code '" ++ entry.id ++ "' has no summary");
+        for (all[i + 1 ..]) |other| {
+            if (std.mem.eql(u8, entry.id, other.info().id)) {
+                @compileError("diagnostic code '" ++ entry.id ++ "' is used by both " ++ @tagName(code) ++ " and " ++ @tagName(other));
+            }
+        }
+    }
+}
@@ -0,0 +1,1047 @@
+//! ConstEval - 编译期求值（const fn / const 全局常量 / 数组长度）
+//!
+//! 🆕 合并导入的声明之后运行（独立的一遍，不在 Parser.parse 中），直接解释 AST：
+//!   - const NAME: T = expr;  初始化表达式求值后替换为字面量
+//!   - 🆕 数组 / 结构体字面量的常量：逐个元素（字段）求值为字面量，后端直接生成静态数据
+//!   - [T; expr]              数组长度求值后写回 Type.array.size
+//!   - const fn               求值过程中只能调用 const fn
+//!
+//! 求值有调用深度和步数限制，无限递归或死循环会报错而不是卡住编译器。
+//! 🆕 求值看到本文件和导入的声明：常量可以调用导入的 const fn。
+//! 模块加载时先在模块内求值一遍（模块的私有常量），导入的 const fn 只能再调用同样导入的函数。
+//!
+//! 🆕 同一个求值器也用作解释器（Mode.interpret，见 interpreter.zig）：
+//!   允许调用普通函数、泛型函数和 paw_write 等内置函数（println 等是其上的 prelude 函数），不限制步数。
+//!   常量求值时整数统一按 i64 计算，溢出报错而不是回绕；
+//!   🆕 解释模式按变量、参数和返回值声明的整数类型回绕（与原生后端一致，见 intType）。
+//!
+//! 示例：
+//!   const fn square(x: i64) -> i64 { return x * x; }
+//!   const TABLE_SIZE: i64 = square(4);
+//!   fn main() -> i32 { let table: [i32; TABLE_SIZE] = ...; }
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const diagnostic = @import("diagnostic.zig");
+const Diagnostic = diagnostic.Diagnostic;
+const Span = diagnostic.Span;
+
+/// 最大调用深度
+pub const MAX_CALL_DEPTH: usize = 256;
+/// 默认的最大求值步数（每个表达式和语句算一步；🆕 Paw.toml [limits] const_eval_steps 可调整）
+pub const MAX_STEPS: usize = 1_000_000;
+/// 🆕 解释模式的最大调用深度（解释器递归使用宿主栈）
+pub const MAX_INTERP_DEPTH: usize = 2048;
+
+/// 🆕 求值模式
+pub const Mode = enum {
+    const_eval,  // 编译期常量求值：只能调用 const fn
+    interpret,   // 解释执行整个程序（--interpret）
+};
+
+// ============================================================================
+// Value - 编译期值
+// ============================================================================
+
+pub const Value = union(enum) {
+    int: i64,
+    float: f64,
+    bool: bool,
+    char: u32,
+    string: []const u8,
+    void,
+
+    pub fn typeName(self: Value) []const u8 {
+        return switch (self) {
+            .int => "integer",
+            .float => "float",
+            .bool => "bool",
+            .char => "char",
+            .string => "string",
+            .void => "void",
+        };
+    }
+
+    /// 转换回字面量表达式（替换 const 的初始化表达式）
+    pub fn toExpr(self: Value) ?ast.Expr {
+        return switch (self) {
+            .int => |v| ast.Expr{ .int_literal = .{ .value = v } },
+            .float => |v| ast.Expr{ .float_literal = v },
+            .bool => |v| ast.Expr{ .bool_literal = v },
+            .char => |v| ast.Expr{ .char_literal = v },
+            .string => |v| ast.Expr{ .string_literal = v },
+            .void => null,
+        };
+    }
+};
+
+/// Unwind 只在求值器内部使用：块表达式中的 return / break / continue 沿表达式向外传到所在的语句
+pub const EvalError = std.mem.Allocator.Error || error{ ConstEvalFailed, Unwind };
+
+/// 语句执行结果（控制流）
+const Flow = union(enum) {
+    normal,
+    ret: Value,
+    brk: struct { label: ?[]const u8, value: ?Value },
+    cont: ?[]const u8,
+};
+
+/// 局部变量
+const Frame = struct {
+    values: std.StringHashMap(Value),
+    int_types: std.StringHashMap(ast.Type),  // 🆕 解释模式：整数变量声明（或推断）的类型，赋值和运算按位宽回绕
+
+    fn init(allocator: std.mem.Allocator) Frame {
+        return Frame{
+            .values = std.StringHashMap(Value).init(allocator),
+            .int_types = std.StringHashMap(ast.Type).init(allocator),
+        };
+    }
+
+    fn deinit(self: *Frame) void {
+        self.values.deinit();
+        self.int_types.deinit();
+    }
+};
+
+// ============================================================================
+// Evaluator
+// ============================================================================
+
+pub const Evaluator = struct {
+    allocator: std.mem.Allocator,  // arena（与 AST 同生命周期）
+    functions: std.StringHashMap(ast.FunctionDecl),
+    consts: std.StringHashMap(*ast.ConstDecl),
+    values: std.StringHashMap(Value),         // 已求值的 const
+    in_progress: std.StringHashMap(void),     // 正在求值的 const（检测循环依赖）
+    depth: usize,
+    steps: usize,
+    max_steps: u64,                           // 🆕 步数限制
+    call_stack: std.ArrayList([]const u8),    // 报错时显示调用链
+    current_const: ?*ast.ConstDecl,
+    mode: Mode,
+    stdout_buffer: std.ArrayList(u8),  // 🆕 解释模式的 stdout 缓冲（与原生程序的 stdio 缓冲一致）
+    stdout_is_tty: bool,               // 🆕 终端按行写出
+    log_level: ?i64,                   // 🆕 PAW_LOG_LEVEL（第一次 paw_log 时读取）
+    rng_state: u64,                    // 🆕 gen_int / gen_string 的 PRNG 状态（种子 0）
+    unwinding: ?Flow,                  // 🆕 正在向外传递的控制流（error.Unwind）
+
+    pub fn init(allocator: std.mem.Allocator) Evaluator {
+        return Evaluator{
+            .allocator = allocator,
+            .functions = std.StringHashMap(ast.FunctionDecl).init(allocator),
+            .consts = std.StringHashMap(*ast.ConstDecl).init(allocator),
+            .values = std.StringHashMap(Value).init(allocator),
+            .in_progress = std.StringHashMap(void).init(allocator),
+            .depth = 0,
+            .steps = 0,
+            .max_steps = MAX_STEPS,
+            .call_stack = std.ArrayList([]const u8){},
+            .current_const = null,
+            .mode = .const_eval,
+            .stdout_buffer = std.ArrayList(u8){},
+            .stdout_is_tty = std.fs.File.stdout().isTty(),
+            .log_level = null,
+            .rng_state = 0,
+            .unwinding = null,
+        };
+    }
+
+    pub fn deinit(self: *Evaluator) void {
+        self.functions.deinit();
+        self.consts.deinit();
+        self.values.deinit();
+        self.in_progress.deinit();
+        self.call_stack.deinit(self.allocator);
+        self.stdout_buffer.deinit(self.allocator);
+    }
+
+    /// 🆕 写出缓冲的 stdout（paw_flush、退出、程序结束时调用）
+    pub fn flushStdout(self: *Evaluator) void {
+        std.fs.File.stdout().writeAll(self.stdout_buffer.items) catch {};
+        self.stdout_buffer.clearRetainingCapacity();
+    }
+
+    /// 求值程序中所有 const 初始化表达式和数组长度表达式（原地改写 AST）
+    pub fn evaluateProgram(self: *Evaluator, program: ast.Program) EvalError!void {
+        try self.registerDeclarations(program);
+        try self.evaluateDeclarations(program.declarations);
+    }
+
+    fn evaluateDeclarations(self: *Evaluator, declarations: []ast.TopLevelDecl) EvalError!void {
+        for (declarations) |*decl| {
+            switch (decl.*) {
+                .const_decl => |*cd| {
+                    if (isAggregate(cd.value.*)) try self.foldAggregateConst(cd) else _ = try self.evalConst(cd);
+                    if (cd.type) |*t| {
+                        try self.resolveType(t);
+                        try self.checkArrayLengths(cd, t.*, cd.value.*);
+                    }
+                },
+                .function => |*func| try self.resolveFunctionTypes(func),
+                .type_decl => |*td| switch (td.kind) {
+                    .struct_type => |st| {
+                        for (st.fields) |*field| try self.resolveType(&field.type);
+                        for (st.methods) |*method| try self.resolveFunctionTypes(method);
+                    },
+                    .enum_type => |et| {
+                        for (et.methods) |*method| try self.resolveFunctionTypes(method);
+                    },
+                    .trait_type => {},
+                    .alias => |*target| try self.resolveType(target),  // 🆕 导入后再展开时带上数组长度
+                },
+                else => {},
+            }
+        }
+    }
+
+    /// 登记可调用的函数和可引用的 const
+    pub fn registerDeclarations(self: *Evaluator, program: ast.Program) EvalError!void {
+        for (program.declarations) |*decl| {
+            switch (decl.*) {
+                .function => |func| try self.functions.put(func.name, func),
+                .const_decl => |*cd| try self.consts.put(cd.name, cd),
+                else => {},
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // const 全局常量
+    // ------------------------------------------------------------------------
+
+    fn evalConst(self: *Evaluator, cd: *ast.ConstDecl) EvalError!Value {
+        if (self.values.get(cd.name)) |value| return value;
+        if (isAggregate(cd.value.*)) {
+            return self.fail("constant '{s}' is an aggregate and cannot be used in {s}", .{ cd.name, self.subject() }, "only integer, float, bool, char and string constants can be used at compile time");
+        }
+        if (self.in_progress.contains(cd.name)) {
+            return self.fail("constant '{s}' depends on itself", .{cd.name}, "break the cycle by writing one of the values as a literal");
+        }
+        try self.in_progress.put(cd.name, {});
+        defer _ = self.in_progress.remove(cd.name);
+
+        const prev_const = self.current_const;
+        self.current_const = cd;
+        defer self.current_const = prev_const;
+        self.steps = 0;
+
+        var frame = Frame.init(self.allocator);
+        defer frame.deinit();
+        const value = try self.evalRoot(cd.value.*, &frame);
+        const literal = value.toExpr() orelse {
+            return self.fail("constant '{s}' has no value", .{cd.name}, "the initializer must produce an integer, float, bool, char or string");
+        };
+        cd.value.* = literal;
+        try self.values.put(cd.name, value);
+        return value;
+    }
+
+    // ------------------------------------------------------------------------
+    // 🆕 数组 / 结构体常量
+    // ------------------------------------------------------------------------
+
+    fn isAggregate(expr: ast.Expr) bool {
+        return expr == .array_literal or expr == .struct_init;
+    }
+
+    /// 把聚合常量的每个元素（字段）求值为字面量，整个初始化表达式成为静态数据
+    fn foldAggregateConst(self: *Evaluator, cd: *ast.ConstDecl) EvalError!void {
+        const prev_const = self.current_const;
+        self.current_const = cd;
+        defer self.current_const = prev_const;
+
+        if (cd.type == null and cd.value.* == .array_literal) {
+            const help = try std.fmt.allocPrint(self.allocator, "write the element type and length, e.g. `const {s}: [i32; {d}] = [...]`", .{ cd.name, cd.value.array_literal.len });
+            return self.fail("array constant '{s}' needs a type annotation", .{cd.name}, help);
+        }
+        if (cd.type) |t| {
+            if (t == .generic_instance) {
+                return self.fail("constants of generic struct type '{s}' are not supported yet", .{t.generic_instance.name}, null);
+            }
+        }
+        try self.rejectGenericStruct(cd.value.*);
+        self.steps = 0;
+        var frame = Frame.init(self.allocator);
+        defer frame.deinit();
+        try self.foldAggregate(cd.value, &frame);
+    }
+
+    /// 泛型结构体的实例在单态化时才生成定义，常量还不能使用它们
+    fn rejectGenericStruct(self: *Evaluator, expr: ast.Expr) EvalError!void {
+        switch (expr) {
+            .array_literal => |elements| for (elements) |element| try self.rejectGenericStruct(element),
+            .struct_init => |si| {
+                if (si.type_args.len > 0) {
+                    return self.fail("constants of generic struct type '{s}' are not supported yet", .{si.type_name}, null);
+                }
+                for (si.fields) |field| try self.rejectGenericStruct(field.value);
+            },
+            else => {},
+        }
+    }
+
+    fn foldAggregate(self: *Evaluator, expr: *ast.Expr, frame: *Frame) EvalError!void {
+        switch (expr.*) {
+            .array_literal => |elements| for (elements) |*element| try self.foldAggregate(element, frame),
+            .struct_init => |si| for (si.fields) |*field| try self.foldAggregate(&field.value, frame),
+            else => {
+                const value = try self.evalRoot(expr.*, frame);
+                expr.* = value.toExpr() orelse return self.fail("an element of the constant has no value", .{}, null);
+            },
+        }
+    }
+
+    /// 数组常量的元素个数必须与声明的长度一致（嵌套数组逐层检查）
+    fn checkArrayLengths(self: *Evaluator, cd: *ast.ConstDecl, t: ast.Type, expr: ast.Expr) EvalError!void {
+        if (t != .array or expr != .array_literal) return;
+        const elements = expr.array_literal;
+        if (t.array.size) |size| {
+            if (elements.len != size) {
+                const prev_const = self.current_const;
+                self.current_const = cd;
+                defer self.current_const = prev_const;
+                return self.fail("constant '{s}' has {d} element(s), but its type declares {d}", .{ cd.name, elements.len, size }, null);
+            }
+        }
+        for (elements) |element| try self.checkArrayLengths(cd, t.array.element.*, element);
+    }
+
+    // ------------------------------------------------------------------------
+    // 数组长度 [T; expr]
+    // ------------------------------------------------------------------------
+
+    fn resolveFunctionTypes(self: *Evaluator, func: *ast.FunctionDecl) EvalError!void {
+        for (func.params) |*param| try self.resolveType(&param.type);
+        try self.resolveType(&func.return_type);
+        try self.resolveStmtTypes(func.body);
+    }
+
+    fn resolveStmtTypes(self: *Evaluator, stmts: []ast.Stmt) EvalError!void {
+        for (stmts) |*stmt| {
+            switch (stmt.*) {
+                .let_decl => |*let| if (let.type) |*t| try self.resolveType(t),
+                .loop_stmt => |loop| try self.resolveStmtTypes(loop.body),
+                .while_loop => |loop| try self.resolveStmtTypes(loop.body),
+                .for_loop => |loop| try self.resolveStmtTypes(loop.body),
+                else => {},
+            }
+        }
+    }
+
+    fn resolveType(self: *Evaluator, t: *ast.Type) EvalError!void {
+        switch (t.*) {
+            .array => |*arr| {
+                try self.resolveType(arr.element);
+                const size_expr = arr.size_expr orelse return;
+                if (arr.size != null) return;
+
+                self.steps = 0;
+                var frame = Frame.init(self.allocator);
+                defer frame.deinit();
+                const value = try self.evalRoot(size_expr.*, &frame);
+                if (value != .int) {
+                    return self.fail("array length must be an integer, found {s}", .{value.typeName()}, null);
+                }
+                if (value.int < 0) {
+                    return self.fail("array length must not be negative, found {d}", .{value.int}, null);
+                }
+                arr.size = @intCast(value.int);
+            },
+            .pointer => |inner| try self.resolveType(inner),
+            else => {},
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // 表达式求值
+    // ------------------------------------------------------------------------
+
+    fn step(self: *Evaluator) EvalError!void {
+        if (self.mode == .interpret) return;
+        self.steps += 1;
+        if (self.steps > self.max_steps) {
+            return self.fail("constant evaluation exceeded the limit of {d} steps", .{self.max_steps}, "check for an infinite loop in the const fn, or raise `const_eval_steps` in the [limits] section of Paw.toml");
+        }
+    }
+
+    /// 不在语句中的表达式（const 初始化、数组长度）：控制流无处可去
+    fn evalRoot(self: *Evaluator, expr: ast.Expr, frame: *Frame) EvalError!Value {
+        return self.evalExpr(expr, frame) catch |err| switch (err) {
+            error.Unwind => {
+                self.unwinding = null;
+                return self.fail("control flow out of a constant expression is not supported", .{}, null);
+            },
+            else => return err,
+        };
+    }
+
+    fn evalExpr(self: *Evaluator, expr: ast.Expr, frame: *Frame) EvalError!Value {
+        try self.step();
+        return switch (expr) {
+            .int_literal => |literal| Value{ .int = literal.value },
+            .float_literal => |v| Value{ .float = v },
+            .bool_literal => |v| Value{ .bool = v },
+            .char_literal => |v| Value{ .char = v },
+            .byte_literal => |v| Value{ .int = v },
+            .string_literal => |v| Value{ .string = v },
+            .identifier => |name| blk: {
+                if (frame.values.get(name)) |value| break :blk value;
+                if (self.consts.get(name)) |cd| break :blk try self.evalConst(cd);
+                if (self.mode == .interpret) break :blk self.fail("unknown variable '{s}'", .{name}, null);
+                break :blk self.fail("cannot use '{s}' in a constant expression", .{name}, "only literals, constants, parameters and local variables of const fn are allowed");
+            },
+            .binary => |bin| if (bin.resolved_operator.*) |type_name|
+                self.fail("operator method '{s}.{s}' cannot be evaluated {s}", .{ type_name, bin.op.overloadMethod().?, self.where() }, null)
+            else
+                try self.evalBinary(bin.op, bin.left.*, bin.right.*, frame),
+            .unary => |un| blk: {
+                const operand = try self.evalExpr(un.operand.*, frame);
+                switch (un.op) {
+                    .neg => switch (operand) {
+                        .int => |v| {
+                            if (self.intType(expr, frame)) |t| break :blk Value{ .int = wrapInt(0 -% v, t) };
+                            break :blk Value{ .int = std.math.sub(i64, 0, v) catch return self.overflow("-") };
+                        },
+                        .float => |v| break :blk Value{ .float = -v },
+                        else => break :blk self.typeError("-", operand),
+                    },
+                    .not => switch (operand) {
+                        .bool => |v| break :blk Value{ .bool = !v },
+                        else => break :blk self.typeError("!", operand),
+                    },
+                }
+            },
+            .as_expr => |cast| if (cast.resolved_from.*)
+                self.fail("From conversion to '{s}' cannot be evaluated {s}", .{ cast.target_type.named, self.where() }, null)
+            else
+                try self.evalCast(try self.evalExpr(cast.value.*, frame), cast.target_type),
+            .if_expr => |if_expr| blk: {
+                const cond = try self.evalExpr(if_expr.condition.*, frame);
+                if (cond != .bool) break :blk self.typeError("if", cond);
+                if (cond.bool) break :blk try self.evalExpr(if_expr.then_branch.*, frame);
+                if (if_expr.else_branch) |else_branch| break :blk try self.evalExpr(else_branch.*, frame);
+                break :blk Value.void;
+            },
+            .block => |stmts| blk: {
+                // 块的值是尾表达式的值
+                // 🆕 return / break / continue 不在块内结束：交给所在的语句（execStmt）继续传递
+                if (stmts.len == 0) break :blk Value.void;
+                for (stmts[0 .. stmts.len - 1]) |stmt| {
+                    const flow = try self.execStmt(stmt, frame);
+                    if (flow != .normal) return self.unwind(flow);
+                }
+                const tail = stmts[stmts.len - 1];
+                if (tail == .expr) break :blk try self.evalExpr(tail.expr, frame);
+                const flow = try self.execStmt(tail, frame);
+                if (flow != .normal) return self.unwind(flow);
+                break :blk Value.void;
+            },
+            .call => |call| blk: {
+                // 🆕 into(x)：同类型时就是 x
+                if (call.resolved_into.*) {
+                    if (ast.intoTarget(call)) |target| break :blk self.fail("From conversion to '{s}' cannot be evaluated {s}", .{ target, self.where() }, null);
+                    break :blk try self.evalExpr(call.args[0], frame);
+                }
+                break :blk try self.evalCall(call.callee.*, call.args, frame);
+            },
+            .string_interp => |si| blk: {
+                var buf = std.ArrayList(u8){};
+                for (si.parts) |part| switch (part) {
+                    .literal => |text| try buf.appendSlice(self.allocator, text),
+                    .expr => |e| try self.appendValue(&buf, try self.evalExpr(e, frame)),
+                };
+                break :blk Value{ .string = try buf.toOwnedSlice(self.allocator) };
+            },
+            else => self.fail("this expression cannot be evaluated {s}", .{self.where()}, null),
+        };
+    }
+
+    fn evalBinary(self: *Evaluator, op: ast.BinaryOp, left_expr: ast.Expr, right_expr: ast.Expr, frame: *Frame) EvalError!Value {
+        // 短路求值
+        if (op == .and_op or op == .or_op) {
+            const left = try self.evalExpr(left_expr, frame);
+            if (left != .bool) return self.typeError(if (op == .and_op) "&&" else "||", left);
+            if (op == .and_op and !left.bool) return Value{ .bool = false };
+            if (op == .or_op and left.bool) return Value{ .bool = true };
+            const right = try self.evalExpr(right_expr, frame);
+            if (right != .bool) return self.typeError(if (op == .and_op) "&&" else "||", right);
+            return right;
+        }
+
+        const left = try self.evalExpr(left_expr, frame);
+        const right = try self.evalExpr(right_expr, frame);
+        if (@as(std.meta.Tag(Value), left) != @as(std.meta.Tag(Value), right)) {
+            return self.fail("mismatched operand types in {s}: {s} and {s}", .{ self.subject(), left.typeName(), right.typeName() }, null);
+        }
+
+        switch (left) {
+            .int => |a| {
+                const b = right.int;
+                if (self.binaryIntType(left_expr, right_expr, frame)) |t| return self.evalIntBinary(op, a, b, t);
+                return switch (op) {
+                    .add => Value{ .int = std.math.add(i64, a, b) catch return self.overflow("+") },
+                    .sub => Value{ .int = std.math.sub(i64, a, b) catch return self.overflow("-") },
+                    .mul => Value{ .int = std.math.mul(i64, a, b) catch return self.overflow("*") },
+                    .div => if (b == 0) self.fail("division by zero in {s}", .{self.subject()}, null) else Value{ .int = @divTrunc(a, b) },
+                    .mod => if (b == 0) self.fail("division by zero in {s}", .{self.subject()}, null) else Value{ .int = @rem(a, b) },
+                    .eq => Value{ .bool = a == b },
+                    .ne => Value{ .bool = a != b },
+                    .lt => Value{ .bool = a < b },
+                    .le => Value{ .bool = a <= b },
+                    .gt => Value{ .bool = a > b },
+                    .ge => Value{ .bool = a >= b },
+                    .and_op, .or_op => unreachable,
+                };
+            },
+            .float => |a| {
+                const b = right.float;
+                return switch (op) {
+                    .add => Value{ .float = a + b },
+                    .sub => Value{ .float = a - b },
+                    .mul => Value{ .float = a * b },
+                    .div => Value{ .float = a / b },
+                    .mod => Value{ .float = @rem(a, b) },
+                    .eq => Value{ .bool = a == b },
+                    .ne => Value{ .bool = a != b },
+                    .lt => Value{ .bool = a < b },
+                    .le => Value{ .bool = a <= b },
+                    .gt => Value{ .bool = a > b },
+                    .ge => Value{ .bool = a >= b },
+                    .and_op, .or_op => unreachable,
+                };
+            },
+            .bool => |a| return switch (op) {
+                .eq => Value{ .bool = a == right.bool },
+                .ne => Value{ .bool = a != right.bool },
+                else => self.typeError("arithmetic", left),
+            },
+            .char => |a| {
+                const b = right.char;
+                return switch (op) {
+                    .eq => Value{ .bool = a == b },
+                    .ne => Value{ .bool = a != b },
+                    .lt => Value{ .bool = a < b },
+                    .le => Value{ .bool = a <= b },
+                    .gt => Value{ .bool = a > b },
+                    .ge => Value{ .bool = a >= b },
+                    else => self.typeError("arithmetic", left),
+                };
+            },
+            .string => |a| return switch (op) {
+                .eq => Value{ .bool = std.mem.eql(u8, a, right.string) },
+                .ne => Value{ .bool = !std.mem.eql(u8, a, right.string) },
+                else => self.typeError("arithmetic", left),
+            },
+            .void => return self.typeError("arithmetic", left),
+        }
+    }
+
+    /// 🆕 解释模式下已知类型的整数运算：按类型的位宽回绕，u64 按无符号比较和除法
+    fn evalIntBinary(self: *Evaluator, op: ast.BinaryOp, a: i64, b: i64, t: ast.Type) EvalError!Value {
+        if ((op == .div or op == .mod) and b == 0) return self.fail("division by zero in {s}", .{self.subject()}, null);
+        if (t == .u64) {
+            const ua: u64 = @bitCast(a);
+            const ub: u64 = @bitCast(b);
+            switch (op) {
+                .div => return Value{ .int = @bitCast(ua / ub) },
+                .mod => return Value{ .int = @bitCast(ua % ub) },
+                .lt => return Value{ .bool = ua < ub },
+                .le => return Value{ .bool = ua <= ub },
+                .gt => return Value{ .bool = ua > ub },
+                .ge => return Value{ .bool = ua >= ub },
+                else => {},
+            }
+        }
+        return switch (op) {
+            .add => Value{ .int = wrapInt(a +% b, t) },
+            .sub => Value{ .int = wrapInt(a -% b, t) },
+            .mul => Value{ .int = wrapInt(a *% b, t) },
+            // 除以 -1 单独处理：i64 的 MIN / -1 按回绕得到 MIN
+            .div => Value{ .int = wrapInt(if (b == -1) 0 -% a else @divTrunc(a, b), t) },
+            .mod => Value{ .int = if (b == -1) 0 else wrapInt(@rem(a, b), t) },
+            .eq => Value{ .bool = a == b },
+            .ne => Value{ .bool = a != b },
+            .lt => Value{ .bool = a < b },
+            .le => Value{ .bool = a <= b },
+            .gt => Value{ .bool = a > b },
+            .ge => Value{ .bool = a >= b },
+            .and_op, .or_op => unreachable,
+        };
+    }
+
+    /// 🆕 解释模式下整数表达式的静态类型（与 C 后端的 narrowIntType 规则一致），未知时返回 null
+    ///
+    /// 变量、参数、常量和函数返回值按声明的类型；未标注类型的整数字面量跟随另一侧的类型。
+    /// 常量求值时总是返回 null：按 i64 计算，溢出报错。
+    fn intType(self: *Evaluator, expr: ast.Expr, frame: *const Frame) ?ast.Type {
+        if (self.mode != .interpret) return null;
+        return switch (expr) {
+            .byte_literal => ast.Type.u8,
+            .identifier => |name| frame.int_types.get(name) orelse blk: {
+                if (frame.values.contains(name)) break :blk null;
+                const cd = self.consts.get(name) orelse break :blk null;
+                break :blk if (cd.type) |t| (if (isIntType(t)) t else null) else null;
+            },
+            .as_expr => |as_cast| if (isIntType(as_cast.target_type)) as_cast.target_type else null,
+            .unary => |un| if (un.op == .neg) self.intType(un.operand.*, frame) else null,
+            .binary => |bin| switch (bin.op) {
+                .add, .sub, .mul, .div, .mod => self.binaryIntType(bin.left.*, bin.right.*, frame),
+                else => null,
+            },
+            .call => |call| blk: {
+                if (call.callee.* != .identifier) break :blk null;
+                const func = self.functions.get(call.callee.identifier) orelse break :blk null;
+                break :blk if (isIntType(func.return_type)) func.return_type else null;
+            },
+            else => null,
+        };
+    }
+
+    /// 二元运算两侧的共同整数类型
+    fn binaryIntType(self: *Evaluator, left_expr: ast.Expr, right_expr: ast.Expr, frame: *const Frame) ?ast.Type {
+        const left = self.intType(left_expr, frame);
+        const right = self.intType(right_expr, frame);
+        if (left) |l| {
+            if (right) |r| return if (l.eql(r)) l else null;
+            return if (right_expr == .int_literal) l else null;
+        }
+        if (right) |r| return if (left_expr == .int_literal) r else null;
+        return null;
+    }
+
+    fn isIntType(t: ast.Type) bool {
+        return switch (t) {
+            .i8, .i16, .i32, .i64, .i128, .u8, .u16, .u32, .u64, .u128 => true,
+            else => false,
+        };
+    }
+
+    fn evalCast(self: *Evaluator, value: Value, target: ast.Type) EvalError!Value {
+        return switch (target) {
+            .i8, .i16, .i32, .i64, .i128, .u8, .u16, .u32, .u64, .u128 => switch (value) {
+                .int => |v| Value{ .int = wrapInt(v, target) },
+                .float => |v| Value{ .int = wrapInt(std.math.lossyCast(i64, v), target) },
+                .bool => |v| Value{ .int = @intFromBool(v) },
+                .char => |v| Value{ .int = wrapInt(v, target) },
+                else => self.typeError("as", value),
+            },
+            .f32, .f64 => switch (value) {
+                .int => |v| Value{ .float = @floatFromInt(v) },
+                .float => value,
+                else => self.typeError("as", value),
+            },
+            .bool => if (value == .bool) value else self.typeError("as", value),
+            .char => switch (value) {
+                .char => value,
+                .int => |v| Value{ .char = try self.checkChar(v) },
+                else => self.typeError("as", value),
+            },
+            else => self.typeError("as", value),
+        };
+    }
+
+    /// 🆕 `n as char` 要求 n 是 Unicode 标量值
+    /// 常量求值时报告编译错误；解释执行时与原生程序一样 panic（退出码 101）
+    fn checkChar(self: *Evaluator, v: i64) EvalError!u32 {
+        if (ast.isValidChar(v)) return @intCast(v);
+        if (self.mode == .interpret) {
+            self.flushStdout();
+            std.fs.File.stderr().writeAll("PANIC:\n" ++ ast.INVALID_CHAR_PANIC ++ "\n") catch {};
+            std.process.exit(101);
+        }
+        return self.fail("invalid char value {d}", .{v}, "a char must be a Unicode scalar value (0..=0x10FFFF, excluding surrogates 0xD800..=0xDFFF)");
+    }
+
+    /// 🆕 按目标整数类型的位宽回绕（与 C 的 (uint8_t) 等转换、LLVM 的 trunc 一致）
+    fn wrapInt(v: i64, target: ast.Type) i64 {
+        return switch (target) {
+            .i8 => @as(i8, @truncate(v)),
+            .u8 => @as(u8, @truncate(@as(u64, @bitCast(v)))),
+            .i16 => @as(i16, @truncate(v)),
+            .u16 => @as(u16, @truncate(@as(u64, @bitCast(v)))),
+            .i32 => @as(i32, @truncate(v)),
+            .u32 => @as(u32, @truncate(@as(u64, @bitCast(v)))),
+            else => v,
+        };
+    }
+
+    fn evalCall(self: *Evaluator, callee: ast.Expr, args: []ast.Expr, frame: *Frame) EvalError!Value {
+        if (callee != .identifier) {
+            if (self.mode == .interpret) return self.fail("method calls are not supported by the interpreter yet", .{}, null);
+            return self.fail("only calls to const fn can be evaluated at compile time", .{}, null);
+        }
+        const name = callee.identifier;
+
+        var values = std.ArrayList(Value){};
+        defer values.deinit(self.allocator);
+        for (args) |arg| try values.append(self.allocator, try self.evalExpr(arg, frame));
+
+        if (self.mode == .interpret) {
+            if (try self.callBuiltin(name, values.items)) |result| return result;
+        }
+        return self.callFunction(name, values.items);
+    }
+
+    /// 调用函数（参数已求值）
+    pub fn callFunction(self: *Evaluator, name: []const u8, args: []const Value) EvalError!Value {
+        const func = self.functions.get(name) orelse {
+            if (self.mode == .interpret) return self.fail("cannot call '{s}': not a function known to the interpreter", .{name}, null);
+            return self.fail("cannot call '{s}' in a constant expression", .{name}, "only functions declared with `const fn` in this module can be called");
+        };
+        // 🆕 extern fn 由外部库提供，解释器无法执行（prelude 中常用的由 callBuiltin 直接实现）
+        if (func.is_extern and self.mode == .interpret) {
+            return self.fail("extern function '{s}' cannot be called by the interpreter", .{name}, null);
+        }
+        // 🆕 @asm 函数的函数体是机器码，只有编译后才能运行
+        if (func.asm_bodies.len > 0) {
+            return self.fail("@asm function '{s}' cannot be evaluated", .{name}, "@asm functions only run in compiled programs");
+        }
+        if (self.mode == .const_eval) {
+            if (!func.is_const) {
+                const help = try std.fmt.allocPrint(self.allocator, "declare it as `const fn {s}(...)` if it has no side effects", .{name});
+                return self.fail("cannot call non-const function '{s}' in a constant expression", .{name}, help);
+            }
+            if (func.type_params.len > 0) {
+                return self.fail("generic const fn '{s}' cannot be evaluated at compile time yet", .{name}, null);
+            }
+        }
+        if (args.len != func.params.len) {
+            return self.fail("'{s}' expects {d} argument(s), but got {d}", .{ name, func.params.len, args.len }, null);
+        }
+        const max_depth = if (self.mode == .interpret) MAX_INTERP_DEPTH else MAX_CALL_DEPTH;
+        if (self.depth >= max_depth) {
+            if (self.mode == .interpret) return self.fail(ast.STACK_OVERFLOW_PANIC ++ "'{s}': call depth exceeded {d}", .{ name, max_depth }, null);
+            return self.fail("constant evaluation exceeded the call depth limit of {d}", .{max_depth}, "check for unbounded recursion in the const fn");
+        }
+
+        // 泛型函数不需要单态化：值自带类型
+        var callee_frame = Frame.init(self.allocator);
+        defer callee_frame.deinit();
+        for (func.params, args) |param, arg| {
+            try self.bindLocal(&callee_frame, param.name, param.type, arg);
+        }
+
+        self.depth += 1;
+        defer self.depth -= 1;
+        try self.call_stack.append(self.allocator, name);
+        defer _ = self.call_stack.pop();
+
+        for (func.body) |stmt| {
+            switch (try self.execStmt(stmt, &callee_frame)) {
+                .normal => {},
+                .ret => |value| return if (value == .int) Value{ .int = wrapInt(value.int, func.return_type) } else value,
+                .brk, .cont => return self.fail("'break' or 'continue' outside of a loop", .{}, null),
+            }
+        }
+        return Value.void;
+    }
+
+    /// 🆕 解释模式的内置函数（prelude 中的声明只是占位）
+    fn callBuiltin(self: *Evaluator, name: []const u8, args: []const Value) EvalError!?Value {
+        // 🆕 paw_exit 直接结束解释器进程（先写出缓冲的 stdout）
+        if (std.mem.eql(u8, name, "paw_exit")) {
+            const code = if (args.len > 0 and args[0] == .int) args[0].int else 0;
+            self.flushStdout();
+            std.process.exit(@truncate(@as(u64, @bitCast(code))));
+        }
+        if (std.mem.eql(u8, name, "paw_flush")) {
+            self.flushStdout();
+            return Value{ .int = 0 };
+        }
+        // 🆕 paw_log(level, msg)：与生成的 C 运行时相同的过滤规则和输出格式
+        if (std.mem.eql(u8, name, "paw_log") and args.len == 2 and args[0] == .int and args[1] == .string) {
+            const threshold = self.log_level orelse blk: {
+                const env = std.process.getEnvVarOwned(self.allocator, "PAW_LOG_LEVEL") catch null;
+                const parsed = ast.parseLogLevel(env);
+                self.log_level = parsed;
+                break :blk parsed;
+            };
+            const level = args[0].int;
+            if (level < 1 or level > threshold) return Value{ .int = 0 };
+            const index: usize = @intCast(@min(level, ast.LOG_LEVEL_NAMES.len - 1));
+            var upper: [16]u8 = undefined;
+            const line = try std.fmt.allocPrint(self.allocator, "[{s}] {s}\n", .{ std.ascii.upperString(&upper, ast.LOG_LEVEL_NAMES[index]), args[1].string });
+            std.fs.File.stderr().writeAll(line) catch return self.fail("failed to write to stderr", .{}, null);
+            return Value{ .int = 0 };
+        }
+        // 🆕 paw_write(fd, text)：println 等 prelude 函数的输出原语（fd 2 为 stderr，其余为 stdout）
+        if (std.mem.eql(u8, name, "paw_write") and args.len == 2 and args[0] == .int and args[1] == .string) {
+            try self.writeFd(args[0].int, args[1].string);
+            return Value{ .int = 0 };
+        }
+        // 🆕 paw_write_f64(fd, value, style, digits)：按指定格式输出浮点数
+        if (std.mem.eql(u8, name, "paw_write_f64") and args.len == 4 and args[0] == .int and args[1] == .float and args[2] == .int and args[3] == .int) {
+            const style = std.meta.intToEnum(ast.FloatStyle, args[2].int) catch ast.FloatStyle.shortest;
+            var buf = std.ArrayList(u8){};
+            defer buf.deinit(self.allocator);
+            try ast.formatFloat(buf.writer(self.allocator), args[1].float, style, args[3].int);
+            try self.writeFd(args[0].int, buf.items);
+            return Value{ .int = 0 };
+        }
+        // 🆕 gen_int(lo, hi) / gen_string(len)：与生成的 C 运行时相同的 PRNG
+        if (std.mem.eql(u8, name, "gen_int") and args.len == 2 and args[0] == .int and args[1] == .int) {
+            return Value{ .int = ast.randomInRange(&self.rng_state, args[0].int, args[1].int) };
+        }
+        if (std.mem.eql(u8, name, "gen_string") and args.len == 1 and args[0] == .int) {
+            const len: usize = @intCast(@max(args[0].int, 0));
+            const text = try self.allocator.alloc(u8, len);
+            for (text) |*c| c.* = ast.GEN_STRING_ALPHABET[ast.nextRandom(&self.rng_state) % ast.GEN_STRING_ALPHABET.len];
+            return Value{ .string = text };
+        }
+        return null;
+    }
+
+    /// 输出到 stdout（fd 2 以外）或 stderr
+    fn writeFd(self: *Evaluator, fd: i64, text: []const u8) EvalError!void {
+        // stdout 带缓冲：终端遇到换行写出，否则缓冲区满时写出；stderr 不缓冲
+        if (fd != 2) {
+            try self.stdout_buffer.appendSlice(self.allocator, text);
+            const line_done = self.stdout_is_tty and std.mem.indexOfScalar(u8, text, '\n') != null;
+            if (line_done or self.stdout_buffer.items.len >= ast.STDOUT_BUFFER_SIZE) self.flushStdout();
+        } else {
+            std.fs.File.stderr().writeAll(text) catch return self.fail("failed to write to stderr", .{}, null);
+        }
+    }
+
+    /// 值的文本形式（字符串插值）
+    fn appendValue(self: *Evaluator, buf: *std.ArrayList(u8), value: Value) EvalError!void {
+        switch (value) {
+            .int => |v| try buf.writer(self.allocator).print("{d}", .{v}),
+            .float => |v| try ast.formatFloat(buf.writer(self.allocator), v, .shortest, 0),
+            .bool => |v| try buf.appendSlice(self.allocator, if (v) "true" else "false"),
+            .char => |v| {
+                var utf8: [4]u8 = undefined;
+                const cp = std.math.cast(u21, v) orelse return self.fail("invalid char value {d}", .{v}, null);
+                const len = std.unicode.utf8Encode(cp, &utf8) catch return self.fail("invalid char value {d}", .{v}, null);
+                try buf.appendSlice(self.allocator, utf8[0..len]);
+            },
+            .string => |v| try buf.appendSlice(self.allocator, v),
+            .void => {},
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // 语句执行
+    // ------------------------------------------------------------------------
+
+    /// 🆕 从表达式中间开始传递控制流（见 execStmt）
+    /// 🆕 声明局部变量（参数、let、循环变量）；解释模式下记录整数类型并按位宽回绕
+    fn bindLocal(self: *Evaluator, frame: *Frame, name: []const u8, declared: ?ast.Type, value: Value) EvalError!void {
+        if (value == .int) {
+            if (declared) |t| {
+                if (isIntType(t)) {
+                    if (self.mode == .interpret) try frame.int_types.put(name, t);
+                    try frame.values.put(name, Value{ .int = wrapInt(value.int, t) });
+                    return;
+                }
+            }
+        }
+        _ = frame.int_types.remove(name);
+        try frame.values.put(name, value);
+    }
+
+    fn unwind(self: *Evaluator, flow: Flow) EvalError {
+        self.unwinding = flow;
+        return error.Unwind;
+    }
+
+    /// 执行语句；语句中的块表达式传出的控制流在这里成为语句的结果
+    fn execStmt(self: *Evaluator, stmt: ast.Stmt, frame: *Frame) EvalError!Flow {
+        return self.execStmtInner(stmt, frame) catch |err| switch (err) {
+            error.Unwind => {
+                const flow = self.unwinding.?;
+                self.unwinding = null;
+                return flow;
+            },
+            else => return err,
+        };
+    }
+
+    fn execStmtInner(self: *Evaluator, stmt: ast.Stmt, frame: *Frame) EvalError!Flow {
+        try self.step();
+        switch (stmt) {
+            .expr => |expr| {
+                _ = try self.evalExpr(expr, frame);
+                return .normal;
+            },
+            .let_decl => |let| {
+                const value = if (let.init) |init_expr| try self.evalExpr(init_expr, frame) else Value.void;
+                // 🆕 标注了整数类型的变量按位宽回绕（let b: Byte = x + 10;）
+                // 解释模式下未标注时按初始化表达式的类型，未标注类型的整数字面量是 i32
+                const declared: ?ast.Type = let.type orelse if (let.init) |init_expr|
+                    (self.intType(init_expr, frame) orelse if (self.mode == .interpret and init_expr == .int_literal) ast.Type.i32 else null)
+                else
+                    null;
+                try self.bindLocal(frame, let.name, declared, value);
+                return .normal;
+            },
+            .assign => |assign| {
+                // 🆕 `_ = expr;`：只求值，丢弃结果
+                if (assign.target.isDiscardTarget()) {
+                    _ = try self.evalExpr(assign.value, frame);
+                    return .normal;
+                }
+                if (assign.target != .identifier) {
+                    return self.fail("only local variables can be assigned {s}", .{self.where()}, null);
+                }
+                const name = assign.target.identifier;
+                const value = try self.evalExpr(assign.value, frame);
+                try self.bindLocal(frame, name, frame.int_types.get(name), value);
+                return .normal;
+            },
+            .compound_assign => |ca| {
+                if (ca.target != .identifier) {
+                    return self.fail("only local variables can be assigned {s}", .{self.where()}, null);
+                }
+                const name = ca.target.identifier;
+                const value = try self.evalBinary(ca.op.binaryOp(), ca.target, ca.value, frame);
+                try self.bindLocal(frame, name, frame.int_types.get(name), value);
+                return .normal;
+            },
+            .return_stmt => |ret| {
+                const value = if (ret) |expr| try self.evalExpr(expr, frame) else Value.void;
+                return Flow{ .ret = value };
+            },
+            .break_stmt => |brk| {
+                const value: ?Value = if (brk.value) |expr| try self.evalExpr(expr, frame) else null;
+                return Flow{ .brk = .{ .label = brk.label, .value = value } };
+            },
+            .continue_stmt => |label| return Flow{ .cont = label },
+            .loop_stmt => |loop| {
+                if (loop.iterator) |iter| return self.execRangeLoop(loop.label, iter, loop.body, frame);
+                while (true) {
+                    if (loop.condition) |cond| {
+                        const c = try self.evalExpr(cond, frame);
+                        if (c != .bool) return self.typeError("loop", c);
+                        if (!c.bool) break;
+                    }
+                    switch (try self.execBody(loop.body, frame)) {
+                        .normal => {},
+                        .ret => |value| return Flow{ .ret = value },
+                        .brk => |brk| {
+                            if (brk.label == null or std.mem.eql(u8, brk.label.?, loop.label orelse "")) break;
+                            return Flow{ .brk = brk };
+                        },
+                        .cont => |label| {
+                            if (label == null or std.mem.eql(u8, label.?, loop.label orelse "")) continue;
+                            return Flow{ .cont = label };
+                        },
+                    }
+                }
+                return .normal;
+            },
+            else => return self.fail("this statement cannot be executed {s}", .{self.where()}, null),
+        }
+    }
+
+    /// loop i in a..b { }
+    fn execRangeLoop(self: *Evaluator, label: ?[]const u8, iter: ast.LoopIterator, body: []ast.Stmt, frame: *Frame) EvalError!Flow {
+        if (iter.iterable != .range) {
+            return self.fail("only range loops can be executed {s}", .{self.where()}, null);
+        }
+        const start = try self.evalExpr(iter.iterable.range.start.*, frame);
+        const end = try self.evalExpr(iter.iterable.range.end.*, frame);
+        if (start != .int or end != .int) return self.typeError("range", if (start != .int) start else end);
+
+        const range = iter.iterable.range;
+        const binding_type: ?ast.Type = if (self.mode == .interpret) self.binaryIntType(range.start.*, range.end.*, frame) orelse ast.Type.i32 else null;
+        const last = if (range.inclusive) end.int else end.int - 1;
+        var i = start.int;
+        while (i <= last) : (i += 1) {
+            try self.bindLocal(frame, iter.binding, binding_type, Value{ .int = i });
+            switch (try self.execBody(body, frame)) {
+                .normal => {},
+                .ret => |value| return Flow{ .ret = value },
+                .brk => |brk| {
+                    if (brk.label == null or std.mem.eql(u8, brk.label.?, label orelse "")) break;
+                    return Flow{ .brk = brk };
+                },
+                .cont => |cont_label| {
+                    if (cont_label == null or std.mem.eql(u8, cont_label.?, label orelse "")) continue;
+                    return Flow{ .cont = cont_label };
+                },
+            }
+        }
+        return .normal;
+    }
+
+    fn execBody(self: *Evaluator, body: []ast.Stmt, frame: *Frame) EvalError!Flow {
+        for (body) |stmt| {
+            const flow = try self.execStmt(stmt, frame);
+            if (flow != .normal) return flow;
+        }
+        return .normal;
+    }
+
+    // ------------------------------------------------------------------------
+    // 错误报告
+    // ------------------------------------------------------------------------
+
+    /// 报错措辞："at compile time" / "by the interpreter"
+    fn where(self: *const Evaluator) []const u8 {
+        return switch (self.mode) {
+            .const_eval => "at compile time",
+            .interpret => "by the interpreter",
+        };
+    }
+
+    fn subject(self: *const Evaluator) []const u8 {
+        return switch (self.mode) {
+            .const_eval => "a constant expression",
+            .interpret => "the interpreted program",
+        };
+    }
+
+    fn typeError(self: *Evaluator, op: []const u8, value: Value) EvalError {
+        return self.fail("cannot apply '{s}' to a {s} value in {s}", .{ op, value.typeName(), self.subject() }, null);
+    }
+
+    fn overflow(self: *Evaluator, op: []const u8) EvalError {
+        return self.fail("arithmetic overflow in {s} ('{s}')", .{ self.subject(), op }, null);
+    }
+
+    /// 打印诊断（指向正在求值的 const，附带 const fn 调用链）
+    fn fail(self: *Evaluator, comptime fmt: []const u8, args: anytype, help: ?[]const u8) EvalError {
+        const message = try std.fmt.allocPrint(self.allocator, fmt, args);
+
+        var notes = std.ArrayList([]const u8){};
+        if (self.current_const) |cd| {
+            try notes.append(self.allocator, try std.fmt.allocPrint(self.allocator, "while evaluating constant '{s}'", .{cd.name}));
+        }
+        var i = self.call_stack.items.len;
+        while (i > 0) {
+            i -= 1;
+            const kind = if (self.mode == .interpret) "function" else "const fn";
+            try notes.append(self.allocator, try std.fmt.allocPrint(self.allocator, "in call to {s} '{s}'", .{ kind, self.call_stack.items[i] }));
+        }
+
+        // 解释模式没有 const 可指向：指向最内层正在执行的函数
+        const location: ?ast.SourceLocation = if (self.current_const) |cd|
+            cd.location
+        else if (self.call_stack.items.len > 0)
+            (if (self.functions.get(self.call_stack.items[self.call_stack.items.len - 1])) |func| func.location else null)
+        else
+            null;
+        const span: ?Span = if (location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        const diag = Diagnostic.init(.Error, message, span, notes.items, help).withCode(.const_eval_failed);
+        diag.print(self.allocator) catch {};
+        return error.ConstEvalFailed;
+    }
+};
+
+/// 求值程序中的编译期常量（AST 内存来自 arena，直接在 arena 中分配）
+pub fn evaluateProgram(arena: std.mem.Allocator, program: ast.Program, max_steps: u64) EvalError!void {
+    return evaluateMerged(arena, program, &.{}, max_steps);
+}
+
+/// 🆕 求值合并导入后的程序；copies 是同一批声明的其他副本（分离编译的 own / imported 列表），
+/// 副本中内联的类型（const 的数组类型、返回类型、别名）同样写回长度
+pub fn evaluateMerged(arena: std.mem.Allocator, program: ast.Program, copies: []const []ast.TopLevelDecl, max_steps: u64) EvalError!void {
+    var evaluator = Evaluator.init(arena);
+    defer evaluator.deinit();
+    evaluator.max_steps = max_steps;
+    try evaluator.evaluateProgram(program);
+    // 已求值的 const 直接取缓存的值；副本中的声明不再登记，调用仍然指向合并后的程序
+    for (copies) |declarations| try evaluator.evaluateDeclarations(declarations);
+}
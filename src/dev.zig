@@ -0,0 +1,522 @@
+//! Dev - 热重载开发服务器（pawc dev <file> [-- args]）
+//!
+//! 用 LLVM ORC JIT 运行程序，同时监视源文件（入口文件和它导入的模块）：
+//!   1. 初始模块（版本 0）中每个可替换的函数都有一个槽位 paw.slot.<name>，
+//!      函数之间的调用都经过槽位（见 llvm_native_backend.HotReload）
+//!   2. 文件变化后重新检查整个程序，比较每个顶层函数的签名和函数体指纹
+//!   3. 函数体变化的函数和新增的函数编译进新模块（版本 N），
+//!      再把已有的槽位原子地改为新函数体的地址
+//!
+//! 正在执行的调用继续运行旧代码，下一次调用进入新代码；程序状态保持不变。
+//! 只有非泛型的顶层函数可以热替换：签名、类型、常量或泛型函数的修改需要重启。
+//! 新代码有错误时打印诊断，旧代码继续运行。程序调用 exit() 会结束整个会话。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const Token = @import("token.zig").Token;
+const Lexer = @import("lexer.zig").Lexer;
+const Parser = @import("parser.zig").Parser;
+const TypeChecker = @import("typechecker.zig").TypeChecker;
+const ModuleLoader = @import("module.zig").ModuleLoader;
+const SourceMap = @import("source_map.zig").SourceMap;
+const package = @import("package.zig");
+const type_alias = @import("type_alias.zig");
+const track_caller = @import("track_caller.zig");
+const consteval = @import("consteval.zig");
+const llvm = @import("llvm_c_api.zig");
+const llvm_backend = @import("llvm_native_backend.zig");
+const LLVMNativeBackend = llvm_backend.LLVMNativeBackend;
+const HotReload = llvm_backend.HotReload;
+
+/// 检查源文件修改的间隔
+const POLL_INTERVAL_NS = 250 * std.time.ns_per_ms;
+/// 运行程序的线程的栈大小（与主线程的默认栈相当）
+const PROGRAM_STACK_SIZE = 16 * 1024 * 1024;
+
+const prelude_source = @embedFile("prelude/prelude.paw");
+
+/// 生成的 C ABI main（见 LLVMNativeBackend.generateMainWrapper）
+const EntryFn = *const fn (c_int, [*]?[*:0]const u8) callconv(.c) c_int;
+
+/// 运行程序，源文件变化时热替换函数；返回程序的退出码
+pub fn run(allocator: std.mem.Allocator, source_file: [:0]const u8, program_args: []const [:0]const u8) !u8 {
+    var session = try Session.init(allocator, source_file);
+    const entry_address = session.start() catch |err| {
+        session.deinit();
+        return err;
+    };
+    // 之后不再释放 session：JIT 代码注册了 atexit 钩子，进程退出前必须保持有效
+
+    const argv = try allocator.alloc(?[*:0]const u8, program_args.len + 2);
+    argv[0] = source_file.ptr;
+    for (program_args, 1..) |arg, i| argv[i] = arg.ptr;
+    argv[argv.len - 1] = null;
+
+    std.debug.print("🔥 pawc dev: running {s} (edit the source to hot-reload, Ctrl+C to stop)\n", .{source_file});
+
+    var program = Program{};
+    const entry: EntryFn = @ptrFromInt(entry_address);
+    const thread = try std.Thread.spawn(.{ .stack_size = PROGRAM_STACK_SIZE }, Program.run, .{
+        &program,
+        entry,
+        @as(c_int, @intCast(argv.len - 1)),
+        argv.ptr,
+    });
+
+    while (!program.done.load(.acquire)) {
+        std.Thread.sleep(POLL_INTERVAL_NS);
+        // 保存过程中文件可能暂时读不到，下一轮再检查
+        const modified = session.sources.refreshAll() catch 0;
+        if (modified == 0) continue;
+        try session.reload();
+    }
+    thread.join();
+
+    return @truncate(@as(u32, @bitCast(program.exit_code)));
+}
+
+/// 在单独的线程中运行的程序（主线程负责监视源文件）
+const Program = struct {
+    done: std.atomic.Value(bool) = std.atomic.Value(bool).init(false),
+    exit_code: c_int = 0,
+
+    fn run(self: *Program, entry: EntryFn, argc: c_int, argv: [*]?[*:0]const u8) void {
+        self.exit_code = entry(argc, argv);
+        self.done.store(true, .release);
+    }
+};
+
+// ============================================================================
+// 会话：JIT 和已加载的版本
+// ============================================================================
+
+const Session = struct {
+    allocator: std.mem.Allocator,
+    entry_file: []const u8,
+    jit: llvm.Jit,
+    /// 最近一次加载的模块版本
+    version: u32,
+    /// 已经有槽位的函数（名字归 session 所有）
+    defined_slots: std.StringHashMap(void),
+    /// 正在运行的代码对应的源码指纹
+    applied: Fingerprints,
+    /// 监视的源文件：入口文件和导入的模块
+    sources: SourceMap,
+
+    fn init(allocator: std.mem.Allocator, entry_file: []const u8) !Session {
+        return Session{
+            .allocator = allocator,
+            .entry_file = entry_file,
+            .jit = try llvm.Jit.create(),
+            .version = 0,
+            .defined_slots = std.StringHashMap(void).init(allocator),
+            .applied = Fingerprints.init(allocator),
+            .sources = SourceMap.init(allocator),
+        };
+    }
+
+    fn deinit(self: *Session) void {
+        var it = self.defined_slots.keyIterator();
+        while (it.next()) |name| self.allocator.free(name.*);
+        self.defined_slots.deinit();
+        self.applied.deinit();
+        self.sources.deinit();
+        self.jit.dispose();
+    }
+
+    /// 编译并加载初始模块，返回 main 的地址
+    fn start(self: *Session) !u64 {
+        _ = try self.sources.loadFile(self.entry_file);
+
+        const frontend = try Frontend.create(self.allocator, self.entry_file, self.entrySource());
+        defer frontend.destroy(self.allocator);
+        try self.watchModules(&frontend.loader);
+
+        const prints = try self.fingerprint();
+        self.applied.deinit();
+        self.applied = prints;
+
+        const program = frontend.program();
+        try self.emit(program, null);
+        for (program.declarations) |decl| {
+            if (decl != .function or !HotReload.isSwappable(decl.function)) continue;
+            if (self.defined_slots.contains(decl.function.name)) continue;
+            try self.defined_slots.put(try self.allocator.dupe(u8, decl.function.name), {});
+        }
+
+        return self.jit.lookup("main");
+    }
+
+    /// 源文件变化后：重新检查程序，热替换函数体变化的函数
+    /// 新代码有错误或无法热替换时只打印信息，旧代码继续运行
+    fn reload(self: *Session) !void {
+        const frontend = Frontend.create(self.allocator, self.entry_file, self.entrySource()) catch |err| switch (err) {
+            error.OutOfMemory => return err,
+            else => {
+                std.debug.print("⚠️  Build failed, still running the previous version\n", .{});
+                return;
+            },
+        };
+        defer frontend.destroy(self.allocator);
+        try self.watchModules(&frontend.loader);
+
+        var prints = try self.fingerprint();
+        defer prints.deinit();
+
+        const program = frontend.program();
+        var changed = std.StringHashMap(void).init(self.allocator);
+        defer changed.deinit();
+        if (try self.plan(&prints, program, &changed)) |restart| {
+            restart.print();
+            return;
+        }
+        if (changed.count() == 0) return;  // 只改了注释或格式
+
+        self.version += 1;
+        self.emit(program, &changed) catch |err| switch (err) {
+            error.OutOfMemory => return err,
+            else => {
+                std.debug.print("⚠️  Code generation failed, still running the previous version\n", .{});
+                return;
+            },
+        };
+        self.swap(&changed) catch |err| switch (err) {
+            error.OutOfMemory => return err,
+            else => {
+                std.debug.print("⚠️  Loading the new code failed, still running the previous version\n", .{});
+                return;
+            },
+        };
+        std.mem.swap(Fingerprints, &self.applied, &prints);
+
+        std.debug.print("🔁 Reloaded (v{d}):", .{self.version});
+        var it = changed.keyIterator();
+        while (it.next()) |name| std.debug.print(" {s}", .{name.*});
+        std.debug.print("\n", .{});
+    }
+
+    /// 与正在运行的代码比较，把需要重新生成的函数（程序中的名字）放入 changed；
+    /// 不能热替换时返回原因
+    fn plan(self: *Session, prints: *const Fingerprints, program: ast.Program, changed: *std.StringHashMap(void)) !?Restart {
+        if (prints.structure != self.applied.structure) return .structure;
+
+        var touched = std.StringHashMap(void).init(self.allocator);
+        defer touched.deinit();
+        var it = prints.functions.iterator();
+        while (it.next()) |entry| {
+            const name = entry.key_ptr.*;
+            if (self.applied.functions.get(name)) |old| {
+                if (old.signature != entry.value_ptr.signature) return Restart{ .signature = name };
+                if (old.body == entry.value_ptr.body) continue;
+            }
+            try touched.put(name, {});
+        }
+
+        for (program.declarations) |decl| {
+            if (decl != .function) continue;
+            const func = decl.function;
+            const name = sourceName(func.name);
+            if (!touched.contains(func.name) and !touched.contains(name)) continue;
+            if (!HotReload.isSwappable(func)) {
+                // 新增的泛型 / extern 函数没有问题：每个模块都会生成自己用到的实例和声明
+                if (self.applied.functions.contains(name)) return Restart{ .not_swappable = func.name };
+                continue;
+            }
+            try changed.put(func.name, {});
+        }
+        return null;
+    }
+
+    /// 生成版本 self.version 的模块（changed 为 null 时是包含全部函数的初始模块）并交给 JIT
+    fn emit(self: *Session, program: ast.Program, changed: ?*const std.StringHashMap(void)) !void {
+        // 每个模块使用自己的上下文，生成下一个版本时不会与 JIT 编译上一个版本冲突
+        var jit_context = llvm.JitContext.create();
+        defer jit_context.dispose();
+
+        const module_name = try std.fmt.allocPrint(self.allocator, "paw_dev_v{d}", .{self.version});
+        defer self.allocator.free(module_name);
+
+        var backend = try LLVMNativeBackend.initInContext(self.allocator, jit_context.context(), module_name, .O0);
+        defer backend.deinit();
+        backend.hot_reload = .{
+            .version = self.version,
+            .changed = changed,
+            .defined_slots = &self.defined_slots,
+        };
+        try backend.generateModule(program);
+        try self.jit.addModule(backend.releaseModule(), jit_context);
+    }
+
+    /// 把已有的槽位指向新的函数体；新函数的槽位由新模块定义，只需记录
+    fn swap(self: *Session, changed: *const std.StringHashMap(void)) !void {
+        const Update = struct { slot: u64, body: u64 };
+        var updates = std.ArrayList(Update){};
+        defer updates.deinit(self.allocator);
+
+        // 先查找全部地址（第一次查找时 JIT 编译整个新模块），再一起切换
+        var it = changed.keyIterator();
+        while (it.next()) |name_ptr| {
+            const name = name_ptr.*;
+            if (!self.defined_slots.contains(name)) {
+                try self.defined_slots.put(try self.allocator.dupe(u8, name), {});
+                continue;
+            }
+            const slot_name = try HotReload.slotName(self.allocator, name);
+            defer self.allocator.free(slot_name);
+            const body_name = try HotReload.bodyName(self.allocator, name, self.version);
+            defer self.allocator.free(body_name);
+            try updates.append(self.allocator, .{
+                .slot = try self.jit.lookup(slot_name),
+                .body = try self.jit.lookup(body_name),
+            });
+        }
+
+        // release 与生成代码读取槽位的 acquire 配对
+        for (updates.items) |update| {
+            const slot: *std.atomic.Value(usize) = @ptrFromInt(update.slot);
+            slot.store(@intCast(update.body), .release);
+        }
+    }
+
+    fn entrySource(self: *Session) []const u8 {
+        return self.sources.get(self.sources.findByName(self.entry_file).?).contents;
+    }
+
+    /// 把新导入的模块文件加入监视列表
+    fn watchModules(self: *Session, loader: *const ModuleLoader) !void {
+        var it = loader.modules.valueIterator();
+        while (it.next()) |module| {
+            if (self.sources.findByName(module.source_file) == null) {
+                _ = try self.sources.loadFile(module.source_file);
+            }
+        }
+    }
+
+    fn fingerprint(self: *Session) !Fingerprints {
+        var prints = Fingerprints.init(self.allocator);
+        errdefer prints.deinit();
+        var structure = std.hash.Wyhash.init(0);
+        for (self.sources.files.items) |file| {
+            try prints.addFile(self.allocator, file.name, file.contents, &structure);
+        }
+        prints.structure = structure.final();
+        return prints;
+    }
+};
+
+/// 无法热替换的修改
+const Restart = union(enum) {
+    structure,                  // 类型、常量等非函数的顶层声明
+    signature: []const u8,      // 函数签名
+    not_swappable: []const u8,  // 泛型或 extern 函数
+
+    fn print(self: Restart) void {
+        switch (self) {
+            .structure => std.debug.print("⚠️  Types or constants changed", .{}),
+            .signature => |name| std.debug.print("⚠️  The signature of `{s}` changed", .{name}),
+            .not_swappable => |name| std.debug.print("⚠️  `{s}` is generic or extern and cannot be hot-swapped", .{name}),
+        }
+        std.debug.print(": restart `pawc dev` to apply the change\n", .{});
+    }
+};
+
+/// 命名空间导入的函数（m__name）在源文件中的名字
+fn sourceName(name: []const u8) []const u8 {
+    const sep = std.mem.lastIndexOf(u8, name, "__") orelse return name;
+    return name[sep + 2 ..];
+}
+
+// ============================================================================
+// 前端：解析、合并导入、类型检查
+// ============================================================================
+
+const Frontend = struct {
+    combined_source: []u8,
+    lexer: Lexer,
+    parser: Parser,
+    loader: ModuleLoader,
+    declarations: []ast.TopLevelDecl,
+
+    /// 检查入口文件（与 pawc <file> 相同的流程）；出错时诊断已经打印
+    fn create(allocator: std.mem.Allocator, source_file: []const u8, source: []const u8) !*Frontend {
+        // 地址固定：parser 的 arena 和 lexer 的 token 在 AST 中被引用
+        const self = try allocator.create(Frontend);
+        errdefer allocator.destroy(self);
+
+        self.combined_source = try std.fmt.allocPrint(allocator, "{s}\n\n{s}", .{ prelude_source, source });
+        errdefer allocator.free(self.combined_source);
+
+        self.lexer = Lexer.init(allocator, self.combined_source, source_file);
+        self.lexer.setLineOffset(std.mem.count(u8, prelude_source, "\n") + 2);
+        errdefer self.lexer.deinit();
+        const tokens = try self.lexer.tokenize();
+
+        self.parser = Parser.init(allocator, tokens);
+        errdefer self.parser.deinit();
+        const parsed = try self.parser.parse();
+
+        self.loader = ModuleLoader.init(allocator);
+        errdefer self.loader.deinit();
+        package.registerDependencies(allocator, &self.loader, false) catch |err| {
+            std.debug.print("Error: Dependency resolution failed: {any}\n", .{err});
+            return err;
+        };
+
+        var resolved = try self.loader.resolveImports(allocator, self.parser.arena.allocator(), parsed.declarations);
+        defer resolved.deinit(allocator);
+        if (resolved.errors > 0) return error.ImportFailed;
+        self.declarations = try resolved.all.toOwnedSlice(allocator);
+        errdefer allocator.free(self.declarations);
+
+        if (resolved.imported.items.len > 0) {
+            try type_alias.expandProgram(self.parser.arena.allocator(), self.program());
+            try track_caller.lowerProgram(self.parser.arena.allocator(), self.program());
+        }
+        try consteval.evaluateProgram(self.parser.arena.allocator(), self.program(), consteval.MAX_STEPS);
+
+        var type_checker = TypeChecker.init(allocator, tokens);
+        defer type_checker.deinit();
+        try type_checker.check(self.program());
+
+        return self;
+    }
+
+    fn destroy(self: *Frontend, allocator: std.mem.Allocator) void {
+        allocator.free(self.declarations);
+        self.loader.deinit();
+        self.parser.deinit();
+        self.lexer.deinit();
+        allocator.free(self.combined_source);
+        allocator.destroy(self);
+    }
+
+    fn program(self: *const Frontend) ast.Program {
+        return ast.Program{ .declarations = self.declarations };
+    }
+};
+
+// ============================================================================
+// 源码指纹：按 token 比较，注释和空白的修改不会触发重新编译
+// ============================================================================
+
+const FnPrint = struct {
+    signature: u64,  // `fn` 之前的修饰到函数体的 `{` 之前
+    body: u64,       // 函数体（extern fn 没有函数体）
+};
+
+const Fingerprints = struct {
+    /// 顶层函数名 -> 指纹（名字归 Fingerprints 所有）
+    functions: std.StringHashMap(FnPrint),
+    /// 其余顶层声明（类型、常量等，不含 import）
+    structure: u64,
+
+    fn init(allocator: std.mem.Allocator) Fingerprints {
+        return Fingerprints{
+            .functions = std.StringHashMap(FnPrint).init(allocator),
+            .structure = 0,
+        };
+    }
+
+    fn deinit(self: *Fingerprints) void {
+        var it = self.functions.keyIterator();
+        while (it.next()) |name| self.functions.allocator.free(name.*);
+        self.functions.deinit();
+    }
+
+    fn addFile(self: *Fingerprints, allocator: std.mem.Allocator, path: []const u8, source: []const u8, structure: *std.hash.Wyhash) !void {
+        var lexer = Lexer.init(allocator, source, path);
+        defer lexer.deinit();
+        const tokens = try lexer.tokenize();
+
+        var start: usize = 0;
+        while (start < tokens.len and tokens[start].type != .eof) {
+            const end = declEnd(tokens, start);
+            const decl = tokens[start..end];
+            if (functionName(decl)) |name_index| {
+                try self.addFunction(decl, name_index);
+            } else if (!isImport(decl)) {
+                hashTokens(structure, decl);
+            }
+            start = end;
+        }
+    }
+
+    fn addFunction(self: *Fingerprints, decl: []const Token, name_index: usize) !void {
+        var body_start = decl.len;
+        var depth: usize = 0;
+        for (decl[name_index..], name_index..) |token, i| {
+            switch (token.type) {
+                .lparen, .lbracket => depth += 1,
+                .rparen, .rbracket => depth -|= 1,
+                .lbrace => if (depth == 0) {
+                    body_start = i;
+                    break;
+                },
+                else => {},
+            }
+        }
+
+        var signature = std.hash.Wyhash.init(0);
+        hashTokens(&signature, decl[0..body_start]);
+        var body = std.hash.Wyhash.init(0);
+        hashTokens(&body, decl[body_start..]);
+
+        // 不同模块中的同名函数合并为一个指纹（任何一个变化都会重新生成）
+        const current = FnPrint{ .signature = signature.final(), .body = body.final() };
+        const entry = try self.functions.getOrPut(decl[name_index].lexeme);
+        if (entry.found_existing) {
+            entry.value_ptr.signature ^= current.signature;
+            entry.value_ptr.body ^= current.body;
+        } else {
+            entry.key_ptr.* = try self.functions.allocator.dupe(u8, decl[name_index].lexeme);
+            entry.value_ptr.* = current;
+        }
+    }
+};
+
+/// 顶层声明的结束位置：深度为 0 的 `;`，或使深度回到 0 的 `}`
+fn declEnd(tokens: []const Token, start: usize) usize {
+    var depth: usize = 0;
+    var i = start;
+    while (i < tokens.len and tokens[i].type != .eof) : (i += 1) {
+        switch (tokens[i].type) {
+            .lparen, .lbrace, .lbracket => depth += 1,
+            .rparen, .rbracket => depth -|= 1,
+            .rbrace => {
+                depth -|= 1;
+                if (depth == 0) return i + 1;
+            },
+            .semicolon => if (depth == 0) return i + 1,
+            else => {},
+        }
+    }
+    return i;
+}
+
+/// 顶层函数声明中函数名 token 的下标（类型中的方法不算）
+fn functionName(decl: []const Token) ?usize {
+    var depth: usize = 0;
+    for (decl, 0..) |token, i| {
+        switch (token.type) {
+            .lparen, .lbrace, .lbracket => depth += 1,
+            .rparen, .rbrace, .rbracket => depth -|= 1,
+            .keyword_fn => if (depth == 0 and i + 1 < decl.len and decl[i + 1].type == .identifier) return i + 1,
+            else => {},
+        }
+    }
+    return null;
+}
+
+fn isImport(decl: []const Token) bool {
+    for (decl) |token| {
+        if (token.type != .keyword_pub) return token.type == .keyword_import;
+    }
+    return false;
+}
+
+fn hashTokens(hasher: *std.hash.Wyhash, tokens: []const Token) void {
+    for (tokens) |token| {
+        hasher.update(std.mem.asBytes(&token.type));
+        hasher.update(token.lexeme);
+    }
+}
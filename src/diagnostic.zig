@@ -7,7 +7,7 @@
 //! - Smart suggestions
 //!
 //! Example output:
-//!   error: cannot cast from 'string' to 'i32'
+//!   error[E0011]: cannot cast from 'string' to 'i32'
 //!      --> app.paw:5:14
 //!      |
 //!    5 |     let x = name as i32;
@@ -18,6 +18,57 @@
 
 const std = @import("std");
 const Token = @import("token.zig").Token;
+const SourceMap = @import("source_map.zig").SourceMap;  // 🆕 增量源文件表
+const messages = @import("messages.zig");  // 🆕 本地化的消息模板
+const bugreport = @import("bugreport.zig");  // 🆕 ICE 记录
+const codes = @import("codes.zig");  // 🆕 诊断代码注册表
+
+// ============================================================================
+// 🆕 FileId - Interned Source File Paths
+// ============================================================================
+
+/// Identifies a source file. Paths are interned once per process; tokens,
+/// declaration locations and spans carry only the id, and the renderer looks
+/// the path up again, so every diagnostic names the file its span belongs to.
+///
+/// The table is process-wide because diagnostics are created and printed in
+/// every phase of the compiler, including places with no context object.
+pub const FileId = enum(u32) {
+    /// Not associated with a source file (e.g. interning ran out of memory)
+    unknown = std.math.maxInt(u32),
+    _,
+
+    /// Intern `path`; the same path always yields the same id
+    pub fn intern(path: []const u8) FileId {
+        file_table.mutex.lock();
+        defer file_table.mutex.unlock();
+        
+        if (file_table.ids.get(path)) |id| return id;
+        const allocator = file_table.arena.allocator();
+        const owned = allocator.dupe(u8, path) catch return .unknown;
+        const id: FileId = @enumFromInt(file_table.paths.items.len);
+        file_table.paths.append(allocator, owned) catch return .unknown;
+        file_table.ids.put(allocator, owned, id) catch return .unknown;
+        return id;
+    }
+
+    /// The interned path of this file
+    pub fn path(self: FileId) []const u8 {
+        if (self == .unknown) return "<unknown>";
+        file_table.mutex.lock();
+        defer file_table.mutex.unlock();
+        return file_table.paths.items[@intFromEnum(self)];
+    }
+};
+
+const FileTable = struct {
+    mutex: std.Thread.Mutex = .{},
+    arena: std.heap.ArenaAllocator = std.heap.ArenaAllocator.init(std.heap.page_allocator),
+    paths: std.ArrayList([]const u8) = .{},
+    ids: std.StringHashMapUnmanaged(FileId) = .{},
+};
+
+var file_table = FileTable{};
 
 // ============================================================================
 // Span - Source Code Location
@@ -25,15 +76,15 @@ const Token = @import("token.zig").Token;
 
 /// Represents a span of source code
 pub const Span = struct {
-    filename: []const u8,
+    file: FileId,
     start_line: usize,
     start_col: usize,
     end_line: usize,
     end_col: usize,
     
-    pub fn init(filename: []const u8, start_line: usize, start_col: usize, end_line: usize, end_col: usize) Span {
+    pub fn init(file: FileId, start_line: usize, start_col: usize, end_line: usize, end_col: usize) Span {
         return Span{
-            .filename = filename,
+            .file = file,
             .start_line = start_line,
             .start_col = start_col,
             .end_line = end_line,
@@ -42,8 +93,8 @@ pub const Span = struct {
     }
     
     /// Create span from a single position
-    pub fn fromPosition(filename: []const u8, line: usize, col: usize) Span {
-        return Span.init(filename, line, col, line, col);
+    pub fn fromPosition(file: FileId, line: usize, col: usize) Span {
+        return Span.init(file, line, col, line, col);
     }
 };
 
@@ -66,6 +117,16 @@ pub const DiagnosticLevel = enum {
         };
     }
     
+    /// 🆕 显示给用户的标签（随 --locale 变化；toString 保持英文供工具使用）
+    pub fn label(self: DiagnosticLevel) []const u8 {
+        return messages.text(switch (self) {
+            .Error => .level_error,
+            .Warning => .level_warning,
+            .Note => .level_note,
+            .Help => .level_help,
+        });
+    }
+    
     pub fn color(self: DiagnosticLevel) []const u8 {
         return switch (self) {
             .Error => "\x1b[1;31m",    // Bold Red
@@ -86,6 +147,7 @@ pub const Diagnostic = struct {
     span: ?Span,
     notes: []const []const u8,
     help: ?[]const u8,
+    code: ?codes.Code = null,  // 🆕 稳定的诊断代码（见 codes.zig）
     
     pub fn init(
         level: DiagnosticLevel,
@@ -111,10 +173,18 @@ pub const Diagnostic = struct {
         notes: []const []const u8,
         help: ?[]const u8,
     ) Diagnostic {
-        const span = Span.fromPosition(token.filename, token.line, token.column);
+        const span = Span.fromPosition(token.file, token.line, token.column);
         return Diagnostic.init(level, message, span, notes, help);
     }
     
+    /// 🆕 附上诊断代码（警告只能使用 W 类代码，错误不能使用）
+    pub fn withCode(self: Diagnostic, code: codes.Code) Diagnostic {
+        std.debug.assert((code.info().category == .warning) == (self.level == .Warning));
+        var diag = self;
+        diag.code = code;
+        return diag;
+    }
+    
     /// Create simple error (no span, no notes)
     pub fn simpleError(message: []const u8) Diagnostic {
         return Diagnostic.init(.Error, message, null, &[_][]const u8{}, null);
@@ -122,38 +192,63 @@ pub const Diagnostic = struct {
     
     /// Print diagnostic to stderr with colors and source code snippet
     pub fn print(self: Diagnostic, allocator: std.mem.Allocator) !void {
+        return self.printWithSourceMap(allocator, null);
+    }
+    
+    /// Print diagnostic, taking snippets from the source map when the file is
+    /// registered there (so edited-but-unsaved contents are shown correctly)
+    pub fn printWithSourceMap(self: Diagnostic, allocator: std.mem.Allocator, source_map: ?*SourceMap) !void {
         // Print main error message with color
-        std.debug.print("{s}{s}\x1b[0m: {s}\n", .{
-            self.level.color(),
-            self.level.toString(),
-            self.message,
-        });
+        if (self.code) |code| {
+            std.debug.print("{s}{s}[{s}]\x1b[0m: {s}\n", .{
+                self.level.color(),
+                self.level.label(),
+                code.id(),
+                self.message,
+            });
+        } else {
+            std.debug.print("{s}{s}\x1b[0m: {s}\n", .{
+                self.level.color(),
+                self.level.label(),
+                self.message,
+            });
+        }
         
         // Print source location if available
         if (self.span) |span| {
             std.debug.print("   {s}--> {s}:{d}:{d}\x1b[0m\n", .{
                 "\x1b[1;36m",  // Cyan
-                span.filename,
+                span.file.path(),
                 span.start_line,
                 span.start_col,
             });
             
             // Print source code snippet
-            try printSourceSnippet(allocator, span);
+            if (source_map) |map| {
+                if (try map.lineText(span.file.path(), span.start_line)) |line| {
+                    printSnippetLine(span, line);
+                } else {
+                    try printSourceSnippet(allocator, span);
+                }
+            } else {
+                try printSourceSnippet(allocator, span);
+            }
         }
         
         // Print notes
         for (self.notes) |note| {
-            std.debug.print("   {s}= note\x1b[0m: {s}\n", .{
+            std.debug.print("   {s}= {s}\x1b[0m: {s}\n", .{
                 "\x1b[1;36m",  // Cyan
+                messages.text(.level_note),
                 note,
             });
         }
         
         // Print help
         if (self.help) |help| {
-            std.debug.print("   {s}= help\x1b[0m: {s}\n", .{
+            std.debug.print("   {s}= {s}\x1b[0m: {s}\n", .{
                 "\x1b[1;32m",  // Green
+                messages.text(.level_help),
                 help,
             });
         }
@@ -162,6 +257,182 @@ pub const Diagnostic = struct {
     }
 };
 
+// ============================================================================
+// 🆕 CodegenErrors - 后端错误收集
+// ============================================================================
+
+/// 🆕 内部编译器错误的帮助信息（report 据此保存 ICE 记录，见 bugreport.zig）
+pub const COMPILER_BUG_HELP = "this is a compiler bug; please report it together with the source of this function";
+
+/// 代码生成阶段的错误收集器
+///
+/// 某个函数生成失败时不立即中止，而是记录下来继续生成其余函数，
+/// 最后一次性报告所有失败的函数。
+pub const CodegenErrors = struct {
+    allocator: std.mem.Allocator,
+    diagnostics: std.ArrayList(Diagnostic),
+
+    pub fn init(allocator: std.mem.Allocator) CodegenErrors {
+        return CodegenErrors{
+            .allocator = allocator,
+            .diagnostics = std.ArrayList(Diagnostic){},
+        };
+    }
+
+    pub fn deinit(self: *CodegenErrors) void {
+        for (self.diagnostics.items) |diag| {
+            self.allocator.free(diag.message);
+            for (diag.notes) |note| self.allocator.free(note);
+            self.allocator.free(diag.notes);
+        }
+        self.diagnostics.deinit(self.allocator);
+    }
+
+    /// 记录函数 func_name 的代码生成失败
+    pub fn record(self: *CodegenErrors, func_name: []const u8, span: ?Span, err: anyerror) !void {
+        const message = try std.fmt.allocPrint(
+            self.allocator,
+            "failed to generate code for function '{s}': {s}",
+            .{ func_name, @errorName(err) },
+        );
+        errdefer self.allocator.free(message);
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, message, span, &[_][]const u8{}, null).withCode(.codegen_failed));
+    }
+
+    /// 🆕 记录函数 func_name 中无法按类型生成的代码（附带具体原因）
+    pub fn recordLoweringError(self: *CodegenErrors, func_name: []const u8, span: ?Span, reason: []const u8) !void {
+        const message = try std.fmt.allocPrint(
+            self.allocator,
+            "failed to generate code for function '{s}'",
+            .{func_name},
+        );
+        errdefer self.allocator.free(message);
+
+        const notes = try self.allocator.alloc([]const u8, 1);
+        errdefer self.allocator.free(notes);
+        notes[0] = try self.allocator.dupe(u8, reason);
+        errdefer self.allocator.free(notes[0]);
+
+        const help: ?[]const u8 = if (std.mem.startsWith(u8, reason, "internal error"))
+            COMPILER_BUG_HELP
+        else
+            "use the C backend (--backend=c) for this program";
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, message, span, notes, help).withCode(.codegen_failed));
+    }
+
+    /// 🆕 函数 func_name 的代码生成超过了 Paw.toml [limits] 的 function_codegen_ms
+    pub fn recordTimeLimit(self: *CodegenErrors, func_name: []const u8, span: ?Span, limit_ms: u64) !void {
+        const message = try std.fmt.allocPrint(
+            self.allocator,
+            "code generation for function '{s}' exceeded the time limit of {d} ms",
+            .{ func_name, limit_ms },
+        );
+        errdefer self.allocator.free(message);
+        try self.diagnostics.append(self.allocator, Diagnostic.init(
+            .Error,
+            message,
+            span,
+            &[_][]const u8{},
+            "if the function really needs more time, raise `function_codegen_ms` in the [limits] section of Paw.toml",
+        ).withCode(.codegen_time_limit));
+    }
+
+    /// 🆕 生成的代码中出现了没有解析为具体类型的关联类型（T::Item）：类型检查应已替换它，属于编译器 bug
+    pub fn recordUnresolvedProjection(self: *CodegenErrors, base_name: []const u8, assoc_name: []const u8) !void {
+        const message = try std.fmt.allocPrint(
+            self.allocator,
+            "internal compiler error: associated type '{s}::{s}' has no concrete type in the generated code",
+            .{ base_name, assoc_name },
+        );
+        for (self.diagnostics.items) |diag| {
+            if (std.mem.eql(u8, diag.message, message)) {
+                self.allocator.free(message);
+                return;
+            }
+        }
+        errdefer self.allocator.free(message);
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, message, null, &[_][]const u8{}, COMPILER_BUG_HELP).withCode(.codegen_failed));
+    }
+
+    /// 记录后端 verifier 拒绝了函数 func_name 生成的代码（属于编译器 bug）
+    pub fn recordVerifierError(self: *CodegenErrors, func_name: []const u8, span: ?Span, verifier_message: ?[]const u8) !void {
+        var notes = std.ArrayList([]const u8){};
+        errdefer {
+            for (notes.items) |note| self.allocator.free(note);
+            notes.deinit(self.allocator);
+        }
+        if (verifier_message) |text| {
+            var lines = std.mem.splitScalar(u8, text, '\n');
+            while (lines.next()) |line| {
+                const trimmed = std.mem.trim(u8, line, " \t\r");
+                if (trimmed.len == 0) continue;
+                try notes.append(self.allocator, try std.fmt.allocPrint(self.allocator, "verifier: {s}", .{trimmed}));
+            }
+        }
+        try self.recordInternalError(func_name, span, &notes);
+    }
+
+    /// 🆕 --verify：函数 func_name 生成的代码违反了后端的不变量（每条违反一个 note）
+    pub fn recordInvariantErrors(self: *CodegenErrors, func_name: []const u8, span: ?Span, violations: []const []const u8) !void {
+        var notes = std.ArrayList([]const u8){};
+        errdefer {
+            for (notes.items) |note| self.allocator.free(note);
+            notes.deinit(self.allocator);
+        }
+        for (violations) |violation| {
+            try notes.append(self.allocator, try std.fmt.allocPrint(self.allocator, "invariant: {s}", .{violation}));
+        }
+        try self.recordInternalError(func_name, span, &notes);
+    }
+
+    /// 记录内部编译器错误（notes 的所有权转移给诊断）
+    fn recordInternalError(self: *CodegenErrors, func_name: []const u8, span: ?Span, notes: *std.ArrayList([]const u8)) !void {
+        const message = try std.fmt.allocPrint(
+            self.allocator,
+            "internal compiler error: invalid code generated for function '{s}'",
+            .{func_name},
+        );
+        errdefer self.allocator.free(message);
+
+        const owned_notes = try notes.toOwnedSlice(self.allocator);
+        errdefer {
+            for (owned_notes) |note| self.allocator.free(note);
+            self.allocator.free(owned_notes);
+        }
+        try self.diagnostics.append(self.allocator, Diagnostic.init(
+            .Error,
+            message,
+            span,
+            owned_notes,
+            COMPILER_BUG_HELP,
+        ).withCode(.invalid_ir));
+    }
+
+    pub fn count(self: *const CodegenErrors) usize {
+        return self.diagnostics.items.len;
+    }
+
+    /// 打印所有记录的错误及汇总
+    pub fn report(self: *const CodegenErrors) void {
+        for (self.diagnostics.items) |diag| {
+            diag.print(self.allocator) catch {};
+        }
+        std.debug.print("{s}error\x1b[0m: could not generate code: {d} function(s) failed\n", .{
+            Color.BoldRed,
+            self.diagnostics.items.len,
+        });
+        
+        // 🆕 内部编译器错误：保存到 target/ice/ 供 pawc report-bug 打包
+        for (self.diagnostics.items) |diag| {
+            const help = diag.help orelse continue;
+            if (std.mem.eql(u8, help, COMPILER_BUG_HELP)) {
+                bugreport.saveIce(self.allocator, self.diagnostics.items);
+                break;
+            }
+        }
+    }
+};
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -171,7 +442,7 @@ fn printSourceSnippet(allocator: std.mem.Allocator, span: Span) !void {
     // Read source file
     const source = std.fs.cwd().readFileAlloc(
         allocator,
-        span.filename,
+        span.file.path(),
         10 * 1024 * 1024,
     ) catch {
         // If we can't read the file, just skip the snippet
@@ -186,43 +457,48 @@ fn printSourceSnippet(allocator: std.mem.Allocator, span: Span) !void {
     // Find and print the relevant line
     while (lines.next()) |line| {
         if (current_line == span.start_line) {
-            // Print line number gutter
-            std.debug.print("   {s}|\x1b[0m\n", .{"\x1b[1;36m"});  // Cyan
-            
-            // Print line number and code
-            std.debug.print(" {s}{d:>3} |\x1b[0m {s}\n", .{
-                "\x1b[1;36m",  // Cyan
-                current_line,
-                line,
-            });
-            
-            // Print error marker
-            std.debug.print("   {s}|", .{"\x1b[1;36m"});  // Cyan
-            
-            // Calculate spaces before ^
-            var i: usize = 0;
-            while (i < span.start_col) : (i += 1) {
-                std.debug.print(" ", .{});
-            }
-            
-            // Print ^ markers
-            std.debug.print("\x1b[1;31m", .{});  // Red
-            const marker_len = if (span.end_col > span.start_col) 
-                span.end_col - span.start_col + 1 
-            else 
-                1;
-            i = 0;
-            while (i < marker_len) : (i += 1) {
-                std.debug.print("^", .{});
-            }
-            
-            std.debug.print("\x1b[0m\n", .{});
+            printSnippetLine(span, line);
             break;
         }
         current_line += 1;
     }
 }
 
+/// Print a single source line with error marker
+fn printSnippetLine(span: Span, line: []const u8) void {
+    // Print line number gutter
+    std.debug.print("   {s}|\x1b[0m\n", .{"\x1b[1;36m"});  // Cyan
+    
+    // Print line number and code
+    std.debug.print(" {s}{d:>3} |\x1b[0m {s}\n", .{
+        "\x1b[1;36m",  // Cyan
+        span.start_line,
+        line,
+    });
+    
+    // Print error marker
+    std.debug.print("   {s}|", .{"\x1b[1;36m"});  // Cyan
+    
+    // Calculate spaces before ^
+    var i: usize = 0;
+    while (i < span.start_col) : (i += 1) {
+        std.debug.print(" ", .{});
+    }
+    
+    // Print ^ markers
+    std.debug.print("\x1b[1;31m", .{});  // Red
+    const marker_len = if (span.end_col > span.start_col) 
+        span.end_col - span.start_col + 1 
+    else 
+        1;
+    i = 0;
+    while (i < marker_len) : (i += 1) {
+        std.debug.print("^", .{});
+    }
+    
+    std.debug.print("\x1b[0m\n", .{});
+}
+
 // ============================================================================
 // Color Helpers
 // ============================================================================
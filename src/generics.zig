@@ -12,6 +12,8 @@
 
 const std = @import("std");
 const ast = @import("ast.zig");
+const layout = @import("layout.zig");
+const limits = @import("limits.zig");  // 🆕 实例数上限
 
 // ============================================================================
 // 🆕 类型推导辅助函数
@@ -24,6 +26,7 @@ fn inferTypeFromExpr(expr: ast.Expr) ast.Type {
         .float_literal => ast.Type.f64,
         .string_literal => ast.Type.string,
         .char_literal => ast.Type.char,
+        .byte_literal => ast.Type.u8,
         .bool_literal => ast.Type.bool,
         .identifier => ast.Type.i32,  // 简化：默认 i32
         .call => ast.Type.i32,
@@ -279,6 +282,9 @@ pub const TypeInference = struct {
 // 单态化引擎
 // ============================================================================
 
+/// 🆕 记录实例可能失败的原因
+pub const InstanceError = error{ OutOfMemory, TooManyGenericInstances };
+
 pub const Monomorphizer = struct {
     allocator: std.mem.Allocator,
     instances: std.ArrayList(GenericInstance),
@@ -290,6 +296,9 @@ pub const Monomorphizer = struct {
     /// 🆕 泛型方法实例
     method_instances: std.ArrayList(GenericMethodInstance),
     method_seen: std.StringHashMap(void),
+    /// 🆕 实例总数上限（Paw.toml [limits] generic_instances）；超出时报错而不是无限展开
+    max_instances: u64 = std.math.maxInt(u64),
+    limit_reported: bool = false,
 
     pub fn init(allocator: std.mem.Allocator) Monomorphizer {
         return Monomorphizer{
@@ -335,17 +344,17 @@ pub const Monomorphizer = struct {
         if (self.seen.contains(mangled)) {
             // 已存在，释放传入的 type_args 和 mangled
             self.allocator.free(type_args);
-            self.allocator.free(mangled);
-            // 返回已存在的 mangled name
+            // 返回已存在的 mangled name（同名的其他实例类型实参不同，必须按修饰名匹配）
+            defer self.allocator.free(mangled);
             for (self.instances.items) |instance| {
-                if (std.mem.eql(u8, instance.generic_name, generic_name) and 
-                    instance.type_args.len == type_args.len) {
+                if (std.mem.eql(u8, instance.mangled_name, mangled)) {
                     return instance.mangled_name;
                 }
             }
             return generic_name; // fallback
         }
 
+        try self.checkInstanceLimit(mangled, type_args);
         try self.seen.put(mangled, {});
         try self.instances.append(self.allocator, GenericInstance{
             .generic_name = generic_name,
@@ -381,6 +390,7 @@ pub const Monomorphizer = struct {
             return mangled; // fallback
         }
 
+        try self.checkInstanceLimit(mangled, type_args);
         try self.struct_seen.put(mangled, {});
         try self.struct_instances.append(self.allocator, GenericStructInstance{
             .generic_name = struct_name,
@@ -423,6 +433,7 @@ pub const Monomorphizer = struct {
             return mangled_name; // fallback
         }
 
+        try self.checkInstanceLimit(mangled_name, type_args);
         try self.method_seen.put(mangled_name, {});
         try self.method_instances.append(self.allocator, GenericMethodInstance{
             .struct_name = struct_name,
@@ -434,6 +445,29 @@ pub const Monomorphizer = struct {
         return mangled_name;
     }
 
+    /// 🆕 新实例超出上限时报告（只报告一次）并释放传入的名称和类型实参
+    fn checkInstanceLimit(self: *Monomorphizer, mangled: []const u8, type_args: []ast.Type) InstanceError!void {
+        const count = self.instances.items.len + self.struct_instances.items.len + self.method_instances.items.len;
+        if (count < self.max_instances) return;
+        defer {
+            self.allocator.free(mangled);
+            self.allocator.free(type_args);
+        }
+        if (!self.limit_reported) {
+            self.limit_reported = true;
+            const message = try std.fmt.allocPrint(self.allocator, "too many generic instances: more than {d} functions, structs and methods were monomorphized", .{self.max_instances});
+            defer self.allocator.free(message);
+            const note = try std.fmt.allocPrint(self.allocator, "the limit was reached while instantiating '{s}'", .{mangled});
+            defer self.allocator.free(note);
+            const notes = [_][]const u8{
+                note,
+                "a generic function that calls itself with a larger type (e.g. T -> Box<T>) never stops instantiating",
+            };
+            limits.report(self.allocator, message, null, &notes, "generic_instances");
+        }
+        return error.TooManyGenericInstances;
+    }
+
     /// 名称修饰 (Name Mangling)
     /// Vec<i32> -> Vec_i32
     /// HashMap<string, i32> -> HashMap_string_i32
@@ -543,6 +577,8 @@ pub const Monomorphizer = struct {
             .body = try new_body.toOwnedSlice(),
             .is_public = func.is_public,
             .is_async = func.is_async,
+            .location = func.location,
+            // 实例化后没有类型参数，约束已在类型检查时验证
         };
     }
 
@@ -569,6 +605,8 @@ pub const GenericContext = struct {
     monomorphizer: Monomorphizer,
     /// 函数表：用于获取泛型函数的定义
     function_table: *std.StringHashMap(ast.FunctionDecl),
+    /// 🆕 正在收集的泛型函数实例的类型参数绑定
+    bindings: layout.Bindings,
 
     pub fn init(allocator: std.mem.Allocator) GenericContext {
         return GenericContext{
@@ -576,6 +614,7 @@ pub const GenericContext = struct {
             .inference = TypeInference.init(allocator),
             .monomorphizer = Monomorphizer.init(allocator),
             .function_table = undefined, // 需要外部设置
+            .bindings = layout.Bindings.empty,
         };
     }
 
@@ -584,6 +623,53 @@ pub const GenericContext = struct {
         self.monomorphizer.deinit();
     }
 
+    /// 🆕 泛型函数调用的类型实参（每个类型参数一个）
+    ///
+    /// 优先使用类型检查器推导的结果（call.resolved_type_args），其中外层函数的类型参数
+    /// 按 bindings 替换；没有推导结果时从参数表达式推导。
+    /// 类型实参仍包含未绑定的类型参数时返回 null（外层泛型函数单态化时再处理）。
+    pub fn callTypeArgs(
+        self: *GenericContext,
+        func: ast.FunctionDecl,
+        call_args: []const ast.Expr,
+        resolved: []const ast.Type,
+        bindings: layout.Bindings,
+    ) !?[]ast.Type {
+        const type_args = try self.allocator.alloc(ast.Type, func.type_params.len);
+        errdefer self.allocator.free(type_args);
+        
+        for (func.type_params, 0..) |tp, i| {
+            const ty = if (resolved.len == func.type_params.len)
+                layout.resolve(resolved[i], bindings)
+            else for (func.params, 0..) |param, j| {
+                const name = switch (param.type) {
+                    .generic, .named => |n| n,
+                    else => continue,
+                };
+                if (std.mem.eql(u8, name, tp) and j < call_args.len) break inferTypeFromExpr(call_args[j]);
+            } else ast.Type.i32;
+            
+            if (!isConcrete(ty)) {
+                self.allocator.free(type_args);
+                return null;
+            }
+            type_args[i] = ty;
+        }
+        return type_args;
+    }
+    
+    fn isConcrete(ty: ast.Type) bool {
+        return switch (ty) {
+            .generic => false,
+            .pointer => |ptr| isConcrete(ptr.*),
+            .array => |arr| isConcrete(arr.element.*),
+            .generic_instance => |gi| for (gi.type_args) |arg| {
+                if (!isConcrete(arg)) break false;
+            } else true,
+            else => true,
+        };
+    }
+
     /// 处理泛型函数调用，返回实例化后的函数名
     pub fn processGenericCall(
         self: *GenericContext,
@@ -631,15 +717,30 @@ pub const GenericContext = struct {
     }
 
     /// 🆕 收集 Program 中所有的泛型调用
+    ///
+    /// 泛型函数体中的调用依赖类型参数，在每个实例上按其绑定收集
+    /// （实例可能产生新的实例，直到不再增加）。
     pub fn collectGenericCalls(self: *GenericContext, program: ast.Program) !void {
         for (program.declarations) |decl| {
             try self.collectDeclCalls(decl);
         }
+        
+        var i: usize = 0;
+        while (i < self.monomorphizer.instances.items.len) : (i += 1) {
+            const instance = self.monomorphizer.instances.items[i];
+            const func = self.function_table.get(instance.generic_name) orelse continue;
+            self.bindings = .{ .type_params = func.type_params, .type_args = instance.type_args };
+            for (func.body) |stmt| {
+                try self.collectStmtCalls(stmt);
+            }
+        }
+        self.bindings = layout.Bindings.empty;
     }
 
     fn collectDeclCalls(self: *GenericContext, decl: ast.TopLevelDecl) !void {
         switch (decl) {
             .function => |func| {
+                if (func.type_params.len > 0) return;
                 for (func.body) |stmt| {
                     try self.collectStmtCalls(stmt);
                 }
@@ -667,7 +768,7 @@ pub const GenericContext = struct {
         }
     }
 
-    fn collectStmtCalls(self: *GenericContext, stmt: ast.Stmt) error{OutOfMemory}!void {
+    fn collectStmtCalls(self: *GenericContext, stmt: ast.Stmt) InstanceError!void {
         switch (stmt) {
             .expr => |expr| try self.collectExprCalls(expr),
             .let_decl => |let| {
@@ -688,6 +789,11 @@ pub const GenericContext = struct {
                     try self.collectExprCalls(expr);
                 }
             },
+            .break_stmt => |brk| {
+                if (brk.value) |expr| {
+                    try self.collectExprCalls(expr);
+                }
+            },
             .loop_stmt => |loop| {
                 if (loop.condition) |cond| {
                     try self.collectExprCalls(cond);
@@ -703,7 +809,7 @@ pub const GenericContext = struct {
         }
     }
 
-    fn collectExprCalls(self: *GenericContext, expr: ast.Expr) error{OutOfMemory}!void {
+    fn collectExprCalls(self: *GenericContext, expr: ast.Expr) InstanceError!void {
         switch (expr) {
             .call => |call| {
                 // 检查 callee
@@ -719,17 +825,10 @@ pub const GenericContext = struct {
                     const func_name = call.callee.identifier;
                     if (self.function_table.get(func_name)) |func| {
                         if (func.type_params.len > 0) {
-                            // 这是泛型函数！收集参数类型
-                            var arg_types = std.ArrayList(ast.Type){};
-                            defer arg_types.deinit(self.allocator);
-                            
-                            // 🆕 从参数表达式推导类型
-                            for (call.args) |arg| {
-                                const arg_type = inferTypeFromExpr(arg);
-                                try arg_types.append(self.allocator, arg_type);
+                            // 这是泛型函数！记录类型实参对应的实例
+                            if (try self.callTypeArgs(func, call.args, call.resolved_type_args.*, self.bindings)) |type_args| {
+                                _ = try self.monomorphizer.recordInstance(func_name, type_args);
                             }
-
-                            _ = try self.inferGenericInstance(func_name, try arg_types.toOwnedSlice(self.allocator));
                         }
                     }
                 }
@@ -817,6 +916,17 @@ pub const GenericContext = struct {
             .try_expr => |inner| {
                 try self.collectExprCalls(inner.*);
             },
+            .loop_expr => |loop| {
+                for (loop.body) |stmt| {
+                    try self.collectStmtCalls(stmt);
+                }
+            },
+            // 🆕 lambda 体中的泛型调用也要实例化
+            .lambda => |lambda| {
+                for (lambda.body) |stmt| {
+                    try self.collectStmtCalls(stmt);
+                }
+            },
             else => {},
         }
     }
@@ -0,0 +1,635 @@
+//! Grammar - Paw 语法的 EBNF / 铁路图导出（pawc grammar [--ebnf | --html | --check]）
+//!
+//! 解析器是手写的递归下降，这里的规则表是语言规范中语法部分的唯一来源：
+//! 每条规则对应 parser.zig 中的一个 parseXxx 函数，改动语法时两边一起改。
+//!   --ebnf    W3C 风格的 EBNF（::=、?、*、+、|），可以直接交给铁路图生成工具
+//!   --html    每条规则一张 SVG 铁路图，非终结符链接到自己的定义
+//!   --check   只做一致性检查
+//!
+//! 导出前总是检查规则表与实现是否一致，不一致时拒绝输出：
+//!   - 引用的规则都有定义，除 Program 外的规则都被引用
+//!   - 词法分析器的每个关键字和内置类型名（TokenType 中的 keyword_* / type_*）都出现在
+//!     某条规则中，或列在 RESERVED 中
+//! zig build grammar 重新生成 docs/GRAMMAR.ebnf 和 docs/grammar.html。
+
+const std = @import("std");
+const TokenType = @import("token.zig").TokenType;
+
+pub const Rule = struct {
+    name: []const u8,
+    body: []const u8,
+    note: ?[]const u8 = null,  // 不能用语法表达的限制，作为注释输出
+};
+
+/// 由词法分析器产生的记号类（规则体中的大写名字）
+pub const TokenClass = struct {
+    name: []const u8,
+    description: []const u8,
+};
+
+pub const START_RULE = "Program";
+
+pub const TOKEN_CLASSES = [_]TokenClass{
+    .{ .name = "IDENT", .description = "identifier: a letter or _ followed by letters, digits or _" },
+    .{ .name = "INT", .description = "decimal integer literal with an optional integer type suffix, e.g. 42 or 42u32" },
+    .{ .name = "FLOAT", .description = "decimal literal with a fraction, e.g. 3.14" },
+    .{ .name = "STRING", .description = "string literal \"...\"; $name and ${expr} interpolate" },
+    .{ .name = "CHAR", .description = "character literal, e.g. 'a'" },
+    .{ .name = "BYTE", .description = "byte literal, e.g. b'A'" },
+    .{ .name = "BYTE_STRING", .description = "byte string literal, e.g. b\"GIF89a\"" },
+    .{ .name = "LABEL", .description = "loop label, e.g. 'outer" },
+};
+
+/// 保留但还没有语法的关键字
+pub const RESERVED = [_][]const u8{"async"};
+
+pub const RULES = [_]Rule{
+    // ------------------------------------------------------------------ 声明
+    .{ .name = "Program", .body = "TopLevelDecl*" },
+    .{
+        .name = "TopLevelDecl",
+        .body = "Attribute* 'pub'? ( FunctionDecl | ExternFunction | ConstDecl | TypeDecl | ImplDecl | ImportDecl )",
+        .note = "@link_name only on an extern fn; @asm only on a fn whose body is replaced by ';'",
+    },
+    .{ .name = "Attribute", .body = "'@' ( 'link_name' '(' LinkNameArg ( ',' LinkNameArg )* ','? ')' | 'allow' '(' IDENT ( ',' IDENT )* ','? ')' | 'asm' '(' STRING ',' STRING ')' | 'test' ( '(' 'should_panic' ( '=' STRING )? ')' )? | 'property' ( '(' 'runs' '=' INT ')' )? | 'skip' '(' STRING ')' | 'track_caller' )" },
+    .{ .name = "LinkNameArg", .body = "STRING | IDENT ':' STRING" },
+    .{ .name = "FunctionDecl", .body = "'const'? 'fn' FunctionSignature ( Block | ';' )" },
+    .{ .name = "ExternFunction", .body = "'extern' 'fn' FunctionSignature ';'" },
+    .{ .name = "FunctionSignature", .body = "IDENT GenericParams? '(' ParamList ')' '->' Type WhereClause?" },
+    .{ .name = "GenericParams", .body = "'<' ( GenericParam ( ',' GenericParam )* ','? )? '>'" },
+    .{ .name = "GenericParam", .body = "IDENT ( ':' BoundList )?" },
+    .{ .name = "BoundList", .body = "IDENT ( '+' IDENT )*", .note = "bounds are Num, Ord, Eq and Display; a where clause can also name a declared trait" },
+    .{ .name = "WhereClause", .body = "'where' IDENT ':' BoundList ( ',' IDENT ':' BoundList )*" },
+    .{ .name = "ParamList", .body = "( Param ( ',' Param )* ','? )?" },
+    .{ .name = "Param", .body = "'mut'? ( 'self' | IDENT ':' Type )" },
+    .{ .name = "ConstDecl", .body = "'const' IDENT ( ':' Type )? '=' Expr ';'?" },
+    .{ .name = "TypeDecl", .body = "'type' IDENT TypeParams? '=' ( StructBody | EnumBody | TraitBody | Type ';'? )" },
+    .{ .name = "TypeParams", .body = "'<' ( IDENT ( ',' IDENT )* ','? )? '>'" },
+    .{ .name = "StructBody", .body = "'struct' '{' ( 'pub'? ( 'fn' FunctionSignature Block | AssocTypeDef | IDENT ':' Type ','? ) )* '}'" },
+    .{ .name = "EnumBody", .body = "'enum' '{' ( 'pub'? ( 'fn' FunctionSignature Block | AssocTypeDef | IDENT ( '(' ( Type ( ',' Type )* ','? )? ')' )? ','? ) )* '}'" },
+    .{ .name = "TraitBody", .body = "'trait' ( ':' TraitRef ( '+' TraitRef )* )? '{' ( 'type' IDENT ';'? | 'fn' IDENT '(' ParamList ')' '->' Type ';'? )* '}'", .note = "a type implements the trait only if it also implements every supertrait and defines every associated type" },
+    .{ .name = "TraitRef", .body = "IDENT TypeArgs?" },
+    .{
+        .name = "ImplDecl",
+        .body = "'impl' TypeParams? TraitRef 'for' IDENT TypeArgs? WhereClause? '{' ( 'pub'? 'fn' FunctionSignature Block )* '}'",
+        .note = "the target is a struct or enum declared in the same file, taking the impl's type parameters in order; its methods become methods of the target",
+    },
+    .{ .name = "AssocTypeDef", .body = "'type' IDENT '=' Type ';'?", .note = "the type of an associated type declared by a trait (type Item;)" },
+    .{
+        .name = "ImportDecl",
+        .body = "'import' ( IDENT ( '.' IDENT )* ( 'as' IDENT | '.' '{' ImportItems '}' )? | STRING ( 'as' IDENT | '{' ImportItems '}' )? ) ';'?",
+        .note = "import a.b.item imports one item; a string path \"a::b\" without a list is a namespace named after its last segment",
+    },
+    .{ .name = "ImportItems", .body = "( IDENT ( ',' IDENT )* ','? )?" },
+
+    // ------------------------------------------------------------------ 类型
+    .{ .name = "Type", .body = "PrimitiveType | 'Self' ( '::' IDENT )? | '!' | '[' Type ( ';' Expr )? ']' | FunctionType | IDENT ( TypeArgs | '::' IDENT )? | '<' Type 'as' IDENT '>' '::' IDENT", .note = "Byte is u8, Bytes is [u8], UInt is u32, ULong is u64; T::Item names an associated type" },
+    .{ .name = "FunctionType", .body = "'fn' '(' ( Type ( ',' Type )* ','? )? ')' ( '->' Type )?", .note = "without '-> Type' the function returns void" },
+    .{ .name = "PrimitiveType", .body = "'i8' | 'i16' | 'i32' | 'i64' | 'i128' | 'u8' | 'u16' | 'u32' | 'u64' | 'u128' | 'f32' | 'f64' | 'bool' | 'char' | 'string' | 'void'" },
+    .{ .name = "TypeArgs", .body = "'<' ( Type ( ',' Type )* ','? )? '>'" },
+
+    // ------------------------------------------------------------------ 语句
+    .{ .name = "Block", .body = "'{' Stmt* '}'" },
+    .{ .name = "Stmt", .body = "LetStmt | ReturnStmt | BreakStmt | ContinueStmt | LoopStmt | ExprStmt" },
+    .{ .name = "LetStmt", .body = "'let' 'mut'? IDENT ( ':' Type )? ( '=' Expr )? ';'?" },
+    .{ .name = "ReturnStmt", .body = "'return' Expr? ';'?" },
+    .{ .name = "BreakStmt", .body = "'break' LABEL? Expr? ';'?" },
+    .{ .name = "ContinueStmt", .body = "'continue' LABEL? ';'?" },
+    .{ .name = "LoopStmt", .body = "( LABEL ':' )? 'loop' ( IDENT 'in' Expr | Expr )? Block" },
+    .{ .name = "ExprStmt", .body = "Expr ( ( '=' | '+=' | '-=' | '*=' | '/=' | '%=' ) Expr )? ';'?" },
+
+    // ------------------------------------------------------------------ 表达式（优先级从低到高）
+    .{ .name = "Expr", .body = "AsExpr ( 'is' '{' IsArm* '}' )?" },
+    .{ .name = "IsArm", .body = "Pattern ( 'if' Expr )? '=>' Expr ','?" },
+    .{ .name = "Pattern", .body = "SinglePattern ( '|' SinglePattern )*", .note = "alternatives of an or-pattern cannot bind variables" },
+    .{ .name = "SinglePattern", .body = "PatternLiteral ( ( '..' | '..=' ) PatternLiteral )? | '_' | IDENT ( '(' ( IDENT ( ',' IDENT )* ','? )? ')' )?" },
+    .{ .name = "PatternLiteral", .body = "'-' ( INT | FLOAT ) | Literal" },
+    .{ .name = "AsExpr", .body = "OrExpr ( 'as' Type )?" },
+    .{ .name = "OrExpr", .body = "AndExpr ( '||' AndExpr )*" },
+    .{ .name = "AndExpr", .body = "EqualityExpr ( '&&' EqualityExpr )*" },
+    .{ .name = "EqualityExpr", .body = "ComparisonExpr ( ( '==' | '!=' ) ComparisonExpr )*" },
+    .{ .name = "ComparisonExpr", .body = "RangeExpr ( ( '<' | '<=' | '>' | '>=' ) RangeExpr )*" },
+    .{ .name = "RangeExpr", .body = "AddExpr ( ( '..' | '..=' ) AddExpr )?" },
+    .{ .name = "AddExpr", .body = "MulExpr ( ( '+' | '-' ) MulExpr )*" },
+    .{ .name = "MulExpr", .body = "UnaryExpr ( ( '*' | '/' | '%' ) UnaryExpr )*" },
+    .{ .name = "UnaryExpr", .body = "( '-' | '!' ) UnaryExpr | PostfixExpr" },
+    .{ .name = "PostfixExpr", .body = "PrimaryExpr ( '(' ( Expr ( ',' Expr )* ','? )? ')' | '.' 'await' | '.' IDENT | '[' Expr ']' | '?' )*" },
+    .{
+        .name = "PrimaryExpr",
+        .body = "Literal | BYTE_STRING | 'self' | IfExpr | LoopExpr | Lambda | Block | '(' Expr ')' | ArrayLiteral | TypeQuery | EmbedFile | SourceMeta | Dispatch | PathExpr",
+    },
+    .{ .name = "Literal", .body = "INT | FLOAT | STRING | CHAR | BYTE | 'true' | 'false'" },
+    .{ .name = "IfExpr", .body = "'if' Expr Expr ( 'else' Expr )?", .note = "the branches are usually blocks: if x > 0 { 1 } else { 2 }" },
+    .{ .name = "LoopExpr", .body = "( LABEL ':' )? 'loop' Block", .note = "the value of a loop expression comes from break value" },
+    .{ .name = "Lambda", .body = "( '||' | '|' ( 'mut'? IDENT ':' Type ( ',' 'mut'? IDENT ':' Type )* ','? )? '|' ) ( '->' Type )? Block", .note = "captures the local variables it uses by value" },
+    .{ .name = "ArrayLiteral", .body = "'[' ( Expr ( ',' Expr )* ','? )? ']'" },
+    .{ .name = "TypeQuery", .body = "( 'size_of' | 'align_of' ) '<' Type '>' '(' ')'" },
+    .{ .name = "EmbedFile", .body = "'embed_file' '(' STRING ')'" },
+    .{ .name = "SourceMeta", .body = "'__FILE__' | '__LINE__' | '__FUNCTION__'", .note = "replaced by a string / i32 literal for the position where it is written" },
+    .{ .name = "Dispatch", .body = "'dispatch' '<' IDENT 'in' '[' Type ( ',' Type )* ','? ']' '>' '(' Expr ')' Block", .note = "the block is checked once per listed type; the tag is the index of the type in the list" },
+    .{
+        .name = "PathExpr",
+        .body = "IDENT ( TypeArgs ( '::' IDENT '(' ( Expr ( ',' Expr )* ','? )? ')' | StructInit )? | StructInit )?",
+        .note = "'<' starts TypeArgs only when followed by a type; StructInit without TypeArgs needs a capitalized name",
+    },
+    .{ .name = "StructInit", .body = "'{' ( IDENT ':' Expr ( ',' IDENT ':' Expr )* ','? )? '}'" },
+};
+
+// ============================================================================
+// 规则体的语法树
+// ============================================================================
+
+pub const Node = union(enum) {
+    terminal: []const u8,     // 'fn'（不含引号）
+    token_class: []const u8,  // IDENT
+    rule_ref: []const u8,     // Expr
+    sequence: []const Node,
+    choice: []const Node,
+    optional: *const Node,
+    zero_or_more: *const Node,
+    one_or_more: *const Node,
+};
+
+const BodyParser = struct {
+    arena: std.mem.Allocator,
+    text: []const u8,
+    pos: usize = 0,
+
+    fn skipSpace(self: *BodyParser) void {
+        while (self.pos < self.text.len and self.text[self.pos] == ' ') self.pos += 1;
+    }
+
+    fn peek(self: *BodyParser) ?u8 {
+        self.skipSpace();
+        return if (self.pos < self.text.len) self.text[self.pos] else null;
+    }
+
+    // choice := sequence ( '|' sequence )*
+    fn parseChoice(self: *BodyParser) error{ OutOfMemory, InvalidGrammar }!Node {
+        var options = std.ArrayList(Node){};
+        try options.append(self.arena, try self.parseSequence());
+        while (self.peek() == '|') {
+            self.pos += 1;
+            try options.append(self.arena, try self.parseSequence());
+        }
+        if (options.items.len == 1) return options.items[0];
+        return Node{ .choice = options.items };
+    }
+
+    // sequence := postfix*
+    fn parseSequence(self: *BodyParser) error{ OutOfMemory, InvalidGrammar }!Node {
+        var items = std.ArrayList(Node){};
+        while (self.peek()) |c| {
+            if (c == '|' or c == ')') break;
+            try items.append(self.arena, try self.parsePostfix());
+        }
+        if (items.items.len == 0) return error.InvalidGrammar;
+        if (items.items.len == 1) return items.items[0];
+        return Node{ .sequence = items.items };
+    }
+
+    // postfix := primary ( '?' | '*' | '+' )?
+    fn parsePostfix(self: *BodyParser) error{ OutOfMemory, InvalidGrammar }!Node {
+        const primary = try self.parsePrimary();
+        if (self.pos >= self.text.len) return primary;
+        const suffix = self.text[self.pos];
+        if (suffix != '?' and suffix != '*' and suffix != '+') return primary;
+        self.pos += 1;
+        const inner = try self.arena.create(Node);
+        inner.* = primary;
+        return switch (suffix) {
+            '?' => Node{ .optional = inner },
+            '*' => Node{ .zero_or_more = inner },
+            else => Node{ .one_or_more = inner },
+        };
+    }
+
+    // primary := 'terminal' | NAME | '(' choice ')'
+    fn parsePrimary(self: *BodyParser) error{ OutOfMemory, InvalidGrammar }!Node {
+        const c = self.peek() orelse return error.InvalidGrammar;
+        if (c == '(') {
+            self.pos += 1;
+            const inner = try self.parseChoice();
+            if (self.peek() != ')') return error.InvalidGrammar;
+            self.pos += 1;
+            return inner;
+        }
+        if (c == '\'') {
+            const end = std.mem.indexOfScalarPos(u8, self.text, self.pos + 1, '\'') orelse return error.InvalidGrammar;
+            const terminal = self.text[self.pos + 1 .. end];
+            if (terminal.len == 0) return error.InvalidGrammar;
+            self.pos = end + 1;
+            return Node{ .terminal = terminal };
+        }
+        const start = self.pos;
+        while (self.pos < self.text.len and (std.ascii.isAlphanumeric(self.text[self.pos]) or self.text[self.pos] == '_')) {
+            self.pos += 1;
+        }
+        if (self.pos == start) return error.InvalidGrammar;
+        const name = self.text[start..self.pos];
+        return if (isTokenClassName(name)) Node{ .token_class = name } else Node{ .rule_ref = name };
+    }
+};
+
+fn isTokenClassName(name: []const u8) bool {
+    for (name) |c| {
+        if (std.ascii.isLower(c)) return false;
+    }
+    return true;
+}
+
+/// 把规则体解析为语法树（节点分配在 arena 中）
+pub fn parseBody(arena: std.mem.Allocator, body: []const u8) !Node {
+    var parser = BodyParser{ .arena = arena, .text = body };
+    const node = try parser.parseChoice();
+    if (parser.peek() != null) return error.InvalidGrammar;
+    return node;
+}
+
+// ============================================================================
+// 一致性检查
+// ============================================================================
+
+/// 检查规则表；问题打印到 stderr，返回问题数
+pub fn check(arena: std.mem.Allocator) !usize {
+    var problems: usize = 0;
+    var referenced = std.StringHashMap(void).init(arena);
+    var terminals = std.StringHashMap(void).init(arena);
+    var defined = std.StringHashMap(void).init(arena);
+
+    for (RULES) |rule| {
+        if (defined.contains(rule.name)) {
+            std.debug.print("\x1b[1;31merror\x1b[0m: grammar rule {s} is defined more than once\n", .{rule.name});
+            problems += 1;
+        }
+        try defined.put(rule.name, {});
+
+        const node = parseBody(arena, rule.body) catch {
+            std.debug.print("\x1b[1;31merror\x1b[0m: grammar rule {s} has a malformed body\n", .{rule.name});
+            problems += 1;
+            continue;
+        };
+        try collect(node, &referenced, &terminals);
+    }
+
+    var it = referenced.keyIterator();
+    while (it.next()) |name| {
+        const known = if (isTokenClassName(name.*)) findTokenClass(name.*) != null else defined.contains(name.*);
+        if (!known) {
+            std.debug.print("\x1b[1;31merror\x1b[0m: grammar refers to undefined {s} '{s}'\n", .{ if (isTokenClassName(name.*)) "token class" else "rule", name.* });
+            problems += 1;
+        }
+    }
+    for (RULES) |rule| {
+        if (!std.mem.eql(u8, rule.name, START_RULE) and !referenced.contains(rule.name)) {
+            std.debug.print("\x1b[1;31merror\x1b[0m: grammar rule {s} is not used by any other rule\n", .{rule.name});
+            problems += 1;
+        }
+    }
+
+    // 词法分析器的关键字和内置类型名必须出现在语法中
+    inline for (@typeInfo(TokenType).@"enum".fields) |field| {
+        const word: ?[]const u8 = comptime if (std.mem.startsWith(u8, field.name, "keyword_"))
+            field.name["keyword_".len..]
+        else if (std.mem.startsWith(u8, field.name, "type_"))
+            field.name["type_".len..]
+        else
+            null;
+        if (word) |w| {
+            if (!terminals.contains(w) and !isReserved(w)) {
+                std.debug.print("\x1b[1;31merror\x1b[0m: keyword '{s}' (TokenType.{s}) does not appear in the grammar\n", .{ w, field.name });
+                problems += 1;
+            }
+        }
+    }
+    return problems;
+}
+
+fn collect(node: Node, referenced: *std.StringHashMap(void), terminals: *std.StringHashMap(void)) !void {
+    switch (node) {
+        .terminal => |t| try terminals.put(t, {}),
+        .token_class, .rule_ref => |name| try referenced.put(name, {}),
+        .sequence, .choice => |items| for (items) |item| try collect(item, referenced, terminals),
+        .optional, .zero_or_more, .one_or_more => |inner| try collect(inner.*, referenced, terminals),
+    }
+}
+
+fn findTokenClass(name: []const u8) ?TokenClass {
+    for (TOKEN_CLASSES) |class| {
+        if (std.mem.eql(u8, class.name, name)) return class;
+    }
+    return null;
+}
+
+fn isReserved(word: []const u8) bool {
+    for (RESERVED) |r| {
+        if (std.mem.eql(u8, r, word)) return true;
+    }
+    return false;
+}
+
+// ============================================================================
+// EBNF
+// ============================================================================
+
+pub fn writeEbnf(allocator: std.mem.Allocator, out: *std.ArrayList(u8)) !void {
+    const w = out.writer(allocator);
+    try w.writeAll("/* Paw grammar (W3C EBNF). Generated by `pawc grammar --ebnf` from src/grammar.zig; do not edit. */\n\n");
+    try w.writeAll("/* Tokens produced by the lexer:\n");
+    for (TOKEN_CLASSES) |class| {
+        try w.print("     {s:<12} {s}\n", .{ class.name, class.description });
+    }
+    try w.writeAll("   Reserved keywords without syntax yet:");
+    for (RESERVED) |word| try w.print(" {s}", .{word});
+    try w.writeAll("\n   Comments: // to end of line, and block comments. */\n\n");
+
+    const width = longestRuleName();
+    for (RULES) |rule| {
+        if (rule.note) |note| try w.print("/* {s} */\n", .{note});
+        try w.print("{s}", .{rule.name});
+        try out.appendNTimes(allocator, ' ', width - rule.name.len);
+        try w.print(" ::= {s}\n", .{rule.body});
+    }
+}
+
+fn longestRuleName() usize {
+    var width: usize = 0;
+    for (RULES) |rule| width = @max(width, rule.name.len);
+    return width;
+}
+
+// ============================================================================
+// 铁路图（HTML + 内联 SVG）
+// ============================================================================
+
+const CHAR_WIDTH = 8;   // 13px 等宽字体的近似字宽
+const BOX_HALF = 11;    // 方框高度的一半
+const GAP = 10;         // 序列中相邻节点的间距
+const ARC = 10;         // 分支曲线的半径
+
+/// 节点的尺寸：从左端基线到右端基线的宽度，基线以上 / 以下的高度
+const Size = struct {
+    width: usize,
+    up: usize,
+    down: usize,
+};
+
+fn measure(node: Node) Size {
+    return switch (node) {
+        .terminal, .token_class, .rule_ref => |text| .{ .width = text.len * CHAR_WIDTH + 20, .up = BOX_HALF, .down = BOX_HALF },
+        .sequence => |items| blk: {
+            var size = Size{ .width = 0, .up = 0, .down = 0 };
+            for (items, 0..) |item, i| {
+                const s = measure(item);
+                size.width += s.width + (if (i > 0) @as(usize, GAP) else 0);
+                size.up = @max(size.up, s.up);
+                size.down = @max(size.down, s.down);
+            }
+            break :blk size;
+        },
+        .choice => |items| blk: {
+            var size = measure(items[0]);
+            var inner_width = size.width;
+            for (items[1..]) |item| {
+                const s = measure(item);
+                inner_width = @max(inner_width, s.width);
+                size.down = branchOffset(size.down, s) + s.down;
+            }
+            size.width = inner_width + 4 * ARC;
+            break :blk size;
+        },
+        // 可选：主线直接通过，内容在下方的分支上
+        .optional => |inner| blk: {
+            const s = measure(inner.*);
+            break :blk .{ .width = s.width + 4 * ARC, .up = 0, .down = branchOffset(0, s) + s.down };
+        },
+        // 重复：内容在主线上，回路在下方
+        .one_or_more => |inner| blk: {
+            const s = measure(inner.*);
+            break :blk .{ .width = s.width + 2 * ARC, .up = s.up, .down = s.down + 2 * ARC };
+        },
+        .zero_or_more => |inner| blk: {
+            const s = measure(inner.*);
+            const loop = Size{ .width = s.width + 2 * ARC, .up = s.up, .down = s.down + 2 * ARC };
+            break :blk .{ .width = loop.width + 4 * ARC, .up = 0, .down = branchOffset(0, loop) + loop.down };
+        },
+    };
+}
+
+/// 分支（下一个选项）相对于主线的纵向偏移：上一个分支的底部 + 间距 + 本分支基线以上的高度
+fn branchOffset(previous_down: usize, branch: Size) usize {
+    return @max(previous_down + GAP + branch.up, 2 * ARC);
+}
+
+const SvgWriter = struct {
+    allocator: std.mem.Allocator,
+    out: *std.ArrayList(u8),
+
+    fn print(self: SvgWriter, comptime fmt: []const u8, args: anytype) !void {
+        try self.out.writer(self.allocator).print(fmt, args);
+    }
+
+    fn line(self: SvgWriter, x1: usize, y: usize, x2: usize) !void {
+        if (x2 <= x1) return;
+        try self.print("<path d=\"M{d} {d}h{d}\"/>", .{ x1, y, x2 - x1 });
+    }
+
+    /// 从主线 (x, y) 向下拐到 y + dy 处的分支，终点 (x + 2*ARC, y + dy)
+    fn down(self: SvgWriter, x: usize, y: usize, dy: usize) !void {
+        try self.print("<path d=\"M{d} {d}q{d} 0 {d} {d}v{d}q0 {d} {d} {d}\"/>", .{ x, y, ARC, ARC, ARC, dy - 2 * ARC, ARC, ARC, ARC });
+    }
+
+    /// 从分支 (x, y + dy) 向上拐回主线，终点 (x + 2*ARC, y)
+    fn up(self: SvgWriter, x: usize, y: usize, dy: usize) !void {
+        try self.print("<path d=\"M{d} {d}q{d} 0 {d} -{d}v-{d}q0 -{d} {d} -{d}\"/>", .{ x, y + dy, ARC, ARC, ARC, dy - 2 * ARC, ARC, ARC, ARC });
+    }
+
+    fn text(self: SvgWriter, value: []const u8) !void {
+        for (value) |c| switch (c) {
+            '<' => try self.out.appendSlice(self.allocator, "&lt;"),
+            '>' => try self.out.appendSlice(self.allocator, "&gt;"),
+            '&' => try self.out.appendSlice(self.allocator, "&amp;"),
+            '"' => try self.out.appendSlice(self.allocator, "&quot;"),
+            else => try self.out.append(self.allocator, c),
+        };
+    }
+
+    fn box(self: SvgWriter, x: usize, y: usize, label: []const u8, class: []const u8, link: bool) !void {
+        const width = label.len * CHAR_WIDTH + 20;
+        if (link) {
+            try self.print("<a href=\"#", .{});
+            try self.text(label);
+            try self.print("\">", .{});
+        }
+        try self.print("<rect class=\"{s}\" x=\"{d}\" y=\"{d}\" width=\"{d}\" height=\"{d}\"/>", .{ class, x, y - BOX_HALF, width, 2 * BOX_HALF });
+        try self.print("<text x=\"{d}\" y=\"{d}\">", .{ x + width / 2, y + 4 });
+        try self.text(label);
+        try self.print("</text>", .{});
+        if (link) try self.print("</a>", .{});
+    }
+
+    /// 把节点画在 (x, y)：从左端基线到右端基线
+    fn node(self: SvgWriter, n: Node, x: usize, y: usize) error{OutOfMemory}!void {
+        const size = measure(n);
+        switch (n) {
+            .terminal => |t| try self.box(x, y, t, "terminal", false),
+            .token_class => |name| try self.box(x, y, name, "token", false),
+            .rule_ref => |name| try self.box(x, y, name, "rule", true),
+            .sequence => |items| {
+                var cursor = x;
+                for (items, 0..) |item, i| {
+                    if (i > 0) {
+                        try self.line(cursor, y, cursor + GAP);
+                        cursor += GAP;
+                    }
+                    try self.node(item, cursor, y);
+                    cursor += measure(item).width;
+                }
+            },
+            .choice => |items| {
+                const inner_right = x + size.width - 2 * ARC;
+                const first = measure(items[0]);
+                try self.line(x, y, x + 2 * ARC);
+                try self.node(items[0], x + 2 * ARC, y);
+                try self.line(x + 2 * ARC + first.width, y, x + size.width);
+                var bottom = first.down;
+                for (items[1..]) |item| {
+                    const s = measure(item);
+                    const dy = branchOffset(bottom, s);
+                    try self.down(x, y, dy);
+                    try self.node(item, x + 2 * ARC, y + dy);
+                    try self.line(x + 2 * ARC + s.width, y + dy, inner_right);
+                    try self.up(inner_right, y, dy);
+                    bottom = dy + s.down;
+                }
+            },
+            .optional => |inner| try self.bypass(inner.*, x, y, size.width),
+            .zero_or_more => |inner| {
+                const loop = Node{ .one_or_more = inner };
+                try self.bypass(loop, x, y, size.width);
+            },
+            .one_or_more => |inner| {
+                const s = measure(inner.*);
+                try self.line(x, y, x + ARC);
+                try self.node(inner.*, x + ARC, y);
+                try self.line(x + ARC + s.width, y, x + size.width);
+                // 回路：从内容右端向下，在下方向左，再回到内容左端
+                const depth = s.down + ARC;
+                const right = x + ARC + s.width;
+                try self.print("<path d=\"M{d} {d}q{d} 0 {d} {d}v{d}q0 {d} -{d} {d}h-{d}q-{d} 0 -{d} -{d}v-{d}q0 -{d} {d} -{d}\"/>", .{
+                    right, y, ARC, ARC, ARC, depth - 2 * ARC, ARC, ARC, ARC, s.width, ARC, ARC, ARC, depth - 2 * ARC, ARC, ARC, ARC,
+                });
+            },
+        }
+    }
+
+    /// 主线直接通过，内容画在下方的分支上（optional / zero_or_more）
+    fn bypass(self: SvgWriter, inner: Node, x: usize, y: usize, width: usize) !void {
+        const s = measure(inner);
+        const dy = branchOffset(0, s);
+        try self.line(x, y, x + width);
+        try self.down(x, y, dy);
+        try self.node(inner, x + 2 * ARC, y + dy);
+        try self.line(x + 2 * ARC + s.width, y + dy, x + width - 2 * ARC);
+        try self.up(x + width - 2 * ARC, y, dy);
+    }
+};
+
+pub fn writeHtml(allocator: std.mem.Allocator, out: *std.ArrayList(u8)) !void {
+    var arena_state = std.heap.ArenaAllocator.init(allocator);
+    defer arena_state.deinit();
+    const arena = arena_state.allocator();
+
+    const svg = SvgWriter{ .allocator = allocator, .out = out };
+    try svg.print(
+        \\<!DOCTYPE html>
+        \\<html><head><meta charset="utf-8"><title>Paw grammar</title>
+        \\<!-- Generated by `pawc grammar --html` from src/grammar.zig; do not edit. -->
+        \\<style>
+        \\body {{ font-family: sans-serif; margin: 2em; }}
+        \\h2 {{ font-size: 1.1em; margin: 1.5em 0 0.3em; }}
+        \\p.note {{ color: #555; margin: 0.2em 0; }}
+        \\code.ebnf {{ display: block; color: #333; margin-bottom: 0.5em; }}
+        \\svg path {{ fill: none; stroke: #333; stroke-width: 1.5; }}
+        \\svg rect {{ stroke: #333; stroke-width: 1.5; }}
+        \\svg rect.terminal {{ fill: #fdf2c4; rx: 10; }}
+        \\svg rect.token {{ fill: #e3f0da; rx: 10; }}
+        \\svg rect.rule {{ fill: #dde8f6; }}
+        \\svg text {{ font: 13px monospace; text-anchor: middle; }}
+        \\</style></head><body>
+        \\<h1>Paw grammar</h1>
+        \\
+    , .{});
+
+    for (RULES) |rule| {
+        const node = try parseBody(arena, rule.body);
+        const size = measure(node);
+        const width = size.width + 40;
+        const height = size.up + size.down + 20;
+        const y = size.up + 10;
+
+        try svg.print("<h2 id=\"{s}\">{s}</h2>\n<code class=\"ebnf\">", .{ rule.name, rule.name });
+        try svg.text(rule.body);
+        try svg.print("</code>\n", .{});
+        if (rule.note) |note| {
+            try svg.print("<p class=\"note\">", .{});
+            try svg.text(note);
+            try svg.print("</p>\n", .{});
+        }
+        try svg.print("<svg width=\"{d}\" height=\"{d}\" viewBox=\"0 0 {d} {d}\">", .{ width, height, width, height });
+        // 起点和终点的竖线
+        try svg.print("<path d=\"M10 {d}v{d}M10 {d}h10\"/>", .{ y - 8, 16, y });
+        try svg.node(node, 20, y);
+        try svg.print("<path d=\"M{d} {d}h10M{d} {d}v{d}\"/>", .{ 20 + size.width, y, 30 + size.width, y - 8, 16 });
+        try svg.print("</svg>\n", .{});
+    }
+
+    try svg.print("<h2>Tokens</h2>\n<ul>\n", .{});
+    for (TOKEN_CLASSES) |class| {
+        try svg.print("<li><code>{s}</code> ", .{class.name});
+        try svg.text(class.description);
+        try svg.print("</li>\n", .{});
+    }
+    try svg.print("</ul>\n</body></html>\n", .{});
+}
+
+// ============================================================================
+// pawc grammar
+// ============================================================================
+
+pub const Format = enum { ebnf, html, check_only };
+
+/// 检查规则表，按格式输出到 stdout 或 output_path；返回是否一致
+pub fn run(allocator: std.mem.Allocator, format: Format, output_path: ?[]const u8) !bool {
+    var arena_state = std.heap.ArenaAllocator.init(allocator);
+    defer arena_state.deinit();
+
+    const problems = try check(arena_state.allocator());
+    if (problems > 0) {
+        std.debug.print("\x1b[1;31merror\x1b[0m: the grammar in src/grammar.zig is out of sync ({d} problem(s)); nothing was written\n", .{problems});
+        return false;
+    }
+    if (format == .check_only) {
+        std.debug.print("✅ Grammar is consistent: {d} rules, every keyword covered\n", .{RULES.len});
+        return true;
+    }
+
+    var out = std.ArrayList(u8){};
+    defer out.deinit(allocator);
+    switch (format) {
+        .ebnf => try writeEbnf(allocator, &out),
+        .html => try writeHtml(allocator, &out),
+        .check_only => unreachable,
+    }
+
+    if (output_path) |path| {
+        std.fs.cwd().writeFile(.{ .sub_path = path, .data = out.items }) catch |err| {
+            std.debug.print("Error: Cannot write {s}: {any}\n", .{ path, err });
+            return false;
+        };
+        std.debug.print("📝 Grammar written to {s}\n", .{path});
+    } else {
+        try std.fs.File.stdout().writeAll(out.items);
+    }
+    return true;
+}
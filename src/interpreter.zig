@@ -0,0 +1,39 @@
+//! Interpreter - 直接解释执行类型检查后的 AST（pawc <file> --interpret）
+//!
+//! 用途：
+//!   - 作为差分测试的参照：同一程序解释执行的输出应与原生后端一致
+//!   - 在没有 C 编译器 / 链接器的平台上运行程序
+//!
+//! 复用 consteval.Evaluator 的解释模式。目前支持整数、浮点、布尔、字符、
+//! 字符串、控制流、普通函数和泛型函数调用；结构体、枚举、数组和方法调用
+//! 会报出 "not supported by the interpreter" 错误。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const consteval = @import("consteval.zig");
+
+pub const RunError = consteval.EvalError || error{NoMainFunction};
+
+/// 执行程序的 main 函数，返回进程退出码
+pub fn run(allocator: std.mem.Allocator, program: ast.Program) RunError!u8 {
+    // 解释过程中的字符串等临时值统一放在 arena 中
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+
+    var evaluator = consteval.Evaluator.init(arena.allocator());
+    defer evaluator.deinit();
+    defer evaluator.flushStdout();  // 🆕 与原生程序的 paw_rt_shutdown 一致：结束时写出缓冲的输出
+    evaluator.mode = .interpret;
+    try evaluator.registerDeclarations(program);
+
+    if (!evaluator.functions.contains("main")) {
+        std.debug.print("error: no 'main' function to interpret\n", .{});
+        return error.NoMainFunction;
+    }
+
+    const result = try evaluator.callFunction("main", &[_]consteval.Value{});
+    return switch (result) {
+        .int => |code| @truncate(@as(u64, @bitCast(code))),
+        else => 0,
+    };
+}
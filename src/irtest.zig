@@ -0,0 +1,374 @@
+//! IrTest - 按函数检查后端输出的 IR（pawc irtest <dir>）
+//!
+//! pawc irtest <dir> [--backend=<llvm|c>] [--update]
+//!
+//! 对目录下（递归）每个 .paw 文件：
+//!   1. pawc <file> --backend=<backend> -o <tmp>   生成 <tmp>.ll（LLVM IR）或 <tmp>.c
+//!   2. 按源文件注释中的指令逐个函数检查生成的代码（类似 LLVM FileCheck）：
+//!
+//!      // CHECK-LABEL: <fn>    之后的检查只看函数 <fn> 的定义（main 为 paw_main）
+//!      // CHECK: <text>        之后某一行包含 <text>
+//!      // CHECK-NEXT: <text>   紧接着的下一行包含 <text>
+//!      // CHECK-NOT: <text>    到下一个匹配（或函数结束）之前没有包含 <text> 的行
+//!      // SNAPSHOT: <fn>       函数 <fn> 的完整代码与 <file>.<fn>.ll（或 .c）逐字节相同
+//!
+//! 用于审查降级（bool、match 链、类型转换……）的变化：改动以 IR 差异的形式出现在
+//! 快照文件中，而不只体现在端到端的运行结果上。--update 重写快照文件。
+//! 没有任何指令的文件被跳过；当前构建不支持所选后端时所有文件被跳过。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const selftest = @import("selftest.zig");
+
+/// 生成代码的临时目录（结束后删除）
+const WORK_DIR = ".paw-irtest";
+/// 生成代码的大小上限
+const MAX_IR_BYTES = 16 * 1024 * 1024;
+
+pub const Backend = enum {
+    llvm,
+    c,
+
+    pub fn fromString(name: []const u8) ?Backend {
+        return std.meta.stringToEnum(Backend, name);
+    }
+
+    /// 生成文件（和快照文件）的扩展名
+    fn extension(self: Backend) []const u8 {
+        return switch (self) {
+            .llvm => ".ll",
+            .c => ".c",
+        };
+    }
+};
+
+pub const Options = struct {
+    backend: Backend = .llvm,
+    update: bool = false,  // 重写快照文件
+};
+
+const DirectiveKind = enum {
+    label,
+    check,
+    next,
+    not,
+    snapshot,
+
+    const prefixes = [_]struct { text: []const u8, kind: DirectiveKind }{
+        .{ .text = "CHECK-LABEL:", .kind = .label },
+        .{ .text = "CHECK-NEXT:", .kind = .next },
+        .{ .text = "CHECK-NOT:", .kind = .not },
+        .{ .text = "CHECK:", .kind = .check },
+        .{ .text = "SNAPSHOT:", .kind = .snapshot },
+    };
+
+    fn prefix(self: DirectiveKind) []const u8 {
+        for (prefixes) |entry| {
+            if (entry.kind == self) return entry.text;
+        }
+        unreachable;
+    }
+};
+
+const Directive = struct {
+    kind: DirectiveKind,
+    text: []const u8,  // 指向源文件内容
+    line: usize,       // 在源文件中的行号
+};
+
+/// 运行目录中的所有测试，全部通过（或跳过）时返回 true
+pub fn run(allocator: std.mem.Allocator, dir_path: []const u8, options: Options) !bool {
+    const self_exe = try std.fs.selfExePathAlloc(allocator);
+    defer allocator.free(self_exe);
+
+    var files = std.ArrayList([]const u8){};
+    defer {
+        for (files.items) |file| allocator.free(file);
+        files.deinit(allocator);
+    }
+    try selftest.collectPrograms(allocator, dir_path, &files);
+    std.mem.sort([]const u8, files.items, {}, selftest.lessThan);
+
+    if (files.items.len == 0) {
+        std.debug.print("No .paw files found in {s}\n", .{dir_path});
+        return true;
+    }
+
+    try std.fs.cwd().makePath(WORK_DIR);
+    defer std.fs.cwd().deleteTree(WORK_DIR) catch {};
+
+    var passed: usize = 0;
+    var failed: usize = 0;
+    var skipped: usize = 0;
+    for (files.items) |file| {
+        switch (try checkFile(allocator, self_exe, file, options)) {
+            .pass => passed += 1,
+            .fail => failed += 1,
+            .skip => skipped += 1,
+        }
+    }
+
+    std.debug.print("\nirtest: {d} passed, {d} failed, {d} skipped\n", .{ passed, failed, skipped });
+    return failed == 0;
+}
+
+/// 生成单个文件的代码并检查其中的指令
+fn checkFile(allocator: std.mem.Allocator, self_exe: []const u8, file: []const u8, options: Options) !selftest.Outcome {
+    const source = try std.fs.cwd().readFileAlloc(allocator, file, 1024 * 1024);
+    defer allocator.free(source);
+
+    var directives = std.ArrayList(Directive){};
+    defer directives.deinit(allocator);
+    try parseDirectives(allocator, source, &directives);
+    if (directives.items.len == 0) {
+        std.debug.print("SKIP  {s} (no CHECK or SNAPSHOT directives)\n", .{file});
+        return .skip;
+    }
+
+    // 1. 生成代码
+    const base = try std.fmt.allocPrint(allocator, "{s}/{s}", .{ WORK_DIR, std.fs.path.stem(file) });
+    defer allocator.free(base);
+    const output_path = try std.fmt.allocPrint(allocator, "{s}{s}", .{ base, options.backend.extension() });
+    defer allocator.free(output_path);
+    const backend_flag = try std.fmt.allocPrint(allocator, "--backend={s}", .{@tagName(options.backend)});
+    defer allocator.free(backend_flag);
+
+    const generate = try selftest.observe(allocator, &.{ self_exe, file, backend_flag, "-o", base });
+    defer generate.deinit(allocator);
+    const ir = std.fs.cwd().readFileAlloc(allocator, output_path, MAX_IR_BYTES) catch {
+        if (std.mem.indexOf(u8, generate.stderr, "not available in this build") != null) {
+            std.debug.print("SKIP  {s} ({s} backend not available in this build)\n", .{ file, @tagName(options.backend) });
+            return .skip;
+        }
+        std.debug.print("FAIL  {s} (does not compile)\n{s}\n", .{ file, generate.stderr });
+        return .fail;
+    };
+    defer allocator.free(ir);
+    defer std.fs.cwd().deleteFile(output_path) catch {};
+
+    var lines = std.ArrayList([]const u8){};
+    defer lines.deinit(allocator);
+    var it = std.mem.splitScalar(u8, ir, '\n');
+    while (it.next()) |line| try lines.append(allocator, line);
+
+    // 2. 逐条检查指令
+    var checker = Checker{ .backend = options.backend, .lines = lines.items };
+    for (directives.items) |directive| {
+        const ok = switch (directive.kind) {
+            .snapshot => try checkSnapshot(allocator, file, directive, options, lines.items),
+            else => checker.apply(file, directive),
+        };
+        if (!ok) return .fail;
+    }
+    if (!checker.finish(file)) return .fail;
+
+    std.debug.print("{s}  {s}\n", .{ if (options.update) "UPDATE" else "PASS", file });
+    return .pass;
+}
+
+fn parseDirectives(allocator: std.mem.Allocator, source: []const u8, directives: *std.ArrayList(Directive)) !void {
+    var it = std.mem.splitScalar(u8, source, '\n');
+    var line_number: usize = 0;
+    while (it.next()) |raw| {
+        line_number += 1;
+        const line = std.mem.trim(u8, raw, " \t\r");
+        if (!std.mem.startsWith(u8, line, "//")) continue;
+        const comment = std.mem.trimLeft(u8, line[2..], " \t");
+        for (DirectiveKind.prefixes) |prefix| {
+            if (!std.mem.startsWith(u8, comment, prefix.text)) continue;
+            try directives.append(allocator, .{
+                .kind = prefix.kind,
+                .text = std.mem.trim(u8, comment[prefix.text.len..], " \t"),
+                .line = line_number,
+            });
+            break;
+        }
+    }
+}
+
+/// 函数定义在生成代码中的行范围 [start, end]（end 为结束的 "}"）
+const Range = struct { start: usize, end: usize };
+
+/// 查找函数 name 的定义
+///   LLVM: define ... @name(...) ... {
+///   C:    <返回类型> name(...) {
+fn findFunction(backend: Backend, lines: []const []const u8, name: []const u8) ?Range {
+    const symbol = if (std.mem.eql(u8, name, "main")) ast.ENTRY_SYMBOL else name;
+    for (lines, 0..) |line, i| {
+        if (!std.mem.endsWith(u8, line, "{")) continue;
+        const is_definition = switch (backend) {
+            .llvm => std.mem.startsWith(u8, line, "define ") and containsCall(line, "@", symbol),
+            .c => line.len > 0 and line[0] != ' ' and line[0] != '\t' and containsCall(line, " ", symbol),
+        };
+        if (!is_definition) continue;
+        for (lines[i + 1 ..], i + 1..) |body_line, j| {
+            if (std.mem.eql(u8, body_line, "}")) return .{ .start = i, .end = j };
+        }
+        return null;
+    }
+    return null;
+}
+
+/// line 是否包含 "<before><symbol>("
+fn containsCall(line: []const u8, before: []const u8, symbol: []const u8) bool {
+    var start: usize = 0;
+    while (std.mem.indexOfPos(u8, line, start, symbol)) |pos| : (start = pos + 1) {
+        const end = pos + symbol.len;
+        if (pos >= before.len and std.mem.eql(u8, line[pos - before.len .. pos], before) and
+            end < line.len and line[end] == '(') return true;
+    }
+    return false;
+}
+
+/// CHECK 指令的匹配状态
+const Checker = struct {
+    backend: Backend,
+    lines: []const []const u8,
+    function: ?[]const u8 = null,  // 当前 CHECK-LABEL 的函数
+    range: Range = .{ .start = 0, .end = 0 },
+    cursor: usize = 0,              // 下一次匹配开始的行
+    // 等待下一个匹配确定检查范围的 CHECK-NOT
+    pending_nots: [8]Directive = undefined,
+    pending_count: usize = 0,
+
+    fn apply(self: *Checker, file: []const u8, directive: Directive) bool {
+        if (directive.kind == .label) {
+            if (!self.finish(file)) return false;
+            self.range = findFunction(self.backend, self.lines, directive.text) orelse {
+                self.fail(file, directive, "function not found in the generated code");
+                return false;
+            };
+            self.function = directive.text;
+            self.cursor = self.range.start;
+            return true;
+        }
+        if (self.function == null) {
+            self.fail(file, directive, "directive before the first CHECK-LABEL");
+            return false;
+        }
+        switch (directive.kind) {
+            .not => {
+                if (self.pending_count == self.pending_nots.len) {
+                    self.fail(file, directive, "too many consecutive CHECK-NOT directives");
+                    return false;
+                }
+                self.pending_nots[self.pending_count] = directive;
+                self.pending_count += 1;
+                return true;
+            },
+            .check => {
+                const found = self.search(directive.text, self.cursor, self.range.end + 1) orelse {
+                    self.fail(file, directive, "no matching line");
+                    return false;
+                };
+                if (!self.checkNots(file, found)) return false;
+                self.cursor = found + 1;
+                return true;
+            },
+            .next => {
+                if (self.cursor > self.range.end or std.mem.indexOf(u8, self.lines[self.cursor], directive.text) == null) {
+                    self.fail(file, directive, "the next line does not match");
+                    return false;
+                }
+                if (!self.checkNots(file, self.cursor)) return false;
+                self.cursor += 1;
+                return true;
+            },
+            .label, .snapshot => unreachable,
+        }
+    }
+
+    /// 函数结束：剩余的 CHECK-NOT 检查到函数末尾
+    fn finish(self: *Checker, file: []const u8) bool {
+        if (self.function == null) return true;
+        return self.checkNots(file, self.range.end + 1);
+    }
+
+    fn search(self: *Checker, text: []const u8, from: usize, to: usize) ?usize {
+        var i = from;
+        while (i < to) : (i += 1) {
+            if (std.mem.indexOf(u8, self.lines[i], text) != null) return i;
+        }
+        return null;
+    }
+
+    /// [cursor, until) 中不能出现任何待检查的 CHECK-NOT 文本
+    fn checkNots(self: *Checker, file: []const u8, until: usize) bool {
+        defer self.pending_count = 0;
+        for (self.pending_nots[0..self.pending_count]) |directive| {
+            if (self.search(directive.text, self.cursor, until)) |found| {
+                self.fail(file, directive, "excluded text found");
+                std.debug.print("      found at: {s}\n", .{self.lines[found]});
+                return false;
+            }
+        }
+        return true;
+    }
+
+    fn fail(self: *Checker, file: []const u8, directive: Directive, reason: []const u8) void {
+        std.debug.print("FAIL  {s}\n", .{file});
+        std.debug.print("      {s}:{d}: {s} {s}: {s}\n", .{ file, directive.line, directive.kind.prefix(), directive.text, reason });
+        if (self.function) |name| {
+            std.debug.print("      in function {s}:\n", .{name});
+            for (self.lines[self.range.start .. self.range.end + 1]) |line| {
+                std.debug.print("        {s}\n", .{line});
+            }
+        }
+    }
+};
+
+/// 比较函数的完整代码与快照文件 <file>.<fn><ext>（--update 时重写）
+fn checkSnapshot(
+    allocator: std.mem.Allocator,
+    file: []const u8,
+    directive: Directive,
+    options: Options,
+    lines: []const []const u8,
+) !bool {
+    const range = findFunction(options.backend, lines, directive.text) orelse {
+        std.debug.print("FAIL  {s}\n      {s}:{d}: function {s} not found in the generated code\n", .{ file, file, directive.line, directive.text });
+        return false;
+    };
+    var actual = std.ArrayList(u8){};
+    defer actual.deinit(allocator);
+    for (lines[range.start .. range.end + 1]) |line| {
+        try actual.appendSlice(allocator, line);
+        try actual.append(allocator, '\n');
+    }
+
+    const snapshot_path = try std.fmt.allocPrint(allocator, "{s}.{s}{s}", .{
+        file[0 .. file.len - ".paw".len],
+        directive.text,
+        options.backend.extension(),
+    });
+    defer allocator.free(snapshot_path);
+
+    if (options.update) {
+        try std.fs.cwd().writeFile(.{ .sub_path = snapshot_path, .data = actual.items });
+        return true;
+    }
+
+    const expected = std.fs.cwd().readFileAlloc(allocator, snapshot_path, MAX_IR_BYTES) catch {
+        std.debug.print("FAIL  {s} (missing {s}; run with --update to create it)\n", .{ file, snapshot_path });
+        return false;
+    };
+    defer allocator.free(expected);
+    if (std.mem.eql(u8, expected, actual.items)) return true;
+
+    std.debug.print("FAIL  {s} (function {s} differs from {s})\n", .{ file, directive.text, snapshot_path });
+    var expected_lines = std.mem.splitScalar(u8, expected, '\n');
+    var actual_lines = std.mem.splitScalar(u8, actual.items, '\n');
+    var line: usize = 1;
+    while (true) : (line += 1) {
+        const a = expected_lines.next();
+        const b = actual_lines.next();
+        if (a == null and b == null) break;
+        if (a != null and b != null and std.mem.eql(u8, a.?, b.?)) continue;
+        std.debug.print("      first difference at line {d}:\n", .{line});
+        std.debug.print("        snapshot: {s}\n", .{a orelse "<end of function>"});
+        std.debug.print("        actual:   {s}\n", .{b orelse "<end of function>"});
+        break;
+    }
+    std.debug.print("      rerun with --update to accept the new code\n", .{});
+    return false;
+}
@@ -0,0 +1,180 @@
+//! Layout - 类型的大小与对齐
+//!
+//! 所有后端共享的内存布局规则，与 C 后端生成的 C 类型保持一致：
+//! - 基础类型使用自然对齐（i128/u128 按 16 字节对齐）
+//! - string、指针、动态数组 [T] 都是一个指针；函数值是两个指针 { 环境, 函数 }
+//! - 结构体按字段声明顺序排列，字段间及尾部按最大对齐填充
+//! - 带数据的 enum 是 { tag; union { ... } data; }，tag 为 C enum（4 字节）
+//!
+//! 用于 size_of<T>() / align_of<T>() 编译期内置函数。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+
+pub const Layout = struct {
+    size: usize,
+    alignment: usize,
+};
+
+pub const LayoutError = error{
+    UnknownType,    // 未定义的类型名
+    GenericType,    // 尚未单态化的类型参数
+    RecursiveType,  // 类型直接包含自身，大小无限
+    TraitType,      // trait 不是具体类型
+};
+
+/// 目标平台指针大小（与 C 后端使用同一个编译器目标）
+const POINTER = Layout{ .size = @sizeOf(usize), .alignment = @alignOf(usize) };
+/// C enum 的 tag
+const TAG = Layout{ .size = 4, .alignment = 4 };
+
+/// 泛型参数绑定：type_params[i] -> type_args[i]
+pub const Bindings = struct {
+    type_params: []const []const u8,
+    type_args: []const ast.Type,
+
+    pub const empty = Bindings{ .type_params = &[_][]const u8{}, .type_args = &[_]ast.Type{} };
+
+    fn lookup(self: Bindings, name: []const u8) ?ast.Type {
+        for (self.type_params, 0..) |param, i| {
+            if (i < self.type_args.len and std.mem.eql(u8, param, name)) return self.type_args[i];
+        }
+        return null;
+    }
+};
+
+/// 计算类型的布局
+pub fn ofType(
+    t: ast.Type,
+    types: *const std.StringHashMap(ast.TypeDecl),
+    bindings: Bindings,
+) LayoutError!Layout {
+    return ofTypeDepth(t, types, bindings, 0);
+}
+
+fn ofTypeDepth(
+    t: ast.Type,
+    types: *const std.StringHashMap(ast.TypeDecl),
+    bindings: Bindings,
+    depth: usize,
+) LayoutError!Layout {
+    // 值类型的嵌套超过此深度只可能是递归包含
+    if (depth > 64) return error.RecursiveType;
+
+    return switch (t) {
+        .i8, .u8, .bool, .char => .{ .size = 1, .alignment = 1 },
+        .i16, .u16 => .{ .size = 2, .alignment = 2 },
+        .i32, .u32, .f32 => .{ .size = 4, .alignment = 4 },
+        .i64, .u64, .f64 => .{ .size = 8, .alignment = 8 },
+        .i128, .u128 => .{ .size = 16, .alignment = 16 },
+        .void, .never => .{ .size = 0, .alignment = 1 },
+        .string, .pointer => POINTER,
+        .function => .{ .size = 2 * POINTER.size, .alignment = POINTER.alignment },
+        .array => |arr| blk: {
+            const size = arr.size orelse break :blk POINTER;
+            const elem = try ofTypeDepth(arr.element.*, types, bindings, depth + 1);
+            break :blk .{ .size = elem.size * size, .alignment = elem.alignment };
+        },
+        .generic => |name| blk: {
+            const bound = bindings.lookup(name) orelse return error.GenericType;
+            break :blk try ofTypeDepth(bound, types, Bindings.empty, depth + 1);
+        },
+        .named => |name| blk: {
+            if (bindings.lookup(name)) |bound| {
+                break :blk try ofTypeDepth(bound, types, Bindings.empty, depth + 1);
+            }
+            const decl = types.get(name) orelse return error.UnknownType;
+            break :blk try ofDecl(decl, &[_]ast.Type{}, types, depth);
+        },
+        .generic_instance => |gi| blk: {
+            const decl = types.get(gi.name) orelse return error.UnknownType;
+            // 类型实参本身可能引用外层绑定
+            var args: [16]ast.Type = undefined;
+            const count = @min(gi.type_args.len, args.len);
+            for (gi.type_args[0..count], 0..) |arg, i| {
+                args[i] = resolve(arg, bindings);
+            }
+            break :blk try ofDecl(decl, args[0..count], types, depth);
+        },
+        // 🆕 具体类型的投影在解析后已展开，剩下的是类型参数上的投影
+        .projection => error.GenericType,
+    };
+}
+
+/// 把外层绑定中的类型参数替换为具体类型（只处理顶层）
+pub fn resolve(t: ast.Type, bindings: Bindings) ast.Type {
+    return switch (t) {
+        .generic => |name| bindings.lookup(name) orelse t,
+        .named => |name| bindings.lookup(name) orelse t,
+        else => t,
+    };
+}
+
+fn ofDecl(
+    decl: ast.TypeDecl,
+    type_args: []const ast.Type,
+    types: *const std.StringHashMap(ast.TypeDecl),
+    depth: usize,
+) LayoutError!Layout {
+    const inner = Bindings{ .type_params = decl.type_params, .type_args = type_args };
+
+    switch (decl.kind) {
+        .struct_type => |st| {
+            var builder = StructBuilder{};
+            for (st.fields) |field| {
+                builder.add(try ofTypeDepth(field.type, types, inner, depth + 1));
+            }
+            return builder.finish();
+        },
+        .enum_type => |et| {
+            var payload = Layout{ .size = 0, .alignment = 1 };
+            var has_data = false;
+            for (et.variants) |variant| {
+                if (variant.fields.len == 0) continue;
+                has_data = true;
+                var builder = StructBuilder{};
+                for (variant.fields) |field| {
+                    builder.add(try ofTypeDepth(field, types, inner, depth + 1));
+                }
+                const variant_layout = builder.finish();
+                payload.size = @max(payload.size, variant_layout.size);
+                payload.alignment = @max(payload.alignment, variant_layout.alignment);
+            }
+            if (!has_data) return TAG;
+
+            // union 大小向上取整到其对齐
+            payload.size = std.mem.alignForward(usize, payload.size, payload.alignment);
+            var builder = StructBuilder{};
+            builder.add(TAG);
+            builder.add(payload);
+            return builder.finish();
+        },
+        .trait_type => return error.TraitType,
+        .alias => |target| return ofTypeDepth(target, types, inner, depth + 1),
+    }
+}
+
+/// 按 C 规则依次排列字段
+const StructBuilder = struct {
+    size: usize = 0,
+    alignment: usize = 1,
+
+    fn add(self: *StructBuilder, field: Layout) void {
+        self.size = std.mem.alignForward(usize, self.size, field.alignment) + field.size;
+        self.alignment = @max(self.alignment, field.alignment);
+    }
+
+    fn finish(self: StructBuilder) Layout {
+        return .{ .size = std.mem.alignForward(usize, self.size, self.alignment), .alignment = self.alignment };
+    }
+};
+
+/// 错误的说明文字（用于诊断）
+pub fn describeError(err: LayoutError) []const u8 {
+    return switch (err) {
+        error.UnknownType => "unknown type",
+        error.GenericType => "type parameter has no concrete type here",
+        error.RecursiveType => "type contains itself and has infinite size",
+        error.TraitType => "traits have no size",
+    };
+}
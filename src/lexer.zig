@@ -1,11 +1,14 @@
 const std = @import("std");
 const Token = @import("token.zig").Token;
 const TokenType = @import("token.zig").TokenType;
+const FileId = @import("diagnostic.zig").FileId;
+const messages = @import("messages.zig");  // 🆕 本地化的消息模板
+const codes = @import("codes.zig");  // 🆕 诊断代码注册表
 
 pub const Lexer = struct {
     allocator: std.mem.Allocator,
     source: []const u8,
-    filename: []const u8,  // 🆕 v0.1.8: 文件名
+    file: FileId,  // 🆕 源文件（驻留的路径）
     tokens: std.ArrayList(Token),
     start: usize,
     current: usize,
@@ -22,7 +25,7 @@ pub const Lexer = struct {
         return Lexer{
             .allocator = allocator,
             .source = source,
-            .filename = filename,  // 🆕 v0.1.8
+            .file = FileId.intern(filename),
             .tokens = tokens,
             .start = 0,
             .current = 0,
@@ -50,7 +53,7 @@ pub const Lexer = struct {
             try self.scanToken();
         }
 
-        try self.tokens.append(self.allocator, Token.init(.eof, "", self.line, self.column, self.filename));
+        try self.tokens.append(self.allocator, Token.init(.eof, "", self.line, self.column, self.file));
         return self.tokens.items;
     }
 
@@ -151,6 +154,8 @@ pub const Lexer = struct {
             '|' => {
                 if (self.match('|')) {
                     try self.addToken(.or_or);
+                } else {
+                    try self.addToken(.pipe);
                 }
             },
             ':' => {
@@ -175,15 +180,25 @@ pub const Lexer = struct {
                     try self.addToken(.slash);
                 }
             },
+            '@' => try self.addToken(.at),  // 🆕 属性
             '"' => try self.string(),
-            '\'' => try self.char(),
+            '\'' => {
+                // 🆕 'outer 是循环标签，'a' 是字符字面量
+                if (isAlpha(self.peek()) and self.peekNext() != '\'') {
+                    try self.label();
+                } else {
+                    try self.char();
+                }
+            },
             else => {
                 if (isDigit(c)) {
                     try self.number();
+                } else if (c == 'b' and (self.peek() == '\'' or self.peek() == '"')) {
+                    try self.byteLiteral();  // 🆕 b'A' / b"bytes"
                 } else if (isAlpha(c)) {
                     try self.identifier();
                 } else {
-                    std.debug.print("Error: Unknown character '{c}' at line {d} column {d}\n", .{ c, self.line, self.column });
+                    reportError(.unknown_character, .unknown_character, .{ c, self.line, self.column });
                 }
             },
         }
@@ -228,7 +243,7 @@ pub const Lexer = struct {
         }
 
         if (self.isAtEnd()) {
-            std.debug.print("Error: Unterminated string\n", .{});
+            reportError(.unterminated_literal, .unterminated_string, .{});
             return;
         }
 
@@ -236,6 +251,33 @@ pub const Lexer = struct {
         try self.addToken(.string_literal);
     }
 
+    /// 🆕 字节字面量 b'A' 和字节串 b"bytes"（转义由解析器处理）
+    fn byteLiteral(self: *Lexer) !void {
+        const quote = self.advance();
+        while (self.peek() != quote and !self.isAtEnd()) {
+            if (self.peek() == '\n') {
+                self.line += 1;
+                self.column = 1;
+            }
+            if (self.peek() == '\\') {
+                _ = self.advance(); // 消耗 \
+                if (!self.isAtEnd()) {
+                    _ = self.advance(); // 消耗转义字符
+                }
+            } else {
+                _ = self.advance();
+            }
+        }
+
+        if (self.isAtEnd()) {
+            reportError(.unterminated_literal, .unterminated_byte, .{});
+            return;
+        }
+
+        _ = self.advance(); // 消耗结束的引号
+        try self.addToken(if (quote == '"') .byte_string_literal else .byte_literal);
+    }
+
     fn char(self: *Lexer) !void {
         while (self.peek() != '\'' and !self.isAtEnd()) {
             if (self.peek() == '\n') {
@@ -254,7 +296,7 @@ pub const Lexer = struct {
         }
 
         if (self.isAtEnd()) {
-            std.debug.print("Error: Unterminated character literal\n", .{});
+            reportError(.unterminated_literal, .unterminated_char, .{});
             return;
         }
 
@@ -262,6 +304,14 @@ pub const Lexer = struct {
         try self.addToken(.char_literal);
     }
 
+    /// 🆕 循环标签：'name（lexeme 包含开头的 '）
+    fn label(self: *Lexer) !void {
+        while (isAlphaNumeric(self.peek())) {
+            _ = self.advance();
+        }
+        try self.addToken(.label);
+    }
+
     fn number(self: *Lexer) !void {
         while (isDigit(self.peek())) {
             _ = self.advance();
@@ -277,10 +327,25 @@ pub const Lexer = struct {
 
             try self.addToken(.float_literal);
         } else {
+            // 🆕 整数类型后缀：42u32、7i64（lexeme 包含后缀，由解析器拆分）
+            var end = self.current;
+            while (end < self.source.len and isAlphaNumeric(self.source[end])) : (end += 1) {}
+            if (isIntSuffix(self.source[self.current..end])) {
+                while (self.current < end) _ = self.advance();
+            }
             try self.addToken(.int_literal);
         }
     }
 
+    fn isIntSuffix(text: []const u8) bool {
+        const token_type = getKeywordType(text) orelse return false;
+        return switch (token_type) {
+            .type_i8, .type_i16, .type_i32, .type_i64, .type_i128,
+            .type_u8, .type_u16, .type_u32, .type_u64, .type_u128 => true,
+            else => false,
+        };
+    }
+
     fn identifier(self: *Lexer) !void {
         while (isAlphaNumeric(self.peek())) {
             _ = self.advance();
@@ -295,6 +360,7 @@ pub const Lexer = struct {
         // Paw 核心关键字 (19个) - 极简设计
         if (std.mem.eql(u8, text, "fn")) return .keyword_fn;
         if (std.mem.eql(u8, text, "let")) return .keyword_let;
+        if (std.mem.eql(u8, text, "const")) return .keyword_const;
         if (std.mem.eql(u8, text, "type")) return .keyword_type;
         if (std.mem.eql(u8, text, "import")) return .keyword_import;
         if (std.mem.eql(u8, text, "pub")) return .keyword_pub;
@@ -302,6 +368,7 @@ pub const Lexer = struct {
         if (std.mem.eql(u8, text, "else")) return .keyword_else;
         if (std.mem.eql(u8, text, "loop")) return .keyword_loop;
         if (std.mem.eql(u8, text, "break")) return .keyword_break;
+        if (std.mem.eql(u8, text, "continue")) return .keyword_continue;
         if (std.mem.eql(u8, text, "return")) return .keyword_return;
         if (std.mem.eql(u8, text, "is")) return .keyword_is;
         if (std.mem.eql(u8, text, "as")) return .keyword_as;
@@ -313,6 +380,7 @@ pub const Lexer = struct {
         if (std.mem.eql(u8, text, "true")) return .keyword_true;
         if (std.mem.eql(u8, text, "false")) return .keyword_false;
         if (std.mem.eql(u8, text, "in")) return .keyword_in;
+        if (std.mem.eql(u8, text, "extern")) return .keyword_extern;
         
         // 内置类型（Rust 风格，纯粹无别名）
         // 有符号整数
@@ -393,8 +461,13 @@ pub const Lexer = struct {
             self.line - self.line_offset 
         else 
             self.line;
-        const token = Token.init(token_type, text, adjusted_line, self.column, self.filename);
+        const token = Token.init(token_type, text, adjusted_line, self.column, self.file);
         try self.tokens.append(self.allocator, token);
     }
 };
 
+/// 🆕 词法错误：与解析器的诊断一样以 error[代码] 开头
+fn reportError(code: codes.Code, comptime id: messages.Id, args: anytype) void {
+    std.debug.print("\x1b[1;31m{s}[{s}]\x1b[0m: ", .{ messages.text(.level_error), code.id() });
+    messages.print(id, args);
+}
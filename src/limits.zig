@@ -0,0 +1,85 @@
+//! Limits - 编译资源限制（Paw.toml 的 [limits] 节）
+//!
+//! 对抗性或有缺陷的输入（死循环的 const fn、不断用更大的类型实例化自己的泛型函数等）
+//! 超出限制时报告诊断并停止，而不是让编译器看起来卡住或耗尽内存。
+//!
+//!   [limits]
+//!   const_eval_steps = 1000000     # 每个常量（或数组长度）编译期求值的步数
+//!   generic_instances = 10000      # 单态化实例总数（泛型函数、结构体和方法）
+//!   object_size = "64M"            # 生成代码的大小（可带 K / M / G 后缀）
+//!   function_codegen_ms = 10000    # 单个函数的代码生成时间（毫秒）
+//!
+//! 没有 Paw.toml 或没有 [limits] 节时使用默认值。C 后端和 LLVM 后端都执行全部限制。
+
+const std = @import("std");
+const diagnostic = @import("diagnostic.zig");
+const Diagnostic = diagnostic.Diagnostic;
+const Span = diagnostic.Span;
+const consteval = @import("consteval.zig");
+
+pub const SECTION = "limits";
+
+pub const Limits = struct {
+    const_eval_steps: u64 = consteval.MAX_STEPS,
+    generic_instances: u64 = 10_000,
+    object_size: u64 = 64 * 1024 * 1024,
+    function_codegen_ms: u64 = 10_000,
+
+    /// 设置 [limits] 中的一项；未知的键或无效的值返回错误
+    pub fn set(self: *Limits, key: []const u8, value: []const u8) error{ UnknownLimit, InvalidLimit }!void {
+        inline for (@typeInfo(Limits).@"struct".fields) |field| {
+            if (std.mem.eql(u8, key, field.name)) {
+                const text = std.mem.trim(u8, value, "\"");
+                const parsed = if (std.mem.eql(u8, field.name, "object_size"))
+                    parseByteSize(text)
+                else
+                    std.fmt.parseInt(u64, text, 10) catch null;
+                @field(self, field.name) = parsed orelse return error.InvalidLimit;
+                if (@field(self, field.name) == 0) return error.InvalidLimit;
+                return;
+            }
+        }
+        return error.UnknownLimit;
+    }
+};
+
+/// 解析字节数，可带 K / M / G 后缀（1024 进制），用于 --stack-size 和 object_size
+pub fn parseByteSize(text: []const u8) ?u64 {
+    if (text.len == 0) return null;
+    const shift: u6 = switch (std.ascii.toUpper(text[text.len - 1])) {
+        'K' => 10,
+        'M' => 20,
+        'G' => 30,
+        else => 0,
+    };
+    const digits = if (shift == 0) text else text[0 .. text.len - 1];
+    const value = std.fmt.parseInt(u64, digits, 10) catch return null;
+    if (value == 0) return null;
+    return std.math.shlExact(u64, value, shift) catch null;
+}
+
+/// 报告超出限制：说明是哪一项限制，以及如何在 Paw.toml 中调整
+pub fn report(
+    allocator: std.mem.Allocator,
+    message: []const u8,
+    span: ?Span,
+    notes: []const []const u8,
+    comptime key: []const u8,
+) void {
+    comptime std.debug.assert(@hasField(Limits, key));
+    const help = "if the program really needs more, raise `" ++ key ++ "` in the [" ++ SECTION ++ "] section of Paw.toml";
+    const diag = Diagnostic.init(.Error, message, span, notes, help).withCode(.limit_exceeded);
+    diag.print(allocator) catch {};
+}
+
+/// 生成代码超过 object_size 时报告并返回 false
+pub fn checkObjectSize(allocator: std.mem.Allocator, source_file: []const u8, size: usize, limits: Limits) bool {
+    if (size <= limits.object_size) return true;
+    var message_buf: [256]u8 = undefined;
+    const message = std.fmt.bufPrint(&message_buf, "generated code for '{s}' is {d} bytes, which exceeds the limit of {d} bytes", .{
+        source_file, size, limits.object_size,
+    }) catch "generated code exceeds the object size limit";
+    const notes = [_][]const u8{"large generated code usually comes from many generic instances or huge constant arrays"};
+    report(allocator, message, null, &notes, "object_size");
+    return false;
+}
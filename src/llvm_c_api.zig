@@ -15,6 +15,11 @@ pub const BuilderRef = ?*opaque {};
 pub const TypeRef = ?*opaque {};
 pub const ValueRef = ?*opaque {};
 pub const BasicBlockRef = ?*opaque {};
+pub const AttributeRef = ?*opaque {};  // 🆕 参数 / 返回值属性（zeroext 等）
+
+/// 🆕 属性位置：0 是返回值，1..n 是参数
+pub const AttributeReturnIndex: c_uint = 0;
+pub const AttributeFunctionIndex: c_uint = std.math.maxInt(c_uint);  // 🆕 函数本身的属性（naked 等）
 
 // LLVM Linkage Types
 pub const Linkage = enum(c_uint) {
@@ -51,6 +56,17 @@ pub const IntPredicate = enum(c_uint) {
     SLE = 41, // signed less or equal
 };
 
+// 🆕 LLVM Floating Point Comparison Predicates (ordered)
+pub const RealPredicate = enum(c_uint) {
+    OEQ = 1,  // ordered and equal
+    OGT = 2,  // ordered and greater than
+    OGE = 3,  // ordered and greater or equal
+    OLT = 4,  // ordered and less than
+    OLE = 5,  // ordered and less or equal
+    ONE = 6,  // ordered and not equal
+    UNO = 8,  // 🆕 unordered (either operand is NaN)
+};
+
 // ============================================================================
 // Context Functions
 // ============================================================================
@@ -87,6 +103,16 @@ pub extern "c" fn LLVMVerifyModule(
     OutMessage: *[*:0]u8,
 ) c_int;
 
+/// 🆕 Verifier failure action (LLVMVerifierFailureAction)
+pub const VerifierFailureAction = struct {
+    pub const AbortProcess: c_uint = 0;
+    pub const PrintMessage: c_uint = 1;
+    pub const ReturnStatus: c_uint = 2;
+};
+
+/// 🆕 Verify a single function (returns non-zero if invalid)
+pub extern "c" fn LLVMVerifyFunction(Fn: ValueRef, Action: c_uint) c_int;
+
 // ============================================================================
 // Type Functions
 // ============================================================================
@@ -143,12 +169,58 @@ pub extern "c" fn LLVMAddFunction(
     FunctionTy: TypeRef,
 ) ValueRef;
 
+/// Remove a function from its module and delete it
+pub extern "c" fn LLVMDeleteFunction(Fn: ValueRef) void;
+
+/// 🆕 Redirect every use of a value to another value (replacing a discarded function body)
+pub extern "c" fn LLVMReplaceAllUsesWith(OldVal: ValueRef, NewVal: ValueRef) void;
+
+/// 🆕 Rename a value
+pub extern "c" fn LLVMSetValueName2(Val: ValueRef, Name: [*]const u8, NameLen: usize) void;
+
+/// 🆕 Get the value type of a global (the function type of a function)
+pub extern "c" fn LLVMGlobalGetValueType(Global: ValueRef) TypeRef;
+
+/// 🆕 Look up a function by name (null if the module has none)
+pub extern "c" fn LLVMGetNamedFunction(M: ModuleRef, Name: [*:0]const u8) ?ValueRef;
+
 /// Get a function parameter
 pub extern "c" fn LLVMGetParam(Fn: ValueRef, Index: c_uint) ValueRef;
 
 /// Set function linkage
 pub extern "c" fn LLVMSetLinkage(Global: ValueRef, Linkage: Linkage) void;
 
+/// 🆕 Look up the kind id of an enum attribute ("zeroext", "signext", ...)
+pub extern "c" fn LLVMGetEnumAttributeKindForName(Name: [*]const u8, SLen: usize) c_uint;
+
+/// 🆕 Create an enum attribute
+pub extern "c" fn LLVMCreateEnumAttribute(C: ContextRef, KindID: c_uint, Val: u64) AttributeRef;
+
+/// 🆕 Attach an attribute to a function's return value or parameter
+pub extern "c" fn LLVMAddAttributeAtIndex(F: ValueRef, Idx: c_uint, A: AttributeRef) void;
+
+/// 🆕 Inline assembly syntax
+pub const InlineAsmDialect = enum(c_uint) {
+    ATT = 0,
+    Intel = 1,
+};
+
+/// 🆕 Create an inline assembly value callable with the given function type (@asm)
+pub extern "c" fn LLVMGetInlineAsm(
+    Ty: TypeRef,
+    AsmString: [*]const u8,
+    AsmStringSize: usize,
+    Constraints: [*]const u8,
+    ConstraintsSize: usize,
+    HasSideEffects: c_int,
+    IsAlignStack: c_int,
+    Dialect: InlineAsmDialect,
+    CanThrow: c_int,
+) ValueRef;
+
+/// 🆕 Attach an attribute to a call site's return value or argument
+pub extern "c" fn LLVMAddCallSiteAttribute(C: ValueRef, Idx: c_uint, A: AttributeRef) void;
+
 /// Create a constant integer
 pub extern "c" fn LLVMConstInt(
     IntTy: TypeRef,
@@ -165,6 +237,20 @@ pub extern "c" fn LLVMConstNull(Ty: TypeRef) ValueRef;
 /// Create a constant array
 pub extern "c" fn LLVMConstArray(ElementTy: TypeRef, ConstantVals: [*c]ValueRef, Length: c_uint) ValueRef;
 
+/// 🆕 Create a constant byte array (binary-safe, may contain NUL)
+pub extern "c" fn LLVMConstStringInContext(
+    C: ContextRef,
+    Str: [*]const u8,
+    Length: c_uint,
+    DontNullTerminate: c_int,
+) ValueRef;
+
+/// 🆕 Global variables (for constant data objects)
+pub extern "c" fn LLVMAddGlobal(M: ModuleRef, Ty: TypeRef, Name: [*:0]const u8) ValueRef;
+pub extern "c" fn LLVMGetNamedGlobal(M: ModuleRef, Name: [*:0]const u8) ?ValueRef;
+pub extern "c" fn LLVMSetInitializer(GlobalVar: ValueRef, ConstantVal: ValueRef) void;
+pub extern "c" fn LLVMSetGlobalConstant(GlobalVar: ValueRef, IsConstant: c_int) void;
+
 // ============================================================================
 // Basic Block Functions
 // ============================================================================
@@ -176,6 +262,23 @@ pub extern "c" fn LLVMAppendBasicBlockInContext(
     Name: [*:0]const u8,
 ) BasicBlockRef;
 
+/// 🆕 Walk a function's blocks and a block's instructions (--verify invariant checks)
+pub extern "c" fn LLVMCountBasicBlocks(Fn: ValueRef) c_uint;
+pub extern "c" fn LLVMGetEntryBasicBlock(Fn: ValueRef) BasicBlockRef;
+pub extern "c" fn LLVMGetFirstBasicBlock(Fn: ValueRef) BasicBlockRef;
+pub extern "c" fn LLVMGetNextBasicBlock(BB: BasicBlockRef) BasicBlockRef;
+pub extern "c" fn LLVMGetBasicBlockName(BB: BasicBlockRef) [*:0]const u8;
+pub extern "c" fn LLVMGetFirstInstruction(BB: BasicBlockRef) ValueRef;
+pub extern "c" fn LLVMGetNextInstruction(Inst: ValueRef) ValueRef;
+pub extern "c" fn LLVMGetLastInstruction(BB: BasicBlockRef) ValueRef;
+pub extern "c" fn LLVMGetNumOperands(Val: ValueRef) c_int;
+pub extern "c" fn LLVMGetOperand(Val: ValueRef, Index: c_uint) ValueRef;
+pub extern "c" fn LLVMGetValueName2(Val: ValueRef, Length: *usize) [*]const u8;
+/// 🆕 Instruction kind tests (return the instruction, or null if it is not of that kind)
+pub extern "c" fn LLVMIsATerminatorInst(Val: ValueRef) ValueRef;
+pub extern "c" fn LLVMIsAAllocaInst(Val: ValueRef) ValueRef;
+pub extern "c" fn LLVMIsALoadInst(Val: ValueRef) ValueRef;
+
 // ============================================================================
 // Builder Functions
 // ============================================================================
@@ -192,6 +295,9 @@ pub extern "c" fn LLVMPositionBuilderAtEnd(
     Block: BasicBlockRef,
 ) void;
 
+/// 🆕 Position builder before an instruction
+pub extern "c" fn LLVMPositionBuilderBefore(Builder: BuilderRef, Instr: ValueRef) void;
+
 // ============================================================================
 // Instruction Building Functions
 // ============================================================================
@@ -234,6 +340,86 @@ pub extern "c" fn LLVMBuildSDiv(
     Name: [*:0]const u8,
 ) ValueRef;
 
+/// 🆕 Build unsigned division instruction
+pub extern "c" fn LLVMBuildUDiv(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build signed remainder instruction
+pub extern "c" fn LLVMBuildSRem(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build unsigned remainder instruction
+pub extern "c" fn LLVMBuildURem(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build floating point add instruction
+pub extern "c" fn LLVMBuildFAdd(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build floating point subtract instruction
+pub extern "c" fn LLVMBuildFSub(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build floating point multiply instruction
+pub extern "c" fn LLVMBuildFMul(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build floating point division instruction
+pub extern "c" fn LLVMBuildFDiv(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build floating point remainder instruction
+pub extern "c" fn LLVMBuildFRem(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build floating point comparison
+pub extern "c" fn LLVMBuildFCmp(
+    Builder: BuilderRef,
+    Op: RealPredicate,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build floating point negation instruction
+pub extern "c" fn LLVMBuildFNeg(
+    Builder: BuilderRef,
+    V: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
 /// Build function call
 pub extern "c" fn LLVMBuildCall2(
     Builder: BuilderRef,
@@ -286,6 +472,9 @@ pub extern "c" fn LLVMBuildCondBr(
 /// Build unconditional branch
 pub extern "c" fn LLVMBuildBr(Builder: BuilderRef, Dest: BasicBlockRef) ValueRef;
 
+/// 🆕 Build unreachable terminator
+pub extern "c" fn LLVMBuildUnreachable(Builder: BuilderRef) ValueRef;
+
 /// Build logical AND instruction
 pub extern "c" fn LLVMBuildAnd(
     Builder: BuilderRef,
@@ -302,6 +491,22 @@ pub extern "c" fn LLVMBuildOr(
     Name: [*:0]const u8,
 ) ValueRef;
 
+/// Build bitwise XOR instruction
+pub extern "c" fn LLVMBuildXor(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// Build logical shift right instruction
+pub extern "c" fn LLVMBuildLShr(
+    Builder: BuilderRef,
+    LHS: ValueRef,
+    RHS: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
 /// Build logical NOT instruction
 pub extern "c" fn LLVMBuildNot(
     Builder: BuilderRef,
@@ -344,6 +549,15 @@ pub extern "c" fn LLVMBuildGlobalStringPtr(
     Name: [*:0]const u8,
 ) ValueRef;
 
+/// 🆕 Build select (cond ? then : else)
+pub extern "c" fn LLVMBuildSelect(
+    Builder: BuilderRef,
+    If: ValueRef,
+    Then: ValueRef,
+    Else: ValueRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
 /// Get element pointer (GEP) for array/struct access
 pub extern "c" fn LLVMBuildGEP2(
     Builder: BuilderRef,
@@ -369,6 +583,13 @@ pub extern "c" fn LLVMBuildInBoundsGEP2(
 // ============================================================================
 
 /// Build zero extension (无符号扩展)
+pub extern "c" fn LLVMBuildPtrToInt(
+    Builder: BuilderRef,
+    Val: ValueRef,
+    DestTy: TypeRef,
+    Name: [*:0]const u8,
+) ValueRef;
+
 pub extern "c" fn LLVMBuildZExt(
     Builder: BuilderRef,
     Val: ValueRef,
@@ -463,6 +684,40 @@ pub extern "c" fn LLVMStructTypeInContext(
     Packed: c_int,
 ) TypeRef;
 
+/// 🆕 Create a named (identified) struct type; the body is set later
+pub extern "c" fn LLVMStructCreateNamed(C: ContextRef, Name: [*:0]const u8) TypeRef;
+
+/// 🆕 Set the element types of a named struct type
+pub extern "c" fn LLVMStructSetBody(
+    StructTy: TypeRef,
+    ElementTypes: [*]TypeRef,
+    ElementCount: c_uint,
+    Packed: c_int,
+) void;
+
+/// 🆕 Undefined value of a type (starting point for insertvalue)
+pub extern "c" fn LLVMGetUndef(Ty: TypeRef) ValueRef;
+
+/// 🆕 Build insertvalue (aggregate with one element replaced)
+pub extern "c" fn LLVMBuildInsertValue(
+    Builder: BuilderRef,
+    AggVal: ValueRef,
+    EltVal: ValueRef,
+    Index: c_uint,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Build extractvalue (one element of an aggregate value)
+pub extern "c" fn LLVMBuildExtractValue(
+    Builder: BuilderRef,
+    AggVal: ValueRef,
+    Index: c_uint,
+    Name: [*:0]const u8,
+) ValueRef;
+
+/// 🆕 Size of a type in bytes as an i64 constant expression (heap allocation of closure environments)
+pub extern "c" fn LLVMSizeOf(Ty: TypeRef) ValueRef;
+
 // ============================================================================
 // Wrapper Types for Better Zig Experience
 // ============================================================================
@@ -532,6 +787,12 @@ pub const Context = struct {
         return LLVMPointerType(self.i32Type(), address_space);
     }
     
+    /// 🆕 按名字创建无参数的枚举属性，如 enumAttribute("zeroext")
+    pub fn enumAttribute(self: Context, name: []const u8) AttributeRef {
+        const kind = LLVMGetEnumAttributeKindForName(name.ptr, name.len);
+        return LLVMCreateEnumAttribute(self.ref, kind, 0);
+    }
+    
     pub fn structType(self: Context, element_types: []TypeRef, is_packed: bool) TypeRef {
         return LLVMStructTypeInContext(
             self.ref,
@@ -560,6 +821,43 @@ pub const Module = struct {
         return LLVMAddFunction(self.ref, name.ptr, func_type);
     }
 
+    /// 🆕 Add a private constant global holding raw bytes (no NUL terminator)
+    pub fn addConstantBytes(self: Module, name: [:0]const u8, bytes: []const u8) ValueRef {
+        const array_type = LLVMArrayType(self.context.i8Type(), @intCast(bytes.len));
+        const global = LLVMAddGlobal(self.ref, array_type, name.ptr);
+        LLVMSetInitializer(global, LLVMConstStringInContext(self.context.ref, bytes.ptr, @intCast(bytes.len), 1));
+        LLVMSetGlobalConstant(global, 1);
+        LLVMSetLinkage(global, .Private);
+        return global;
+    }
+
+    /// 🆕 Add an internal (module-local) mutable global
+    pub fn addInternalGlobal(self: Module, name: [:0]const u8, ty: TypeRef, initializer: ValueRef) ValueRef {
+        const global = LLVMAddGlobal(self.ref, ty, name.ptr);
+        LLVMSetInitializer(global, initializer);
+        LLVMSetLinkage(global, .Internal);
+        return global;
+    }
+
+    /// 🆕 Reuse an existing global named `name`, or declare an external one
+    pub fn getOrAddGlobal(self: Module, name: [:0]const u8, ty: TypeRef) ValueRef {
+        return LLVMGetNamedGlobal(self.ref, name.ptr) orelse LLVMAddGlobal(self.ref, ty, name.ptr);
+    }
+
+    /// 🆕 Reuse an existing declaration of `name`, or declare it
+    pub fn getOrAddFunction(self: Module, name: [:0]const u8, func_type: TypeRef) ValueRef {
+        return LLVMGetNamedFunction(self.ref, name.ptr) orelse LLVMAddFunction(self.ref, name.ptr, func_type);
+    }
+
+    /// 🆕 运行模块验证，返回 verifier 的错误信息（通过验证时返回 null）
+    pub fn verifierMessage(self: Module, allocator: std.mem.Allocator) !?[]u8 {
+        var error_msg: [*:0]u8 = undefined;
+        const result = LLVMVerifyModule(self.ref, VerifierFailureAction.ReturnStatus, &error_msg);
+        defer LLVMDisposeMessage(error_msg);
+        if (result == 0) return null;
+        return try allocator.dupe(u8, std.mem.trimRight(u8, std.mem.span(error_msg), "\n"));
+    }
+    
     pub fn verify(self: Module) !void {
         var error_msg: [*:0]u8 = undefined;
         const result = LLVMVerifyModule(self.ref, 2, &error_msg); // 2 = ReturnStatusAction
@@ -582,6 +880,10 @@ pub const Builder = struct {
         LLVMPositionBuilderAtEnd(self.ref, block);
     }
 
+    pub fn positionBefore(self: Builder, instr: ValueRef) void {
+        LLVMPositionBuilderBefore(self.ref, instr);
+    }
+
     pub fn buildRet(self: Builder, value: ValueRef) ValueRef {
         return LLVMBuildRet(self.ref, value);
     }
@@ -606,6 +908,46 @@ pub const Builder = struct {
         return LLVMBuildSDiv(self.ref, lhs, rhs, name.ptr);
     }
 
+    pub fn buildUDiv(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildUDiv(self.ref, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildSRem(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildSRem(self.ref, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildURem(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildURem(self.ref, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildFAdd(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildFAdd(self.ref, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildFSub(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildFSub(self.ref, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildFMul(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildFMul(self.ref, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildFDiv(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildFDiv(self.ref, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildFRem(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildFRem(self.ref, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildFCmp(self: Builder, op: RealPredicate, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildFCmp(self.ref, op, lhs, rhs, name.ptr);
+    }
+
+    pub fn buildFNeg(self: Builder, value: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildFNeg(self.ref, value, name.ptr);
+    }
+
     pub fn buildCall(
         self: Builder,
         func_type: TypeRef,
@@ -626,6 +968,10 @@ pub const Builder = struct {
     pub fn buildBr(self: Builder, dest: BasicBlockRef) ValueRef {
         return LLVMBuildBr(self.ref, dest);
     }
+
+    pub fn buildUnreachable(self: Builder) ValueRef {
+        return LLVMBuildUnreachable(self.ref);
+    }
     
     pub fn buildICmp(self: Builder, op: IntPredicate, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
         return LLVMBuildICmp(self.ref, op, lhs, rhs, name.ptr);
@@ -639,6 +985,14 @@ pub const Builder = struct {
         return LLVMBuildOr(self.ref, lhs, rhs, name.ptr);
     }
     
+    pub fn buildXor(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildXor(self.ref, lhs, rhs, name.ptr);
+    }
+    
+    pub fn buildLShr(self: Builder, lhs: ValueRef, rhs: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildLShr(self.ref, lhs, rhs, name.ptr);
+    }
+    
     pub fn buildNot(self: Builder, value: ValueRef, name: [:0]const u8) ValueRef {
         return LLVMBuildNot(self.ref, value, name.ptr);
     }
@@ -690,6 +1044,10 @@ pub const Builder = struct {
     }
     
     // 🆕 v0.1.7: Type cast wrappers
+    pub fn buildPtrToInt(self: Builder, value: ValueRef, dest_ty: TypeRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildPtrToInt(self.ref, value, dest_ty, name.ptr);
+    }
+    
     pub fn buildZExt(self: Builder, value: ValueRef, dest_ty: TypeRef, name: [:0]const u8) ValueRef {
         return LLVMBuildZExt(self.ref, value, dest_ty, name.ptr);
     }
@@ -725,6 +1083,10 @@ pub const Builder = struct {
     pub fn buildFPTrunc(self: Builder, value: ValueRef, dest_ty: TypeRef, name: [:0]const u8) ValueRef {
         return LLVMBuildFPTrunc(self.ref, value, dest_ty, name.ptr);
     }
+    
+    pub fn buildSelect(self: Builder, cond: ValueRef, then_value: ValueRef, else_value: ValueRef, name: [:0]const u8) ValueRef {
+        return LLVMBuildSelect(self.ref, cond, then_value, else_value, name.ptr);
+    }
 };
 
 // Helper function to create constant int
@@ -788,3 +1150,148 @@ pub fn arrayType(element_type: TypeRef, count: u32) TypeRef {
 // PawLang 生成的 LLVM IR 已经是高质量的 SSA 形式，
 // clang 可以直接进行各种优化。
 
+// ============================================================================
+// 🆕 ORC JIT (paw dev)
+// ============================================================================
+
+pub const ErrorRef = ?*opaque {};
+pub const OrcLLJITRef = ?*opaque {};
+pub const OrcJITDylibRef = ?*opaque {};
+pub const OrcThreadSafeContextRef = ?*opaque {};
+pub const OrcThreadSafeModuleRef = ?*opaque {};
+pub const OrcDefinitionGeneratorRef = ?*opaque {};
+pub const OrcExecutorAddress = u64;
+
+/// Memory ordering of atomic loads / stores (LLVMAtomicOrdering)
+pub const AtomicOrdering = enum(c_uint) {
+    NotAtomic = 0,
+    Unordered = 1,
+    Monotonic = 2,
+    Acquire = 4,
+    Release = 5,
+};
+
+pub extern "c" fn LLVMSetOrdering(MemoryAccessInst: ValueRef, Ordering: AtomicOrdering) void;
+
+pub extern "c" fn LLVMGetErrorMessage(Err: ErrorRef) [*:0]u8;
+pub extern "c" fn LLVMDisposeErrorMessage(ErrMsg: [*:0]u8) void;
+
+pub extern "c" fn LLVMOrcCreateLLJIT(Result: *OrcLLJITRef, Builder: ?*anyopaque) ErrorRef;
+pub extern "c" fn LLVMOrcDisposeLLJIT(J: OrcLLJITRef) ErrorRef;
+pub extern "c" fn LLVMOrcLLJITGetMainJITDylib(J: OrcLLJITRef) OrcJITDylibRef;
+pub extern "c" fn LLVMOrcLLJITGetGlobalPrefix(J: OrcLLJITRef) u8;
+pub extern "c" fn LLVMOrcLLJITAddLLVMIRModule(J: OrcLLJITRef, JD: OrcJITDylibRef, TSM: OrcThreadSafeModuleRef) ErrorRef;
+pub extern "c" fn LLVMOrcLLJITLookup(J: OrcLLJITRef, Result: *OrcExecutorAddress, Name: [*:0]const u8) ErrorRef;
+pub extern "c" fn LLVMOrcCreateNewThreadSafeContext() OrcThreadSafeContextRef;
+pub extern "c" fn LLVMOrcThreadSafeContextGetContext(TSCtx: OrcThreadSafeContextRef) ContextRef;
+pub extern "c" fn LLVMOrcDisposeThreadSafeContext(TSCtx: OrcThreadSafeContextRef) void;
+pub extern "c" fn LLVMOrcCreateNewThreadSafeModule(M: ModuleRef, TSCtx: OrcThreadSafeContextRef) OrcThreadSafeModuleRef;
+pub extern "c" fn LLVMOrcCreateDynamicLibrarySearchGeneratorForProcess(
+    Result: *OrcDefinitionGeneratorRef,
+    GlobalPrefix: u8,
+    Filter: ?*anyopaque,
+    FilterCtx: ?*anyopaque,
+) ErrorRef;
+pub extern "c" fn LLVMOrcJITDylibAddGenerator(JD: OrcJITDylibRef, DG: OrcDefinitionGeneratorRef) void;
+
+// LLVMInitializeNativeTarget() is a static inline function in the C headers,
+// so the per-architecture initializers are bound directly
+extern "c" fn LLVMInitializeX86TargetInfo() void;
+extern "c" fn LLVMInitializeX86Target() void;
+extern "c" fn LLVMInitializeX86TargetMC() void;
+extern "c" fn LLVMInitializeX86AsmPrinter() void;
+extern "c" fn LLVMInitializeAArch64TargetInfo() void;
+extern "c" fn LLVMInitializeAArch64Target() void;
+extern "c" fn LLVMInitializeAArch64TargetMC() void;
+extern "c" fn LLVMInitializeAArch64AsmPrinter() void;
+
+/// Initialize the host target (required before creating a JIT)
+pub fn initializeNativeTarget() !void {
+    switch (@import("builtin").cpu.arch) {
+        .x86_64 => {
+            LLVMInitializeX86TargetInfo();
+            LLVMInitializeX86Target();
+            LLVMInitializeX86TargetMC();
+            LLVMInitializeX86AsmPrinter();
+        },
+        .aarch64 => {
+            LLVMInitializeAArch64TargetInfo();
+            LLVMInitializeAArch64Target();
+            LLVMInitializeAArch64TargetMC();
+            LLVMInitializeAArch64AsmPrinter();
+        },
+        else => return error.UnsupportedJitTarget,
+    }
+}
+
+/// Print and consume an LLVM error; returns error.JitFailed for a non-null error
+fn checkError(err: ErrorRef, what: []const u8) !void {
+    if (err == null) return;
+    const message = LLVMGetErrorMessage(err);
+    defer LLVMDisposeErrorMessage(message);
+    std.debug.print("❌ JIT {s} failed: {s}\n", .{ what, message });
+    return error.JitFailed;
+}
+
+/// A context for building one module that will be added to a JIT session.
+/// Each module gets its own context, so building the next module never
+/// races with the JIT compiling the previous one.
+pub const JitContext = struct {
+    ref: OrcThreadSafeContextRef,
+
+    pub fn create() JitContext {
+        return JitContext{ .ref = LLVMOrcCreateNewThreadSafeContext() };
+    }
+
+    /// Release this handle (modules added to the JIT keep the context alive)
+    pub fn dispose(self: *JitContext) void {
+        LLVMOrcDisposeThreadSafeContext(self.ref);
+    }
+
+    pub fn context(self: JitContext) Context {
+        return Context{ .ref = LLVMOrcThreadSafeContextGetContext(self.ref) };
+    }
+};
+
+/// An LLJIT session: modules added to it may reference each other's
+/// symbols and those of the host process (libc)
+pub const Jit = struct {
+    ref: OrcLLJITRef,
+    dylib: OrcJITDylibRef,
+
+    pub fn create() !Jit {
+        try initializeNativeTarget();
+        var ref: OrcLLJITRef = null;
+        try checkError(LLVMOrcCreateLLJIT(&ref, null), "creation");
+        errdefer _ = LLVMOrcDisposeLLJIT(ref);
+        
+        const dylib = LLVMOrcLLJITGetMainJITDylib(ref);
+        var generator: OrcDefinitionGeneratorRef = null;
+        try checkError(LLVMOrcCreateDynamicLibrarySearchGeneratorForProcess(
+            &generator,
+            LLVMOrcLLJITGetGlobalPrefix(ref),
+            null,
+            null,
+        ), "process symbol lookup");
+        LLVMOrcJITDylibAddGenerator(dylib, generator);
+        
+        return Jit{ .ref = ref, .dylib = dylib };
+    }
+
+    pub fn dispose(self: *Jit) void {
+        _ = LLVMOrcDisposeLLJIT(self.ref);
+    }
+
+    /// Hand `module` (built in `jit_context`) over to the JIT, which owns it afterwards
+    pub fn addModule(self: Jit, module: Module, jit_context: JitContext) !void {
+        const tsm = LLVMOrcCreateNewThreadSafeModule(module.ref, jit_context.ref);
+        try checkError(LLVMOrcLLJITAddLLVMIRModule(self.ref, self.dylib, tsm), "adding module");
+    }
+
+    /// Address of symbol `name`; the first lookup compiles the symbol's module
+    pub fn lookup(self: Jit, name: [:0]const u8) !OrcExecutorAddress {
+        var address: OrcExecutorAddress = 0;
+        try checkError(LLVMOrcLLJITLookup(self.ref, &address, name.ptr), "symbol lookup");
+        return address;
+    }
+};
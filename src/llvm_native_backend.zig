@@ -6,8 +6,18 @@
 const std = @import("std");
 const ast = @import("ast.zig");
 const llvm = @import("llvm_c_api.zig");
+const layout = @import("layout.zig");
+const generics = @import("generics.zig");
+const diagnostic = @import("diagnostic.zig");
+const cancel = @import("cancel.zig");
+const closure = @import("closure.zig");
+const Limits = @import("limits.zig").Limits;  // 🆕 Paw.toml [limits]
+const builtin = @import("builtin");
 
 // 🆕 v0.1.7: LLVM 优化级别
+/// 🆕 栈溢出检查的下限之上保留的空间（main 包装函数、libc 和 paw_rt_stack_overflow 使用）
+const STACK_CHECK_MARGIN: i64 = 256 * 1024;
+
 pub const OptLevel = enum {
     O0,  // No optimization
     O1,  // Basic optimization
@@ -15,9 +25,37 @@ pub const OptLevel = enum {
     O3,  // Aggressive optimization
 };
 
+/// 🆕 paw dev 的热重载模式
+///
+/// 用户函数不直接调用，而是通过槽位（全局函数指针 paw.slot.<name>）间接调用；
+/// 函数体以 <name>.v<版本> 命名。重新编译的函数放在新的模块中，dev 服务器把
+/// 新函数体的地址写入已有的槽位，正在运行的程序下一次调用时就会使用新代码。
+pub const HotReload = struct {
+    /// 本次生成的模块版本（0 是初始模块）
+    version: u32,
+    /// 只生成这些函数的函数体（null：全部生成，即初始模块）
+    changed: ?*const std.StringHashMap(void),
+    /// 之前的模块已经定义了槽位的函数（本模块只声明这些槽位）
+    defined_slots: *const std.StringHashMap(void),
+
+    pub fn slotName(allocator: std.mem.Allocator, func_name: []const u8) ![:0]u8 {
+        return std.fmt.allocPrintSentinel(allocator, "paw.slot.{s}", .{func_name}, 0);
+    }
+
+    pub fn bodyName(allocator: std.mem.Allocator, func_name: []const u8, version: u32) ![:0]u8 {
+        return std.fmt.allocPrintSentinel(allocator, "{s}.v{d}", .{ func_name, version }, 0);
+    }
+
+    /// 可以热替换的函数：非泛型、非 extern、没有指定链接名、不是 @asm 函数、不是提升的 lambda
+    pub fn isSwappable(func: ast.FunctionDecl) bool {
+        return func.type_params.len == 0 and !func.is_extern and func.link_name == null and func.asm_bodies.len == 0 and !func.isLambda();
+    }
+};
+
 pub const LLVMNativeBackend = struct {
     allocator: std.mem.Allocator,
     context: llvm.Context,
+    owns_context: bool,  // 🆕 JIT 提供的上下文由 JIT 管理
     module: llvm.Module,
     builder: llvm.Builder,
     
@@ -25,6 +63,8 @@ pub const LLVMNativeBackend = struct {
     functions: std.StringHashMap(llvm.ValueRef),
     variables: std.StringHashMap(llvm.ValueRef),
     variable_types: std.StringHashMap(llvm.TypeRef),  // Track variable types for load/store
+    type_decls: std.StringHashMap(ast.TypeDecl),  // 🆕 类型声明（用于布局计算）
+    struct_types: std.StringHashMap(llvm.TypeRef),  // 🆕 已创建的结构体类型（%Name）
     
     // Current function context
     current_function: ?llvm.ValueRef,
@@ -32,16 +72,59 @@ pub const LLVMNativeBackend = struct {
     // Loop context for break/continue
     current_loop_exit: ?llvm.BasicBlockRef,
     current_loop_continue: ?llvm.BasicBlockRef,
+    current_loop_value: ?llvm.ValueRef,  // 🆕 loop 表达式的结果变量（break value 写入）
+    labeled_loops: std.ArrayList(LabeledLoop),  // 🆕 带标签的外层循环（break 'outer）
     
     // 🆕 v0.1.7: Optimization level
     opt_level: OptLevel,
     
+    // 🆕 生成失败的函数（生成结束后统一报告）
+    codegen_errors: diagnostic.CodegenErrors,
+    // 🆕 逐个函数运行 LLVM verifier（开发构建，即 O0 时开启）
+    verify_functions: bool,
+    // 🆕 --verify：另外检查后端自己的不变量（见 checkInvariants）
+    check_invariants: bool,
+    // 🆕 全局常量（值已在编译期求值为字面量，使用处直接内联）
+    constants: std.StringHashMap(ast.Expr),
+    // 🆕 类型导向的代码生成：变量 / 函数的 Paw 类型（不再从 LLVM 值的类型猜测）
+    variable_paw_types: std.StringHashMap(ast.Type),
+    function_decls: std.StringHashMap(ast.FunctionDecl),
+    current_return_type: ?ast.Type,
+    // 🆕 最近一次 error.LoweringFailed 的说明（记录到 codegen_errors）
+    lowering_error: ?[]const u8,
+    // 🆕 取消令牌：每个函数之间检查
+    cancel_token: ?*const cancel.CancelToken,
+    // 🆕 Paw.toml [limits]：泛型实例数和单个函数的生成时间（与 C 后端相同）
+    limits: Limits,
+    function_deadline_ns: ?i128,
+    function_timed_out: bool,
+    // 🆕 泛型函数的单态化实例（与 C 后端使用同一套收集和名称修饰）
+    generic_context: generics.GenericContext,
+    // 🆕 正在生成的单态化实例的类型参数绑定
+    current_bindings: layout.Bindings,
+    // 🆕 字符串插值的结果缓冲区（与 C 后端一样是静态缓冲区）
+    interp_buffer: ?llvm.ValueRef,
+    // 🆕 浮点数格式化的运行时函数（第一次插值浮点数时生成）
+    format_f64_func: ?llvm.ValueRef,
+    // 🆕 gen_int / gen_string 的运行时函数（第一次调用时生成）
+    gen_int_func: ?llvm.ValueRef,
+    gen_string_func: ?llvm.ValueRef,
+    // 🆕 paw dev：热重载模式（null 为普通编译）
+    hot_reload: ?HotReload,
+    // 🆕 生成过程中构造的 Paw 类型（函数值的类型），deinit 时统一释放
+    type_arena: std.heap.ArenaAllocator,
+    
     /// 初始化 LLVM 后端
     /// 创建 LLVM 上下文、模块和构建器
     /// 🆕 v0.1.7: 添加优化级别参数
     pub fn init(allocator: std.mem.Allocator, module_name: []const u8, opt_level: OptLevel) !LLVMNativeBackend {
-        const context = llvm.Context.create();
-        
+        var backend = try initInContext(allocator, llvm.Context.create(), module_name, opt_level);
+        backend.owns_context = true;
+        return backend;
+    }
+    
+    /// 🆕 在已有的上下文中创建模块（paw dev：上下文属于 JIT）
+    pub fn initInContext(allocator: std.mem.Allocator, context: llvm.Context, module_name: []const u8, opt_level: OptLevel) !LLVMNativeBackend {
         const module_name_z = try allocator.dupeZ(u8, module_name);
         defer allocator.free(module_name_z);
         
@@ -51,15 +134,40 @@ pub const LLVMNativeBackend = struct {
         return LLVMNativeBackend{
             .allocator = allocator,
             .context = context,
+            .owns_context = false,
             .module = module,
             .builder = builder,
             .functions = std.StringHashMap(llvm.ValueRef).init(allocator),
             .variables = std.StringHashMap(llvm.ValueRef).init(allocator),
             .variable_types = std.StringHashMap(llvm.TypeRef).init(allocator),
+            .type_decls = std.StringHashMap(ast.TypeDecl).init(allocator),
+            .struct_types = std.StringHashMap(llvm.TypeRef).init(allocator),
             .current_function = null,
             .current_loop_exit = null,
             .current_loop_continue = null,
+            .current_loop_value = null,
+            .labeled_loops = std.ArrayList(LabeledLoop){},
             .opt_level = opt_level,  // 🆕 v0.1.7: 保存优化级别
+            .codegen_errors = diagnostic.CodegenErrors.init(allocator),
+            .verify_functions = opt_level == .O0,
+            .check_invariants = false,
+            .constants = std.StringHashMap(ast.Expr).init(allocator),
+            .variable_paw_types = std.StringHashMap(ast.Type).init(allocator),
+            .function_decls = std.StringHashMap(ast.FunctionDecl).init(allocator),
+            .current_return_type = null,
+            .lowering_error = null,
+            .cancel_token = null,
+            .limits = .{},
+            .function_deadline_ns = null,
+            .function_timed_out = false,
+            .generic_context = generics.GenericContext.init(allocator),
+            .current_bindings = layout.Bindings.empty,
+            .interp_buffer = null,
+            .format_f64_func = null,
+            .gen_int_func = null,
+            .gen_string_func = null,
+            .hot_reload = null,
+            .type_arena = std.heap.ArenaAllocator.init(allocator),
         };
     }
     
@@ -68,9 +176,26 @@ pub const LLVMNativeBackend = struct {
         self.functions.deinit();
         self.variables.deinit();
         self.variable_types.deinit();
+        self.type_decls.deinit();
+        self.struct_types.deinit();
+        self.constants.deinit();
+        self.variable_paw_types.deinit();
+        self.function_decls.deinit();
+        self.generic_context.deinit();
+        if (self.lowering_error) |message| self.allocator.free(message);
+        self.labeled_loops.deinit(self.allocator);
+        self.codegen_errors.deinit();
+        self.type_arena.deinit();
         self.builder.dispose();
-        self.module.dispose();
-        self.context.dispose();
+        if (self.module.ref != null) self.module.dispose();
+        if (self.owns_context) self.context.dispose();
+    }
+    
+    /// 🆕 交出生成的模块（paw dev：交给 JIT），之后 deinit 不再释放它
+    pub fn releaseModule(self: *LLVMNativeBackend) llvm.Module {
+        const module = self.module;
+        self.module.ref = null;
+        return module;
     }
     
     // ============================================================================
@@ -100,29 +225,93 @@ pub const LLVMNativeBackend = struct {
     const LoopContext = struct {
         exit: ?llvm.BasicBlockRef,
         continue_block: ?llvm.BasicBlockRef,
+        value_slot: ?llvm.ValueRef,
     };
     
     fn saveLoopContext(self: *LLVMNativeBackend) LoopContext {
         return LoopContext{
             .exit = self.current_loop_exit,
             .continue_block = self.current_loop_continue,
+            .value_slot = self.current_loop_value,
         };
     }
     
     fn restoreLoopContext(self: *LLVMNativeBackend, ctx: LoopContext) void {
         self.current_loop_exit = ctx.exit;
         self.current_loop_continue = ctx.continue_block;
+        self.current_loop_value = ctx.value_slot;
+    }
+    
+    /// 🆕 带标签的循环
+    const LabeledLoop = struct {
+        name: []const u8,
+        exit: llvm.BasicBlockRef,
+        continue_block: llvm.BasicBlockRef,
+        value_slot: ?llvm.ValueRef,
+    };
+    
+    /// 🆕 进入循环：带标签时记录跳转目标（调用者负责 popLoopLabel）
+    fn pushLoopLabel(self: *LLVMNativeBackend, label: ?[]const u8) !void {
+        const name = label orelse return;
+        try self.labeled_loops.append(self.allocator, .{
+            .name = name,
+            .exit = self.current_loop_exit.?,
+            .continue_block = self.current_loop_continue.?,
+            .value_slot = self.current_loop_value,
+        });
+    }
+    
+    fn popLoopLabel(self: *LLVMNativeBackend, label: ?[]const u8) void {
+        if (label != null) _ = self.labeled_loops.pop();
+    }
+    
+    fn findLabeledLoop(self: *LLVMNativeBackend, name: []const u8) ?LabeledLoop {
+        var i = self.labeled_loops.items.len;
+        while (i > 0) {
+            i -= 1;
+            if (std.mem.eql(u8, self.labeled_loops.items[i].name, name)) return self.labeled_loops.items[i];
+        }
+        return null;
+    }
+    
+    /// 🆕 跳转到 target，之后的代码放入新的（不可达）基本块，避免一个块中出现多个终结指令
+    fn buildJump(self: *LLVMNativeBackend, target: llvm.BasicBlockRef, after_name: [:0]const u8) !void {
+        const func = self.current_function orelse return error.NoCurrentFunction;
+        _ = self.builder.buildBr(target);
+        const after_block = llvm.appendBasicBlock(self.context, func, after_name);
+        self.builder.positionAtEnd(after_block);
+    }
+    
+    /// 🆕 当前块还没有终结指令时才跳转（循环体以 break/return 结尾的情况）
+    fn branchIfOpen(self: *LLVMNativeBackend, target: llvm.BasicBlockRef) void {
+        if (!llvm.Builder.blockHasTerminator(self.builder.getInsertBlock())) {
+            _ = self.builder.buildBr(target);
+        }
     }
     
     // ============================================================================
     // 代码生成主函数
     // ============================================================================
     
+    /// 🆕 无法按类型生成代码：记录原因并返回 error.LoweringFailed，
+    /// 而不是静默生成 0 常量掩盖问题
+    fn loweringError(self: *LLVMNativeBackend, comptime fmt: []const u8, args: anytype) error{ LoweringFailed, OutOfMemory } {
+        if (self.lowering_error) |old| self.allocator.free(old);
+        self.lowering_error = null;
+        self.lowering_error = try std.fmt.allocPrint(self.allocator, fmt, args);
+        return error.LoweringFailed;
+    }
+    
+    /// 🆕 当前函数的生成超过 function_codegen_ms 时失败（由 generateCheckedFunction 报告为超时）
+    fn checkFunctionClock(self: *LLVMNativeBackend) error{LoweringFailed}!void {
+        const deadline = self.function_deadline_ns orelse return;
+        if (std.time.nanoTimestamp() <= deadline) return;
+        self.function_timed_out = true;
+        return error.LoweringFailed;
+    }
+    
     pub fn generate(self: *LLVMNativeBackend, program: ast.Program) ![]const u8 {
-        // Generate all declarations
-        for (program.declarations) |decl| {
-            try self.generateDecl(decl);
-        }
+        try self.generateModule(program);
         
         // Verify module (disabled for now due to linking complexity)
         // self.module.verify() catch |err| {
@@ -137,41 +326,376 @@ pub const LLVMNativeBackend = struct {
         return try self.allocator.dupe(u8, ir);
     }
     
-    fn generateDecl(self: *LLVMNativeBackend, decl: ast.TopLevelDecl) !void {
-        switch (decl) {
-            .function => |func| try self.generateFunction(func),
-            else => {
-                // TODO: Handle other declaration types
-            },
+    /// 🆕 生成模块但不输出 IR 文本（paw dev 直接把模块交给 JIT）
+    pub fn generateModule(self: *LLVMNativeBackend, source_program: ast.Program) !void {
+        // 🆕 闭包转换：lambda 提升为顶层函数（见 closure.zig）
+        const program = (try closure.convert(self.type_arena.allocator(), source_program)).program;
+        
+        // 🆕 收集类型声明和函数声明（签名中的结构体、调用的形参类型）
+        for (program.declarations) |decl| {
+            if (decl == .type_decl) try self.type_decls.put(decl.type_decl.name, decl.type_decl);
+            if (decl == .function) try self.function_decls.put(decl.function.name, decl.function);
         }
+        
+        // 🆕 收集泛型函数的实例
+        self.generic_context.function_table = &self.function_decls;
+        self.generic_context.monomorphizer.max_instances = self.limits.generic_instances;
+        try self.generic_context.collectGenericCalls(program);
+        
+        // 🆕 先声明全部函数再生成函数体
+        try self.lower(program);
     }
     
-    fn generateFunction(self: *LLVMNativeBackend, func: ast.FunctionDecl) !void {
-        // Get return type
+    // ============================================================================
+    // 🆕 降级顺序：数据 -> 声明全部函数 -> 定义全部函数 -> 收尾
+    // ============================================================================
+    
+    pub const LowerError = std.mem.Allocator.Error || cancel.Error || error{CodegenFailed};
+    
+    /// 所有函数先声明、再定义，调用源码中位置更靠后的函数不依赖生成顺序。
+    /// 声明或定义失败的函数先记录下来，在 finish 中统一报告（返回 error.CodegenFailed）。
+    /// 输出 .ll 的编译路径与 pawc dev 的 JIT 模块共用
+    fn lower(self: *LLVMNativeBackend, program: ast.Program) LowerError!void {
+        for (program.declarations) |decl| {
+            if (decl == .const_decl) try self.defineData(decl.const_decl);
+        }
+        for (program.declarations) |decl| {
+            if (decl == .function) try self.declareFunction(decl.function);
+        }
+        for (program.declarations) |decl| {
+            if (decl != .function) continue;
+            try cancel.checkOptional(self.cancel_token);  // 每个函数之前检查取消
+            try self.defineFunction(decl.function);
+        }
+        try self.finish();
+    }
+    
+    /// 全局常量：值已在编译期求值为字面量，使用处直接内联
+    fn defineData(self: *LLVMNativeBackend, decl: ast.ConstDecl) std.mem.Allocator.Error!void {
+        try self.constants.put(decl.name, decl.value.*);
+    }
+    
+    /// 声明函数（泛型函数声明已收集的每个实例）
+    /// 声明失败的函数在生成函数体时会再次失败，由 defineFunction 记录错误
+    fn declareFunction(self: *LLVMNativeBackend, func: ast.FunctionDecl) std.mem.Allocator.Error!void {
+        if (!self.isInModule(func)) return;
+        if (func.type_params.len == 0) {
+            if (self.declareLLVMFunction(func)) |_| {} else |err| {
+                if (err == error.OutOfMemory) return error.OutOfMemory;
+            }
+            return;
+        }
+        for (self.generic_context.monomorphizer.instances.items) |instance| {
+            if (!std.mem.eql(u8, instance.generic_name, func.name)) continue;
+            var instance_func = func;
+            instance_func.name = instance.mangled_name;
+            self.current_bindings = .{ .type_params = func.type_params, .type_args = instance.type_args };
+            defer self.current_bindings = layout.Bindings.empty;
+            if (self.declareLLVMFunction(instance_func)) |_| {} else |err| {
+                if (err == error.OutOfMemory) return error.OutOfMemory;
+            }
+        }
+    }
+    
+    fn defineFunction(self: *LLVMNativeBackend, func: ast.FunctionDecl) std.mem.Allocator.Error!void {
+        if (!self.isInModule(func)) return;
+        if (func.type_params.len == 0) return self.generateCheckedFunction(func);
+        
+        // 🆕 泛型函数：为每个实例生成一份，类型参数按实例绑定
+        // （生成过程中可能记录新的实例，按下标遍历；新实例在生成时才声明）
+        var i: usize = 0;
+        while (i < self.generic_context.monomorphizer.instances.items.len) : (i += 1) {
+            const instance = self.generic_context.monomorphizer.instances.items[i];
+            if (!std.mem.eql(u8, instance.generic_name, func.name)) continue;
+            var instance_func = func;
+            instance_func.name = instance.mangled_name;
+            self.current_bindings = .{ .type_params = func.type_params, .type_args = instance.type_args };
+            defer self.current_bindings = layout.Bindings.empty;
+            try self.generateCheckedFunction(instance_func);
+        }
+    }
+    
+    fn finish(self: *LLVMNativeBackend) LowerError!void {
+        // 🆕 --verify：所有有函数体的 Paw 函数都已定义
+        if (self.check_invariants and self.hot_reload == null and self.codegen_errors.count() == 0) {
+            try self.checkDefinedFunctions();
+        }
+        
+        // 🆕 C ABI 入口（热重载时只在初始模块中）
+        const is_reload = if (self.hot_reload) |hot| hot.changed != null else false;
+        if (!is_reload) {
+            self.generateMainWrapper() catch |err| switch (err) {
+                error.OutOfMemory => return error.OutOfMemory,
+                error.LoweringFailed => try self.codegen_errors.recordLoweringError("main", null, self.lowering_error orelse "unknown reason"),
+            };
+        }
+        
+        // 🆕 报告所有生成失败的函数
+        if (self.codegen_errors.count() > 0) {
+            self.codegen_errors.report();
+            return error.CodegenFailed;
+        }
+    }
+    
+    /// 🆕 热重载：重新编译的模块只包含改动的函数（其余可替换的函数通过槽位调用）
+    fn isInModule(self: *LLVMNativeBackend, func: ast.FunctionDecl) bool {
+        const hot = self.hot_reload orelse return true;
+        const changed = hot.changed orelse return true;
+        return !HotReload.isSwappable(func) or changed.contains(func.name);
+    }
+    
+    /// 生成函数；失败时记录错误并继续生成其余函数
+    fn generateCheckedFunction(self: *LLVMNativeBackend, func: ast.FunctionDecl) std.mem.Allocator.Error!void {
+        // 🆕 function_codegen_ms：此后每条语句检查是否超时
+        self.function_deadline_ns = std.time.nanoTimestamp() + @as(i128, self.limits.function_codegen_ms) * std.time.ns_per_ms;
+        self.function_timed_out = false;
+        defer self.function_deadline_ns = null;
+        self.generateFunction(func) catch |err| {
+            // 🆕 记录错误，丢弃生成了一半的函数体，继续生成其余函数
+            if (err == error.OutOfMemory) return error.OutOfMemory;
+            try self.discardFunction(func.name);
+            if (self.function_timed_out) {
+                try self.codegen_errors.recordTimeLimit(func.name, spanOf(func), self.limits.function_codegen_ms);
+            } else if (err == error.LoweringFailed) {
+                try self.codegen_errors.recordLoweringError(func.name, spanOf(func), self.lowering_error orelse "unknown reason");
+            } else {
+                try self.codegen_errors.record(func.name, spanOf(func), err);
+            }
+            return;
+        };
+        if (self.verify_functions) try self.verifyFunction(func);
+        if (self.check_invariants) try self.checkInvariants(func);
+    }
+    
+    /// 🆕 丢弃（生成失败的）函数体，避免损坏的 IR 留在模块中
+    /// 其他函数可能已经调用了它：换成同名同类型的空声明，而不是直接删除
+    fn discardFunction(self: *LLVMNativeBackend, name: []const u8) std.mem.Allocator.Error!void {
+        self.current_function = null;
+        const broken = self.functions.get(name) orelse return;
+        
+        var len: usize = 0;
+        const symbol = try self.allocator.dupe(u8, llvm.LLVMGetValueName2(broken, &len)[0..len]);
+        defer self.allocator.free(symbol);
+        const declaration = llvm.LLVMAddFunction(self.module.ref, "", llvm.LLVMGlobalGetValueType(broken));
+        llvm.LLVMReplaceAllUsesWith(broken, declaration);
+        llvm.LLVMDeleteFunction(broken);
+        llvm.LLVMSetValueName2(declaration, symbol.ptr, symbol.len);
+        try self.functions.put(name, declaration);
+    }
+    
+    /// 🆕 用 LLVM verifier 检查刚生成的函数
+    /// 失败时把 verifier 的输出转换为指向 Paw 函数的诊断，而不是让 LLVM 在之后崩溃
+    fn verifyFunction(self: *LLVMNativeBackend, func: ast.FunctionDecl) !void {
+        const llvm_func = self.functions.get(func.name) orelse return;
+        if (llvm.LLVMVerifyFunction(llvm_func, llvm.VerifierFailureAction.ReturnStatus) == 0) return;
+        
+        // 之前的函数都已通过验证，所以模块级的错误信息就是这个函数的
+        const message = try self.module.verifierMessage(self.allocator);
+        defer if (message) |msg| self.allocator.free(msg);
+        
+        try self.discardFunction(func.name);
+        try self.codegen_errors.recordVerifierError(func.name, spanOf(func), message);
+    }
+    
+    /// 🆕 在当前函数的入口块分配局部变量（循环体中的 let 不会每次迭代都增长栈）
+    /// 构建器总是位于当前块的末尾，分配之后回到那里
+    fn buildEntryAlloca(self: *LLVMNativeBackend, ty: llvm.TypeRef, name: [:0]const u8) error{NoCurrentFunction}!llvm.ValueRef {
+        const func = self.current_function orelse return error.NoCurrentFunction;
+        const current_block = self.builder.getInsertBlock();
+        const entry = llvm.LLVMGetEntryBasicBlock(func);
+        const first = llvm.LLVMGetFirstInstruction(entry);
+        if (first != null) self.builder.positionBefore(first) else self.builder.positionAtEnd(entry);
+        const alloca = self.builder.buildAlloca(ty, name);
+        self.builder.positionAtEnd(current_block);
+        return alloca;
+    }
+    
+    /// 🆕 --verify：检查刚生成的函数是否满足后端的不变量
+    ///   - 每个基本块以且仅以一条终结指令结束（生成 if / loop / match 的辅助函数最容易漏掉）
+    ///   - alloca 只出现在入口块（否则循环中每次迭代都会增长栈）
+    ///   - 入口块中不会在变量被写入（或取地址）之前读取它
+    /// LLVM verifier 只说明哪条指令有问题，这里的检查指出是哪个块、哪个变量
+    fn checkInvariants(self: *LLVMNativeBackend, func: ast.FunctionDecl) !void {
+        const llvm_func = self.functions.get(func.name) orelse return;
+        if (llvm.LLVMCountBasicBlocks(llvm_func) == 0) return;
+        
+        var arena = std.heap.ArenaAllocator.init(self.allocator);
+        defer arena.deinit();
+        const a = arena.allocator();
+        var violations = std.ArrayList([]const u8){};
+        
+        const entry = llvm.LLVMGetEntryBasicBlock(llvm_func);
+        var block = llvm.LLVMGetFirstBasicBlock(llvm_func);
+        while (block != null) : (block = llvm.LLVMGetNextBasicBlock(block)) {
+            const block_name = std.mem.span(llvm.LLVMGetBasicBlockName(block));
+            const last = llvm.LLVMGetLastInstruction(block);
+            if (last == null or llvm.LLVMIsATerminatorInst(last) == null) {
+                try violations.append(a, try std.fmt.allocPrint(a, "block '{s}' does not end with a terminator", .{block_name}));
+            }
+            var inst = llvm.LLVMGetFirstInstruction(block);
+            while (inst != null) : (inst = llvm.LLVMGetNextInstruction(inst)) {
+                if (inst != last and llvm.LLVMIsATerminatorInst(inst) != null) {
+                    try violations.append(a, try std.fmt.allocPrint(a, "block '{s}' has instructions after its terminator", .{block_name}));
+                    break;
+                }
+                if (block != entry and llvm.LLVMIsAAllocaInst(inst) != null) {
+                    try violations.append(a, try std.fmt.allocPrint(a, "variable '{s}' is allocated in block '{s}' instead of the entry block", .{ valueName(inst), block_name }));
+                }
+            }
+        }
+        
+        // 入口块支配所有其他块：在这里先读后写一定是使用了未定义的变量
+        var touched = std.AutoHashMap(llvm.ValueRef, void).init(a);
+        var inst = llvm.LLVMGetFirstInstruction(entry);
+        while (inst != null) : (inst = llvm.LLVMGetNextInstruction(inst)) {
+            if (llvm.LLVMIsALoadInst(inst) != null) {
+                const ptr = llvm.LLVMGetOperand(inst, 0);
+                if (llvm.LLVMIsAAllocaInst(ptr) != null and !touched.contains(ptr)) {
+                    try violations.append(a, try std.fmt.allocPrint(a, "variable '{s}' is read before it is defined", .{valueName(ptr)}));
+                    try touched.put(ptr, {});
+                }
+                continue;
+            }
+            var i: c_uint = 0;
+            while (i < @as(c_uint, @intCast(llvm.LLVMGetNumOperands(inst)))) : (i += 1) {
+                const operand = llvm.LLVMGetOperand(inst, i);
+                if (operand != null and llvm.LLVMIsAAllocaInst(operand) != null) try touched.put(operand, {});
+            }
+        }
+        
+        if (violations.items.len == 0) return;
+        try self.discardFunction(func.name);
+        try self.codegen_errors.recordInvariantErrors(func.name, spanOf(func), violations.items);
+    }
+    
+    /// 🆕 --verify：每个有函数体的（非泛型）Paw 函数在模块中都有定义
+    fn checkDefinedFunctions(self: *LLVMNativeBackend) !void {
+        var it = self.function_decls.valueIterator();
+        while (it.next()) |decl| {
+            const func = decl.*;
+            if (func.type_params.len > 0 or func.is_extern) continue;
+            const defined = if (self.functions.get(func.name)) |llvm_func| llvm.LLVMCountBasicBlocks(llvm_func) > 0 else false;
+            if (defined) continue;
+            const violation = [_][]const u8{"the function is declared but no body was generated"};
+            try self.codegen_errors.recordInvariantErrors(func.name, spanOf(func), &violation);
+        }
+    }
+    
+    fn valueName(value: llvm.ValueRef) []const u8 {
+        var len: usize = 0;
+        const name = llvm.LLVMGetValueName2(value, &len);
+        return if (len == 0) "<unnamed>" else name[0..len];
+    }
+    
+    fn spanOf(func: ast.FunctionDecl) ?diagnostic.Span {
+        const loc = func.location orelse return null;
+        return diagnostic.Span.fromPosition(loc.file, loc.line, loc.column);
+    }
+    
+    /// 🆕 函数的 LLVM 类型（单态化实例按当前绑定替换类型参数）
+    fn functionTypeOf(self: *LLVMNativeBackend, func: ast.FunctionDecl) !llvm.TypeRef {
         const return_type = try self.toLLVMType(func.return_type);
         
-        // Get parameter types
         var param_types = std.ArrayList(llvm.TypeRef){};
         defer param_types.deinit(self.allocator);
-        
+        // 🆕 有捕获的 lambda：第一个参数是环境指针
+        if (func.hasEnv()) try param_types.append(self.allocator, self.context.pointerType(0));
         for (func.params) |param| {
-            const param_type = try self.toLLVMType(param.type);
-            try param_types.append(self.allocator, param_type);
+            try param_types.append(self.allocator, try self.toLLVMType(param.type));
         }
         
-        // Create function type
-        const func_type = llvm.functionType(return_type, param_types.items, false);
+        return llvm.functionType(return_type, param_types.items, false);
+    }
+    
+    /// 🆕 热重载：函数 name 的槽位（本模块中没有时声明为外部全局变量）
+    fn hotSlot(self: *LLVMNativeBackend, name: []const u8) !llvm.ValueRef {
+        const slot_name = try HotReload.slotName(self.allocator, name);
+        defer self.allocator.free(slot_name);
+        return self.module.getOrAddGlobal(slot_name, self.context.pointerType(0));
+    }
+    
+    /// 🆕 热重载：通过槽位间接调用的目标（不是热重载模式或函数不可替换时返回 null）
+    fn hotCallee(self: *LLVMNativeBackend, decl: ?ast.FunctionDecl) !?Callee {
+        if (self.hot_reload == null) return null;
+        const func = decl orelse return null;
+        if (!HotReload.isSwappable(func)) return null;
+        
+        // acquire 与 dev 服务器写入槽位的 release 配对：读到新地址时新代码已经可见
+        const target = self.builder.buildLoad(self.context.pointerType(0), try self.hotSlot(func.name), "target");
+        llvm.LLVMSetOrdering(target, .Acquire);
+        return Callee{ .value = target, .type = try self.functionTypeOf(func) };
+    }
+    
+    const Callee = struct {
+        value: llvm.ValueRef,
+        type: llvm.TypeRef,
+    };
+    
+    /// 🆕 在模块中声明函数（已声明时返回已有的声明）
+    fn declareLLVMFunction(self: *LLVMNativeBackend, func: ast.FunctionDecl) !llvm.ValueRef {
+        if (self.functions.get(func.name)) |existing| return existing;
+        
+        // 🆕 单态化实例：返回类型和参数类型中的类型参数按实例绑定替换
+        const func_type = try self.functionTypeOf(func);
         
         // Create null-terminated function name
-        const func_name_z = try self.allocator.dupeZ(u8, func.name);
+        // 🆕 @link_name 是原始符号名：加 \x01 前缀，LLVM 不再添加平台前缀（如 macOS 的 _）
+        const link_symbol: ?[]const u8 = if (func.link_name) |ln| ln.resolve(@tagName(builtin.os.tag)) else null;
+        const func_name_z = if (link_symbol) |symbol|
+            try std.fmt.allocPrintSentinel(self.allocator, "\x01{s}", .{symbol}, 0)
+        else if (self.hot_reload != null and HotReload.isSwappable(func))
+            try HotReload.bodyName(self.allocator, func.name, self.hot_reload.?.version)
+        else if (func.isEntryPoint())
+            try self.allocator.dupeZ(u8, ast.ENTRY_SYMBOL)
+        else
+            try self.allocator.dupeZ(u8, func.name);
         defer self.allocator.free(func_name_z);
         
         // Add function to module
         const llvm_func = self.module.addFunction(func_name_z, func_type);
         try self.functions.put(func.name, llvm_func);
+        self.addExtensionAttributes(llvm_func, func, llvm.LLVMAddAttributeAtIndex);
+        // 🆕 提升的 lambda 只在本模块中通过函数值使用
+        if (func.isLambda()) llvm.LLVMSetLinkage(llvm_func, .Internal);
+        if (self.hot_reload) |hot| {
+            if (HotReload.isSwappable(func)) {
+                // 新函数的槽位在本模块中定义，指向这个函数体；已有的槽位由 dev 服务器更新
+                if (!hot.defined_slots.contains(func.name)) {
+                    llvm.LLVMSetInitializer(try self.hotSlot(func.name), llvm_func);
+                }
+            } else if (func.type_params.len > 0) {
+                // 泛型实例在每个模块中各生成一份，模块之间不能有同名的外部符号
+                llvm.LLVMSetLinkage(llvm_func, .Internal);
+            }
+        }
+        return llvm_func;
+    }
+    
+    fn generateFunction(self: *LLVMNativeBackend, func: ast.FunctionDecl) !void {
+        const paw_return_type = layout.resolve(func.return_type, self.current_bindings);
+        const llvm_func = try self.declareLLVMFunction(func);
+        
+        // 🆕 extern fn：只有声明
+        if (func.is_extern) return;
+        
+        // 🆕 @asm 函数：naked 函数，函数体是一条有副作用的内联汇编（汇编自己返回）
+        if (func.asmFor(@tagName(builtin.cpu.arch))) |text| {
+            llvm.LLVMAddAttributeAtIndex(llvm_func, llvm.AttributeFunctionIndex, self.context.enumAttribute("naked"));
+            llvm.LLVMAddAttributeAtIndex(llvm_func, llvm.AttributeFunctionIndex, self.context.enumAttribute("noinline"));
+            self.builder.positionAtEnd(llvm.appendBasicBlock(self.context, llvm_func, "entry"));
+            var no_params = [_]llvm.TypeRef{};
+            const asm_type = llvm.functionType(self.context.voidType(), &no_params, false);
+            const asm_value = llvm.LLVMGetInlineAsm(asm_type, text.ptr, text.len, "", 0, 1, 0, .ATT, 0);
+            var no_args = [_]llvm.ValueRef{};
+            _ = self.builder.buildCall(asm_type, asm_value, &no_args, "");
+            _ = self.builder.buildUnreachable();
+            return;
+        }
         
         // Set current function context
         self.current_function = llvm_func;
+        self.current_return_type = paw_return_type;
+        defer self.current_return_type = null;
         
         // Create entry basic block
         const entry_block = llvm.appendBasicBlock(self.context, llvm_func, "entry");
@@ -180,8 +704,10 @@ pub const LLVMNativeBackend = struct {
         // Store parameters in variables map
         self.variables.clearRetainingCapacity();
         self.variable_types.clearRetainingCapacity();
+        self.variable_paw_types.clearRetainingCapacity();
+        const first_param: usize = if (func.hasEnv()) 1 else 0;
         for (func.params, 0..) |param, i| {
-            const param_value = llvm.LLVMGetParam(llvm_func, @intCast(i));
+            const param_value = llvm.LLVMGetParam(llvm_func, @intCast(first_param + i));
             const param_type = try self.toLLVMType(param.type);
             
             // Allocate space for parameter and store it
@@ -192,32 +718,248 @@ pub const LLVMNativeBackend = struct {
             
             try self.variables.put(param.name, alloca);
             try self.variable_types.put(param.name, param_type);
+            try self.variable_paw_types.put(param.name, layout.resolve(param.type, self.current_bindings));
         }
         
+        // 🆕 捕获的变量：从环境复制到局部变量（捕获是按值的，lambda 中不能赋值）
+        if (func.hasEnv()) {
+            const captures = func.captures.?;
+            const env_type = try self.envType(captures);
+            const env = llvm.LLVMGetParam(llvm_func, 0);
+            for (captures, 0..) |capture, i| {
+                const capture_type = try self.toLLVMType(capture.type);
+                const field = self.builder.buildStructGEP(env_type, env, @intCast(i), "env.field");
+                const value = self.builder.buildLoad(capture_type, field, "capture");
+                const name_z = try self.allocator.dupeZ(u8, capture.name);
+                defer self.allocator.free(name_z);
+                const alloca = self.builder.buildAlloca(capture_type, name_z);
+                _ = self.builder.buildStore(value, alloca);
+                
+                try self.variables.put(capture.name, alloca);
+                try self.variable_types.put(capture.name, capture_type);
+                try self.variable_paw_types.put(capture.name, capture.type);
+            }
+        }
+        
+        // 🆕 栈溢出检查（参数的 alloca 留在入口块中）
+        try self.generateStackCheck(llvm_func, func.name);
+        
         // Generate function body
         // 🆕 v0.1.6: 特殊处理最后一个表达式语句 - 应该生成 return
         for (func.body, 0..) |stmt, i| {
             const is_last = (i == func.body.len - 1);
-            const is_non_void = func.return_type != .void;
+            const is_non_void = paw_return_type != .void and paw_return_type != .never;
             
             // 如果是最后一个语句，且是表达式语句，且函数返回非void，生成return
             if (is_last and stmt == .expr and is_non_void) {
-                const ret_value = try self.generateExpr(stmt.expr);
+                const ret_value = try self.generateTyped(stmt.expr, paw_return_type);
                 _ = self.builder.buildRet(ret_value);
             } else {
                 try self.generateStmt(stmt);
             }
         }
         
+        // 🆕 `-> !` 函数的末尾不可达（类型检查已保证）
+        if (paw_return_type == .never) self.terminateUnreachable(self.builder.getInsertBlock());
+        
         // Clear function context
         self.current_function = null;
     }
     
-    fn generateStmt(self: *LLVMNativeBackend, stmt: ast.Stmt) (error{NoCurrentFunction} || std.mem.Allocator.Error)!void {
+    /// 🆕 函数入口的栈溢出检查（C 后端在信号处理函数中检测，见 codegen.generateStackRuntime）：
+    /// 栈位置低于 paw_rt_stack_limit 时与 panic 一样输出 "PANIC:\n<STACK_OVERFLOW_PANIC><name>" 并以 101 退出。
+    /// paw_rt_stack_limit 由 paw_rt_init 设置，之前为 0（不检查）
+    fn generateStackCheck(self: *LLVMNativeBackend, llvm_func: llvm.ValueRef, name: []const u8) !void {
+        const i64_type = self.context.i64Type();
+        const probe = self.builder.buildAlloca(self.context.i8Type(), "stack.probe");
+        const position = self.builder.buildPtrToInt(probe, i64_type, "stack.pos");
+        const limit = self.builder.buildLoad(i64_type, self.stackLimitGlobal(), "stack.limit");
+        const overflow_block = llvm.appendBasicBlock(self.context, llvm_func, "stack.overflow");
+        const body_block = llvm.appendBasicBlock(self.context, llvm_func, "body");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, self.builder.buildICmp(.ULT, position, limit, "stack.exhausted"), overflow_block, body_block);
+        
+        self.builder.positionAtEnd(overflow_block);
+        const overflow_func = self.stackOverflowFunc();
+        const name_z = try self.allocator.dupeZ(u8, name);
+        defer self.allocator.free(name_z);
+        var args = [_]llvm.ValueRef{self.builder.buildGlobalStringPtr(name_z, "fn.name")};
+        _ = self.builder.buildCall(llvm.LLVMGlobalGetValueType(overflow_func), overflow_func, &args, "");
+        _ = self.builder.buildUnreachable();
+        
+        self.builder.positionAtEnd(body_block);
+    }
+    
+    /// 🆕 paw_rt_stack_limit：common 链接，分离编译的各个模块共用一个（由含 main 的模块初始化）
+    fn stackLimitGlobal(self: *LLVMNativeBackend) llvm.ValueRef {
+        if (llvm.LLVMGetNamedGlobal(self.module.ref, "paw_rt_stack_limit")) |global| return global;
+        const global = llvm.LLVMAddGlobal(self.module.ref, self.context.i64Type(), "paw_rt_stack_limit");
+        llvm.LLVMSetInitializer(global, llvm.constI64(self.context, 0));
+        llvm.LLVMSetLinkage(global, .Common);
+        return global;
+    }
+    
+    /// 🆕 paw_rt_stack_overflow(name)：write 输出消息和函数名后 _exit(101)（不经过 stdio，栈上只剩保留的空间）
+    fn stackOverflowFunc(self: *LLVMNativeBackend) llvm.ValueRef {
+        if (llvm.LLVMGetNamedFunction(self.module.ref, "paw_rt_stack_overflow")) |func| return func;
+        const saved_block = self.builder.getInsertBlock();
+        defer self.builder.positionAtEnd(saved_block);
+        
+        const i32_type = self.context.i32Type();
+        const i64_type = self.context.i64Type();
+        const ptr_type = self.context.pointerType(0);
+        var params = [_]llvm.TypeRef{ptr_type};
+        const func = self.module.addFunction("paw_rt_stack_overflow", llvm.functionType(self.context.voidType(), &params, false));
+        llvm.LLVMSetLinkage(func, .Internal);
+        llvm.LLVMAddAttributeAtIndex(func, llvm.AttributeFunctionIndex, self.context.enumAttribute("noreturn"));
+        llvm.LLVMAddAttributeAtIndex(func, llvm.AttributeFunctionIndex, self.context.enumAttribute("cold"));
+        self.builder.positionAtEnd(llvm.appendBasicBlock(self.context, func, "entry"));
+        
+        var strlen_params = [_]llvm.TypeRef{ptr_type};
+        const strlen_type = llvm.functionType(i64_type, &strlen_params, false);
+        const strlen_func = self.module.getOrAddFunction("strlen", strlen_type);
+        var write_params = [_]llvm.TypeRef{ i32_type, ptr_type, i64_type };
+        const write_type = llvm.functionType(i64_type, &write_params, false);
+        const write_func = self.module.getOrAddFunction("write", write_type);
+        var exit_params = [_]llvm.TypeRef{i32_type};
+        const exit_type = llvm.functionType(self.context.voidType(), &exit_params, false);
+        const exit_func = self.module.getOrAddFunction("_exit", exit_type);
+        
+        const name = llvm.LLVMGetParam(func, 0);
+        var strlen_args = [_]llvm.ValueRef{name};
+        const pieces = [_]struct { text: llvm.ValueRef, len: llvm.ValueRef }{
+            .{ .text = self.builder.buildGlobalStringPtr("PANIC:\n" ++ ast.STACK_OVERFLOW_PANIC, "overflow.msg"), .len = llvm.constI64(self.context, ("PANIC:\n" ++ ast.STACK_OVERFLOW_PANIC).len) },
+            .{ .text = name, .len = self.builder.buildCall(strlen_type, strlen_func, &strlen_args, "name.len") },
+            .{ .text = self.builder.buildGlobalStringPtr("\n", "overflow.nl"), .len = llvm.constI64(self.context, 1) },
+        };
+        for (pieces) |piece| {
+            var write_args = [_]llvm.ValueRef{ llvm.constI32(self.context, 2), piece.text, piece.len };
+            _ = self.builder.buildCall(write_type, write_func, &write_args, "");
+        }
+        var exit_args = [_]llvm.ValueRef{llvm.constI32(self.context, 101)};
+        _ = self.builder.buildCall(exit_type, exit_func, &exit_args, "");
+        _ = self.builder.buildUnreachable();
+        return func;
+    }
+    
+    /// 🆕 paw_rt_init 中设置 paw_rt_stack_limit = 栈顶 - (栈大小 - STACK_CHECK_MARGIN)。
+    /// 栈大小为 RLIMIT_STACK 的软限制（无限制时与 C 后端一样按 4 GiB 算），Windows 为默认的 1 MiB
+    fn generateStackLimitInit(self: *LLVMNativeBackend) void {
+        const i32_type = self.context.i32Type();
+        const i64_type = self.context.i64Type();
+        const top = self.builder.buildPtrToInt(self.builder.buildAlloca(self.context.i8Type(), "stack.top"), i64_type, "stack.top.pos");
+        const size = if (builtin.os.tag == .windows) llvm.constI64(self.context, 1 << 20) else blk: {
+            // struct rlimit { rlim_t cur, max; }，RLIMIT_STACK 在 Linux 和 macOS 上都是 3
+            const rlimit_type = llvm.arrayType(i64_type, 2);
+            const rlimit = self.builder.buildAlloca(rlimit_type, "rlimit");
+            var getrlimit_params = [_]llvm.TypeRef{ i32_type, self.context.pointerType(0) };
+            const getrlimit_type = llvm.functionType(i32_type, &getrlimit_params, false);
+            const getrlimit_func = self.module.getOrAddFunction("getrlimit", getrlimit_type);
+            var getrlimit_args = [_]llvm.ValueRef{ llvm.constI32(self.context, 3), rlimit };
+            const status = self.builder.buildCall(getrlimit_type, getrlimit_func, &getrlimit_args, "rlimit.status");
+            const current = self.builder.buildLoad(i64_type, rlimit, "rlimit.cur");
+            const cap = llvm.constI64(self.context, 1 << 32);
+            const capped = self.builder.buildSelect(self.builder.buildICmp(.UGT, current, cap, "rlimit.huge"), cap, current, "rlimit.capped");
+            const ok = self.builder.buildICmp(.EQ, status, llvm.constI32(self.context, 0), "rlimit.ok");
+            break :blk self.builder.buildSelect(ok, capped, llvm.constI64(self.context, 8 << 20), "stack.size");
+        };
+        const margin = llvm.constI64(self.context, STACK_CHECK_MARGIN);
+        const limit = self.builder.buildSub(top, self.builder.buildSub(size, margin, "stack.budget"), "stack.limit");
+        const usable = self.builder.buildICmp(.UGT, size, margin, "stack.usable");
+        _ = self.builder.buildStore(self.builder.buildSelect(usable, limit, llvm.constI64(self.context, 0), "stack.limit"), self.stackLimitGlobal());
+    }
+    
+    /// 🆕 生成 C ABI 的 main 包装函数和运行时钩子（与 C 后端生成的头部一致）
+    ///
+    ///   main(argc, argv): paw_rt_init(argc, argv); code = paw_main(); paw_rt_shutdown(); ret code
+    ///
+    /// paw_rt_shutdown 同时注册到 atexit，由 exit()/panic 结束的程序也执行清理（只执行一次）
+    fn generateMainWrapper(self: *LLVMNativeBackend) !void {
+        const entry = self.function_decls.get("main") orelse return;
+        if (!entry.isEntryPoint()) return;
+        const entry_func = self.functions.get("main") orelse return;  // 生成失败，已报告
+        
+        const i32_type = self.context.i32Type();
+        const i1_type = self.context.i1Type();
+        const ptr_type = self.context.pointerType(0);
+        const void_type = self.context.voidType();
+        
+        const argc_global = self.module.addInternalGlobal("paw_rt_argc", i32_type, llvm.constI32(self.context, 0));
+        const argv_global = self.module.addInternalGlobal("paw_rt_argv", ptr_type, llvm.constNull(self.context, ptr_type));
+        const done_global = self.module.addInternalGlobal("paw_rt_done", i1_type, llvm.LLVMConstInt(i1_type, 0, 0));
+        
+        var ptr_param = [_]llvm.TypeRef{ptr_type};
+        const libc_type = llvm.functionType(i32_type, &ptr_param, false);
+        const fflush_func = self.module.getOrAddFunction("fflush", libc_type);
+        const atexit_func = self.module.getOrAddFunction("atexit", libc_type);
+        
+        // paw_rt_shutdown: if (paw_rt_done) return; paw_rt_done = true; fflush(NULL);
+        var no_params = [_]llvm.TypeRef{};
+        var no_args = [_]llvm.ValueRef{};
+        const hook_type = llvm.functionType(void_type, &no_params, false);
+        const shutdown_func = self.module.addFunction("paw_rt_shutdown", hook_type);
+        llvm.LLVMSetLinkage(shutdown_func, .Internal);
+        {
+            const entry_block = llvm.appendBasicBlock(self.context, shutdown_func, "entry");
+            const run_block = llvm.appendBasicBlock(self.context, shutdown_func, "run");
+            const done_block = llvm.appendBasicBlock(self.context, shutdown_func, "done");
+            self.builder.positionAtEnd(entry_block);
+            const done = self.builder.buildLoad(i1_type, done_global, "done");
+            _ = llvm.LLVMBuildCondBr(self.builder.ref, done, done_block, run_block);
+            
+            self.builder.positionAtEnd(run_block);
+            _ = self.builder.buildStore(llvm.LLVMConstInt(i1_type, 1, 0), done_global);
+            var args = [_]llvm.ValueRef{llvm.constNull(self.context, ptr_type)};
+            _ = self.builder.buildCall(libc_type, fflush_func, &args, "");
+            _ = self.builder.buildBr(done_block);
+            
+            self.builder.positionAtEnd(done_block);
+            _ = self.builder.buildRetVoid();
+        }
+        
+        // paw_rt_init(argc, argv): 保存参数，注册 atexit
+        // （stdout 使用 libc 的默认缓冲策略：终端按行、文件 / 管道按块，与 C 后端的 setvbuf 一致）
+        var init_params = [_]llvm.TypeRef{ i32_type, ptr_type };
+        const init_type = llvm.functionType(void_type, &init_params, false);
+        const init_func = self.module.addFunction("paw_rt_init", init_type);
+        llvm.LLVMSetLinkage(init_func, .Internal);
+        {
+            self.builder.positionAtEnd(llvm.appendBasicBlock(self.context, init_func, "entry"));
+            _ = self.builder.buildStore(llvm.LLVMGetParam(init_func, 0), argc_global);
+            _ = self.builder.buildStore(llvm.LLVMGetParam(init_func, 1), argv_global);
+            var args = [_]llvm.ValueRef{shutdown_func};
+            _ = self.builder.buildCall(libc_type, atexit_func, &args, "");
+            self.generateStackLimitInit();
+            _ = self.builder.buildRetVoid();
+        }
+        
+        // main(argc, argv)
+        const main_func = self.module.addFunction("main", llvm.functionType(i32_type, &init_params, false));
+        {
+            self.builder.positionAtEnd(llvm.appendBasicBlock(self.context, main_func, "entry"));
+            var init_args = [_]llvm.ValueRef{ llvm.LLVMGetParam(main_func, 0), llvm.LLVMGetParam(main_func, 1) };
+            _ = self.builder.buildCall(init_type, init_func, &init_args, "");
+            
+            const entry_type = llvm.LLVMGlobalGetValueType(entry_func);
+            const result = self.builder.buildCall(entry_type, entry_func, &no_args, "");
+            const exit_code = switch (entry.return_type) {
+                .void, .never => llvm.constI32(self.context, 0),
+                else => try self.generateCast(result, entry.return_type, .i32, i32_type),
+            };
+            
+            _ = self.builder.buildCall(hook_type, shutdown_func, &no_args, "");
+            _ = self.builder.buildRet(exit_code);
+        }
+    }
+    
+    fn generateStmt(self: *LLVMNativeBackend, stmt: ast.Stmt) (error{ NoCurrentFunction, LoweringFailed } || std.mem.Allocator.Error)!void {
+        try self.checkFunctionClock();
         switch (stmt) {
             .return_stmt => |maybe_val| {
                 if (maybe_val) |val| {
-                    const ret_value = try self.generateExpr(val);
+                    const ret_value = if (self.current_return_type) |ret_type|
+                        try self.generateTyped(val, ret_type)
+                    else
+                        try self.generateExpr(val);
                     _ = self.builder.buildRet(ret_value);
                 } else {
                     _ = self.builder.buildRetVoid();
@@ -225,29 +967,18 @@ pub const LLVMNativeBackend = struct {
             },
             .let_decl => |let_stmt| {
                 if (let_stmt.init) |init_expr| {
-                    var init_value = try self.generateExpr(init_expr);
-                    
-                    // Determine variable type
-                    const var_type = if (let_stmt.type) |typ|
-                        try self.toLLVMType(typ)
+                    // 🆕 按声明类型生成初始值（字面量直接生成目标类型），否则使用表达式自身的类型
+                    const init_value = if (let_stmt.type) |typ|
+                        try self.generateTyped(init_expr, typ)
                     else
-                        llvm.LLVMTypeOf(init_value);
-                    
-                    // 🆕 v0.2.0: 如果初始值类型与变量类型不匹配，进行转换
-                    const init_type = llvm.LLVMTypeOf(init_value);
-                    if (init_type != var_type) {
-                        // 需要类型转换
-                        const cast_name_z = try self.allocator.dupeZ(u8, "auto_cast");
-                        defer self.allocator.free(cast_name_z);
-                        
-                        // 简化：假设 i8 -> i32 的扩展（最常见情况）
-                        init_value = self.builder.buildZExt(init_value, var_type, cast_name_z);
-                    }
+                        try self.generateExpr(init_expr);
+                    const paw_type = let_stmt.type orelse try self.inferExprType(init_expr);
+                    const var_type = llvm.LLVMTypeOf(init_value);
                     
                     // Allocate space for variable
                     const alloca_name_z = try self.allocator.dupeZ(u8, let_stmt.name);
                     defer self.allocator.free(alloca_name_z);
-                    const alloca = self.builder.buildAlloca(var_type, alloca_name_z);
+                    const alloca = try self.buildEntryAlloca(var_type, alloca_name_z);
                     
                     // Store initial value (已转换到正确类型)
                     _ = self.builder.buildStore(init_value, alloca);
@@ -255,56 +986,59 @@ pub const LLVMNativeBackend = struct {
                     // Store pointer in variables map
                     try self.variables.put(let_stmt.name, alloca);
                     try self.variable_types.put(let_stmt.name, var_type);
+                    try self.variable_paw_types.put(let_stmt.name, paw_type);
                 }
             },
             .assign => |assign_stmt| {
+                // 🆕 `_ = expr;`：只求值，丢弃结果
+                if (assign_stmt.target.isDiscardTarget()) {
+                    _ = try self.generateExpr(assign_stmt.value);
+                    return;
+                }
                 // Handle assignment to existing variable
                 if (assign_stmt.target == .identifier) {
                     const var_name = assign_stmt.target.identifier;
-                    if (self.variables.get(var_name)) |var_ptr| {
-                        const new_value = try self.generateExpr(assign_stmt.value);
-                        _ = self.builder.buildStore(new_value, var_ptr);
-                    } else {
-                        std.debug.print("⚠️  Undefined variable in assignment: {s}\n", .{var_name});
-                    }
+                    const var_ptr = self.variables.get(var_name) orelse
+                        return self.loweringError("internal error: undefined variable '{s}' in assignment", .{var_name});
+                    const var_type = self.variable_paw_types.get(var_name) orelse ast.Type.i32;
+                    const new_value = try self.generateTyped(assign_stmt.value, var_type);
+                    _ = self.builder.buildStore(new_value, var_ptr);
+                } else if (try self.place(assign_stmt.target)) |field| {
+                    // 🆕 结构体字段：写入字段地址
+                    const new_value = try self.generateTyped(assign_stmt.value, field.paw_type);
+                    _ = self.builder.buildStore(new_value, field.ptr);
                 } else {
-                    std.debug.print("⚠️  Complex assignment target not yet supported\n", .{});
+                    return self.loweringError("assignment to '{s}' is not supported by the LLVM backend yet", .{@tagName(assign_stmt.target)});
                 }
             },
             .compound_assign => |compound_stmt| {
                 // Handle compound assignment (+=, -=, etc.)
                 if (compound_stmt.target == .identifier) {
                     const var_name = compound_stmt.target.identifier;
-                    if (self.variables.get(var_name)) |var_ptr| {
-                        if (self.variable_types.get(var_name)) |var_type| {
-                            // Load current value
-                            const load_name_z = try self.allocator.dupeZ(u8, var_name);
-                            defer self.allocator.free(load_name_z);
-                            const current_value = self.builder.buildLoad(var_type, var_ptr, load_name_z);
-                            
-                            // Generate right-hand side value
-                            const rhs_value = try self.generateExpr(compound_stmt.value);
-                            
-                            // Perform operation
-                            const op_name_z = try self.allocator.dupeZ(u8, "compound_op");
-                            defer self.allocator.free(op_name_z);
-                            
-                            const result = switch (compound_stmt.op) {
-                                .add_assign => self.builder.buildAdd(current_value, rhs_value, op_name_z),
-                                .sub_assign => self.builder.buildSub(current_value, rhs_value, op_name_z),
-                                .mul_assign => self.builder.buildMul(current_value, rhs_value, op_name_z),
-                                .div_assign => self.builder.buildSDiv(current_value, rhs_value, op_name_z),
-                                else => current_value,
-                            };
-                            
-                            // Store result back
-                            _ = self.builder.buildStore(result, var_ptr);
-                        }
-                    } else {
-                        std.debug.print("⚠️  Undefined variable in compound assignment: {s}\n", .{var_name});
-                    }
+                    const var_ptr = self.variables.get(var_name) orelse
+                        return self.loweringError("internal error: undefined variable '{s}' in compound assignment", .{var_name});
+                    const var_type = self.variable_types.get(var_name).?;
+                    const paw_type = self.variable_paw_types.get(var_name) orelse ast.Type.i32;
+                    
+                    // Load current value
+                    const load_name_z = try self.allocator.dupeZ(u8, var_name);
+                    defer self.allocator.free(load_name_z);
+                    const current_value = self.builder.buildLoad(var_type, var_ptr, load_name_z);
+                    
+                    // 🆕 右侧按变量类型生成，运算指令由变量类型决定
+                    const rhs_value = try self.generateTyped(compound_stmt.value, paw_type);
+                    const result = try self.buildBinary(compound_stmt.op.binaryOp(), current_value, rhs_value, paw_type);
+                    
+                    // Store result back
+                    _ = self.builder.buildStore(result, var_ptr);
+                } else if (try self.place(compound_stmt.target)) |field| {
+                    // 🆕 结构体字段：读取、运算、写回同一个字段地址
+                    const current_value = self.builder.buildLoad(field.llvm_type, field.ptr, "field");
+                    const rhs_value = try self.generateTyped(compound_stmt.value, field.paw_type);
+                    const result = try self.buildBinary(compound_stmt.op.binaryOp(), current_value, rhs_value, field.paw_type);
+                    _ = self.builder.buildStore(result, field.ptr);
                 } else {
-                    std.debug.print("⚠️  Complex compound assignment target not yet supported\n", .{});
+                    return self.loweringError("compound assignment to '{s}' is not supported by the LLVM backend yet", .{@tagName(compound_stmt.target)});
                 }
             },
             .expr => |expr| {
@@ -322,34 +1056,52 @@ pub const LLVMNativeBackend = struct {
                     try self.generateWhileLoop(.{
                         .condition = cond,
                         .body = loop_stmt.body,
+                        .label = loop_stmt.label,
                     });
                 } else if (loop_stmt.iterator) |iter| {
                     // loop item in collection { } - 迭代循环
-                    try self.generateLoopIterator(iter, loop_stmt.body);
+                    try self.generateLoopIterator(iter, loop_stmt.body, loop_stmt.label);
                 } else {
                     // loop { } - 无限循环
-                    try self.generateInfiniteLoop(loop_stmt.body);
+                    try self.generateInfiniteLoop(loop_stmt.body, loop_stmt.label);
                 }
             },
-            .break_stmt => |_| {
-                if (self.current_loop_exit) |exit_block| {
-                    _ = self.builder.buildBr(exit_block);
+            .break_stmt => |brk| {
+                var target = self.current_loop_exit;
+                var value_slot = self.current_loop_value;
+                if (brk.label) |name| {
+                    const loop = self.findLabeledLoop(name);
+                    target = if (loop) |l| l.exit else null;
+                    value_slot = if (loop) |l| l.value_slot else null;
+                }
+                
+                // 🆕 break value：写入 loop 表达式的结果变量
+                if (brk.value) |value| {
+                    const result = try self.generateExpr(value);
+                    if (value_slot) |slot| _ = self.builder.buildStore(result, slot);
+                }
+                if (target) |exit_block| {
+                    try self.buildJump(exit_block, "after.break");
                 }
             },
-            .continue_stmt => {
-                if (self.current_loop_continue) |continue_block| {
-                    _ = self.builder.buildBr(continue_block);
+            .continue_stmt => |label| {
+                const target = if (label) |name|
+                    if (self.findLabeledLoop(name)) |loop| loop.continue_block else null
+                else
+                    self.current_loop_continue;
+                if (target) |continue_block| {
+                    try self.buildJump(continue_block, "after.continue");
                 }
             },
             else => {
-                // TODO: Handle other statement types
+                return self.loweringError("statement '{s}' is not supported by the LLVM backend yet", .{@tagName(stmt)});
             },
         }
     }
     
     /// 生成 while 风格的条件循环
     /// 生成: while.cond -> while.body -> while.cond (循环) | while.exit
-    fn generateWhileLoop(self: *LLVMNativeBackend, loop: struct { condition: ast.Expr, body: []ast.Stmt }) !void {
+    fn generateWhileLoop(self: *LLVMNativeBackend, loop: struct { condition: ast.Expr, body: []ast.Stmt, label: ?[]const u8 = null }) !void {
         const func = self.current_function orelse return error.NoCurrentFunction;
         
         // 创建基本块
@@ -363,6 +1115,9 @@ pub const LLVMNativeBackend = struct {
         
         self.current_loop_exit = exit_block;
         self.current_loop_continue = cond_block;
+        self.current_loop_value = null;
+        try self.pushLoopLabel(loop.label);
+        defer self.popLoopLabel(loop.label);
         
         // 跳转到条件块
         _ = self.builder.buildBr(cond_block);
@@ -377,7 +1132,7 @@ pub const LLVMNativeBackend = struct {
         for (loop.body) |stmt| {
             try self.generateStmt(stmt);
         }
-        _ = self.builder.buildBr(cond_block);
+        self.branchIfOpen(cond_block);
         
         // 继续从退出块执行
         self.builder.positionAtEnd(exit_block);
@@ -385,13 +1140,12 @@ pub const LLVMNativeBackend = struct {
     
     /// 生成 loop 迭代器（范围迭代）
     /// 生成: loop.cond -> loop.body -> loop.incr -> loop.cond (循环) | loop.exit
-    fn generateLoopIterator(self: *LLVMNativeBackend, iter: ast.LoopIterator, body: []ast.Stmt) !void {
+    fn generateLoopIterator(self: *LLVMNativeBackend, iter: ast.LoopIterator, body: []ast.Stmt, label: ?[]const u8) !void {
         const func = self.current_function orelse return error.NoCurrentFunction;
         
         // 只支持范围表达式
         if (iter.iterable != .range) {
-            std.debug.print("⚠️  Only range iterators are supported in LLVM backend\n", .{});
-            return;
+            return self.loweringError("only range iterators are supported by the LLVM backend", .{});
         }
         
         const range = iter.iterable.range;
@@ -401,20 +1155,22 @@ pub const LLVMNativeBackend = struct {
         const iter_name_z = try self.createCString(iter.binding);
         defer self.allocator.free(iter_name_z);
         
-        const iter_var = self.builder.buildAlloca(i32_type, iter_name_z);
-        const start_value = try self.generateExpr(range.start.*);
+        const iter_var = try self.buildEntryAlloca(i32_type, iter_name_z);
+        const start_value = try self.generateTyped(range.start.*, .i32);
         _ = self.builder.buildStore(start_value, iter_var);
         
         // 注册循环变量（作用域内有效）
         try self.variables.put(iter.binding, iter_var);
         try self.variable_types.put(iter.binding, i32_type);
+        try self.variable_paw_types.put(iter.binding, .i32);
         defer {
             _ = self.variables.remove(iter.binding);
             _ = self.variable_types.remove(iter.binding);
+            _ = self.variable_paw_types.remove(iter.binding);
         }
         
         // 生成结束值
-        const end_value = try self.generateExpr(range.end.*);
+        const end_value = try self.generateTyped(range.end.*, .i32);
         
         // 创建基本块
         const cond_block = llvm.appendBasicBlock(self.context, func, "loop.cond");
@@ -428,6 +1184,9 @@ pub const LLVMNativeBackend = struct {
         
         self.current_loop_exit = exit_block;
         self.current_loop_continue = incr_block;
+        self.current_loop_value = null;
+        try self.pushLoopLabel(label);
+        defer self.popLoopLabel(label);
         
         // 跳转到条件块
         _ = self.builder.buildBr(cond_block);
@@ -444,7 +1203,7 @@ pub const LLVMNativeBackend = struct {
         for (body) |stmt| {
             try self.generateStmt(stmt);
         }
-        _ = self.builder.buildBr(incr_block);
+        self.branchIfOpen(incr_block);
         
         // 生成递增块：i = i + 1
         self.builder.positionAtEnd(incr_block);
@@ -460,7 +1219,7 @@ pub const LLVMNativeBackend = struct {
     
     /// 生成无限循环
     /// 生成: loop.body -> loop.body (无限循环，只能通过 break 退出)
-    fn generateInfiniteLoop(self: *LLVMNativeBackend, body: []ast.Stmt) !void {
+    fn generateInfiniteLoop(self: *LLVMNativeBackend, body: []ast.Stmt, label: ?[]const u8) !void {
         const func = self.current_function orelse return error.NoCurrentFunction;
         
         // 创建基本块
@@ -473,6 +1232,9 @@ pub const LLVMNativeBackend = struct {
         
         self.current_loop_exit = exit_block;
         self.current_loop_continue = body_block;
+        self.current_loop_value = null;
+        try self.pushLoopLabel(label);
+        defer self.popLoopLabel(label);
         
         // 跳转到循环体
         _ = self.builder.buildBr(body_block);
@@ -482,17 +1244,52 @@ pub const LLVMNativeBackend = struct {
         for (body) |stmt| {
             try self.generateStmt(stmt);
         }
-        _ = self.builder.buildBr(body_block);
+        self.branchIfOpen(body_block);
         
         // 退出块（只能通过 break 到达）
         self.builder.positionAtEnd(exit_block);
     }
     
+    /// 🆕 loop 表达式：break value 写入栈上的结果变量，循环结束后读出
+    /// 生成: loopexpr.body -> loopexpr.body (循环) | loopexpr.exit
+    fn generateLoopExpr(self: *LLVMNativeBackend, label: ?[]const u8, body: []ast.Stmt, result_type: ast.Type) !llvm.ValueRef {
+        const func = self.current_function orelse return error.NoCurrentFunction;
+        
+        const value_type: ?llvm.TypeRef = if (result_type != .void) try self.toLLVMType(result_type) else null;
+        const value_slot: ?llvm.ValueRef = if (value_type) |t| try self.buildEntryAlloca(t, "loop.value") else null;
+        
+        const body_block = llvm.appendBasicBlock(self.context, func, "loopexpr.body");
+        const exit_block = llvm.appendBasicBlock(self.context, func, "loopexpr.exit");
+        
+        const saved_ctx = self.saveLoopContext();
+        defer self.restoreLoopContext(saved_ctx);
+        
+        self.current_loop_exit = exit_block;
+        self.current_loop_continue = body_block;
+        self.current_loop_value = value_slot;
+        try self.pushLoopLabel(label);
+        defer self.popLoopLabel(label);
+        
+        _ = self.builder.buildBr(body_block);
+        self.builder.positionAtEnd(body_block);
+        for (body) |stmt| {
+            try self.generateStmt(stmt);
+        }
+        self.branchIfOpen(body_block);
+        
+        self.builder.positionAtEnd(exit_block);
+        if (value_slot) |slot| {
+            return self.builder.buildLoad(value_type.?, slot, "loop.result");
+        }
+        return llvm.constI32(self.context, 0);
+    }
+    
     fn generateExpr(self: *LLVMNativeBackend, expr: ast.Expr) !llvm.ValueRef {
         return switch (expr) {
-            .int_literal => |val| blk: {
+            .loop_expr => |loop| try self.generateLoopExpr(loop.label, loop.body, loop.result_type.*),
+            .int_literal => |literal| blk: {
                 const i32_type = self.context.i32Type();
-                break :blk llvm.LLVMConstInt(i32_type, @intCast(val), 1);
+                break :blk llvm.LLVMConstInt(i32_type, @intCast(literal.value), 1);
             },
             .float_literal => |val| blk: {
                 break :blk llvm.constDouble(self.context, val);
@@ -505,6 +1302,23 @@ pub const LLVMNativeBackend = struct {
                 const i32_type = self.context.i32Type();
                 break :blk llvm.LLVMConstInt(i32_type, @intCast(val), 0);
             },
+            .type_query => |tq| blk: {
+                // 🆕 size_of<T>() / align_of<T>()：与 C 后端相同的布局规则
+                const l = layout.ofType(tq.type, &self.type_decls, layout.Bindings.empty) catch
+                    return self.loweringError("cannot compute the layout of the type in size_of/align_of", .{});
+                const value = if (tq.kind == .size_of) l.size else l.alignment;
+                break :blk llvm.constI32(self.context, @intCast(value));
+            },
+            .byte_literal => |val| blk: {
+                // 🆕 b'A' -> i8
+                break :blk llvm.LLVMConstInt(self.context.i8Type(), val, 0);
+            },
+            .byte_string_literal => |bytes| blk: {
+                // 🆕 b"..." -> 私有常量全局 [N x i8]，不追加 NUL
+                const name_z = try self.allocator.dupeZ(u8, "bytes");
+                defer self.allocator.free(name_z);
+                break :blk self.module.addConstantBytes(name_z, bytes);
+            },
             .string_literal => |str| blk: {
                 // Create null-terminated string
                 const str_z = try self.allocator.dupeZ(u8, str);
@@ -527,53 +1341,45 @@ pub const LLVMNativeBackend = struct {
                         // Fallback: assume it's a direct value (for backward compatibility)
                         break :blk var_ptr;
                     }
+                } else if (self.constants.get(name)) |value| {
+                    // 🆕 全局常量：内联字面量
+                    break :blk try self.generateExpr(value);
+                } else if (self.function_decls.get(name)) |decl| {
+                    // 🆕 函数名作为值：没有环境的函数值（热重载模式下取槽位中的当前版本）
+                    const target = if (try self.hotCallee(decl)) |hot| hot.value else self.functions.get(name) orelse
+                        return self.loweringError("function '{s}' is used as a value but has no generated code", .{name});
+                    break :blk self.buildFunctionValue(llvm.constNull(self.context, self.context.pointerType(0)), target);
                 } else {
-                    std.debug.print("⚠️  Undefined variable: {s}\n", .{name});
-                    break :blk llvm.constI32(self.context, 0);
+                    return self.loweringError("internal error: undefined variable '{s}'", .{name});
                 }
             },
             .binary => |binop| blk: {
-                const lhs = try self.generateExpr(binop.left.*);
-                const rhs = try self.generateExpr(binop.right.*);
-                
-                const result_name_z = try self.allocator.dupeZ(u8, "binop");
-                defer self.allocator.free(result_name_z);
-                
-                const result = switch (binop.op) {
-                    .add => self.builder.buildAdd(lhs, rhs, result_name_z),
-                    .sub => self.builder.buildSub(lhs, rhs, result_name_z),
-                    .mul => self.builder.buildMul(lhs, rhs, result_name_z),
-                    .div => self.builder.buildSDiv(lhs, rhs, result_name_z),
-                    // Comparison operators
-                    .eq => self.builder.buildICmp(.EQ, lhs, rhs, result_name_z),
-                    .ne => self.builder.buildICmp(.NE, lhs, rhs, result_name_z),
-                    .lt => self.builder.buildICmp(.SLT, lhs, rhs, result_name_z),
-                    .le => self.builder.buildICmp(.SLE, lhs, rhs, result_name_z),
-                    .gt => self.builder.buildICmp(.SGT, lhs, rhs, result_name_z),
-                    .ge => self.builder.buildICmp(.SGE, lhs, rhs, result_name_z),
-                    // Logical operators
-                    .and_op => self.builder.buildAnd(lhs, rhs, result_name_z),
-                    .or_op => self.builder.buildOr(lhs, rhs, result_name_z),
-                    else => llvm.constI32(self.context, 0),
-                };
-                break :blk result;
+                if (binop.resolved_operator.*) |type_name| {
+                    return self.loweringError("operator overloading ('{s}.{s}') is not supported by the LLVM backend yet", .{ type_name, binop.op.overloadMethod().? });
+                }
+                if (binop.op == .and_op or binop.op == .or_op) {
+                    break :blk try self.generateShortCircuit(binop.op, binop.left.*, binop.right.*);
+                }
+                // 🆕 由操作数的 Paw 类型决定指令（整数有/无符号、浮点），字面量按另一侧的类型生成
+                const operand_type = try self.operandType(binop.left.*, binop.right.*);
+                const lhs = try self.generateTyped(binop.left.*, operand_type);
+                const rhs = try self.generateTyped(binop.right.*, operand_type);
+                break :blk try self.buildBinary(binop.op, lhs, rhs, operand_type);
             },
             .unary => |unop| blk: {
                 const operand = try self.generateExpr(unop.operand.*);
                 
-                const result_name_z = try self.allocator.dupeZ(u8, "unop");
-                defer self.allocator.free(result_name_z);
-                
                 const result = switch (unop.op) {
-                    .neg => self.builder.buildNeg(operand, result_name_z),
-                    .not => self.builder.buildNot(operand, result_name_z),
+                    .neg => if (self.isFloatType(try self.inferExprType(unop.operand.*)))
+                        self.builder.buildFNeg(operand, "fneg")
+                    else
+                        self.builder.buildNeg(operand, "neg"),
+                    .not => self.builder.buildNot(operand, "not"),
                 };
                 break :blk result;
             },
             .if_expr => |if_expr| blk: {
-                const func = self.current_function orelse {
-                    break :blk llvm.constI32(self.context, 0);
-                };
+                const func = self.current_function orelse return error.NoCurrentFunction;
                 
                 // Generate condition
                 const cond_value = try self.generateExpr(if_expr.condition.*);
@@ -590,6 +1396,8 @@ pub const LLVMNativeBackend = struct {
                 self.builder.positionAtEnd(then_block);
                 const then_value = try self.generateExpr(if_expr.then_branch.*);
                 const then_end_block = self.builder.getInsertBlock();
+                // 🆕 不会产生值的分支（如内层 if 的两个分支都 break）结束于不可达的块
+                if (if_expr.then_branch.diverges()) self.terminateUnreachable(then_end_block);
                 // 🆕 v0.2.0: 只有当块没有终止符时才添加跳转
                 const then_has_terminator = llvm.Builder.blockHasTerminator(then_end_block);
                 if (!then_has_terminator) {
@@ -601,8 +1409,11 @@ pub const LLVMNativeBackend = struct {
                 const else_value = if (if_expr.else_branch) |else_br|
                     try self.generateExpr(else_br.*)
                 else
-                    llvm.constI32(self.context, 0);
+                    null;
                 const else_end_block = self.builder.getInsertBlock();
+                if (if_expr.else_branch) |else_br| {
+                    if (else_br.diverges()) self.terminateUnreachable(else_end_block);
+                }
                 // 🆕 v0.2.0: 只有当块没有终止符时才添加跳转
                 const else_has_terminator = llvm.Builder.blockHasTerminator(else_end_block);
                 if (!else_has_terminator) {
@@ -612,6 +1423,9 @@ pub const LLVMNativeBackend = struct {
                 // Continue block with PHI node
                 self.builder.positionAtEnd(cont_block);
                 
+                // 🆕 没有 else 的 if 没有值（类型检查保证结果不会被使用），不需要 PHI
+                const else_result = else_value orelse break :blk then_value;
+                
                 // 🆕 v0.2.0: 只为实际到达的分支创建 PHI
                 // 如果两个分支都终止了，cont_block 不可达
                 if (then_has_terminator and else_has_terminator) {
@@ -619,8 +1433,13 @@ pub const LLVMNativeBackend = struct {
                     break :blk llvm.constI32(self.context, 0);
                 }
                 
-                // Create PHI node to merge values from both branches
+                // 🆕 两个分支都到达时，值的类型必须一致
                 const result_type = llvm.LLVMTypeOf(then_value);
+                if (!then_has_terminator and !else_has_terminator and llvm.LLVMTypeOf(else_result) != result_type) {
+                    return self.loweringError("internal error: the branches of an if expression were lowered to different types", .{});
+                }
+                
+                // Create PHI node to merge values from both branches
                 const phi_name_z = try self.allocator.dupeZ(u8, "if.result");
                 defer self.allocator.free(phi_name_z);
                 const phi = self.builder.buildPhi(result_type, phi_name_z);
@@ -628,7 +1447,7 @@ pub const LLVMNativeBackend = struct {
                 // 🆕 v0.2.0: 只添加未终止的分支到 PHI
                 if (!then_has_terminator and !else_has_terminator) {
                     // 两个分支都未终止
-                    var incoming_values = [_]llvm.ValueRef{ then_value, else_value };
+                    var incoming_values = [_]llvm.ValueRef{ then_value, else_result };
                     var incoming_blocks = [_]llvm.BasicBlockRef{ then_end_block, else_end_block };
                     llvm.LLVMAddIncoming(phi, &incoming_values, &incoming_blocks, 2);
                 } else if (!then_has_terminator) {
@@ -638,7 +1457,7 @@ pub const LLVMNativeBackend = struct {
                     llvm.LLVMAddIncoming(phi, &incoming_values, &incoming_blocks, 1);
                 } else {
                     // 只有 else 分支未终止
-                    var incoming_values = [_]llvm.ValueRef{else_value};
+                    var incoming_values = [_]llvm.ValueRef{else_result};
                     var incoming_blocks = [_]llvm.BasicBlockRef{else_end_block};
                     llvm.LLVMAddIncoming(phi, &incoming_values, &incoming_blocks, 1);
                 }
@@ -659,13 +1478,17 @@ pub const LLVMNativeBackend = struct {
                         },
                     }
                 }
-                // Return the last expression value, or 0 if none
+                // Return the last expression value, or 0 if none（无值的块，结果不会被使用）
                 break :blk last_value orelse llvm.constI32(self.context, 0);
             },
             .array_index => |index_expr| blk: {
                 // Array/string indexing: arr[index]
                 const array_value = try self.generateExpr(index_expr.array.*);
                 const index_value = try self.generateExpr(index_expr.index.*);
+                // 🆕 长度已知的数组：越界时 panic（与 C 后端的 paw_index_check 一致）
+                if (index_expr.length.*) |length| {
+                    try self.buildIndexCheck(index_value, try self.inferExprType(index_expr.index.*), length);
+                }
                 
                 // 🆕 v0.2.0: 检测是否是字符串索引
                 // 如果 array 是字符串字面量或字符串类型，使用 i8
@@ -693,15 +1516,25 @@ pub const LLVMNativeBackend = struct {
                 break :blk self.builder.buildLoad(element_type, gep, load_name_z);
             },
             .field_access => |field_expr| blk: {
-                // Struct field access: obj.field
-                _ = try self.generateExpr(field_expr.object.*);
-                
-                // For now, return a placeholder
-                // TODO: Need type information to determine field index
-                std.debug.print("⚠️  Field access not yet fully implemented: {s}\n", .{field_expr.field});
-                break :blk llvm.constI32(self.context, 0);
+                // 🆕 变量（或其字段）的字段：从字段地址读取；
+                // 其他表达式（如返回结构体的调用）：从结构体值中取出字段
+                if (try self.place(expr)) |field| break :blk self.builder.buildLoad(field.llvm_type, field.ptr, "field");
+                const field = try self.structField(try self.inferExprType(field_expr.object.*), field_expr.field);
+                const object = try self.generateExpr(field_expr.object.*);
+                break :blk llvm.LLVMBuildExtractValue(self.builder.ref, object, field.index, "field");
             },
             .call => |call_expr| blk: {
+                // 🆕 into(x)：同类型时就是 x；From 转换需要类型的方法，LLVM 后端还不生成方法
+                if (call_expr.resolved_into.*) {
+                    if (ast.intoTarget(call_expr)) |target| {
+                        return self.loweringError("From conversion to '{s}' is not supported by the LLVM backend yet", .{target});
+                    }
+                    break :blk try self.generateExpr(call_expr.args[0]);
+                }
+                // 🆕 直接调用 trait 方法 show(x)：LLVM 后端还不生成方法
+                if (call_expr.resolved_receiver.*) |type_name| {
+                    return self.loweringError("calling method '{s}' of '{s}' as a function is not supported by the LLVM backend yet", .{ call_expr.callee.identifier, type_name });
+                }
                 // 🆕 检查是否是实例方法调用 (obj.method 形式)
                 if (call_expr.callee.* == .field_access) {
                     const field = call_expr.callee.field_access;
@@ -740,14 +1573,8 @@ pub const LLVMNativeBackend = struct {
                                 try args.append(self.allocator, arg_value);
                             }
                             
-                            // 获取函数类型
-                            const i32_type = self.context.i32Type();
-                            var param_types = std.ArrayList(llvm.TypeRef){};
-                            defer param_types.deinit(self.allocator);
-                            for (args.items) |_| {
-                                try param_types.append(self.allocator, i32_type);
-                            }
-                            const func_type = llvm.functionType(i32_type, param_types.items, false);
+                            // 🆕 使用函数声明时的类型，而不是假设参数和返回值都是 i32
+                            const func_type = llvm.LLVMGlobalGetValueType(func);
                             
                             // 构建调用
                             const call_name_z = try self.allocator.dupeZ(u8, "method_call");
@@ -759,41 +1586,76 @@ pub const LLVMNativeBackend = struct {
                     }
                 }
                 
+                // 🆕 内置函数 paw_write(fd, text)：println 等 prelude 函数的输出原语
+                if (call_expr.callee.* == .identifier and std.mem.eql(u8, call_expr.callee.identifier, "paw_write") and call_expr.args.len == 2) {
+                    break :blk try self.generatePawWrite(call_expr.args[0], call_expr.args[1]);
+                }
+                
+                // 🆕 内置函数 gen_int(lo, hi) / gen_string(len)（prelude 中的声明只是占位）
+                if (call_expr.callee.* == .identifier and std.mem.eql(u8, call_expr.callee.identifier, "gen_int") and call_expr.args.len == 2) {
+                    const func = self.gen_int_func orelse self.generateRandomRuntime().gen_int;
+                    var params = [_]llvm.TypeRef{ self.context.i32Type(), self.context.i32Type() };
+                    var args = [_]llvm.ValueRef{ try self.generateTyped(call_expr.args[0], .i32), try self.generateTyped(call_expr.args[1], .i32) };
+                    break :blk self.builder.buildCall(llvm.functionType(self.context.i32Type(), &params, false), func, &args, "gen.int");
+                }
+                if (call_expr.callee.* == .identifier and std.mem.eql(u8, call_expr.callee.identifier, "gen_string") and call_expr.args.len == 1) {
+                    const func = self.gen_string_func orelse self.generateRandomRuntime().gen_string;
+                    var params = [_]llvm.TypeRef{self.context.i32Type()};
+                    var args = [_]llvm.ValueRef{try self.generateTyped(call_expr.args[0], .i32)};
+                    break :blk self.builder.buildCall(llvm.functionType(self.context.pointerType(0), &params, false), func, &args, "gen.string");
+                }
+                
+                // 🆕 通过函数值调用：函数类型的变量、返回函数的调用等
+                if (try self.functionValueCallee(call_expr.callee.*)) |func_type| {
+                    break :blk try self.generateIndirectCall(call_expr.callee.*, func_type, call_expr.args);
+                }
+                
                 // 普通函数调用
-                const func_name = if (call_expr.callee.* == .identifier)
+                var func_name = if (call_expr.callee.* == .identifier)
                     call_expr.callee.identifier
                 else
                     "unknown";
                 
+                // 🆕 泛型函数：调用类型实参对应的实例，形参类型按实例绑定替换
+                const decl = self.function_decls.get(func_name);
+                var param_bindings = layout.Bindings.empty;
+                if (decl) |d| {
+                    if (d.type_params.len > 0) {
+                        const instance = try self.genericInstance(d, call_expr.args, call_expr.resolved_type_args.*) orelse
+                            return self.loweringError("cannot infer the type arguments of the call to '{s}'", .{func_name});
+                        func_name = instance.mangled_name;
+                        param_bindings = .{ .type_params = d.type_params, .type_args = instance.type_args };
+                    }
+                }
+                
                 // Look up function
-                const func = self.functions.get(func_name) orelse {
-                    std.debug.print("⚠️  Undefined function: {s}\n", .{func_name});
-                    break :blk llvm.constI32(self.context, 0);
+                // 🆕 热重载模式下通过槽位调用，被调函数不需要在本模块中生成
+                const callee = if (try self.hotCallee(decl)) |hot| hot else direct: {
+                    const func = self.functions.get(func_name) orelse {
+                        return self.loweringError("call to '{s}', which has no generated code", .{func_name});
+                    };
+                    // 🆕 使用函数声明时的类型，而不是假设参数和返回值都是 i32
+                    break :direct Callee{ .value = func, .type = llvm.LLVMGlobalGetValueType(func) };
                 };
                 
-                // Generate arguments
+                // 🆕 参数按形参类型生成
                 var args = std.ArrayList(llvm.ValueRef){};
                 defer args.deinit(self.allocator);
                 
-                for (call_expr.args) |arg| {
-                    const arg_value = try self.generateExpr(arg);
+                for (call_expr.args, 0..) |arg, i| {
+                    const arg_value = if (decl != null and i < decl.?.params.len)
+                        try self.generateTyped(arg, layout.resolve(decl.?.params[i].type, param_bindings))
+                    else
+                        try self.generateExpr(arg);
                     try args.append(self.allocator, arg_value);
                 }
                 
-                // Get function type
-                const i32_type = self.context.i32Type();
-                var param_types = std.ArrayList(llvm.TypeRef){};
-                defer param_types.deinit(self.allocator);
-                for (args.items) |_| {
-                    try param_types.append(self.allocator, i32_type);
-                }
-                const func_type = llvm.functionType(i32_type, param_types.items, false);
-                
                 // Build call
                 const call_name_z = try self.allocator.dupeZ(u8, "call");
                 defer self.allocator.free(call_name_z);
                 
-                const result = self.builder.buildCall(func_type, func, args.items, call_name_z);
+                const result = self.builder.buildCall(callee.type, callee.value, args.items, call_name_z);
+                if (decl) |d| self.addExtensionAttributes(result, d, llvm.LLVMAddCallSiteAttribute);
                 break :blk result;
             },
             .static_method_call => |smc| blk: {
@@ -824,8 +1686,7 @@ pub const LLVMNativeBackend = struct {
                 
                 // 查找函数
                 const func = self.functions.get(mangled_name) orelse {
-                    std.debug.print("⚠️  Undefined static method: {s}\n", .{mangled_name});
-                    break :blk llvm.constI32(self.context, 0);
+                    return self.loweringError("call to static method '{s}', which has no generated code", .{mangled_name});
                 };
                 
                 // 生成参数
@@ -837,14 +1698,8 @@ pub const LLVMNativeBackend = struct {
                     try args.append(self.allocator, arg_value);
                 }
                 
-                // 获取函数类型
-                const i32_type = self.context.i32Type();
-                var param_types = std.ArrayList(llvm.TypeRef){};
-                defer param_types.deinit(self.allocator);
-                for (args.items) |_| {
-                    try param_types.append(self.allocator, i32_type);
-                }
-                const func_type = llvm.functionType(i32_type, param_types.items, false);
+                // 🆕 使用函数声明时的类型
+                const func_type = llvm.LLVMGlobalGetValueType(func);
                 
                 // 构建调用
                 const call_name_z = try self.allocator.dupeZ(u8, "static_call");
@@ -853,44 +1708,777 @@ pub const LLVMNativeBackend = struct {
                 const result = self.builder.buildCall(func_type, func, args.items, call_name_z);
                 break :blk result;
             },
-            .array_literal => |elements| blk: {
-                // 🆕 数组字面量：[1, 2, 3]
-                // 简化实现：返回第一个元素的值
-                if (elements.len == 0) {
-                    break :blk llvm.constI32(self.context, 0);
-                }
-                
-                // 返回第一个元素的值（简化实现）
-                const first_element = try self.generateExpr(elements[0]);
-                break :blk first_element;
-            },
-            .struct_init => |si| blk: {
-                // 🆕 结构体字面量：Point { x: 1, y: 2 }
-                // 简化实现：返回第一个字段的值
-                if (si.fields.len == 0) {
-                    break :blk llvm.constI32(self.context, 0);
-                }
-                
-                // 返回第一个字段的值（简化实现）
-                const first_field_value = try self.generateExpr(si.fields[0].value);
-                break :blk first_field_value;
-            },
+            // 🆕 数组字面量：以前只返回第一个元素的值，现在明确报告不支持
+            .array_literal => return self.loweringError("array literals are not supported by the LLVM backend yet", .{}),
+            .struct_init => |si| try self.generateStructInit(si.type_name, si.type_args, si.fields),
             // 🆕 v0.1.7: as 类型转换
             .as_expr => |as_cast| blk: {
+                if (as_cast.resolved_from.*) {
+                    return self.loweringError("From conversion to '{s}' is not supported by the LLVM backend yet", .{as_cast.target_type.named});
+                }
+                // 🆕 字面量直接生成目标类型的常量（42u64、5000000000 as i64 不经过 i32 截断）
+                if (isUntypedLiteral(as_cast.value.*) and (self.isIntType(as_cast.target_type) or self.isFloatType(as_cast.target_type))) {
+                    break :blk try self.generateTyped(as_cast.value.*, as_cast.target_type);
+                }
                 const value = try self.generateExpr(as_cast.value.*);
                 const target_llvm_type = try self.toLLVMType(as_cast.target_type);
+                const source_type = try self.inferExprType(as_cast.value.*);
+                
+                // 🆕 整数转换为 char 时检查是否是 Unicode 标量值（u8 总是合法，字面量已在编译期检查）
+                if (as_cast.target_type == .char and self.isIntType(source_type) and source_type != .u8 and as_cast.value.* != .int_literal) {
+                    try self.buildCharCheck(value, source_type);
+                }
                 
                 // 生成类型转换指令
-                break :blk try self.generateCast(value, as_cast.value, as_cast.target_type, target_llvm_type);
+                break :blk try self.generateCast(value, source_type, as_cast.target_type, target_llvm_type);
             },
-            else => llvm.constI32(self.context, 0),
+            // 🆕 字符串插值
+            .string_interp => |si| try self.generateStringInterp(si.parts),
+            // 🆕 lambda（函数体已提升为顶层函数，见 closure.zig）
+            .lambda => |lambda| try self.generateLambdaValue(lambda),
+            else => return self.loweringError("expression '{s}' is not supported by the LLVM backend yet", .{@tagName(expr)}),
         };
     }
     
-    fn toLLVMType(self: *LLVMNativeBackend, paw_type: ast.Type) !llvm.TypeRef {
-        return switch (paw_type) {
+    /// 🆕 字符串插值：按各部分的类型 snprintf 到静态缓冲区（与 C 后端一致，结果在下一次插值前有效）
+    /// 只有一个 string 表达式（"$s"）时直接使用原字符串
+    fn generateStringInterp(self: *LLVMNativeBackend, parts: []ast.StringInterpPart) (error{ NoCurrentFunction, LoweringFailed } || std.mem.Allocator.Error)!llvm.ValueRef {
+        if (parts.len == 1 and parts[0] == .expr and try self.inferExprType(parts[0].expr) == .string) {
+            return self.generateExpr(parts[0].expr);
+        }
+        
+        const buffer_size = 1024;
+        const i32_type = self.context.i32Type();
+        const i64_type = self.context.i64Type();
+        const ptr_type = self.context.pointerType(0);
+        const buffer = self.interp_buffer orelse blk: {
+            const buffer_type = llvm.arrayType(self.context.i8Type(), buffer_size);
+            const global = self.module.addInternalGlobal("paw_str_buf", buffer_type, llvm.constNull(self.context, buffer_type));
+            self.interp_buffer = global;
+            break :blk global;
+        };
+        
+        var format = std.ArrayList(u8){};
+        defer format.deinit(self.allocator);
+        var args = std.ArrayList(llvm.ValueRef){};
+        defer args.deinit(self.allocator);
+        try args.append(self.allocator, buffer);
+        try args.append(self.allocator, llvm.constI64(self.context, buffer_size));
+        try args.append(self.allocator, undefined);  // 格式字符串，生成完所有部分后填入
+        
+        for (parts) |part| {
+            switch (part) {
+                .literal => |lit| for (lit) |c| {
+                    if (c == '%') try format.append(self.allocator, '%');
+                    try format.append(self.allocator, c);
+                },
+                .expr => |expr| {
+                    const expr_type = try self.inferExprType(expr);
+                    const value = try self.generateExpr(expr);
+                    // 可变参数按 C 的默认提升规则传递：小整数提升为 int，float 提升为 double
+                    const formatted: struct { spec: []const u8, arg: llvm.ValueRef } = switch (expr_type) {
+                        .string => .{ .spec = "%s", .arg = value },
+                        .bool => .{ .spec = "%s", .arg = self.builder.buildSelect(
+                            value,
+                            self.builder.buildGlobalStringPtr("true", "true"),
+                            self.builder.buildGlobalStringPtr("false", "false"),
+                            "bool.str",
+                        ) },
+                        .f32 => .{ .spec = "%s", .arg = try self.formatF64(self.builder.buildFPExt(value, self.context.doubleType(), "fpext")) },
+                        .f64 => .{ .spec = "%s", .arg = try self.formatF64(value) },
+                        .i64 => .{ .spec = "%lld", .arg = value },
+                        .u64 => .{ .spec = "%llu", .arg = value },
+                        .u8, .u16 => .{ .spec = "%u", .arg = self.builder.buildZExt(value, i32_type, "zext") },
+                        .u32 => .{ .spec = "%u", .arg = value },
+                        .char => .{ .spec = "%c", .arg = value },
+                        .i8, .i16 => .{ .spec = "%d", .arg = self.builder.buildSExt(value, i32_type, "sext") },
+                        .i32 => .{ .spec = "%d", .arg = value },
+                        else => return self.loweringError("cannot interpolate a value of type '{s}'", .{@tagName(expr_type)}),
+                    };
+                    try format.appendSlice(self.allocator, formatted.spec);
+                    try args.append(self.allocator, formatted.arg);
+                },
+            }
+        }
+        
+        const format_z = try self.allocator.dupeZ(u8, format.items);
+        defer self.allocator.free(format_z);
+        args.items[2] = self.builder.buildGlobalStringPtr(format_z, "interp.fmt");
+        
+        var snprintf_params = [_]llvm.TypeRef{ ptr_type, i64_type, ptr_type };
+        const snprintf_type = llvm.functionType(i32_type, &snprintf_params, true);
+        const snprintf_func = self.module.getOrAddFunction("snprintf", snprintf_type);
+        _ = self.builder.buildCall(snprintf_type, snprintf_func, args.items, "");
+        return buffer;
+    }
+    
+    /// 🆕 paw_write(fd, text)：write(fd, text, strlen(text))，与 C 后端的 paw_rt_write 一样成功返回 0、失败返回 -1。
+    /// 不经过 stdio 缓冲，pawc dev 中每次输出立即可见
+    fn generatePawWrite(self: *LLVMNativeBackend, fd_expr: ast.Expr, text_expr: ast.Expr) !llvm.ValueRef {
+        const i32_type = self.context.i32Type();
+        const i64_type = self.context.i64Type();
+        const ptr_type = self.context.pointerType(0);
+        const fd = try self.generateTyped(fd_expr, .i32);
+        const text = try self.generateExpr(text_expr);
+        
+        var strlen_params = [_]llvm.TypeRef{ptr_type};
+        const strlen_type = llvm.functionType(i64_type, &strlen_params, false);
+        const strlen_func = self.module.getOrAddFunction("strlen", strlen_type);
+        var strlen_args = [_]llvm.ValueRef{text};
+        const len = self.builder.buildCall(strlen_type, strlen_func, &strlen_args, "text.len");
+        
+        var write_params = [_]llvm.TypeRef{ i32_type, ptr_type, i64_type };
+        const write_type = llvm.functionType(i64_type, &write_params, false);
+        const write_func = self.module.getOrAddFunction("write", write_type);
+        var write_args = [_]llvm.ValueRef{ fd, text, len };
+        const written = self.builder.buildCall(write_type, write_func, &write_args, "written");
+        const failed = self.builder.buildICmp(.SLT, written, llvm.constI64(self.context, 0), "write.failed");
+        return self.builder.buildSelect(failed, llvm.constI32(self.context, -1), llvm.constI32(self.context, 0), "write.result");
+    }
+    
+    /// 🆕 生成 gen_int / gen_string 的运行时函数（与 C 后端的 generateRandomRuntime 相同的 splitmix64，
+    /// 同一个种子产生相同的输入）：
+    ///
+    ///   paw_rt_next_random() -> i64              推进 paw_rt_rng_state
+    ///   paw_rt_gen_int(lo, hi) -> i32            hi < lo 时返回 lo，不消耗随机数
+    ///   paw_rt_gen_string(len) -> ptr            malloc 的 len 个 GEN_STRING_ALPHABET 中的字符
+    fn generateRandomRuntime(self: *LLVMNativeBackend) struct { gen_int: llvm.ValueRef, gen_string: llvm.ValueRef } {
+        const saved_block = self.builder.getInsertBlock();
+        defer self.builder.positionAtEnd(saved_block);
+        
+        const i8_type = self.context.i8Type();
+        const i32_type = self.context.i32Type();
+        const i64_type = self.context.i64Type();
+        const ptr_type = self.context.pointerType(0);
+        const state = self.module.addInternalGlobal("paw_rt_rng_state", i64_type, llvm.constI64(self.context, 0));
+        
+        // paw_rt_next_random
+        var no_params = [_]llvm.TypeRef{};
+        var no_args = [_]llvm.ValueRef{};
+        const next_type = llvm.functionType(i64_type, &no_params, false);
+        const next_func = self.module.addFunction("paw_rt_next_random", next_type);
+        llvm.LLVMSetLinkage(next_func, .Internal);
+        self.builder.positionAtEnd(llvm.appendBasicBlock(self.context, next_func, "entry"));
+        const advanced = self.builder.buildAdd(
+            self.builder.buildLoad(i64_type, state, "state"),
+            llvm.LLVMConstInt(i64_type, 0x9E3779B97F4A7C15, 0),
+            "state.next",
+        );
+        _ = self.builder.buildStore(advanced, state);
+        var z = advanced;
+        for ([_]struct { shift: u64, mul: u64 }{ .{ .shift = 30, .mul = 0xBF58476D1CE4E5B9 }, .{ .shift = 27, .mul = 0x94D049BB133111EB } }) |round| {
+            const mixed = self.builder.buildXor(z, self.builder.buildLShr(z, llvm.LLVMConstInt(i64_type, round.shift, 0), "z.shr"), "z.mix");
+            z = self.builder.buildMul(mixed, llvm.LLVMConstInt(i64_type, round.mul, 0), "z.mul");
+        }
+        _ = self.builder.buildRet(self.builder.buildXor(z, self.builder.buildLShr(z, llvm.LLVMConstInt(i64_type, 31, 0), "z.shr"), "random"));
+        
+        // paw_rt_gen_int
+        var int_params = [_]llvm.TypeRef{ i32_type, i32_type };
+        const gen_int = self.module.addFunction("paw_rt_gen_int", llvm.functionType(i32_type, &int_params, false));
+        llvm.LLVMSetLinkage(gen_int, .Internal);
+        const lo = llvm.LLVMGetParam(gen_int, 0);
+        const hi = llvm.LLVMGetParam(gen_int, 1);
+        const int_entry = llvm.appendBasicBlock(self.context, gen_int, "entry");
+        const empty_block = llvm.appendBasicBlock(self.context, gen_int, "empty");
+        const pick_block = llvm.appendBasicBlock(self.context, gen_int, "pick");
+        self.builder.positionAtEnd(int_entry);
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, self.builder.buildICmp(.SLT, hi, lo, "is.empty"), empty_block, pick_block);
+        self.builder.positionAtEnd(empty_block);
+        _ = self.builder.buildRet(lo);
+        self.builder.positionAtEnd(pick_block);
+        const lo64 = self.builder.buildSExt(lo, i64_type, "lo64");
+        const span = self.builder.buildAdd(self.builder.buildSub(self.builder.buildSExt(hi, i64_type, "hi64"), lo64, "diff"), llvm.constI64(self.context, 1), "span");
+        const random = self.builder.buildCall(next_type, next_func, &no_args, "random");
+        const offset = self.builder.buildURem(random, span, "offset");
+        _ = self.builder.buildRet(self.builder.buildTrunc(self.builder.buildAdd(lo64, offset, "value"), i32_type, "value.i32"));
+        
+        // paw_rt_gen_string
+        var string_params = [_]llvm.TypeRef{i32_type};
+        const gen_string = self.module.addFunction("paw_rt_gen_string", llvm.functionType(ptr_type, &string_params, false));
+        llvm.LLVMSetLinkage(gen_string, .Internal);
+        const string_entry = llvm.appendBasicBlock(self.context, gen_string, "entry");
+        const loop_block = llvm.appendBasicBlock(self.context, gen_string, "loop");
+        const body_block = llvm.appendBasicBlock(self.context, gen_string, "body");
+        const done_block = llvm.appendBasicBlock(self.context, gen_string, "done");
+        
+        self.builder.positionAtEnd(string_entry);
+        const requested = llvm.LLVMGetParam(gen_string, 0);
+        const is_negative = self.builder.buildICmp(.SLT, requested, llvm.constI32(self.context, 0), "len.neg");
+        const len = self.builder.buildSExt(self.builder.buildSelect(is_negative, llvm.constI32(self.context, 0), requested, "len"), i64_type, "len64");
+        var malloc_params = [_]llvm.TypeRef{i64_type};
+        const malloc_type = llvm.functionType(ptr_type, &malloc_params, false);
+        const malloc_func = self.module.getOrAddFunction("malloc", malloc_type);
+        var malloc_args = [_]llvm.ValueRef{self.builder.buildAdd(len, llvm.constI64(self.context, 1), "size")};
+        const text = self.builder.buildCall(malloc_type, malloc_func, &malloc_args, "text");
+        const alphabet = self.builder.buildGlobalStringPtr(ast.GEN_STRING_ALPHABET, "gen.alphabet");
+        _ = self.builder.buildBr(loop_block);
+        
+        self.builder.positionAtEnd(loop_block);
+        const i = self.builder.buildPhi(i64_type, "i");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, self.builder.buildICmp(.SGE, i, len, "filled"), done_block, body_block);
+        
+        self.builder.positionAtEnd(body_block);
+        const char_random = self.builder.buildCall(next_type, next_func, &no_args, "random");
+        var char_index = [_]llvm.ValueRef{self.builder.buildURem(char_random, llvm.constI64(self.context, ast.GEN_STRING_ALPHABET.len), "char.index")};
+        const char = self.builder.buildLoad(i8_type, self.builder.buildGEP(i8_type, alphabet, &char_index, "char.ptr"), "char");
+        var slot_index = [_]llvm.ValueRef{i};
+        _ = self.builder.buildStore(char, self.builder.buildGEP(i8_type, text, &slot_index, "slot"));
+        const i_next = self.builder.buildAdd(i, llvm.constI64(self.context, 1), "i.next");
+        _ = self.builder.buildBr(loop_block);
+        
+        var incoming_values = [_]llvm.ValueRef{ llvm.constI64(self.context, 0), i_next };
+        var incoming_blocks = [_]llvm.BasicBlockRef{ string_entry, body_block };
+        llvm.LLVMAddIncoming(i, &incoming_values, &incoming_blocks, 2);
+        
+        self.builder.positionAtEnd(done_block);
+        var end_index = [_]llvm.ValueRef{len};
+        _ = self.builder.buildStore(llvm.LLVMConstInt(i8_type, 0, 0), self.builder.buildGEP(i8_type, text, &end_index, "end"));
+        _ = self.builder.buildRet(text);
+        
+        self.gen_int_func = gen_int;
+        self.gen_string_func = gen_string;
+        return .{ .gen_int = gen_int, .gen_string = gen_string };
+    }
+    
+    /// 🆕 浮点数的最短往返文本（与 C 后端的 paw_rt_format_f64 相同的规则，见 ast.formatFloat）
+    /// 结果写入当前位置分配的栈缓冲区
+    fn formatF64(self: *LLVMNativeBackend, value: llvm.ValueRef) error{NoCurrentFunction}!llvm.ValueRef {
+        const func = self.format_f64_func orelse self.generateFormatF64();
+        const ptr_type = self.context.pointerType(0);
+        const buffer_size = 32;
+        const buffer = try self.buildEntryAlloca(llvm.arrayType(self.context.i8Type(), buffer_size), "f64.buf");
+        var params = [_]llvm.TypeRef{ ptr_type, self.context.i64Type(), self.context.doubleType() };
+        var args = [_]llvm.ValueRef{ buffer, llvm.constI64(self.context, buffer_size), value };
+        return self.builder.buildCall(llvm.functionType(ptr_type, &params, false), func, &args, "f64.str");
+    }
+    
+    /// 🆕 生成 paw_rt_format_f64(buf, size, v) -> buf：
+    ///
+    ///   nan / inf / -inf 直接输出；否则 p 从 0 增加到 16，直到 strtod("%.*e", p) 读回 v，
+    ///   十进制指数在 [FLOAT_FIXED_MIN_EXP, FLOAT_FIXED_MAX_EXP) 内用 "%.*f"（p - exp 位小数），
+    ///   否则输出 "<尾数>e<exp>"
+    fn generateFormatF64(self: *LLVMNativeBackend) llvm.ValueRef {
+        const saved_block = self.builder.getInsertBlock();
+        defer self.builder.positionAtEnd(saved_block);
+        
+        const i8_type = self.context.i8Type();
+        const i32_type = self.context.i32Type();
+        const i64_type = self.context.i64Type();
+        const double_type = self.context.doubleType();
+        const ptr_type = self.context.pointerType(0);
+        
+        var params = [_]llvm.TypeRef{ ptr_type, i64_type, double_type };
+        const func = self.module.addFunction("paw_rt_format_f64", llvm.functionType(ptr_type, &params, false));
+        llvm.LLVMSetLinkage(func, .Internal);
+        self.format_f64_func = func;
+        const buf = llvm.LLVMGetParam(func, 0);
+        const size = llvm.LLVMGetParam(func, 1);
+        const v = llvm.LLVMGetParam(func, 2);
+        
+        var snprintf_params = [_]llvm.TypeRef{ ptr_type, i64_type, ptr_type };
+        const snprintf_type = llvm.functionType(i32_type, &snprintf_params, true);
+        const snprintf_func = self.module.getOrAddFunction("snprintf", snprintf_type);
+        var strtod_params = [_]llvm.TypeRef{ ptr_type, ptr_type };
+        const strtod_type = llvm.functionType(double_type, &strtod_params, false);
+        const strtod_func = self.module.getOrAddFunction("strtod", strtod_type);
+        var strchr_params = [_]llvm.TypeRef{ ptr_type, i32_type };
+        const strchr_type = llvm.functionType(ptr_type, &strchr_params, false);
+        const strchr_func = self.module.getOrAddFunction("strchr", strchr_type);
+        var atoi_params = [_]llvm.TypeRef{ptr_type};
+        const atoi_type = llvm.functionType(i32_type, &atoi_params, false);
+        const atoi_func = self.module.getOrAddFunction("atoi", atoi_type);
+        
+        const entry_block = llvm.appendBasicBlock(self.context, func, "entry");
+        const special_block = llvm.appendBasicBlock(self.context, func, "special");
+        const loop_block = llvm.appendBasicBlock(self.context, func, "loop");
+        const next_block = llvm.appendBasicBlock(self.context, func, "next");
+        const layout_block = llvm.appendBasicBlock(self.context, func, "layout");
+        const fixed_block = llvm.appendBasicBlock(self.context, func, "fixed");
+        const sci_block = llvm.appendBasicBlock(self.context, func, "scientific");
+        
+        // entry: nan / inf 单独处理
+        self.builder.positionAtEnd(entry_block);
+        const tmp_size = 64;
+        const tmp = self.builder.buildAlloca(llvm.arrayType(i8_type, tmp_size), "tmp");
+        const is_nan = self.builder.buildFCmp(.UNO, v, v, "is.nan");
+        const is_neg = self.builder.buildFCmp(.OLT, v, llvm.constDouble(self.context, 0), "is.neg");
+        const magnitude = self.builder.buildSelect(is_neg, self.builder.buildFNeg(v, "neg"), v, "abs");
+        const is_inf = self.builder.buildFCmp(.OEQ, magnitude, llvm.constDouble(self.context, std.math.inf(f64)), "is.inf");
+        const is_special = self.builder.buildOr(is_nan, is_inf, "is.special");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, is_special, special_block, loop_block);
+        
+        // special: "nan" / "-inf" / "inf"
+        self.builder.positionAtEnd(special_block);
+        const inf_text = self.builder.buildSelect(
+            is_neg,
+            self.builder.buildGlobalStringPtr("-inf", "f64.neg.inf"),
+            self.builder.buildGlobalStringPtr("inf", "f64.inf"),
+            "inf.text",
+        );
+        const special_text = self.builder.buildSelect(is_nan, self.builder.buildGlobalStringPtr("nan", "f64.nan"), inf_text, "special.text");
+        var special_args = [_]llvm.ValueRef{ buf, size, self.builder.buildGlobalStringPtr("%s", "f64.fmt.s"), special_text };
+        _ = self.builder.buildCall(snprintf_type, snprintf_func, &special_args, "");
+        _ = self.builder.buildRet(buf);
+        
+        // loop: p = 0..16，直到 "%.*e" 能读回 v
+        self.builder.positionAtEnd(loop_block);
+        const p = self.builder.buildPhi(i32_type, "p");
+        const sci_format = self.builder.buildGlobalStringPtr("%.*e", "f64.fmt.e");
+        var sci_args = [_]llvm.ValueRef{ tmp, llvm.constI64(self.context, tmp_size), sci_format, p, v };
+        _ = self.builder.buildCall(snprintf_type, snprintf_func, &sci_args, "");
+        var strtod_args = [_]llvm.ValueRef{ tmp, llvm.constNull(self.context, ptr_type) };
+        const round_trip = self.builder.buildCall(strtod_type, strtod_func, &strtod_args, "round.trip");
+        const exact = self.builder.buildFCmp(.OEQ, round_trip, v, "exact");
+        const exhausted = self.builder.buildICmp(.SGE, p, llvm.constI32(self.context, 16), "exhausted");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, self.builder.buildOr(exact, exhausted, "done"), layout_block, next_block);
+        
+        self.builder.positionAtEnd(next_block);
+        const p_next = self.builder.buildAdd(p, llvm.constI32(self.context, 1), "p.next");
+        _ = self.builder.buildBr(loop_block);
+        
+        var incoming_values = [_]llvm.ValueRef{ llvm.constI32(self.context, 0), p_next };
+        var incoming_blocks = [_]llvm.BasicBlockRef{ entry_block, next_block };
+        llvm.LLVMAddIncoming(p, &incoming_values, &incoming_blocks, 2);
+        
+        // layout: 按十进制指数选择定点或科学计数法
+        self.builder.positionAtEnd(layout_block);
+        var strchr_args = [_]llvm.ValueRef{ tmp, llvm.constI32(self.context, 'e') };
+        const e_ptr = self.builder.buildCall(strchr_type, strchr_func, &strchr_args, "e");
+        var exp_index = [_]llvm.ValueRef{llvm.constI64(self.context, 1)};
+        var atoi_args = [_]llvm.ValueRef{self.builder.buildGEP(i8_type, e_ptr, &exp_index, "exp.text")};
+        const exp10 = self.builder.buildCall(atoi_type, atoi_func, &atoi_args, "exp10");
+        const above_min = self.builder.buildICmp(.SGE, exp10, llvm.constI32(self.context, ast.FLOAT_FIXED_MIN_EXP), "above.min");
+        const below_max = self.builder.buildICmp(.SLT, exp10, llvm.constI32(self.context, ast.FLOAT_FIXED_MAX_EXP), "below.max");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, self.builder.buildAnd(above_min, below_max, "use.fixed"), fixed_block, sci_block);
+        
+        // fixed: "%.*f"，小数位数 max(p - exp, 0)
+        self.builder.positionAtEnd(fixed_block);
+        const decimals = self.builder.buildSub(p, exp10, "decimals");
+        const is_negative = self.builder.buildICmp(.SLT, decimals, llvm.constI32(self.context, 0), "decimals.neg");
+        const clamped = self.builder.buildSelect(is_negative, llvm.constI32(self.context, 0), decimals, "decimals.clamped");
+        var fixed_args = [_]llvm.ValueRef{ buf, size, self.builder.buildGlobalStringPtr("%.*f", "f64.fmt.f"), clamped, v };
+        _ = self.builder.buildCall(snprintf_type, snprintf_func, &fixed_args, "");
+        _ = self.builder.buildRet(buf);
+        
+        // scientific: 截断 tmp 中的 'e'，输出 "<尾数>e<exp>"
+        self.builder.positionAtEnd(sci_block);
+        _ = self.builder.buildStore(llvm.LLVMConstInt(i8_type, 0, 0), e_ptr);
+        var sci_out_args = [_]llvm.ValueRef{ buf, size, self.builder.buildGlobalStringPtr("%se%d", "f64.fmt.sci"), tmp, exp10 };
+        _ = self.builder.buildCall(snprintf_type, snprintf_func, &sci_out_args, "");
+        _ = self.builder.buildRet(buf);
+        
+        return func;
+    }
+    
+    // ============================================================================
+    // 🆕 结构体
+    // ============================================================================
+    
+    /// 结构体的 LLVM 类型：命名结构体 %Name，字段按声明顺序排列。
+    /// LLVM 的自然布局与 C 后端生成的 C 结构体相同（字段间及尾部按对齐填充）
+    fn structType(self: *LLVMNativeBackend, name: []const u8) (error{LoweringFailed} || std.mem.Allocator.Error)!llvm.TypeRef {
+        if (self.struct_types.get(name)) |t| return t;
+        const decl = self.type_decls.get(name) orelse
+            return self.loweringError("internal error: unknown type '{s}'", .{name});
+        if (decl.kind != .struct_type) {
+            return self.loweringError("values of type '{s}' are not supported by the LLVM backend yet", .{name});
+        }
+        if (decl.type_params.len > 0) {
+            return self.loweringError("generic struct '{s}' is not supported by the LLVM backend yet", .{name});
+        }
+        
+        const name_z = try self.allocator.dupeZ(u8, name);
+        defer self.allocator.free(name_z);
+        const struct_type = llvm.LLVMStructCreateNamed(self.context.ref, name_z.ptr);
+        try self.struct_types.put(name, struct_type);
+        
+        var field_types = std.ArrayList(llvm.TypeRef){};
+        defer field_types.deinit(self.allocator);
+        for (decl.kind.struct_type.fields) |field| {
+            try field_types.append(self.allocator, try self.toLLVMType(field.type));
+        }
+        llvm.LLVMStructSetBody(struct_type, field_types.items.ptr, @intCast(field_types.items.len), 0);
+        return struct_type;
+    }
+    
+    const FieldInfo = struct {
+        index: u32,
+        type: ast.Type,
+    };
+    
+    /// 结构体类型 object_type 中字段 name 的下标和类型
+    fn structField(self: *LLVMNativeBackend, object_type: ast.Type, name: []const u8) (error{LoweringFailed} || std.mem.Allocator.Error)!FieldInfo {
+        const resolved = layout.resolve(object_type, self.current_bindings);
+        if (resolved == .named) {
+            if (self.type_decls.get(resolved.named)) |decl| {
+                if (decl.kind == .struct_type) {
+                    for (decl.kind.struct_type.fields, 0..) |field, i| {
+                        if (std.mem.eql(u8, field.name, name)) return .{ .index = @intCast(i), .type = field.type };
+                    }
+                }
+            }
+        }
+        return self.loweringError("field access '.{s}' on type '{s}' is not supported by the LLVM backend yet", .{ name, @tagName(resolved) });
+    }
+    
+    /// 可寻址的值：变量或变量的（嵌套）字段
+    const Place = struct {
+        ptr: llvm.ValueRef,
+        llvm_type: llvm.TypeRef,
+        paw_type: ast.Type,
+    };
+    
+    /// 表达式的地址；不是变量或变量的字段时返回 null
+    fn place(self: *LLVMNativeBackend, expr: ast.Expr) (error{LoweringFailed} || std.mem.Allocator.Error)!?Place {
+        switch (expr) {
+            .identifier => |name| {
+                const ptr = self.variables.get(name) orelse return null;
+                const llvm_type = self.variable_types.get(name) orelse return null;
+                const paw_type = self.variable_paw_types.get(name) orelse return null;
+                return Place{ .ptr = ptr, .llvm_type = llvm_type, .paw_type = paw_type };
+            },
+            .field_access => |fa| {
+                const base = try self.place(fa.object.*) orelse return null;
+                const field = try self.structField(base.paw_type, fa.field);
+                return Place{
+                    .ptr = self.builder.buildStructGEP(base.llvm_type, base.ptr, field.index, "field.ptr"),
+                    .llvm_type = try self.toLLVMType(field.type),
+                    .paw_type = field.type,
+                };
+            },
+            else => return null,
+        }
+    }
+    
+    /// 结构体字面量：按书写顺序求值各字段，再按声明顺序插入结构体值
+    fn generateStructInit(
+        self: *LLVMNativeBackend,
+        type_name: []const u8,
+        type_args: []const ast.Type,
+        inits: []const ast.StructFieldInit,
+    ) (error{ NoCurrentFunction, LoweringFailed } || std.mem.Allocator.Error)!llvm.ValueRef {
+        if (type_args.len > 0) {
+            return self.loweringError("generic struct '{s}' is not supported by the LLVM backend yet", .{type_name});
+        }
+        const struct_type = try self.structType(type_name);
+        const fields = self.type_decls.get(type_name).?.kind.struct_type.fields;
+        
+        const values = try self.allocator.alloc(?llvm.ValueRef, fields.len);
+        defer self.allocator.free(values);
+        @memset(values, null);
+        for (inits) |init| {
+            const field = try self.structField(.{ .named = type_name }, init.name);
+            values[field.index] = try self.generateTyped(init.value, field.type);
+        }
+        
+        var value = llvm.LLVMGetUndef(struct_type);
+        for (values, 0..) |field_value, i| {
+            const v = field_value orelse
+                return self.loweringError("internal error: field '{s}' of '{s}' is not initialized", .{ fields[i].name, type_name });
+            value = llvm.LLVMBuildInsertValue(self.builder.ref, value, v, @intCast(i), "");
+        }
+        return value;
+    }
+    
+    /// 🆕 泛型函数调用对应的单态化实例（类型实参由类型检查器推导）
+    fn genericInstance(
+        self: *LLVMNativeBackend,
+        func: ast.FunctionDecl,
+        call_args: []const ast.Expr,
+        resolved_type_args: []const ast.Type,
+    ) !?generics.GenericInstance {
+        const type_args = try self.generic_context.callTypeArgs(func, call_args, resolved_type_args, self.current_bindings) orelse return null;
+        const mangled = try self.generic_context.monomorphizer.recordInstance(func.name, type_args);
+        for (self.generic_context.monomorphizer.instances.items) |instance| {
+            if (std.mem.eql(u8, instance.mangled_name, mangled)) return instance;
+        }
+        return null;
+    }
+    
+    /// 🆕 按期望的 Paw 类型生成表达式：
+    ///   - 整数 / 浮点字面量直接生成目标类型的常量
+    ///   - 其余表达式的值类型与期望不同时，按两者的 Paw 类型插入转换
+    ///   - 无法转换说明前端和后端对类型的理解不一致，报告内部错误
+    fn generateTyped(self: *LLVMNativeBackend, expr: ast.Expr, want: ast.Type) !llvm.ValueRef {
+        const want_llvm = try self.toLLVMType(want);
+        switch (expr) {
+            .int_literal => |literal| {
+                if (self.isIntType(want)) return llvm.LLVMConstInt(want_llvm, @bitCast(literal.value), 1);
+                if (self.isFloatType(want)) return llvm.LLVMConstReal(want_llvm, @floatFromInt(literal.value));
+            },
+            .float_literal => |val| {
+                if (self.isFloatType(want)) return llvm.LLVMConstReal(want_llvm, val);
+            },
+            .unary => |unop| if (unop.op == .neg and isUntypedLiteral(unop.operand.*)) {
+                const operand = try self.generateTyped(unop.operand.*, want);
+                return if (self.isFloatType(want)) self.builder.buildFNeg(operand, "fneg") else self.builder.buildNeg(operand, "neg");
+            },
+            else => {},
+        }
+        
+        const value = try self.generateExpr(expr);
+        if (want == .void or llvm.LLVMTypeOf(value) == want_llvm) return value;
+        
+        const have = try self.inferExprType(expr);
+        // 🆕 不返回的调用（panic 等）之后的代码不可达，值任意
+        if (have == .never) return llvm.constNull(self.context, want_llvm);
+        if (self.isNumericType(have) and self.isNumericType(want)) {
+            return try self.generateCast(value, have, want, want_llvm);
+        }
+        return self.loweringError("internal error: a value of type '{s}' was lowered where '{s}' is expected", .{ @tagName(have), @tagName(want) });
+    }
+    
+    /// 🆕 窄整数的 ABI：参数和返回值按 C 的约定扩展到寄存器宽度
+    /// （u8 / Byte、u16、bool 零扩展，i8、i16 符号扩展），与 C 后端的 uint8_t 等一致
+    fn addExtensionAttributes(
+        self: *LLVMNativeBackend,
+        target: llvm.ValueRef,
+        func: ast.FunctionDecl,
+        comptime add: fn (llvm.ValueRef, c_uint, llvm.AttributeRef) callconv(.c) void,
+    ) void {
+        if (extensionAttribute(func.return_type)) |name| {
+            add(target, llvm.AttributeReturnIndex, self.context.enumAttribute(name));
+        }
+        for (func.params, 0..) |param, i| {
+            if (extensionAttribute(param.type)) |name| {
+                add(target, @intCast(i + 1), self.context.enumAttribute(name));
+            }
+        }
+    }
+    
+    fn extensionAttribute(t: ast.Type) ?[]const u8 {
+        return switch (t) {
+            .u8, .u16, .bool => "zeroext",
+            .i8, .i16 => "signext",
+            else => null,
+        };
+    }
+    
+    /// 🆕 不会产生值的分支结束时若块尚无终止符，补上 unreachable（该块没有前驱）
+    fn terminateUnreachable(self: *LLVMNativeBackend, block: llvm.BasicBlockRef) void {
+        if (llvm.Builder.blockHasTerminator(block)) return;
+        _ = self.builder.buildUnreachable();
+    }
+    
+    /// 🆕 && / || 短路求值：右侧在单独的基本块中求值，结果由 PHI 合并
+    /// （以前两侧都会求值，再做按位 and/or）
+    /// 生成: lhs -> sc.rhs -> sc.end | lhs -> sc.end（短路）
+    fn generateShortCircuit(self: *LLVMNativeBackend, op: ast.BinaryOp, left: ast.Expr, right: ast.Expr) !llvm.ValueRef {
+        const func = self.current_function orelse return error.NoCurrentFunction;
+        const i1_type = self.context.i1Type();
+        
+        const lhs = try self.generateTyped(left, .bool);
+        const lhs_end_block = self.builder.getInsertBlock();
+        const rhs_block = llvm.appendBasicBlock(self.context, func, "sc.rhs");
+        const end_block = llvm.appendBasicBlock(self.context, func, "sc.end");
+        if (op == .and_op) {
+            _ = llvm.LLVMBuildCondBr(self.builder.ref, lhs, rhs_block, end_block);
+        } else {
+            _ = llvm.LLVMBuildCondBr(self.builder.ref, lhs, end_block, rhs_block);
+        }
+        
+        // 右侧可能包含控制流，PHI 的来源是它结束时所在的块
+        self.builder.positionAtEnd(rhs_block);
+        const rhs = try self.generateTyped(right, .bool);
+        const rhs_end_block = self.builder.getInsertBlock();
+        const rhs_reaches_end = !llvm.Builder.blockHasTerminator(rhs_end_block);
+        if (rhs_reaches_end) _ = self.builder.buildBr(end_block);
+        
+        // 短路时的结果：&& 为 false，|| 为 true
+        self.builder.positionAtEnd(end_block);
+        const short_value = llvm.LLVMConstInt(i1_type, if (op == .and_op) 0 else 1, 0);
+        const phi = self.builder.buildPhi(i1_type, "sc.result");
+        var incoming_values = [_]llvm.ValueRef{ short_value, rhs };
+        var incoming_blocks = [_]llvm.BasicBlockRef{ lhs_end_block, rhs_end_block };
+        llvm.LLVMAddIncoming(phi, &incoming_values, &incoming_blocks, if (rhs_reaches_end) 2 else 1);
+        return phi;
+    }
+    
+    /// 🆕 二元运算的操作数类型：未标注类型的字面量跟随另一侧
+    fn operandType(self: *LLVMNativeBackend, left: ast.Expr, right: ast.Expr) !ast.Type {
+        if (isUntypedLiteral(left) and !isUntypedLiteral(right)) return try self.inferExprType(right);
+        return try self.inferExprType(left);
+    }
+    
+    fn isUntypedLiteral(expr: ast.Expr) bool {
+        return switch (expr) {
+            .int_literal, .float_literal => true,
+            .unary => |unop| unop.op == .neg and isUntypedLiteral(unop.operand.*),
+            else => false,
+        };
+    }
+    
+    /// 🆕 按操作数类型选择二元运算指令
+    fn buildBinary(self: *LLVMNativeBackend, op: ast.BinaryOp, lhs: llvm.ValueRef, rhs: llvm.ValueRef, operand_type: ast.Type) !llvm.ValueRef {
+        if (self.isFloatType(operand_type)) {
+            return switch (op) {
+                .add => self.builder.buildFAdd(lhs, rhs, "fadd"),
+                .sub => self.builder.buildFSub(lhs, rhs, "fsub"),
+                .mul => self.builder.buildFMul(lhs, rhs, "fmul"),
+                .div => self.builder.buildFDiv(lhs, rhs, "fdiv"),
+                .mod => self.builder.buildFRem(lhs, rhs, "frem"),
+                .eq => self.builder.buildFCmp(.OEQ, lhs, rhs, "fcmp"),
+                .ne => self.builder.buildFCmp(.ONE, lhs, rhs, "fcmp"),
+                .lt => self.builder.buildFCmp(.OLT, lhs, rhs, "fcmp"),
+                .le => self.builder.buildFCmp(.OLE, lhs, rhs, "fcmp"),
+                .gt => self.builder.buildFCmp(.OGT, lhs, rhs, "fcmp"),
+                .ge => self.builder.buildFCmp(.OGE, lhs, rhs, "fcmp"),
+                .and_op, .or_op => self.loweringError("internal error: logical operator applied to a float", .{}),
+            };
+        }
+        
+        switch (operand_type) {
+            .i8, .i16, .i32, .i64, .i128, .u8, .u16, .u32, .u64, .u128, .char, .bool => {},
+            else => return self.loweringError("operator '{s}' on type '{s}' is not supported by the LLVM backend yet", .{ @tagName(op), @tagName(operand_type) }),
+        }
+        const signed = self.isSignedIntType(operand_type);
+        return switch (op) {
+            .add => self.builder.buildAdd(lhs, rhs, "add"),
+            .sub => self.builder.buildSub(lhs, rhs, "sub"),
+            .mul => self.builder.buildMul(lhs, rhs, "mul"),
+            .div => if (signed) self.builder.buildSDiv(lhs, rhs, "div") else self.builder.buildUDiv(lhs, rhs, "div"),
+            .mod => if (signed) self.builder.buildSRem(lhs, rhs, "rem") else self.builder.buildURem(lhs, rhs, "rem"),
+            .eq => self.builder.buildICmp(.EQ, lhs, rhs, "cmp"),
+            .ne => self.builder.buildICmp(.NE, lhs, rhs, "cmp"),
+            .lt => self.builder.buildICmp(if (signed) .SLT else .ULT, lhs, rhs, "cmp"),
+            .le => self.builder.buildICmp(if (signed) .SLE else .ULE, lhs, rhs, "cmp"),
+            .gt => self.builder.buildICmp(if (signed) .SGT else .UGT, lhs, rhs, "cmp"),
+            .ge => self.builder.buildICmp(if (signed) .SGE else .UGE, lhs, rhs, "cmp"),
+            .and_op => self.builder.buildAnd(lhs, rhs, "and"),
+            .or_op => self.builder.buildOr(lhs, rhs, "or"),
+        };
+    }
+    
+    /// 🆕 被调用的表达式是函数值（而不是函数名）时返回它的函数类型
+    fn functionValueCallee(self: *LLVMNativeBackend, callee: ast.Expr) (error{LoweringFailed} || std.mem.Allocator.Error)!?ast.Type {
+        if (callee == .identifier and !self.variables.contains(callee.identifier)) return null;
+        if (callee == .field_access) return null;
+        const callee_type = layout.resolve(try self.inferExprType(callee), self.current_bindings);
+        return if (callee_type == .function) callee_type else null;
+    }
+    
+    /// 🆕 间接调用：实参按函数类型的形参类型生成
+    ///
+    /// 函数值的环境不为 null 时（有捕获的 lambda），环境指针作为第一个实参传入。
+    fn generateIndirectCall(self: *LLVMNativeBackend, callee: ast.Expr, func_type: ast.Type, args: []const ast.Expr) !llvm.ValueRef {
+        const func = self.current_function orelse return error.NoCurrentFunction;
+        const value = try self.generateExpr(callee);
+        const env = llvm.LLVMBuildExtractValue(self.builder.ref, value, 0, "env");
+        const target = llvm.LLVMBuildExtractValue(self.builder.ref, value, 1, "fn");
+        
+        // 环境参数占第 0 个位置，两种调用共用同一组实参
+        var param_types = std.ArrayList(llvm.TypeRef){};
+        defer param_types.deinit(self.allocator);
+        var arg_values = std.ArrayList(llvm.ValueRef){};
+        defer arg_values.deinit(self.allocator);
+        try param_types.append(self.allocator, self.context.pointerType(0));
+        try arg_values.append(self.allocator, env);
+        for (func_type.function.params, args) |param_type, arg| {
+            try param_types.append(self.allocator, try self.toLLVMType(param_type));
+            try arg_values.append(self.allocator, try self.generateTyped(arg, param_type));
+        }
+        
+        const return_type = try self.toLLVMType(func_type.function.return_type.*);
+        const env_fn_type = llvm.functionType(return_type, param_types.items, false);
+        const plain_fn_type = llvm.functionType(return_type, param_types.items[1..], false);
+        
+        const env_block = llvm.appendBasicBlock(self.context, func, "call.env");
+        const plain_block = llvm.appendBasicBlock(self.context, func, "call.plain");
+        const merge_block = llvm.appendBasicBlock(self.context, func, "call.merge");
+        const has_env = self.builder.buildICmp(.NE, env, llvm.constNull(self.context, self.context.pointerType(0)), "has.env");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, has_env, env_block, plain_block);
+        
+        self.builder.positionAtEnd(env_block);
+        const env_result = self.builder.buildCall(env_fn_type, target, arg_values.items, "");
+        _ = self.builder.buildBr(merge_block);
+        
+        self.builder.positionAtEnd(plain_block);
+        const plain_result = self.builder.buildCall(plain_fn_type, target, arg_values.items[1..], "");
+        _ = self.builder.buildBr(merge_block);
+        
+        self.builder.positionAtEnd(merge_block);
+        if (func_type.function.return_type.* == .void) return env_result;
+        const phi = self.builder.buildPhi(return_type, "indirect");
+        var incoming_values = [_]llvm.ValueRef{ env_result, plain_result };
+        var incoming_blocks = [_]llvm.BasicBlockRef{ env_block, plain_block };
+        llvm.LLVMAddIncoming(phi, &incoming_values, &incoming_blocks, 2);
+        return phi;
+    }
+    
+    /// 🆕 函数值 { 环境, 函数 }
+    fn buildFunctionValue(self: *LLVMNativeBackend, env: llvm.ValueRef, target: llvm.ValueRef) llvm.ValueRef {
+        const with_env = llvm.LLVMBuildInsertValue(self.builder.ref, llvm.LLVMGetUndef(self.functionValueType()), env, 0, "");
+        return llvm.LLVMBuildInsertValue(self.builder.ref, with_env, target, 1, "fnval");
+    }
+    
+    /// 🆕 函数值的 LLVM 类型：{ ptr 环境, ptr 函数 }（与 C 后端的 PawFn 一致）
+    fn functionValueType(self: *LLVMNativeBackend) llvm.TypeRef {
+        var elements = [_]llvm.TypeRef{ self.context.pointerType(0), self.context.pointerType(0) };
+        return self.context.structType(&elements, false);
+    }
+    
+    /// 🆕 lambda 环境的结构体类型：按顺序存放捕获的变量
+    fn envType(self: *LLVMNativeBackend, captures: []const ast.Capture) !llvm.TypeRef {
+        var field_types = std.ArrayList(llvm.TypeRef){};
+        defer field_types.deinit(self.allocator);
+        for (captures) |capture| {
+            try field_types.append(self.allocator, try self.toLLVMType(capture.type));
+        }
+        return self.context.structType(field_types.items, false);
+    }
+    
+    /// 🆕 lambda 作为值：有捕获时在堆上分配环境并复制捕获的变量（环境不释放）
+    fn generateLambdaValue(self: *LLVMNativeBackend, lambda: ast.Lambda) (error{ NoCurrentFunction, LoweringFailed } || std.mem.Allocator.Error)!llvm.ValueRef {
+        const name = lambda.lifted_name.*;
+        const target = self.functions.get(name) orelse
+            return self.loweringError("internal error: lambda '{s}' has no generated code", .{name});
+        const captures = lambda.captures.*;
+        if (captures.len == 0) return self.buildFunctionValue(llvm.constNull(self.context, self.context.pointerType(0)), target);
+        
+        const env_type = try self.envType(captures);
+        const env = try self.buildHeapAlloc(env_type);
+        for (captures, 0..) |capture, i| {
+            const field = self.builder.buildStructGEP(env_type, env, @intCast(i), "env.field");
+            _ = self.builder.buildStore(try self.generateExpr(.{ .identifier = capture.name }), field);
+        }
+        return self.buildFunctionValue(env, target);
+    }
+    
+    /// 🆕 malloc 一个 ty 大小的内存块，失败时 panic（与 C 后端的 paw_rt_alloc 一致）
+    fn buildHeapAlloc(self: *LLVMNativeBackend, ty: llvm.TypeRef) (error{ NoCurrentFunction, LoweringFailed } || std.mem.Allocator.Error)!llvm.ValueRef {
+        const func = self.current_function orelse return error.NoCurrentFunction;
+        const panic_func = self.functions.get("panic") orelse
+            return self.loweringError("internal error: the runtime panic hook is not declared", .{});
+        const ptr_type = self.context.pointerType(0);
+        
+        var malloc_params = [_]llvm.TypeRef{self.context.i64Type()};
+        const malloc_type = llvm.functionType(ptr_type, &malloc_params, false);
+        const malloc_func = self.module.getOrAddFunction("malloc", malloc_type);
+        var malloc_args = [_]llvm.ValueRef{llvm.LLVMSizeOf(ty)};
+        const memory = self.builder.buildCall(malloc_type, malloc_func, &malloc_args, "env");
+        
+        const ok_block = llvm.appendBasicBlock(self.context, func, "alloc.ok");
+        const fail_block = llvm.appendBasicBlock(self.context, func, "alloc.fail");
+        const is_null = self.builder.buildICmp(.EQ, memory, llvm.constNull(self.context, ptr_type), "alloc.null");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, is_null, fail_block, ok_block);
+        
+        self.builder.positionAtEnd(fail_block);
+        var panic_args = [_]llvm.ValueRef{self.builder.buildGlobalStringPtr(ast.OUT_OF_MEMORY_PANIC, "oom.msg")};
+        _ = self.builder.buildCall(llvm.LLVMGlobalGetValueType(panic_func), panic_func, &panic_args, "");
+        _ = self.builder.buildUnreachable();
+        
+        self.builder.positionAtEnd(ok_block);
+        return memory;
+    }
+    
+    fn toLLVMType(self: *LLVMNativeBackend, paw_type: ast.Type) (error{LoweringFailed} || std.mem.Allocator.Error)!llvm.TypeRef {
+        // 🆕 单态化实例中的类型参数
+        return switch (layout.resolve(paw_type, self.current_bindings)) {
             // 🆕 v0.1.7: 完整的类型映射（支持 as 转换）
-            .i8, .u8, .bool, .char => self.context.i8Type(),
+            // 🆕 bool 与比较结果一致使用 i1，char 与字符字面量一致使用 i32（Unicode 标量值）
+            .bool => self.context.i1Type(),
+            .char => self.context.i32Type(),
+            .i8, .u8 => self.context.i8Type(),
             .i16, .u16 => self.context.i16Type(),
             .i32, .u32 => self.context.i32Type(),
             .i64, .u64 => self.context.i64Type(),
@@ -899,8 +2487,14 @@ pub const LLVMNativeBackend = struct {
             .f64 => self.context.doubleType(),
             .void => self.context.voidType(),
             .string => self.context.pointerType(0),
+            // 🆕 函数值是 { 环境, 函数 } 对（见 closure.zig）
+            .function => self.functionValueType(),
             
             .named => |name| blk: {
+                // 🆕 用户定义的结构体
+                if (self.type_decls.get(name)) |decl| {
+                    if (decl.kind == .struct_type) break :blk try self.structType(name);
+                }
                 if (std.mem.eql(u8, name, "i32") or std.mem.eql(u8, name, "int")) {
                     break :blk self.context.i32Type();
                 } else if (std.mem.eql(u8, name, "i64")) {
@@ -913,6 +2507,16 @@ pub const LLVMNativeBackend = struct {
                     break :blk self.context.i32Type(); // Default
                 }
             },
+            // 🆕 T::Item：绑定类型定义的关联类型
+            .projection => |proj| blk: {
+                const base = layout.resolve(proj.base.*, self.current_bindings);
+                const target = switch (base) {
+                    .named => |name| if (self.type_decls.get(name)) |decl| decl.assocType(proj.name, &.{}) else null,
+                    .generic_instance => |gi| if (self.type_decls.get(gi.name)) |decl| decl.assocType(proj.name, gi.type_args) else null,
+                    else => null,
+                } orelse return self.loweringError("associated type '{s}' cannot be resolved here", .{proj.name});
+                break :blk try self.toLLVMType(target);
+            },
             else => self.context.i32Type(), // Default
         };
     }
@@ -982,16 +2586,17 @@ pub const LLVMNativeBackend = struct {
     fn generateCast(
         self: *LLVMNativeBackend,
         value: llvm.ValueRef,
-        source_expr: *ast.Expr,
-        target_type: ast.Type,
+        source_type_in: ast.Type,
+        target_type_in: ast.Type,
         target_llvm_type: llvm.TypeRef,
     ) !llvm.ValueRef {
-        // 获取源类型（简化：从表达式推断）
-        const source_type = try self.inferExprType(source_expr.*);
-        
         const cast_name_z = try self.allocator.dupeZ(u8, "cast");
         defer self.allocator.free(cast_name_z);
         
+        // 🆕 char 表示为 32 位无符号整数，与整数之间按整数规则转换
+        const source_type = if (source_type_in == .char) ast.Type.u32 else source_type_in;
+        const target_type = if (target_type_in == .char) ast.Type.u32 else target_type_in;
+        
         // 判断源类型和目标类型的类别
         const is_source_int = self.isIntType(source_type);
         const is_source_float = self.isFloatType(source_type);
@@ -1052,37 +2657,151 @@ pub const LLVMNativeBackend = struct {
         } else if (source_type == .bool and is_target_int) {
             // bool -> 整数
             return self.builder.buildZExt(value, target_llvm_type, cast_name_z);
-        } else if (source_type == .char and is_target_int) {
-            // char -> 整数
-            const is_signed = self.isSignedIntType(target_type);
-            if (is_signed) {
-                return self.builder.buildSExt(value, target_llvm_type, cast_name_z);
-            } else {
-                return self.builder.buildZExt(value, target_llvm_type, cast_name_z);
-            }
-        } else if (is_source_int and target_type == .char) {
-            // 整数 -> char
-            return self.builder.buildTrunc(value, target_llvm_type, cast_name_z);
-        } else {
-            // 未知转换，返回原值
+        } else if (llvm.LLVMTypeOf(value) == target_llvm_type) {
+            // 表示相同（如 bool -> bool）
             return value;
+        } else {
+            // 🆕 不再静默返回原值
+            return self.loweringError("internal error: cannot convert '{s}' to '{s}'", .{ @tagName(source_type), @tagName(target_type) });
         }
     }
     
-    /// 推断表达式的类型（简化版）
-    fn inferExprType(self: *LLVMNativeBackend, expr: ast.Expr) !ast.Type {
-        _ = self;
+    /// 🆕 value 不是 Unicode 标量值时调用 prelude 的 panic（与 C 后端、解释器的行为一致）
+    fn buildCharCheck(self: *LLVMNativeBackend, value: llvm.ValueRef, source_type: ast.Type) !void {
+        const func = self.current_function orelse return error.NoCurrentFunction;
+        const panic_func = self.functions.get("panic") orelse {
+            return self.loweringError("internal error: 'panic' is needed to check a char conversion", .{});
+        };
+        
+        // 窄于 64 位的值先扩展到 i64，使负数按无符号比较时一定越界
+        const wide = if (self.getTypeBits(source_type) < 64)
+            try self.generateCast(value, source_type, .i64, self.context.i64Type())
+        else
+            value;
+        const wide_type = llvm.LLVMTypeOf(wide);
+        
+        // code < 0xD800 || (code - 0xE000) < (0x10FFFF - 0xE000 + 1)，均为无符号比较
+        const below_surrogates = self.builder.buildICmp(.ULT, wide, llvm.LLVMConstInt(wide_type, 0xD800, 0), "char.low");
+        const offset = self.builder.buildSub(wide, llvm.LLVMConstInt(wide_type, 0xE000, 0), "char.off");
+        const above_surrogates = self.builder.buildICmp(.ULT, offset, llvm.LLVMConstInt(wide_type, 0x10FFFF - 0xE000 + 1, 0), "char.high");
+        const valid = self.builder.buildOr(below_surrogates, above_surrogates, "char.valid");
+        
+        const ok_block = llvm.appendBasicBlock(self.context, func, "char.ok");
+        const fail_block = llvm.appendBasicBlock(self.context, func, "char.invalid");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, valid, ok_block, fail_block);
+        
+        self.builder.positionAtEnd(fail_block);
+        var args = [_]llvm.ValueRef{self.builder.buildGlobalStringPtr(ast.INVALID_CHAR_PANIC, "char.msg")};
+        _ = self.builder.buildCall(llvm.LLVMGlobalGetValueType(panic_func), panic_func, &args, "");
+        _ = self.builder.buildUnreachable();
+        
+        self.builder.positionAtEnd(ok_block);
+    }
+    
+    /// 🆕 index 不在 0..length 中时调用 prelude 的 panic，消息与 C 后端的 paw_index_check 相同
+    fn buildIndexCheck(self: *LLVMNativeBackend, index: llvm.ValueRef, index_type: ast.Type, length: usize) !void {
+        const func = self.current_function orelse return error.NoCurrentFunction;
+        const panic_func = self.functions.get("panic") orelse {
+            return self.loweringError("internal error: 'panic' is needed to check an array index", .{});
+        };
+        
+        // 扩展到 i64 后按无符号比较：负数一定越界
+        const i64_type = self.context.i64Type();
+        const wide = if (self.getTypeBits(index_type) < 64)
+            try self.generateCast(index, index_type, .i64, i64_type)
+        else
+            index;
+        const length_value = llvm.constI64(self.context, @intCast(length));
+        const in_bounds = self.builder.buildICmp(.ULT, wide, length_value, "index.ok");
+        
+        const ok_block = llvm.appendBasicBlock(self.context, func, "index.ok");
+        const fail_block = llvm.appendBasicBlock(self.context, func, "index.oob");
+        _ = llvm.LLVMBuildCondBr(self.builder.ref, in_bounds, ok_block, fail_block);
+        
+        // snprintf 到栈上的缓冲区后作为 panic 的消息
+        self.builder.positionAtEnd(fail_block);
+        const buffer_type = llvm.arrayType(self.context.i8Type(), 128);
+        const buffer = self.builder.buildAlloca(buffer_type, "index.msg");
+        const ptr_type = self.context.pointerType(0);
+        var snprintf_params = [_]llvm.TypeRef{ ptr_type, i64_type, ptr_type };
+        const snprintf_type = llvm.functionType(self.context.i32Type(), &snprintf_params, true);
+        const snprintf_func = self.module.getOrAddFunction("snprintf", snprintf_type);
+        var format_args = [_]llvm.ValueRef{
+            buffer,
+            llvm.constI64(self.context, 128),
+            self.builder.buildGlobalStringPtr(ast.INDEX_OUT_OF_BOUNDS_PANIC, "index.fmt"),
+            length_value,
+            wide,
+        };
+        _ = self.builder.buildCall(snprintf_type, snprintf_func, &format_args, "");
+        var panic_args = [_]llvm.ValueRef{buffer};
+        _ = self.builder.buildCall(llvm.LLVMGlobalGetValueType(panic_func), panic_func, &panic_args, "");
+        _ = self.builder.buildUnreachable();
+        
+        self.builder.positionAtEnd(ok_block);
+    }
+    
+    /// 表达式的 Paw 类型
+    /// 🆕 按变量、函数声明和运算规则还原（类型检查已通过），不再从 LLVM 值的类型猜测
+    fn inferExprType(self: *LLVMNativeBackend, expr: ast.Expr) (error{LoweringFailed} || std.mem.Allocator.Error)!ast.Type {
         return switch (expr) {
             .int_literal => ast.Type.i32,
             .float_literal => ast.Type.f64,
             .bool_literal => ast.Type.bool,
             .char_literal => ast.Type.char,
-            .string_literal => ast.Type.string,
-            .identifier => ast.Type.i32,  // 简化
+            .byte_literal => ast.Type.u8,
+            .string_literal, .string_interp => ast.Type.string,
+            .identifier => |name| blk: {
+                if (self.variable_paw_types.get(name)) |t| break :blk t;
+                if (self.constants.get(name)) |value| break :blk try self.inferExprType(value);
+                // 🆕 函数名作为值
+                if (self.function_decls.get(name)) |decl| break :blk try decl.valueType(self.type_arena.allocator());
+                break :blk ast.Type.i32;
+            },
+            .binary => |binop| switch (binop.op) {
+                .eq, .ne, .lt, .le, .gt, .ge, .and_op, .or_op => ast.Type.bool,
+                else => try self.operandType(binop.left.*, binop.right.*),
+            },
+            .unary => |unop| if (unop.op == .not) ast.Type.bool else try self.inferExprType(unop.operand.*),
+            .as_expr => |as_cast| as_cast.target_type,
+            .call => |call| blk: {
+                if (call.resolved_into.*) break :blk call.resolved_type_args.*[1];
+                if (try self.functionValueCallee(call.callee.*)) |func_type| break :blk func_type.function.return_type.*;
+                if (call.callee.* == .identifier) {
+                    if (self.function_decls.get(call.callee.identifier)) |decl| {
+                        // 🆕 泛型函数：返回类型按实例的类型实参替换
+                        if (decl.type_params.len > 0) {
+                            const instance = try self.genericInstance(decl, call.args, call.resolved_type_args.*) orelse break :blk ast.Type.i32;
+                            break :blk layout.resolve(decl.return_type, .{ .type_params = decl.type_params, .type_args = instance.type_args });
+                        }
+                        break :blk decl.return_type;
+                    }
+                }
+                break :blk ast.Type.i32;
+            },
+            .if_expr => |if_expr| blk: {
+                // 🆕 不会产生值的分支不决定 if 的类型
+                if (if_expr.then_branch.diverges()) {
+                    if (if_expr.else_branch) |else_br| break :blk try self.inferExprType(else_br.*);
+                }
+                break :blk try self.inferExprType(if_expr.then_branch.*);
+            },
+            .block => |stmts| blk: {
+                if (stmts.len == 0 or stmts[stmts.len - 1] != .expr) break :blk ast.Type.void;
+                break :blk try self.inferExprType(stmts[stmts.len - 1].expr);
+            },
+            .loop_expr => |loop| loop.result_type.*,
+            .struct_init => |si| ast.Type{ .named = si.type_name },
+            .field_access => |fa| (try self.structField(try self.inferExprType(fa.object.*), fa.field)).type,
+            .lambda => |lambda| try lambda.valueType(self.type_arena.allocator()),
             else => ast.Type.i32,
         };
     }
     
+    fn isNumericType(self: *LLVMNativeBackend, t: ast.Type) bool {
+        return self.isIntType(t) or self.isFloatType(t) or t == .bool or t == .char;
+    }
+    
     /// 检查是否是整数类型
     fn isIntType(_: *LLVMNativeBackend, t: ast.Type) bool {
         return switch (t) {
@@ -1111,9 +2830,10 @@ pub const LLVMNativeBackend = struct {
     /// 获取类型的位数
     fn getTypeBits(_: *LLVMNativeBackend, t: ast.Type) u32 {
         return switch (t) {
-            .i8, .u8, .bool, .char => 8,
+            .bool => 1,
+            .i8, .u8 => 8,
             .i16, .u16 => 16,
-            .i32, .u32, .f32 => 32,
+            .i32, .u32, .f32, .char => 32,
             .i64, .u64, .f64 => 64,
             .i128, .u128 => 128,
             else => 32,  // 默认
@@ -0,0 +1,207 @@
+//! LSP - 语言服务器（pawc lsp）
+//!
+//! 通过 stdin / stdout 收发 JSON-RPC 消息（每条消息带 Content-Length 头）。支持：
+//!   initialize / initialized / shutdown / exit
+//!   textDocument/didOpen、didChange（全量同步）、didClose：编辑器中的内容保存在 SourceMap 中
+//!   textDocument/documentSymbol：与 pawc query symbols 相同的文档大纲
+//!
+//! 其他请求返回 MethodNotFound，其他通知忽略。stdout 只用于协议，解析错误写到 stderr。
+
+const std = @import("std");
+const query = @import("query.zig");
+const Lexer = @import("lexer.zig").Lexer;
+const Parser = @import("parser.zig").Parser;
+const SourceMap = @import("source_map.zig").SourceMap;
+
+const INITIALIZE_RESULT =
+    \\{"capabilities":{"textDocumentSync":1,"documentSymbolProvider":true},"serverInfo":{"name":"pawc"}}
+;
+
+/// JSON-RPC 错误码
+const METHOD_NOT_FOUND = -32601;
+
+/// 运行服务器直到收到 exit 或 stdin 关闭；返回进程退出码（exit 之前收到过 shutdown 时为 0）
+pub fn run(allocator: std.mem.Allocator) !u8 {
+    var server = Server.init(allocator);
+    defer server.deinit();
+
+    const stdin = std.fs.File.stdin();
+    var pending = std.ArrayList(u8){};
+    defer pending.deinit(allocator);
+    var chunk: [4096]u8 = undefined;
+
+    while (true) {
+        while (try takeMessage(allocator, &pending)) |body| {
+            defer allocator.free(body);
+            if (!try server.handle(body)) return if (server.shutdown_requested) 0 else 1;
+        }
+        const n = try stdin.read(&chunk);
+        if (n == 0) return 1;
+        try pending.appendSlice(allocator, chunk[0..n]);
+    }
+}
+
+/// 从缓冲区取出一条完整的消息体（调用者释放）；消息还不完整时返回 null
+fn takeMessage(allocator: std.mem.Allocator, pending: *std.ArrayList(u8)) !?[]u8 {
+    const header_end = std.mem.indexOf(u8, pending.items, "\r\n\r\n") orelse return null;
+    var content_length: ?usize = null;
+    var lines = std.mem.splitSequence(u8, pending.items[0..header_end], "\r\n");
+    while (lines.next()) |line| {
+        const colon = std.mem.indexOfScalar(u8, line, ':') orelse continue;
+        if (!std.ascii.eqlIgnoreCase(line[0..colon], "Content-Length")) continue;
+        content_length = try std.fmt.parseInt(usize, std.mem.trim(u8, line[colon + 1 ..], " "), 10);
+    }
+    const length = content_length orelse return error.MissingContentLength;
+
+    const body_start = header_end + 4;
+    if (pending.items.len < body_start + length) return null;
+    const body = try allocator.dupe(u8, pending.items[body_start .. body_start + length]);
+
+    const rest = pending.items[body_start + length ..];
+    std.mem.copyForwards(u8, pending.items[0..rest.len], rest);
+    pending.shrinkRetainingCapacity(rest.len);
+    return body;
+}
+
+const Server = struct {
+    allocator: std.mem.Allocator,
+    documents: SourceMap,  // 文档 URI -> 编辑器中的内容
+    shutdown_requested: bool,
+
+    fn init(allocator: std.mem.Allocator) Server {
+        return Server{
+            .allocator = allocator,
+            .documents = SourceMap.init(allocator),
+            .shutdown_requested = false,
+        };
+    }
+
+    fn deinit(self: *Server) void {
+        self.documents.deinit();
+    }
+
+    /// 处理一条消息；返回 false 表示应退出
+    fn handle(self: *Server, body: []const u8) !bool {
+        var parsed = std.json.parseFromSlice(std.json.Value, self.allocator, body, .{}) catch {
+            std.debug.print("pawc lsp: ignoring a message that is not valid JSON\n", .{});
+            return true;
+        };
+        defer parsed.deinit();
+        if (parsed.value != .object) return true;
+        const message = parsed.value.object;
+
+        // 客户端发来的响应（没有 method）忽略
+        const method = stringField(message.get("method"), &.{}) orelse return true;
+        const id = message.get("id");
+        const params = message.get("params");
+
+        if (std.mem.eql(u8, method, "initialize")) {
+            try self.respond(id, INITIALIZE_RESULT);
+        } else if (std.mem.eql(u8, method, "shutdown")) {
+            self.shutdown_requested = true;
+            try self.respond(id, "null");
+        } else if (std.mem.eql(u8, method, "exit")) {
+            return false;
+        } else if (std.mem.eql(u8, method, "textDocument/didOpen")) {
+            const uri = stringField(params, &.{ "textDocument", "uri" }) orelse return true;
+            const text = stringField(params, &.{ "textDocument", "text" }) orelse return true;
+            _ = try self.documents.addFile(uri, text);
+        } else if (std.mem.eql(u8, method, "textDocument/didChange")) {
+            // 全量同步：最后一个变更就是完整的新内容
+            const uri = stringField(params, &.{ "textDocument", "uri" }) orelse return true;
+            const changes = field(params, &.{"contentChanges"}) orelse return true;
+            if (changes != .array or changes.array.items.len == 0) return true;
+            const last = changes.array.items[changes.array.items.len - 1];
+            const text = stringField(last, &.{"text"}) orelse return true;
+            _ = try self.documents.addFile(uri, text);
+        } else if (std.mem.eql(u8, method, "textDocument/didClose")) {
+            // 内容保留：再次打开时会被 didOpen 覆盖
+        } else if (std.mem.eql(u8, method, "textDocument/documentSymbol")) {
+            const uri = stringField(params, &.{ "textDocument", "uri" }) orelse "";
+            const result = try self.documentSymbols(uri);
+            defer self.allocator.free(result);
+            try self.respond(id, result);
+        } else if (id != null) {
+            try self.respondError(id, METHOD_NOT_FOUND, "method not found");
+        }
+        return true;
+    }
+
+    /// 文档大纲的 JSON；文档没有打开或无法解析时返回空数组
+    fn documentSymbols(self: *Server, uri: []const u8) ![]const u8 {
+        const file_id = self.documents.findByName(uri) orelse return self.allocator.dupe(u8, "[]");
+        const file = self.documents.get(file_id);
+
+        var lexer = Lexer.init(self.allocator, file.contents, uriPath(uri));
+        defer lexer.deinit();
+        const tokens = lexer.tokenize() catch return self.allocator.dupe(u8, "[]");
+
+        var parser = Parser.init(self.allocator, tokens);
+        defer parser.deinit();
+        const program = parser.parse() catch return self.allocator.dupe(u8, "[]");
+
+        var arena = std.heap.ArenaAllocator.init(self.allocator);
+        defer arena.deinit();
+        const symbols = try query.documentSymbols(arena.allocator(), file, tokens, program);
+        return query.encodeSymbolsJson(self.allocator, symbols);
+    }
+
+    fn respond(self: *Server, id: ?std.json.Value, result: []const u8) !void {
+        var buf = std.ArrayList(u8){};
+        defer buf.deinit(self.allocator);
+        try buf.appendSlice(self.allocator, "{\"jsonrpc\":\"2.0\",\"id\":");
+        try appendId(&buf, self.allocator, id);
+        try buf.appendSlice(self.allocator, ",\"result\":");
+        try buf.appendSlice(self.allocator, result);
+        try buf.append(self.allocator, '}');
+        try send(buf.items);
+    }
+
+    fn respondError(self: *Server, id: ?std.json.Value, code: i32, text: []const u8) !void {
+        var buf = std.ArrayList(u8){};
+        defer buf.deinit(self.allocator);
+        try buf.appendSlice(self.allocator, "{\"jsonrpc\":\"2.0\",\"id\":");
+        try appendId(&buf, self.allocator, id);
+        try buf.writer(self.allocator).print(",\"error\":{{\"code\":{d},\"message\":", .{code});
+        try query.appendJsonString(&buf, self.allocator, text);
+        try buf.appendSlice(self.allocator, "}}");
+        try send(buf.items);
+    }
+};
+
+fn send(body: []const u8) !void {
+    const stdout = std.fs.File.stdout();
+    var header: [64]u8 = undefined;
+    try stdout.writeAll(try std.fmt.bufPrint(&header, "Content-Length: {d}\r\n\r\n", .{body.len}));
+    try stdout.writeAll(body);
+}
+
+fn appendId(buf: *std.ArrayList(u8), allocator: std.mem.Allocator, id: ?std.json.Value) !void {
+    const value = id orelse return buf.appendSlice(allocator, "null");
+    switch (value) {
+        .integer => |n| try buf.writer(allocator).print("{d}", .{n}),
+        .string => |s| try query.appendJsonString(buf, allocator, s),
+        else => try buf.appendSlice(allocator, "null"),
+    }
+}
+
+/// 沿对象路径取值（path 为空时返回 value 本身）
+fn field(value: ?std.json.Value, path: []const []const u8) ?std.json.Value {
+    var current = value orelse return null;
+    for (path) |key| {
+        if (current != .object) return null;
+        current = current.object.get(key) orelse return null;
+    }
+    return current;
+}
+
+fn stringField(value: ?std.json.Value, path: []const []const u8) ?[]const u8 {
+    const v = field(value, path) orelse return null;
+    return if (v == .string) v.string else null;
+}
+
+/// file:///home/a.paw -> /home/a.paw（诊断和 __FILE__ 使用路径而不是 URI）
+fn uriPath(uri: []const u8) []const u8 {
+    const prefix = "file://";
+    return if (std.mem.startsWith(u8, uri, prefix)) uri[prefix.len..] else uri;
+}
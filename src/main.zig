@@ -1,12 +1,44 @@
 const std = @import("std");
 const Lexer = @import("lexer.zig").Lexer;
+const Token = @import("token.zig").Token;
 const Parser = @import("parser.zig").Parser;
 const TypeChecker = @import("typechecker.zig").TypeChecker;
 const CodeGen = @import("codegen.zig").CodeGen;
-const CBackend = @import("c_backend.zig").CBackend;
+const c_backend_mod = @import("c_backend.zig");
+const CBackend = c_backend_mod.CBackend;
 const ModuleLoader = @import("module.zig").ModuleLoader;
+const ExportIndex = @import("module.zig").ExportIndex;  // 🆕 未知函数的 import 建议
+const SourceMap = @import("source_map.zig").SourceMap;  // 🆕 增量源文件表
+const query = @import("query.zig");  // 🆕 编辑器查询（highlight 等）
+const lsp = @import("lsp.zig");  // 🆕 语言服务器（pawc lsp）
+const nm = @import("nm.zig");  // 🆕 目标文件符号查看
+const interpreter = @import("interpreter.zig");  // 🆕 AST 解释器（--interpret）
+const selftest = @import("selftest.zig");  // 🆕 解释器 vs 原生后端差分测试
+const abitest = @import("abitest.zig");  // 🆕 编译器与运行时的 golden ABI 测试
+const irtest = @import("irtest.zig");  // 🆕 按函数检查生成的 IR（FileCheck 风格指令和快照）
+const testrunner = @import("testrunner.zig");  // 🆕 运行 @test 函数（pawc test）
+const unused_pub = @import("unused_pub.zig");  // 🆕 库中未被使用的 pub 项
+const bench = @import("bench.zig");  // 🆕 编译器性能基准
+const ast_printer = @import("ast_printer.zig");  // 🆕 语法树打印（pawc ast）
+const grammar = @import("grammar.zig");  // 🆕 语法导出（pawc grammar）
+const messages = @import("messages.zig");  // 🆕 诊断消息本地化（--locale / PAW_LANG）
+const run_cmd = @import("run.zig");  // 🆕 构建并运行（pawc run）
+const limits_mod = @import("limits.zig");  // 🆕 编译资源限制（Paw.toml [limits]）
+const cache_mod = @import("cache.zig");  // 🆕 增量编译缓存
+const upgrade = @import("upgrade.zig");  // 🆕 编译器自我更新（pawc upgrade）
+const bugreport = @import("bugreport.zig");  // 🆕 问题报告打包（pawc report-bug）
+const build_lock = @import("build_lock.zig");  // 🆕 构建目录锁与原子输出
+const trace_mod = @import("trace.zig");  // 🆕 执行跟踪（--instrument=trace / pawc trace view）
+const toolchain = @import("toolchain.zig");  // 🆕 C 编译器探测（--linker）与 pawc doctor
 const ast_mod = @import("ast.zig");
 const REPL = @import("repl.zig").REPL;  // 🆕 v0.1.9
+const api = @import("api.zig");
+const package = @import("package.zig");
+const CancelToken = @import("cancel.zig").CancelToken;  // 🆕 编译取消 / 看门狗
+const ResolutionTrace = @import("resolution_trace.zig").ResolutionTrace;  // 🆕 --explain-overloads
+const type_alias = @import("type_alias.zig");  // 🆕 类型别名展开
+const track_caller = @import("track_caller.zig");  // 🆕 @track_caller 调用位置参数
+const consteval = @import("consteval.zig");  // 🆕 编译期求值（合并导入之后）
 
 const builtin = @import("builtin");
 const build_options = @import("build_options");
@@ -19,6 +51,12 @@ const llvm_backend = if (llvm_available) @import("llvm_native_backend.zig") else
 };
 const LLVMNativeBackend = llvm_backend.LLVMNativeBackend;
 const LLVMOptLevel = llvm_backend.OptLevel; // 🆕 v0.1.7
+// 🆕 热重载开发服务器（基于 LLVM JIT）
+const dev = if (llvm_available) @import("dev.zig") else struct {
+    pub fn run(_: std.mem.Allocator, _: [:0]const u8, _: []const [:0]const u8) !u8 {
+        unreachable;
+    }
+};
 
 const VERSION = "0.1.9-dev";
 
@@ -88,84 +126,590 @@ const OptLevel = enum {
     }
 };
 
-// 🆕 check command: type checking only
-fn checkFile(allocator: std.mem.Allocator, source_file: []const u8) !void {
+// 🆕 check 和编译共用的前端选项
+const FrontEndOptions = struct {
+    locked: bool = false,
+    fail_fast: bool = false,
+    const_eval_steps: u64,
+    cancel_token: ?*const CancelToken = null,  // --time-limit
+    timer: ?*CompilationTimer = null,          // --time
+    perf_start: ?i128 = null,                  // --verbose：[PERF] 输出的起始时间
+};
+
+// 🆕 check 和编译共用的前端：合并 prelude、词法和语法分析、Paw.toml 依赖、导入展开，
+// 以及合并导入之后的类型别名、@track_caller 和编译期求值。类型检查由调用者配置。
+// 字段之间互相引用（parser 引用 lexer 的 tokens，AST 分配在 parser 的 arena 中），所以原地初始化
+const FrontEnd = struct {
+    allocator: std.mem.Allocator,
+    combined_source: []u8,
+    lexer: Lexer,
+    tokens: []Token,
+    parser: Parser,
+    module_loader: ModuleLoader,
+    export_index: ExportIndex,
+    resolved: ModuleLoader.ResolvedImports,
+    ast: ast_mod.Program,
+
+    /// 依赖解析失败时打印错误并返回 error.DependencyResolutionFailed；
+    /// --fail-fast 下导入出错时返回 error.ImportFailed
+    fn load(self: *FrontEnd, allocator: std.mem.Allocator, source_file: []const u8, source: []const u8, options: FrontEndOptions) !void {
+        self.allocator = allocator;
+        
+        // 🆕 0. 自动加载标准库 prelude（嵌入到可执行文件中）
+        // 🆕 v0.1.8: 按 prelude 行数偏移行号，诊断指向用户文件的行
+        const prelude_source = @embedFile("prelude/prelude.paw");
+        self.combined_source = try std.fmt.allocPrint(allocator, "{s}\n\n{s}", .{prelude_source, source});
+        errdefer allocator.free(self.combined_source);
+        
+        // 1. Lexical analysis
+        const lexer_start = std.time.milliTimestamp();
+        self.lexer = Lexer.init(allocator, self.combined_source, source_file);
+        errdefer self.lexer.deinit();
+        self.lexer.setLineOffset(std.mem.count(u8, prelude_source, "\n") + 2);  // 加上分隔的两个换行符
+        self.tokens = try self.lexer.tokenize();
+        if (options.timer) |timer| timer.lexer_time = std.time.milliTimestamp() - lexer_start;
+        if (options.perf_start) |perf_start| {
+            std.debug.print("[PERF] Lexical analysis: {d}μs\n", .{@divTrunc(std.time.nanoTimestamp() - perf_start, 1000)});
+        }
+        
+        // 2. Parsing（AST 内存由 parser.arena 管理，parser.deinit() 时释放）
+        const parser_start = std.time.milliTimestamp();
+        self.parser = Parser.init(allocator, self.tokens);
+        errdefer self.parser.deinit();
+        self.parser.cancel_token = options.cancel_token;
+        const parsed = try self.parser.parse();
+        if (options.timer) |timer| timer.parser_time = std.time.milliTimestamp() - parser_start;
+        if (options.perf_start) |perf_start| {
+            std.debug.print("[PERF] Parsing: {d}μs\n", .{@divTrunc(std.time.nanoTimestamp() - perf_start, 1000)});
+        }
+        
+        // 2.5. 🆕 处理导入（模块系统），包括 Paw.toml 依赖
+        self.module_loader = ModuleLoader.init(allocator);
+        errdefer self.module_loader.deinit();
+        package.registerDependencies(allocator, &self.module_loader, options.locked) catch |err| {
+            std.debug.print("Error: Dependency resolution failed: {any}\n", .{err});
+            return error.DependencyResolutionFailed;
+        };
+        
+        // 🆕 项目及依赖的导出名称索引（仅在报错时才真正扫描）
+        self.export_index = ExportIndex.init(allocator);
+        errdefer self.export_index.deinit();
+        try self.export_index.addRoots(".", &self.module_loader);
+        
+        // 🆕 分离编译需要区分本文件声明（own）和导入声明（imported）
+        self.resolved = try self.module_loader.resolveImports(allocator, self.parser.arena.allocator(), parsed.declarations);
+        errdefer self.resolved.deinit(allocator);
+        
+        // 🆕 --fail-fast: 导入阶段出错则不再继续类型检查和代码生成
+        if (options.fail_fast and self.resolved.errors > 0) {
+            std.debug.print("\x1b[1;31merror\x1b[0m: aborting due to {d} import error(s) (--fail-fast)\n", .{self.resolved.errors});
+            return error.ImportFailed;
+        }
+        
+        // 包含导入声明的 AST；deinit 只释放 declarations 数组，内容属于 parser 或 module_loader
+        self.ast = ast_mod.Program{ .declarations = try self.resolved.all.toOwnedSlice(allocator) };
+        errdefer allocator.free(self.ast.declarations);
+        
+        // 🆕 导入的类型别名和 @track_caller 函数：解析本文件时还看不到，合并导入声明后再处理一次
+        if (self.resolved.imported.items.len > 0) {
+            try type_alias.expandProgram(self.parser.arena.allocator(), self.ast);
+            try track_caller.lowerProgram(self.parser.arena.allocator(), self.ast);
+        }
+        
+        // 🆕 编译期求值：const 初始化表达式和 [T; expr] 数组长度（可以调用导入的 const fn）
+        // 分离编译使用 own / imported 中的副本，副本里内联的数组类型也要写回长度
+        try consteval.evaluateMerged(self.parser.arena.allocator(), self.ast, &.{ self.resolved.own.items, self.resolved.imported.items }, options.const_eval_steps);
+        
+        if (options.perf_start) |perf_start| {
+            std.debug.print("[PERF] Module resolution: {d}μs\n", .{@divTrunc(std.time.nanoTimestamp() - perf_start, 1000)});
+        }
+    }
+
+    fn deinit(self: *FrontEnd) void {
+        self.allocator.free(self.ast.declarations);
+        self.resolved.deinit(self.allocator);
+        self.export_index.deinit();
+        self.module_loader.deinit();
+        self.parser.deinit();
+        self.lexer.deinit();
+        self.allocator.free(self.combined_source);
+    }
+};
+
+// 🆕 check 选项
+const CheckOptions = struct {
+    allow_asm: bool = false,
+    max_errors: ?usize = null,
+    fail_fast: bool = false,
+    locked: bool = false,
+};
+
+// 🆕 check command: 与编译相同的前端（FrontEnd），但不生成代码、不调用 C 编译器；
+// 只输出诊断，供编辑器集成和 CI 使用。返回是否没有错误
+fn checkFile(allocator: std.mem.Allocator, source_file: []const u8, options: CheckOptions) !bool {
     std.debug.print("🔍 Checking: {s}\n", .{source_file});
     
-    const source = std.fs.cwd().readFileAlloc(allocator, source_file, 1024 * 1024) catch |err| {
+    const source = std.fs.cwd().readFileAlloc(allocator, source_file, 10 * 1024 * 1024) catch |err| {
         std.debug.print("Error: Cannot read file {s}: {any}\n", .{source_file, err});
-        return;
+        return false;
     };
     defer allocator.free(source);
+    const project_limits = package.loadLimits(allocator) catch return false;
+    
+    var front_end: FrontEnd = undefined;
+    front_end.load(allocator, source_file, source, .{
+        .locked = options.locked,
+        .fail_fast = options.fail_fast,
+        .const_eval_steps = project_limits.const_eval_steps,
+    }) catch |err| {
+        if (err == error.OutOfMemory) return err;
+        return false;
+    };
+    defer front_end.deinit();
     
-    // Load standard library
-    const prelude_source = @embedFile("prelude/prelude.paw");
-    const combined_source = try std.fmt.allocPrint(allocator, "{s}\n\n{s}", .{prelude_source, source});
-    defer allocator.free(combined_source);
+    // Type checking
+    var type_checker = TypeChecker.init(allocator, front_end.tokens);
+    defer type_checker.deinit();
+    type_checker.export_index = &front_end.export_index;
+    var source_map = SourceMap.init(allocator);
+    defer source_map.deinit();
+    _ = try source_map.addFile(source_file, source);
+    type_checker.source_map = &source_map;
+    type_checker.allow_asm = options.allow_asm;
+    type_checker.max_errors = options.max_errors;
+    type_checker.fail_fast = options.fail_fast;
+    type_checker.check(front_end.ast) catch |err| {
+        if (err == error.OutOfMemory) return err;
+        return false;
+    };
+    if (front_end.resolved.errors > 0) return false;
     
-    // Lexical analysis
-    var lexer = Lexer.init(allocator, combined_source, source_file);
+    std.debug.print("✅ Type checking passed!\n", .{});
+    return true;
+}
+
+// 🆕 pawc new <name>：创建项目目录并生成 Paw.toml 和 main.paw
+fn newProject(allocator: std.mem.Allocator, path: []const u8) !void {
+    std.fs.cwd().access(path, .{}) catch |err| switch (err) {
+        error.FileNotFound => {
+            try initProject(allocator, path);
+            return;
+        },
+        else => return err,
+    };
+    std.debug.print("Error: destination '{s}' already exists\n", .{path});
+    std.debug.print("💡 Tip: use `pawc init {s}` to initialize an existing directory\n", .{path});
+    std.process.exit(1);
+}
+
+// 🆕 pawc init [<dir>]：在已有（或新建的）目录中生成项目，不覆盖已有的 main.paw
+fn initProject(allocator: std.mem.Allocator, path: []const u8) !void {
+    // 项目名取目录名（"." 时取当前目录的名字）；在创建任何目录之前检查
+    const real_path: ?[]u8 = std.fs.cwd().realpathAlloc(allocator, path) catch |err| switch (err) {
+        error.FileNotFound => null,
+        else => return err,
+    };
+    defer if (real_path) |p| allocator.free(p);
+    const project_name = std.fs.path.basename(real_path orelse path);
+    if (!package.isValidPackageName(project_name)) {
+        std.debug.print("Error: '{s}' is not a valid package name (use letters, digits, '_' and '-')\n", .{project_name});
+        std.process.exit(1);
+    }
+
+    try std.fs.cwd().makePath(path);
+    var dir = try std.fs.cwd().openDir(path, .{});
+    defer dir.close();
+
+    if (dir.access(package.MANIFEST_FILE, .{})) |_| {
+        std.debug.print("Error: {s} already exists in '{s}'\n", .{ package.MANIFEST_FILE, path });
+        std.process.exit(1);
+    } else |_| {}
+
+    std.debug.print("📦 Creating project: {s}\n", .{project_name});
+
+    // Paw.toml
+    const manifest_content = try std.fmt.allocPrint(allocator,
+        \\[package]
+        \\name = "{s}"
+        \\version = "0.1.0"
+        \\entry = "{s}"
+        \\
+        \\[dependencies]
+        \\
+    , .{ project_name, run_cmd.PROJECT_ENTRY });
+    defer allocator.free(manifest_content);
+    try dir.writeFile(.{ .sub_path = package.MANIFEST_FILE, .data = manifest_content });
+
+    // main.paw（已有时保留）
+    if (dir.access(run_cmd.PROJECT_ENTRY, .{})) |_| {
+        std.debug.print("   {s} already exists, keeping it\n", .{run_cmd.PROJECT_ENTRY});
+    } else |_| {
+        const main_content = try std.fmt.allocPrint(allocator,
+            \\// {s} - Paw Project
+            \\
+            \\fn main() -> i32 {{
+            \\    println("Hello from {s}!");
+            \\    return 0;
+            \\}}
+            \\
+        , .{ project_name, project_name });
+        defer allocator.free(main_content);
+        try dir.writeFile(.{ .sub_path = run_cmd.PROJECT_ENTRY, .data = main_content });
+    }
+
+    // 构建产物不进版本库
+    if (dir.access(".gitignore", .{})) |_| {} else |_| {
+        try dir.writeFile(.{ .sub_path = ".gitignore", .data = run_cmd.TARGET_DIR ++ "/\n" });
+    }
+
+    std.debug.print("✅ Project created successfully!\n", .{});
+    std.debug.print("\nNext steps:\n", .{});
+    if (!std.mem.eql(u8, path, ".")) std.debug.print("  cd {s}\n", .{path});
+    std.debug.print("  pawc run\n", .{});
+}
+
+// 🆕 分离编译：主文件和每个导入模块各生成一个 C 编译单元
+fn compileModulesSeparately(
+    allocator: std.mem.Allocator,
+    own_declarations: []ast_mod.TopLevelDecl,
+    imported_declarations: []ast_mod.TopLevelDecl,
+    module_loader: *ModuleLoader,
+    output_name: []const u8,
+    stack_size: ?u64,
+    limits: limits_mod.Limits,
+    build_plan: ?*const cache_mod.Plan,  // 🆕 增量编译：未变化的单元直接复用目标文件
+    optimize: bool,  // 🆕 -O：优化编译，并跨模块内联小函数
+    linker: ?toolchain.Linker,  // 🆕 已选择的 C 编译器
+) !void {
+    var units = std.ArrayList(CBackend.Unit){};
+    defer {
+        for (units.items) |unit| {
+            allocator.free(unit.name);
+            allocator.free(unit.code);
+        }
+        units.deinit(allocator);
+    }
+    
+    // 主编译单元：导入项只生成原型
+    if (build_plan != null and build_plan.?.find("main").?.fresh) {
+        try units.append(allocator, .{ .name = try allocator.dupe(u8, "main"), .code = "", .cached = true });
+    } else {
+        var codegen = CodeGen.init(allocator);
+        defer codegen.deinit();
+        codegen.stack_size = stack_size;
+        codegen.limits = limits;
+        codegen.inline_external = optimize;
+        const code = try codegen.generateUnit(
+            ast_mod.Program{ .declarations = own_declarations },
+            imported_declarations,
+            false,
+        );
+        if (!limits_mod.checkObjectSize(allocator, "main", code.len, limits)) std.process.exit(1);
+        try units.append(allocator, .{ .name = try allocator.dupe(u8, "main"), .code = code });
+    }
+    
+    // 模块编译单元：私有函数使用 static，主文件声明（含 prelude）和模块自己导入的声明只生成原型。
+    // 间接导入的模块（main → a → b 中的 b）也各生成一个单元
+    try module_loader.loadTransitiveImports();
+    var import_arena = std.heap.ArenaAllocator.init(allocator);
+    defer import_arena.deinit();
+    var it = module_loader.modules.iterator();
+    while (it.next()) |entry| {
+        const module = entry.value_ptr;
+        
+        var module_imports = try module_loader.resolveImports(allocator, import_arena.allocator(), module.declarations);
+        defer module_imports.deinit(allocator);
+        if (module_imports.errors > 0) return error.ImportFailed;
+        var external = std.ArrayList(ast_mod.TopLevelDecl){};
+        defer external.deinit(allocator);
+        try external.appendSlice(allocator, module_imports.imported.items);
+        try external.appendSlice(allocator, own_declarations);
+        
+        const unit_name = try cache_mod.unitName(allocator, module.path);
+        errdefer allocator.free(unit_name);
+        
+        const fresh = if (build_plan) |p| if (p.find(unit_name)) |planned| planned.fresh else false else false;
+        if (fresh) {
+            try units.append(allocator, .{ .name = unit_name, .code = "", .cached = true });
+            continue;
+        }
+        
+        var codegen = CodeGen.init(allocator);
+        defer codegen.deinit();
+        codegen.limits = limits;
+        codegen.inline_external = optimize;
+        const code = try codegen.generateUnit(
+            ast_mod.Program{ .declarations = module.declarations },
+            external.items,
+            true,
+        );
+        if (!limits_mod.checkObjectSize(allocator, module.path, code.len, limits)) std.process.exit(1);
+        try units.append(allocator, .{ .name = unit_name, .code = code });
+    }
+    
+    var c_backend = CBackend.init(allocator);
+    c_backend.stack_size = stack_size;
+    c_backend.optimize = optimize;
+    c_backend.linker = linker;
+    if (build_plan != null) c_backend.object_dir = cache_mod.CACHE_DIR;
+    try c_backend.compileUnits(units.items, output_name);
+    if (build_plan) |p| try p.commit();
+}
+
+// 🆕 分离编译并按需运行（--split-modules）
+fn buildSplitModules(
+    allocator: std.mem.Allocator,
+    own_declarations: []ast_mod.TopLevelDecl,
+    imported_declarations: []ast_mod.TopLevelDecl,
+    module_loader: *ModuleLoader,
+    output_name: []const u8,
+    stack_size: ?u64,
+    limits: limits_mod.Limits,
+    build_plan: ?*const cache_mod.Plan,
+    optimize: bool,
+    linker: ?toolchain.Linker,
+    should_run: bool,
+    verbose: bool,
+) !void {
+    try compileModulesSeparately(
+        allocator,
+        own_declarations,
+        imported_declarations,
+        module_loader,
+        output_name,
+        stack_size,
+        limits,
+        build_plan,
+        optimize,
+        linker,
+    );
+    if (should_run) {
+        build_lock.release();  // 🆕 程序运行期间不阻塞其他构建
+        const run_path = try std.fmt.allocPrint(allocator, "./{s}", .{output_name});
+        defer allocator.free(run_path);
+        var run_child = std.process.Child.init(&[_][]const u8{run_path}, allocator);
+        const run_result = try run_child.spawnAndWait();
+        if (verbose) {
+            std.debug.print("Exit code: {any}\n", .{run_result});
+        }
+        // 与 pawc run 一样以程序的退出码退出
+        const exit_code = run_cmd.exitCode(run_result);
+        if (exit_code != 0) std.process.exit(exit_code);
+    }
+}
+
+// 🆕 api command: 生成模块公共 API 元数据（.pawm）
+fn emitApiMetadata(allocator: std.mem.Allocator, source_file: []const u8, output_file: ?[]const u8) !void {
+    const source = std.fs.cwd().readFileAlloc(allocator, source_file, 10 * 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read file {s}: {any}\n", .{source_file, err});
+        return;
+    };
+    defer allocator.free(source);
+    
+    var lexer = Lexer.init(allocator, source, source_file);
     defer lexer.deinit();
     const tokens = try lexer.tokenize();
     
-    // Parsing
     var parser = Parser.init(allocator, tokens);
     defer parser.deinit();
-    const ast = try parser.parse();
+    const program = try parser.parse();
+    try consteval.evaluateProgram(parser.arena.allocator(), program, consteval.MAX_STEPS);  // 签名中的 [T; N]
     
-    // Type checking
-    var type_checker = TypeChecker.init(allocator, source_file, tokens);
-    defer type_checker.deinit();
-    try type_checker.check(ast);
+    var table = try api.ApiTable.fromProgram(allocator, program);
+    defer table.deinit();
+    const metadata = try table.serialize(allocator);
+    defer allocator.free(metadata);
     
-    std.debug.print("✅ Type checking passed!\n", .{});
+    // 默认输出：lib.paw -> lib.pawm
+    const default_output = try std.fmt.allocPrint(allocator, "{s}m", .{source_file});
+    defer allocator.free(default_output);
+    const output_path = output_file orelse default_output;
+    
+    const file = try std.fs.cwd().createFile(output_path, .{});
+    defer file.close();
+    try file.writeAll(metadata);
+    
+    std.debug.print("✅ API metadata: {s} -> {s} ({d} public items)\n", .{source_file, output_path, table.items.count()});
 }
 
-// 🆕 init command: create new project
-fn initProject(allocator: std.mem.Allocator, project_name: []const u8) !void {
-    std.debug.print("📦 Creating project: {s}\n", .{project_name});
+// 🆕 api-diff command: 比较两次构建的公共 API
+fn diffApiMetadata(allocator: std.mem.Allocator, old_file: []const u8, new_file: []const u8) !void {
+    const old_text = std.fs.cwd().readFileAlloc(allocator, old_file, 10 * 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read file {s}: {any}\n", .{old_file, err});
+        return;
+    };
+    defer allocator.free(old_text);
+    const new_text = std.fs.cwd().readFileAlloc(allocator, new_file, 10 * 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read file {s}: {any}\n", .{new_file, err});
+        return;
+    };
+    defer allocator.free(new_text);
     
-    // Create project directory
-    std.fs.cwd().makeDir(project_name) catch |err| {
-        std.debug.print("Error: Cannot create directory: {any}\n", .{err});
+    var old_table = api.ApiTable.parse(allocator, old_text) catch |err| {
+        std.debug.print("Error: {s} is not a valid .pawm file: {any}\n", .{old_file, err});
         return;
     };
+    defer old_table.deinit();
+    var new_table = api.ApiTable.parse(allocator, new_text) catch |err| {
+        std.debug.print("Error: {s} is not a valid .pawm file: {any}\n", .{new_file, err});
+        return;
+    };
+    defer new_table.deinit();
     
-    // Create main.paw
-    const main_content =
-        \\// {s} - Paw Project
-        \\
-        \\fn main() -> i32 {{
-        \\    println("Hello from {s}!");
-        \\    return 0;
-        \\}}
-        \\
-    ;
+    std.debug.print("🔍 API diff: {s} -> {s}\n\n", .{old_file, new_file});
+    const summary = try api.diff(allocator, &old_table, &new_table);
     
-    const main_path = try std.fmt.allocPrint(allocator, "{s}/main.paw", .{project_name});
-    defer allocator.free(main_path);
+    std.debug.print("\n{d} added, {d} removed, {d} changed\n", .{summary.added, summary.removed, summary.changed});
+    if (summary.inline_changed > 0) {
+        std.debug.print("{d} inline bodies changed: modules that import this one must be rebuilt\n", .{summary.inline_changed});
+    }
+    if (summary.isBreaking()) {
+        std.debug.print("⚠️  Breaking changes detected\n", .{});
+        std.process.exit(1);
+    }
+    std.debug.print("✅ No breaking changes\n", .{});
+}
+
+// 🆕 --explain-overloads: 文本输出到 stderr，或以 JSON 写入文件
+fn emitResolutionTrace(allocator: std.mem.Allocator, trace: *const ResolutionTrace, output_file: ?[]const u8) !void {
+    const path = output_file orelse {
+        trace.print();
+        return;
+    };
+    const json = try trace.toJson(allocator);
+    defer allocator.free(json);
+    std.fs.cwd().writeFile(.{ .sub_path = path, .data = json }) catch |err| {
+        std.debug.print("Error: Cannot write {s}: {any}\n", .{path, err});
+        return;
+    };
+    std.debug.print("📝 Call resolution trace written to {s} ({d} call(s))\n", .{path, trace.resolutions.items.len});
+}
+
+// 🆕 query impls / missing-impls：基于类型检查器收集的 trait 表和类型方法表
+// 返回 false 表示 trait 不存在，或（missing-impls）有类型没有完整实现 trait
+fn runImplsQuery(allocator: std.mem.Allocator, trait_name: []const u8, type_list: ?[]const u8, source_file: []const u8) !bool {
+    const source = std.fs.cwd().readFileAlloc(allocator, source_file, 10 * 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read file {s}: {any}\n", .{source_file, err});
+        return false;
+    };
+    defer allocator.free(source);
     
-    const main_file = try std.fs.cwd().createFile(main_path, .{});
-    defer main_file.close();
+    var lexer = Lexer.init(allocator, source, source_file);
+    defer lexer.deinit();
+    const tokens = try lexer.tokenize();
     
-    const formatted_content = try std.fmt.allocPrint(allocator, main_content, .{project_name, project_name});
-    defer allocator.free(formatted_content);
+    var parser = Parser.init(allocator, tokens);
+    defer parser.deinit();
+    const program = try parser.parse();
     
-    try main_file.writeAll(formatted_content);
+    // 导入的类型也属于项目：合并导入的声明
+    var module_loader = ModuleLoader.init(allocator);
+    defer module_loader.deinit();
+    package.registerDependencies(allocator, &module_loader, false) catch {};
+    var resolved = try module_loader.resolveImports(allocator, parser.arena.allocator(), program.declarations);
+    defer resolved.deinit(allocator);
+    try consteval.evaluateProgram(parser.arena.allocator(), ast_mod.Program{ .declarations = resolved.all.items }, consteval.MAX_STEPS);
     
-    std.debug.print("✅ Project created successfully!\n", .{});
-    std.debug.print("\nNext steps:\n", .{});
-    std.debug.print("  cd {s}\n", .{project_name});
-    std.debug.print("  pawc main.paw --run\n", .{});
+    var type_checker = TypeChecker.init(allocator, tokens);
+    defer type_checker.deinit();
+    try type_checker.collectDeclarations(ast_mod.Program{ .declarations = resolved.all.items });
+    
+    const trait_def = type_checker.trait_table.get(trait_name) orelse {
+        std.debug.print("Error: Unknown trait '{s}'\n", .{trait_name});
+        return false;
+    };
+    
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+    const reports = if (type_list) |list| blk: {
+        var type_names = std.ArrayList([]const u8){};
+        var it = std.mem.tokenizeScalar(u8, list, ',');
+        while (it.next()) |name| try type_names.append(arena.allocator(), std.mem.trim(u8, name, " "));
+        break :blk try query.checkImpls(arena.allocator(), &type_checker, trait_def, type_names.items);
+    } else try query.traitImpls(arena.allocator(), &type_checker, trait_def);
+    
+    const output = try query.encodeImplsJson(allocator, trait_name, reports);
+    defer allocator.free(output);
+    try std.fs.File.stdout().writeAll(output);
+    
+    if (type_list == null) return true;
+    for (reports) |report| {
+        if (!report.isComplete()) return false;
+    }
+    return true;
+}
+
+// 🆕 query command: 面向编辑器的查询，结果以 JSON 输出到 stdout
+fn runQuery(allocator: std.mem.Allocator, kind: []const u8, source_file: []const u8) !void {
+    var source_map = SourceMap.init(allocator);
+    defer source_map.deinit();
+    const file_id = source_map.loadFile(source_file) catch |err| {
+        std.debug.print("Error: Cannot read file {s}: {any}\n", .{source_file, err});
+        return;
+    };
+    const file = source_map.get(file_id);
+    
+    var lexer = Lexer.init(allocator, file.contents, source_file);
+    defer lexer.deinit();
+    const tokens = try lexer.tokenize();
+    
+    var parser = Parser.init(allocator, tokens);
+    defer parser.deinit();
+    const program = try parser.parse();
+    
+    var module_loader = ModuleLoader.init(allocator);
+    defer module_loader.deinit();
+    package.registerDependencies(allocator, &module_loader, false) catch {};
+    
+    var output: []const u8 = undefined;
+    if (std.mem.eql(u8, kind, "highlight")) {
+        const semantic_tokens = try query.highlight(allocator, file, tokens, program, &module_loader);
+        defer allocator.free(semantic_tokens);
+        output = try query.encodeJson(allocator, semantic_tokens);
+    } else if (std.mem.eql(u8, kind, "symbols")) {
+        var arena = std.heap.ArenaAllocator.init(allocator);
+        defer arena.deinit();
+        const symbols = try query.documentSymbols(arena.allocator(), file, tokens, program);
+        output = try query.encodeSymbolsJson(allocator, symbols);
+    } else {
+        std.debug.print("Error: Unknown query '{s}'\n", .{kind});
+        std.debug.print("Available queries: highlight, symbols, impls, missing-impls\n", .{});
+        return;
+    }
+    defer allocator.free(output);
+    
+    try std.fs.File.stdout().writeAll(output);
 }
 
+/// 🆕 --time-limit 的值（取消的诊断使用）
+var time_limit_for_report: u64 = 0;
+
 pub fn main() !void {
+    // 🆕 --time-limit 超时的编译以诊断和退出码 1 结束，而不是未处理的 error.Cancelled
+    run() catch |err| switch (err) {
+        error.Cancelled => {
+            std.debug.print("\x1b[1;31merror\x1b[0m: compilation cancelled: exceeded the time limit of {d} ms (--time-limit)\n", .{time_limit_for_report});
+            std.process.exit(1);
+        },
+        else => return err,
+    };
+}
+
+fn run() !void {
     var gpa = std.heap.GeneralPurposeAllocator(.{}){};
     defer _ = gpa.deinit();
     const allocator = gpa.allocator();
 
-    const args = try std.process.argsAlloc(allocator);
-    defer std.process.argsFree(allocator, args);
+    const raw_args = try std.process.argsAlloc(allocator);
+    defer std.process.argsFree(allocator, raw_args);
+
+    // 🆕 消息语言：--locale 优先于 PAW_LANG，对所有子命令生效，分发前从参数中取出
+    messages.initFromEnv();
+    const args = messages.takeLocaleArgs(allocator, raw_args) catch |err| switch (err) {
+        error.InvalidLocale => std.process.exit(1),
+        else => return err,
+    };
+    defer allocator.free(args);
 
     if (args.len < 2) {
         printUsage();
@@ -188,26 +732,474 @@ pub fn main() !void {
 
     // 🆕 Handle check command
     if (std.mem.eql(u8, args[1], "check")) {
-        if (args.len < 3) {
-            std.debug.print("Error: check command requires a file\n", .{});
-            std.debug.print("Usage: pawc check <file.paw>\n", .{});
-            return;
+        // 🆕 pawc check [<file.paw>] [options]：不指定文件时检查当前项目（Paw.toml 的 entry）
+        var file: ?[]const u8 = null;
+        var options = CheckOptions{};
+        var i: usize = 2;
+        while (i < args.len) : (i += 1) {
+            const arg = args[i];
+            if (std.mem.eql(u8, arg, "--allow-asm")) {
+                options.allow_asm = true;
+            } else if (std.mem.eql(u8, arg, "--fail-fast")) {
+                options.fail_fast = true;
+            } else if (std.mem.eql(u8, arg, "--locked")) {
+                options.locked = true;
+            } else if (std.mem.eql(u8, arg, "--max-errors") and i + 1 < args.len) {
+                i += 1;
+                options.max_errors = parseMaxErrors(args[i]) orelse std.process.exit(1);
+            } else if (file == null and !std.mem.startsWith(u8, arg, "-")) {
+                file = arg;
+            } else {
+                std.debug.print("Error: unknown option for check: '{s}'\n", .{arg});
+                std.debug.print("Usage: pawc check [<file.paw>] [--allow-asm] [--max-errors N] [--fail-fast] [--locked]\n", .{});
+                std.process.exit(1);
+            }
         }
-        try checkFile(allocator, args[2]);
+        
+        var manifest: ?package.Manifest = null;
+        defer if (manifest) |*m| m.deinit();
+        const entry = file orelse blk: {
+            manifest = package.Manifest.load(allocator, package.MANIFEST_FILE) catch |err| switch (err) {
+                error.FileNotFound => {
+                    std.debug.print("Error: check command requires a file (or a {s} in the current directory)\n", .{package.MANIFEST_FILE});
+                    std.debug.print("Usage: pawc check [<file.paw>] [options]\n", .{});
+                    std.process.exit(1);
+                },
+                else => std.process.exit(1),
+            };
+            break :blk manifest.?.package_entry orelse run_cmd.PROJECT_ENTRY;
+        };
+        if (!try checkFile(allocator, entry, options)) std.process.exit(1);
         return;
     }
 
-    // 🆕 Handle init command
+    // 🆕 Handle new / init commands
+    if (std.mem.eql(u8, args[1], "new")) {
+        if (args.len < 3) {
+            std.debug.print("Error: new command requires a project name\n", .{});
+            std.debug.print("Usage: pawc new <project_name>\n", .{});
+            std.process.exit(1);
+        }
+        try newProject(allocator, args[2]);
+        return;
+    }
     if (std.mem.eql(u8, args[1], "init")) {
+        try initProject(allocator, if (args.len >= 3) args[2] else ".");
+        return;
+    }
+    
+    // 🆕 Handle api command
+    if (std.mem.eql(u8, args[1], "api")) {
+        if (args.len < 3) {
+            std.debug.print("Error: api command requires a file\n", .{});
+            std.debug.print("Usage: pawc api <file.paw> [-o <file.pawm>]\n", .{});
+            return;
+        }
+        const api_output: ?[]const u8 = if (args.len >= 5 and std.mem.eql(u8, args[3], "-o")) args[4] else null;
+        try emitApiMetadata(allocator, args[2], api_output);
+        return;
+    }
+    
+    // 🆕 Handle api-diff command
+    if (std.mem.eql(u8, args[1], "api-diff")) {
+        if (args.len < 4) {
+            std.debug.print("Error: api-diff command requires two metadata files\n", .{});
+            std.debug.print("Usage: pawc api-diff <old.pawm> <new.pawm>\n", .{});
+            return;
+        }
+        try diffApiMetadata(allocator, args[2], args[3]);
+        return;
+    }
+    
+    // 🆕 Handle add command
+    if (std.mem.eql(u8, args[1], "add")) {
+        if (args.len < 3) {
+            std.debug.print("Error: add command requires a dependency\n", .{});
+            std.debug.print("Usage: pawc add <name>@<version> [--git <url>]\n", .{});
+            return;
+        }
+        const git_url: ?[]const u8 = if (args.len >= 5 and std.mem.eql(u8, args[3], "--git")) args[4] else null;
+        package.addDependency(allocator, args[2], git_url) catch return;
+        return;
+    }
+    
+    // 🆕 Handle fetch command
+    if (std.mem.eql(u8, args[1], "fetch")) {
+        var manifest = package.Manifest.load(allocator, package.MANIFEST_FILE) catch |err| {
+            std.debug.print("Error: Cannot load {s}: {any}\n", .{package.MANIFEST_FILE, err});
+            return;
+        };
+        defer manifest.deinit();
+        const fetch_locked = args.len >= 3 and std.mem.eql(u8, args[2], "--locked");
+        package.fetchDependencies(allocator, &manifest, fetch_locked) catch return;
+        return;
+    }
+    
+    // 🆕 Handle lsp command：stdin / stdout 上的语言服务器
+    if (std.mem.eql(u8, args[1], "lsp")) {
+        const exit_code = try lsp.run(allocator);
+        std.process.exit(exit_code);
+    }
+    
+    // 🆕 Handle query command
+    if (std.mem.eql(u8, args[1], "query")) {
+        // 🆕 trait 实现情况：query impls <Trait> <file> / query missing-impls <Trait> <Type,...> <file>
+        const is_impls = args.len >= 3 and std.mem.eql(u8, args[2], "impls");
+        const is_missing_impls = args.len >= 3 and std.mem.eql(u8, args[2], "missing-impls");
+        if (is_impls or is_missing_impls) {
+            const operands: usize = if (is_impls) 2 else 3;
+            if (args.len < 3 + operands) {
+                std.debug.print("Error: {s} query requires a trait and a file\n", .{args[2]});
+                std.debug.print("Usage: pawc query impls <Trait> <file.paw>\n", .{});
+                std.debug.print("       pawc query missing-impls <Trait> <Type,...> <file.paw>\n", .{});
+                return;
+            }
+            const type_list: ?[]const u8 = if (is_missing_impls) args[4] else null;
+            const all_implemented = try runImplsQuery(allocator, args[3], type_list, args[2 + operands]);
+            if (!all_implemented) std.process.exit(1);
+            return;
+        }
+        if (args.len < 4) {
+            std.debug.print("Error: query command requires a query name and a file\n", .{});
+            std.debug.print("Usage: pawc query <highlight|symbols> <file.paw>\n", .{});
+            return;
+        }
+        try runQuery(allocator, args[2], args[3]);
+        return;
+    }
+    
+    // 🆕 Handle nm command
+    if (std.mem.eql(u8, args[1], "nm")) {
         if (args.len < 3) {
-            std.debug.print("Error: init command requires a project name\n", .{});
-            std.debug.print("Usage: pawc init <project_name>\n", .{});
+            std.debug.print("Error: nm command requires an object file or executable\n", .{});
+            std.debug.print("Usage: pawc nm <obj-or-exe>\n", .{});
+            return;
+        }
+        nm.listSymbols(allocator, args[2]) catch return;
+        return;
+    }
+    
+    // 🆕 Handle selftest command
+    if (std.mem.eql(u8, args[1], "selftest")) {
+        if (args.len < 3) {
+            std.debug.print("Error: selftest command requires a directory\n", .{});
+            std.debug.print("Usage: pawc selftest <dir>\n", .{});
+            return;
+        }
+        const all_passed = try selftest.run(allocator, args[2]);
+        if (!all_passed) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle unused-pub command
+    if (std.mem.eql(u8, args[1], "unused-pub")) {
+        if (args.len < 3) {
+            std.debug.print("Error: unused-pub command requires a library file\n", .{});
+            std.debug.print("Usage: pawc unused-pub <lib.paw> [<test-file-or-dir>...] [--deny-warnings]\n", .{});
+            return;
+        }
+        // 🆕 --deny-warnings：报告了未使用的 pub 项时以退出码 1 结束（用于 CI）
+        var deny_warnings = false;
+        var test_paths = std.ArrayList([]const u8){};
+        defer test_paths.deinit(allocator);
+        for (args[3..]) |arg| {
+            if (std.mem.eql(u8, arg, "--deny-warnings")) {
+                deny_warnings = true;
+            } else if (std.mem.startsWith(u8, arg, "-")) {
+                std.debug.print("Error: unknown unused-pub option '{s}'\n", .{arg});
+                std.debug.print("Usage: pawc unused-pub <lib.paw> [<test-file-or-dir>...] [--deny-warnings]\n", .{});
+                std.process.exit(1);
+            } else {
+                try test_paths.append(allocator, arg);
+            }
+        }
+        const reported = unused_pub.run(allocator, args[2], test_paths.items) catch std.process.exit(1);
+        if (deny_warnings and reported > 0) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle ast command
+    if (std.mem.eql(u8, args[1], "ast")) {
+        var file: ?[]const u8 = null;
+        var options = ast_printer.Options{};
+        for (args[2..]) |arg| {
+            if (std.mem.eql(u8, arg, "--typed")) {
+                options.typed = true;
+            } else if (std.mem.eql(u8, arg, "--spans")) {
+                options.spans = true;
+            } else if (!std.mem.startsWith(u8, arg, "-") and file == null) {
+                file = arg;
+            } else {
+                std.debug.print("Error: unknown ast option '{s}'\n", .{arg});
+                std.debug.print("Usage: pawc ast <file.paw> [--typed] [--spans]\n", .{});
+                return;
+            }
+        }
+        const source_file = file orelse {
+            std.debug.print("Error: ast command requires a file\n", .{});
+            std.debug.print("Usage: pawc ast <file.paw> [--typed] [--spans]\n", .{});
+            return;
+        };
+        ast_printer.run(allocator, source_file, options) catch std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle grammar command
+    if (std.mem.eql(u8, args[1], "grammar")) {
+        var format = grammar.Format.ebnf;
+        var output_path: ?[]const u8 = null;
+        var i: usize = 2;
+        while (i < args.len) : (i += 1) {
+            const arg = args[i];
+            if (std.mem.eql(u8, arg, "--ebnf")) {
+                format = .ebnf;
+            } else if (std.mem.eql(u8, arg, "--html")) {
+                format = .html;
+            } else if (std.mem.eql(u8, arg, "--check")) {
+                format = .check_only;
+            } else if (std.mem.eql(u8, arg, "-o") and i + 1 < args.len) {
+                i += 1;
+                output_path = args[i];
+            } else {
+                std.debug.print("Error: unknown grammar option '{s}'\n", .{arg});
+                std.debug.print("Usage: pawc grammar [--ebnf | --html | --check] [-o <file>]\n", .{});
+                return;
+            }
+        }
+        const ok = try grammar.run(allocator, format, output_path);
+        if (!ok) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle self-bench command
+    if (std.mem.eql(u8, args[1], "self-bench")) {
+        var dir_path: []const u8 = "benches";
+        var generate = false;
+        var options = bench.Options{};
+        var i: usize = 2;
+        while (i < args.len) : (i += 1) {
+            const arg = args[i];
+            if (std.mem.eql(u8, arg, "--generate")) {
+                generate = true;
+            } else if (std.mem.eql(u8, arg, "--save") and i + 1 < args.len) {
+                i += 1;
+                options.save_path = args[i];
+            } else if (std.mem.eql(u8, arg, "--baseline") and i + 1 < args.len) {
+                i += 1;
+                options.baseline_path = args[i];
+            } else if (std.mem.eql(u8, arg, "--threshold") and i + 1 < args.len) {
+                i += 1;
+                options.threshold_percent = std.fmt.parseFloat(f64, args[i]) catch {
+                    std.debug.print("Error: invalid --threshold value '{s}'\n", .{args[i]});
+                    return;
+                };
+            } else if (std.mem.eql(u8, arg, "--iterations") and i + 1 < args.len) {
+                i += 1;
+                options.iterations = std.fmt.parseInt(usize, args[i], 10) catch 0;
+                if (options.iterations == 0) {
+                    std.debug.print("Error: --iterations expects a positive integer, got '{s}'\n", .{args[i]});
+                    return;
+                }
+            } else if (!std.mem.startsWith(u8, arg, "-")) {
+                dir_path = arg;
+            } else {
+                std.debug.print("Error: unknown self-bench option '{s}'\n", .{arg});
+                std.debug.print("Usage: pawc self-bench [dir] [--iterations N] [--save <file.json>] [--baseline <file.json>] [--threshold <percent>] [--generate]\n", .{});
+                return;
+            }
+        }
+        if (generate) {
+            try bench.generateCorpus(allocator, dir_path);
             return;
         }
-        try initProject(allocator, args[2]);
+        const no_regressions = try bench.run(allocator, dir_path, options);
+        if (!no_regressions) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle abitest command
+    if (std.mem.eql(u8, args[1], "abitest")) {
+        if (args.len < 3) {
+            std.debug.print("Error: abitest command requires a directory\n", .{});
+            std.debug.print("Usage: pawc abitest <dir> [--target <triple>]... [--update]\n", .{});
+            return;
+        }
+        var targets = std.ArrayList([]const u8){};
+        defer targets.deinit(allocator);
+        var options = abitest.Options{};
+        var i: usize = 3;
+        while (i < args.len) : (i += 1) {
+            if (std.mem.eql(u8, args[i], "--update")) {
+                options.update = true;
+            } else if (std.mem.eql(u8, args[i], "--target") and i + 1 < args.len) {
+                i += 1;
+                try targets.append(allocator, args[i]);
+            } else {
+                std.debug.print("Error: unknown abitest option '{s}'\n", .{args[i]});
+                return;
+            }
+        }
+        if (targets.items.len > 0) options.targets = targets.items;
+        const all_passed = try abitest.run(allocator, args[2], options);
+        if (!all_passed) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle irtest command
+    if (std.mem.eql(u8, args[1], "irtest")) {
+        if (args.len < 3) {
+            std.debug.print("Error: irtest command requires a directory\n", .{});
+            std.debug.print("Usage: pawc irtest <dir> [--backend=<llvm|c>] [--update]\n", .{});
+            return;
+        }
+        var options = irtest.Options{};
+        for (args[3..]) |arg| {
+            if (std.mem.eql(u8, arg, "--update")) {
+                options.update = true;
+            } else if (std.mem.startsWith(u8, arg, "--backend=")) {
+                options.backend = irtest.Backend.fromString(arg["--backend=".len..]) orelse {
+                    std.debug.print("Error: unknown irtest backend '{s}' (expected llvm or c)\n", .{arg["--backend=".len..]});
+                    std.process.exit(1);
+                };
+            } else {
+                std.debug.print("Error: unknown irtest option '{s}'\n", .{arg});
+                return;
+            }
+        }
+        const all_passed = try irtest.run(allocator, args[2], options);
+        if (!all_passed) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle test command
+    if (std.mem.eql(u8, args[1], "test")) {
+        var options = testrunner.Options{};
+        var paths = std.ArrayList([]const u8){};
+        defer paths.deinit(allocator);
+        var i: usize = 2;
+        while (i < args.len) : (i += 1) {
+            if (std.mem.eql(u8, args[i], "--filter") and i + 1 < args.len) {
+                i += 1;
+                options.filter = args[i];
+            } else if (std.mem.eql(u8, args[i], "--seed") and i + 1 < args.len) {
+                i += 1;
+                options.seed = std.fmt.parseInt(u64, args[i], 10) catch {
+                    std.debug.print("Error: --seed expects a non-negative integer, got '{s}'\n", .{args[i]});
+                    return;
+                };
+            } else if (std.mem.startsWith(u8, args[i], "-")) {
+                std.debug.print("Error: unknown test option '{s}'\n", .{args[i]});
+                std.debug.print("Usage: pawc test <file-or-dir>... [--filter <text>] [--seed <n>]\n", .{});
+                return;
+            } else {
+                try paths.append(allocator, args[i]);
+            }
+        }
+        if (paths.items.len == 0) {
+            std.debug.print("Error: test command requires a file or directory\n", .{});
+            std.debug.print("Usage: pawc test <file-or-dir>... [--filter <text>] [--seed <n>]\n", .{});
+            return;
+        }
+        const all_passed = try testrunner.run(allocator, paths.items, options);
+        if (!all_passed) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle upgrade command
+    if (std.mem.eql(u8, args[1], "upgrade")) {
+        var options = upgrade.Options{};
+        var i: usize = 2;
+        while (i < args.len) : (i += 1) {
+            if (std.mem.eql(u8, args[i], "--dry-run")) {
+                options.dry_run = true;
+            } else if (std.mem.eql(u8, args[i], "--proxy") and i + 1 < args.len) {
+                i += 1;
+                options.proxy = args[i];
+            } else {
+                std.debug.print("Error: unknown upgrade option '{s}'\n", .{args[i]});
+                std.debug.print("Usage: pawc upgrade [--dry-run] [--proxy <url>]\n", .{});
+                return;
+            }
+        }
+        if (!try upgrade.run(allocator, VERSION, options)) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle trace command
+    if (std.mem.eql(u8, args[1], "trace")) {
+        if (args.len < 3 or !std.mem.eql(u8, args[2], "view") or args.len > 4) {
+            std.debug.print("Usage: pawc trace view [{s}]\n", .{trace_mod.DEFAULT_FILE});
+            return;
+        }
+        const trace_file = if (args.len == 4) args[3] else trace_mod.DEFAULT_FILE;
+        if (!try trace_mod.view(allocator, trace_file)) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle doctor command
+    if (std.mem.eql(u8, args[1], "doctor")) {
+        var options = toolchain.DoctorOptions{};
+        var i: usize = 2;
+        while (i < args.len) : (i += 1) {
+            if (std.mem.eql(u8, args[i], "--linker") and i + 1 < args.len) {
+                i += 1;
+                options.linker = parseLinker(args[i]) orelse return;
+            } else if (std.mem.eql(u8, args[i], "--target") and i + 1 < args.len) {
+                i += 1;
+                options.target = args[i];
+            } else {
+                std.debug.print("Error: unknown doctor option '{s}'\n", .{args[i]});
+                std.debug.print("Usage: pawc doctor [--linker <zig|gcc|clang|cc>] [--target <triple>]\n", .{});
+                return;
+            }
+        }
+        if (!try toolchain.doctor(allocator, options)) std.process.exit(1);
         return;
     }
     
+    // 🆕 Handle report-bug command
+    if (std.mem.eql(u8, args[1], "report-bug")) {
+        var options = bugreport.Options{};
+        var i: usize = 2;
+        while (i < args.len) : (i += 1) {
+            if (std.mem.eql(u8, args[i], "-o") and i + 1 < args.len) {
+                i += 1;
+                options.output = args[i];
+            } else if (options.source_file == null and !std.mem.startsWith(u8, args[i], "-")) {
+                options.source_file = args[i];
+            } else {
+                std.debug.print("Error: unknown report-bug option '{s}'\n", .{args[i]});
+                std.debug.print("Usage: pawc report-bug [file.paw] [-o <archive>]\n", .{});
+                return;
+            }
+        }
+        if (!try bugreport.run(allocator, VERSION, options)) std.process.exit(1);
+        return;
+    }
+    
+    // 🆕 Handle run command
+    if (std.mem.eql(u8, args[1], "run")) {
+        const exit_code = try run_cmd.run(allocator, args[2..]);
+        std.process.exit(exit_code);
+    }
+    
+    // 🆕 Handle dev command
+    if (std.mem.eql(u8, args[1], "dev")) {
+        if (args.len < 3) {
+            std.debug.print("Error: dev command requires a file\n", .{});
+            std.debug.print("Usage: pawc dev <file.paw> [-- <args>...]\n", .{});
+            return;
+        }
+        if (!llvm_available) {
+            std.debug.print("❌ Error: pawc dev needs the LLVM backend, which is not available in this build\n", .{});
+            std.debug.print("💡 Rebuild with: zig build (LLVM auto-detected)\n", .{});
+            return;
+        }
+        const program_args: []const [:0]const u8 = if (args.len > 3 and std.mem.eql(u8, args[3], "--")) args[4..] else args[3..];
+        const exit_code = dev.run(allocator, args[2], program_args) catch std.process.exit(1);
+        std.process.exit(exit_code);
+    }
+    
     // 🆕 v0.1.9: Handle repl command
     if (std.mem.eql(u8, args[1], "repl")) {
         var repl = REPL.init(allocator);
@@ -225,6 +1217,21 @@ pub fn main() !void {
     var backend: ?Backend = null;     // 🆕 v0.1.8: 后端选择，null = 自动检测
     var opt_level: ?OptLevel = null;  // 🆕 v0.1.7: LLVM 优化级别
     var show_timing = false;          // 🆕 v0.1.9: 显示编译时间分析
+    var split_modules = false;        // 🆕 分离编译：每个模块一个目标文件
+    var locked = false;               // 🆕 要求 Paw.lock 与依赖树一致（CI）
+    var max_errors: ?usize = null;    // 🆕 最多报告的错误数
+    var fail_fast = false;            // 🆕 任一阶段出错即停止
+    var interpret = false;            // 🆕 类型检查后直接解释执行，不生成代码
+    var time_limit_ms: ?u64 = null;   // 🆕 看门狗：编译超过该时间即取消
+    var stack_size: ?u64 = null;      // 🆕 生成程序的主线程栈大小（字节）
+    var explain_overloads = false;    // 🆕 记录调用解析过程
+    var explain_overloads_file: ?[]const u8 = null;  // 🆕 解析过程写入 JSON 文件（否则输出到 stderr）
+    var allow_asm = false;            // 🆕 允许 @asm 函数（不安全）
+    var use_cache = true;             // 🆕 --split-modules 时使用 target/cache 增量编译
+    var verify = false;               // 🆕 运行 LLVM verifier 并检查后端不变量
+    var instrument_trace = false;     // 🆕 --instrument=trace：记录函数进入 / 退出
+    var test_harness = false;         // 🆕 --test：main 运行 argv[1] 指定的 @test 函数（pawc test 使用）
+    var requested_linker: ?toolchain.Linker = null;  // 🆕 --linker：编译生成的 C 代码的编译器
 
     // 解析命令行选项
     var i: usize = 2;
@@ -244,6 +1251,52 @@ pub fn main() !void {
             should_compile = true;
         } else if (std.mem.eql(u8, arg, "--compile")) {
             should_compile = true;
+        } else if (std.mem.eql(u8, arg, "--split-modules")) {
+            split_modules = true;
+        } else if (std.mem.eql(u8, arg, "--no-cache")) {
+            use_cache = false;
+        } else if (std.mem.eql(u8, arg, "--verify")) {
+            verify = true;
+        } else if (std.mem.startsWith(u8, arg, "--instrument=")) {
+            const mode = arg["--instrument=".len..];
+            if (!std.mem.eql(u8, mode, "trace")) {
+                std.debug.print("Error: unknown instrumentation '{s}' (supported: trace)\n", .{mode});
+                return;
+            }
+            instrument_trace = true;
+        } else if (std.mem.eql(u8, arg, "--test")) {
+            test_harness = true;
+        } else if (std.mem.eql(u8, arg, "--locked")) {
+            locked = true;
+        } else if (std.mem.eql(u8, arg, "--max-errors") and i + 1 < args.len) {
+            i += 1;
+            max_errors = parseMaxErrors(args[i]) orelse return;
+        } else if (std.mem.eql(u8, arg, "--fail-fast")) {
+            fail_fast = true;
+        } else if (std.mem.eql(u8, arg, "--allow-asm")) {
+            allow_asm = true;
+        } else if (std.mem.eql(u8, arg, "--time-limit") and i + 1 < args.len) {
+            i += 1;
+            time_limit_ms = std.fmt.parseInt(u64, args[i], 10) catch {
+                std.debug.print("Error: --time-limit expects a number of milliseconds, got '{s}'\n", .{args[i]});
+                return;
+            };
+        } else if (std.mem.eql(u8, arg, "--linker") and i + 1 < args.len) {
+            i += 1;
+            requested_linker = parseLinker(args[i]) orelse return;
+        } else if (std.mem.eql(u8, arg, "--stack-size") and i + 1 < args.len) {
+            i += 1;
+            stack_size = limits_mod.parseByteSize(args[i]) orelse {
+                std.debug.print("Error: --stack-size expects a size in bytes (optionally with a K/M/G suffix), got '{s}'\n", .{args[i]});
+                return;
+            };
+        } else if (std.mem.eql(u8, arg, "--interpret")) {
+            interpret = true;
+        } else if (std.mem.eql(u8, arg, "--explain-overloads")) {
+            explain_overloads = true;
+        } else if (std.mem.startsWith(u8, arg, "--explain-overloads=")) {
+            explain_overloads = true;
+            explain_overloads_file = arg["--explain-overloads=".len..];
         } else if (std.mem.eql(u8, arg, "--backend=llvm")) {
             // 🆕 v0.1.4: LLVM后端 (自动选择最佳模式)
             if (!llvm_available) {
@@ -287,136 +1340,109 @@ pub fn main() !void {
         std.debug.print("⚠️  Warning: Optimization flags (-O0/-O1/-O2/-O3) only work with LLVM backend\n", .{});
         std.debug.print("💡 Tip: Remove optimization flag or use --backend=llvm\n", .{});
     }
+    if (verify and selected_backend != .llvm) {
+        std.debug.print("⚠️  Warning: --verify only checks code generated by the LLVM backend\n", .{});
+    }
+    if (instrument_trace and (selected_backend != .c or split_modules)) {
+        std.debug.print("❌ Error: --instrument=trace requires the C backend without --split-modules\n", .{});
+        std.debug.print("💡 Tip: add --backend=c\n", .{});
+        return;
+    }
+    if (test_harness and (selected_backend != .c or split_modules)) {
+        std.debug.print("❌ Error: --test requires the C backend without --split-modules\n", .{});
+        std.debug.print("💡 Tip: add --backend=c (pawc test does this for you)\n", .{});
+        return;
+    }
+    
+    // 🆕 编译之前检查 C 编译器（链接器）：缺少时立即给出安装方法或 --linker 回退，
+    // 而不是在代码生成之后才失败。没有 --linker 时优先使用本地 LLVM 工具链的 clang
+    var linker: ?toolchain.Linker = null;
+    if (should_compile and selected_backend == .c and (requested_linker != null or split_modules or !toolchain.hasLocalClang())) {
+        linker = try toolchain.select(allocator, requested_linker) orelse std.process.exit(1);
+    }
 
     // 读取源文件
     const source = try std.fs.cwd().readFileAlloc(allocator, source_file, 10 * 1024 * 1024);
     defer allocator.free(source);
 
+    // 🆕 Paw.toml [limits]（清单无效时 Manifest.load 已打印错误）
+    const project_limits = package.loadLimits(allocator) catch std.process.exit(1);
+
     if (verbose) {
         std.debug.print("Compiling: {s}\n", .{source_file});
     }
 
     // 编译流程
     const start_time = std.time.nanoTimestamp();
-
-    // 🆕 0. 自动加载标准库 prelude（嵌入到可执行文件中）
-    const prelude_source = @embedFile("prelude/prelude.paw");
-    
-    // 🆕 v0.1.8: 计算 prelude 行数用于行号偏移
-    var prelude_lines: usize = 0;
-    for (prelude_source) |c| {
-        if (c == '\n') prelude_lines += 1;
-    }
-    prelude_lines += 2;  // 加上分隔的两个换行符
-    
-    // 合并 prelude 和用户代码
-    const combined_source = try std.fmt.allocPrint(allocator, "{s}\n\n{s}", .{prelude_source, source});
-    defer allocator.free(combined_source);
     
+    // 🆕 --time-limit: 解析、类型检查和代码生成在声明之间检查令牌，超时即放弃
+    const cancel_token: ?CancelToken = if (time_limit_ms) |ms| CancelToken.withTimeout(ms) else null;
+    const cancel_ptr: ?*const CancelToken = if (cancel_token) |*token| token else null;
+    if (time_limit_ms) |ms| time_limit_for_report = ms;
+
     // 🆕 v0.1.9: 初始化编译时间分析器
     var timer = if (show_timing) CompilationTimer.init() else undefined;
     
-    // 1. Lexical analysis
-    const lexer_start = std.time.milliTimestamp();
-    var lexer = Lexer.init(allocator, combined_source, source_file);
-    lexer.setLineOffset(prelude_lines);  // 🆕 v0.1.8: 设置行号偏移
-    defer lexer.deinit();
-    
-    const tokens = try lexer.tokenize();
-    if (show_timing) {
-        timer.lexer_time = std.time.milliTimestamp() - lexer_start;
-    }
-    if (verbose) {
-        const lex_time = std.time.nanoTimestamp();
-        std.debug.print("[PERF] Lexical analysis: {d}μs\n", .{@divTrunc(lex_time - start_time, 1000)});
-    }
-
-    // 2. Parsing
-    const parser_start = std.time.milliTimestamp();
-    var parser = Parser.init(allocator, tokens);
-    defer parser.deinit();  // 这会自动释放所有 AST 内存（通过 arena）
-    
-    const ast_result = try parser.parse();
-    if (show_timing) {
-        timer.parser_time = std.time.milliTimestamp() - parser_start;
-    }
-    // 注意: ast_result 的内存由 parser.arena 管理，不需要单独 deinit
-    // AST 会在 parser.deinit() 时自动释放
-    
-    if (verbose) {
-        const parse_time = std.time.nanoTimestamp();
-        std.debug.print("[PERF] Parsing: {d}μs\n", .{@divTrunc(parse_time - start_time, 1000)});
-    }
+    // 1-2.5. 词法分析、语法分析、导入展开和编译期求值（与 pawc check 共用）
+    var front_end: FrontEnd = undefined;
+    front_end.load(allocator, source_file, source, .{
+        .locked = locked,
+        .fail_fast = fail_fast,
+        .const_eval_steps = project_limits.const_eval_steps,
+        .cancel_token = cancel_ptr,
+        .timer = if (show_timing) &timer else null,
+        .perf_start = if (verbose) start_time else null,
+    }) catch |err| switch (err) {
+        error.DependencyResolutionFailed => std.process.exit(1),  // 已打印错误
+        error.ImportFailed => std.process.exit(1),  // --fail-fast：导入错误已打印
+        else => return err,
+    };
+    defer front_end.deinit();
+    const tokens = front_end.tokens;
+    const ast = front_end.ast;
+    const module_loader = &front_end.module_loader;
+    const own_declarations = &front_end.resolved.own;
+    const imported_declarations = &front_end.resolved.imported;
 
-    // 2.5. 🆕 处理导入（模块系统）
-    var module_loader = ModuleLoader.init(allocator);
-    defer module_loader.deinit();
-    
-    var resolved_declarations = std.ArrayList(ast_mod.TopLevelDecl){};
-    defer resolved_declarations.deinit(allocator);
-    
-    for (ast_result.declarations) |decl| {
-        if (decl == .import_decl) {
-            const import_decl = decl.import_decl;
-            
-            // 🆕 处理单项或多项导入
-            switch (import_decl.items) {
-                .single => |item_name| {
-                    // 单项导入：import math.add;
-                    const imported_item = module_loader.getImportedItem(
-                        import_decl.module_path,
-                        item_name,
-                    ) catch |err| {
-                        std.debug.print("Error: Failed to import {s}.{s}: {any}\n", 
-                            .{import_decl.module_path, item_name, err});
-                        continue;
-                    };
-                    try resolved_declarations.append(allocator, imported_item);
-                },
-                .multiple => |item_names| {
-                    // 多项导入：import math.{add, sub, Vec2};
-                    for (item_names) |item_name| {
-                        const imported_item = module_loader.getImportedItem(
-                            import_decl.module_path,
-                            item_name,
-                        ) catch |err| {
-                            std.debug.print("Error: Failed to import {s}.{s}: {any}\n", 
-                                .{import_decl.module_path, item_name, err});
-                            continue;
-                        };
-                        try resolved_declarations.append(allocator, imported_item);
-                    }
-                },
-            }
-            
-            // 注意：module_path会在ast_result.deinit()中释放，这里不释放
-        } else {
-            // 非import声明，直接添加
-            try resolved_declarations.append(allocator, decl);
+    // 🆕 增量编译：源文件和选项都没有变化的单元复用 target/cache 中的目标文件；
+    // 全部未变化时跳过类型检查和代码生成，只重新链接
+    var build_plan: ?cache_mod.Plan = null;
+    defer if (build_plan) |*p| p.deinit();
+    // 🆕 并发构建：编译期间持有 target/.pawc-lock（另一个构建正在进行时等待）
+    if (should_compile) try build_lock.acquire();
+    defer build_lock.release();
+    if (split_modules and should_compile and use_cache and selected_backend == .c) {
+        const build_cache = try cache_mod.BuildCache.init(allocator, args[2..]);
+        try module_loader.loadTransitiveImports();  // 间接导入的模块也有自己的单元
+        build_plan = try cache_mod.plan(allocator, build_cache, .{ .path = source_file, .source = source }, module_loader);
+        if (build_plan.?.allFresh()) {
+            if (verbose) std.debug.print("♻️  All units up to date, skipping type checking and code generation\n", .{});
+            try buildSplitModules(allocator, own_declarations.items, imported_declarations.items, module_loader, output_file orelse "output", stack_size, project_limits, &build_plan.?, optimize, linker, should_run, verbose);
+            return;
         }
     }
-    
-    // 创建新的AST（包含导入的声明）
-    const ast = ast_mod.Program{
-        .declarations = try resolved_declarations.toOwnedSlice(allocator),
-    };
-    defer {
-        // 只释放declarations数组，不递归释放内容
-        // 因为内容来自ast_result或module_loader，已有自己的生命周期管理
-        allocator.free(ast.declarations);
-    }
-    
-    if (verbose) {
-        const import_time = std.time.nanoTimestamp();
-        std.debug.print("[PERF] Module resolution: {d}μs\n", .{@divTrunc(import_time - start_time, 1000)});
-    }
 
     // 3. Type checking
     const typecheck_start = std.time.milliTimestamp();
-    var type_checker = TypeChecker.init(allocator, source_file, tokens);
+    var type_checker = TypeChecker.init(allocator, tokens);
     defer type_checker.deinit();
+    type_checker.export_index = &front_end.export_index;
+    var source_map = SourceMap.init(allocator);
+    defer source_map.deinit();
+    _ = try source_map.addFile(source_file, source);
+    type_checker.source_map = &source_map;
+    type_checker.max_errors = max_errors;
+    type_checker.fail_fast = fail_fast;
+    type_checker.allow_asm = allow_asm;
+    type_checker.cancel_token = cancel_ptr;
+    var trace = ResolutionTrace.init(allocator);
+    defer trace.deinit();
+    if (explain_overloads) type_checker.resolution_trace = &trace;
     
-    try type_checker.check(ast);
+    // 🆕 类型检查失败时也输出解析过程（排查歧义调用时最需要它）
+    const check_result = type_checker.check(ast);
+    if (explain_overloads) try emitResolutionTrace(allocator, &trace, explain_overloads_file);
+    try check_result;
     if (show_timing) {
         timer.typecheck_time = std.time.milliTimestamp() - typecheck_start;
     }
@@ -424,6 +1450,39 @@ pub fn main() !void {
         const typecheck_time = std.time.nanoTimestamp();
         std.debug.print("[PERF] Type checking: {d}μs\n", .{@divTrunc(typecheck_time - start_time, 1000)});
     }
+    
+    // 🆕 解释执行：跳过代码生成，以 main 的返回值作为退出码
+    if (interpret) {
+        const exit_code = interpreter.run(allocator, ast) catch |err| switch (err) {
+            error.ConstEvalFailed, error.NoMainFunction => std.process.exit(1),
+            else => return err,
+        };
+        std.process.exit(exit_code);
+    }
+    
+    // 🆕 分离编译：每个模块编译为独立目标文件，再统一链接
+    if (split_modules and should_compile) {
+        if (selected_backend != .c) {
+            std.debug.print("❌ Error: --split-modules requires the C backend\n", .{});
+            std.debug.print("💡 Tip: add --backend=c\n", .{});
+            return;
+        }
+        try buildSplitModules(
+            allocator,
+            own_declarations.items,
+            imported_declarations.items,
+            module_loader,
+            output_file orelse "output",
+            stack_size,
+            project_limits,
+            if (build_plan) |*p| p else null,
+            optimize,
+            linker,
+            should_run,
+            verbose,
+        );
+        return;
+    }
 
         // 4. Code generation - 🆕 v0.1.4: 双后端架构 (C + LLVM Native)
         const codegen_start = std.time.milliTimestamp();
@@ -431,6 +1490,11 @@ pub fn main() !void {
             .c => blk: {
                 var codegen = CodeGen.init(allocator);
                 defer codegen.deinit();
+                codegen.cancel_token = cancel_ptr;
+                codegen.stack_size = stack_size;
+                codegen.limits = project_limits;
+                codegen.instrument_trace = instrument_trace;
+                codegen.test_harness = test_harness;
                 break :blk try codegen.generate(ast);
             },
             .llvm => blk: {
@@ -453,10 +1517,17 @@ pub fn main() !void {
                 
                 var llvm_native = try LLVMNativeBackend.init(allocator, "pawlang_module", llvm_opt_level);
                 defer llvm_native.deinit();
+                llvm_native.cancel_token = cancel_ptr;
+                llvm_native.limits = project_limits;
+                if (verify) {
+                    llvm_native.verify_functions = true;
+                    llvm_native.check_invariants = true;
+                }
                 break :blk try llvm_native.generate(ast);
             },
         };
     defer allocator.free(output_code);  // 🔧 释放生成的代码（来自 codegen 或 llvm_native_backend）
+    if (!limits_mod.checkObjectSize(allocator, source_file, output_code.len, project_limits)) std.process.exit(1);
     
     if (show_timing) {
         timer.codegen_time = std.time.milliTimestamp() - codegen_start;
@@ -470,14 +1541,9 @@ pub fn main() !void {
     if (should_compile) {
         const output_name = output_file orelse "output";
         
-        // 检查是否有本地 LLVM/Clang
-        const local_clang_path = "llvm/install/bin/clang";
-        const has_local_clang = blk: {
-            std.fs.cwd().access(local_clang_path, .{}) catch {
-                break :blk false;
-            };
-            break :blk true;
-        };
+        // 检查是否有本地 LLVM/Clang（🆕 --linker 指定了编译器时不使用）
+        const local_clang_path = toolchain.LOCAL_CLANG;
+        const has_local_clang = linker == null and toolchain.hasLocalClang();
         
         if (has_local_clang and selected_backend == .c) {
             // 使用本地 Clang 编译 C 代码
@@ -485,13 +1551,19 @@ pub fn main() !void {
                 std.debug.print("🔨 Using local Clang for compilation\n", .{});
             }
             
-            // 写入 C 代码到临时文件
-            const temp_c_file = try std.fmt.allocPrint(allocator, "{s}.c", .{output_name});
+            // 写入 C 代码到临时文件（🆕 唯一的文件名，并发构建互不覆盖）
+            const c_path = try std.fmt.allocPrint(allocator, "{s}.c", .{output_name});
+            defer allocator.free(c_path);
+            const temp_c_file = try build_lock.tempPath(allocator, c_path);
             defer allocator.free(temp_c_file);
-            
-            const c_file = try std.fs.cwd().createFile(temp_c_file, .{});
-            defer c_file.close();
-            try c_file.writeAll(output_code);
+            defer std.fs.cwd().deleteFile(temp_c_file) catch {};
+            {
+                const c_file = try std.fs.cwd().createFile(temp_c_file, .{});
+                defer c_file.close();
+                try c_file.writeAll(output_code);
+            }
+            const temp_output = try build_lock.tempPath(allocator, output_name);
+            defer allocator.free(temp_output);
             
             // 使用本地 Clang 编译
             // 需要指定 SDK 路径 (macOS)
@@ -501,8 +1573,11 @@ pub fn main() !void {
             try clang_args.append(allocator, local_clang_path);
             try clang_args.append(allocator, temp_c_file);
             try clang_args.append(allocator, "-o");
-            try clang_args.append(allocator, output_name);
+            try clang_args.append(allocator, temp_output);
             try clang_args.append(allocator, "-O2");
+            try c_backend_mod.appendStackProbeFlags(allocator, &clang_args);
+            var stack_flag_buf: [64]u8 = undefined;
+            try c_backend_mod.appendStackSizeFlag(allocator, &clang_args, stack_size, &stack_flag_buf);
             
             // macOS: 添加 SDK 路径
             if (builtin.os.tag == .macos) {
@@ -515,9 +1590,13 @@ pub fn main() !void {
             const result = try child.spawnAndWait();
             
             if (result != .Exited or result.Exited != 0) {
+                std.fs.cwd().deleteFile(temp_output) catch {};
                 std.debug.print("❌ Compilation failed\n", .{});
                 return;
             }
+            // 🆕 完成后才替换可执行文件（-v 时保留 C 代码）
+            try build_lock.commit(temp_output, output_name);
+            if (verbose) try build_lock.commit(temp_c_file, c_path);
             
             if (verbose) {
                 std.debug.print("✅ Compilation complete: {s} -> {s}\n", .{ source_file, output_name });
@@ -528,6 +1607,7 @@ pub fn main() !void {
                 if (verbose) {
                     std.debug.print("🔥 Running: {s}\n", .{output_name});
                 }
+                build_lock.release();  // 🆕 程序运行期间不阻塞其他构建
                 
                 const run_path = try std.fmt.allocPrint(allocator, "./{s}", .{output_name});
                 defer allocator.free(run_path);
@@ -540,11 +1620,6 @@ pub fn main() !void {
                 }
             }
             
-            // 清理临时文件
-            if (!verbose) {
-                std.fs.cwd().deleteFile(temp_c_file) catch {};
-            }
-            
         } else if (selected_backend == .llvm) {
             // LLVM 后端: 生成 IR 然后用 Clang 编译
             std.debug.print("❌ Error: LLVM backend does not support --compile/--run yet\n", .{});
@@ -559,6 +1634,8 @@ pub fn main() !void {
             }
             
             var c_backend = CBackend.init(allocator);
+            c_backend.stack_size = stack_size;
+            c_backend.linker = linker;
             
             if (should_run) {
                 std.debug.print("🔥 Compiling and running: {s}\n", .{source_file});
@@ -652,6 +1729,24 @@ pub fn main() !void {
     }
 }
 
+/// 🆕 解析 --linker 的值（无效时打印错误并返回 null）
+fn parseLinker(name: []const u8) ?toolchain.Linker {
+    return toolchain.Linker.fromString(name) orelse {
+        std.debug.print("Error: unknown linker '{s}' (supported: zig, gcc, clang, cc)\n", .{name});
+        return null;
+    };
+}
+
+/// 🆕 解析 --max-errors 的值：正整数（0 会让检查在报告任何错误之前就停止，因此拒绝；无效时打印错误并返回 null）
+fn parseMaxErrors(text: []const u8) ?usize {
+    const max = std.fmt.parseInt(usize, text, 10) catch 0;
+    if (max == 0) {
+        std.debug.print("Error: --max-errors expects a positive number, got '{s}'\n", .{text});
+        return null;
+    }
+    return max;
+}
+
 fn printUsage() void {
     std.debug.print("\n", .{});
     std.debug.print("╔═══════════════════════════════════════════════════════════════╗\n", .{});
@@ -660,8 +1755,34 @@ fn printUsage() void {
     std.debug.print("\n", .{});
     std.debug.print("Usage:\n", .{});
     std.debug.print("  pawc <file.paw> [options]       Compile Paw source file\n", .{});
-    std.debug.print("  pawc check <file>               Type check only\n", .{});
-    std.debug.print("  pawc init <name>                Create new project\n", .{});
+    std.debug.print("  pawc check [<file>]             Type check only (no code generation)\n", .{});
+    std.debug.print("  pawc new <name>                 Create a new project directory\n", .{});
+    std.debug.print("  pawc init [<dir>]               Create a project in an existing directory\n", .{});
+    std.debug.print("  pawc run [<file>] [-- <args>]   Build the project (or file) into target/ and run it\n", .{});
+    std.debug.print("  pawc add <name>@<version>       Add a dependency to Paw.toml\n", .{});
+    std.debug.print("  pawc fetch [--locked]           Download dependencies, write Paw.lock\n", .{});
+    std.debug.print("  pawc api <file> [-o <out>]      Write public API metadata (.pawm)\n", .{});
+    std.debug.print("  pawc api-diff <old> <new>       Compare public API between builds\n", .{});
+    std.debug.print("  pawc query highlight <file>     Semantic tokens for editors (JSON)\n", .{});
+    std.debug.print("  pawc query symbols <file>       Document outline for editors (JSON)\n", .{});
+    std.debug.print("  pawc lsp                        Language server on stdin/stdout (document symbols)\n", .{});
+    std.debug.print("  pawc query impls <Trait> <file> Types implementing a trait, with locations (JSON)\n", .{});
+    std.debug.print("  pawc query missing-impls <Trait> <Type,...> <file>  Check types against a trait (exit 1 if incomplete)\n", .{});
+    std.debug.print("  pawc nm <obj-or-exe>            List Paw symbols with sizes and sections\n", .{});
+    std.debug.print("  pawc selftest <dir>             Compare interpreter and native output\n", .{});
+    std.debug.print("  pawc abitest <dir> [--target <triple>]  Run golden ABI tests (--update rewrites goldens)\n", .{});
+    std.debug.print("  pawc irtest <dir> [--backend=<llvm|c>]  Check generated IR per function (--update rewrites snapshots)\n", .{});
+    std.debug.print("  pawc test <file-or-dir>...      Run @test functions (--filter <text> selects by name)\n", .{});
+    std.debug.print("                                  --seed <n> replays @property tests from a reported seed\n", .{});
+    std.debug.print("  pawc unused-pub <lib> [tests...]  Warn about pub items no other public item or test uses (--deny-warnings: exit 1)\n", .{});
+    std.debug.print("  pawc ast <file> [--typed] [--spans]  Print the syntax tree (with inferred types / line:col)\n", .{});
+    std.debug.print("  pawc grammar [--ebnf|--html|--check] [-o <file>]  Export the grammar (EBNF / railroad diagrams)\n", .{});
+    std.debug.print("  pawc self-bench [dir]           Benchmark the compiler on a corpus (default: benches/)\n", .{});
+    std.debug.print("  pawc dev <file> [-- <args>]     Run on the LLVM JIT, hot-reload functions on save\n", .{});
+    std.debug.print("  pawc upgrade [--dry-run]        Install the latest compiler release (--proxy <url>)\n", .{});
+    std.debug.print("  pawc report-bug [file.paw]      Package the last internal compiler error for an issue\n", .{});
+    std.debug.print("  pawc doctor [--target <triple>] Check the C compiler, runtime libraries and target/ directory\n", .{});
+    std.debug.print("  pawc trace view [trace.bin]     Print a --instrument=trace recording as folded stacks\n", .{});
     std.debug.print("  pawc --version, -v              Show version\n", .{});
     std.debug.print("  pawc --help, -h                 Show this help\n", .{});
     std.debug.print("\n", .{});
@@ -671,6 +1792,21 @@ fn printUsage() void {
     std.debug.print("  --time           Show compilation time analysis 🆕\n", .{});
     std.debug.print("  --compile        Compile to executable (C backend only)\n", .{});
     std.debug.print("  --run            Compile and run immediately (C backend only)\n", .{});
+    std.debug.print("  --split-modules  Compile each module to its own object, then link\n", .{});
+    std.debug.print("  --no-cache       With --split-modules, rebuild every unit (ignore target/cache)\n", .{});
+    std.debug.print("  --verify         Run the LLVM verifier and backend invariant checks (any -O level)\n", .{});
+    std.debug.print("  --instrument=trace  Record function entry/exit to paw-trace.bin (C backend)\n", .{});
+    std.debug.print("  --test           Build a test executable: `<exe> <name>` runs one @test function (C backend)\n", .{});
+    std.debug.print("  --locked         Fail if Paw.lock does not match dependencies (CI)\n", .{});
+    std.debug.print("  --max-errors <N> Stop after reporting N errors\n", .{});
+    std.debug.print("  --fail-fast      Abort at the first phase that reports an error\n", .{});
+    std.debug.print("  --time-limit <ms> Cancel the compilation if it runs longer than <ms>\n", .{});
+    std.debug.print("  --stack-size <N> Main thread stack size of the program, e.g. 8M (C backend)\n", .{});
+    std.debug.print("  --linker <zig|gcc|clang|cc>  C compiler that compiles and links the program (default: first found)\n", .{});
+    std.debug.print("  --interpret      Type check, then run by interpreting the AST (no C compiler needed)\n", .{});
+    std.debug.print("  --allow-asm      Allow @asm functions (unchecked target assembly)\n", .{});
+    std.debug.print("  --explain-overloads[=<file.json>]  Trace how generic and trait method calls are resolved\n", .{});
+    std.debug.print("  --locale <en|zh> Language of diagnostics (default: $PAW_LANG, then en)\n", .{});
     std.debug.print("\n", .{});
     std.debug.print("Backends:\n", .{});
     std.debug.print("  --backend=c              Use C backend\n", .{});
@@ -687,12 +1823,13 @@ fn printUsage() void {
     std.debug.print("  pawc hello.paw                       Auto-detect backend (LLVM if available) 🆕\n", .{});
     std.debug.print("  pawc hello.paw --compile             Compile to executable\n", .{});
     std.debug.print("  pawc hello.paw --run                 Compile and run\n", .{});
+    std.debug.print("  pawc hello.paw --interpret           Run without compiling\n", .{});
     std.debug.print("  pawc hello.paw --backend=c           Force C backend\n", .{});
     std.debug.print("  pawc hello.paw --backend=llvm        Force LLVM backend\n", .{});
     std.debug.print("  pawc hello.paw -O2                   Auto-detect + optimization ⚡\n", .{});
     std.debug.print("  pawc fibonacci.paw -O3               Auto-detect + max optimization 🚀\n", .{});
     std.debug.print("  pawc check hello.paw                 Type check only\n", .{});
-    std.debug.print("  pawc init my_project                 Create new project\n", .{});
+    std.debug.print("  pawc new my_project                  Create new project\n", .{});
     std.debug.print("\n", .{});
     std.debug.print("Build with LLVM:\n", .{});
     std.debug.print("  zig build                            Auto-detect and use LLVM if available\n", .{});
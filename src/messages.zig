@@ -0,0 +1,256 @@
+//! Messages - 诊断消息模板与本地化（--locale / PAW_LANG）
+//!
+//! 编译器输出给用户的消息在这里按 Id 集中定义，每条消息带有所有语言的模板：
+//!   - Id 的名称是稳定的，工具和译文都按名称对应；改措辞时只改模板，不改名称
+//!   - 所有语言的模板必须使用相同的占位符（顺序也相同），否则编译失败，
+//!     这样译文不会和参数悄悄错位
+//!   - 默认语言是英文；--locale 优先于环境变量 PAW_LANG
+//!
+//! 用法：
+//!   const msg = try messages.format(allocator, .missing_main, .{});
+//!   messages.print(.unterminated_string, .{});
+
+const std = @import("std");
+
+pub const Locale = enum {
+    en,
+    zh,
+
+    /// 解析 "zh"、"zh_CN.UTF-8"、"en-US" 等写法，只看语言部分
+    pub fn parse(name: []const u8) ?Locale {
+        const end = std.mem.indexOfAny(u8, name, "_-.") orelse name.len;
+        const language = name[0..end];
+        inline for (@typeInfo(Locale).@"enum".fields) |field| {
+            if (std.ascii.eqlIgnoreCase(language, field.name)) return @field(Locale, field.name);
+        }
+        return null;
+    }
+};
+
+/// 当前语言。与 FileId 表一样是进程级的：各个阶段都会产生消息，其中很多地方没有上下文对象
+var current: Locale = .en;
+
+pub fn locale() Locale {
+    return current;
+}
+
+pub fn setLocale(new_locale: Locale) void {
+    current = new_locale;
+}
+
+/// 从 PAW_LANG 选择语言（未设置时保持英文）
+pub fn initFromEnv() void {
+    const value = std.posix.getenv("PAW_LANG") orelse return;
+    if (value.len == 0) return;
+    if (Locale.parse(value)) |parsed| {
+        current = parsed;
+    } else {
+        std.debug.print("\x1b[1;33mwarning\x1b[0m: unknown PAW_LANG '{s}', using English (supported: en, zh)\n", .{value});
+    }
+}
+
+/// 取出所有子命令共用的 --locale <name> / --locale=<name>（-- 之前），返回其余参数（调用者释放）
+pub fn takeLocaleArgs(allocator: std.mem.Allocator, args: []const [:0]u8) ![][:0]u8 {
+    var rest = std.ArrayList([:0]u8){};
+    errdefer rest.deinit(allocator);
+    var i: usize = 0;
+    while (i < args.len) : (i += 1) {
+        const arg = args[i];
+        // -- 之后是传给程序的参数
+        if (std.mem.eql(u8, arg, "--")) {
+            try rest.appendSlice(allocator, args[i..]);
+            break;
+        }
+        const name: []const u8 = if (std.mem.startsWith(u8, arg, "--locale="))
+            arg["--locale=".len..]
+        else if (std.mem.eql(u8, arg, "--locale")) blk: {
+            if (i + 1 >= args.len) {
+                std.debug.print("Error: --locale requires a language (en or zh)\n", .{});
+                return error.InvalidLocale;
+            }
+            i += 1;
+            break :blk args[i];
+        } else {
+            try rest.append(allocator, arg);
+            continue;
+        };
+        current = Locale.parse(name) orelse {
+            std.debug.print("Error: unknown locale '{s}' (supported: en, zh)\n", .{name});
+            return error.InvalidLocale;
+        };
+    }
+    return rest.toOwnedSlice(allocator);
+}
+
+// ============================================================================
+// 消息表
+// ============================================================================
+
+pub const Id = enum {
+    // 诊断级别标签
+    level_error,
+    level_warning,
+    level_note,
+    level_help,
+
+    // 词法分析
+    unknown_character,
+    unterminated_string,
+    unterminated_byte,
+    unterminated_char,
+
+    // 语法分析
+    unexpected_token,
+
+    // 类型检查
+    missing_main,
+    assign_to_immutable,
+    assign_to_immutable_element,
+    variable_not_found,
+    invalid_assignment_target,
+    undefined_variable,
+    undefined_variable_note,
+    undefined_identifier,
+    did_you_mean,
+    unknown_function,
+    also_exported_by,
+    function_arg_count,
+    function_arg_mismatch,
+    static_method_arg_count,
+};
+
+const Template = struct {
+    en: []const u8,
+    zh: []const u8,
+};
+
+fn template(comptime id: Id) Template {
+    return switch (id) {
+        .level_error => .{ .en = "error", .zh = "错误" },
+        .level_warning => .{ .en = "warning", .zh = "警告" },
+        .level_note => .{ .en = "note", .zh = "注" },
+        .level_help => .{ .en = "help", .zh = "帮助" },
+
+        .unknown_character => .{
+            .en = "unknown character '{c}' at line {d} column {d}",
+            .zh = "未知字符 '{c}'（第 {d} 行第 {d} 列）",
+        },
+        .unterminated_string => .{ .en = "unterminated string", .zh = "字符串没有结束引号" },
+        .unterminated_byte => .{ .en = "unterminated byte literal", .zh = "字节字面量没有结束引号" },
+        .unterminated_char => .{ .en = "unterminated character literal", .zh = "字符字面量没有结束引号" },
+
+        .unexpected_token => .{
+            .en = "expected {any}, but found {any} at line {d} column {d}",
+            .zh = "期望 {any}，但得到 {any}（第 {d} 行第 {d} 列）",
+        },
+
+        .missing_main => .{ .en = "missing main function", .zh = "缺少 main 函数" },
+        .assign_to_immutable => .{
+            .en = "cannot assign to immutable variable '{s}'. Use 'let mut {s}' to make it mutable.",
+            .zh = "不能给不可变变量 '{s}' 赋值。使用 'let mut {s}' 声明可变变量。",
+        },
+        .assign_to_immutable_element => .{
+            .en = "cannot assign to an element of immutable array '{s}'. Use 'let mut {s}' to make it mutable.",
+            .zh = "不能给不可变数组 '{s}' 的元素赋值。使用 'let mut {s}' 声明可变数组。",
+        },
+        .variable_not_found => .{ .en = "variable '{s}' not found", .zh = "找不到变量 '{s}'" },
+        .invalid_assignment_target => .{ .en = "invalid assignment target", .zh = "无效的赋值目标" },
+        .undefined_variable => .{ .en = "undefined variable '{s}'", .zh = "未定义的变量 '{s}'" },
+        .undefined_variable_note => .{
+            .en = "variable '{s}' is not declared in this scope",
+            .zh = "变量 '{s}' 没有在当前作用域中声明",
+        },
+        .undefined_identifier => .{ .en = "undefined identifier", .zh = "未定义的标识符" },
+        .did_you_mean => .{ .en = "did you mean '{s}'?", .zh = "是不是想写 '{s}'？" },
+        .unknown_function => .{
+            .en = "cannot find function '{s}' in this scope",
+            .zh = "在当前作用域中找不到函数 '{s}'",
+        },
+        .also_exported_by => .{ .en = "also exported by: {s}", .zh = "同样导出它的模块：{s}" },
+        .function_arg_count => .{
+            .en = "function '{s}' expects {d} arguments, but got {d}",
+            .zh = "函数 '{s}' 需要 {d} 个参数，但传入了 {d} 个",
+        },
+        .function_arg_mismatch => .{
+            .en = "argument {d} type mismatch in '{s}'",
+            .zh = "第 {d} 个参数的类型与 '{s}' 的声明不符",
+        },
+        .static_method_arg_count => .{
+            .en = "method '{s}::{s}' expects {d} arguments, but got {d}",
+            .zh = "方法 '{s}::{s}' 需要 {d} 个参数，但传入了 {d} 个",
+        },
+    };
+}
+
+// 译文与英文模板的占位符必须一致
+comptime {
+    @setEvalBranchQuota(200_000);
+    for (std.enums.values(Id)) |id| {
+        const t = template(id);
+        for (@typeInfo(Template).@"struct".fields) |field| {
+            const text_for_locale = @field(t, field.name);
+            if (text_for_locale.len == 0) {
+                @compileError("message '" ++ @tagName(id) ++ "' has no " ++ field.name ++ " text");
+            }
+            if (!samePlaceholders(t.en, text_for_locale)) {
+                @compileError("message '" ++ @tagName(id) ++ "': the " ++ field.name ++ " text uses different placeholders than the en text");
+            }
+        }
+    }
+}
+
+/// 两个模板的 {...} 占位符序列是否相同（{{ 和 }} 是转义，不算占位符）
+fn samePlaceholders(comptime a: []const u8, comptime b: []const u8) bool {
+    var ia: usize = 0;
+    var ib: usize = 0;
+    while (true) {
+        const pa = nextPlaceholder(a, &ia);
+        const pb = nextPlaceholder(b, &ib);
+        if (pa == null or pb == null) return pa == null and pb == null;
+        if (!std.mem.eql(u8, pa.?, pb.?)) return false;
+    }
+}
+
+fn nextPlaceholder(comptime text: []const u8, comptime pos: *usize) ?[]const u8 {
+    while (pos.* < text.len) {
+        if (text[pos.*] == '{') {
+            if (pos.* + 1 < text.len and text[pos.* + 1] == '{') {
+                pos.* += 2;
+                continue;
+            }
+            const end = std.mem.indexOfScalarPos(u8, text, pos.*, '}') orelse return null;
+            const spec = text[pos.* .. end + 1];
+            pos.* = end + 1;
+            return spec;
+        }
+        pos.* += 1;
+    }
+    return null;
+}
+
+// ============================================================================
+// 取得消息
+// ============================================================================
+
+/// 当前语言下的消息（分配在 allocator 上，由调用者释放）
+pub fn format(allocator: std.mem.Allocator, comptime id: Id, args: anytype) ![]u8 {
+    return switch (current) {
+        inline else => |l| std.fmt.allocPrint(allocator, comptime @field(template(id), @tagName(l)), args),
+    };
+}
+
+/// 把当前语言下的消息打印到 stderr（带换行）
+pub fn print(comptime id: Id, args: anytype) void {
+    switch (current) {
+        inline else => |l| std.debug.print(comptime @field(template(id), @tagName(l)) ++ "\n", args),
+    }
+}
+
+/// 没有参数的消息（静态字符串，例如诊断级别标签）
+pub fn text(id: Id) []const u8 {
+    return switch (current) {
+        inline else => |l| switch (id) {
+            inline else => |i| comptime @field(template(i), @tagName(l)),
+        },
+    };
+}
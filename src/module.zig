@@ -3,17 +3,34 @@
 //! 支持语法：
 //!   import math.add;      // 导入math模块的add函数
 //!   import math.Vec2;     // 导入math模块的Vec2类型
+//!   import math.{add, Vec2};       // 选择性导入
+//!   import math as m;              // 命名空间别名：m.add(1, 2)
+//!   import "math::vec" as v;       // 字符串路径形式
 //!
 //! 模块查找规则：
 //!   1. import math.add -> 查找 math.paw
 //!   2. import math.vec.Vec2 -> 查找 math/vec.paw
 //!
+//! 🆕 搜索根目录（按顺序，先找到的优先）：
+//!   1. Paw.toml 依赖包（模块路径的第一段是包名时）
+//!   2. 项目根目录
+//!   3. Paw.toml [build] include 中的目录（按书写顺序）
+//!   4. PAW_PATH 中的目录（按书写顺序，分隔符与 PATH 相同）
+//! 都找不到时列出所有尝试过的路径。
+//!
 //! 只有标记为pub的声明才能被导入
+//!
+//! 重导出（门面模块）：
+//!   pub import math.vec.{Vec2, dot};   // 本模块的导入者可以直接 import math.Vec2
+//!   pub import "math::vec";            // 重导出 math/vec 的全部公共项
+//! 重导出会递归传播；重导出私有项会报错。
 
 const std = @import("std");
 const ast = @import("ast.zig");
 const Lexer = @import("lexer.zig").Lexer;
 const Parser = @import("parser.zig").Parser;
+const consteval = @import("consteval.zig");
+const namespace = @import("namespace.zig");
 
 /// 模块信息
 pub const Module = struct {
@@ -22,6 +39,7 @@ pub const Module = struct {
     source: []const u8,                       // 源代码（需要保留）
     declarations: []ast.TopLevelDecl,         // 所有声明
     public_items: std.StringHashMap(usize),   // pub项的索引（名称->索引）
+    reexports: std.StringHashMap(ast.TopLevelDecl),  // 🆕 pub import 重导出的项
     
     pub fn deinit(self: *Module, allocator: std.mem.Allocator) void {
         // 释放public_items中的键
@@ -31,6 +49,12 @@ pub const Module = struct {
         }
         self.public_items.deinit();
         
+        var re_it = self.reexports.iterator();
+        while (re_it.next()) |entry| {
+            allocator.free(entry.key_ptr.*);
+        }
+        self.reexports.deinit();
+        
         allocator.free(self.path);
         allocator.free(self.source_file);
         allocator.free(self.source);
@@ -38,15 +62,39 @@ pub const Module = struct {
     }
 };
 
+/// 🆕 项目根目录之外的导入搜索根目录
+pub const SearchRoot = struct {
+    dir: []const u8,
+    origin: Origin,
+    
+    pub const Origin = enum {
+        include,   // Paw.toml [build] include
+        paw_path,  // PAW_PATH 环境变量
+        
+        pub fn describe(self: Origin) []const u8 {
+            return switch (self) {
+                .include => "Paw.toml [build] include",
+                .paw_path => "PAW_PATH",
+            };
+        }
+    };
+};
+
 /// 模块加载器
 pub const ModuleLoader = struct {
     allocator: std.mem.Allocator,
     modules: std.StringHashMap(Module),
+    // 🆕 依赖包：包名 -> 包根目录（来自 Paw.toml + 全局缓存）
+    packages: std.StringHashMap([]const u8),
+    // 🆕 项目根目录之后依次搜索的目录
+    search_roots: std.ArrayList(SearchRoot),
     
     pub fn init(allocator: std.mem.Allocator) ModuleLoader {
         return ModuleLoader{
             .allocator = allocator,
             .modules = std.StringHashMap(Module).init(allocator),
+            .packages = std.StringHashMap([]const u8).init(allocator),
+            .search_roots = std.ArrayList(SearchRoot){},
         };
     }
     
@@ -56,6 +104,42 @@ pub const ModuleLoader = struct {
             entry.value_ptr.deinit(self.allocator);
         }
         self.modules.deinit();
+        
+        var pkg_it = self.packages.iterator();
+        while (pkg_it.next()) |entry| {
+            self.allocator.free(entry.key_ptr.*);
+            self.allocator.free(entry.value_ptr.*);
+        }
+        self.packages.deinit();
+        
+        for (self.search_roots.items) |root| self.allocator.free(root.dir);
+        self.search_roots.deinit(self.allocator);
+    }
+    
+    /// 🆕 添加搜索根目录（排在已添加的目录之后；重复的目录忽略）
+    pub fn addSearchRoot(self: *ModuleLoader, dir: []const u8, origin: SearchRoot.Origin) !void {
+        const trimmed = std.mem.trimRight(u8, dir, "/\\");
+        if (trimmed.len == 0 or std.mem.eql(u8, trimmed, ".")) return;  // 项目根目录总是最先搜索
+        for (self.search_roots.items) |root| {
+            if (std.mem.eql(u8, root.dir, trimmed)) return;
+        }
+        try self.search_roots.append(self.allocator, .{
+            .dir = try self.allocator.dupe(u8, trimmed),
+            .origin = origin,
+        });
+    }
+    
+    /// 🆕 注册依赖包：`import name.xxx` 将在 root_dir 下查找
+    pub fn addPackage(self: *ModuleLoader, name: []const u8, root_dir: []const u8) !void {
+        const name_copy = try self.allocator.dupe(u8, name);
+        errdefer self.allocator.free(name_copy);
+        const dir_copy = try self.allocator.dupe(u8, root_dir);
+        errdefer self.allocator.free(dir_copy);
+        
+        if (try self.packages.fetchPut(name_copy, dir_copy)) |old| {
+            self.allocator.free(old.key);
+            self.allocator.free(old.value);
+        }
     }
     
     /// 从模块中获取导入项
@@ -76,6 +160,11 @@ pub const ModuleLoader = struct {
             return module_ptr.declarations[idx];
         }
         
+        // 🆕 重导出的项
+        if (module_ptr.reexports.get(item_name)) |decl| {
+            return decl;
+        }
+        
         // 没找到
         std.debug.print("Error: Item '{s}' not found in module '{s}'\n", .{item_name, module_path});
         std.debug.print("  Available public items:\n", .{});
@@ -83,10 +172,144 @@ pub const ModuleLoader = struct {
         while (it.next()) |entry| {
             std.debug.print("    - {s}\n", .{entry.key_ptr.*});
         }
+        var re_it = module_ptr.reexports.iterator();
+        while (re_it.next()) |entry| {
+            std.debug.print("    - {s} (re-exported)\n", .{entry.key_ptr.*});
+        }
         
         return error.ItemNotFound;
     }
     
+    /// 🆕 合并导入后的程序声明
+    pub const ResolvedImports = struct {
+        all: std.ArrayList(ast.TopLevelDecl),       // import 替换为导入项之后的全部声明
+        own: std.ArrayList(ast.TopLevelDecl),       // 本文件的声明（分离编译）
+        imported: std.ArrayList(ast.TopLevelDecl),  // 导入的声明
+        errors: usize,                              // 失败的导入数（--fail-fast）
+        
+        pub fn deinit(self: *ResolvedImports, allocator: std.mem.Allocator) void {
+            self.all.deinit(allocator);
+            self.own.deinit(allocator);
+            self.imported.deinit(allocator);
+        }
+    };
+    
+    /// 🆕 把程序中的 import 替换为导入的项（按 import 出现的位置插入）
+    ///
+    /// 命名空间导入（import math as m）的函数和常量改名为 m__name（见 namespace.zig），
+    /// 改名后的声明分配在 name_arena 中；
+    /// 导入失败时打印错误、计数并继续处理其余导入
+    pub fn resolveImports(
+        self: *ModuleLoader,
+        allocator: std.mem.Allocator,
+        name_arena: std.mem.Allocator,
+        declarations: []const ast.TopLevelDecl,
+    ) !ResolvedImports {
+        var result = ResolvedImports{
+            .all = std.ArrayList(ast.TopLevelDecl){},
+            .own = std.ArrayList(ast.TopLevelDecl){},
+            .imported = std.ArrayList(ast.TopLevelDecl){},
+            .errors = 0,
+        };
+        errdefer result.deinit(allocator);
+        
+        for (declarations) |decl| {
+            if (decl != .import_decl) {
+                // 非import声明，直接添加
+                try result.all.append(allocator, decl);
+                try result.own.append(allocator, decl);
+                continue;
+            }
+            const import_decl = decl.import_decl;
+            
+            switch (import_decl.items) {
+                .single => |item_name| {
+                    // 单项导入：import math.add;
+                    const imported_item = self.getImportedItem(import_decl.module_path, item_name) catch |err| {
+                        std.debug.print("Error: Failed to import {s}.{s}: {any}\n", .{ import_decl.module_path, item_name, err });
+                        result.errors += 1;
+                        continue;
+                    };
+                    try result.all.append(allocator, imported_item);
+                    try result.imported.append(allocator, imported_item);
+                },
+                .multiple => |item_names| {
+                    // 多项导入：import math.{add, sub, Vec2};
+                    for (item_names) |item_name| {
+                        const imported_item = self.getImportedItem(import_decl.module_path, item_name) catch |err| {
+                            std.debug.print("Error: Failed to import {s}.{s}: {any}\n", .{ import_decl.module_path, item_name, err });
+                            result.errors += 1;
+                            continue;
+                        };
+                        try result.all.append(allocator, imported_item);
+                        try result.imported.append(allocator, imported_item);
+                    }
+                },
+                .namespace => |alias| {
+                    // 命名空间导入：import math as m;
+                    // 函数和常量以 m__name 形式可见（m.name 在解析时已改写），类型保留原名
+                    const module = self.getModule(import_decl.module_path) catch |err| {
+                        std.debug.print("Error: Failed to import {s} as {s}: {any}\n", .{ import_decl.module_path, alias, err });
+                        result.errors += 1;
+                        continue;
+                    };
+                    // 模块的全部函数和常量（包括私有的）改名导入，pub 类型保留原名
+                    var qualifier = try namespace.Qualifier.init(name_arena, alias, module.declarations);
+                    defer qualifier.deinit();
+                    for (module.declarations, 0..) |item, idx| {
+                        if (item == .import_decl) continue;
+                        if (!namespace.Qualifier.isRenamed(item) and !isPublicIndex(module, idx)) continue;
+                        const imported_item = try qualifier.qualifyDecl(item);
+                        try result.all.append(allocator, imported_item);
+                        try result.imported.append(allocator, imported_item);
+                    }
+                    var reexport_it = module.reexports.valueIterator();
+                    while (reexport_it.next()) |decl_ptr| {
+                        var imported_item = decl_ptr.*;
+                        if (imported_item == .function) {
+                            imported_item.function.name = try ast.namespacedName(name_arena, alias, imported_item.function.name);
+                        }
+                        try result.all.append(allocator, imported_item);
+                        try result.imported.append(allocator, imported_item);
+                    }
+                },
+            }
+        }
+        return result;
+    }
+    
+    /// 🆕 分离编译：加载已加载的模块导入的所有模块（main → a → b 中的 b），直到没有新模块
+    pub fn loadTransitiveImports(self: *ModuleLoader) !void {
+        var visited = std.StringHashMap(void).init(self.allocator);
+        defer visited.deinit();
+        while (self.nextUnvisited(&visited)) |module_path| {
+            try visited.put(module_path, {});
+            // 加载模块可能使模块表扩容，先取出声明
+            const declarations = self.modules.getPtr(module_path).?.declarations;
+            for (declarations) |decl| {
+                if (decl != .import_decl) continue;
+                _ = try self.getModule(decl.import_decl.module_path);
+            }
+        }
+    }
+    
+    /// 模块表中还没有处理过的模块（加载模块会改变模块表，每次重新查找）
+    fn nextUnvisited(self: *ModuleLoader, visited: *const std.StringHashMap(void)) ?[]const u8 {
+        var it = self.modules.keyIterator();
+        while (it.next()) |path| {
+            if (!visited.contains(path.*)) return path.*;
+        }
+        return null;
+    }
+    
+    /// 🆕 获取（必要时加载）模块，用于命名空间导入
+    pub fn getModule(self: *ModuleLoader, module_path: []const u8) !*Module {
+        if (!self.modules.contains(module_path)) {
+            try self.loadModuleInternal(module_path);
+        }
+        return self.modules.getPtr(module_path).?;
+    }
+    
     /// 内部方法：加载模块
     fn loadModuleInternal(self: *ModuleLoader, module_path: []const u8) !void {
         // 查找模块文件
@@ -107,6 +330,8 @@ pub const ModuleLoader = struct {
         
         var parser = Parser.init(self.allocator, tokens);
         const program = try parser.parse();
+        // 模块的私有常量和数组长度在模块内求值；导入它的程序合并声明后再求值一遍
+        try consteval.evaluateProgram(parser.arena.allocator(), program, consteval.MAX_STEPS);
         
         // 收集pub声明
         var public_items = std.StringHashMap(usize).init(self.allocator);
@@ -114,6 +339,7 @@ pub const ModuleLoader = struct {
             const name = switch (decl) {
                 .function => |f| if (f.is_public) f.name else null,
                 .type_decl => |td| if (td.is_public) td.name else null,
+                .const_decl => |cd| if (cd.is_public) cd.name else null,  // 🆕
                 else => null,
             };
             
@@ -130,41 +356,298 @@ pub const ModuleLoader = struct {
             .source = source,
             .declarations = program.declarations,
             .public_items = public_items,
+            .reexports = std.StringHashMap(ast.TopLevelDecl).init(self.allocator),
         };
         
         try self.modules.put(try self.allocator.dupe(u8, module_path), module);
+        
+        // 🆕 处理 pub import（模块已注册，循环导入不会无限递归）
+        for (program.declarations) |decl| {
+            if (decl != .import_decl or !decl.import_decl.is_public) continue;
+            try self.resolveReexports(module_path, decl.import_decl);
+        }
+    }
+    
+    /// 🆕 解析模块中的一条 pub import
+    fn resolveReexports(self: *ModuleLoader, module_path: []const u8, import_decl: ast.ImportDecl) !void {
+        switch (import_decl.items) {
+            .single => |item_name| try self.addReexport(module_path, import_decl.module_path, item_name),
+            .multiple => |item_names| {
+                for (item_names) |item_name| {
+                    try self.addReexport(module_path, import_decl.module_path, item_name);
+                }
+            },
+            .namespace => {
+                // 重导出目标模块的全部公共项（包括它自己的重导出）
+                const target = try self.getModule(import_decl.module_path);
+                var names = std.ArrayList([]const u8){};
+                defer names.deinit(self.allocator);
+                var it = target.public_items.keyIterator();
+                while (it.next()) |key| try names.append(self.allocator, key.*);
+                var re_it = target.reexports.keyIterator();
+                while (re_it.next()) |key| try names.append(self.allocator, key.*);
+                
+                for (names.items) |item_name| {
+                    try self.addReexport(module_path, import_decl.module_path, item_name);
+                }
+            },
+        }
+    }
+    
+    /// 🆕 将 target_path 中的 item_name 重导出到 module_path
+    fn addReexport(
+        self: *ModuleLoader,
+        module_path: []const u8,
+        target_path: []const u8,
+        item_name: []const u8,
+    ) !void {
+        const target = try self.getModule(target_path);
+        if (!target.public_items.contains(item_name) and !target.reexports.contains(item_name)) {
+            if (isPrivateItem(target, item_name)) {
+                std.debug.print("\x1b[1;31merror\x1b[0m: cannot re-export private item '{s}'\n", .{item_name});
+                std.debug.print("   \x1b[1;36m-->\x1b[0m pub import in module '{s}'\n", .{module_path});
+                std.debug.print("   \x1b[1;36m= note\x1b[0m: '{s}' is declared without 'pub' in {s}\n", .{item_name, target.source_file});
+                std.debug.print("   \x1b[1;32m= help\x1b[0m: mark it 'pub' or use a plain 'import'\n\n", .{});
+                return error.PrivateReexport;
+            }
+        }
+        
+        const decl = try self.getImportedItem(target_path, item_name);
+        
+        const module_ptr = self.modules.getPtr(module_path).?;
+        if (module_ptr.reexports.contains(item_name)) return;
+        try module_ptr.reexports.put(try self.allocator.dupe(u8, item_name), decl);
+    }
+    
+    /// 模块的第 idx 个声明是否是 pub 项
+    fn isPublicIndex(module: *const Module, idx: usize) bool {
+        var it = module.public_items.valueIterator();
+        while (it.next()) |public_idx| {
+            if (public_idx.* == idx) return true;
+        }
+        return false;
+    }
+    
+    /// 模块中是否存在同名但未标记 pub 的声明
+    fn isPrivateItem(module: *const Module, item_name: []const u8) bool {
+        for (module.declarations) |decl| {
+            switch (decl) {
+                .function => |f| if (!f.is_public and std.mem.eql(u8, f.name, item_name)) return true,
+                .type_decl => |td| if (!td.is_public and std.mem.eql(u8, td.name, item_name)) return true,
+                .const_decl => |cd| if (!cd.is_public and std.mem.eql(u8, cd.name, item_name)) return true,
+                else => {},
+            }
+        }
+        return false;
     }
     
     /// 查找模块文件
     fn findModuleFile(self: *ModuleLoader, module_path: []const u8) ![]const u8 {
-        // 尝试1: module_path.paw
-        var buf = std.ArrayList(u8){};
-        try buf.appendSlice(self.allocator, module_path);
-        try buf.appendSlice(self.allocator, ".paw");
-        const file1 = try buf.toOwnedSlice(self.allocator);
-        
-        if (std.fs.cwd().access(file1, .{})) {
-            return file1;
-        } else |_| {
-            self.allocator.free(file1);
+        // 🆕 依赖包：foo/bar -> <foo根目录>/bar.paw，foo -> <foo根目录>/mod.paw
+        const first_end = std.mem.indexOfScalar(u8, module_path, '/') orelse module_path.len;
+        if (self.packages.get(module_path[0..first_end])) |root_dir| {
+            const rest = module_path[first_end..];
+            const candidates = [_][]const u8{ ".paw", "/mod.paw" };
+            for (candidates) |suffix| {
+                // 包根模块只有 mod.paw 形式
+                if (rest.len == 0 and suffix[0] == '.') continue;
+                const file = try std.fmt.allocPrint(self.allocator, "{s}{s}{s}", .{ root_dir, rest, suffix });
+                if (std.fs.cwd().access(file, .{})) {
+                    return file;
+                } else |_| {
+                    self.allocator.free(file);
+                }
+            }
         }
         
-        // 尝试2: module_path/mod.paw
-        buf = std.ArrayList(u8){};
-        try buf.appendSlice(self.allocator, module_path);
-        try buf.appendSlice(self.allocator, "/mod.paw");
-        const file2 = try buf.toOwnedSlice(self.allocator);
-        
-        if (std.fs.cwd().access(file2, .{})) {
-            return file2;
-        } else |_| {
-            self.allocator.free(file2);
+        // 项目根目录，然后是 [build] include 和 PAW_PATH 中的目录
+        var tried = std.ArrayList([]const u8){};
+        defer {
+            for (tried.items) |file| self.allocator.free(file);
+            tried.deinit(self.allocator);
+        }
+        var root_index: usize = 0;
+        while (root_index <= self.search_roots.items.len) : (root_index += 1) {
+            for ([_][]const u8{ ".paw", "/mod.paw" }) |suffix| {
+                const file = if (root_index == 0)
+                    try std.fmt.allocPrint(self.allocator, "{s}{s}", .{ module_path, suffix })
+                else
+                    try std.fmt.allocPrint(self.allocator, "{s}/{s}{s}", .{ self.search_roots.items[root_index - 1].dir, module_path, suffix });
+                if (std.fs.cwd().access(file, .{})) {
+                    return file;
+                } else |_| {
+                    try tried.append(self.allocator, file);
+                }
+            }
         }
         
-        // 都找不到
+        // 都找不到：按搜索顺序列出所有尝试过的路径
         std.debug.print("Error: Module not found: {s}\n", .{module_path});
-        std.debug.print("  Tried: {s}.paw\n", .{module_path});
-        std.debug.print("  Tried: {s}/mod.paw\n", .{module_path});
+        for (tried.items, 0..) |file, i| {
+            if (i < 2) {
+                std.debug.print("  Tried: {s}\n", .{file});
+            } else {
+                std.debug.print("  Tried: {s} ({s})\n", .{ file, self.search_roots.items[i / 2 - 1].origin.describe() });
+            }
+        }
+        if (self.search_roots.items.len == 0) {
+            std.debug.print("  help: add directories to search with `include = [\"dir\"]` in the [build] section of Paw.toml, or with PAW_PATH\n", .{});
+        }
         return error.ModuleNotFound;
     }
 };
+
+// ============================================================================
+// 🆕 导出索引：名称 -> 可导入的模块路径（用于“未知函数”诊断的导入建议）
+// ============================================================================
+
+/// 项目根目录和依赖包中所有 pub 函数/类型的索引
+/// 只登记扫描根目录，第一次查询时才扫描文件（避免无错误时的开销）
+pub const ExportIndex = struct {
+    allocator: std.mem.Allocator,
+    roots: std.ArrayList(Root),
+    entries: std.StringHashMap(std.ArrayList([]const u8)),
+    built: bool,
+    
+    const Root = struct {
+        dir: []const u8,
+        prefix: ?[]const u8,  // 依赖包名（项目根目录为 null）
+    };
+    
+    pub fn init(allocator: std.mem.Allocator) ExportIndex {
+        return ExportIndex{
+            .allocator = allocator,
+            .roots = std.ArrayList(Root){},
+            .entries = std.StringHashMap(std.ArrayList([]const u8)).init(allocator),
+            .built = false,
+        };
+    }
+    
+    pub fn deinit(self: *ExportIndex) void {
+        for (self.roots.items) |root| {
+            self.allocator.free(root.dir);
+            if (root.prefix) |p| self.allocator.free(p);
+        }
+        self.roots.deinit(self.allocator);
+        
+        var it = self.entries.iterator();
+        while (it.next()) |entry| {
+            self.allocator.free(entry.key_ptr.*);
+            for (entry.value_ptr.items) |path| self.allocator.free(path);
+            entry.value_ptr.deinit(self.allocator);
+        }
+        self.entries.deinit();
+    }
+    
+    /// 登记项目根目录、搜索根目录和模块加载器中已注册的依赖包
+    pub fn addRoots(self: *ExportIndex, project_root: []const u8, loader: *const ModuleLoader) !void {
+        try self.roots.append(self.allocator, .{
+            .dir = try self.allocator.dupe(u8, project_root),
+            .prefix = null,
+        });
+        for (loader.search_roots.items) |root| {
+            try self.roots.append(self.allocator, .{
+                .dir = try self.allocator.dupe(u8, root.dir),
+                .prefix = null,
+            });
+        }
+        var it = loader.packages.iterator();
+        while (it.next()) |entry| {
+            try self.roots.append(self.allocator, .{
+                .dir = try self.allocator.dupe(u8, entry.value_ptr.*),
+                .prefix = try self.allocator.dupe(u8, entry.key_ptr.*),
+            });
+        }
+    }
+    
+    /// 查找导出了 name 的模块路径（如 "math/vec"）
+    pub fn lookup(self: *ExportIndex, name: []const u8) []const []const u8 {
+        if (!self.built) {
+            self.built = true;
+            self.build() catch {};
+        }
+        if (self.entries.get(name)) |paths| return paths.items;
+        return &[_][]const u8{};
+    }
+    
+    fn build(self: *ExportIndex) !void {
+        for (self.roots.items) |root| {
+            var dir = std.fs.cwd().openDir(root.dir, .{ .iterate = true }) catch continue;
+            defer dir.close();
+            
+            var walker = try dir.walk(self.allocator);
+            defer walker.deinit();
+            while (try walker.next()) |entry| {
+                if (entry.kind != .file or !std.mem.endsWith(u8, entry.path, ".paw")) continue;
+                if (isIgnoredPath(entry.path)) continue;
+                
+                const module_path = try modulePathFor(self.allocator, root.prefix, entry.path);
+                defer self.allocator.free(module_path);
+                if (module_path.len == 0) continue;
+                
+                const full_path = try std.fs.path.join(self.allocator, &[_][]const u8{ root.dir, entry.path });
+                defer self.allocator.free(full_path);
+                try self.indexFile(full_path, module_path);
+            }
+        }
+    }
+    
+    /// 扫描 token 序列中的 `pub fn name` / `pub type name`
+    fn indexFile(self: *ExportIndex, file_path: []const u8, module_path: []const u8) !void {
+        const source = std.fs.cwd().readFileAlloc(self.allocator, file_path, 10 * 1024 * 1024) catch return;
+        defer self.allocator.free(source);
+        
+        var lexer = Lexer.init(self.allocator, source, file_path);
+        defer lexer.deinit();
+        const tokens = lexer.tokenize() catch return;
+        
+        var i: usize = 0;
+        while (i + 2 < tokens.len) : (i += 1) {
+            if (tokens[i].type != .keyword_pub) continue;
+            const kind = tokens[i + 1].type;
+            if (kind != .keyword_fn and kind != .keyword_type) continue;
+            if (tokens[i + 2].type != .identifier) continue;
+            try self.add(tokens[i + 2].lexeme, module_path);
+        }
+    }
+    
+    fn add(self: *ExportIndex, name: []const u8, module_path: []const u8) !void {
+        const gop = try self.entries.getOrPut(name);
+        if (!gop.found_existing) {
+            gop.key_ptr.* = try self.allocator.dupe(u8, name);
+            gop.value_ptr.* = std.ArrayList([]const u8){};
+        }
+        for (gop.value_ptr.items) |existing| {
+            if (std.mem.eql(u8, existing, module_path)) return;
+        }
+        try gop.value_ptr.append(self.allocator, try self.allocator.dupe(u8, module_path));
+    }
+    
+    fn isIgnoredPath(path: []const u8) bool {
+        var parts = std.mem.splitAny(u8, path, "/\\");
+        while (parts.next()) |part| {
+            if (part.len > 0 and part[0] == '.') return true;
+            if (std.mem.eql(u8, part, "zig-out") or std.mem.eql(u8, part, "node_modules")) return true;
+        }
+        return false;
+    }
+    
+    /// math/vec.paw -> math/vec，math/mod.paw -> math，依赖包加上包名前缀
+    fn modulePathFor(allocator: std.mem.Allocator, prefix: ?[]const u8, rel_path: []const u8) ![]const u8 {
+        var path = rel_path[0 .. rel_path.len - ".paw".len];
+        if (std.mem.eql(u8, path, "mod")) {
+            path = "";
+        } else if (std.mem.endsWith(u8, path, "/mod")) {
+            path = path[0 .. path.len - "/mod".len];
+        }
+        
+        var buf = std.ArrayList(u8){};
+        errdefer buf.deinit(allocator);
+        if (prefix) |p| {
+            try buf.appendSlice(allocator, p);
+            if (path.len > 0) try buf.append(allocator, '/');
+        }
+        for (path) |c| try buf.append(allocator, if (c == '\\') '/' else c);
+        return buf.toOwnedSlice(allocator);
+    }
+};
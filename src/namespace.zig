@@ -0,0 +1,294 @@
+//! Namespace - 命名空间导入（import math as m）的改名
+//!
+//! 模块的所有顶层函数和常量（包括私有的）以 m__name 的形式导入，m.name 在解析时已改写为 m__name。
+//! 模块内部对这些函数和常量的引用同样改写，模块中的函数互相调用（包括调用私有辅助函数）时
+//! 才能找到改名后的函数。类型保留原名（只导入 pub 类型），但类型方法中对模块函数的引用也改写。
+//!
+//! 模块的 AST 可能同时被其他导入使用（import math.add 与 import math as m），所以改写在副本上进行：
+//! 函数体的语句和表达式重新分配，名字、类型等叶子数据共享；类型检查器填写的字段
+//! （resolved_operator、resolved_type_args、length 等）在副本中有自己的存储。参数、局部变量、循环变量和模式绑定
+//! 遮蔽同名的模块函数（与 track_caller.zig 一样按整个函数计）。
+//!
+//! 私有的函数和常量仍然导入（pub 函数要调用它们），副本记录导入时的别名（namespace 字段）：
+//! 类型检查只允许同一别名的副本引用它们，导入者写 m.private_fn() 时报错。
+//!
+//! 示例：
+//!   // math.paw
+//!   fn clamp(x: i32) -> i32 { if x > 100 { 100 } else { x } }
+//!   pub fn scale(x: i32) -> i32 { clamp(x * 10) }
+//!   // main.paw
+//!   import math as m;
+//!   m.scale(3)   // 调用 m__scale，其中的 clamp(...) 改写为 m__clamp(...)
+
+const std = @import("std");
+const ast = @import("ast.zig");
+
+pub const Qualifier = struct {
+    allocator: std.mem.Allocator,      // 与 AST 同生命周期（副本和新名字都分配在这里）
+    alias: []const u8,
+    names: std.StringHashMap(void),    // 模块的顶层函数和常量名
+    locals: std.StringHashMap(void),   // 当前函数的参数和局部变量（遮蔽同名的模块函数）
+
+    pub fn init(allocator: std.mem.Allocator, alias: []const u8, declarations: []const ast.TopLevelDecl) !Qualifier {
+        var qualifier = Qualifier{
+            .allocator = allocator,
+            .alias = alias,
+            .names = std.StringHashMap(void).init(allocator),
+            .locals = std.StringHashMap(void).init(allocator),
+        };
+        errdefer qualifier.deinit();
+        for (declarations) |decl| {
+            if (isRenamed(decl)) try qualifier.names.put(declName(decl), {});
+        }
+        return qualifier;
+    }
+
+    pub fn deinit(self: *Qualifier) void {
+        self.names.deinit();
+        self.locals.deinit();
+    }
+
+    /// 函数和常量改名导入（包括私有的）；其他声明保留原名
+    pub fn isRenamed(decl: ast.TopLevelDecl) bool {
+        return decl == .function or decl == .const_decl;
+    }
+
+    fn declName(decl: ast.TopLevelDecl) []const u8 {
+        return switch (decl) {
+            .function => |f| f.name,
+            .const_decl => |cd| cd.name,
+            else => unreachable,
+        };
+    }
+
+    /// 模块声明的副本：函数和常量改名，函数体和方法体中对模块函数的引用改写
+    pub fn qualifyDecl(self: *Qualifier, decl: ast.TopLevelDecl) std.mem.Allocator.Error!ast.TopLevelDecl {
+        switch (decl) {
+            .function => |func| {
+                var copy = try self.qualifyFunction(func);
+                // extern 函数改名后仍链接到原来的符号
+                if (copy.is_extern and copy.link_name == null) {
+                    copy.link_name = .{ .default = func.name, .overrides = &.{} };
+                }
+                copy.name = try ast.namespacedName(self.allocator, self.alias, func.name);
+                copy.source_name = func.name;
+                return .{ .function = copy };
+            },
+            .const_decl => |cd| {
+                var copy = cd;
+                self.locals.clearRetainingCapacity();
+                copy.value = try self.qualifyExprPtr(cd.value);
+                copy.name = try ast.namespacedName(self.allocator, self.alias, cd.name);
+                copy.namespace = self.alias;
+                return .{ .const_decl = copy };
+            },
+            .type_decl => |td| {
+                var copy = td;
+                switch (td.kind) {
+                    .struct_type => |st| copy.kind.struct_type.methods = try self.qualifyMethods(st.methods),
+                    .enum_type => |et| copy.kind.enum_type.methods = try self.qualifyMethods(et.methods),
+                    .trait_type, .alias => {},
+                }
+                return .{ .type_decl = copy };
+            },
+            else => return decl,
+        }
+    }
+
+    fn qualifyMethods(self: *Qualifier, methods: []const ast.FunctionDecl) ![]ast.FunctionDecl {
+        const copies = try self.allocator.alloc(ast.FunctionDecl, methods.len);
+        for (methods, 0..) |method, i| copies[i] = try self.qualifyFunction(method);
+        return copies;
+    }
+
+    fn qualifyFunction(self: *Qualifier, func: ast.FunctionDecl) !ast.FunctionDecl {
+        self.locals.clearRetainingCapacity();
+        for (func.params) |param| try self.locals.put(param.name, {});
+        var copy = func;
+        copy.body = try self.qualifyStmts(func.body);
+        copy.namespace = self.alias;
+        return copy;
+    }
+
+    fn qualifyStmts(self: *Qualifier, stmts: []const ast.Stmt) std.mem.Allocator.Error![]ast.Stmt {
+        const copies = try self.allocator.alloc(ast.Stmt, stmts.len);
+        for (stmts, 0..) |stmt, i| copies[i] = try self.qualifyStmt(stmt);
+        return copies;
+    }
+
+    fn qualifyStmt(self: *Qualifier, stmt: ast.Stmt) std.mem.Allocator.Error!ast.Stmt {
+        var copy = stmt;
+        switch (copy) {
+            .expr => |*expr| expr.* = try self.qualifyExpr(expr.*),
+            .let_decl => |*let| {
+                if (let.init) |init| let.init = try self.qualifyExpr(init);
+                try self.locals.put(let.name, {});
+            },
+            .assign => |*assign| {
+                assign.target = try self.qualifyExpr(assign.target);
+                assign.value = try self.qualifyExpr(assign.value);
+            },
+            .compound_assign => |*assign| {
+                assign.target = try self.qualifyExpr(assign.target);
+                assign.value = try self.qualifyExpr(assign.value);
+            },
+            .return_stmt => |*value| if (value.*) |expr| {
+                value.* = try self.qualifyExpr(expr);
+            },
+            .break_stmt => |*brk| if (brk.value) |expr| {
+                brk.value = try self.qualifyExpr(expr);
+            },
+            .continue_stmt => {},
+            .loop_stmt => |*loop| {
+                if (loop.condition) |cond| loop.condition = try self.qualifyExpr(cond);
+                if (loop.iterator) |*iter| {
+                    iter.iterable = try self.qualifyExpr(iter.iterable);
+                    try self.locals.put(iter.binding, {});
+                }
+                loop.body = try self.qualifyStmts(loop.body);
+            },
+            .while_loop => |*loop| {
+                loop.condition = try self.qualifyExpr(loop.condition);
+                loop.body = try self.qualifyStmts(loop.body);
+            },
+            .for_loop => |*loop| {
+                if (loop.init) |init| {
+                    const init_copy = try self.allocator.create(ast.Stmt);
+                    init_copy.* = try self.qualifyStmt(init.*);
+                    loop.init = init_copy;
+                }
+                if (loop.condition) |cond| loop.condition = try self.qualifyExpr(cond);
+                if (loop.step) |step| loop.step = try self.qualifyExpr(step);
+                loop.body = try self.qualifyStmts(loop.body);
+            },
+        }
+        return copy;
+    }
+
+    fn qualifyExprPtr(self: *Qualifier, expr: *const ast.Expr) std.mem.Allocator.Error!*ast.Expr {
+        const copy = try self.allocator.create(ast.Expr);
+        copy.* = try self.qualifyExpr(expr.*);
+        return copy;
+    }
+
+    fn qualifyExprs(self: *Qualifier, exprs: []const ast.Expr) std.mem.Allocator.Error![]ast.Expr {
+        const copies = try self.allocator.alloc(ast.Expr, exprs.len);
+        for (exprs, 0..) |expr, i| copies[i] = try self.qualifyExpr(expr);
+        return copies;
+    }
+
+    fn qualifyExpr(self: *Qualifier, expr: ast.Expr) std.mem.Allocator.Error!ast.Expr {
+        var copy = expr;
+        switch (copy) {
+            .identifier => |name| {
+                if (self.names.contains(name) and !self.locals.contains(name)) {
+                    copy.identifier = try ast.namespacedName(self.allocator, self.alias, name);
+                }
+            },
+            .binary => |*bin| {
+                bin.left = try self.qualifyExprPtr(bin.left);
+                bin.right = try self.qualifyExprPtr(bin.right);
+                bin.resolved_operator = try self.copySlot(bin.resolved_operator);
+            },
+            .unary => |*un| un.operand = try self.qualifyExprPtr(un.operand),
+            .call => |*call| {
+                call.callee = try self.qualifyExprPtr(call.callee);
+                call.args = try self.qualifyExprs(call.args);
+                call.resolved_type_args = try self.copySlot(call.resolved_type_args);
+                call.resolved_into = try self.copySlot(call.resolved_into);
+                call.resolved_receiver = try self.copySlot(call.resolved_receiver);
+            },
+            .static_method_call => |*call| call.args = try self.qualifyExprs(call.args),
+            .field_access => |*access| access.object = try self.qualifyExprPtr(access.object),
+            .struct_init => |*init| {
+                const fields = try self.allocator.alloc(ast.StructFieldInit, init.fields.len);
+                for (init.fields, 0..) |field, i| {
+                    fields[i] = .{ .name = field.name, .value = try self.qualifyExpr(field.value) };
+                }
+                init.fields = fields;
+            },
+            .enum_variant => |*variant| variant.args = try self.qualifyExprs(variant.args),
+            .block => |*stmts| stmts.* = try self.qualifyStmts(stmts.*),
+            .if_expr => |*if_expr| {
+                if_expr.condition = try self.qualifyExprPtr(if_expr.condition);
+                if_expr.then_branch = try self.qualifyExprPtr(if_expr.then_branch);
+                if (if_expr.else_branch) |else_branch| if_expr.else_branch = try self.qualifyExprPtr(else_branch);
+            },
+            .is_expr => |*is_expr| {
+                is_expr.value = try self.qualifyExprPtr(is_expr.value);
+                const arms = try self.allocator.alloc(ast.IsArm, is_expr.arms.len);
+                for (is_expr.arms, 0..) |arm, i| {
+                    try self.addPatternBindings(arm.pattern);
+                    arms[i] = arm;
+                    if (arm.guard) |guard| arms[i].guard = try self.qualifyExpr(guard);
+                    arms[i].body = try self.qualifyExpr(arm.body);
+                }
+                is_expr.arms = arms;
+            },
+            .match_expr => |*match_expr| {
+                match_expr.value = try self.qualifyExprPtr(match_expr.value);
+                const arms = try self.allocator.alloc(ast.MatchArm, match_expr.arms.len);
+                for (match_expr.arms, 0..) |arm, i| {
+                    try self.addPatternBindings(arm.pattern);
+                    arms[i] = .{ .pattern = arm.pattern, .body = try self.qualifyExpr(arm.body) };
+                }
+                match_expr.arms = arms;
+            },
+            .as_expr => |*as_expr| {
+                as_expr.value = try self.qualifyExprPtr(as_expr.value);
+                as_expr.resolved_from = try self.copySlot(as_expr.resolved_from);
+            },
+            .await_expr => |*inner| inner.* = try self.qualifyExprPtr(inner.*),
+            .array_literal => |*elements| elements.* = try self.qualifyExprs(elements.*),
+            .array_index => |*index| {
+                index.array = try self.qualifyExprPtr(index.array);
+                index.index = try self.qualifyExprPtr(index.index);
+                index.length = try self.copySlot(index.length);
+            },
+            .range => |*range| {
+                range.start = try self.qualifyExprPtr(range.start);
+                range.end = try self.qualifyExprPtr(range.end);
+            },
+            .string_interp => |*interp| {
+                const parts = try self.allocator.alloc(ast.StringInterpPart, interp.parts.len);
+                for (interp.parts, 0..) |part, i| parts[i] = switch (part) {
+                    .expr => |inner| .{ .expr = try self.qualifyExpr(inner) },
+                    .literal => part,
+                };
+                interp.parts = parts;
+            },
+            .try_expr => |*inner| inner.* = try self.qualifyExprPtr(inner.*),
+            .loop_expr => |*loop| {
+                loop.body = try self.qualifyStmts(loop.body);
+                loop.result_type = try self.copySlot(loop.result_type);
+            },
+            .lambda => |*lambda| {
+                // 副本有自己的捕获列表和提升后的名字（由类型检查和闭包转换填写）
+                for (lambda.params) |param| try self.locals.put(param.name, {});
+                lambda.body = try self.qualifyStmts(lambda.body);
+                lambda.captures = try self.allocator.create([]ast.Capture);
+                lambda.captures.* = &.{};
+                lambda.lifted_name = try self.allocator.create([]const u8);
+                lambda.lifted_name.* = "";
+            },
+            .int_literal, .float_literal, .string_literal, .char_literal, .byte_literal,
+            .byte_string_literal, .bool_literal, .type_query => {},
+        }
+        return copy;
+    }
+
+    /// 类型检查器填写的字段：副本分配自己的存储，两处导入各自记录检查结果
+    fn copySlot(self: *Qualifier, slot: anytype) std.mem.Allocator.Error!@TypeOf(slot) {
+        const copy = try self.allocator.create(@TypeOf(slot.*));
+        copy.* = slot.*;
+        return copy;
+    }
+    
+    fn addPatternBindings(self: *Qualifier, pattern: ast.Pattern) !void {
+        switch (pattern) {
+            .identifier => |name| try self.locals.put(name, {}),
+            .variant => |variant| for (variant.bindings) |name| try self.locals.put(name, {}),
+            .literal, .wildcard, .range, .alternatives => {},
+        }
+    }
+};
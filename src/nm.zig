@@ -0,0 +1,228 @@
+//! nm - 目标文件 / 可执行文件符号查看器
+//!
+//! pawc nm <obj-or-exe>
+//!   列出由 Paw 代码生成的符号（函数、全局数据），显示还原后的名称、
+//!   大小和所在段，过滤掉 libc / C 运行时的符号。
+//!   用于排查 "undefined reference to Type_method" 之类的链接错误。
+//!
+//! 目前支持 64 位小端 ELF（Linux）。
+
+const std = @import("std");
+const elf = std.elf;
+
+pub const Symbol = struct {
+    name: []const u8,       // 原始符号名
+    demangled: []const u8,  // 还原后的 Paw 名称
+    size: u64,
+    section: []const u8,
+    is_function: bool,
+};
+
+/// C 运行时 / 链接器生成的符号（不以 _ 开头的那部分）
+const RUNTIME_SYMBOLS = [_][]const u8{
+    "frame_dummy",
+    "register_tm_clones",
+    "deregister_tm_clones",
+    "completed.0",
+    "data_start",
+    "environ",
+    "stdin",
+    "stdout",
+    "stderr",
+};
+
+/// 基础类型名（用于识别单态化后缀，如 Vec_i32_push）
+const PRIMITIVE_TYPES = [_][]const u8{
+    "i8", "i16", "i32", "i64", "i128",
+    "u8", "u16", "u32", "u64", "u128",
+    "f32", "f64", "bool", "char", "string",
+};
+
+/// 读取文件并列出 Paw 符号
+pub fn listSymbols(allocator: std.mem.Allocator, path: []const u8) !void {
+    const data = std.fs.cwd().readFileAlloc(allocator, path, 512 * 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read {s}: {any}\n", .{ path, err });
+        return err;
+    };
+    defer allocator.free(data);
+
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+
+    const symbols = readElfSymbols(arena.allocator(), data) catch |err| {
+        switch (err) {
+            error.NotElf => std.debug.print("Error: {s} is not an ELF object or executable\n", .{path}),
+            error.UnsupportedElf => std.debug.print("Error: {s}: only 64-bit little-endian ELF is supported\n", .{path}),
+            error.NoSymbolTable => std.debug.print("Error: {s} has no symbol table (stripped?)\n", .{path}),
+            error.Truncated => std.debug.print("Error: {s} is truncated or corrupt\n", .{path}),
+            else => std.debug.print("Error: Cannot parse {s}: {any}\n", .{ path, err }),
+        }
+        return err;
+    };
+
+    std.mem.sort(Symbol, symbols, {}, lessThan);
+
+    std.debug.print("\x1b[1m{s:>8}  {s:<10} {s:<4} {s}\x1b[0m\n", .{ "SIZE", "SECTION", "KIND", "NAME" });
+    for (symbols) |sym| {
+        const kind = if (sym.is_function) "fn" else "data";
+        if (std.mem.eql(u8, sym.name, sym.demangled)) {
+            std.debug.print("{d:>8}  {s:<10} {s:<4} {s}\n", .{ sym.size, sym.section, kind, sym.name });
+        } else {
+            std.debug.print("{d:>8}  {s:<10} {s:<4} {s} \x1b[2m({s})\x1b[0m\n", .{ sym.size, sym.section, kind, sym.demangled, sym.name });
+        }
+    }
+    std.debug.print("\n{d} Paw symbol(s)\n", .{symbols.len});
+}
+
+fn lessThan(_: void, a: Symbol, b: Symbol) bool {
+    const order = std.mem.order(u8, a.section, b.section);
+    if (order != .eq) return order == .lt;
+    return std.mem.order(u8, a.demangled, b.demangled) == .lt;
+}
+
+// ============================================================================
+// ELF 解析
+// ============================================================================
+
+fn readStruct(comptime T: type, data: []const u8, offset: u64) !T {
+    if (offset + @sizeOf(T) > data.len) return error.Truncated;
+    const start: usize = @intCast(offset);
+    return std.mem.bytesToValue(T, data[start..][0..@sizeOf(T)]);
+}
+
+fn cString(data: []const u8, table_offset: u64, index: u64) []const u8 {
+    const start: usize = @intCast(table_offset + index);
+    if (start >= data.len) return "";
+    const end = std.mem.indexOfScalarPos(u8, data, start, 0) orelse data.len;
+    return data[start..end];
+}
+
+pub fn readElfSymbols(allocator: std.mem.Allocator, data: []const u8) ![]Symbol {
+    if (data.len < 16 or !std.mem.eql(u8, data[0..4], elf.MAGIC)) return error.NotElf;
+    if (data[elf.EI_CLASS] != elf.ELFCLASS64 or data[elf.EI_DATA] != elf.ELFDATA2LSB) return error.UnsupportedElf;
+
+    const header = try readStruct(elf.Elf64_Ehdr, data, 0);
+    const sections = try allocator.alloc(elf.Elf64_Shdr, header.e_shnum);
+    for (sections, 0..) |*section, i| {
+        section.* = try readStruct(elf.Elf64_Shdr, data, header.e_shoff + i * header.e_shentsize);
+    }
+    if (header.e_shstrndx >= sections.len) return error.Truncated;
+    const shstrtab = sections[header.e_shstrndx];
+
+    // 优先使用完整符号表，没有时退回动态符号表
+    var symtab: ?elf.Elf64_Shdr = null;
+    for (sections) |section| {
+        if (section.sh_type == elf.SHT_SYMTAB) symtab = section;
+    }
+    if (symtab == null) {
+        for (sections) |section| {
+            if (section.sh_type == elf.SHT_DYNSYM) symtab = section;
+        }
+    }
+    const table = symtab orelse return error.NoSymbolTable;
+    if (table.sh_link >= sections.len) return error.Truncated;
+    const strtab = sections[table.sh_link];
+
+    var symbols = std.ArrayList(Symbol){};
+    const count = table.sh_size / @sizeOf(elf.Elf64_Sym);
+    var i: u64 = 1;  // 0 号符号保留
+    while (i < count) : (i += 1) {
+        const sym = try readStruct(elf.Elf64_Sym, data, table.sh_offset + i * @sizeOf(elf.Elf64_Sym));
+
+        const sym_type = sym.st_info & 0xf;
+        if (sym_type != elf.STT_FUNC and sym_type != elf.STT_OBJECT) continue;
+        if (sym.st_shndx == elf.SHN_UNDEF or sym.st_shndx >= sections.len) continue;  // 外部符号（libc 等）
+
+        const name = cString(data, strtab.sh_offset, sym.st_name);
+        if (!isPawSymbol(name)) continue;
+
+        const section = sections[sym.st_shndx];
+        try symbols.append(allocator, Symbol{
+            .name = name,
+            .demangled = try demangle(allocator, name),
+            .size = sym.st_size,
+            .section = cString(data, shstrtab.sh_offset, section.sh_name),
+            .is_function = sym_type == elf.STT_FUNC,
+        });
+    }
+
+    return symbols.toOwnedSlice(allocator);
+}
+
+/// 过滤 C 运行时和编译器内部符号
+fn isPawSymbol(name: []const u8) bool {
+    if (name.len == 0) return false;
+    if (name[0] == '_' or name[0] == '.') return false;  // __libc_*, _start, _init, .L...
+    if (std.mem.indexOfScalar(u8, name, '@') != null) return false;  // 版本化的 libc 符号
+    for (RUNTIME_SYMBOLS) |runtime| {
+        if (std.mem.eql(u8, name, runtime)) return false;
+    }
+    return true;
+}
+
+fn isTypeName(part: []const u8) bool {
+    if (part.len == 0) return false;
+    if (part[0] >= 'A' and part[0] <= 'Z') return true;
+    for (PRIMITIVE_TYPES) |prim| {
+        if (std.mem.eql(u8, part, prim)) return true;
+    }
+    return false;
+}
+
+/// 还原 C 后端的命名：
+///   Point_new        -> Point::new
+///   Vec_i32_push     -> Vec<i32>::push
+///   Option_Some      -> Option::Some
+///   max_i32          -> max<i32>
+///   m__add           -> m.add（命名空间导入）
+pub fn demangle(allocator: std.mem.Allocator, name: []const u8) ![]const u8 {
+    // 用户的 main（真正的 main 是编译器生成的包装函数）
+    if (std.mem.eql(u8, name, "paw_main")) return "main";
+    if (std.mem.indexOf(u8, name, "__")) |idx| {
+        return std.fmt.allocPrint(allocator, "{s}.{s}", .{ name[0..idx], name[idx + 2 ..] });
+    }
+
+    var parts = std.ArrayList([]const u8){};
+    var it = std.mem.splitScalar(u8, name, '_');
+    while (it.next()) |part| try parts.append(allocator, part);
+    if (parts.items.len < 2) return name;
+
+    var out = std.ArrayList(u8){};
+    const first = parts.items[0];
+
+    if (first.len > 0 and first[0] >= 'A' and first[0] <= 'Z') {
+        // 类型方法 / 枚举构造器：Type[_Args]_member
+        var arg_end: usize = 1;
+        while (arg_end < parts.items.len - 1 and isTypeName(parts.items[arg_end])) : (arg_end += 1) {}
+        try out.appendSlice(allocator, first);
+        try appendTypeArgs(&out, allocator, parts.items[1..arg_end]);
+        try out.appendSlice(allocator, "::");
+        for (parts.items[arg_end..], 0..) |part, j| {
+            if (j > 0) try out.append(allocator, '_');
+            try out.appendSlice(allocator, part);
+        }
+        return out.toOwnedSlice(allocator);
+    }
+
+    // 单态化函数：name_T1_T2（后缀全部是类型名时才还原）
+    var arg_start = parts.items.len;
+    while (arg_start > 1 and isTypeName(parts.items[arg_start - 1])) : (arg_start -= 1) {}
+    if (arg_start == parts.items.len) return name;
+
+    for (parts.items[0..arg_start], 0..) |part, j| {
+        if (j > 0) try out.append(allocator, '_');
+        try out.appendSlice(allocator, part);
+    }
+    try appendTypeArgs(&out, allocator, parts.items[arg_start..]);
+    return out.toOwnedSlice(allocator);
+}
+
+fn appendTypeArgs(out: *std.ArrayList(u8), allocator: std.mem.Allocator, args: []const []const u8) !void {
+    if (args.len == 0) return;
+    try out.append(allocator, '<');
+    for (args, 0..) |arg, j| {
+        if (j > 0) try out.appendSlice(allocator, ", ");
+        try out.appendSlice(allocator, arg);
+    }
+    try out.append(allocator, '>');
+}
@@ -0,0 +1,748 @@
+//! Package - 依赖管理（Paw.toml / 注册表 / 全局缓存）
+//!
+//! 命令：
+//!   pawc add foo@1.2                 在 Paw.toml 中添加依赖（注册表）
+//!   pawc add foo@1.2 --git <url>     添加 git 依赖（检出 tag v1.2）
+//!   pawc fetch                       下载所有依赖到 ~/.paw/registry 并生成 Paw.lock
+//!
+//! Paw.toml 示例：
+//!   [package]
+//!   name = "my_app"
+//!   version = "0.1.0"
+//!   entry = "main.paw"               # 🆕 pawc run 构建的入口文件（默认 main.paw）
+//!
+//!   [dependencies]
+//!   json = "1.2"
+//!   http = { git = "https://github.com/user/http.paw", version = "0.3" }
+//!   utils = { path = "../utils", version = "0.1" }
+//!
+//!   [limits]                         # 🆕 编译资源限制（见 limits.zig）
+//!   const_eval_steps = 5000000
+//!
+//!   [build]                          # 🆕 项目根目录之外的导入搜索目录（见 module.zig）
+//!   include = ["vendor", "../shared"]
+//!
+//! 依赖下载后，`import json.parse;` 会在 ~/.paw/registry/json-1.2/ 下查找模块。
+//!
+//! Paw.lock 记录每个依赖解析后的版本、来源和内容哈希（所有 .paw 文件的 SHA-256）。
+//! 每次构建都会校验：依赖内容或版本与锁文件不一致时给出诊断并更新锁文件；
+//! 使用 `--locked`（CI）时拒绝更新，直接报错。
+
+const std = @import("std");
+const ModuleLoader = @import("module.zig").ModuleLoader;
+const Limits = @import("limits.zig").Limits;
+
+pub const MANIFEST_FILE = "Paw.toml";
+pub const LOCK_FILE = "Paw.lock";
+
+/// 🆕 额外的导入搜索目录（在 Paw.toml [build] include 之后搜索）
+pub const PAW_PATH_ENV = "PAW_PATH";
+
+/// 默认注册表地址（可通过 PAW_REGISTRY 覆盖）
+pub const DEFAULT_REGISTRY = "https://registry.pawlang.dev";
+
+/// 依赖项
+pub const Dependency = struct {
+    name: []const u8,
+    version: []const u8,
+    git: ?[]const u8,
+    path: ?[]const u8,  // 🆕 本地路径依赖（相对项目根目录）
+    
+    /// 锁文件中的来源描述
+    pub fn source(self: Dependency, allocator: std.mem.Allocator) ![]const u8 {
+        if (self.path) |p| return std.fmt.allocPrint(allocator, "path+{s}", .{p});
+        if (self.git) |url| return std.fmt.allocPrint(allocator, "git+{s}", .{url});
+        return allocator.dupe(u8, "registry");
+    }
+};
+
+/// Paw.lock 中的一项
+pub const LockEntry = struct {
+    name: []const u8,
+    version: []const u8,
+    source: []const u8,
+    checksum: []const u8,
+};
+
+/// Paw.lock
+pub const Lockfile = struct {
+    arena: std.heap.ArenaAllocator,
+    entries: std.ArrayList(LockEntry),
+    
+    pub fn deinit(self: *Lockfile) void {
+        self.arena.deinit();
+    }
+    
+    pub fn find(self: *const Lockfile, name: []const u8) ?LockEntry {
+        for (self.entries.items) |entry| {
+            if (std.mem.eql(u8, entry.name, name)) return entry;
+        }
+        return null;
+    }
+    
+    /// 读取锁文件（不存在时返回 error.FileNotFound）
+    pub fn load(allocator: std.mem.Allocator, path: []const u8) !Lockfile {
+        var lock = Lockfile{
+            .arena = std.heap.ArenaAllocator.init(allocator),
+            .entries = std.ArrayList(LockEntry){},
+        };
+        errdefer lock.deinit();
+        const arena = lock.arena.allocator();
+        
+        const text = try std.fs.cwd().readFileAlloc(arena, path, 1024 * 1024);
+        
+        var current: ?LockEntry = null;
+        var lines = std.mem.splitScalar(u8, text, '\n');
+        while (lines.next()) |raw_line| {
+            const line = std.mem.trim(u8, stripComment(raw_line), " \t\r");
+            if (line.len == 0) continue;
+            
+            if (std.mem.eql(u8, line, "[[package]]")) {
+                if (current) |entry| try lock.entries.append(arena, entry);
+                current = LockEntry{ .name = "", .version = "", .source = "", .checksum = "" };
+                continue;
+            }
+            
+            const eq = std.mem.indexOfScalar(u8, line, '=') orelse return error.InvalidLockfile;
+            const key = std.mem.trim(u8, line[0..eq], " \t");
+            const value = try parseString(arena, std.mem.trim(u8, line[eq + 1 ..], " \t"));
+            if (current) |*entry| {
+                if (std.mem.eql(u8, key, "name")) entry.name = value;
+                if (std.mem.eql(u8, key, "version")) entry.version = value;
+                if (std.mem.eql(u8, key, "source")) entry.source = value;
+                if (std.mem.eql(u8, key, "checksum")) entry.checksum = value;
+            }
+        }
+        if (current) |entry| try lock.entries.append(arena, entry);
+        
+        return lock;
+    }
+};
+
+/// Paw.toml 清单
+pub const Manifest = struct {
+    arena: std.heap.ArenaAllocator,
+    package_name: ?[]const u8,
+    package_version: ?[]const u8,
+    package_entry: ?[]const u8,  // 🆕 [package] entry
+    dependencies: std.ArrayList(Dependency),
+    limits: Limits,  // 🆕 [limits]
+    include: []const []const u8,  // 🆕 [build] include
+
+    pub fn deinit(self: *Manifest) void {
+        self.arena.deinit();
+    }
+
+    /// 读取并解析清单文件（不存在时返回 error.FileNotFound）
+    pub fn load(allocator: std.mem.Allocator, path: []const u8) !Manifest {
+        var manifest = Manifest{
+            .arena = std.heap.ArenaAllocator.init(allocator),
+            .package_name = null,
+            .package_version = null,
+            .package_entry = null,
+            .dependencies = std.ArrayList(Dependency){},
+            .limits = .{},
+            .include = &.{},
+        };
+        errdefer manifest.deinit();
+        const arena = manifest.arena.allocator();
+
+        const text = try std.fs.cwd().readFileAlloc(arena, path, 1024 * 1024);
+
+        var section: []const u8 = "";
+        var lines = std.mem.splitScalar(u8, text, '\n');
+        var line_no: usize = 0;
+        while (lines.next()) |raw_line| {
+            line_no += 1;
+            const line = std.mem.trim(u8, stripComment(raw_line), " \t\r");
+            if (line.len == 0) continue;
+
+            if (line[0] == '[') {
+                section = std.mem.trim(u8, line, "[] ");
+                continue;
+            }
+
+            const eq = std.mem.indexOfScalar(u8, line, '=') orelse {
+                std.debug.print("Error: {s}:{d}: expected 'key = value'\n", .{ path, line_no });
+                return error.InvalidManifest;
+            };
+            const key = std.mem.trim(u8, line[0..eq], " \t");
+            const value = std.mem.trim(u8, line[eq + 1 ..], " \t");
+
+            if (std.mem.eql(u8, section, "package")) {
+                if (std.mem.eql(u8, key, "name")) manifest.package_name = try parseString(arena, value);
+                if (std.mem.eql(u8, key, "version")) manifest.package_version = try parseString(arena, value);
+                if (std.mem.eql(u8, key, "entry")) manifest.package_entry = try parseString(arena, value);
+            } else if (std.mem.eql(u8, section, "dependencies")) {
+                const dep = try parseDependency(arena, key, value) orelse {
+                    std.debug.print("Error: {s}:{d}: invalid dependency '{s}'\n", .{ path, line_no, key });
+                    return error.InvalidManifest;
+                };
+                // 🆕 手写的清单与 pawc add 写入的一样检查（名字和版本会成为目录名，URL 会传给 git）
+                validateDependency(dep) catch {
+                    std.debug.print("  --> {s}:{d}\n", .{ path, line_no });
+                    return error.InvalidManifest;
+                };
+                try manifest.dependencies.append(arena, dep);
+            } else if (std.mem.eql(u8, section, "limits")) {
+                manifest.limits.set(key, value) catch |err| {
+                    switch (err) {
+                        error.UnknownLimit => std.debug.print("Error: {s}:{d}: unknown limit '{s}' (known: const_eval_steps, generic_instances, object_size, function_codegen_ms)\n", .{ path, line_no, key }),
+                        error.InvalidLimit => std.debug.print("Error: {s}:{d}: invalid value for limit '{s}': {s}\n", .{ path, line_no, key, value }),
+                    }
+                    return error.InvalidManifest;
+                };
+            } else if (std.mem.eql(u8, section, "build")) {
+                if (!std.mem.eql(u8, key, "include")) {
+                    std.debug.print("Error: {s}:{d}: unknown build setting '{s}' (known: include)\n", .{ path, line_no, key });
+                    return error.InvalidManifest;
+                }
+                manifest.include = try parseStringArray(arena, value) orelse {
+                    std.debug.print("Error: {s}:{d}: include must be a list of strings, e.g. [\"vendor\"]\n", .{ path, line_no });
+                    return error.InvalidManifest;
+                };
+            }
+        }
+
+        return manifest;
+    }
+};
+
+/// 解析依赖值：`"1.2"` 或 `{ git = "...", version = "1.2" }`
+fn parseDependency(arena: std.mem.Allocator, name: []const u8, value: []const u8) !?Dependency {
+    if (value.len > 0 and value[0] == '"') {
+        return Dependency{ .name = name, .version = try parseString(arena, value), .git = null, .path = null };
+    }
+    if (value.len < 2 or value[0] != '{' or value[value.len - 1] != '}') return null;
+
+    var dep = Dependency{ .name = name, .version = "", .git = null, .path = null };
+    // 🆕 按字符串之外的逗号分隔字段（URL 中可以有逗号）
+    const body = value[1 .. value.len - 1];
+    var start: usize = 0;
+    var in_string = false;
+    var i: usize = 0;
+    while (i <= body.len) : (i += 1) {
+        if (i < body.len) {
+            if (in_string and body[i] == '\\') {
+                i += 1;
+                continue;
+            }
+            if (body[i] == '"') in_string = !in_string;
+            if (body[i] != ',' or in_string) continue;
+        }
+        const field = body[start..i];
+        start = i + 1;
+        const eq = std.mem.indexOfScalar(u8, field, '=') orelse continue;
+        const key = std.mem.trim(u8, field[0..eq], " \t");
+        const val = try parseString(arena, std.mem.trim(u8, field[eq + 1 ..], " \t"));
+        if (std.mem.eql(u8, key, "version")) dep.version = val;
+        if (std.mem.eql(u8, key, "git")) dep.git = val;
+        if (std.mem.eql(u8, key, "path")) dep.path = val;
+    }
+    // 路径依赖可以省略版本
+    if (dep.version.len == 0) {
+        if (dep.path == null) return null;
+        dep.version = "0.0.0";
+    }
+    return dep;
+}
+
+/// 🆕 解析单行字符串数组：`["a", "b"]`（不是数组或元素不是字符串时返回 null）
+fn parseStringArray(arena: std.mem.Allocator, value: []const u8) !?[]const []const u8 {
+    if (value.len < 2 or value[0] != '[' or value[value.len - 1] != ']') return null;
+    var items = std.ArrayList([]const u8){};
+    var fields = std.mem.splitScalar(u8, value[1 .. value.len - 1], ',');
+    while (fields.next()) |field| {
+        const item = std.mem.trim(u8, field, " \t");
+        if (item.len == 0) continue;  // 允许末尾的逗号
+        if (item.len < 2 or item[0] != '"' or item[item.len - 1] != '"') return null;
+        try items.append(arena, item[1 .. item.len - 1]);
+    }
+    return try items.toOwnedSlice(arena);
+}
+
+/// 🆕 包名只能包含字母、数字、'_' 和 '-'，且不能以数字开头（pawc new / init 检查）
+pub fn isValidPackageName(name: []const u8) bool {
+    if (name.len == 0 or std.ascii.isDigit(name[0])) return false;
+    for (name) |c| {
+        if (!std.ascii.isAlphanumeric(c) and c != '_' and c != '-') return false;
+    }
+    return true;
+}
+
+/// 🆕 版本只能包含字母、数字和 '.'、'-'、'+'、'_'，且以字母或数字开头（不能是 ".." 这样的路径）
+pub fn isValidVersion(version: []const u8) bool {
+    if (version.len == 0 or !std.ascii.isAlphanumeric(version[0])) return false;
+    for (version) |c| {
+        if (!std.ascii.isAlphanumeric(c) and c != '.' and c != '-' and c != '+' and c != '_') return false;
+    }
+    return true;
+}
+
+/// 🆕 git URL 作为参数传给 git clone：不能以 '-' 开头（会被当作选项），不能包含空白和控制字符
+pub fn isValidGitUrl(url: []const u8) bool {
+    if (url.len == 0 or url[0] == '-') return false;
+    for (url) |c| {
+        if (std.ascii.isControl(c) or c == ' ') return false;
+    }
+    return true;
+}
+
+/// 🆕 依赖的名字和版本会成为缓存目录名 <name>-<version>，使用前检查（拒绝 "../" 等路径）
+fn validateDependency(dep: Dependency) !void {
+    if (!isValidPackageName(dep.name)) {
+        std.debug.print("Error: invalid dependency name '{s}': use letters, digits, '_' and '-', not starting with a digit\n", .{dep.name});
+        return error.InvalidDependencySpec;
+    }
+    if (!isValidVersion(dep.version)) {
+        std.debug.print("Error: invalid version '{s}' for dependency {s}: use letters, digits, '.', '-', '+' and '_'\n", .{ dep.version, dep.name });
+        return error.InvalidDependencySpec;
+    }
+    if (dep.git) |url| {
+        if (!isValidGitUrl(url)) {
+            std.debug.print("Error: invalid git URL '{s}' for dependency {s}: it must not be empty, start with '-' or contain whitespace\n", .{ url, dep.name });
+            return error.InvalidDependencySpec;
+        }
+    }
+}
+
+fn stripComment(line: []const u8) []const u8 {
+    var in_string = false;
+    var i: usize = 0;
+    while (i < line.len) : (i += 1) {
+        const c = line[i];
+        if (in_string and c == '\\') {
+            i += 1;  // 转义的字符（\" 不结束字符串）
+            continue;
+        }
+        if (c == '"') in_string = !in_string;
+        if (c == '#' and !in_string) return line[0..i];
+    }
+    return line;
+}
+
+/// 🆕 字符串值：`"..."` 去掉引号并处理 \" 和 \\ 转义（与 writeString 对应）；其他值原样返回
+fn parseString(arena: std.mem.Allocator, value: []const u8) ![]const u8 {
+    if (value.len < 2 or value[0] != '"' or value[value.len - 1] != '"') return value;
+    const inner = value[1 .. value.len - 1];
+    if (std.mem.indexOfScalar(u8, inner, '\\') == null) return inner;
+    var out = std.ArrayList(u8){};
+    var i: usize = 0;
+    while (i < inner.len) : (i += 1) {
+        if (inner[i] == '\\' and i + 1 < inner.len) i += 1;
+        try out.append(arena, inner[i]);
+    }
+    return out.toOwnedSlice(arena);
+}
+
+/// 🆕 写入带引号的字符串值：转义 " 和 \（Paw.toml 和 Paw.lock 共用）
+fn writeString(writer: anytype, value: []const u8) !void {
+    try writer.writeByte('"');
+    for (value) |c| {
+        if (c == '"' or c == '\\') try writer.writeByte('\\');
+        try writer.writeByte(c);
+    }
+    try writer.writeByte('"');
+}
+
+// ============================================================================
+// 全局缓存
+// ============================================================================
+
+/// 注册表缓存目录：$PAW_HOME/registry 或 ~/.paw/registry
+pub fn registryDir(allocator: std.mem.Allocator) ![]const u8 {
+    if (std.process.getEnvVarOwned(allocator, "PAW_HOME")) |paw_home| {
+        defer allocator.free(paw_home);
+        return std.fs.path.join(allocator, &[_][]const u8{ paw_home, "registry" });
+    } else |_| {}
+
+    const home = std.process.getEnvVarOwned(allocator, "HOME") catch
+        try std.process.getEnvVarOwned(allocator, "USERPROFILE");
+    defer allocator.free(home);
+    return std.fs.path.join(allocator, &[_][]const u8{ home, ".paw", "registry" });
+}
+
+/// 依赖的根目录：路径依赖直接使用其路径，其他依赖位于 <registry>/<name>-<version>
+pub fn packageDir(allocator: std.mem.Allocator, registry: []const u8, dep: Dependency) ![]const u8 {
+    if (dep.path) |p| return allocator.dupe(u8, p);
+    const dir_name = try std.fmt.allocPrint(allocator, "{s}-{s}", .{ dep.name, dep.version });
+    defer allocator.free(dir_name);
+    return std.fs.path.join(allocator, &[_][]const u8{ registry, dir_name });
+}
+
+// ============================================================================
+// paw add
+// ============================================================================
+
+/// 将依赖写入 Paw.toml（已存在的同名依赖会被替换）
+pub fn addDependency(
+    allocator: std.mem.Allocator,
+    spec: []const u8,
+    git: ?[]const u8,
+) !void {
+    const at = std.mem.indexOfScalar(u8, spec, '@') orelse {
+        std.debug.print("Error: expected <name>@<version>, got '{s}'\n", .{spec});
+        return error.InvalidDependencySpec;
+    };
+    const name = spec[0..at];
+    const version = spec[at + 1 ..];
+    if (name.len == 0 or version.len == 0) {
+        std.debug.print("Error: expected <name>@<version>, got '{s}'\n", .{spec});
+        return error.InvalidDependencySpec;
+    }
+    try validateDependency(.{ .name = name, .version = version, .git = git, .path = null });
+
+    const text = std.fs.cwd().readFileAlloc(allocator, MANIFEST_FILE, 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read {s}: {any}\n", .{ MANIFEST_FILE, err });
+        std.debug.print("💡 Run this command in a project created with: pawc init <name>\n", .{});
+        return err;
+    };
+    defer allocator.free(text);
+
+    var dep_buf = std.ArrayList(u8){};
+    defer dep_buf.deinit(allocator);
+    const dep_writer = dep_buf.writer(allocator);
+    try dep_writer.print("{s} = ", .{name});
+    if (git) |url| {
+        try dep_writer.writeAll("{ git = ");
+        try writeString(dep_writer, url);
+        try dep_writer.writeAll(", version = ");
+        try writeString(dep_writer, version);
+        try dep_writer.writeAll(" }");
+    } else {
+        try writeString(dep_writer, version);
+    }
+    const dep_line = dep_buf.items;
+
+    var out = std.ArrayList(u8){};
+    defer out.deinit(allocator);
+
+    var in_deps = false;
+    var has_deps_section = false;
+    var written = false;
+    var lines = std.mem.splitScalar(u8, text, '\n');
+    while (lines.next()) |line| {
+        const trimmed = std.mem.trim(u8, line, " \t\r");
+        if (trimmed.len > 0 and trimmed[0] == '[') {
+            // 离开 [dependencies] 时追加新依赖
+            if (in_deps and !written) {
+                try out.appendSlice(allocator, dep_line);
+                try out.append(allocator, '\n');
+                written = true;
+            }
+            in_deps = std.mem.eql(u8, trimmed, "[dependencies]");
+            if (in_deps) has_deps_section = true;
+        } else if (in_deps and isDependencyLine(trimmed, name)) {
+            if (!written) {
+                try out.appendSlice(allocator, dep_line);
+                try out.append(allocator, '\n');
+                written = true;
+            }
+            continue;
+        }
+        try out.appendSlice(allocator, line);
+        if (lines.peek() != null) try out.append(allocator, '\n');
+    }
+
+    if (!written) {
+        if (out.items.len > 0 and out.items[out.items.len - 1] != '\n') try out.append(allocator, '\n');
+        if (!has_deps_section) try out.appendSlice(allocator, "\n[dependencies]\n");
+        try out.appendSlice(allocator, dep_line);
+        try out.append(allocator, '\n');
+    }
+
+    const file = try std.fs.cwd().createFile(MANIFEST_FILE, .{});
+    defer file.close();
+    try file.writeAll(out.items);
+
+    std.debug.print("✅ Added {s}@{s} to {s}\n", .{ name, version, MANIFEST_FILE });
+    std.debug.print("💡 Run 'pawc fetch' to download it\n", .{});
+}
+
+fn isDependencyLine(line: []const u8, name: []const u8) bool {
+    const eq = std.mem.indexOfScalar(u8, line, '=') orelse return false;
+    return std.mem.eql(u8, std.mem.trim(u8, line[0..eq], " \t"), name);
+}
+
+// ============================================================================
+// paw fetch
+// ============================================================================
+
+/// 下载清单中的所有依赖到全局缓存，并生成 Paw.lock
+pub fn fetchDependencies(allocator: std.mem.Allocator, manifest: *Manifest, locked: bool) !void {
+    // 🆕 先检查所有依赖，再创建任何目录
+    for (manifest.dependencies.items) |dep| try validateDependency(dep);
+
+    const registry = try registryDir(allocator);
+    defer allocator.free(registry);
+    try std.fs.cwd().makePath(registry);
+
+    for (manifest.dependencies.items) |dep| {
+        const dir = try packageDir(allocator, registry, dep);
+        defer allocator.free(dir);
+
+        if (std.fs.cwd().access(dir, .{})) {
+            std.debug.print("♻️  {s}@{s} ({s})\n", .{ dep.name, dep.version, if (dep.path != null) "path" else "cached" });
+            continue;
+        } else |_| {}
+        
+        if (dep.path) |p| {
+            std.debug.print("❌ Path dependency {s} not found: {s}\n", .{ dep.name, p });
+            return error.FetchFailed;
+        }
+
+        std.debug.print("📥 Fetching {s}@{s}...\n", .{ dep.name, dep.version });
+        if (dep.git) |url| {
+            try fetchGit(allocator, url, dep.version, dir);
+        } else {
+            try fetchTarball(allocator, dep, dir);
+        }
+    }
+
+    try syncLockfile(allocator, manifest, locked);
+    std.debug.print("✅ {d} dependencies ready in {s}\n", .{ manifest.dependencies.items.len, registry });
+}
+
+/// git 依赖：浅克隆 tag v<version>，失败时尝试 <version>
+fn fetchGit(allocator: std.mem.Allocator, url: []const u8, version: []const u8, dir: []const u8) !void {
+    const tag = try std.fmt.allocPrint(allocator, "v{s}", .{version});
+    defer allocator.free(tag);
+
+    // 🆕 以 '-' 开头的 ref 会被 git 当作选项；"--" 之后的 URL 和目录只作为位置参数
+    if (version.len == 0 or version[0] == '-') {
+        std.debug.print("❌ Invalid git ref '{s}' for {s}\n", .{ version, url });
+        return error.InvalidDependencySpec;
+    }
+    for ([_][]const u8{ tag, version }) |ref| {
+        if (runTool(allocator, &[_][]const u8{ "git", "clone", "--quiet", "--depth", "1", "--branch", ref, "--", url, dir })) {
+            return;
+        } else |_| {}
+    }
+
+    std.debug.print("❌ Failed to clone {s} at {s}\n", .{ url, tag });
+    return error.FetchFailed;
+}
+
+/// 注册表依赖：下载 <registry>/<name>/<name>-<version>.tar.gz 并解压
+fn fetchTarball(allocator: std.mem.Allocator, dep: Dependency, dir: []const u8) !void {
+    const base = std.process.getEnvVarOwned(allocator, "PAW_REGISTRY") catch
+        try allocator.dupe(u8, DEFAULT_REGISTRY);
+    defer allocator.free(base);
+
+    const url = try std.fmt.allocPrint(allocator, "{s}/{s}/{s}-{s}.tar.gz", .{ base, dep.name, dep.name, dep.version });
+    defer allocator.free(url);
+    const archive = try std.fmt.allocPrint(allocator, "{s}.tar.gz", .{dir});
+    defer allocator.free(archive);
+
+    runTool(allocator, &[_][]const u8{ "curl", "-fsSL", "-o", archive, url }) catch {
+        std.debug.print("❌ Failed to download {s}\n", .{url});
+        return error.FetchFailed;
+    };
+    defer std.fs.cwd().deleteFile(archive) catch {};
+
+    try std.fs.cwd().makePath(dir);
+    runTool(allocator, &[_][]const u8{ "tar", "-xzf", archive, "-C", dir, "--strip-components=1" }) catch {
+        std.fs.cwd().deleteTree(dir) catch {};
+        std.debug.print("❌ Failed to unpack {s}\n", .{archive});
+        return error.FetchFailed;
+    };
+}
+
+fn runTool(allocator: std.mem.Allocator, argv: []const []const u8) !void {
+    const result = try std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = argv,
+    });
+    defer allocator.free(result.stdout);
+    defer allocator.free(result.stderr);
+
+    if (result.term != .Exited or result.term.Exited != 0) {
+        if (result.stderr.len > 0) std.debug.print("{s}", .{result.stderr});
+        return error.ToolFailed;
+    }
+}
+
+// ============================================================================
+// Paw.lock
+// ============================================================================
+
+/// 计算依赖内容哈希：按相对路径排序后对所有 .paw 文件的路径和内容做 SHA-256
+pub fn hashPackage(allocator: std.mem.Allocator, dir_path: []const u8) ![]const u8 {
+    var dir = try std.fs.cwd().openDir(dir_path, .{ .iterate = true });
+    defer dir.close();
+
+    var files = std.ArrayList([]const u8){};
+    defer {
+        for (files.items) |f| allocator.free(f);
+        files.deinit(allocator);
+    }
+
+    var walker = try dir.walk(allocator);
+    defer walker.deinit();
+    while (try walker.next()) |entry| {
+        if (entry.kind != .file or !std.mem.endsWith(u8, entry.path, ".paw")) continue;
+        try files.append(allocator, try allocator.dupe(u8, entry.path));
+    }
+    std.mem.sort([]const u8, files.items, {}, struct {
+        fn lessThan(_: void, a: []const u8, b: []const u8) bool {
+            return std.mem.order(u8, a, b) == .lt;
+        }
+    }.lessThan);
+
+    var hasher = std.crypto.hash.sha2.Sha256.init(.{});
+    for (files.items) |rel_path| {
+        const content = try dir.readFileAlloc(allocator, rel_path, 10 * 1024 * 1024);
+        defer allocator.free(content);
+        hasher.update(rel_path);
+        hasher.update(&[_]u8{0});
+        hasher.update(content);
+        hasher.update(&[_]u8{0});
+    }
+
+    var digest: [std.crypto.hash.sha2.Sha256.digest_length]u8 = undefined;
+    hasher.final(&digest);
+    return std.fmt.allocPrint(allocator, "sha256:{s}", .{std.fmt.bytesToHex(digest, .lower)});
+}
+
+/// 校验 Paw.lock 与当前依赖树是否一致，不一致时打印诊断
+/// locked = true 时不一致即报错；否则重写锁文件
+pub fn syncLockfile(allocator: std.mem.Allocator, manifest: *Manifest, locked: bool) !void {
+    const registry = try registryDir(allocator);
+    defer allocator.free(registry);
+
+    var lock_opt: ?Lockfile = Lockfile.load(allocator, LOCK_FILE) catch |err| switch (err) {
+        error.FileNotFound => null,
+        else => return err,
+    };
+    defer if (lock_opt) |*lock| lock.deinit();
+
+    var out = std.ArrayList(u8){};
+    defer out.deinit(allocator);
+    const writer = out.writer(allocator);
+    try writer.writeAll("# This file is generated by pawc. Do not edit.\n");
+
+    var drift: usize = 0;
+    if (lock_opt == null and manifest.dependencies.items.len > 0) {
+        std.debug.print("📝 {s} not found, it will be created\n", .{LOCK_FILE});
+        drift += 1;
+    }
+
+    for (manifest.dependencies.items) |dep| {
+        const dir = try packageDir(allocator, registry, dep);
+        defer allocator.free(dir);
+        const source = try dep.source(allocator);
+        defer allocator.free(source);
+
+        const checksum = hashPackage(allocator, dir) catch |err| {
+            std.debug.print("❌ Cannot hash dependency {s} at {s}: {any}\n", .{ dep.name, dir, err });
+            std.debug.print("💡 Run: pawc fetch\n", .{});
+            return error.DependencyNotFetched;
+        };
+        defer allocator.free(checksum);
+
+        if (lock_opt) |lock| {
+            if (lock.find(dep.name)) |entry| {
+                if (!std.mem.eql(u8, entry.version, dep.version) or !std.mem.eql(u8, entry.source, source)) {
+                    std.debug.print("⚠️  {s}: {s} {s} in {s}, but {s} {s} in {s}\n", .{
+                        dep.name, entry.version, entry.source, LOCK_FILE, dep.version, source, MANIFEST_FILE,
+                    });
+                    drift += 1;
+                } else if (!std.mem.eql(u8, entry.checksum, checksum)) {
+                    std.debug.print("⚠️  {s}@{s}: content changed since it was locked\n", .{ dep.name, dep.version });
+                    std.debug.print("     locked:  {s}\n", .{entry.checksum});
+                    std.debug.print("     current: {s}\n", .{checksum});
+                    drift += 1;
+                }
+            } else {
+                std.debug.print("⚠️  {s}@{s} is not in {s}\n", .{ dep.name, dep.version, LOCK_FILE });
+                drift += 1;
+            }
+        }
+
+        try writer.print("\n[[package]]\nname = \"{s}\"\nversion = \"{s}\"\nsource = ", .{ dep.name, dep.version });
+        try writeString(writer, source);
+        try writer.print("\nchecksum = \"{s}\"\n", .{checksum});
+    }
+
+    // 锁文件中多余的依赖
+    if (lock_opt) |lock| {
+        for (lock.entries.items) |entry| {
+            var still_used = false;
+            for (manifest.dependencies.items) |dep| {
+                if (std.mem.eql(u8, dep.name, entry.name)) still_used = true;
+            }
+            if (!still_used) {
+                std.debug.print("⚠️  {s} is locked but no longer in {s}\n", .{ entry.name, MANIFEST_FILE });
+                drift += 1;
+            }
+        }
+    }
+
+    if (drift == 0) return;
+
+    if (locked) {
+        std.debug.print("❌ {s} is out of date and --locked was given\n", .{LOCK_FILE});
+        std.debug.print("💡 Run 'pawc fetch' locally and commit the updated {s}\n", .{LOCK_FILE});
+        return error.LockfileOutOfDate;
+    }
+
+    const file = try std.fs.cwd().createFile(LOCK_FILE, .{});
+    defer file.close();
+    try file.writeAll(out.items);
+    std.debug.print("📝 Updated {s}\n", .{LOCK_FILE});
+}
+
+// ============================================================================
+// 导入解析
+// ============================================================================
+
+/// 🆕 PAW_PATH 中的目录依次作为搜索根目录（空项忽略）
+fn registerPawPath(allocator: std.mem.Allocator, loader: *ModuleLoader) !void {
+    const paw_path = std.process.getEnvVarOwned(allocator, PAW_PATH_ENV) catch return;
+    defer allocator.free(paw_path);
+    var dirs = std.mem.splitScalar(u8, paw_path, std.fs.path.delimiter);
+    while (dirs.next()) |dir| try loader.addSearchRoot(dir, .paw_path);
+}
+
+/// 🆕 当前项目的编译资源限制（没有 Paw.toml 时使用默认值）
+pub fn loadLimits(allocator: std.mem.Allocator) !Limits {
+    var manifest = Manifest.load(allocator, MANIFEST_FILE) catch |err| switch (err) {
+        error.FileNotFound => return Limits{},
+        else => return err,
+    };
+    defer manifest.deinit();
+    return manifest.limits;
+}
+
+/// 将依赖和导入搜索目录注册到模块加载器并校验 Paw.lock
+/// （没有 Paw.toml 时只注册 PAW_PATH 中的目录）
+pub fn registerDependencies(allocator: std.mem.Allocator, loader: *ModuleLoader, locked: bool) !void {
+    var manifest = Manifest.load(allocator, MANIFEST_FILE) catch |err| switch (err) {
+        error.FileNotFound => return registerPawPath(allocator, loader),
+        else => return err,
+    };
+    defer manifest.deinit();
+
+    // 🆕 搜索顺序：项目根目录 → [build] include → PAW_PATH
+    for (manifest.include) |dir| try loader.addSearchRoot(dir, .include);
+    try registerPawPath(allocator, loader);
+
+    if (manifest.dependencies.items.len == 0) return;
+
+    const registry = try registryDir(allocator);
+    defer allocator.free(registry);
+
+    for (manifest.dependencies.items) |dep| {
+        const dir = try packageDir(allocator, registry, dep);
+        defer allocator.free(dir);
+
+        std.fs.cwd().access(dir, .{}) catch {
+            std.debug.print("❌ Dependency {s}@{s} is not available at {s}\n", .{ dep.name, dep.version, dir });
+            std.debug.print("💡 Run: pawc fetch\n", .{});
+            return error.DependencyNotFetched;
+        };
+        try loader.addPackage(dep.name, dir);
+    }
+
+    try syncLockfile(allocator, &manifest, locked);
+}
@@ -21,6 +21,11 @@ const std = @import("std");
 const Token = @import("token.zig").Token;
 const TokenType = @import("token.zig").TokenType;
 const ast = @import("ast.zig");
+const type_alias = @import("type_alias.zig");
+const track_caller = @import("track_caller.zig");
+const cancel = @import("cancel.zig");
+const messages = @import("messages.zig");  // 🆕 本地化的消息模板
+const codes = @import("codes.zig");  // 🆕 诊断代码注册表
 
 // ============================================================================
 // Parser Structure
@@ -33,6 +38,22 @@ pub const Parser = struct {
     current: usize,
     // 🆕 类型名集合（用于消除泛型歧义）
     known_types: std.StringHashMap(void),
+    // 🆕 命名空间导入的别名（import math as m）
+    namespaces: std.StringHashMap(void),
+    // 🆕 取消令牌（watch / LSP）：每个顶层声明之间检查
+    cancel_token: ?*const cancel.CancelToken = null,
+    current_function: ?[]const u8 = null,  // 🆕 正在解析的函数（__FUNCTION__；方法为 Type.method）
+    in_track_caller: bool = false,  // 🆕 正在解析 @track_caller 函数（__FILE__ / __LINE__ 是调用处的位置）
+    dispatch_bindings: std.ArrayList(DispatchBinding) = .{},  // 🆕 正在解析的 dispatch 体中类型参数的替换（内层在后）
+    dispatch_reparse: bool = false,  // 🆕 dispatch 体按第二个及以后的类型重新解析（诊断在第一次解析时已报告）
+    impl_targets: std.ArrayList(Token) = .{},  // 🆕 每个 impl 块的目标类型 token（并入目标类型时报错用）
+    locals: std.ArrayList([]const u8) = .{},  // 🆕 当前函数的参数和局部变量（遮蔽同名的命名空间别名）
+    
+    /// 🆕 dispatch<T in [...]> 的体按每个类型解析一次：T 替换为当前的类型
+    const DispatchBinding = struct {
+        name: []const u8,
+        type: ast.Type,
+    };
 
     pub fn init(allocator: std.mem.Allocator, tokens: []Token) Parser {
         return Parser{
@@ -41,15 +62,25 @@ pub const Parser = struct {
             .tokens = tokens,
             .current = 0,
             .known_types = std.StringHashMap(void).init(allocator),
+            .namespaces = std.StringHashMap(void).init(allocator),
         };
     }
 
     pub fn deinit(self: *Parser) void {
         self.known_types.deinit();
+        self.namespaces.deinit();
         // Arena 会在这里自动释放所有 AST 分配的内存
         self.arena.deinit();
     }
     
+    /// 🆕 name 是否是当前函数的参数或已声明的局部变量
+    fn isLocal(self: *const Parser, name: []const u8) bool {
+        for (self.locals.items) |local| {
+            if (std.mem.eql(u8, local, name)) return true;
+        }
+        return false;
+    }
+    
     // 🆕 获取 arena allocator 用于 AST 节点
     fn arenaAllocator(self: *Parser) std.mem.Allocator {
         return self.arena.allocator();
@@ -122,14 +153,27 @@ pub const Parser = struct {
         var declarations: std.ArrayList(ast.TopLevelDecl) = .{};
         
         while (!self.isAtEnd()) {
+            try cancel.checkOptional(self.cancel_token);
             const decl = try self.parseTopLevelDecl();
             try declarations.append(self.arenaAllocator(), decl);
         }
         
         const decls_slice = try declarations.toOwnedSlice(self.arenaAllocator());
-        return ast.Program{
+        const program = ast.Program{
             .declarations = decls_slice,
         };
+        
+        // 🆕 impl 块的方法并入目标类型（见 parseImplDecl）
+        try self.mergeImpls(decls_slice);
+        
+        // 🆕 展开类型别名（编译期求值需要看到别名指向的 [T; expr]）
+        try type_alias.expandProgram(self.arenaAllocator(), program);
+        
+        // 🆕 调用 @track_caller 函数时传入调用处的位置（const 初始化表达式中的调用也需要）
+        try track_caller.lowerProgram(self.arenaAllocator(), program);
+        
+        // 编译期求值（consteval）不在这里：合并导入的声明之后单独运行，常量才能调用导入的函数
+        return program;
     }
 
     // ============================================================================
@@ -137,8 +181,91 @@ pub const Parser = struct {
     // ============================================================================
     
     fn parseTopLevelDecl(self: *Parser) !ast.TopLevelDecl {
+        // 🆕 属性：@link_name(...) 只能用于 extern fn；@allow(...) 对该声明关闭检查；
+        // @asm(...) 给出 fn 在各目标架构上的汇编函数体；@test / @skip(...) 标记测试函数；
+        // @track_caller 使 fn 中的 __FILE__ / __LINE__ 是调用处的位置
+        const attrs = try self.parseAttributes();
+        self.in_track_caller = attrs.track_caller_token != null;
+        defer self.in_track_caller = false;
+        var decl = try self.parseUnattributedDecl(attrs);
+        if (attrs.skip_token != null and attrs.test_token == null) {
+            self.reportTokenError(.misplaced_attribute, attrs.skip_token.?, "@skip can only be applied to a @test or @property function", "write `@test @skip(\"reason\") fn name() { ... }`");
+            return error.UnexpectedToken;
+        }
+        if (attrs.test_token) |test_token| {
+            if (decl != .function or decl.function.is_extern or decl.function.asm_bodies.len > 0) {
+                self.reportTokenError(.misplaced_attribute, test_token, "@test and @property can only be applied to a fn with a Paw body", null);
+                return error.UnexpectedToken;
+            }
+            var test_attr = attrs.test_attr;
+            test_attr.skip = attrs.skip_reason;
+            decl.function.test_attr = test_attr;
+        }
+        if (attrs.track_caller_token) |track_caller_token| {
+            if (decl != .function or decl.function.is_extern or decl.function.test_attr != null or decl.function.isEntryPoint()) {
+                self.reportTokenError(.misplaced_attribute, track_caller_token, "@track_caller can only be applied to a fn with a Paw body that is not main or a test", null);
+                return error.UnexpectedToken;
+            }
+            decl.function.track_caller = true;
+        }
+        if (attrs.allow_token != null) {
+            switch (decl) {
+                .function => |*func| func.allow = attrs.allow,
+                .type_decl => |*td| td.allow = attrs.allow,
+                .const_decl => |*cd| cd.allow = attrs.allow,
+                else => {
+                    self.reportTokenError(.misplaced_attribute, attrs.allow_token.?, "@allow can only be applied to a fn, type or const declaration", null);
+                    return error.UnexpectedToken;
+                },
+            }
+        }
+        return decl;
+    }
+    
+    fn parseUnattributedDecl(self: *Parser, attrs: Attributes) !ast.TopLevelDecl {
+        const link_name = attrs.link_name;
         const is_public = self.match(.keyword_pub);
         
+        // 🆕 @asm(...) fn name(...) -> T;（函数体是各目标架构的汇编）
+        if (attrs.asm_token) |asm_token| {
+            if (link_name != null or !self.match(.keyword_fn)) {
+                self.reportTokenError(.misplaced_attribute, asm_token, "@asm can only be applied to a plain fn declaration", "write `@asm(\"x86_64\", \"...\") fn name(...) -> T;`");
+                return error.UnexpectedToken;
+            }
+            const name_token = self.tokens[self.current];
+            var func = try self.parseFunctionSignature(is_public, false, null);
+            if (func.type_params.len > 0) {
+                self.reportTokenError(.generic_foreign_function, name_token, "@asm functions cannot be generic", null);
+                return error.UnexpectedToken;
+            }
+            if (self.check(.lbrace)) {
+                self.reportTokenError(.invalid_asm_declaration, self.tokens[self.current], "an @asm function cannot have a Paw body", "end the signature with `;`: the assembly is the whole function body");
+                return error.UnexpectedToken;
+            }
+            _ = try self.consume(.semicolon);
+            func.asm_bodies = attrs.asm_bodies;
+            return ast.TopLevelDecl{ .function = func };
+        }
+        
+        // 🆕 extern fn name(...) -> T;（由外部库提供，没有函数体）
+        if (self.match(.keyword_extern)) {
+            _ = try self.consume(.keyword_fn);
+            const name_token = self.tokens[self.current];
+            var func = try self.parseFunctionSignature(is_public, false, null);
+            if (func.type_params.len > 0) {
+                self.reportTokenError(.generic_foreign_function, name_token, "extern functions cannot be generic", null);
+                return error.UnexpectedToken;
+            }
+            _ = try self.consume(.semicolon);
+            func.is_extern = true;
+            func.link_name = link_name;
+            return ast.TopLevelDecl{ .function = func };
+        }
+        if (link_name != null) {
+            self.reportTokenError(.misplaced_attribute, attrs.link_name_token.?, "@link_name can only be applied to an extern fn", "declare the function as `extern fn name(...) -> T;`");
+            return error.UnexpectedToken;
+        }
+        
         // Paw 统一语法：只支持 type 和 fn
         if (self.match(.keyword_type)) {
             const type_decl = try self.parseTypeDecl(is_public);
@@ -146,45 +273,284 @@ pub const Parser = struct {
         } else if (self.match(.keyword_fn)) {
             const func = try self.parseFunctionDecl(is_public, false, null);
             return ast.TopLevelDecl{ .function = func };
+        } else if (self.match(.keyword_const)) {
+            // 🆕 const fn（可在编译期求值）或 const NAME = expr;
+            if (self.match(.keyword_fn)) {
+                var func = try self.parseFunctionDecl(is_public, false, null);
+                func.is_const = true;
+                return ast.TopLevelDecl{ .function = func };
+            }
+            return ast.TopLevelDecl{ .const_decl = try self.parseConstDecl(is_public) };
         } else if (self.match(.keyword_import)) {
-            const import_decl = try self.parseImportDecl();
+            var import_decl = try self.parseImportDecl();
+            import_decl.is_public = is_public;  // 🆕 pub import（重导出）
             return ast.TopLevelDecl{ .import_decl = import_decl };
+        } else if (self.check(.identifier) and std.mem.eql(u8, self.tokens[self.current].lexeme, "impl")) {
+            // 🆕 impl 是上下文关键字
+            return ast.TopLevelDecl{ .impl_decl = try self.parseImplDecl() };
         } else {
             // 🆕 v0.1.9: 更友好的错误信息
             const current = self.tokens[self.current];
-            std.debug.print("\x1b[1;31merror\x1b[0m: unexpected token\n", .{});
+            std.debug.print("\x1b[1;31m{s}[{s}]\x1b[0m: unexpected token\n", .{ messages.text(.level_error), codes.Code.expected_declaration.id() });
             std.debug.print("   \x1b[1;36m--> {s}:{d}:{d}\x1b[0m\n", .{
-                current.filename,
+                current.file.path(),
                 current.line,
                 current.column,
             });
             std.debug.print("   \x1b[1;36m|\x1b[0m\n", .{});
             std.debug.print(" \x1b[1;36m{d:>3} |\x1b[0m expected top-level declaration\n", .{current.line});
             std.debug.print("   \x1b[1;36m|\x1b[0m\n", .{});
-            std.debug.print("   \x1b[1;36m= note\x1b[0m: top-level declarations must be one of:\n", .{});
+            std.debug.print("   \x1b[1;36m= {s}\x1b[0m: top-level declarations must be one of:\n", .{messages.text(.level_note)});
             std.debug.print("     • 'let' (variable declaration)\n", .{});
+            std.debug.print("     • 'const' (compile-time constant or const fn)\n", .{});
             std.debug.print("     • 'type' (type definition)\n", .{});
             std.debug.print("     • 'fn' (function definition)\n", .{});
             std.debug.print("     • 'import' (module import)\n", .{});
-            std.debug.print("   \x1b[1;32m= help\x1b[0m: found '{s}', did you mean to start a declaration?\n\n", .{
+            std.debug.print("     • 'impl' (trait implementation)\n", .{});
+            std.debug.print("   \x1b[1;32m= {s}\x1b[0m: found '{s}', did you mean to start a declaration?\n\n", .{
+                messages.text(.level_help),
                 current.lexeme,
             });
             return error.UnexpectedToken;
         }
     }
 
-    fn parseFunctionDecl(self: *Parser, is_public: bool, is_async: bool, struct_context: ?struct {
+    /// 🆕 声明前的属性
+    const Attributes = struct {
+        link_name: ?ast.LinkName = null,
+        link_name_token: ?Token = null,
+        allow: ast.LintSet = ast.LintSet.initEmpty(),
+        allow_token: ?Token = null,
+        asm_bodies: []const ast.AsmBody = &[_]ast.AsmBody{},
+        asm_token: ?Token = null,
+        test_attr: ast.TestAttr = .{},
+        test_token: ?Token = null,
+        skip_reason: ?[]const u8 = null,
+        skip_token: ?Token = null,
+        track_caller_token: ?Token = null,
+    };
+    
+    fn parseAttributes(self: *Parser) !Attributes {
+        var attrs = Attributes{};
+        var asm_bodies = std.ArrayList(ast.AsmBody){};
+        while (self.check(.at)) {
+            const at = self.advance();
+            const attr = try self.consume(.identifier);
+            if (std.mem.eql(u8, attr.lexeme, "link_name")) {
+                if (attrs.link_name != null) {
+                    self.reportTokenError(.duplicate_attribute, attr, "duplicate @link_name attribute", null);
+                    return error.UnexpectedToken;
+                }
+                attrs.link_name = try self.parseLinkNameArgs(attr);
+                attrs.link_name_token = at;
+            } else if (std.mem.eql(u8, attr.lexeme, "allow")) {
+                try self.parseAllowArgs(&attrs.allow);
+                attrs.allow_token = at;
+            } else if (std.mem.eql(u8, attr.lexeme, "asm")) {
+                try self.parseAsmArgs(&asm_bodies);
+                attrs.asm_token = at;
+            } else if (std.mem.eql(u8, attr.lexeme, "test") or std.mem.eql(u8, attr.lexeme, "property")) {
+                if (attrs.test_token != null) {
+                    self.reportTokenError(.duplicate_attribute, attr, "a function can have only one @test or @property attribute", null);
+                    return error.UnexpectedToken;
+                }
+                attrs.test_attr = if (attr.lexeme[0] == 't') try self.parseTestArgs() else try self.parsePropertyArgs();
+                attrs.test_token = at;
+            } else if (std.mem.eql(u8, attr.lexeme, "skip")) {
+                if (attrs.skip_token != null) {
+                    self.reportTokenError(.duplicate_attribute, attr, "duplicate @skip attribute", null);
+                    return error.UnexpectedToken;
+                }
+                _ = try self.consume(.lparen);
+                attrs.skip_reason = try self.attributeString();
+                _ = try self.consume(.rparen);
+                attrs.skip_token = at;
+            } else if (std.mem.eql(u8, attr.lexeme, "track_caller")) {
+                if (attrs.track_caller_token != null) {
+                    self.reportTokenError(.duplicate_attribute, attr, "duplicate @track_caller attribute", null);
+                    return error.UnexpectedToken;
+                }
+                attrs.track_caller_token = at;
+            } else {
+                self.reportTokenError(.unknown_attribute, attr, "unknown attribute", "supported attributes are @link_name(\"symbol\"), @allow(lint), @asm(\"arch\", \"...\"), @test, @property, @skip(\"reason\") and @track_caller");
+                return error.UnexpectedToken;
+            }
+        }
+        attrs.asm_bodies = asm_bodies.items;
+        return attrs;
+    }
+    
+    /// 🆕 @allow(unused_pub, ...)：检查名见 ast.Lint
+    fn parseAllowArgs(self: *Parser, allow: *ast.LintSet) !void {
+        _ = try self.consume(.lparen);
+        while (!self.check(.rparen) and !self.isAtEnd()) {
+            const name = try self.consume(.identifier);
+            const lint = std.meta.stringToEnum(ast.Lint, name.lexeme) orelse {
+                self.reportTokenError(.unknown_lint, name, "unknown lint in @allow", "the lint that can be allowed is unused_pub");
+                return error.UnexpectedToken;
+            };
+            allow.insert(lint);
+            if (!self.match(.comma)) break;
+        }
+        _ = try self.consume(.rparen);
+    }
+    
+    /// 🆕 @test 或 @test(should_panic) / @test(should_panic = "消息片段")
+    fn parseTestArgs(self: *Parser) !ast.TestAttr {
+        var test_attr = ast.TestAttr{};
+        if (!self.match(.lparen)) return test_attr;
+        const option = try self.consume(.identifier);
+        if (!std.mem.eql(u8, option.lexeme, "should_panic")) {
+            self.reportTokenError(.unknown_attribute, option, "unknown @test option", "the supported option is should_panic or should_panic = \"message\"");
+            return error.UnexpectedToken;
+        }
+        test_attr.should_panic = if (self.match(.assign)) try self.attributeString() else "";
+        _ = try self.consume(.rparen);
+        return test_attr;
+    }
+    
+    /// 🆕 @property 或 @property(runs = N)
+    fn parsePropertyArgs(self: *Parser) !ast.TestAttr {
+        var test_attr = ast.TestAttr{ .runs = ast.DEFAULT_PROPERTY_RUNS };
+        if (!self.match(.lparen)) return test_attr;
+        const option = try self.consume(.identifier);
+        if (!std.mem.eql(u8, option.lexeme, "runs")) {
+            self.reportTokenError(.unknown_attribute, option, "unknown @property option", "the supported option is runs = N");
+            return error.UnexpectedToken;
+        }
+        _ = try self.consume(.assign);
+        const count = try self.consume(.int_literal);
+        test_attr.runs = std.fmt.parseInt(u32, count.lexeme, 10) catch 0;
+        if (test_attr.runs.? == 0) {
+            self.reportTokenError(.invalid_attribute_argument, count, "@property runs must be a positive integer", "write @property(runs = 500)");
+            return error.UnexpectedToken;
+        }
+        _ = try self.consume(.rparen);
+        return test_attr;
+    }
+    
+    /// 属性参数中的字符串内容（去掉引号）
+    fn attributeString(self: *Parser) ![]const u8 {
+        const token = try self.consume(.string_literal);
+        return token.lexeme[1 .. token.lexeme.len - 1];
+    }
+    
+    /// 🆕 @asm("x86_64", "汇编文本")：每个目标架构一个属性，架构名是 std.Target.Cpu.Arch
+    /// 文本中的转义（\n \t \xNN ...）在这里解码，只允许 ASCII
+    fn parseAsmArgs(self: *Parser, bodies: *std.ArrayList(ast.AsmBody)) !void {
+        _ = try self.consume(.lparen);
+        const arch_token = try self.consume(.string_literal);
+        const arch = arch_token.lexeme[1 .. arch_token.lexeme.len - 1];
+        if (std.meta.stringToEnum(std.Target.Cpu.Arch, arch) == null) {
+            self.reportTokenError(.invalid_asm_declaration, arch_token, "unknown target architecture in @asm", "use a name such as x86_64, aarch64 or riscv64");
+            return error.UnexpectedToken;
+        }
+        for (bodies.items) |body| {
+            if (std.mem.eql(u8, body.arch, arch)) {
+                self.reportTokenError(.duplicate_attribute, arch_token, "duplicate @asm body for this architecture", null);
+                return error.UnexpectedToken;
+            }
+        }
+        _ = try self.consume(.comma);
+        const text_token = try self.consume(.string_literal);
+        const text = try self.decodeByteEscapes(text_token, text_token.lexeme[1 .. text_token.lexeme.len - 1]);
+        if (std.mem.trim(u8, text, " \t\r\n").len == 0) {
+            self.reportTokenError(.invalid_asm_declaration, text_token, "empty @asm body", "the assembly must return from the function itself (e.g. end with `ret`)");
+            return error.UnexpectedToken;
+        }
+        _ = try self.consume(.rparen);
+        try bodies.append(self.arenaAllocator(), .{ .arch = arch, .text = text });
+    }
+    
+    /// 🆕 @link_name("default", macos: "_sym", windows: "sym")
+    /// 默认名称可以省略；每个目标系统名称必须是 Zig 的 std.Target.Os.Tag
+    fn parseLinkNameArgs(self: *Parser, attr: Token) !ast.LinkName {
+        _ = try self.consume(.lparen);
+        var default: ?[]const u8 = null;
+        var overrides = std.ArrayList(ast.TargetLinkName){};
+        while (!self.check(.rparen) and !self.isAtEnd()) {
+            if (self.check(.string_literal)) {
+                const token = self.advance();
+                if (default != null) {
+                    self.reportTokenError(.invalid_link_name, token, "@link_name has more than one default symbol name", "per-target names are written as `macos: \"_symbol\"`");
+                    return error.UnexpectedToken;
+                }
+                default = try self.linkSymbol(token);
+            } else {
+                const os = try self.consume(.identifier);
+                if (std.meta.stringToEnum(std.Target.Os.Tag, os.lexeme) == null) {
+                    self.reportTokenError(.invalid_link_name, os, "unknown target system in @link_name", "use a name such as linux, macos, windows or freebsd");
+                    return error.UnexpectedToken;
+                }
+                _ = try self.consume(.colon);
+                const name = try self.linkSymbol(try self.consume(.string_literal));
+                try overrides.append(self.arenaAllocator(), .{ .os = os.lexeme, .name = name });
+            }
+            if (!self.match(.comma)) break;
+        }
+        _ = try self.consume(.rparen);
+        
+        if (default == null and overrides.items.len == 0) {
+            self.reportTokenError(.invalid_link_name, attr, "@link_name needs a symbol name", "write @link_name(\"symbol\")");
+            return error.UnexpectedToken;
+        }
+        return ast.LinkName{
+            .default = default,
+            .overrides = try overrides.toOwnedSlice(self.arenaAllocator()),
+        };
+    }
+    
+    /// @link_name 中的符号名（不允许为空或含转义）
+    fn linkSymbol(self: *Parser, token: Token) ![]const u8 {
+        const name = token.lexeme[1 .. token.lexeme.len - 1];
+        if (name.len == 0 or std.mem.indexOfAny(u8, name, "\\ $") != null) {
+            self.reportTokenError(.invalid_link_name, token, "invalid symbol name in @link_name", "symbol names cannot be empty or contain spaces, '\\' or '$'");
+            return error.UnexpectedToken;
+        }
+        return name;
+    }
+
+    /// 方法所在的类型（self 参数的类型）
+    const MethodContext = struct {
         name: []const u8,
         type_params: [][]const u8,
-    }) !ast.FunctionDecl {
+    };
+
+    fn parseFunctionDecl(self: *Parser, is_public: bool, is_async: bool, struct_context: ?MethodContext) !ast.FunctionDecl {
+        var func = try self.parseFunctionSignature(is_public, is_async, struct_context);
+        
+        const outer_function = self.current_function;
+        defer self.current_function = outer_function;
+        self.current_function = if (struct_context) |ctx|
+            try std.fmt.allocPrint(self.arenaAllocator(), "{s}.{s}", .{ ctx.name, func.name })
+        else
+            func.name;
+        const outer_locals = self.locals.items.len;
+        defer self.locals.shrinkRetainingCapacity(outer_locals);
+        for (func.params) |param| try self.locals.append(self.arenaAllocator(), param.name);
+        
+        // 解析函数体
+        _ = try self.consume(.lbrace);
+        func.body = try self.parseStmtList();
+        _ = try self.consume(.rbrace);
+        return func;
+    }
+
+    /// 🆕 函数签名（名称、泛型参数、参数、返回类型、where 子句），函数体为空
+    fn parseFunctionSignature(self: *Parser, is_public: bool, is_async: bool, struct_context: ?MethodContext) !ast.FunctionDecl {
         const name = try self.consume(.identifier);
         
-        // 解析泛型参数
+        // 解析泛型参数（🆕 可带约束：<T: Num + Ord>）
         var type_params = std.ArrayList([]const u8){};
+        var bounds = std.ArrayList(ast.TypeBound){};
+        var trait_bounds = std.ArrayList(ast.TraitBound){};
         if (self.match(.lt)) {
             while (!self.check(.gt)) {
                 const type_param = try self.consume(.identifier);
                 try type_params.append(self.arenaAllocator(), type_param.lexeme);
+                if (self.match(.colon)) {
+                    try self.parseBoundList(type_param.lexeme, &bounds, null);
+                }
                 if (!self.match(.comma)) break;
             }
             _ = try self.consume(.gt);
@@ -280,19 +646,211 @@ pub const Parser = struct {
         _ = try self.consume(.arrow);
         const return_type = try self.parseType();
         
-        // 解析函数体
-        _ = try self.consume(.lbrace);
-        const body = try self.parseStmtList();
-        _ = try self.consume(.rbrace);
-
+        // 🆕 where 子句：where T: Num, U: Eq（where 是上下文关键字）
+        // 泛型类型的方法还可以约束类型的参数：方法只在满足约束的实例上存在
+        // 🆕 类型的参数还可以用用户 trait 约束（where T: Show）
+        if (self.check(.identifier) and std.mem.eql(u8, self.tokens[self.current].lexeme, "where")) {
+            _ = self.advance();
+            while (self.check(.identifier)) {
+                const param = self.advance();
+                const is_own_param = containsName(type_params.items, param.lexeme);
+                const is_type_param = if (struct_context) |ctx| containsName(ctx.type_params, param.lexeme) else false;
+                if (!is_own_param and !is_type_param) {
+                    self.reportTokenError(.unknown_where_parameter, param, "unknown type parameter in where clause", "declare it in the generic parameter list: fn name<T>(...)");
+                    return error.UnexpectedToken;
+                }
+                _ = try self.consume(.colon);
+                try self.parseBoundList(param.lexeme, &bounds, if (is_own_param) null else &trait_bounds);
+                if (!self.match(.comma)) break;
+            }
+        }
+        
         return ast.FunctionDecl{
             .name = name.lexeme,
+            .bounds = try bounds.toOwnedSlice(self.arenaAllocator()),
+            .trait_bounds = try trait_bounds.toOwnedSlice(self.arenaAllocator()),
             .type_params = try type_params.toOwnedSlice(self.arenaAllocator()),
             .params = try params.toOwnedSlice(self.arenaAllocator()),
             .return_type = return_type,
-            .body = body,
+            .body = &.{},
             .is_public = is_public,
             .is_async = is_async,
+            .location = .{
+                .file = name.file,
+                .line = name.line,
+                .column = name.column - name.lexeme.len,
+            },
+        };
+    }
+
+    /// 🆕 impl<T> Trait for Type<T> where T: Bound { fn ... }
+    ///
+    /// 目标类型依次以 impl 的参数为类型实参，并且是本文件中声明的 struct / enum。
+    /// 解析结束后方法并入目标类型（mergeImpls），where 约束加到每个方法上：
+    /// 方法只在类型实参满足约束的实例上存在，后端只为这些实例生成方法
+    fn parseImplDecl(self: *Parser) !ast.ImplDecl {
+        const impl_token = self.advance();
+        var type_params = std.ArrayList([]const u8){};
+        if (self.match(.lt)) {
+            while (!self.check(.gt)) {
+                const type_param = try self.consume(.identifier);
+                try type_params.append(self.arenaAllocator(), type_param.lexeme);
+                if (!self.match(.comma)) break;
+            }
+            _ = try self.consume(.gt);
+        }
+        
+        const trait_name = try self.consume(.identifier);
+        var trait_args = std.ArrayList(ast.Type){};
+        if (self.match(.lt)) {
+            while (!self.check(.gt)) {
+                try trait_args.append(self.arenaAllocator(), try self.parseType());
+                if (!self.match(.comma)) break;
+            }
+            _ = try self.consume(.gt);
+        }
+        
+        const for_token = try self.consume(.identifier);
+        if (!std.mem.eql(u8, for_token.lexeme, "for")) {
+            self.reportTokenError(.invalid_impl, for_token, "expected `for` after the trait name", "write `impl Trait for Type { ... }`");
+            return error.UnexpectedToken;
+        }
+        const target_token = self.tokens[self.current];
+        const target = try self.parseType();
+        const target_name: []const u8, const target_args: []const ast.Type = switch (target) {
+            .named => |n| .{ n, &.{} },
+            .generic_instance => |gi| .{ gi.name, gi.type_args },
+            else => {
+                self.reportTokenError(.invalid_impl, target_token, "an impl can only target a struct or enum", null);
+                return error.UnexpectedToken;
+            },
+        };
+        const args_are_params = target_args.len == type_params.items.len and for (target_args, type_params.items) |arg, tp| {
+            if (arg != .named or !std.mem.eql(u8, arg.named, tp)) break false;
+        } else true;
+        if (!args_are_params) {
+            self.reportTokenError(.invalid_impl, target_token, "the impl's target must take the impl's type parameters in order", "write `impl<T> Trait for Box<T> { ... }`");
+            return error.UnexpectedToken;
+        }
+        
+        // where 子句：约束 impl 的参数
+        var bounds = std.ArrayList(ast.TypeBound){};
+        var trait_bounds = std.ArrayList(ast.TraitBound){};
+        if (self.check(.identifier) and std.mem.eql(u8, self.tokens[self.current].lexeme, "where")) {
+            _ = self.advance();
+            while (self.check(.identifier)) {
+                const param = self.advance();
+                if (!containsName(type_params.items, param.lexeme)) {
+                    self.reportTokenError(.unknown_where_parameter, param, "unknown type parameter in where clause", "declare it in the impl's parameter list: impl<T> ...");
+                    return error.UnexpectedToken;
+                }
+                _ = try self.consume(.colon);
+                try self.parseBoundList(param.lexeme, &bounds, &trait_bounds);
+                if (!self.match(.comma)) break;
+            }
+        }
+        
+        _ = try self.consume(.lbrace);
+        var methods = std.ArrayList(ast.FunctionDecl){};
+        while (!self.check(.rbrace) and !self.isAtEnd()) {
+            const method_is_pub = self.match(.keyword_pub);
+            _ = try self.consume(.keyword_fn);
+            var method = try self.parseFunctionDecl(method_is_pub, false, .{
+                .name = target_name,
+                .type_params = type_params.items,
+            });
+            method.bounds = try std.mem.concat(self.arenaAllocator(), ast.TypeBound, &.{ bounds.items, method.bounds });
+            method.trait_bounds = try std.mem.concat(self.arenaAllocator(), ast.TraitBound, &.{ trait_bounds.items, method.trait_bounds });
+            try methods.append(self.arenaAllocator(), method);
+        }
+        _ = try self.consume(.rbrace);
+        
+        try self.impl_targets.append(self.arenaAllocator(), target_token);
+        return ast.ImplDecl{
+            .trait_name = trait_name.lexeme,
+            .type_args = try trait_args.toOwnedSlice(self.arenaAllocator()),
+            .target_type = target,
+            .methods = try methods.toOwnedSlice(self.arenaAllocator()),
+            .type_params = try type_params.toOwnedSlice(self.arenaAllocator()),
+            .location = .{
+                .file = impl_token.file,
+                .line = impl_token.line,
+                .column = impl_token.column - impl_token.lexeme.len,
+            },
+        };
+    }
+    
+    /// 🆕 把 impl 块的方法加到目标类型的方法中（类型检查和后端只看到类型自己的方法）
+    fn mergeImpls(self: *Parser, declarations: []ast.TopLevelDecl) !void {
+        var impl_index: usize = 0;
+        for (declarations) |decl| {
+            if (decl != .impl_decl) continue;
+            const impl = decl.impl_decl;
+            const target_token = self.impl_targets.items[impl_index];
+            impl_index += 1;
+            
+            const target_name = switch (impl.target_type) {
+                .named => |n| n,
+                .generic_instance => |gi| gi.name,
+                else => unreachable,  // parseImplDecl 已检查
+            };
+            const type_decl: *ast.TypeDecl = for (declarations) |*d| {
+                if (d.* == .type_decl and std.mem.eql(u8, d.type_decl.name, target_name)) break &d.type_decl;
+            } else {
+                self.reportTokenError(.invalid_impl, target_token, "the target of an impl must be a struct or enum declared in this file", null);
+                return error.UnexpectedToken;
+            };
+            const methods: *[]ast.FunctionDecl = switch (type_decl.kind) {
+                .struct_type => |*st| &st.methods,
+                .enum_type => |*et| &et.methods,
+                .trait_type, .alias => {
+                    self.reportTokenError(.invalid_impl, target_token, "the target of an impl must be a struct or enum", null);
+                    return error.UnexpectedToken;
+                },
+            };
+            // 方法签名使用 impl 的参数名：必须与类型声明中的参数名相同
+            const same_params = type_decl.type_params.len == impl.type_params.len and for (type_decl.type_params, impl.type_params) |a, b| {
+                if (!std.mem.eql(u8, a, b)) break false;
+            } else true;
+            if (!same_params) {
+                const help = try std.fmt.allocPrint(self.arenaAllocator(), "use the parameter names from the declaration of '{s}'", .{target_name});
+                self.reportTokenError(.invalid_impl, target_token, "the impl's type parameters differ from the type's", help);
+                return error.UnexpectedToken;
+            }
+            for (impl.methods) |method| {
+                for (methods.*) |existing| {
+                    if (!std.mem.eql(u8, existing.name, method.name)) continue;
+                    const message = try std.fmt.allocPrint(self.arenaAllocator(), "'{s}' already has a method named '{s}'", .{ target_name, method.name });
+                    self.reportTokenError(.invalid_impl, target_token, message, null);
+                    return error.UnexpectedToken;
+                }
+            }
+            methods.* = try std.mem.concat(self.arenaAllocator(), ast.FunctionDecl, &.{ methods.*, impl.methods });
+        }
+    }
+
+    /// 🆕 const NAME[: T] = expr;
+    fn parseConstDecl(self: *Parser, is_public: bool) !ast.ConstDecl {
+        const name = try self.consume(.identifier);
+        var const_type: ?ast.Type = null;
+        if (self.match(.colon)) {
+            const_type = try self.parseType();
+        }
+        _ = try self.consume(.assign);
+        const value = try self.arenaAllocator().create(ast.Expr);
+        value.* = try self.parseExpr();
+        _ = self.match(.semicolon);
+        
+        return ast.ConstDecl{
+            .name = name.lexeme,
+            .type = const_type,
+            .value = value,
+            .is_public = is_public,
+            .location = .{
+                .file = name.file,
+                .line = name.line,
+                .column = name.column - name.lexeme.len,
+            },
         };
     }
 
@@ -313,6 +871,25 @@ pub const Parser = struct {
         
         _ = try self.consume(.assign);
         
+        const location = ast.SourceLocation{
+            .file = name.file,
+            .line = name.line,
+            .column = name.column - name.lexeme.len,
+        };
+        
+        // 🆕 类型别名：= 后面不是 struct / enum / trait { ... }
+        if (!self.isTypeKindStart()) {
+            const target = try self.parseType();
+            _ = self.match(.semicolon);
+            return ast.TypeDecl{
+                .name = name.lexeme,
+                .type_params = try type_params.toOwnedSlice(self.arenaAllocator()),
+                .kind = ast.TypeDeclKind{ .alias = target },
+                .is_public = is_public,
+                .location = location,
+            };
+        }
+        
         // 判断类型种类（struct, enum, trait 现在是标识符而非关键字）
         const type_kind_tok = try self.consume(.identifier);
         const type_kind = type_kind_tok.lexeme;
@@ -324,12 +901,15 @@ pub const Parser = struct {
             
             var fields = std.ArrayList(ast.StructField){};
             var methods = std.ArrayList(ast.FunctionDecl){};
+            var assoc_types = std.ArrayList(ast.AssocType){};
             
             while (!self.check(.rbrace) and !self.isAtEnd()) {
                 const field_is_pub = self.match(.keyword_pub);
                 
-                // 检查是否是方法定义
-                if (self.check(.keyword_fn)) {
+                if (self.match(.keyword_type)) {
+                    try assoc_types.append(self.arenaAllocator(), try self.parseAssocType());
+                } else if (self.check(.keyword_fn)) {
+                    // 方法定义
                     _ = self.advance();
                     // 🆕 传递struct上下文给parseFunctionDecl
                     const method = try self.parseFunctionDecl(field_is_pub, false, .{
@@ -359,17 +939,21 @@ pub const Parser = struct {
             kind = ast.TypeDeclKind{ .struct_type = .{
                 .fields = try fields.toOwnedSlice(self.arenaAllocator()),
                 .methods = try methods.toOwnedSlice(self.arenaAllocator()),
+                .assoc_types = try assoc_types.toOwnedSlice(self.arenaAllocator()),
             }};
         } else if (std.mem.eql(u8, type_kind, "enum")) {
             _ = try self.consume(.lbrace);
             
             var variants = std.ArrayList(ast.EnumVariant){};
             var methods = std.ArrayList(ast.FunctionDecl){};
+            var assoc_types = std.ArrayList(ast.AssocType){};
             
             while (!self.check(.rbrace) and !self.isAtEnd()) {
                 const variant_is_pub = self.match(.keyword_pub);
                 
-                if (self.check(.keyword_fn)) {
+                if (self.match(.keyword_type)) {
+                    try assoc_types.append(self.arenaAllocator(), try self.parseAssocType());
+                } else if (self.check(.keyword_fn)) {
                     _ = self.advance();
                     // 🆕 传递enum上下文给parseFunctionDecl
                     const method = try self.parseFunctionDecl(variant_is_pub, false, .{
@@ -404,12 +988,40 @@ pub const Parser = struct {
             kind = ast.TypeDeclKind{ .enum_type = .{
                 .variants = try variants.toOwnedSlice(self.arenaAllocator()),
                 .methods = try methods.toOwnedSlice(self.arenaAllocator()),
+                .assoc_types = try assoc_types.toOwnedSlice(self.arenaAllocator()),
             }};
         } else if (std.mem.eql(u8, type_kind, "trait")) {
+            // 🆕 supertrait：trait: Eq + Display<T> { ... }
+            var supertraits = std.ArrayList(ast.TraitRef){};
+            if (self.match(.colon)) {
+                while (true) {
+                    const super_name = try self.consume(.identifier);
+                    var super_args = std.ArrayList(ast.Type){};
+                    if (self.match(.lt)) {
+                        while (!self.check(.gt)) {
+                            try super_args.append(self.arenaAllocator(), try self.parseType());
+                            if (!self.match(.comma)) break;
+                        }
+                        _ = try self.consume(.gt);
+                    }
+                    try supertraits.append(self.arenaAllocator(), .{
+                        .name = super_name.lexeme,
+                        .type_args = try super_args.toOwnedSlice(self.arenaAllocator()),
+                    });
+                    if (!self.match(.plus)) break;
+                }
+            }
             _ = try self.consume(.lbrace);
             
             var method_sigs = std.ArrayList(ast.FunctionSignature){};
+            var assoc_names = std.ArrayList([]const u8){};
             while (!self.check(.rbrace) and !self.isAtEnd()) {
+                // 🆕 关联类型声明：type Item;（实现 trait 的类型用 type Item = ...; 给出具体类型）
+                if (self.match(.keyword_type)) {
+                    try assoc_names.append(self.arenaAllocator(), (try self.consume(.identifier)).lexeme);
+                    _ = self.match(.semicolon);
+                    continue;
+                }
                 _ = try self.consume(.keyword_fn);
                 const method_name = try self.consume(.identifier);
                 
@@ -454,6 +1066,7 @@ pub const Parser = struct {
                 
                 _ = try self.consume(.arrow);
                 const return_type = try self.parseType();
+                _ = self.match(.semicolon);  // 🆕 签名以 ; 结束（与旧的 trait 声明一致）
                 
                 try method_sigs.append(self.arenaAllocator(), ast.FunctionSignature{
                     .name = method_name.lexeme,
@@ -466,8 +1079,11 @@ pub const Parser = struct {
             
             kind = ast.TypeDeclKind{ .trait_type = .{
                 .methods = try method_sigs.toOwnedSlice(self.arenaAllocator()),
+                .supertraits = try supertraits.toOwnedSlice(self.arenaAllocator()),
+                .assoc_types = try assoc_names.toOwnedSlice(self.arenaAllocator()),
             }};
         } else {
+            self.reportTokenError(.expected_type, type_kind_tok, "expected 'struct', 'enum' or 'trait'", null);
             return error.ExpectedTypeKind;
         }
         
@@ -476,9 +1092,29 @@ pub const Parser = struct {
             .type_params = try type_params.toOwnedSlice(self.arenaAllocator()),
             .kind = kind,
             .is_public = is_public,
+            .location = location,
         };
     }
     
+    /// 🆕 struct / enum 中的关联类型（type 已读取）：Item = i32;
+    fn parseAssocType(self: *Parser) !ast.AssocType {
+        const name = try self.consume(.identifier);
+        _ = try self.consume(.assign);
+        const target = try self.parseType();
+        _ = self.match(.semicolon);
+        return ast.AssocType{ .name = name.lexeme, .type = target };
+    }
+    
+    /// 🆕 type Name = 之后是否是 struct / enum / trait 定义（否则是类型别名）
+    fn isTypeKindStart(self: *Parser) bool {
+        if (!self.check(.identifier) or self.current + 1 >= self.tokens.len) return false;
+        const kind = self.tokens[self.current].lexeme;
+        // trait 可以带 supertrait：trait: Eq { ... }
+        if (self.tokens[self.current + 1].type == .colon) return std.mem.eql(u8, kind, "trait");
+        if (self.tokens[self.current + 1].type != .lbrace) return false;
+        return std.mem.eql(u8, kind, "struct") or std.mem.eql(u8, kind, "enum") or std.mem.eql(u8, kind, "trait");
+    }
+    
     fn parseStructDecl(self: *Parser, is_public: bool) !ast.StructDecl {
         const name = try self.consume(.identifier);
         
@@ -706,9 +1342,16 @@ pub const Parser = struct {
     }
 
     fn parseImportDecl(self: *Parser) !ast.ImportDecl {
-        // 🆕 支持两种格式：
-        // 1. import math.add;           (单项导入)
-        // 2. import math.{add, sub};    (多项导入)
+        // 🆕 支持以下格式：
+        // 1. import math.add;              (单项导入)
+        // 2. import math.{add, sub};       (多项导入)
+        // 3. import math as m;             (命名空间别名，m.add 访问)
+        // 4. import "a::b::c" as util;     (字符串路径 + 别名)
+        // 5. import "a::b::c" { foo, Bar } (字符串路径 + 选择性导入)
+        
+        if (self.check(.string_literal)) {
+            return self.parseStringImportDecl();
+        }
         
         var path_parts = std.ArrayList([]const u8){};
         defer path_parts.deinit(self.arenaAllocator());
@@ -731,26 +1374,24 @@ pub const Parser = struct {
         }
         const module_path_owned = try module_path.toOwnedSlice(self.arenaAllocator());
         
+        // 🆕 命名空间别名：import math as m;
+        if (self.match(.keyword_as)) {
+            const alias = try self.consume(.identifier);
+            try self.namespaces.put(alias.lexeme, {});
+            _ = self.match(.semicolon);
+            
+            return ast.ImportDecl{
+                .module_path = module_path_owned,
+                .items = .{ .namespace = alias.lexeme },
+            };
+        }
+        
         // 检查是否是多项导入
         if (self.match(.lbrace)) {
             // 多项导入：import math.{add, sub, Vec2}
-            var items = std.ArrayList([]const u8){};
-            
-            while (!self.check(.rbrace)) {
-                const item = try self.consume(.identifier);
-                try items.append(self.arenaAllocator(), item.lexeme);
-                
-                if (!self.match(.comma)) {
-                    break;
-                }
-            }
-            
-            _ = try self.consume(.rbrace);
-            _ = self.match(.semicolon);
-            
             return ast.ImportDecl{
                 .module_path = module_path_owned,
-                .items = .{ .multiple = try items.toOwnedSlice(self.arenaAllocator()) },
+                .items = .{ .multiple = try self.parseImportItemList() },
             };
         } else {
             // 单项导入：import math.add;
@@ -777,6 +1418,62 @@ pub const Parser = struct {
             };
         }
     }
+    
+    /// 🆕 字符串路径导入：import "a::b::c" as util; / import "a::b::c" { foo, Bar }
+    fn parseStringImportDecl(self: *Parser) !ast.ImportDecl {
+        const path_token = self.advance();
+        const raw_path = path_token.lexeme[1 .. path_token.lexeme.len - 1];  // 去掉引号
+        
+        // a::b::c -> a/b/c
+        var module_path = std.ArrayList(u8){};
+        var segments = std.mem.splitSequence(u8, raw_path, "::");
+        var last_segment: []const u8 = raw_path;
+        var first = true;
+        while (segments.next()) |segment| {
+            if (segment.len == 0) return error.InvalidImportPath;
+            if (!first) try module_path.append(self.arenaAllocator(), '/');
+            try module_path.appendSlice(self.arenaAllocator(), segment);
+            last_segment = segment;
+            first = false;
+        }
+        const module_path_owned = try module_path.toOwnedSlice(self.arenaAllocator());
+        
+        if (self.match(.lbrace)) {
+            return ast.ImportDecl{
+                .module_path = module_path_owned,
+                .items = .{ .multiple = try self.parseImportItemList() },
+            };
+        }
+        
+        // 无别名时使用最后一段作为命名空间：import "a::b::c" 等价于 as c
+        const alias = if (self.match(.keyword_as)) (try self.consume(.identifier)).lexeme else last_segment;
+        try self.namespaces.put(alias, {});
+        _ = self.match(.semicolon);
+        
+        return ast.ImportDecl{
+            .module_path = module_path_owned,
+            .items = .{ .namespace = alias },
+        };
+    }
+    
+    /// 解析 { a, b, C } 导入列表（左花括号已消费）
+    fn parseImportItemList(self: *Parser) ![][]const u8 {
+        var items = std.ArrayList([]const u8){};
+        
+        while (!self.check(.rbrace)) {
+            const item = try self.consume(.identifier);
+            try items.append(self.arenaAllocator(), item.lexeme);
+            
+            if (!self.match(.comma)) {
+                break;
+            }
+        }
+        
+        _ = try self.consume(.rbrace);
+        _ = self.match(.semicolon);
+        
+        return items.toOwnedSlice(self.arenaAllocator());
+    }
 
     fn parseType(self: *Parser) !ast.Type {
         // 有符号整数类型（8-128位）
@@ -802,16 +1499,59 @@ pub const Parser = struct {
         if (self.match(.type_char)) return ast.Type.char;
         if (self.match(.type_string)) return ast.Type.string;
         if (self.match(.type_void)) return ast.Type.void;
+        // 🆕 `!`：不会返回的函数（panic、paw_exit、死循环）
+        if (self.match(.bang)) return ast.Type.never;
+        // 🆕 Self：trait 方法签名中实现该 trait 的类型（与 self 参数的类型相同）
+        if (self.match(.keyword_Self)) return self.parseProjection(ast.Type{ .named = "Self" }, null);
         
+        // 🆕 <T as Iter>::Item：指明由哪个 trait 声明的关联类型
+        if (self.match(.lt)) {
+            const base = try self.parseType();
+            _ = try self.consume(.keyword_as);
+            const trait_name = try self.consume(.identifier);
+            _ = try self.consume(.gt);
+            const projection = try self.parseProjection(base, trait_name.lexeme);
+            if (projection != .projection) _ = try self.consume(.double_colon);
+            return projection;
+        }
+
+        // 🆕 函数类型 fn(T, U) -> R（省略 -> R 时返回 void）
+        if (self.match(.keyword_fn)) {
+            _ = try self.consume(.lparen);
+            var params = std.ArrayList(ast.Type){};
+            while (!self.check(.rparen)) {
+                try params.append(self.arenaAllocator(), try self.parseType());
+                if (!self.match(.comma)) break;
+            }
+            _ = try self.consume(.rparen);
+
+            const return_type = try self.arenaAllocator().create(ast.Type);
+            return_type.* = if (self.match(.arrow)) try self.parseType() else ast.Type.void;
+            return ast.Type{
+                .function = .{
+                    .params = try params.toOwnedSlice(self.arenaAllocator()),
+                    .return_type = return_type,
+                },
+            };
+        }
+
         // 🆕 数组类型 [T] 或 [T; N]
         if (self.match(.lbracket)) {
             const elem_type = try self.parseType();
             
             var size: ?usize = null;
+            var size_expr: ?*ast.Expr = null;
             if (self.match(.semicolon)) {
                 // 固定大小数组 [T; N]
-                const size_token = try self.consume(.int_literal);
-                size = try std.fmt.parseInt(usize, size_token.lexeme, 10);
+                if (self.check(.int_literal) and self.tokens[self.current + 1].type == .rbracket) {
+                    const size_token = self.advance();
+                    size = try std.fmt.parseInt(usize, ast.splitIntLiteral(size_token.lexeme).digits, 10);
+                } else {
+                    // 🆕 [T; expr]：长度在解析结束后编译期求值
+                    const expr_ptr = try self.arenaAllocator().create(ast.Expr);
+                    expr_ptr.* = try self.parseExpr();
+                    size_expr = expr_ptr;
+                }
             }
             
             _ = try self.consume(.rbracket);
@@ -821,6 +1561,7 @@ pub const Parser = struct {
             
             return ast.Type{
                 .array = .{
+                    .size_expr = size_expr,
                     .element = elem_type_ptr,
                     .size = size,
                 },
@@ -830,6 +1571,9 @@ pub const Parser = struct {
         if (self.check(.identifier)) {
             const name = self.advance();
             
+            // 🆕 dispatch 体中的类型参数
+            if (self.dispatchBinding(name.lexeme)) |bound| return self.parseProjection(bound, null);
+            
             // 检查是否有泛型参数
             if (self.match(.lt)) {
                 var type_args = std.ArrayList(ast.Type){};
@@ -848,11 +1592,35 @@ pub const Parser = struct {
                 };
             }
             
-            return ast.Type{ .named = name.lexeme };
+            // 🆕 Byte 是 u8 的别名
+            if (std.mem.eql(u8, name.lexeme, "Byte")) return ast.Type.u8;
+            
+            // 🆕 UInt / ULong 是 u32 / u64 的别名
+            if (std.mem.eql(u8, name.lexeme, "UInt")) return ast.Type.u32;
+            if (std.mem.eql(u8, name.lexeme, "ULong")) return ast.Type.u64;
+            
+            // 🆕 Bytes 是 [u8] 的别名：不保证是 UTF-8 的原始数据（string 总是 UTF-8）
+            if (std.mem.eql(u8, name.lexeme, "Bytes")) {
+                const elem = try self.arenaAllocator().create(ast.Type);
+                elem.* = ast.Type.u8;
+                return ast.Type{ .array = .{ .element = elem, .size = null } };
+            }
+            
+            return self.parseProjection(ast.Type{ .named = name.lexeme }, null);
         }
         
+        self.reportTokenError(.expected_type, self.tokens[self.current], "expected a type", null);
         return error.ExpectedType;
     }
+    
+    /// 🆕 类型之后的 ::Name 是关联类型投影（T::Item、Self::Item）；没有 :: 时返回 base
+    fn parseProjection(self: *Parser, base: ast.Type, trait_name: ?[]const u8) !ast.Type {
+        if (!self.match(.double_colon)) return base;
+        const name = try self.consume(.identifier);
+        const base_ptr = try self.arenaAllocator().create(ast.Type);
+        base_ptr.* = base;
+        return ast.Type{ .projection = .{ .base = base_ptr, .trait_name = trait_name, .name = name.lexeme } };
+    }
 
     fn parseStmtList(self: *Parser) (std.mem.Allocator.Error || error{UnexpectedToken,ExpectedType,ExpectedPattern,InvalidCharacter,Overflow})![]ast.Stmt {
         var stmts = std.ArrayList(ast.Stmt){};
@@ -875,10 +1643,23 @@ pub const Parser = struct {
         } else if (self.match(.keyword_return)) {
             return try self.parseReturnStmt();
         } else if (self.match(.keyword_loop)) {
-            return try self.parseLoopStmt();
+            return try self.parseLoopStmt(null);
+        } else if (self.check(.label)) {
+            // 🆕 'outer: loop { }
+            const label_token = self.advance();
+            _ = try self.consume(.colon);
+            _ = try self.consume(.keyword_loop);
+            return try self.parseLoopStmt(labelName(label_token));
         } else if (self.match(.keyword_break)) {
+            const label = if (self.check(.label)) labelName(self.advance()) else null;
+            // 🆕 break value;
+            const value = if (!self.check(.semicolon) and !self.check(.rbrace)) try self.parseExpr() else null;
             _ = self.match(.semicolon);
-            return ast.Stmt{ .break_stmt = null };
+            return ast.Stmt{ .break_stmt = .{ .label = label, .value = value } };
+        } else if (self.match(.keyword_continue)) {
+            const label = if (self.check(.label)) labelName(self.advance()) else null;
+            _ = self.match(.semicolon);
+            return ast.Stmt{ .continue_stmt = label };
         } else {
             // 🆕 尝试解析赋值语句或表达式语句
             const expr = try self.parseExpr();
@@ -936,6 +1717,7 @@ pub const Parser = struct {
         if (self.match(.assign)) {
             init_expr = try self.parseExpr();
         }
+        try self.locals.append(self.arenaAllocator(), name.lexeme);
         
         _ = self.match(.semicolon);
         
@@ -961,8 +1743,53 @@ pub const Parser = struct {
         return ast.Stmt{ .return_stmt = value };
     }
 
+    /// 🆕 标签 token 'outer -> outer
+    fn labelName(token: Token) []const u8 {
+        return token.lexeme[1..];
+    }
+
+    /// 🆕 loop 表达式：只有无限循环形式，值来自 break value
+    fn parseLoopExpr(self: *Parser, label: ?[]const u8) (std.mem.Allocator.Error || error{UnexpectedToken,ExpectedType,ExpectedPattern,InvalidCharacter,Overflow})!ast.Expr {
+        _ = try self.consume(.lbrace);
+        const body = try self.parseStmtList();
+        _ = try self.consume(.rbrace);
+        
+        const result_type = try self.arenaAllocator().create(ast.Type);
+        result_type.* = ast.Type.void;
+        return ast.Expr{
+            .loop_expr = .{
+                .label = label,
+                .body = body,
+                .result_type = result_type,
+            },
+        };
+    }
+
+    /// 🆕 lambda 的返回类型和函数体（省略 -> 时返回 void，与 fn 类型一致）
+    fn parseLambda(self: *Parser, params: []ast.Param) (std.mem.Allocator.Error || error{UnexpectedToken,ExpectedType,ExpectedPattern,InvalidCharacter,Overflow})!ast.Expr {
+        const return_type = if (self.match(.arrow)) try self.parseType() else ast.Type.void;
+        for (params) |param| try self.locals.append(self.arenaAllocator(), param.name);
+        _ = try self.consume(.lbrace);
+        const body = try self.parseStmtList();
+        _ = try self.consume(.rbrace);
+
+        const captures = try self.arenaAllocator().create([]ast.Capture);
+        captures.* = &[_]ast.Capture{};
+        const lifted_name = try self.arenaAllocator().create([]const u8);
+        lifted_name.* = "";
+        return ast.Expr{
+            .lambda = .{
+                .params = params,
+                .return_type = return_type,
+                .body = body,
+                .captures = captures,
+                .lifted_name = lifted_name,
+            },
+        };
+    }
+
     // 新增：解析 loop 统一循环语句
-    fn parseLoopStmt(self: *Parser) !ast.Stmt {
+    fn parseLoopStmt(self: *Parser, label: ?[]const u8) !ast.Stmt {
         // loop { } - 无限循环
         // loop condition { } - 条件循环
         // loop i in iter { } - 遍历循环（🆕 移除 for）
@@ -983,6 +1810,7 @@ pub const Parser = struct {
                 _ = self.advance();  // 消费 identifier (binding)
                 _ = try self.consume(.keyword_in);  // 消费 in
                 const iterable = try self.parseExpr();
+                try self.locals.append(self.arenaAllocator(), binding_token.lexeme);
                 
                 iterator = ast.LoopIterator{
                     .binding = binding_token.lexeme,
@@ -1004,6 +1832,7 @@ pub const Parser = struct {
         
         return ast.Stmt{
             .loop_stmt = .{
+                .label = label,
                 .condition = condition,
                 .iterator = iterator,
                 .body = body,
@@ -1071,6 +1900,7 @@ pub const Parser = struct {
             var arms = std.ArrayList(ast.IsArm){};
             
             while (!self.check(.rbrace) and !self.isAtEnd()) {
+                const pattern_token = self.tokens[self.current];
                 const pattern = try self.parsePattern();
                 
                 // 可选的 if guard
@@ -1086,6 +1916,11 @@ pub const Parser = struct {
                     .pattern = pattern,
                     .guard = guard,
                     .body = body,
+                    .location = .{
+                        .file = pattern_token.file,
+                        .line = pattern_token.line,
+                        .column = pattern_token.column - pattern_token.lexeme.len,
+                    },
                 });
                 
                 // 可选的逗号或换行
@@ -1115,37 +1950,39 @@ pub const Parser = struct {
         // value as Type
         if (self.match(.keyword_as)) {
             const target_type = try self.parseType();
-            
-            const value_ptr = try self.arenaAllocator().create(ast.Expr);
-            value_ptr.* = expr;
-            
-            return ast.Expr{
-                .as_expr = .{
-                    .value = value_ptr,
-                    .target_type = target_type,
-                },
-            };
+            return try self.makeCast(expr, target_type);
         }
         
         return expr;
     }
+    
+    /// value as target_type（🆕 也用于带类型后缀的整数字面量）
+    fn makeCast(self: *Parser, value: ast.Expr, target_type: ast.Type) std.mem.Allocator.Error!ast.Expr {
+        const value_ptr = try self.arenaAllocator().create(ast.Expr);
+        value_ptr.* = value;
+        const resolved_from = try self.arenaAllocator().create(bool);
+        resolved_from.* = false;
+        
+        return ast.Expr{
+            .as_expr = .{
+                .value = value_ptr,
+                .target_type = target_type,
+                .resolved_from = resolved_from,
+            },
+        };
+    }
 
     fn parseLogicalOr(self: *Parser) (std.mem.Allocator.Error || error{UnexpectedToken,ExpectedType,ExpectedPattern,InvalidCharacter,Overflow})!ast.Expr {
         var expr = try self.parseLogicalAnd();
         
         while (self.match(.or_or)) {
+            const op_token = self.tokens[self.current - 1];
             const left = try self.arenaAllocator().create(ast.Expr);
             left.* = expr;
             const right = try self.arenaAllocator().create(ast.Expr);
             right.* = try self.parseLogicalAnd();
             
-            expr = ast.Expr{
-                .binary = .{
-                    .left = left,
-                    .op = .or_op,
-                    .right = right,
-                },
-            };
+            expr = try self.makeBinary(left, .or_op, op_token, right);
         }
         
         return expr;
@@ -1155,23 +1992,38 @@ pub const Parser = struct {
         var expr = try self.parseEquality();
         
         while (self.match(.and_and)) {
+            const op_token = self.tokens[self.current - 1];
             const left = try self.arenaAllocator().create(ast.Expr);
             left.* = expr;
             const right = try self.arenaAllocator().create(ast.Expr);
             right.* = try self.parseEquality();
             
-            expr = ast.Expr{
-                .binary = .{
-                    .left = left,
-                    .op = .and_op,
-                    .right = right,
-                },
-            };
+            expr = try self.makeBinary(left, .and_op, op_token, right);
         }
         
         return expr;
     }
 
+    /// 二元运算表达式（resolved_operator 由类型检查器填写；op_token 为运算符，编译器合成的表达式为 null）
+    fn makeBinary(self: *Parser, left: *ast.Expr, op: ast.BinaryOp, op_token: ?Token, right: *ast.Expr) std.mem.Allocator.Error!ast.Expr {
+        const resolved_operator = try self.arenaAllocator().create(?[]const u8);
+        resolved_operator.* = null;
+        const location: ?ast.SourceLocation = if (op_token) |token| .{
+            .file = token.file,
+            .line = token.line,
+            .column = token.column - token.lexeme.len,
+        } else null;
+        return ast.Expr{
+            .binary = .{
+                .left = left,
+                .op = op,
+                .right = right,
+                .resolved_operator = resolved_operator,
+                .location = location,
+            },
+        };
+    }
+
     fn parseEquality(self: *Parser) (std.mem.Allocator.Error || error{UnexpectedToken,ExpectedType,ExpectedPattern,InvalidCharacter,Overflow})!ast.Expr {
         var expr = try self.parseComparison();
         
@@ -1184,19 +2036,14 @@ pub const Parser = struct {
                 null;
             
             if (op == null) break;
+            const op_token = self.tokens[self.current - 1];
             
             const left = try self.arenaAllocator().create(ast.Expr);
             left.* = expr;
             const right = try self.arenaAllocator().create(ast.Expr);
             right.* = try self.parseComparison();
             
-            expr = ast.Expr{
-                .binary = .{
-                    .left = left,
-                    .op = op.?,
-                    .right = right,
-                },
-            };
+            expr = try self.makeBinary(left, op.?, op_token, right);
         }
         
         return expr;
@@ -1218,19 +2065,14 @@ pub const Parser = struct {
                 null;
             
             if (op == null) break;
+            const op_token = self.tokens[self.current - 1];
             
             const left = try self.arenaAllocator().create(ast.Expr);
             left.* = expr;
             const right = try self.arenaAllocator().create(ast.Expr);
             right.* = try self.parseRange();
             
-            expr = ast.Expr{
-                .binary = .{
-                    .left = left,
-                    .op = op.?,
-                    .right = right,
-                },
-            };
+            expr = try self.makeBinary(left, op.?, op_token, right);
         }
         
         return expr;
@@ -1275,19 +2117,14 @@ pub const Parser = struct {
                 null;
             
             if (op == null) break;
+            const op_token = self.tokens[self.current - 1];
             
             const left = try self.arenaAllocator().create(ast.Expr);
             left.* = expr;
             const right = try self.arenaAllocator().create(ast.Expr);
             right.* = try self.parseFactor();
             
-            expr = ast.Expr{
-                .binary = .{
-                    .left = left,
-                    .op = op.?,
-                    .right = right,
-                },
-            };
+            expr = try self.makeBinary(left, op.?, op_token, right);
         }
         
         return expr;
@@ -1307,19 +2144,14 @@ pub const Parser = struct {
                 null;
             
             if (op == null) break;
+            const op_token = self.tokens[self.current - 1];
             
             const left = try self.arenaAllocator().create(ast.Expr);
             left.* = expr;
             const right = try self.arenaAllocator().create(ast.Expr);
             right.* = try self.parseUnary();
             
-            expr = ast.Expr{
-                .binary = .{
-                    .left = left,
-                    .op = op.?,
-                    .right = right,
-                },
-            };
+            expr = try self.makeBinary(left, op.?, op_token, right);
         }
         
         return expr;
@@ -1329,6 +2161,13 @@ pub const Parser = struct {
         if (self.match(.minus)) {
             const operand = try self.arenaAllocator().create(ast.Expr);
             operand.* = try self.parseUnary();
+            // 🆕 -128i8：负号属于带后缀的字面量（-(128 as i8) 中的 128 超出 i8 的范围）
+            if (operand.* == .as_expr and operand.as_expr.value.* == .int_literal) {
+                const literal = try self.arenaAllocator().create(ast.Expr);
+                literal.* = operand.as_expr.value.*;
+                operand.as_expr.value.* = .{ .unary = .{ .op = .neg, .operand = literal } };
+                return operand.*;
+            }
             return ast.Expr{
                 .unary = .{
                     .op = .neg,
@@ -1357,6 +2196,7 @@ pub const Parser = struct {
         while (true) {
             if (self.match(.lparen)) {
                 // 函数调用
+                const paren = self.tokens[self.current - 1];
                 var args = std.ArrayList(ast.Expr){};
                 
                 while (!self.check(.rparen) and !self.isAtEnd()) {
@@ -1369,12 +2209,22 @@ pub const Parser = struct {
                 
                 const callee = try self.arenaAllocator().create(ast.Expr);
                 callee.* = expr;
+                const resolved_type_args = try self.arenaAllocator().create([]ast.Type);
+                resolved_type_args.* = &[_]ast.Type{};
+                const resolved_into = try self.arenaAllocator().create(bool);
+                resolved_into.* = false;
+                const resolved_receiver = try self.arenaAllocator().create(?[]const u8);
+                resolved_receiver.* = null;
                 
                 expr = ast.Expr{
                     .call = .{
                         .callee = callee,
                         .args = try args.toOwnedSlice(self.arenaAllocator()),
                         .type_args = &[_]ast.Type{},
+                        .resolved_type_args = resolved_type_args,
+                        .resolved_into = resolved_into,
+                        .resolved_receiver = resolved_receiver,
+                        .location = .{ .file = paren.file, .line = paren.line, .column = paren.column - paren.lexeme.len },
                     },
                 };
             } else if (self.match(.dot)) {
@@ -1384,6 +2234,12 @@ pub const Parser = struct {
                     value_ptr.* = expr;
                     
                     expr = ast.Expr{ .await_expr = value_ptr };
+                } else if (expr == .identifier and self.namespaces.contains(expr.identifier) and !self.isLocal(expr.identifier)) {
+                    // 🆕 命名空间访问：m.add -> m__add（m 被局部变量遮蔽时是普通字段访问）
+                    const item = try self.consume(.identifier);
+                    expr = ast.Expr{
+                        .identifier = try ast.namespacedName(self.arenaAllocator(), expr.identifier, item.lexeme),
+                    };
                 } else {
                     // 普通字段访问
                     const field = try self.consume(.identifier);
@@ -1408,10 +2264,14 @@ pub const Parser = struct {
                 const index_ptr = try self.arenaAllocator().create(ast.Expr);
                 index_ptr.* = index_expr;
                 
+                const length = try self.arenaAllocator().create(?usize);
+                length.* = null;
+                
                 expr = ast.Expr{
                     .array_index = .{
                         .array = array_ptr,
                         .index = index_ptr,
+                        .length = length,
                     },
                 };
             } else if (self.match(.question)) {
@@ -1439,8 +2299,21 @@ pub const Parser = struct {
         
         if (self.check(.int_literal)) {
             const token = self.advance();
-            const value = try std.fmt.parseInt(i64, token.lexeme, 10);
-            return ast.Expr{ .int_literal = value };
+            const text = ast.splitIntLiteral(token.lexeme);
+            const value = std.fmt.parseInt(i64, text.digits, 10) catch |err| {
+                // 🆕 超出 i64 的字面量：在字面量位置报告，而不是抛出裸错误
+                if (err == error.Overflow) {
+                    self.reportTokenError(.integer_literal_too_large, token, "integer literal is too large", "the largest integer literal is 9223372036854775807 (i64 max)");
+                }
+                return err;
+            };
+            const literal = ast.Expr{ .int_literal = .{
+                .value = value,
+                .location = .{ .file = token.file, .line = token.line, .column = token.column - token.lexeme.len },
+            } };
+            // 🆕 类型后缀：42u32 即 42 as u32
+            if (text.suffix) |suffix| return try self.makeCast(literal, suffix);
+            return literal;
         }
         
         if (self.check(.float_literal)) {
@@ -1453,6 +2326,12 @@ pub const Parser = struct {
             const token = self.advance();
             const str_content = token.lexeme[1 .. token.lexeme.len - 1];
             
+            // 🆕 string 是 UTF-8：源码中的字符串字面量必须是合法的 UTF-8
+            if (!std.unicode.utf8ValidateSlice(str_content)) {
+                self.reportTokenError(.invalid_utf8_string, token, "invalid UTF-8 in string literal", "strings are UTF-8; use a byte string b\"...\" (type Bytes) for raw data");
+                return error.UnexpectedToken;
+            }
+            
             // 🆕 检查是否包含插值 $ 或 ${}
             if (self.hasInterpolation(str_content)) {
                 return try self.parseStringInterpolation(str_content);
@@ -1469,10 +2348,57 @@ pub const Parser = struct {
             return ast.Expr{ .char_literal = value };
         }
         
+        // 🆕 字节字面量 b'A'
+        if (self.check(.byte_literal)) {
+            const token = self.advance();
+            const bytes = try self.decodeByteEscapes(token, token.lexeme[2 .. token.lexeme.len - 1]);
+            if (bytes.len != 1) {
+                self.reportTokenError(.invalid_byte_literal, token, "byte literal must contain exactly one byte", "use b\"...\" for multiple bytes");
+                return error.InvalidCharacter;
+            }
+            return ast.Expr{ .byte_literal = bytes[0] };
+        }
+        
+        // 🆕 字节串 b"bytes"
+        if (self.check(.byte_string_literal)) {
+            const token = self.advance();
+            const bytes = try self.decodeByteEscapes(token, token.lexeme[2 .. token.lexeme.len - 1]);
+            return ast.Expr{ .byte_string_literal = bytes };
+        }
+        
         if (self.match(.keyword_if)) {
             return try self.parseIfExpr();
         }
         
+        // 🆕 loop 表达式（可带标签）：loop { ... break value; }
+        if (self.match(.keyword_loop)) {
+            return try self.parseLoopExpr(null);
+        }
+        if (self.check(.label)) {
+            const label_token = self.advance();
+            _ = try self.consume(.colon);
+            _ = try self.consume(.keyword_loop);
+            return try self.parseLoopExpr(labelName(label_token));
+        }
+
+        // 🆕 lambda 表达式：|x: i32| -> i32 { ... }（没有参数时写作 ||）
+        if (self.match(.or_or)) {
+            return try self.parseLambda(&[_]ast.Param{});
+        }
+        if (self.match(.pipe)) {
+            var params = std.ArrayList(ast.Param){};
+            while (!self.check(.pipe) and !self.isAtEnd()) {
+                const is_mut = self.match(.keyword_mut);
+                const name = try self.consume(.identifier);
+                _ = try self.consume(.colon);
+                const param_type = try self.parseType();
+                try params.append(self.arenaAllocator(), .{ .name = name.lexeme, .type = param_type, .is_mut = is_mut });
+                if (!self.match(.comma)) break;
+            }
+            _ = try self.consume(.pipe);
+            return try self.parseLambda(try params.toOwnedSlice(self.arenaAllocator()));
+        }
+
         // 🆕 支持 self 关键字作为标识符
         if (self.match(.keyword_self)) {
             return ast.Expr{ .identifier = "self" };
@@ -1513,6 +2439,13 @@ pub const Parser = struct {
         if (self.check(.identifier)) {
             const name = self.advance();
             
+            // 🆕 dispatch<T in [A, B]>(tag) { ... }（dispatch 是上下文关键字）
+            if (std.mem.eql(u8, name.lexeme, "dispatch") and self.check(.lt) and self.current + 2 < self.tokens.len and
+                self.tokens[self.current + 1].type == .identifier and self.tokens[self.current + 2].type == .keyword_in)
+            {
+                return try self.parseDispatch(name);
+            }
+            
             // 🆕 智能判断：检查是否是泛型调用
             // 只有当 < 后面跟着类型名（identifier 或 type_xxx）时，才认为是泛型
             const is_generic = self.check(.lt) and self.isGenericStart();
@@ -1526,6 +2459,19 @@ pub const Parser = struct {
                 }
                 _ = try self.consume(.gt);
                 
+                // 🆕 编译期内置函数：size_of<T>() / align_of<T>()
+                if (ast.Expr.TypeQueryKind.fromName(name.lexeme)) |kind| {
+                    if (self.check(.lparen)) {
+                        _ = self.advance();
+                        _ = try self.consume(.rparen);
+                        if (type_args.items.len != 1) {
+                            self.reportTokenError(.type_query_arity, name, "size_of/align_of take exactly one type argument", "write size_of<T>()");
+                            return error.UnexpectedToken;
+                        }
+                        return ast.Expr{ .type_query = .{ .kind = kind, .type = type_args.items[0] } };
+                    }
+                }
+                
                 // 🆕 检查是否是静态方法调用：Type<T>::method()
                 if (self.match(.double_colon)) {
                     const method_name = try self.consume(.identifier);
@@ -1608,9 +2554,20 @@ pub const Parser = struct {
                 };
             }
             
+            // 🆕 embed_file("path")：编译期读取文件，结果与 b"..." 相同（指针 + 静态长度）
+            if (std.mem.eql(u8, name.lexeme, "embed_file") and self.check(.lparen)) {
+                return try self.parseEmbedFile(name);
+            }
+            
+            // 🆕 __FILE__ / __LINE__ / __FUNCTION__
+            if (ast.Expr.SourceMeta.fromName(name.lexeme)) |meta| {
+                return try self.sourceMetaLiteral(meta, name);
+            }
+            
             return ast.Expr{ .identifier = name.lexeme };
         }
         
+        self.reportTokenError(.expected_expression, self.tokens[self.current], "expected an expression", null);
         return error.UnexpectedToken;
     }
 
@@ -1675,12 +2632,34 @@ pub const Parser = struct {
         };
     }
 
+    /// 🆕 模式，可以是用 | 分隔的多个子模式（或模式）
     fn parsePattern(self: *Parser) (std.mem.Allocator.Error || error{UnexpectedToken,ExpectedPattern,ExpectedType,InvalidCharacter,Overflow})!ast.Pattern {
+        const first = try self.parseSinglePattern();
+        if (!self.check(.pipe)) return first;
+        
+        var alternatives = std.ArrayList(ast.Pattern){};
+        try alternatives.append(self.arenaAllocator(), first);
+        while (self.match(.pipe)) {
+            try alternatives.append(self.arenaAllocator(), try self.parseSinglePattern());
+        }
+        return ast.Pattern{ .alternatives = try alternatives.toOwnedSlice(self.arenaAllocator()) };
+    }
+    
+    fn parseSinglePattern(self: *Parser) (std.mem.Allocator.Error || error{UnexpectedToken,ExpectedPattern,ExpectedType,InvalidCharacter,Overflow})!ast.Pattern {
         // 字面量模式
-        if (self.check(.int_literal) or self.check(.float_literal) or 
-            self.check(.string_literal) or self.check(.char_literal) or
-            self.check(.keyword_true) or self.check(.keyword_false)) {
-            const literal_expr = try self.parsePrimary();
+        if (self.isLiteralStart()) {
+            const literal_expr = try self.parsePatternLiteral();
+            
+            // 🆕 范围模式：1..=5 / 'a'..'z'
+            if (self.check(.dot_dot) or self.check(.dot_dot_eq)) {
+                const inclusive = self.advance().type == .dot_dot_eq;
+                if (!self.isLiteralStart()) {
+                    self.reportTokenError(.expected_pattern, self.tokens[self.current], "expected a literal as the end of the range pattern", null);
+                    return error.ExpectedPattern;
+                }
+                const end_expr = try self.parsePatternLiteral();
+                return ast.Pattern{ .range = .{ .start = literal_expr, .end = end_expr, .inclusive = inclusive } };
+            }
             return ast.Pattern{ .literal = literal_expr };
         }
         
@@ -1700,6 +2679,7 @@ pub const Parser = struct {
                 while (!self.check(.rparen) and !self.isAtEnd()) {
                     const binding = try self.consume(.identifier);
                     try bindings.append(self.arenaAllocator(), binding.lexeme);
+                    try self.locals.append(self.arenaAllocator(), binding.lexeme);
                     if (!self.match(.comma)) break;
                 }
                 
@@ -1713,12 +2693,27 @@ pub const Parser = struct {
                 };
             }
             
+            try self.locals.append(self.arenaAllocator(), name.lexeme);
             return ast.Pattern{ .identifier = name.lexeme };
         }
         
+        self.reportTokenError(.expected_pattern, self.tokens[self.current], "expected a pattern", "a pattern is a literal, a range, `_`, a variable name or a variant such as `Some(x)`");
         return error.ExpectedPattern;
     }
 
+    /// 🆕 模式中的字面量：负号直接折叠进数字字面量（-10..=-1、-5i8）
+    fn parsePatternLiteral(self: *Parser) (std.mem.Allocator.Error || error{UnexpectedToken,ExpectedPattern,ExpectedType,InvalidCharacter,Overflow})!ast.Expr {
+        const negative = self.match(.minus);
+        var literal = try self.parsePrimary();
+        if (negative) switch (literal) {
+            .int_literal => |*literal| literal.value = -literal.value,
+            .float_literal => |*value| value.* = -value.*,
+            .as_expr => |as_expr| as_expr.value.int_literal.value = -as_expr.value.int_literal.value,  // 带类型后缀
+            else => unreachable,  // isLiteralStart 只允许负号后面是数字
+        };
+        return literal;
+    }
+
     fn match(self: *Parser, token_type: TokenType) bool {
         if (self.check(token_type)) {
             _ = self.advance();
@@ -1736,6 +2731,18 @@ pub const Parser = struct {
         return self.tokens[self.current].type == token_type;
     }
 
+    /// 当前记号是否开始一个字面量（模式中的 Literal，数字可以带负号）
+    fn isLiteralStart(self: *Parser) bool {
+        if (self.check(.minus)) {
+            const next = self.tokens[self.current + 1].type;
+            return next == .int_literal or next == .float_literal;
+        }
+        return self.check(.int_literal) or self.check(.float_literal) or
+            self.check(.string_literal) or self.check(.char_literal) or
+            self.check(.byte_literal) or
+            self.check(.keyword_true) or self.check(.keyword_false);
+    }
+    
     fn advance(self: *Parser) Token {
         if (!self.isAtEnd()) {
             self.current += 1;
@@ -1758,6 +2765,11 @@ pub const Parser = struct {
         
         const next_token = self.tokens[next_idx];
         
+        // 🆕 size_of<T>() / align_of<T>() 的实参总是类型（包括泛型参数 T）
+        if (self.current > 0 and ast.Expr.TypeQueryKind.fromName(self.tokens[self.current - 1].lexeme) != null) {
+            return true;
+        }
+        
         return switch (next_token.type) {
             // 策略 1: 内置类型关键字 → 100% 确定是泛型
             .type_i8, .type_i16, .type_i32, .type_i64, .type_i128,
@@ -1770,8 +2782,8 @@ pub const Parser = struct {
             
             // 策略 3: identifier → 查询类型表（上下文感知！）
             .identifier => blk: {
-                // 🆕 查询第一遍收集的类型表
-                if (self.known_types.contains(next_token.lexeme)) {
+                // 🆕 查询第一遍收集的类型表（以及 dispatch 体中的类型参数）
+                if (self.known_types.contains(next_token.lexeme) or self.dispatchBinding(next_token.lexeme) != null) {
                     break :blk true;  // 确定是类型 → 是泛型
                 }
                 
@@ -1792,6 +2804,258 @@ pub const Parser = struct {
         return first_char >= 'A' and first_char <= 'Z';
     }
     
+    /// 🆕 源码位置常量展开为字面量：路径按字符串字面量的写法转义
+    fn sourceMetaLiteral(self: *Parser, meta: ast.Expr.SourceMeta, token: Token) !ast.Expr {
+        // 🆕 @track_caller 函数：调用处的位置由隐藏参数传入（见 track_caller.zig）
+        if (self.in_track_caller) switch (meta) {
+            .line => return ast.Expr{ .identifier = ast.CALLER_LINE_PARAM },
+            .file => return ast.Expr{ .identifier = ast.CALLER_FILE_PARAM },
+            .function => {},
+        };
+        switch (meta) {
+            .line => return ast.Expr{ .int_literal = .{ .value = @intCast(token.line) } },
+            .function => {
+                const name = self.current_function orelse {
+                    self.reportTokenError(.function_name_outside_function, token, "__FUNCTION__ can only be used inside a function body", null);
+                    return error.UnexpectedToken;
+                };
+                return ast.Expr{ .string_literal = name };
+            },
+            .file => return ast.Expr{ .string_literal = try ast.fileLiteral(self.arenaAllocator(), token.file) },
+        }
+    }
+    
+    /// 🆕 dispatch<T in [A, B, ...]>(tag) { 体 }：在封闭的类型集合上按运行时的 tag 静态分派
+    ///
+    /// tag 是类型在列表中的下标（0 起）。体按列表中的每个类型各解析一次（T 替换为该类型），
+    /// 所以对每个类型分别做类型检查和代码生成；整个表达式展开为
+    ///   { let __dispatch_tag = tag; if __dispatch_tag == 0 { 体[T = A] } else if ... else { panic(...) } }
+    /// 体的值对每个类型必须是同一个类型。tag 超出列表时 panic（ast.DISPATCH_TAG_PANIC）
+    /// 解析不依赖 T，解析错误只在第一次解析时报告；每个分支记录 "T = A"，类型检查的诊断注明是哪个类型
+    fn parseDispatch(self: *Parser, keyword: Token) !ast.Expr {
+        _ = try self.consume(.lt);
+        const param = try self.consume(.identifier);
+        _ = try self.consume(.keyword_in);
+        _ = try self.consume(.lbracket);
+        var types = std.ArrayList(ast.Type){};
+        var instances = std.ArrayList([]const u8){};
+        while (!self.check(.rbracket) and !self.isAtEnd()) {
+            const type_start = self.current;
+            try types.append(self.arenaAllocator(), try self.parseType());
+            var instance = std.ArrayList(u8){};
+            try instance.writer(self.arenaAllocator()).print("{s} = ", .{param.lexeme});
+            for (self.tokens[type_start..self.current]) |token| try instance.appendSlice(self.arenaAllocator(), token.lexeme);
+            try instances.append(self.arenaAllocator(), instance.items);
+            if (!self.match(.comma)) break;
+        }
+        _ = try self.consume(.rbracket);
+        _ = try self.consume(.gt);
+        if (types.items.len == 0) {
+            self.reportTokenError(.empty_dispatch, keyword, "dispatch needs at least one type", "list the types the tag can select: dispatch<T in [i32, string]>(tag) { ... }");
+            return error.UnexpectedToken;
+        }
+        
+        _ = try self.consume(.lparen);
+        const tag = try self.parseExpr();
+        _ = try self.consume(.rparen);
+        
+        // 最后一个分支：tag 不在列表中
+        var chain = try self.arenaAllocator().create(ast.Expr);
+        chain.* = try self.panicCall(ast.DISPATCH_TAG_PANIC);
+        
+        // 体按每个类型重新解析一次，从最后一个类型开始向前组成 if / else if 链
+        const body_start = self.current;
+        var body_end = body_start;
+        const outer_reparse = self.dispatch_reparse;
+        defer self.dispatch_reparse = outer_reparse;
+        var i = types.items.len;
+        while (i > 0) {
+            i -= 1;
+            self.current = body_start;
+            self.dispatch_reparse = outer_reparse or i + 1 < types.items.len;
+            try self.dispatch_bindings.append(self.arenaAllocator(), .{ .name = param.lexeme, .type = types.items[i] });
+            defer _ = self.dispatch_bindings.pop();
+            _ = try self.consume(.lbrace);
+            const stmts = try self.parseStmtList();
+            _ = try self.consume(.rbrace);
+            body_end = self.current;
+            
+            const left = try self.arenaAllocator().create(ast.Expr);
+            left.* = ast.Expr{ .identifier = DISPATCH_TAG };
+            const right = try self.arenaAllocator().create(ast.Expr);
+            right.* = ast.Expr{ .int_literal = .{ .value = @intCast(i) } };
+            const condition = try self.arenaAllocator().create(ast.Expr);
+            condition.* = try self.makeBinary(left, .eq, null, right);
+            const then_branch = try self.arenaAllocator().create(ast.Expr);
+            then_branch.* = ast.Expr{ .block = stmts };
+            
+            const branch = try self.arenaAllocator().create(ast.Expr);
+            branch.* = ast.Expr{ .if_expr = .{ .condition = condition, .then_branch = then_branch, .else_branch = chain, .dispatch_instance = instances.items[i] } };
+            chain = branch;
+        }
+        self.current = body_end;
+        
+        const stmts = try self.arenaAllocator().alloc(ast.Stmt, 2);
+        stmts[0] = ast.Stmt{ .let_decl = .{ .name = DISPATCH_TAG, .is_mut = false, .type = null, .init = tag } };
+        stmts[1] = ast.Stmt{ .expr = chain.* };
+        return ast.Expr{ .block = stmts };
+    }
+    
+    /// 🆕 dispatch 展开后保存 tag 的局部变量
+    const DISPATCH_TAG = "__dispatch_tag";
+    
+    fn dispatchBinding(self: *Parser, name: []const u8) ?ast.Type {
+        var i = self.dispatch_bindings.items.len;
+        while (i > 0) {
+            i -= 1;
+            const binding = self.dispatch_bindings.items[i];
+            if (std.mem.eql(u8, binding.name, name)) return binding.type;
+        }
+        return null;
+    }
+    
+    /// 🆕 调用 prelude 的 panic(message)
+    fn panicCall(self: *Parser, message: []const u8) !ast.Expr {
+        const callee = try self.arenaAllocator().create(ast.Expr);
+        callee.* = ast.Expr{ .identifier = "panic" };
+        const args = try self.arenaAllocator().alloc(ast.Expr, 1);
+        args[0] = ast.Expr{ .string_literal = message };
+        const resolved_type_args = try self.arenaAllocator().create([]ast.Type);
+        resolved_type_args.* = &[_]ast.Type{};
+        const resolved_into = try self.arenaAllocator().create(bool);
+        resolved_into.* = false;
+        const resolved_receiver = try self.arenaAllocator().create(?[]const u8);
+        resolved_receiver.* = null;
+        return ast.Expr{
+            .call = .{
+                .callee = callee,
+                .args = args,
+                .type_args = &[_]ast.Type{},
+                .resolved_type_args = resolved_type_args,
+                .resolved_into = resolved_into,
+                .resolved_receiver = resolved_receiver,
+            },
+        };
+    }
+    
+    /// 🆕 embed_file 最大文件大小
+    const EMBED_FILE_LIMIT = 16 * 1024 * 1024;
+    
+    /// 🆕 embed_file("path")：路径相对于当前源文件所在目录
+    fn parseEmbedFile(self: *Parser, name: Token) !ast.Expr {
+        _ = try self.consume(.lparen);
+        const path_token = try self.consume(.string_literal);
+        _ = try self.consume(.rparen);
+        
+        const rel_path = path_token.lexeme[1 .. path_token.lexeme.len - 1];
+        const base_dir = std.fs.path.dirname(name.file.path()) orelse ".";
+        const full_path = if (std.fs.path.isAbsolute(rel_path))
+            rel_path
+        else
+            try std.fs.path.join(self.arenaAllocator(), &[_][]const u8{ base_dir, rel_path });
+        
+        const contents = std.fs.cwd().readFileAlloc(self.arenaAllocator(), full_path, EMBED_FILE_LIMIT) catch |err| {
+            const message = switch (err) {
+                error.FileNotFound => "embed_file: file not found",
+                error.FileTooBig => "embed_file: file exceeds the 16 MiB limit",
+                else => "embed_file: cannot read file",
+            };
+            const help = try std.fmt.allocPrint(self.arenaAllocator(), "path resolved to '{s}' (relative to the source file)", .{full_path});
+            self.reportTokenError(.embed_file_failed, path_token, message, help);
+            return error.UnexpectedToken;
+        };
+        
+        return ast.Expr{ .byte_string_literal = contents };
+    }
+    
+    /// 🆕 解码字节字面量中的转义：\n \r \t \0 \\ \' \" \xNN
+    /// 字节字面量只允许 ASCII，其余字节必须写成 \xNN
+    fn decodeByteEscapes(self: *Parser, token: Token, content: []const u8) ![]const u8 {
+        var bytes = std.ArrayList(u8){};
+        var i: usize = 0;
+        while (i < content.len) : (i += 1) {
+            const c = content[i];
+            if (c >= 0x80) {
+                self.reportTokenError(.invalid_byte_literal, token, "non-ASCII character in byte literal", "write the byte as an escape like \\xFF");
+                return error.InvalidCharacter;
+            }
+            if (c != '\\') {
+                try bytes.append(self.arenaAllocator(), c);
+                continue;
+            }
+            
+            i += 1;
+            if (i >= content.len) {
+                self.reportTokenError(.invalid_byte_literal, token, "unterminated escape sequence in byte literal", null);
+                return error.InvalidCharacter;
+            }
+            const byte: u8 = switch (content[i]) {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => 0,
+                '\\' => '\\',
+                '\'' => '\'',
+                '"' => '"',
+                'x' => blk: {
+                    if (i + 2 >= content.len) {
+                        self.reportTokenError(.invalid_byte_literal, token, "\\x escape needs two hex digits", null);
+                        return error.InvalidCharacter;
+                    }
+                    const value = std.fmt.parseInt(u8, content[i + 1 .. i + 3], 16) catch {
+                        self.reportTokenError(.invalid_byte_literal, token, "invalid hex digits in \\x escape", null);
+                        return error.InvalidCharacter;
+                    };
+                    i += 2;
+                    break :blk value;
+                },
+                else => {
+                    self.reportTokenError(.invalid_byte_literal, token, "unknown escape sequence in byte literal", "supported: \\n \\r \\t \\0 \\\\ \\' \\\" \\xNN");
+                    return error.InvalidCharacter;
+                },
+            };
+            try bytes.append(self.arenaAllocator(), byte);
+        }
+        return bytes.toOwnedSlice(self.arenaAllocator());
+    }
+    
+    /// 🆕 解析约束列表：Num + Ord（只支持编译器内置的约束）
+    /// trait_bounds 不为 null 时（约束泛型类型的参数）还接受用户 trait，是否是 trait 由类型检查器验证
+    fn parseBoundList(self: *Parser, param: []const u8, bounds: *std.ArrayList(ast.TypeBound), trait_bounds: ?*std.ArrayList(ast.TraitBound)) !void {
+        while (true) {
+            const bound_token = try self.consume(.identifier);
+            if (ast.BuiltinBound.fromName(bound_token.lexeme)) |bound| {
+                try bounds.append(self.arenaAllocator(), .{ .param = param, .bound = bound });
+            } else if (trait_bounds) |list| {
+                try list.append(self.arenaAllocator(), .{ .param = param, .trait_name = bound_token.lexeme });
+            } else {
+                self.reportTokenError(.unknown_bound, bound_token, "unknown bound", "available bounds: Num, Ord, Eq, Display");
+                return error.UnexpectedToken;
+            }
+            if (!self.match(.plus)) break;
+        }
+    }
+    
+    fn containsName(names: []const []const u8, name: []const u8) bool {
+        for (names) |n| {
+            if (std.mem.eql(u8, n, name)) return true;
+        }
+        return false;
+    }
+    
+    /// 🆕 在 token 位置报告解析错误
+    fn reportTokenError(self: *Parser, code: codes.Code, token: Token, message: []const u8, help: ?[]const u8) void {
+        if (self.dispatch_reparse) return;
+        std.debug.print("\x1b[1;31m{s}[{s}]\x1b[0m: {s}\n", .{ messages.text(.level_error), code.id(), message });
+        std.debug.print("   \x1b[1;36m--> {s}:{d}:{d}\x1b[0m\n", .{ token.file.path(), token.line, token.column });
+        std.debug.print("   \x1b[1;36m|\x1b[0m\n", .{});
+        std.debug.print(" \x1b[1;36m{d:>3} |\x1b[0m {s}\n", .{ token.line, token.lexeme });
+        if (help) |h| {
+            std.debug.print("   \x1b[1;32m= {s}\x1b[0m: {s}\n", .{ messages.text(.level_help), h });
+        }
+        std.debug.print("\n", .{});
+    }
+    
     // 🆕 检查字符串是否包含插值
     fn hasInterpolation(self: *Parser, str: []const u8) bool {
         _ = self;
@@ -1892,12 +3156,15 @@ pub const Parser = struct {
         }
         
         const current_token = self.tokens[self.current];
-        std.debug.print("语法错误: 期望 {any}, 但得�?{any} 在第 {d} 行第 {d} 列\n", .{
-            token_type,
-            current_token.type,
-            current_token.line,
-            current_token.column,
-        });
+        if (!self.dispatch_reparse) {
+            std.debug.print("\x1b[1;31m{s}[{s}]\x1b[0m: ", .{ messages.text(.level_error), codes.Code.unexpected_token.id() });
+            messages.print(.unexpected_token, .{
+                token_type,
+                current_token.type,
+                current_token.line,
+                current_token.column,
+            });
+        }
         
         return error.UnexpectedToken;
     }
@@ -0,0 +1,878 @@
+//! Query - 面向编辑器的源码查询
+//!
+//! pawc query highlight <file>
+//!   输出语义高亮 token（LSP semantic tokens 编码）：
+//!   每个 token 5 个整数 [deltaLine, deltaStartChar, length, tokenType, tokenModifiers]
+//!   行列号从 0 开始（列按字节计算），相对上一个 token 编码
+//!
+//! 分类来自语法树和导入解析结果，而不是正则：
+//!   - 函数 / 方法名、类型名、泛型参数、枚举变体都按声明收集
+//!   - 导入的项通过 ModuleLoader 解析出真实的种类
+//!
+//! pawc query symbols <file>
+//!   输出文档大纲（LSP DocumentSymbol[]）：函数（含签名）、类型及其
+//!   字段 / 变体 / 方法、trait 及其方法签名、impl 块及其方法、常量，每项带完整范围和名称范围
+//!   （pawc lsp 的 textDocument/documentSymbol 返回同样的结果）
+//!
+//! pawc query impls <Trait> <file>
+//!   列出项目（文件及其导入）中实现了 trait 的类型和声明位置（行列号从 1 开始）
+//!
+//! pawc query missing-impls <Trait> <Type,...> <file>
+//!   逐个检查给定的类型，列出缺少的方法和签名不一致的方法
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const api = @import("api.zig");
+const Token = @import("token.zig").Token;
+const TokenType = @import("token.zig").TokenType;
+const ModuleLoader = @import("module.zig").ModuleLoader;
+const SourceFile = @import("source_map.zig").SourceFile;
+const typechecker = @import("typechecker.zig");
+const TypeChecker = typechecker.TypeChecker;
+
+// ============================================================================
+// 语义 token 类型（顺序即 legend 中的下标）
+// ============================================================================
+
+pub const SemanticKind = enum(u32) {
+    keyword,
+    type,
+    function,
+    type_parameter,
+    constant,
+    string,
+    comment,
+    number,
+
+    pub fn legendName(self: SemanticKind) []const u8 {
+        return switch (self) {
+            .keyword => "keyword",
+            .type => "type",
+            .function => "function",
+            .type_parameter => "typeParameter",
+            .constant => "constant",
+            .string => "string",
+            .comment => "comment",
+            .number => "number",
+        };
+    }
+};
+
+/// 修饰符位
+pub const MODIFIER_DECLARATION: u32 = 1 << 0;
+const MODIFIER_NAMES = [_][]const u8{"declaration"};
+
+pub const SemanticToken = struct {
+    line: usize,    // 从 0 开始
+    column: usize,  // 从 0 开始
+    length: usize,
+    kind: SemanticKind,
+    modifiers: u32,
+};
+
+// ============================================================================
+// 名称分类表（从语法树和导入解析结果构建）
+// ============================================================================
+
+const NameKind = enum { function, type, type_parameter, constant };
+
+const NameTable = struct {
+    names: std.StringHashMap(NameKind),
+
+    fn init(allocator: std.mem.Allocator) NameTable {
+        return NameTable{ .names = std.StringHashMap(NameKind).init(allocator) };
+    }
+
+    fn deinit(self: *NameTable) void {
+        self.names.deinit();
+    }
+
+    fn put(self: *NameTable, name: []const u8, kind: NameKind) !void {
+        // 函数 / 类型 / 变体优先于泛型参数（T 可能同时是某个类型名）
+        if (kind == .type_parameter and self.names.contains(name)) return;
+        try self.names.put(name, kind);
+    }
+
+    fn addTypeParams(self: *NameTable, params: []const []const u8) !void {
+        for (params) |param| try self.put(param, .type_parameter);
+    }
+
+    fn addFunction(self: *NameTable, func: ast.FunctionDecl) !void {
+        try self.put(func.name, .function);
+        try self.addTypeParams(func.type_params);
+    }
+
+    fn addDecl(self: *NameTable, decl: ast.TopLevelDecl) !void {
+        switch (decl) {
+            .function => |func| try self.addFunction(func),
+            .type_decl => |td| {
+                try self.put(td.name, .type);
+                try self.addTypeParams(td.type_params);
+                switch (td.kind) {
+                    .struct_type => |st| for (st.methods) |m| try self.addFunction(m),
+                    .enum_type => |et| {
+                        for (et.variants) |v| try self.put(v.name, .constant);
+                        for (et.methods) |m| try self.addFunction(m);
+                    },
+                    .trait_type => |tt| for (tt.methods) |m| try self.put(m.name, .function),
+                    .alias => {},
+                }
+            },
+            .struct_decl => |sd| {
+                try self.put(sd.name, .type);
+                try self.addTypeParams(sd.type_params);
+                for (sd.methods) |m| try self.addFunction(m);
+            },
+            .enum_decl => |ed| {
+                try self.put(ed.name, .type);
+                try self.addTypeParams(ed.type_params);
+                for (ed.variants) |v| try self.put(v.name, .constant);
+                for (ed.methods) |m| try self.addFunction(m);
+            },
+            .trait_decl => |td| {
+                try self.put(td.name, .type);
+                try self.addTypeParams(td.type_params);
+                for (td.methods) |m| try self.put(m.name, .function);
+            },
+            .impl_decl => |impl| {
+                try self.put(impl.trait_name, .type);
+                for (impl.methods) |m| try self.addFunction(m);
+            },
+            .import_decl => {},
+            .const_decl => |cd| try self.put(cd.name, .constant),
+        }
+    }
+};
+
+// ============================================================================
+// highlight
+// ============================================================================
+
+/// 计算文件的语义 token（按位置排序）
+pub fn highlight(
+    allocator: std.mem.Allocator,
+    file: *SourceFile,
+    tokens: []const Token,
+    program: ast.Program,
+    loader: *ModuleLoader,
+) ![]SemanticToken {
+    var table = NameTable.init(allocator);
+    defer table.deinit();
+
+    for (program.declarations) |decl| {
+        try table.addDecl(decl);
+    }
+
+    // 导入项：按解析出的声明种类分类（解析失败的项保持未分类）
+    for (program.declarations) |decl| {
+        if (decl != .import_decl) continue;
+        const import_decl = decl.import_decl;
+        switch (import_decl.items) {
+            .single => |item| {
+                const resolved = loader.getImportedItem(import_decl.module_path, item) catch continue;
+                try table.addDecl(resolved);
+            },
+            .multiple => |items| for (items) |item| {
+                const resolved = loader.getImportedItem(import_decl.module_path, item) catch continue;
+                try table.addDecl(resolved);
+            },
+            .namespace => {},
+        }
+    }
+
+    var result = std.ArrayList(SemanticToken){};
+    errdefer result.deinit(allocator);
+
+    const source = file.contents;
+    var gap_start: usize = 0;
+
+    for (tokens, 0..) |token, i| {
+        if (token.type == .eof) break;
+        const offset = offsetIn(source, token.lexeme) orelse continue;
+
+        // token 之间只可能是空白和注释
+        try collectComments(allocator, &result, file, source[gap_start..offset], gap_start);
+        gap_start = offset + token.lexeme.len;
+
+        const prev: ?TokenType = if (i > 0) tokens[i - 1].type else null;
+        const next: ?TokenType = if (i + 1 < tokens.len) tokens[i + 1].type else null;
+        const classified = classify(token, prev, next, &table) orelse continue;
+
+        try appendRange(allocator, &result, file, offset, token.lexeme.len, classified.kind, classified.modifiers);
+    }
+    try collectComments(allocator, &result, file, source[gap_start..], gap_start);
+
+    return result.toOwnedSlice(allocator);
+}
+
+const Classified = struct { kind: SemanticKind, modifiers: u32 };
+
+fn classify(token: Token, prev: ?TokenType, next: ?TokenType, table: *NameTable) ?Classified {
+    const kind: SemanticKind = switch (token.type) {
+        .keyword_fn, .keyword_let, .keyword_const, .keyword_type, .keyword_import, .keyword_pub,
+        .keyword_if, .keyword_else, .keyword_loop, .keyword_break, .keyword_continue, .keyword_return,
+        .keyword_is, .keyword_as, .keyword_async, .keyword_await, .keyword_self,
+        .keyword_mut, .keyword_in, .keyword_extern => .keyword,
+        .keyword_true, .keyword_false => .constant,
+        .keyword_Self,
+        .type_i8, .type_i16, .type_i32, .type_i64, .type_i128,
+        .type_u8, .type_u16, .type_u32, .type_u64, .type_u128,
+        .type_f32, .type_f64, .type_bool, .type_char, .type_string, .type_void => .type,
+        .string_literal, .char_literal, .byte_literal, .byte_string_literal => .string,
+        .int_literal, .float_literal => .number,
+        .identifier => return classifyIdentifier(token.lexeme, prev, next, table),
+        else => return null,
+    };
+    return Classified{ .kind = kind, .modifiers = 0 };
+}
+
+fn classifyIdentifier(name: []const u8, prev: ?TokenType, next: ?TokenType, table: *NameTable) ?Classified {
+    // 声明位置
+    if (prev == .keyword_fn) return Classified{ .kind = .function, .modifiers = MODIFIER_DECLARATION };
+    if (prev == .keyword_type) return Classified{ .kind = .type, .modifiers = MODIFIER_DECLARATION };
+
+    if (table.names.get(name)) |kind| {
+        return Classified{
+            .kind = switch (kind) {
+                .function => .function,
+                .type => .type,
+                .type_parameter => .type_parameter,
+                .constant => .constant,
+            },
+            .modifiers = 0,
+        };
+    }
+
+    // 未解析的方法调用：obj.method(...)
+    if (prev == .dot and next == .lparen) return Classified{ .kind = .function, .modifiers = 0 };
+    return null;
+}
+
+/// token 的 lexeme 是源码的切片，由此得到字节偏移
+fn offsetIn(source: []const u8, lexeme: []const u8) ?usize {
+    const base = @intFromPtr(source.ptr);
+    const ptr = @intFromPtr(lexeme.ptr);
+    if (ptr < base or ptr + lexeme.len > base + source.len) return null;
+    return ptr - base;
+}
+
+/// 在 token 间隙中查找 // 和 /* */ 注释
+fn collectComments(
+    allocator: std.mem.Allocator,
+    result: *std.ArrayList(SemanticToken),
+    file: *SourceFile,
+    gap: []const u8,
+    gap_offset: usize,
+) !void {
+    var i: usize = 0;
+    while (i + 1 < gap.len) {
+        if (gap[i] == '/' and gap[i + 1] == '/') {
+            const end = std.mem.indexOfScalarPos(u8, gap, i, '\n') orelse gap.len;
+            try appendRange(allocator, result, file, gap_offset + i, end - i, .comment, 0);
+            i = end;
+        } else if (gap[i] == '/' and gap[i + 1] == '*') {
+            const close = std.mem.indexOfPos(u8, gap, i + 2, "*/");
+            const end = if (close) |c| c + 2 else gap.len;
+            try appendRange(allocator, result, file, gap_offset + i, end - i, .comment, 0);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// 添加一个范围；跨行的范围（块注释、多行字符串）按行拆分，LSP 不允许 token 跨行
+fn appendRange(
+    allocator: std.mem.Allocator,
+    result: *std.ArrayList(SemanticToken),
+    file: *SourceFile,
+    offset: usize,
+    length: usize,
+    kind: SemanticKind,
+    modifiers: u32,
+) !void {
+    var start = offset;
+    const end = offset + length;
+    while (start < end) {
+        const pos = try file.lookupLineCol(allocator, start);
+        const line_end = std.mem.indexOfScalarPos(u8, file.contents, start, '\n') orelse file.contents.len;
+        const piece_end = @min(end, line_end);
+        if (piece_end > start) {
+            try result.append(allocator, SemanticToken{
+                .line = pos.line - 1,
+                .column = pos.column - 1,
+                .length = piece_end - start,
+                .kind = kind,
+                .modifiers = modifiers,
+            });
+        }
+        start = piece_end + 1;
+    }
+}
+
+/// 编码为 LSP semantic tokens JSON（含 legend）
+pub fn encodeJson(allocator: std.mem.Allocator, tokens: []const SemanticToken) ![]const u8 {
+    var buf = std.ArrayList(u8){};
+    errdefer buf.deinit(allocator);
+
+    try buf.appendSlice(allocator, "{\"legend\":{\"tokenTypes\":[");
+    inline for (@typeInfo(SemanticKind).@"enum".fields, 0..) |field, i| {
+        if (i > 0) try buf.appendSlice(allocator, ",");
+        const kind: SemanticKind = @enumFromInt(field.value);
+        try buf.writer(allocator).print("\"{s}\"", .{kind.legendName()});
+    }
+    try buf.appendSlice(allocator, "],\"tokenModifiers\":[");
+    for (MODIFIER_NAMES, 0..) |name, i| {
+        if (i > 0) try buf.appendSlice(allocator, ",");
+        try buf.writer(allocator).print("\"{s}\"", .{name});
+    }
+    try buf.appendSlice(allocator, "]},\"data\":[");
+
+    var prev_line: usize = 0;
+    var prev_col: usize = 0;
+    for (tokens, 0..) |token, i| {
+        const delta_line = token.line - prev_line;
+        const delta_col = if (delta_line == 0) token.column - prev_col else token.column;
+        if (i > 0) try buf.appendSlice(allocator, ",");
+        try buf.writer(allocator).print("{d},{d},{d},{d},{d}", .{
+            delta_line, delta_col, token.length, @intFromEnum(token.kind), token.modifiers,
+        });
+        prev_line = token.line;
+        prev_col = token.column;
+    }
+    try buf.appendSlice(allocator, "]}\n");
+
+    return buf.toOwnedSlice(allocator);
+}
+
+// ============================================================================
+// symbols - 文档大纲（LSP DocumentSymbol）
+// ============================================================================
+
+/// LSP SymbolKind 编号
+pub const SymbolKind = enum(u32) {
+    method = 6,
+    field = 8,
+    @"enum" = 10,
+    interface = 11,
+    function = 12,
+    constant = 14,
+    object = 19,  // 🆕 impl 块（与 rust-analyzer 一致）
+    struct_ = 23,
+    enum_member = 22,
+    type_parameter = 26,  // 🆕 类型别名（LSP 没有别名种类，与 rust-analyzer 一致）
+};
+
+pub const Position = struct {
+    line: usize,       // 从 0 开始
+    character: usize,  // 从 0 开始
+};
+
+pub const Range = struct {
+    start: Position,
+    end: Position,
+};
+
+pub const DocumentSymbol = struct {
+    name: []const u8,
+    detail: []const u8,
+    kind: SymbolKind,
+    range: Range,             // 整个声明
+    selection_range: Range,   // 名称
+    children: []DocumentSymbol,
+};
+
+/// 在 token 序列中定位声明的工具
+const DeclLocator = struct {
+    allocator: std.mem.Allocator,
+    file: *SourceFile,
+    tokens: []const Token,
+
+    /// 从 start 开始查找 `<keyword> name`，返回关键字的下标
+    fn findKeyword(self: *DeclLocator, start: usize, end: usize, keyword: TokenType, name: []const u8) ?usize {
+        var i = start;
+        while (i + 1 < end) : (i += 1) {
+            if (self.tokens[i].type == keyword and
+                self.tokens[i + 1].type == .identifier and
+                std.mem.eql(u8, self.tokens[i + 1].lexeme, name)) return i;
+        }
+        return null;
+    }
+
+    /// 🆕 从 start 开始查找顶层（花括号外）的 `<任意 token> name`，返回 name 前一个 token 的下标
+    fn findNamed(self: *DeclLocator, start: usize, end: usize, name: []const u8) ?usize {
+        var depth: usize = 0;
+        var i = start;
+        while (i + 1 < end) : (i += 1) {
+            switch (self.tokens[i].type) {
+                .lbrace => depth += 1,
+                .rbrace => depth -|= 1,
+                else => {},
+            }
+            if (depth == 0 and self.tokens[i + 1].type == .identifier and
+                std.mem.eql(u8, self.tokens[i + 1].lexeme, name)) return i;
+        }
+        return null;
+    }
+
+    /// 🆕 从 start 开始查找顶层的上下文关键字（impl），返回它的下标
+    fn findContextual(self: *DeclLocator, start: usize, end: usize, word: []const u8) ?usize {
+        var depth: usize = 0;
+        var i = start;
+        while (i < end) : (i += 1) {
+            switch (self.tokens[i].type) {
+                .lbrace => depth += 1,
+                .rbrace => depth -|= 1,
+                .identifier => if (depth == 0 and std.mem.eql(u8, self.tokens[i].lexeme, word)) return i,
+                else => {},
+            }
+        }
+        return null;
+    }
+
+    /// 在 [start, end) 的第一层花括号内查找名为 name 的标识符（字段、变体）
+    fn findMember(self: *DeclLocator, start: usize, end: usize, name: []const u8) ?usize {
+        var depth: usize = 0;
+        var i = start;
+        while (i < end) : (i += 1) {
+            switch (self.tokens[i].type) {
+                .lbrace => depth += 1,
+                .rbrace => depth -|= 1,
+                .identifier => if (depth == 1 and std.mem.eql(u8, self.tokens[i].lexeme, name)) return i,
+                else => {},
+            }
+        }
+        return null;
+    }
+
+    /// 声明结束位置：匹配的 `}`，或在遇到 `{` 之前的 `;`（trait 方法签名）
+    fn declEnd(self: *DeclLocator, start: usize, end: usize) usize {
+        var depth: usize = 0;
+        var i = start;
+        while (i < end) : (i += 1) {
+            switch (self.tokens[i].type) {
+                .lbrace => depth += 1,
+                .rbrace => {
+                    depth -|= 1;
+                    if (depth == 0) return i;
+                },
+                .semicolon => if (depth == 0) return i,
+                else => {},
+            }
+        }
+        return end - 1;
+    }
+
+    fn position(self: *DeclLocator, token: Token, at_end: bool) !Position {
+        const offset = offsetIn(self.file.contents, token.lexeme) orelse
+            return Position{ .line = token.line -| 1, .character = token.column -| (token.lexeme.len + 1) };
+        const pos = try self.file.lookupLineCol(self.allocator, if (at_end) offset + token.lexeme.len else offset);
+        return Position{ .line = pos.line - 1, .character = pos.column - 1 };
+    }
+
+    fn range(self: *DeclLocator, first: usize, last: usize) !Range {
+        return Range{
+            .start = try self.position(self.tokens[first], false),
+            .end = try self.position(self.tokens[last], true),
+        };
+    }
+
+    /// 声明的起始 token（包含前面的 pub）
+    fn declStart(self: *DeclLocator, keyword_index: usize) usize {
+        if (keyword_index > 0 and self.tokens[keyword_index - 1].type == .keyword_pub) return keyword_index - 1;
+        return keyword_index;
+    }
+};
+
+/// 构建文档大纲；返回的数据全部分配在 allocator 上（建议使用 arena）
+pub fn documentSymbols(
+    allocator: std.mem.Allocator,
+    file: *SourceFile,
+    tokens: []const Token,
+    program: ast.Program,
+) ![]DocumentSymbol {
+    var locator = DeclLocator{ .allocator = allocator, .file = file, .tokens = tokens };
+    var symbols = std.ArrayList(DocumentSymbol){};
+    var cursor: usize = 0;
+
+    for (program.declarations) |decl| {
+        switch (decl) {
+            .function => |func| {
+                const kw = locator.findKeyword(cursor, tokens.len, .keyword_fn, func.name) orelse continue;
+                const last = locator.declEnd(kw, tokens.len);
+                try symbols.append(allocator, try functionSymbol(&locator, func, .function, kw, last));
+                cursor = last + 1;
+            },
+            .type_decl => |td| {
+                const kw = locator.findKeyword(cursor, tokens.len, .keyword_type, td.name) orelse continue;
+                const last = locator.declEnd(kw, tokens.len);
+                try symbols.append(allocator, try typeSymbol(&locator, td, kw, last));
+                cursor = last + 1;
+            },
+            .const_decl => |cd| {
+                const kw = locator.findKeyword(cursor, tokens.len, .keyword_const, cd.name) orelse continue;
+                const last = locator.declEnd(kw, tokens.len);
+                var detail = std.ArrayList(u8){};
+                try detail.writer(allocator).print("const {s}", .{cd.name});
+                if (cd.type) |t| {
+                    try detail.appendSlice(allocator, ": ");
+                    try api.appendType(&detail, allocator, t);
+                }
+                try symbols.append(allocator, DocumentSymbol{
+                    .name = cd.name,
+                    .detail = detail.items,
+                    .kind = .constant,
+                    .range = try locator.range(locator.declStart(kw), last),
+                    .selection_range = try locator.range(kw + 1, kw + 1),
+                    .children = &[_]DocumentSymbol{},
+                });
+                cursor = last + 1;
+            },
+            .impl_decl => |impl| {
+                const kw = locator.findContextual(cursor, tokens.len, "impl") orelse continue;
+                const last = locator.declEnd(kw, tokens.len);
+                try symbols.append(allocator, try implSymbol(&locator, impl, kw, last));
+                cursor = last + 1;
+            },
+            // 旧语法的声明按 type 声明的形式列出（名称前一个 token 是 struct / enum / trait）
+            .struct_decl => |sd| {
+                const kw = locator.findNamed(cursor, tokens.len, sd.name) orelse continue;
+                const last = locator.declEnd(kw, tokens.len);
+                const td = ast.TypeDecl{
+                    .name = sd.name,
+                    .type_params = sd.type_params,
+                    .kind = .{ .struct_type = .{ .fields = sd.fields, .methods = sd.methods } },
+                    .is_public = sd.is_public,
+                };
+                try symbols.append(allocator, try typeSymbol(&locator, td, kw, last));
+                cursor = last + 1;
+            },
+            .enum_decl => |ed| {
+                const kw = locator.findNamed(cursor, tokens.len, ed.name) orelse continue;
+                const last = locator.declEnd(kw, tokens.len);
+                const td = ast.TypeDecl{
+                    .name = ed.name,
+                    .type_params = ed.type_params,
+                    .kind = .{ .enum_type = .{ .variants = ed.variants, .methods = ed.methods } },
+                    .is_public = ed.is_public,
+                };
+                try symbols.append(allocator, try typeSymbol(&locator, td, kw, last));
+                cursor = last + 1;
+            },
+            .trait_decl => |trait| {
+                const kw = locator.findNamed(cursor, tokens.len, trait.name) orelse continue;
+                const last = locator.declEnd(kw, tokens.len);
+                const td = ast.TypeDecl{
+                    .name = trait.name,
+                    .type_params = trait.type_params,
+                    .kind = .{ .trait_type = .{ .methods = trait.methods } },
+                    .is_public = trait.is_public,
+                };
+                try symbols.append(allocator, try typeSymbol(&locator, td, kw, last));
+                cursor = last + 1;
+            },
+            // 导入不在大纲中
+            .import_decl => {},
+        }
+    }
+
+    return symbols.toOwnedSlice(allocator);
+}
+
+fn functionSymbol(
+    locator: *DeclLocator,
+    func: ast.FunctionDecl,
+    kind: SymbolKind,
+    kw: usize,
+    last: usize,
+) !DocumentSymbol {
+    return DocumentSymbol{
+        .name = func.name,
+        .detail = try api.formatFunction(locator.allocator, func.name, func.type_params, func.params, func.return_type),
+        .kind = kind,
+        .range = try locator.range(locator.declStart(kw), last),
+        .selection_range = try locator.range(kw + 1, kw + 1),
+        .children = &[_]DocumentSymbol{},
+    };
+}
+
+fn typeSymbol(locator: *DeclLocator, td: ast.TypeDecl, kw: usize, last: usize) !DocumentSymbol {
+    const allocator = locator.allocator;
+    var children = std.ArrayList(DocumentSymbol){};
+    var detail = std.ArrayList(u8){};
+    try detail.appendSlice(allocator, "type ");
+    try detail.appendSlice(allocator, td.name);
+    if (td.type_params.len > 0) {
+        try detail.append(allocator, '<');
+        for (td.type_params, 0..) |param, i| {
+            if (i > 0) try detail.appendSlice(allocator, ", ");
+            try detail.appendSlice(allocator, param);
+        }
+        try detail.append(allocator, '>');
+    }
+
+    var kind: SymbolKind = undefined;
+    var methods: []const ast.FunctionDecl = &[_]ast.FunctionDecl{};
+    switch (td.kind) {
+        .struct_type => |st| {
+            kind = .struct_;
+            try detail.appendSlice(allocator, " = struct");
+            for (st.fields) |field| {
+                const index = locator.findMember(kw, last + 1, field.name) orelse continue;
+                var field_detail = std.ArrayList(u8){};
+                try api.appendType(&field_detail, allocator, field.type);
+                try children.append(allocator, DocumentSymbol{
+                    .name = field.name,
+                    .detail = field_detail.items,
+                    .kind = .field,
+                    .range = try locator.range(index, index),
+                    .selection_range = try locator.range(index, index),
+                    .children = &[_]DocumentSymbol{},
+                });
+            }
+            methods = st.methods;
+        },
+        .enum_type => |et| {
+            kind = .@"enum";
+            try detail.appendSlice(allocator, " = enum");
+            for (et.variants) |variant| {
+                const index = locator.findMember(kw, last + 1, variant.name) orelse continue;
+                try children.append(allocator, DocumentSymbol{
+                    .name = variant.name,
+                    .detail = "",
+                    .kind = .enum_member,
+                    .range = try locator.range(index, index),
+                    .selection_range = try locator.range(index, index),
+                    .children = &[_]DocumentSymbol{},
+                });
+            }
+            methods = et.methods;
+        },
+        .trait_type => |tt| {
+            kind = .interface;
+            try detail.appendSlice(allocator, " = trait");
+            var sig_cursor = kw + 2;
+            for (tt.methods) |sig| {
+                const method_kw = locator.findKeyword(sig_cursor, last + 1, .keyword_fn, sig.name) orelse continue;
+                const method_last = locator.declEnd(method_kw, last + 1);
+                try children.append(allocator, DocumentSymbol{
+                    .name = sig.name,
+                    .detail = try api.formatFunction(allocator, sig.name, &[_][]const u8{}, sig.params, sig.return_type),
+                    .kind = .method,
+                    .range = try locator.range(method_kw, method_last),
+                    .selection_range = try locator.range(method_kw + 1, method_kw + 1),
+                    .children = &[_]DocumentSymbol{},
+                });
+                sig_cursor = method_last + 1;
+            }
+        },
+        .alias => |target| {
+            kind = .type_parameter;
+            try detail.appendSlice(allocator, " = ");
+            try api.appendType(&detail, allocator, target);
+        },
+    }
+
+    var cursor = kw + 2;
+    for (methods) |method| {
+        const method_kw = locator.findKeyword(cursor, last + 1, .keyword_fn, method.name) orelse continue;
+        const method_last = locator.declEnd(method_kw, last + 1);
+        try children.append(allocator, try functionSymbol(locator, method, .method, method_kw, method_last));
+        cursor = method_last + 1;
+    }
+
+    return DocumentSymbol{
+        .name = td.name,
+        .detail = detail.items,
+        .kind = kind,
+        .range = try locator.range(locator.declStart(kw), last),
+        .selection_range = try locator.range(kw + 1, kw + 1),
+        .children = children.items,
+    };
+}
+
+/// 🆕 impl 块：名称是 `impl Trait for Type`，子项是它的方法
+fn implSymbol(locator: *DeclLocator, impl: ast.ImplDecl, kw: usize, last: usize) !DocumentSymbol {
+    const allocator = locator.allocator;
+    var name = std.ArrayList(u8){};
+    try name.writer(allocator).print("impl {s} for ", .{impl.trait_name});
+    try api.appendType(&name, allocator, impl.target_type);
+
+    var children = std.ArrayList(DocumentSymbol){};
+    var cursor = kw + 1;
+    for (impl.methods) |method| {
+        const method_kw = locator.findKeyword(cursor, last + 1, .keyword_fn, method.name) orelse continue;
+        const method_last = locator.declEnd(method_kw, last + 1);
+        try children.append(allocator, try functionSymbol(locator, method, .method, method_kw, method_last));
+        cursor = method_last + 1;
+    }
+
+    return DocumentSymbol{
+        .name = name.items,
+        .detail = "",
+        .kind = .object,
+        .range = try locator.range(locator.declStart(kw), last),
+        .selection_range = try locator.range(kw, kw),
+        .children = children.items,
+    };
+}
+
+/// 编码为 LSP DocumentSymbol[] JSON
+pub fn encodeSymbolsJson(allocator: std.mem.Allocator, symbols: []const DocumentSymbol) ![]const u8 {
+    var buf = std.ArrayList(u8){};
+    errdefer buf.deinit(allocator);
+    try appendSymbols(&buf, allocator, symbols);
+    try buf.append(allocator, '\n');
+    return buf.toOwnedSlice(allocator);
+}
+
+fn appendSymbols(buf: *std.ArrayList(u8), allocator: std.mem.Allocator, symbols: []const DocumentSymbol) !void {
+    try buf.append(allocator, '[');
+    for (symbols, 0..) |symbol, i| {
+        if (i > 0) try buf.append(allocator, ',');
+        try buf.appendSlice(allocator, "{\"name\":");
+        try appendJsonString(buf, allocator, symbol.name);
+        try buf.appendSlice(allocator, ",\"detail\":");
+        try appendJsonString(buf, allocator, symbol.detail);
+        try buf.writer(allocator).print(",\"kind\":{d},\"range\":", .{@intFromEnum(symbol.kind)});
+        try appendRangeJson(buf, allocator, symbol.range);
+        try buf.appendSlice(allocator, ",\"selectionRange\":");
+        try appendRangeJson(buf, allocator, symbol.selection_range);
+        try buf.appendSlice(allocator, ",\"children\":");
+        try appendSymbols(buf, allocator, symbol.children);
+        try buf.append(allocator, '}');
+    }
+    try buf.append(allocator, ']');
+}
+
+fn appendRangeJson(buf: *std.ArrayList(u8), allocator: std.mem.Allocator, r: Range) !void {
+    try buf.writer(allocator).print(
+        "{{\"start\":{{\"line\":{d},\"character\":{d}}},\"end\":{{\"line\":{d},\"character\":{d}}}}}",
+        .{ r.start.line, r.start.character, r.end.line, r.end.character },
+    );
+}
+
+pub fn appendJsonString(buf: *std.ArrayList(u8), allocator: std.mem.Allocator, text: []const u8) !void {
+    try buf.append(allocator, '"');
+    for (text) |c| {
+        switch (c) {
+            '"' => try buf.appendSlice(allocator, "\\\""),
+            '\\' => try buf.appendSlice(allocator, "\\\\"),
+            '\n' => try buf.appendSlice(allocator, "\\n"),
+            else => try buf.append(allocator, c),
+        }
+    }
+    try buf.append(allocator, '"');
+}
+
+// ============================================================================
+// impls / missing-impls - trait 实现情况
+// ============================================================================
+
+/// 一个类型对 trait 的实现情况
+pub const ImplReport = struct {
+    type_name: []const u8,
+    type_params: []const []const u8,
+    location: ?ast.SourceLocation,
+    found: bool,  // missing-impls 中给出的类型是否存在
+    missing: []const []const u8,
+    mismatched: []const []const u8,
+
+    pub fn isComplete(self: ImplReport) bool {
+        return self.found and self.missing.len == 0 and self.mismatched.len == 0;
+    }
+};
+
+/// 实现了 trait 的全部类型（类型上有 trait 及其 supertrait 的每个方法；签名不一致的也列出），按类型名排序
+/// 使用类型检查器收集的 trait 表和类型方法表（见 TypeChecker.collectDeclarations）
+pub fn traitImpls(allocator: std.mem.Allocator, checker: *TypeChecker, trait_def: typechecker.TraitDef) ![]ImplReport {
+    var reports = std.ArrayList(ImplReport){};
+    var it = checker.type_table.valueIterator();
+    while (it.next()) |td| {
+        if (td.kind != .struct_type and td.kind != .enum_type) continue;
+        const report = try implReport(allocator, checker, trait_def, td.*);
+        if (report.missing.len == 0) try reports.append(allocator, report);
+    }
+    std.mem.sort(ImplReport, reports.items, {}, struct {
+        fn lessThan(_: void, a: ImplReport, b: ImplReport) bool {
+            return std.mem.lessThan(u8, a.type_name, b.type_name);
+        }
+    }.lessThan);
+    return reports.toOwnedSlice(allocator);
+}
+
+/// 按给定的顺序检查每个类型
+pub fn checkImpls(
+    allocator: std.mem.Allocator,
+    checker: *TypeChecker,
+    trait_def: typechecker.TraitDef,
+    type_names: []const []const u8,
+) ![]ImplReport {
+    const reports = try allocator.alloc(ImplReport, type_names.len);
+    for (type_names, reports) |name, *report| {
+        const td = checker.type_table.get(name) orelse {
+            report.* = ImplReport{
+                .type_name = name,
+                .type_params = &[_][]const u8{},
+                .location = null,
+                .found = false,
+                .missing = &[_][]const u8{},
+                .mismatched = &[_][]const u8{},
+            };
+            continue;
+        };
+        report.* = try implReport(allocator, checker, trait_def, td);
+    }
+    return reports;
+}
+
+fn implReport(allocator: std.mem.Allocator, checker: *TypeChecker, trait_def: typechecker.TraitDef, td: ast.TypeDecl) !ImplReport {
+    const methods = try checker.missingTraitMethods(allocator, td.name, trait_def);
+    return ImplReport{
+        .type_name = td.name,
+        .type_params = td.type_params,
+        .location = td.location,
+        .found = true,
+        .missing = methods.missing,
+        .mismatched = methods.mismatched,
+    };
+}
+
+/// 编码为 JSON：{"trait":..., "impls":[{"type","typeParams","file","line","column","found","missing","mismatched"}]}
+pub fn encodeImplsJson(allocator: std.mem.Allocator, trait_name: []const u8, reports: []const ImplReport) ![]const u8 {
+    var buf = std.ArrayList(u8){};
+    errdefer buf.deinit(allocator);
+
+    try buf.appendSlice(allocator, "{\"trait\":");
+    try appendJsonString(&buf, allocator, trait_name);
+    try buf.appendSlice(allocator, ",\"impls\":[");
+    for (reports, 0..) |report, i| {
+        if (i > 0) try buf.append(allocator, ',');
+        try buf.appendSlice(allocator, "{\"type\":");
+        try appendJsonString(&buf, allocator, report.type_name);
+        try buf.appendSlice(allocator, ",\"typeParams\":");
+        try appendJsonStrings(&buf, allocator, report.type_params);
+        if (report.location) |loc| {
+            try buf.appendSlice(allocator, ",\"file\":");
+            try appendJsonString(&buf, allocator, loc.file.path());
+            try buf.writer(allocator).print(",\"line\":{d},\"column\":{d}", .{ loc.line, loc.column });
+        }
+        try buf.writer(allocator).print(",\"found\":{},\"missing\":", .{report.found});
+        try appendJsonStrings(&buf, allocator, report.missing);
+        try buf.appendSlice(allocator, ",\"mismatched\":");
+        try appendJsonStrings(&buf, allocator, report.mismatched);
+        try buf.append(allocator, '}');
+    }
+    try buf.appendSlice(allocator, "]}\n");
+
+    return buf.toOwnedSlice(allocator);
+}
+
+fn appendJsonStrings(buf: *std.ArrayList(u8), allocator: std.mem.Allocator, items: []const []const u8) !void {
+    try buf.append(allocator, '[');
+    for (items, 0..) |item, i| {
+        if (i > 0) try buf.append(allocator, ',');
+        try appendJsonString(buf, allocator, item);
+    }
+    try buf.append(allocator, ']');
+}
@@ -0,0 +1,150 @@
+//! ResolutionTrace - 调用解析过程的记录（pawc <file> --explain-overloads）
+//!
+//! 类型检查器解析以下调用时记录每个候选、推导出的替换、约束检查和得分（通过的检查数），以及最终选中的候选：
+//!   - 泛型函数调用：max(a, b) → max<T> with T = i32，检查 T: Ord
+//!   - 直接调用 trait 方法：show(p) → 每个声明了 show 的 trait 是否由 p 的类型实现
+//!
+//! 用于排查歧义调用（"ambiguous call"）和约束不满足的错误：
+//!   --explain-overloads           文本输出到 stderr
+//!   --explain-overloads=<file>    JSON 写入文件（供编辑器和脚本使用）
+
+const std = @import("std");
+const diagnostic = @import("diagnostic.zig");
+const query = @import("query.zig");
+const Span = diagnostic.Span;
+
+/// 对一个候选做的一项检查（约束、trait 实现、接收者）
+pub const Check = struct {
+    description: []const u8,
+    passed: bool,
+};
+
+pub const Candidate = struct {
+    name: []const u8,          // max<T>、Show::show
+    substitution: []const u8,  // "T = i32, U = bool"；没有类型参数时为空
+    checks: []const Check,
+
+    pub fn isViable(self: Candidate) bool {
+        return self.score() == self.checks.len;
+    }
+
+    /// 通过的检查数：可用的候选通过全部检查，被拒绝的候选按离可用有多近排列
+    pub fn score(self: Candidate) usize {
+        var passed: usize = 0;
+        for (self.checks) |check| {
+            if (check.passed) passed += 1;
+        }
+        return passed;
+    }
+};
+
+/// 一次调用的解析
+pub const Resolution = struct {
+    callee: []const u8,
+    span: ?Span,
+    candidates: []const Candidate,
+    selected: ?[]const u8,  // null：没有可用候选，或有多个（歧义）
+};
+
+pub const ResolutionTrace = struct {
+    arena: std.heap.ArenaAllocator,
+    resolutions: std.ArrayList(Resolution),
+
+    pub fn init(allocator: std.mem.Allocator) ResolutionTrace {
+        return ResolutionTrace{
+            .arena = std.heap.ArenaAllocator.init(allocator),
+            .resolutions = std.ArrayList(Resolution){},
+        };
+    }
+
+    pub fn deinit(self: *ResolutionTrace) void {
+        self.arena.deinit();  // resolutions 也在 arena 中
+    }
+
+    /// 记录中的字符串和切片都分配在这里
+    pub fn allocator(self: *ResolutionTrace) std.mem.Allocator {
+        return self.arena.allocator();
+    }
+
+    pub fn record(self: *ResolutionTrace, resolution: Resolution) !void {
+        try self.resolutions.append(self.arena.allocator(), resolution);
+    }
+
+    /// 文本形式（输出到 stderr）
+    pub fn print(self: *const ResolutionTrace) void {
+        for (self.resolutions.items) |r| {
+            if (r.span) |span| {
+                std.debug.print("resolving call to '{s}' at {s}:{d}:{d}\n", .{ r.callee, span.file.path(), span.start_line, span.start_col });
+            } else {
+                std.debug.print("resolving call to '{s}'\n", .{r.callee});
+            }
+            if (r.candidates.len == 0) std.debug.print("  no candidates\n", .{});
+            for (r.candidates) |c| {
+                if (c.substitution.len > 0) {
+                    std.debug.print("  candidate {s} with {s}\n", .{ c.name, c.substitution });
+                } else {
+                    std.debug.print("  candidate {s}\n", .{c.name});
+                }
+                for (c.checks) |check| {
+                    std.debug.print("    {s} {s}\n", .{ if (check.passed) "✓" else "✗", check.description });
+                }
+                std.debug.print("    => {s} (score {d}/{d})\n", .{ if (c.isViable()) "viable" else "rejected", c.score(), c.checks.len });
+            }
+            if (r.selected) |name| {
+                std.debug.print("  selected {s}\n\n", .{name});
+            } else {
+                std.debug.print("  no candidate selected ({s})\n\n", .{if (viableCount(r) > 1) "ambiguous" else "none viable"});
+            }
+        }
+    }
+
+    /// JSON 形式：[{"callee", "file", "line", "column", "candidates": [{"name", "substitution", "checks", "score", "viable"}], "selected"}]
+    pub fn toJson(self: *const ResolutionTrace, gpa: std.mem.Allocator) ![]u8 {
+        var buf = std.ArrayList(u8){};
+        errdefer buf.deinit(gpa);
+        try buf.append(gpa, '[');
+        for (self.resolutions.items, 0..) |r, i| {
+            if (i > 0) try buf.append(gpa, ',');
+            try buf.appendSlice(gpa, "{\"callee\":");
+            try query.appendJsonString(&buf, gpa, r.callee);
+            if (r.span) |span| {
+                try buf.appendSlice(gpa, ",\"file\":");
+                try query.appendJsonString(&buf, gpa, span.file.path());
+                try buf.writer(gpa).print(",\"line\":{d},\"column\":{d}", .{ span.start_line, span.start_col });
+            }
+            try buf.appendSlice(gpa, ",\"candidates\":[");
+            for (r.candidates, 0..) |c, j| {
+                if (j > 0) try buf.append(gpa, ',');
+                try buf.appendSlice(gpa, "{\"name\":");
+                try query.appendJsonString(&buf, gpa, c.name);
+                try buf.appendSlice(gpa, ",\"substitution\":");
+                try query.appendJsonString(&buf, gpa, c.substitution);
+                try buf.appendSlice(gpa, ",\"checks\":[");
+                for (c.checks, 0..) |check, k| {
+                    if (k > 0) try buf.append(gpa, ',');
+                    try buf.appendSlice(gpa, "{\"description\":");
+                    try query.appendJsonString(&buf, gpa, check.description);
+                    try buf.writer(gpa).print(",\"passed\":{}}}", .{check.passed});
+                }
+                try buf.writer(gpa).print("],\"score\":{d},\"viable\":{}}}", .{ c.score(), c.isViable() });
+            }
+            try buf.appendSlice(gpa, "],\"selected\":");
+            if (r.selected) |name| {
+                try query.appendJsonString(&buf, gpa, name);
+            } else {
+                try buf.appendSlice(gpa, "null");
+            }
+            try buf.append(gpa, '}');
+        }
+        try buf.append(gpa, ']');
+        return buf.toOwnedSlice(gpa);
+    }
+
+    fn viableCount(r: Resolution) usize {
+        var count: usize = 0;
+        for (r.candidates) |c| {
+            if (c.isViable()) count += 1;
+        }
+        return count;
+    }
+};
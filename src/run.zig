@@ -0,0 +1,108 @@
+//! Run - 构建并运行（pawc run [<file.paw>] [options] [-- <args>...]）
+//!
+//! 不指定文件时构建当前目录的项目（Paw.toml 的 entry，默认 main.paw，与 pawc new / init 创建的布局一致）。
+//!   1. pawc <file> --compile --backend=c -o target/<name> [options]   构建
+//!   2. target/<name> <args>...                                        运行
+//! 构建选项与直接编译时相同；-- 之后的参数原样传给程序，pawc 以程序的退出码退出。
+
+const std = @import("std");
+const package = @import("package.zig");
+const messages = @import("messages.zig");
+
+/// 构建产物目录
+pub const TARGET_DIR = "target";
+/// 项目的默认入口文件（Paw.toml 没有 entry 时）
+pub const PROJECT_ENTRY = "main.paw";
+
+/// 构建并运行，返回程序的退出码（构建失败时返回 1）
+pub fn run(allocator: std.mem.Allocator, args: []const [:0]const u8) !u8 {
+    var file: ?[]const u8 = null;
+    var build_options = std.ArrayList([]const u8){};
+    defer build_options.deinit(allocator);
+    var program_args: []const [:0]const u8 = &.{};
+
+    for (args, 0..) |arg, i| {
+        if (std.mem.eql(u8, arg, "--")) {
+            program_args = args[i + 1 ..];
+            break;
+        }
+        if (file == null and std.mem.endsWith(u8, arg, ".paw")) {
+            file = arg;
+        } else if (std.mem.eql(u8, arg, "--run") or std.mem.eql(u8, arg, "--compile") or std.mem.eql(u8, arg, "-o")) {
+            std.debug.print("Error: '{s}' cannot be used with pawc run (the program is built to {s}/)\n", .{ arg, TARGET_DIR });
+            return 1;
+        } else {
+            try build_options.append(allocator, arg);
+        }
+    }
+
+    // 要构建的文件和产物名
+    var manifest: ?package.Manifest = null;
+    defer if (manifest) |*m| m.deinit();
+    const entry = file orelse blk: {
+        manifest = package.Manifest.load(allocator, package.MANIFEST_FILE) catch |err| switch (err) {
+            error.FileNotFound => {
+                std.debug.print("Error: no {s} in the current directory\n", .{package.MANIFEST_FILE});
+                std.debug.print("Usage: pawc run [<file.paw>] [options] [-- <args>...]\n", .{});
+                return 1;
+            },
+            else => return 1,
+        };
+        const project_entry = manifest.?.package_entry orelse PROJECT_ENTRY;
+        std.fs.cwd().access(project_entry, .{}) catch {
+            std.debug.print("Error: the project has no {s}\n", .{project_entry});
+            return 1;
+        };
+        break :blk project_entry;
+    };
+    const name = if (manifest) |m| m.package_name orelse std.fs.path.stem(entry) else std.fs.path.stem(entry);
+
+    try std.fs.cwd().makePath(TARGET_DIR);
+    const output_path = try std.fs.path.join(allocator, &.{ TARGET_DIR, name });
+    defer allocator.free(output_path);
+    // 构建失败的某些路径不设置退出码，删除旧产物后以产物是否存在为准
+    std.fs.cwd().deleteFile(output_path) catch {};
+
+    // 1. 构建：复用编译命令（选项在后，可以覆盖默认的 --backend=c）
+    const self_exe = try std.fs.selfExePathAlloc(allocator);
+    defer allocator.free(self_exe);
+    var build_argv = std.ArrayList([]const u8){};
+    defer build_argv.deinit(allocator);
+    try build_argv.appendSlice(allocator, &.{ self_exe, entry, "--compile", "--backend=c", "-o", output_path });
+    try build_argv.appendSlice(allocator, &.{ "--locale", @tagName(messages.locale()) });
+    try build_argv.appendSlice(allocator, build_options.items);
+
+    var build = std.process.Child.init(build_argv.items, allocator);
+    const build_term = try build.spawnAndWait();
+    const built = build_term == .Exited and build_term.Exited == 0;
+    std.fs.cwd().access(output_path, .{}) catch {
+        if (built) std.debug.print("Error: the build of {s} did not produce {s}\n", .{ entry, output_path });
+        return 1;
+    };
+    if (!built) return 1;
+
+    // 2. 运行，转发参数和退出码
+    var run_argv = std.ArrayList([]const u8){};
+    defer run_argv.deinit(allocator);
+    const run_path = try std.fmt.allocPrint(allocator, "./{s}", .{output_path});
+    defer allocator.free(run_path);
+    try run_argv.append(allocator, run_path);
+    for (program_args) |arg| try run_argv.append(allocator, arg);
+
+    var child = std.process.Child.init(run_argv.items, allocator);
+    const term = child.spawnAndWait() catch |err| {
+        std.debug.print("Error: cannot run {s}: {any}\n", .{ output_path, err });
+        return 1;
+    };
+    return exitCode(term);
+}
+
+/// 子进程的结束状态对应的退出码
+pub fn exitCode(term: std.process.Child.Term) u8 {
+    return switch (term) {
+        .Exited => |code| code,
+        // 与 shell 一样：被信号终止时退出码为 128 + 信号
+        .Signal => |sig| @truncate(128 + sig),
+        else => 1,
+    };
+}
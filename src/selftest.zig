@@ -0,0 +1,165 @@
+//! SelfTest - 差分测试（pawc selftest <dir>）
+//!
+//! 对目录下（递归）每个 .paw 程序：
+//!   1. pawc <file> --interpret                      解释执行
+//!   2. pawc <file> --compile --backend=c -o <tmp>   编译后运行
+//! 比较两者的 stdout 和退出码，不一致即视为后端误编译（或解释器错误）。
+//!
+//! 无法编译的程序（如 tests/error_messages）和解释器尚不支持的程序会被跳过。
+
+const std = @import("std");
+
+/// 编译产物的临时目录（结束后删除）
+const WORK_DIR = ".paw-selftest";
+/// 每个程序输出的上限
+const MAX_OUTPUT_BYTES = 1024 * 1024;
+
+pub const Outcome = enum { pass, fail, skip };
+
+/// 一次运行的可观察结果
+pub const Observation = struct {
+    stdout: []const u8,
+    stderr: []const u8,
+    exit_code: ?u8,  // null = 被信号终止等异常退出
+
+    pub fn deinit(self: Observation, allocator: std.mem.Allocator) void {
+        allocator.free(self.stdout);
+        allocator.free(self.stderr);
+    }
+};
+
+/// 运行目录中的所有程序，全部通过（或跳过）时返回 true
+pub fn run(allocator: std.mem.Allocator, dir_path: []const u8) !bool {
+    const self_exe = try std.fs.selfExePathAlloc(allocator);
+    defer allocator.free(self_exe);
+
+    var files = std.ArrayList([]const u8){};
+    defer {
+        for (files.items) |file| allocator.free(file);
+        files.deinit(allocator);
+    }
+    try collectPrograms(allocator, dir_path, &files);
+    std.mem.sort([]const u8, files.items, {}, lessThan);
+
+    if (files.items.len == 0) {
+        std.debug.print("No .paw files found in {s}\n", .{dir_path});
+        return true;
+    }
+
+    try std.fs.cwd().makePath(WORK_DIR);
+    defer std.fs.cwd().deleteTree(WORK_DIR) catch {};
+
+    var passed: usize = 0;
+    var failed: usize = 0;
+    var skipped: usize = 0;
+    for (files.items) |file| {
+        switch (try checkProgram(allocator, self_exe, file)) {
+            .pass => passed += 1,
+            .fail => failed += 1,
+            .skip => skipped += 1,
+        }
+    }
+
+    std.debug.print("\nselftest: {d} passed, {d} failed, {d} skipped\n", .{ passed, failed, skipped });
+    return failed == 0;
+}
+
+pub fn collectPrograms(allocator: std.mem.Allocator, dir_path: []const u8, files: *std.ArrayList([]const u8)) !void {
+    var dir = try std.fs.cwd().openDir(dir_path, .{ .iterate = true });
+    defer dir.close();
+
+    var walker = try dir.walk(allocator);
+    defer walker.deinit();
+    while (try walker.next()) |entry| {
+        if (entry.kind != .file or !std.mem.endsWith(u8, entry.path, ".paw")) continue;
+        try files.append(allocator, try std.fs.path.join(allocator, &.{ dir_path, entry.path }));
+    }
+}
+
+pub fn lessThan(_: void, a: []const u8, b: []const u8) bool {
+    return std.mem.lessThan(u8, a, b);
+}
+
+/// 对单个程序做差分比较
+fn checkProgram(allocator: std.mem.Allocator, self_exe: []const u8, file: []const u8) !Outcome {
+    // 1. 编译为可执行文件（编译失败的程序不参与比较）
+    const exe_path = try std.fmt.allocPrint(allocator, "{s}/{s}", .{ WORK_DIR, std.fs.path.stem(file) });
+    defer allocator.free(exe_path);
+
+    const compile = try observe(allocator, &.{ self_exe, file, "--compile", "--backend=c", "-o", exe_path });
+    defer compile.deinit(allocator);
+    std.fs.cwd().access(exe_path, .{}) catch {
+        std.debug.print("SKIP  {s} (does not compile)\n", .{file});
+        return .skip;
+    };
+    defer std.fs.cwd().deleteFile(exe_path) catch {};
+
+    // 2. 解释执行
+    const interpreted = try observe(allocator, &.{ self_exe, file, "--interpret" });
+    defer interpreted.deinit(allocator);
+    if (interpreted.exit_code != 0 and std.mem.indexOf(u8, interpreted.stderr, "by the interpreter") != null) {
+        std.debug.print("SKIP  {s} (not supported by the interpreter)\n", .{file});
+        return .skip;
+    }
+
+    // 3. 运行原生程序
+    const native = try observe(allocator, &.{exe_path});
+    defer native.deinit(allocator);
+
+    // 4. 比较
+    const same_output = std.mem.eql(u8, native.stdout, interpreted.stdout);
+    const same_exit = native.exit_code == interpreted.exit_code;
+    if (same_output and same_exit) {
+        std.debug.print("PASS  {s}\n", .{file});
+        return .pass;
+    }
+
+    std.debug.print("FAIL  {s}\n", .{file});
+    if (!same_exit) {
+        std.debug.print("      exit code: native {?d}, interpreter {?d}\n", .{ native.exit_code, interpreted.exit_code });
+    }
+    if (!same_output) reportFirstDifference(native.stdout, interpreted.stdout);
+    if (interpreted.exit_code != 0 and interpreted.stderr.len > 0) {
+        std.debug.print("      interpreter stderr:\n{s}\n", .{interpreted.stderr});
+    }
+    return .fail;
+}
+
+pub fn observe(allocator: std.mem.Allocator, argv: []const []const u8) !Observation {
+    return observeWithEnv(allocator, argv, null);
+}
+
+/// 🆕 在指定的环境变量下运行（null 时继承当前环境）
+pub fn observeWithEnv(allocator: std.mem.Allocator, argv: []const []const u8, env_map: ?*const std.process.EnvMap) !Observation {
+    const result = try std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = argv,
+        .env_map = env_map,
+        .max_output_bytes = MAX_OUTPUT_BYTES,
+    });
+    return Observation{
+        .stdout = result.stdout,
+        .stderr = result.stderr,
+        .exit_code = switch (result.term) {
+            .Exited => |code| code,
+            else => null,
+        },
+    };
+}
+
+/// 打印 stdout 第一处不同的行
+fn reportFirstDifference(native: []const u8, interpreted: []const u8) void {
+    var native_lines = std.mem.splitScalar(u8, native, '\n');
+    var interp_lines = std.mem.splitScalar(u8, interpreted, '\n');
+    var line: usize = 1;
+    while (true) : (line += 1) {
+        const a = native_lines.next();
+        const b = interp_lines.next();
+        if (a == null and b == null) return;
+        if (a != null and b != null and std.mem.eql(u8, a.?, b.?)) continue;
+        std.debug.print("      stdout differs at line {d}:\n", .{line});
+        std.debug.print("        native:      {s}\n", .{a orelse "<end of output>"});
+        std.debug.print("        interpreter: {s}\n", .{b orelse "<end of output>"});
+        return;
+    }
+}
@@ -0,0 +1,288 @@
+//! SourceMap - 源文件表
+//!
+//! 为长时间运行的工具（watch 模式、LSP、REPL）设计的可增量更新的源文件表：
+//! - 每个文件带有版本号，内容更新时递增
+//! - 行首偏移表在添加/更新文件时预先计算，偏移量 -> 行列号为 O(log n) 二分查找
+//! - 通过修改时间（mtime）检测磁盘上的文件变化
+//! - 文件更新时，它自身以及所有（传递）依赖它的文件的诊断结果都会失效
+//!
+//! 用法：
+//!   var map = SourceMap.init(allocator);
+//!   defer map.deinit();
+//!   const id = try map.loadFile("app.paw");
+//!   ...
+//!   if (try map.refreshFromDisk(id)) { /* 重新检查 */ }
+
+const std = @import("std");
+
+pub const FileId = u32;
+
+// ============================================================================
+// SourceFile - 单个源文件
+// ============================================================================
+
+pub const SourceFile = struct {
+    name: []const u8,
+    contents: []const u8,
+    version: u32,
+    mtime: i128,  // 0 表示内存中的文件（不对应磁盘）
+    line_starts: ?[]usize,  // 行首偏移表（添加/更新时计算）
+    diagnostics_version: ?u32,  // 诊断结果对应的版本（null = 已失效）
+    dependents: std.ArrayList(FileId),  // 依赖（import）本文件的文件
+
+    fn deinit(self: *SourceFile, allocator: std.mem.Allocator) void {
+        allocator.free(self.name);
+        allocator.free(self.contents);
+        if (self.line_starts) |starts| allocator.free(starts);
+        self.dependents.deinit(allocator);
+    }
+
+    /// 获取行首偏移表（未计算时立即计算）
+    pub fn lineStarts(self: *SourceFile, allocator: std.mem.Allocator) ![]const usize {
+        if (self.line_starts) |starts| return starts;
+        return self.computeLineStarts(allocator);
+    }
+
+    /// 重新计算行首偏移表
+    fn computeLineStarts(self: *SourceFile, allocator: std.mem.Allocator) ![]const usize {
+        if (self.line_starts) |old| allocator.free(old);
+        self.line_starts = null;
+
+        var starts = std.ArrayList(usize){};
+        errdefer starts.deinit(allocator);
+        try starts.append(allocator, 0);
+        for (self.contents, 0..) |c, i| {
+            if (c == '\n') try starts.append(allocator, i + 1);
+        }
+
+        self.line_starts = try starts.toOwnedSlice(allocator);
+        return self.line_starts.?;
+    }
+
+    /// 获取第 line 行（从 1 开始）的文本，不含换行符
+    pub fn lineText(self: *SourceFile, allocator: std.mem.Allocator, line: usize) !?[]const u8 {
+        const starts = try self.lineStarts(allocator);
+        if (line == 0 or line > starts.len) return null;
+
+        const start = starts[line - 1];
+        var end = if (line < starts.len) starts[line] - 1 else self.contents.len;
+        if (end > start and self.contents[end - 1] == '\r') end -= 1;
+        return self.contents[start..end];
+    }
+
+    /// 总行数
+    pub fn lineCount(self: *SourceFile, allocator: std.mem.Allocator) !usize {
+        return (try self.lineStarts(allocator)).len;
+    }
+
+    /// 字节偏移 -> 行列号（均从 1 开始），对行首表二分查找
+    pub fn lookupLineCol(self: *SourceFile, allocator: std.mem.Allocator, offset: usize) !LineCol {
+        const starts = try self.lineStarts(allocator);
+        const clamped = @min(offset, self.contents.len);
+
+        // 找到最后一个 <= offset 的行首
+        var lo: usize = 0;
+        var hi: usize = starts.len;
+        while (hi - lo > 1) {
+            const mid = lo + (hi - lo) / 2;
+            if (starts[mid] <= clamped) lo = mid else hi = mid;
+        }
+
+        return LineCol{ .line = lo + 1, .column = clamped - starts[lo] + 1 };
+    }
+
+    /// 行列号（从 1 开始）-> 字节偏移
+    pub fn offsetOf(self: *SourceFile, allocator: std.mem.Allocator, line: usize, column: usize) !?usize {
+        const starts = try self.lineStarts(allocator);
+        if (line == 0 or line > starts.len) return null;
+        const offset = starts[line - 1] + (if (column > 0) column - 1 else 0);
+        return @min(offset, self.contents.len);
+    }
+};
+
+/// 行列号（均从 1 开始）
+pub const LineCol = struct {
+    line: usize,
+    column: usize,
+};
+
+// ============================================================================
+// SourceMap
+// ============================================================================
+
+pub const SourceMap = struct {
+    allocator: std.mem.Allocator,
+    files: std.ArrayList(SourceFile),
+    by_name: std.StringHashMap(FileId),
+
+    pub fn init(allocator: std.mem.Allocator) SourceMap {
+        return SourceMap{
+            .allocator = allocator,
+            .files = std.ArrayList(SourceFile){},
+            .by_name = std.StringHashMap(FileId).init(allocator),
+        };
+    }
+
+    pub fn deinit(self: *SourceMap) void {
+        for (self.files.items) |*file| {
+            file.deinit(self.allocator);
+        }
+        self.files.deinit(self.allocator);
+        self.by_name.deinit();
+    }
+
+    /// 添加文件（内容会被复制）；若同名文件已存在则更新其内容
+    pub fn addFile(self: *SourceMap, name: []const u8, contents: []const u8) !FileId {
+        if (self.by_name.get(name)) |id| {
+            _ = try self.updateFile(id, contents);
+            return id;
+        }
+
+        const owned_name = try self.allocator.dupe(u8, name);
+        errdefer self.allocator.free(owned_name);
+        const owned_contents = try self.allocator.dupe(u8, contents);
+        errdefer self.allocator.free(owned_contents);
+
+        const id: FileId = @intCast(self.files.items.len);
+        try self.files.append(self.allocator, SourceFile{
+            .name = owned_name,
+            .contents = owned_contents,
+            .version = 1,
+            .mtime = 0,
+            .line_starts = null,
+            .diagnostics_version = null,
+            .dependents = std.ArrayList(FileId){},
+        });
+        errdefer {
+            var file = self.files.pop().?;
+            if (file.line_starts) |starts| self.allocator.free(starts);
+            file.dependents.deinit(self.allocator);
+        }
+        _ = try self.get(id).computeLineStarts(self.allocator);
+        try self.by_name.put(owned_name, id);
+        return id;
+    }
+
+    /// 从磁盘读取文件并添加（记录 mtime 以便之后检测修改）
+    pub fn loadFile(self: *SourceMap, path: []const u8) !FileId {
+        const stat = try std.fs.cwd().statFile(path);
+        const contents = try std.fs.cwd().readFileAlloc(self.allocator, path, 10 * 1024 * 1024);
+        defer self.allocator.free(contents);
+
+        const id = try self.addFile(path, contents);
+        self.get(id).mtime = stat.mtime;
+        return id;
+    }
+
+    /// 原地更新文件内容
+    /// 返回 false 表示内容没有变化（版本号不变）
+    pub fn updateFile(self: *SourceMap, id: FileId, contents: []const u8) !bool {
+        const file = self.get(id);
+        if (std.mem.eql(u8, file.contents, contents)) return false;
+
+        const owned_contents = try self.allocator.dupe(u8, contents);
+        self.allocator.free(file.contents);
+        file.contents = owned_contents;
+        file.version += 1;
+
+        _ = try file.computeLineStarts(self.allocator);
+
+        self.invalidate(id);
+        return true;
+    }
+
+    /// 如果磁盘上的文件被修改（mtime 变化），重新读取
+    /// 返回 true 表示内容发生了变化
+    pub fn refreshFromDisk(self: *SourceMap, id: FileId) !bool {
+        const file = self.get(id);
+        if (file.mtime == 0) return false;  // 内存中的文件
+
+        const stat = std.fs.cwd().statFile(file.name) catch return false;
+        if (stat.mtime == file.mtime) return false;
+
+        const contents = try std.fs.cwd().readFileAlloc(self.allocator, file.name, 10 * 1024 * 1024);
+        defer self.allocator.free(contents);
+
+        const changed = try self.updateFile(id, contents);
+        self.get(id).mtime = stat.mtime;
+        return changed;
+    }
+
+    /// 检查所有磁盘文件，返回发生变化的文件数
+    pub fn refreshAll(self: *SourceMap) !usize {
+        var changed: usize = 0;
+        var id: FileId = 0;
+        while (id < self.files.items.len) : (id += 1) {
+            if (try self.refreshFromDisk(id)) changed += 1;
+        }
+        return changed;
+    }
+
+    pub fn get(self: *SourceMap, id: FileId) *SourceFile {
+        return &self.files.items[id];
+    }
+
+    pub fn findByName(self: *SourceMap, name: []const u8) ?FileId {
+        return self.by_name.get(name);
+    }
+
+    /// 按文件名把字节偏移转换为行列号
+    pub fn lookupLineCol(self: *SourceMap, name: []const u8, offset: usize) !?LineCol {
+        const id = self.findByName(name) orelse return null;
+        return try self.get(id).lookupLineCol(self.allocator, offset);
+    }
+
+    /// 获取文件中某一行的文本（按文件名查找）
+    pub fn lineText(self: *SourceMap, name: []const u8, line: usize) !?[]const u8 {
+        const id = self.findByName(name) orelse return null;
+        return self.get(id).lineText(self.allocator, line);
+    }
+
+    // ========================================================================
+    // 依赖关系与诊断失效
+    // ========================================================================
+
+    /// 记录 file 依赖（import）了 dependency
+    pub fn addDependency(self: *SourceMap, file: FileId, dependency: FileId) !void {
+        const dep = self.get(dependency);
+        for (dep.dependents.items) |existing| {
+            if (existing == file) return;
+        }
+        try dep.dependents.append(self.allocator, file);
+    }
+
+    /// 标记文件的诊断结果与当前版本一致
+    pub fn markDiagnosticsFresh(self: *SourceMap, id: FileId) void {
+        const file = self.get(id);
+        file.diagnostics_version = file.version;
+    }
+
+    /// 文件的诊断结果是否仍然有效
+    pub fn hasFreshDiagnostics(self: *SourceMap, id: FileId) bool {
+        const file = self.get(id);
+        return file.diagnostics_version != null and file.diagnostics_version.? == file.version;
+    }
+
+    /// 使文件及所有（传递）依赖它的文件的诊断失效
+    pub fn invalidate(self: *SourceMap, id: FileId) void {
+        const visited = self.allocator.alloc(bool, self.files.items.len) catch {
+            // 内存不足时保守处理：全部失效
+            for (self.files.items) |*file| file.diagnostics_version = null;
+            return;
+        };
+        defer self.allocator.free(visited);
+        @memset(visited, false);
+        self.invalidateFrom(id, visited);
+    }
+
+    fn invalidateFrom(self: *SourceMap, id: FileId, visited: []bool) void {
+        if (visited[id]) return;  // 防止循环依赖
+        visited[id] = true;
+
+        const file = self.get(id);
+        file.diagnostics_version = null;
+        for (file.dependents.items) |dependent| {
+            self.invalidateFrom(dependent, visited);
+        }
+    }
+};
@@ -0,0 +1,231 @@
+//! TestRunner - 运行 @test 函数（pawc test <file-or-dir>...）
+//!
+//! pawc test <file-or-dir>... [--filter <text>]
+//!
+//! 对每个含有 @test 函数的 .paw 文件（目录递归）：
+//!   1. pawc <file> --compile --backend=c --test -o <tmp>   生成测试可执行文件
+//!      （main 按 argv[1] 调用对应的测试函数，见 CodeGen.generateTestMain）
+//!   2. 每个测试在单独的进程中运行：<tmp> <test>
+//!
+//! 结果：
+//!   - @test：正常返回（退出码 0）通过；panic（退出码 101，消息在 stderr）失败
+//!   - @test(should_panic = "msg")：panic 且 panic 消息包含 msg 时通过
+//!   - @skip("reason")：不运行，计入跳过
+//!   - 🆕 @property(runs = N)：<tmp> <test> <seed> 在一个进程中以 seed, seed+1, ... 运行 N 次，
+//!     失败时报告那次运行的种子；pawc test --seed <n> 从该种子开始，复现同样的输入
+//! 每个测试一个进程，panic 不会影响其余的测试。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const Lexer = @import("lexer.zig").Lexer;
+const Parser = @import("parser.zig").Parser;
+const selftest = @import("selftest.zig");
+
+/// 测试可执行文件的临时目录（结束后删除）
+const WORK_DIR = ".paw-test";
+/// panic 的退出码（与运行时一致）
+const PANIC_EXIT_CODE = 101;
+
+pub const Options = struct {
+    filter: ?[]const u8 = null,  // 只运行名字包含该文本的测试
+    seed: ?u64 = null,           // 🆕 @property 的起始种子（默认按时间选取）
+};
+
+const Counts = struct {
+    passed: usize = 0,
+    failed: usize = 0,
+    skipped: usize = 0,
+    filtered: usize = 0,
+};
+
+/// 一个测试函数
+const TestCase = struct {
+    name: []const u8,
+    attr: ast.TestAttr,
+};
+
+/// 运行给定文件和目录中的所有测试，全部通过（或跳过）时返回 true
+pub fn run(allocator: std.mem.Allocator, paths: []const []const u8, options: Options) !bool {
+    const self_exe = try std.fs.selfExePathAlloc(allocator);
+    defer allocator.free(self_exe);
+
+    var files = std.ArrayList([]const u8){};
+    defer {
+        for (files.items) |file| allocator.free(file);
+        files.deinit(allocator);
+    }
+    for (paths) |path| {
+        const stat = std.fs.cwd().statFile(path) catch |err| {
+            std.debug.print("Error: Cannot access {s}: {any}\n", .{ path, err });
+            return false;
+        };
+        if (stat.kind == .directory) {
+            try selftest.collectPrograms(allocator, path, &files);
+        } else {
+            try files.append(allocator, try allocator.dupe(u8, path));
+        }
+    }
+    std.mem.sort([]const u8, files.items, {}, selftest.lessThan);
+
+    try std.fs.cwd().makePath(WORK_DIR);
+    defer std.fs.cwd().deleteTree(WORK_DIR) catch {};
+
+    var run_options = options;
+    if (run_options.seed == null) run_options.seed = @as(u64, @bitCast(std.time.milliTimestamp()));
+
+    var counts = Counts{};
+    var files_with_tests: usize = 0;
+    for (files.items) |file| {
+        if (try runFile(allocator, self_exe, file, run_options, &counts)) files_with_tests += 1;
+    }
+
+    if (files_with_tests == 0) {
+        std.debug.print("No @test functions found\n", .{});
+        return true;
+    }
+    std.debug.print("\ntest result: {s}. {d} passed, {d} failed, {d} skipped", .{
+        if (counts.failed == 0) "ok" else "FAILED",
+        counts.passed,
+        counts.failed,
+        counts.skipped,
+    });
+    if (counts.filtered > 0) std.debug.print(", {d} filtered out", .{counts.filtered});
+    std.debug.print("\n", .{});
+    return counts.failed == 0;
+}
+
+/// 编译并运行一个文件中的测试；文件中没有 @test 函数时返回 false
+fn runFile(allocator: std.mem.Allocator, self_exe: []const u8, file: []const u8, options: Options, counts: *Counts) !bool {
+    var arena_state = std.heap.ArenaAllocator.init(allocator);
+    defer arena_state.deinit();
+    const arena = arena_state.allocator();
+
+    const tests = discover(arena, file) catch |err| switch (err) {
+        error.OutOfMemory => return err,
+        else => {
+            // 解析失败：错误已由解析器输出
+            std.debug.print("FAIL  {s} (does not parse)\n", .{file});
+            counts.failed += 1;
+            return true;
+        },
+    };
+    if (tests.len == 0) return false;
+
+    std.debug.print("\nrunning {d} test(s) in {s} (seed {d})\n", .{ tests.len, file, options.seed.? });
+
+    const exe_path = try std.fmt.allocPrint(arena, "{s}/{s}", .{ WORK_DIR, std.fs.path.stem(file) });
+    const compile = try selftest.observe(allocator, &.{ self_exe, file, "--compile", "--backend=c", "--test", "-o", exe_path });
+    defer compile.deinit(allocator);
+    std.fs.cwd().access(exe_path, .{}) catch {
+        std.debug.print("FAIL  {s} (does not compile)\n", .{file});
+        if (compile.stderr.len > 0) std.debug.print("{s}\n", .{compile.stderr});
+        counts.failed += 1;
+        return true;
+    };
+    defer std.fs.cwd().deleteFile(exe_path) catch {};
+
+    for (tests) |test_case| {
+        if (options.filter) |filter| {
+            if (std.mem.indexOf(u8, test_case.name, filter) == null) {
+                counts.filtered += 1;
+                continue;
+            }
+        }
+        if (test_case.attr.skip) |reason| {
+            std.debug.print("SKIP  {s} ({s})\n", .{ test_case.name, reason });
+            counts.skipped += 1;
+            continue;
+        }
+
+        var seed_buf: [20]u8 = undefined;
+        const seed_arg = std.fmt.bufPrint(&seed_buf, "{d}", .{options.seed.?}) catch unreachable;
+        const result = try selftest.observe(allocator, &.{ exe_path, test_case.name, seed_arg });
+        defer result.deinit(allocator);
+        if (check(test_case, result)) {
+            if (test_case.attr.runs) |runs| {
+                std.debug.print("PASS  {s} ({d} runs)\n", .{ test_case.name, runs });
+            } else {
+                std.debug.print("PASS  {s}\n", .{test_case.name});
+            }
+            counts.passed += 1;
+        } else {
+            if (failingSeed(result.stderr)) |seed| {
+                std.debug.print("      reproduce with: pawc test {s} --filter {s} --seed {d}\n", .{ file, test_case.name, seed });
+            }
+            counts.failed += 1;
+        }
+    }
+    return true;
+}
+
+/// 解析文件，按声明顺序列出 @test 函数
+fn discover(arena: std.mem.Allocator, file: []const u8) ![]TestCase {
+    const source = try std.fs.cwd().readFileAlloc(arena, file, 10 * 1024 * 1024);
+
+    var lexer = Lexer.init(arena, source, file);
+    const tokens = try lexer.tokenize();
+    var parser = Parser.init(arena, tokens);
+    const program = try parser.parse();
+
+    var tests = std.ArrayList(TestCase){};
+    for (program.declarations) |decl| {
+        if (decl != .function) continue;
+        const attr = decl.function.test_attr orelse continue;
+        try tests.append(arena, .{ .name = decl.function.name, .attr = attr });
+    }
+    return tests.toOwnedSlice(arena);
+}
+
+/// 判断一次运行是否通过；不通过时打印原因
+fn check(test_case: TestCase, result: selftest.Observation) bool {
+    const panicked = result.exit_code == PANIC_EXIT_CODE;
+    if (test_case.attr.should_panic) |expected| {
+        if (!panicked) {
+            std.debug.print("FAIL  {s} (expected a panic, but the test {s})\n", .{
+                test_case.name,
+                if (result.exit_code == 0) "returned normally" else "exited abnormally",
+            });
+            printOutput(result);
+            return false;
+        }
+        if (std.mem.indexOf(u8, panicMessage(result.stderr), expected) == null) {
+            std.debug.print("FAIL  {s} (panic message does not contain \"{s}\")\n", .{ test_case.name, expected });
+            printOutput(result);
+            return false;
+        }
+        return true;
+    }
+
+    if (result.exit_code == 0) return true;
+    if (panicked) {
+        std.debug.print("FAIL  {s} (panicked: {s})\n", .{ test_case.name, panicMessage(result.stderr) });
+    } else if (result.exit_code) |code| {
+        std.debug.print("FAIL  {s} (exit code {d})\n", .{ test_case.name, code });
+    } else {
+        std.debug.print("FAIL  {s} (terminated by a signal)\n", .{test_case.name});
+    }
+    printOutput(result);
+    return false;
+}
+
+/// 运行时的 panic 输出是 "PANIC:\n<消息>\n"：取出消息（没有 PANIC: 行时返回整个 stderr）
+/// 🆕 property 测试在消息之后输出的种子行不属于消息
+fn panicMessage(stderr: []const u8) []const u8 {
+    const marker = "PANIC:\n";
+    const output = stderr[0 .. std.mem.lastIndexOf(u8, stderr, ast.PROPERTY_SEED_REPORT) orelse stderr.len];
+    const start = std.mem.lastIndexOf(u8, output, marker) orelse return std.mem.trimRight(u8, output, "\n");
+    return std.mem.trimRight(u8, output[start + marker.len ..], "\n");
+}
+
+/// 🆕 失败的 property 测试报告的种子
+fn failingSeed(stderr: []const u8) ?u64 {
+    const start = std.mem.lastIndexOf(u8, stderr, ast.PROPERTY_SEED_REPORT) orelse return null;
+    const rest = stderr[start + ast.PROPERTY_SEED_REPORT.len ..];
+    const end = std.mem.indexOfScalar(u8, rest, '\n') orelse rest.len;
+    return std.fmt.parseInt(u64, rest[0..end], 10) catch null;
+}
+
+fn printOutput(result: selftest.Observation) void {
+    if (result.stdout.len > 0) std.debug.print("      stdout:\n{s}\n", .{result.stdout});
+    if (result.stderr.len > 0) std.debug.print("      stderr:\n{s}\n", .{result.stderr});
+}
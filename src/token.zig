@@ -1,7 +1,10 @@
+const FileId = @import("diagnostic.zig").FileId;
+
 pub const TokenType = enum {
     // Paw 核心关键字 (19个) - 极简设计
     keyword_fn,       // 函数定义
     keyword_let,      // 变量声明
+    keyword_const,    // 🆕 编译期常量 / const fn
     keyword_type,     // 类型定义（统一 struct/enum/trait）
     keyword_import,   // 导入
     keyword_pub,      // 可见性
@@ -9,6 +12,7 @@ pub const TokenType = enum {
     keyword_else,     // 条件分支
     keyword_loop,     // 循环（统一 while/for）
     keyword_break,    // 中断
+    keyword_continue, // 🆕 继续下一次循环
     keyword_return,   // 返回
     keyword_is,       // 模式匹配（统一 match）
     keyword_as,       // 类型转换
@@ -20,6 +24,7 @@ pub const TokenType = enum {
     keyword_true,     // 布尔真
     keyword_false,    // 布尔假
     keyword_in,       // 上下文关键字（用于 loop for）
+    keyword_extern,   // 🆕 外部函数声明（extern fn）
 
     // 内置类型（Rust 风格，无别名）
     // 整数类型（有符号）
@@ -52,6 +57,9 @@ pub const TokenType = enum {
     float_literal,
     string_literal,
     char_literal,
+    byte_literal,         // 🆕 b'A'
+    byte_string_literal,  // 🆕 b"bytes"
+    label,                // 🆕 循环标签 'outer
 
     // 运算符
     plus,        // +
@@ -69,8 +77,10 @@ pub const TokenType = enum {
     
     and_and,     // &&
     or_or,       // ||
+    pipe,        // 🆕 | (或模式、lambda 参数)
     bang,        // !
     question,    // ? (错误传播)
+    at,          // 🆕 @ (属性，如 @link_name)
     
     assign,      // =
     // 🆕 复合赋值操作符
@@ -108,15 +118,15 @@ pub const Token = struct {
     lexeme: []const u8,
     line: usize,
     column: usize,
-    filename: []const u8,  // 🆕 v0.1.8: 文件名
+    file: FileId,  // 🆕 所在源文件（驻留的路径）
 
-    pub fn init(token_type: TokenType, lexeme: []const u8, line: usize, column: usize, filename: []const u8) Token {
+    pub fn init(token_type: TokenType, lexeme: []const u8, line: usize, column: usize, file: FileId) Token {
         return Token{
             .type = token_type,
             .lexeme = lexeme,
             .line = line,
             .column = column,
-            .filename = filename,
+            .file = file,
         };
     }
 };
@@ -0,0 +1,277 @@
+//! Toolchain - C 编译器（链接器）的探测与 pawc doctor
+//!
+//! C 后端把生成的代码交给 C 编译器编译并链接。可用的编译器按以下顺序自动选择：
+//!   zig cc  ->  gcc  ->  clang  ->  cc
+//! --linker <zig|gcc|clang|cc> 指定编译器；在生成代码之前检查它是否存在，
+//! 缺少时给出安装方法，并在检测到其他系统编译器时建议 --linker 回退。
+//!
+//! pawc doctor [--linker <name>] [--target <triple>] 一次检查整个工具链：
+//!   1. C 编译器（链接器）是否存在及其版本
+//!   2. 本地 LLVM 工具链（llvm/install，可选）
+//!   3. 目标的 C 运行库：编译并链接一个使用生成代码所需头文件的小程序
+//!   4. target/ 目录是否可写（构建锁、缓存和临时输出都在这里）
+
+const std = @import("std");
+const build_lock = @import("build_lock.zig");
+
+/// 本地 LLVM 工具链中的 clang（存在时 C 后端优先使用它）
+pub const LOCAL_CLANG = "llvm/install/bin/clang";
+const TARGET_DIR = "target";
+const PROBE_DIR = TARGET_DIR ++ "/doctor";
+
+pub const Linker = enum {
+    zig,
+    gcc,
+    clang,
+    cc,
+
+    /// 自动选择时的顺序
+    pub const search_order = [_]Linker{ .zig, .gcc, .clang, .cc };
+
+    pub fn fromString(name: []const u8) ?Linker {
+        return std.meta.stringToEnum(Linker, name);
+    }
+
+    /// 调用编译器的命令前缀
+    pub fn argv(self: Linker) []const []const u8 {
+        return switch (self) {
+            .zig => &.{ "zig", "cc" },
+            .gcc => &.{"gcc"},
+            .clang => &.{"clang"},
+            .cc => &.{"cc"},
+        };
+    }
+
+    pub fn displayName(self: Linker) []const u8 {
+        return switch (self) {
+            .zig => "Zig CC",
+            .gcc => "GCC",
+            .clang => "Clang",
+            .cc => "system cc",
+        };
+    }
+
+    /// 能否用 -target 交叉编译
+    pub fn canCrossCompile(self: Linker) bool {
+        return self == .zig or self == .clang;
+    }
+
+    pub fn installHint(self: Linker) []const u8 {
+        return switch (self) {
+            .zig => "install Zig 0.15 from https://ziglang.org/download/ and make sure `zig` is on PATH",
+            .gcc => "Linux: sudo apt-get install gcc   macOS: brew install gcc",
+            .clang => "Linux: sudo apt-get install clang   macOS: xcode-select --install",
+            .cc => "install a system C compiler (Linux: sudo apt-get install build-essential   macOS: xcode-select --install)",
+        };
+    }
+};
+
+/// 运行 `<linker> --version`：存在时返回输出的第一行（调用者释放），不存在时返回 null
+pub fn probe(allocator: std.mem.Allocator, linker: Linker) !?[]u8 {
+    var argv = std.ArrayList([]const u8){};
+    defer argv.deinit(allocator);
+    try argv.appendSlice(allocator, linker.argv());
+    try argv.append(allocator, "--version");
+
+    const result = std.process.Child.run(.{ .allocator = allocator, .argv = argv.items }) catch return null;
+    defer allocator.free(result.stdout);
+    defer allocator.free(result.stderr);
+    if (result.term != .Exited or result.term.Exited != 0) return null;
+
+    // gcc 把版本写到 stdout，个别编译器写到 stderr
+    const text = if (result.stdout.len > 0) result.stdout else result.stderr;
+    var lines = std.mem.splitScalar(u8, text, '\n');
+    return try allocator.dupe(u8, std.mem.trim(u8, lines.first(), " \r\t"));
+}
+
+fn isAvailable(allocator: std.mem.Allocator, linker: Linker) !bool {
+    const version = try probe(allocator, linker) orelse return false;
+    allocator.free(version);
+    return true;
+}
+
+/// 按顺序查找第一个可用的编译器（跳过 except）
+fn firstAvailable(allocator: std.mem.Allocator, except: ?Linker) !?Linker {
+    for (Linker.search_order) |linker| {
+        if (except != null and except.? == linker) continue;
+        if (try isAvailable(allocator, linker)) return linker;
+    }
+    return null;
+}
+
+/// 选择编译 C 代码用的编译器：requested 为 --linker 指定的编译器，null 为自动选择。
+/// 找不到时打印安装方法和可用的回退，返回 null
+pub fn select(allocator: std.mem.Allocator, requested: ?Linker) !?Linker {
+    if (requested) |linker| {
+        if (try isAvailable(allocator, linker)) return linker;
+        reportMissing(linker, try firstAvailable(allocator, linker));
+        return null;
+    }
+    if (try firstAvailable(allocator, null)) |linker| return linker;
+    reportMissing(null, null);
+    return null;
+}
+
+fn reportMissing(requested: ?Linker, fallback: ?Linker) void {
+    if (requested) |linker| {
+        std.debug.print("❌ Error: linker '{s}' not found (needed to compile and link the generated C code)\n", .{linker.argv()[0]});
+        std.debug.print("💡 To install it: {s}\n", .{linker.installHint()});
+        if (fallback) |other| {
+            std.debug.print("💡 {s} is available: rerun with --linker {s}\n", .{ other.displayName(), @tagName(other) });
+        }
+    } else {
+        std.debug.print("❌ Error: no C compiler found (tried zig cc, gcc, clang, cc)\n", .{});
+        std.debug.print("💡 Install one of them:\n", .{});
+        for (Linker.search_order) |linker| {
+            std.debug.print("   • {s}: {s}\n", .{ linker.displayName(), linker.installHint() });
+        }
+    }
+    std.debug.print("💡 Run `pawc doctor` to check the whole toolchain\n", .{});
+}
+
+/// 本地 LLVM 工具链是否存在
+pub fn hasLocalClang() bool {
+    std.fs.cwd().access(LOCAL_CLANG, .{}) catch return false;
+    return true;
+}
+
+pub const DoctorOptions = struct {
+    linker: ?Linker = null,         // 只检查指定的编译器
+    target: ?[]const u8 = null,     // 检查该目标的 C 运行库（默认本机）
+};
+
+const Status = enum {
+    ok,
+    absent,  // 可选项不存在
+    fail,
+
+    fn icon(self: Status) []const u8 {
+        return switch (self) {
+            .ok => "✅",
+            .absent => "➖",
+            .fail => "❌",
+        };
+    }
+};
+
+/// 检查整个工具链并打印结果；没有失败的检查时返回 true
+pub fn doctor(allocator: std.mem.Allocator, options: DoctorOptions) !bool {
+    var failed = false;
+    std.debug.print("🩺 Checking the Paw toolchain\n\n", .{});
+
+    // 1. C 编译器（链接器）
+    std.debug.print("C compiler / linker:\n", .{});
+    var selected: ?Linker = null;
+    for (Linker.search_order) |linker| {
+        if (options.linker != null and options.linker.? != linker) continue;
+        if (try probe(allocator, linker)) |version| {
+            defer allocator.free(version);
+            const marker = if (selected == null) "  (selected)" else "";
+            std.debug.print("  {s} {s}: {s}{s}\n", .{ Status.ok.icon(), linker.displayName(), version, marker });
+            if (selected == null) selected = linker;
+        } else {
+            const status: Status = if (options.linker != null) .fail else .absent;
+            std.debug.print("  {s} {s}: not found\n", .{ status.icon(), linker.displayName() });
+        }
+    }
+    if (selected == null) {
+        failed = true;
+        if (options.linker) |linker| {
+            std.debug.print("  💡 To install it: {s}\n", .{linker.installHint()});
+        } else {
+            std.debug.print("  💡 Install a C compiler: {s}\n", .{Linker.zig.installHint()});
+        }
+    }
+
+    // 2. 本地 LLVM 工具链（可选）
+    std.debug.print("\nLocal LLVM toolchain:\n", .{});
+    if (hasLocalClang()) {
+        std.debug.print("  {s} {s}\n", .{ Status.ok.icon(), LOCAL_CLANG });
+    } else {
+        std.debug.print("  ℹ️  {s} not found (optional: only used for faster C builds and the LLVM workflow)\n", .{LOCAL_CLANG});
+    }
+
+    // 3. 目标的 C 运行库
+    const target_name = options.target orelse "native";
+    std.debug.print("\nRuntime libraries ({s}):\n", .{target_name});
+    if (selected) |linker| {
+        if (!try checkRuntime(allocator, linker, options.target)) failed = true;
+    } else {
+        std.debug.print("  {s} skipped: no C compiler\n", .{Status.fail.icon()});
+    }
+
+    // 4. target/ 目录
+    std.debug.print("\nBuild directory:\n", .{});
+    if (checkWritable(allocator)) |_| {
+        std.debug.print("  {s} {s}/ is writable\n", .{ Status.ok.icon(), TARGET_DIR });
+    } else |err| {
+        failed = true;
+        std.debug.print("  {s} cannot write to {s}/: {s}\n", .{ Status.fail.icon(), TARGET_DIR, @errorName(err) });
+        std.debug.print("  💡 Check the directory's permissions, or run pawc from a writable project directory\n", .{});
+    }
+
+    std.debug.print("\n", .{});
+    if (failed) {
+        std.debug.print("❌ Some checks failed\n", .{});
+    } else {
+        std.debug.print("✅ Toolchain is ready\n", .{});
+    }
+    return !failed;
+}
+
+/// 编译并链接一个包含生成代码所用头文件的程序
+fn checkRuntime(allocator: std.mem.Allocator, linker: Linker, target: ?[]const u8) !bool {
+    if (target != null and !linker.canCrossCompile()) {
+        std.debug.print("  {s} {s} cannot compile for other targets\n", .{ Status.fail.icon(), linker.displayName() });
+        std.debug.print("  💡 Use --linker zig (or clang) to cross-compile\n", .{});
+        return false;
+    }
+
+    const source =
+        \\#include <stdio.h>
+        \\#include <stdlib.h>
+        \\#include <stdint.h>
+        \\#include <stdbool.h>
+        \\#include <string.h>
+        \\#include <float.h>
+        \\#include <time.h>
+        \\#ifdef _WIN32
+        \\#include <io.h>
+        \\#else
+        \\#include <unistd.h>
+        \\#endif
+        \\int main(void) { printf("%s", ""); return (int)strlen(""); }
+        \\
+    ;
+    try std.fs.cwd().makePath(PROBE_DIR);
+    defer std.fs.cwd().deleteTree(PROBE_DIR) catch {};
+    try std.fs.cwd().writeFile(.{ .sub_path = PROBE_DIR ++ "/probe.c", .data = source });
+
+    var argv = std.ArrayList([]const u8){};
+    defer argv.deinit(allocator);
+    try argv.appendSlice(allocator, linker.argv());
+    if (target) |triple| try argv.appendSlice(allocator, &.{ "-target", triple });
+    try argv.appendSlice(allocator, &.{ PROBE_DIR ++ "/probe.c", "-o", PROBE_DIR ++ "/probe" });
+
+    const result = std.process.Child.run(.{ .allocator = allocator, .argv = argv.items }) catch |err| {
+        std.debug.print("  {s} cannot run {s}: {s}\n", .{ Status.fail.icon(), linker.argv()[0], @errorName(err) });
+        return false;
+    };
+    defer allocator.free(result.stdout);
+    defer allocator.free(result.stderr);
+    if (result.term != .Exited or result.term.Exited != 0) {
+        std.debug.print("  {s} compiling and linking a C program failed:\n{s}\n", .{ Status.fail.icon(), result.stderr });
+        std.debug.print("  💡 Install the C library headers and startup files for this target (Linux: libc6-dev)\n", .{});
+        return false;
+    }
+    std.debug.print("  {s} C library headers and startup files found (using {s})\n", .{ Status.ok.icon(), linker.displayName() });
+    return true;
+}
+
+/// 在 target/ 中原子地写入并删除一个文件
+fn checkWritable(allocator: std.mem.Allocator) !void {
+    try std.fs.cwd().makePath(TARGET_DIR);
+    const path = TARGET_DIR ++ "/.doctor-probe";
+    try build_lock.writeFileAtomic(allocator, path, &.{"ok\n"});
+    try std.fs.cwd().deleteFile(path);
+}
@@ -0,0 +1,208 @@
+//! Trace - 执行跟踪（--instrument=trace）与 pawc trace view
+//!
+//! 使用 --instrument=trace 编译的程序在每个函数进入和离开时调用
+//! paw_trace_enter(fn_id) / paw_trace_exit(fn_id)，运行时把事件写入
+//! $PAW_TRACE_FILE（默认 paw-trace.bin）。文件格式（整数均为小端）：
+//!
+//!   "PAWTRACE" u32 版本
+//!   事件      u8 kind(0 进入 / 1 离开) u32 fn_id u64 时间(ns)
+//!   函数名    u8 kind(2) u32 fn_id u32 长度 名字（程序结束时追加）
+//!
+//! pawc trace view [file] 输出折叠栈（flamegraph.pl / speedscope / inferno 的输入）：
+//!   main;fib;fib 1200
+//! 每行的数值是该调用栈的自身时间（ns）。程序经 panic 等方式提前退出时，
+//! 未离开的函数在最后一个事件处结束。
+
+const std = @import("std");
+
+pub const MAGIC = "PAWTRACE";
+pub const FORMAT_VERSION: u32 = 1;
+pub const FILE_ENV = "PAW_TRACE_FILE";
+pub const DEFAULT_FILE = "paw-trace.bin";
+
+pub const EventKind = enum(u8) {
+    enter = 0,
+    exit = 1,
+    name = 2,
+};
+
+const Event = struct {
+    kind: EventKind,
+    fn_id: u32,
+    time_ns: u64,
+};
+
+const Frame = struct {
+    fn_id: u32,
+    start_ns: u64,
+    child_ns: u64,
+};
+
+const TraceError = error{ InvalidTrace, UnsupportedVersion };
+
+/// 读取跟踪文件并把折叠栈写到 stdout；出错时返回 false
+pub fn view(allocator: std.mem.Allocator, path: []const u8) !bool {
+    const data = std.fs.cwd().readFileAlloc(allocator, path, 1024 * 1024 * 1024) catch |err| {
+        std.debug.print("❌ Cannot read {s}: {s}\n", .{ path, @errorName(err) });
+        std.debug.print("💡 Build with --instrument=trace and run the program to record a trace\n", .{});
+        return false;
+    };
+    defer allocator.free(data);
+
+    const folded = fold(allocator, data) catch |err| switch (err) {
+        error.InvalidTrace => {
+            std.debug.print("❌ {s} is not a valid paw trace (truncated or corrupted)\n", .{path});
+            return false;
+        },
+        error.UnsupportedVersion => {
+            std.debug.print("❌ {s} was written by a different pawc version\n", .{path});
+            return false;
+        },
+        else => |e| return e,
+    };
+    defer allocator.free(folded);
+
+    try std.fs.File.stdout().writeAll(folded);
+    return true;
+}
+
+/// 跟踪数据 -> 折叠栈文本（按调用栈排序，调用者释放）
+pub fn fold(allocator: std.mem.Allocator, data: []const u8) (std.mem.Allocator.Error || TraceError)![]u8 {
+    var reader = Reader{ .data = data };
+    if (!std.mem.eql(u8, try reader.bytes(MAGIC.len), MAGIC)) return error.InvalidTrace;
+    if (try reader.int(u32) != FORMAT_VERSION) return error.UnsupportedVersion;
+
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+    const scratch = arena.allocator();
+
+    var events = std.ArrayList(Event){};
+    var names = std.AutoHashMap(u32, []const u8).init(scratch);
+    while (reader.pos < data.len) {
+        const kind = std.meta.intToEnum(EventKind, try reader.int(u8)) catch return error.InvalidTrace;
+        const fn_id = try reader.int(u32);
+        switch (kind) {
+            .enter, .exit => try events.append(scratch, .{ .kind = kind, .fn_id = fn_id, .time_ns = try reader.int(u64) }),
+            .name => {
+                const len = try reader.int(u32);
+                try names.put(fn_id, try reader.bytes(len));
+            },
+        }
+    }
+
+    // 自身时间 = 总时间 - 直接调用的函数的总时间
+    var stacks = std.StringHashMap(u64).init(scratch);
+    var frames = std.ArrayList(Frame){};
+    var last_ns: u64 = 0;
+    events: for (events.items) |event| {
+        last_ns = event.time_ns;
+        switch (event.kind) {
+            .enter => try frames.append(scratch, .{ .fn_id = event.fn_id, .start_ns = event.time_ns, .child_ns = 0 }),
+            .exit => {
+                // 没有对应进入事件的离开（不应出现）忽略；不匹配时先结束更内层的函数
+                const depth = for (0..frames.items.len) |i| {
+                    const index = frames.items.len - 1 - i;
+                    if (frames.items[index].fn_id == event.fn_id) break index;
+                } else continue :events;
+                while (frames.items.len > depth) try popFrame(scratch, &frames, &stacks, &names, event.time_ns);
+            },
+            .name => unreachable,
+        }
+    }
+    while (frames.items.len > 0) try popFrame(scratch, &frames, &stacks, &names, last_ns);
+
+    var keys = std.ArrayList([]const u8){};
+    var it = stacks.keyIterator();
+    while (it.next()) |key| try keys.append(scratch, key.*);
+    std.mem.sort([]const u8, keys.items, {}, struct {
+        fn lessThan(_: void, a: []const u8, b: []const u8) bool {
+            return std.mem.order(u8, a, b) == .lt;
+        }
+    }.lessThan);
+
+    var out = std.ArrayList(u8){};
+    errdefer out.deinit(allocator);
+    for (keys.items) |key| {
+        try out.writer(allocator).print("{s} {d}\n", .{ key, stacks.get(key).? });
+    }
+    return out.toOwnedSlice(allocator);
+}
+
+/// 结束最内层的函数：自身时间累加到它的调用栈，总时间计入调用者
+fn popFrame(
+    scratch: std.mem.Allocator,
+    frames: *std.ArrayList(Frame),
+    stacks: *std.StringHashMap(u64),
+    names: *const std.AutoHashMap(u32, []const u8),
+    end_ns: u64,
+) !void {
+    const frame = frames.items[frames.items.len - 1];
+    const total = end_ns -| frame.start_ns;
+
+    var key = std.ArrayList(u8){};
+    for (frames.items, 0..) |f, i| {
+        if (i > 0) try key.append(scratch, ';');
+        if (names.get(f.fn_id)) |name| {
+            try key.appendSlice(scratch, name);
+        } else {
+            try key.writer(scratch).print("fn#{d}", .{f.fn_id});
+        }
+    }
+    const entry = try stacks.getOrPut(key.items);
+    if (!entry.found_existing) entry.value_ptr.* = 0;
+    entry.value_ptr.* += total -| frame.child_ns;
+
+    frames.items.len -= 1;
+    if (frames.items.len > 0) frames.items[frames.items.len - 1].child_ns += total;
+}
+
+const Reader = struct {
+    data: []const u8,
+    pos: usize = 0,
+
+    fn bytes(self: *Reader, len: usize) TraceError![]const u8 {
+        if (self.data.len - self.pos < len) return error.InvalidTrace;
+        defer self.pos += len;
+        return self.data[self.pos .. self.pos + len];
+    }
+
+    fn int(self: *Reader, comptime T: type) TraceError!T {
+        const raw = try self.bytes(@sizeOf(T));
+        return std.mem.readInt(T, raw[0..@sizeOf(T)], .little);
+    }
+};
+
+test "fold: self time per call stack" {
+    const allocator = std.testing.allocator;
+    var data = std.ArrayList(u8){};
+    defer data.deinit(allocator);
+    const w = data.writer(allocator);
+    try w.writeAll(MAGIC);
+    try w.writeInt(u32, FORMAT_VERSION, .little);
+    // main(0) 0..100：leaf(1) 10..30 和 40..50；第二次 leaf 之后 panic，main 没有离开事件
+    const events = [_]struct { EventKind, u32, u64 }{
+        .{ .enter, 0, 0 }, .{ .enter, 1, 10 }, .{ .exit, 1, 30 },
+        .{ .enter, 1, 40 }, .{ .exit, 1, 50 },  .{ .enter, 2, 60 },
+        .{ .exit, 0, 100 },
+    };
+    for (events) |event| {
+        try w.writeInt(u8, @intFromEnum(event[0]), .little);
+        try w.writeInt(u32, event[1], .little);
+        try w.writeInt(u64, event[2], .little);
+    }
+    // fn 2 没有名字（显示为 fn#2）
+    for ([_][]const u8{ "main", "leaf" }, 0..) |name, id| {
+        try w.writeInt(u8, @intFromEnum(EventKind.name), .little);
+        try w.writeInt(u32, @intCast(id), .little);
+        try w.writeInt(u32, @intCast(name.len), .little);
+        try w.writeAll(name);
+    }
+
+    const folded = try fold(allocator, data.items);
+    defer allocator.free(folded);
+    // main 离开时先结束没有离开事件的 fn#2（60..100）
+    try std.testing.expectEqualStrings("main 30\nmain;fn#2 40\nmain;leaf 30\n", folded);
+
+    try std.testing.expectError(error.InvalidTrace, fold(allocator, data.items[0..MAGIC.len + 6]));
+    try std.testing.expectError(error.InvalidTrace, fold(allocator, "NOTTRACE\x01\x00\x00\x00"));
+}
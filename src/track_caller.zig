@@ -0,0 +1,210 @@
+//! TrackCaller - @track_caller 函数的调用位置参数
+//!
+//! @track_caller 函数中的 __FILE__ / __LINE__ 是调用处的位置，而不是函数体中的位置，
+//! 断言一类的辅助函数因此可以报告是哪一次调用失败。解析结束后、类型检查之前原地改写 AST：
+//!   - @track_caller 函数末尾加两个隐藏参数 __caller_file: string, __caller_line: i32
+//!     （函数体中的 __FILE__ / __LINE__ 已由解析器展开为这两个参数）
+//!   - 调用这样的函数时，在实参末尾传入调用处的位置（call.location）；
+//!     调用者自己也是 @track_caller 函数时传入它收到的位置，位置一直传到最外层的调用处
+//!
+//! 改写过的调用 location 置为 null，加过参数的函数不再加，所以本遍可以重复运行：
+//! 解析结束时改写本文件内的调用，合并导入声明后再运行一次，改写对导入函数的调用（见 main.zig）。
+//! 之后的阶段把隐藏参数当作普通参数处理。@track_caller 函数只能直接调用（不能作为值使用）。
+//!
+//! 示例：
+//!   @track_caller
+//!   fn expect(ok: bool) { if !ok { let line = __LINE__; println("failed at line $line"); } }
+//!   expect(1 > 2);   // 改写为 expect(1 > 2, "main.paw", 5)
+
+const std = @import("std");
+const ast = @import("ast.zig");
+
+/// 改写程序中的 @track_caller 函数和对它们的调用（allocator 应与 AST 同生命周期）
+pub fn lowerProgram(allocator: std.mem.Allocator, program: ast.Program) std.mem.Allocator.Error!void {
+    var lowering = Lowering.init(allocator);
+    defer lowering.deinit();
+    try lowering.lowerProgram(program);
+}
+
+const Lowering = struct {
+    allocator: std.mem.Allocator,
+    tracked: std.StringHashMap(void),   // @track_caller 函数名
+    locals: std.StringHashMap(void),    // 当前函数的参数和局部变量（遮蔽同名函数）
+    propagate: bool = false,            // 当前函数是 @track_caller：传入它收到的位置
+
+    fn init(allocator: std.mem.Allocator) Lowering {
+        return Lowering{
+            .allocator = allocator,
+            .tracked = std.StringHashMap(void).init(allocator),
+            .locals = std.StringHashMap(void).init(allocator),
+        };
+    }
+
+    fn deinit(self: *Lowering) void {
+        self.tracked.deinit();
+        self.locals.deinit();
+    }
+
+    fn lowerProgram(self: *Lowering, program: ast.Program) std.mem.Allocator.Error!void {
+        for (program.declarations) |*decl| {
+            if (decl.* != .function or !decl.function.track_caller) continue;
+            try self.tracked.put(decl.function.name, {});
+            try self.addLocationParams(&decl.function);
+        }
+        if (self.tracked.count() == 0) return;
+
+        for (program.declarations) |*decl| {
+            switch (decl.*) {
+                .function => |*func| try self.lowerFunction(func),
+                .type_decl => |*td| switch (td.kind) {
+                    .struct_type => |st| for (st.methods) |*method| try self.lowerFunction(method),
+                    .enum_type => |et| for (et.methods) |*method| try self.lowerFunction(method),
+                    else => {},
+                },
+                .const_decl => |*cd| {
+                    self.locals.clearRetainingCapacity();
+                    self.propagate = false;
+                    try self.lowerExpr(cd.value);
+                },
+                else => {},
+            }
+        }
+    }
+
+    /// 在参数末尾加上 __caller_file 和 __caller_line（已经加过时不变）
+    fn addLocationParams(self: *Lowering, func: *ast.FunctionDecl) std.mem.Allocator.Error!void {
+        if (func.params.len > 0 and std.mem.eql(u8, func.params[func.params.len - 1].name, ast.CALLER_LINE_PARAM)) return;
+        const params = try self.allocator.alloc(ast.Param, func.params.len + 2);
+        @memcpy(params[0..func.params.len], func.params);
+        params[func.params.len] = .{ .name = ast.CALLER_FILE_PARAM, .type = .string, .is_mut = false };
+        params[func.params.len + 1] = .{ .name = ast.CALLER_LINE_PARAM, .type = .i32, .is_mut = false };
+        func.params = params;
+    }
+
+    fn lowerFunction(self: *Lowering, func: *ast.FunctionDecl) std.mem.Allocator.Error!void {
+        self.locals.clearRetainingCapacity();
+        self.propagate = func.track_caller;
+        for (func.params) |param| try self.locals.put(param.name, {});
+        try self.lowerStmts(func.body);
+    }
+
+    fn lowerStmts(self: *Lowering, stmts: []ast.Stmt) std.mem.Allocator.Error!void {
+        for (stmts) |*stmt| try self.lowerStmt(stmt);
+    }
+
+    fn lowerStmt(self: *Lowering, stmt: *ast.Stmt) std.mem.Allocator.Error!void {
+        switch (stmt.*) {
+            .expr => |*expr| try self.lowerExpr(expr),
+            .let_decl => |*let| {
+                if (let.init) |*init| try self.lowerExpr(init);
+                try self.locals.put(let.name, {});
+            },
+            .assign => |*assign| {
+                try self.lowerExpr(&assign.target);
+                try self.lowerExpr(&assign.value);
+            },
+            .compound_assign => |*assign| {
+                try self.lowerExpr(&assign.target);
+                try self.lowerExpr(&assign.value);
+            },
+            .return_stmt => |*value| if (value.*) |*expr| try self.lowerExpr(expr),
+            .break_stmt => |*brk| if (brk.value) |*expr| try self.lowerExpr(expr),
+            .continue_stmt => {},
+            .loop_stmt => |*loop| {
+                if (loop.condition) |*cond| try self.lowerExpr(cond);
+                if (loop.iterator) |*iter| try self.lowerExpr(&iter.iterable);
+                try self.lowerStmts(loop.body);
+            },
+            .while_loop => |*loop| {
+                try self.lowerExpr(&loop.condition);
+                try self.lowerStmts(loop.body);
+            },
+            .for_loop => |*loop| {
+                if (loop.init) |init| try self.lowerStmt(init);
+                if (loop.condition) |*cond| try self.lowerExpr(cond);
+                if (loop.step) |*step| try self.lowerExpr(step);
+                try self.lowerStmts(loop.body);
+            },
+        }
+    }
+
+    fn lowerExpr(self: *Lowering, expr: *ast.Expr) std.mem.Allocator.Error!void {
+        switch (expr.*) {
+            .binary => |bin| {
+                try self.lowerExpr(bin.left);
+                try self.lowerExpr(bin.right);
+            },
+            .unary => |un| try self.lowerExpr(un.operand),
+            .call => |*call| {
+                try self.lowerExpr(call.callee);
+                for (call.args) |*arg| try self.lowerExpr(arg);
+                try self.lowerCall(call);
+            },
+            .static_method_call => |call| for (call.args) |*arg| try self.lowerExpr(arg),
+            .field_access => |access| try self.lowerExpr(access.object),
+            .struct_init => |init| for (init.fields) |*field| try self.lowerExpr(&field.value),
+            .enum_variant => |variant| for (variant.args) |*arg| try self.lowerExpr(arg),
+            .block => |stmts| try self.lowerStmts(stmts),
+            .if_expr => |if_expr| {
+                try self.lowerExpr(if_expr.condition);
+                try self.lowerExpr(if_expr.then_branch);
+                if (if_expr.else_branch) |else_branch| try self.lowerExpr(else_branch);
+            },
+            .is_expr => |is_expr| {
+                try self.lowerExpr(is_expr.value);
+                for (is_expr.arms) |*arm| {
+                    if (arm.guard) |*guard| try self.lowerExpr(guard);
+                    try self.lowerExpr(&arm.body);
+                }
+            },
+            .match_expr => |match_expr| {
+                try self.lowerExpr(match_expr.value);
+                for (match_expr.arms) |*arm| try self.lowerExpr(&arm.body);
+            },
+            .as_expr => |as_expr| try self.lowerExpr(as_expr.value),
+            .await_expr => |inner| try self.lowerExpr(inner),
+            .array_literal => |elements| for (elements) |*element| try self.lowerExpr(element),
+            .array_index => |index| {
+                try self.lowerExpr(index.array);
+                try self.lowerExpr(index.index);
+            },
+            .range => |range| {
+                try self.lowerExpr(range.start);
+                try self.lowerExpr(range.end);
+            },
+            .string_interp => |interp| for (interp.parts) |*part| switch (part.*) {
+                .expr => |*inner| try self.lowerExpr(inner),
+                .literal => {},
+            },
+            .try_expr => |inner| try self.lowerExpr(inner),
+            .loop_expr => |loop| try self.lowerStmts(loop.body),
+            .lambda => |lambda| {
+                // lambda 体中使用的隐藏参数由闭包捕获
+                for (lambda.params) |param| try self.locals.put(param.name, {});
+                try self.lowerStmts(lambda.body);
+            },
+            .int_literal, .float_literal, .string_literal, .char_literal, .byte_literal,
+            .byte_string_literal, .bool_literal, .identifier, .type_query => {},
+        }
+    }
+
+    /// 直接调用 @track_caller 函数：实参末尾加上调用处的位置
+    fn lowerCall(self: *Lowering, call: anytype) std.mem.Allocator.Error!void {
+        const location = call.location orelse return;
+        if (call.callee.* != .identifier) return;
+        const name = call.callee.identifier;
+        if (!self.tracked.contains(name) or self.locals.contains(name)) return;
+
+        const args = try self.allocator.alloc(ast.Expr, call.args.len + 2);
+        @memcpy(args[0..call.args.len], call.args);
+        if (self.propagate) {
+            args[call.args.len] = .{ .identifier = ast.CALLER_FILE_PARAM };
+            args[call.args.len + 1] = .{ .identifier = ast.CALLER_LINE_PARAM };
+        } else {
+            args[call.args.len] = .{ .string_literal = try ast.fileLiteral(self.allocator, location.file) };
+            args[call.args.len + 1] = .{ .int_literal = .{ .value = @intCast(location.line) } };
+        }
+        call.args = args;
+        call.location = null;
+    }
+};
@@ -0,0 +1,402 @@
+//! TypeAlias - 类型别名展开（type Name = Ty; / type Name<T> = Ty;）
+//!
+//! 解析结束后、编译期求值和类型检查之前运行，原地改写 AST：
+//!   - 所有出现类型的位置（参数、返回值、let、结构体字段、枚举变体、trait 签名、
+//!     const、as、类型实参、size_of<T>() 等）中的别名替换为目标类型
+//!   - 泛型别名按类型实参替换类型参数：Grid<i32> → [[i32; 3]; 3]
+//!   - 表达式中作为名字出现的别名（Name { ... }、Name::method()）改写为目标类型名
+//!   - 🆕 具体类型上的关联类型投影按类型中的 `type Item = ...;` 展开：Counter::Item → i32，
+//!     结构体和枚举的方法中 Self::Item 是本类型的关联类型；类型参数上的投影（T::Item）
+//!     保留到单态化时确定
+//!
+//! 之后的阶段看不到别名。别名声明本身保留在程序中（可以 pub / import），
+//! 其他阶段忽略它；导入的别名在合并导入声明后再展开一次（见 main.zig）。
+//!
+//! 示例：
+//!   type Meters = f64;
+//!   type Grid<T> = [[T; 3]; 3];
+//!   fn area(w: Meters, h: Meters) -> Meters { return w * h; }
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const diagnostic = @import("diagnostic.zig");
+const Diagnostic = diagnostic.Diagnostic;
+const Span = diagnostic.Span;
+
+pub const Error = std.mem.Allocator.Error || error{TypeAliasFailed};
+
+/// 展开程序中的所有类型别名（allocator 应与 AST 同生命周期）
+pub fn expandProgram(allocator: std.mem.Allocator, program: ast.Program) Error!void {
+    var expander = Expander.init(allocator);
+    defer expander.deinit();
+    try expander.expandProgram(program);
+}
+
+const Expander = struct {
+    allocator: std.mem.Allocator,
+    aliases: std.StringHashMap(*ast.TypeDecl),
+    resolved: std.StringHashMap(void),         // 目标类型已展开的别名
+    in_progress: std.ArrayList([]const u8),    // 正在展开的别名（检测循环）
+    owners: std.StringHashMap(*ast.TypeDecl),  // 🆕 有关联类型的 struct / enum
+    self_type: ?*ast.TypeDecl = null,          // 🆕 正在展开其成员的 struct / enum（Self）
+
+    fn init(allocator: std.mem.Allocator) Expander {
+        return Expander{
+            .allocator = allocator,
+            .aliases = std.StringHashMap(*ast.TypeDecl).init(allocator),
+            .owners = std.StringHashMap(*ast.TypeDecl).init(allocator),
+            .resolved = std.StringHashMap(void).init(allocator),
+            .in_progress = std.ArrayList([]const u8){},
+        };
+    }
+
+    fn deinit(self: *Expander) void {
+        self.aliases.deinit();
+        self.owners.deinit();
+        self.resolved.deinit();
+        self.in_progress.deinit(self.allocator);
+    }
+
+    fn expandProgram(self: *Expander, program: ast.Program) Error!void {
+        for (program.declarations) |*decl| {
+            if (decl.* != .type_decl) continue;
+            switch (decl.type_decl.kind) {
+                .alias => try self.aliases.put(decl.type_decl.name, &decl.type_decl),
+                .struct_type => |st| if (st.assoc_types.len > 0) try self.owners.put(decl.type_decl.name, &decl.type_decl),
+                .enum_type => |et| if (et.assoc_types.len > 0) try self.owners.put(decl.type_decl.name, &decl.type_decl),
+                .trait_type => {},
+            }
+        }
+        if (self.aliases.count() == 0 and self.owners.count() == 0) return;
+
+        // 先展开别名自身（报告循环）和关联类型，再展开使用处
+        var it = self.aliases.valueIterator();
+        while (it.next()) |td| try self.resolveAlias(td.*);
+        var owner_it = self.owners.valueIterator();
+        while (owner_it.next()) |td| {
+            self.self_type = td.*;
+            defer self.self_type = null;
+            const assoc_types = switch (td.*.kind) {
+                .struct_type => |st| st.assoc_types,
+                .enum_type => |et| et.assoc_types,
+                else => unreachable,
+            };
+            for (assoc_types) |*assoc| try self.expandType(&assoc.type);
+        }
+
+        for (program.declarations) |*decl| {
+            switch (decl.*) {
+                .function => |*func| try self.expandFunction(func),
+                .type_decl => |*td| switch (td.kind) {
+                    .struct_type => |st| {
+                        self.self_type = td;
+                        defer self.self_type = null;
+                        for (st.fields) |*field| try self.expandType(&field.type);
+                        for (st.methods) |*method| try self.expandFunction(method);
+                    },
+                    .enum_type => |et| {
+                        self.self_type = td;
+                        defer self.self_type = null;
+                        for (et.variants) |variant| {
+                            for (variant.fields) |*field| try self.expandType(field);
+                        }
+                        for (et.methods) |*method| try self.expandFunction(method);
+                    },
+                    .trait_type => |tt| {
+                        for (tt.methods) |*sig| {
+                            for (sig.params) |*param| try self.expandType(&param.type);
+                            try self.expandType(&sig.return_type);
+                        }
+                    },
+                    .alias => {},
+                },
+                .const_decl => |*cd| {
+                    if (cd.type) |*t| try self.expandType(t);
+                    try self.expandExpr(cd.value);
+                },
+                else => {},
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // 别名
+    // ------------------------------------------------------------------------
+
+    /// 展开别名的目标类型（其中引用的其他别名）
+    fn resolveAlias(self: *Expander, td: *ast.TypeDecl) Error!void {
+        if (self.resolved.contains(td.name)) return;
+        for (self.in_progress.items, 0..) |name, i| {
+            if (!std.mem.eql(u8, name, td.name)) continue;
+            var chain = std.ArrayList(u8){};
+            for (self.in_progress.items[i..]) |step| {
+                try chain.writer(self.allocator).print("'{s}' -> ", .{step});
+            }
+            try chain.writer(self.allocator).print("'{s}'", .{td.name});
+            const note = try std.fmt.allocPrint(self.allocator, "cycle: {s}", .{chain.items});
+            return self.fail(td, "type alias '{s}' refers to itself", .{td.name}, &.{note}, "an alias must eventually name a concrete type; use a struct or enum for recursive types");
+        }
+
+        try self.in_progress.append(self.allocator, td.name);
+        defer _ = self.in_progress.pop();
+        try self.expandType(&td.kind.alias);
+        try self.resolved.put(td.name, {});
+    }
+
+    /// 别名的目标类型，类型参数替换为 type_args（返回新的副本）
+    fn instantiate(self: *Expander, td: *ast.TypeDecl, type_args: []const ast.Type) Error!ast.Type {
+        try self.resolveAlias(td);
+        if (type_args.len != td.type_params.len) {
+            return self.fail(td, "type alias '{s}' expects {d} type argument(s), found {d}", .{ td.name, td.type_params.len, type_args.len }, &.{}, null);
+        }
+        return self.substitute(td.kind.alias, td.type_params, type_args);
+    }
+
+    /// 复制类型，把类型参数 params[i] 替换为 args[i]
+    fn substitute(self: *Expander, t: ast.Type, params: []const []const u8, args: []const ast.Type) Error!ast.Type {
+        return switch (t) {
+            .named, .generic => |name| blk: {
+                for (params, 0..) |param, i| {
+                    if (std.mem.eql(u8, param, name)) break :blk try self.substitute(args[i], &.{}, &.{});
+                }
+                break :blk t;
+            },
+            .pointer => |inner| ast.Type{ .pointer = try self.create(try self.substitute(inner.*, params, args)) },
+            .array => |arr| ast.Type{ .array = .{
+                .element = try self.create(try self.substitute(arr.element.*, params, args)),
+                .size = arr.size,
+                .size_expr = arr.size_expr,
+            } },
+            .function => |func| blk: {
+                const func_params = try self.allocator.alloc(ast.Type, func.params.len);
+                for (func.params, 0..) |param, i| func_params[i] = try self.substitute(param, params, args);
+                break :blk ast.Type{ .function = .{
+                    .params = func_params,
+                    .return_type = try self.create(try self.substitute(func.return_type.*, params, args)),
+                } };
+            },
+            .generic_instance => |gi| blk: {
+                const inst_args = try self.allocator.alloc(ast.Type, gi.type_args.len);
+                for (gi.type_args, 0..) |arg, i| inst_args[i] = try self.substitute(arg, params, args);
+                break :blk ast.Type{ .generic_instance = .{ .name = gi.name, .type_args = inst_args } };
+            },
+            .projection => |proj| ast.Type{ .projection = .{
+                .base = try self.create(try self.substitute(proj.base.*, params, args)),
+                .trait_name = proj.trait_name,
+                .name = proj.name,
+            } },
+            else => t,
+        };
+    }
+
+    fn create(self: *Expander, t: ast.Type) Error!*ast.Type {
+        const ptr = try self.allocator.create(ast.Type);
+        ptr.* = t;
+        return ptr;
+    }
+
+    // ------------------------------------------------------------------------
+    // 类型
+    // ------------------------------------------------------------------------
+
+    fn expandType(self: *Expander, t: *ast.Type) Error!void {
+        switch (t.*) {
+            .named => |name| {
+                const td = self.aliases.get(name) orelse return;
+                t.* = try self.instantiate(td, &.{});
+            },
+            .generic_instance => |gi| {
+                for (gi.type_args) |*arg| try self.expandType(arg);
+                const td = self.aliases.get(gi.name) orelse return;
+                t.* = try self.instantiate(td, gi.type_args);
+            },
+            .pointer => |inner| try self.expandType(inner),
+            .array => |arr| {
+                try self.expandType(arr.element);
+                if (arr.size_expr) |size_expr| try self.expandExpr(size_expr);
+            },
+            .function => |func| {
+                for (func.params) |*param| try self.expandType(param);
+                try self.expandType(func.return_type);
+            },
+            .projection => |proj| {
+                try self.expandType(proj.base);
+                if (try self.project(proj.base.*, proj.name)) |target| t.* = target;
+            },
+            else => {},
+        }
+    }
+
+    /// 🆕 具体类型 base 的关联类型 name（返回新的副本）；base 是类型参数或没有该关联类型时返回 null
+    fn project(self: *Expander, base: ast.Type, name: []const u8) Error!?ast.Type {
+        const target = switch (base) {
+            .named => |type_name| blk: {
+                const td = if (std.mem.eql(u8, type_name, "Self")) self.self_type else self.owners.get(type_name);
+                break :blk (td orelse return null).assocType(name, &.{});
+            },
+            .generic_instance => |gi| (self.owners.get(gi.name) orelse return null).assocType(name, gi.type_args),
+            else => null,
+        } orelse return null;
+        return try self.substitute(target, &.{}, &.{});
+    }
+
+    /// 表达式中以名字出现的类型（Name { ... }、Name::method()）：
+    /// 别名指向结构体或枚举时改写为目标类型名和类型实参
+    fn expandTypeName(self: *Expander, name: *[]const u8, type_args: *[]ast.Type) Error!void {
+        for (type_args.*) |*arg| try self.expandType(arg);
+        const td = self.aliases.get(name.*) orelse return;
+        switch (try self.instantiate(td, type_args.*)) {
+            .named => |target| {
+                name.* = target;
+                type_args.* = type_args.*[0..0];
+            },
+            .generic_instance => |gi| {
+                name.* = gi.name;
+                type_args.* = gi.type_args;
+            },
+            else => return self.fail(td, "type alias '{s}' does not name a struct or enum", .{td.name}, &.{}, "only aliases of struct and enum types can be used in constructors and static method calls"),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // 函数体
+    // ------------------------------------------------------------------------
+
+    fn expandFunction(self: *Expander, func: *ast.FunctionDecl) Error!void {
+        for (func.params) |*param| try self.expandType(&param.type);
+        try self.expandType(&func.return_type);
+        try self.expandStmts(func.body);
+    }
+
+    fn expandStmts(self: *Expander, stmts: []ast.Stmt) Error!void {
+        for (stmts) |*stmt| try self.expandStmt(stmt);
+    }
+
+    fn expandStmt(self: *Expander, stmt: *ast.Stmt) Error!void {
+        switch (stmt.*) {
+            .expr => |*expr| try self.expandExpr(expr),
+            .let_decl => |*let| {
+                if (let.type) |*t| try self.expandType(t);
+                if (let.init) |*init| try self.expandExpr(init);
+            },
+            .assign => |*assign| {
+                try self.expandExpr(&assign.target);
+                try self.expandExpr(&assign.value);
+            },
+            .compound_assign => |*assign| {
+                try self.expandExpr(&assign.target);
+                try self.expandExpr(&assign.value);
+            },
+            .return_stmt => |*value| if (value.*) |*expr| try self.expandExpr(expr),
+            .break_stmt => |*brk| if (brk.value) |*expr| try self.expandExpr(expr),
+            .continue_stmt => {},
+            .loop_stmt => |*loop| {
+                if (loop.condition) |*cond| try self.expandExpr(cond);
+                if (loop.iterator) |*iter| try self.expandExpr(&iter.iterable);
+                try self.expandStmts(loop.body);
+            },
+            .while_loop => |*loop| {
+                try self.expandExpr(&loop.condition);
+                try self.expandStmts(loop.body);
+            },
+            .for_loop => |*loop| {
+                if (loop.init) |init| try self.expandStmt(init);
+                if (loop.condition) |*cond| try self.expandExpr(cond);
+                if (loop.step) |*step| try self.expandExpr(step);
+                try self.expandStmts(loop.body);
+            },
+        }
+    }
+
+    fn expandExpr(self: *Expander, expr: *ast.Expr) Error!void {
+        switch (expr.*) {
+            .binary => |bin| {
+                try self.expandExpr(bin.left);
+                try self.expandExpr(bin.right);
+            },
+            .unary => |un| try self.expandExpr(un.operand),
+            .call => |call| {
+                try self.expandExpr(call.callee);
+                for (call.args) |*arg| try self.expandExpr(arg);
+                for (call.type_args) |*t| try self.expandType(t);
+            },
+            .static_method_call => |*call| {
+                try self.expandTypeName(&call.type_name, &call.type_args);
+                for (call.args) |*arg| try self.expandExpr(arg);
+            },
+            .field_access => |access| try self.expandExpr(access.object),
+            .struct_init => |*init| {
+                try self.expandTypeName(&init.type_name, &init.type_args);
+                for (init.fields) |*field| try self.expandExpr(&field.value);
+            },
+            .enum_variant => |*variant| {
+                // 枚举的类型实参由变体参数推导：别名只改写名字
+                if (self.aliases.get(variant.enum_name)) |td| {
+                    switch (try self.substitute(td.kind.alias, &.{}, &.{})) {
+                        .named => |target| variant.enum_name = target,
+                        .generic_instance => |gi| variant.enum_name = gi.name,
+                        else => return self.fail(td, "type alias '{s}' does not name a struct or enum", .{td.name}, &.{}, null),
+                    }
+                }
+                for (variant.args) |*arg| try self.expandExpr(arg);
+            },
+            .block => |stmts| try self.expandStmts(stmts),
+            .if_expr => |if_expr| {
+                try self.expandExpr(if_expr.condition);
+                try self.expandExpr(if_expr.then_branch);
+                if (if_expr.else_branch) |else_branch| try self.expandExpr(else_branch);
+            },
+            .is_expr => |is_expr| {
+                try self.expandExpr(is_expr.value);
+                for (is_expr.arms) |*arm| {
+                    if (arm.guard) |*guard| try self.expandExpr(guard);
+                    try self.expandExpr(&arm.body);
+                }
+            },
+            .match_expr => |match_expr| {
+                try self.expandExpr(match_expr.value);
+                for (match_expr.arms) |*arm| try self.expandExpr(&arm.body);
+            },
+            .as_expr => |*as_expr| {
+                try self.expandExpr(as_expr.value);
+                try self.expandType(&as_expr.target_type);
+            },
+            .await_expr => |inner| try self.expandExpr(inner),
+            .array_literal => |elements| for (elements) |*element| try self.expandExpr(element),
+            .array_index => |index| {
+                try self.expandExpr(index.array);
+                try self.expandExpr(index.index);
+            },
+            .range => |range| {
+                try self.expandExpr(range.start);
+                try self.expandExpr(range.end);
+            },
+            .string_interp => |interp| for (interp.parts) |*part| switch (part.*) {
+                .expr => |*inner| try self.expandExpr(inner),
+                .literal => {},
+            },
+            .try_expr => |inner| try self.expandExpr(inner),
+            .type_query => |*query| try self.expandType(&query.type),
+            .loop_expr => |loop| try self.expandStmts(loop.body),
+            .lambda => |*lambda| {
+                for (lambda.params) |*param| try self.expandType(&param.type);
+                try self.expandType(&lambda.return_type);
+                try self.expandStmts(lambda.body);
+            },
+            .int_literal, .float_literal, .string_literal, .char_literal, .byte_literal,
+            .byte_string_literal, .bool_literal, .identifier => {},
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // 诊断
+    // ------------------------------------------------------------------------
+
+    fn fail(self: *Expander, td: *const ast.TypeDecl, comptime fmt: []const u8, args: anytype, notes: []const []const u8, help: ?[]const u8) Error {
+        const message = try std.fmt.allocPrint(self.allocator, fmt, args);
+        const span: ?Span = if (td.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        const diag = Diagnostic.init(.Error, message, span, notes, help).withCode(.invalid_type_alias);
+        diag.print(self.allocator) catch {};
+        return error.TypeAliasFailed;
+    }
+};
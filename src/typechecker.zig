@@ -1,4 +1,5 @@
 const std = @import("std");
+const builtin = @import("builtin");
 const ast = @import("ast.zig");
 const generics = @import("generics.zig");
 const diagnostic = @import("diagnostic.zig");  // 🆕 v0.1.8
@@ -11,6 +12,8 @@ pub const TraitDef = struct {
     name: []const u8,
     methods: []ast.FunctionSignature,  // trait 中是签名，不是完整实现
     type_params: [][]const u8,  // 泛型参数
+    supertraits: []const ast.TraitRef = &.{},  // 🆕 实现本 trait 的类型也必须实现它们
+    assoc_types: []const []const u8 = &.{},    // 🆕 实现本 trait 的类型必须定义的关联类型
 };
 
 // 类型的方法信息
@@ -23,52 +26,83 @@ pub const TypeMethods = struct {
     }
 };
 
+/// 🆕 类型检查时的循环上下文
+const LoopScope = struct {
+    label: ?[]const u8,
+    is_expr: bool,            // loop 表达式（break 可以带值）
+    break_type: ?ast.Type,    // 第一个 break 值的类型
+};
+
 const Token = @import("token.zig").Token;  // 🆕 v0.1.8
+const ExportIndex = @import("module.zig").ExportIndex;
+const SourceMap = @import("source_map.zig").SourceMap;
+const layout = @import("layout.zig");
+const callgraph = @import("callgraph.zig");  // 🆕 无条件递归检测
+const cancel = @import("cancel.zig");
+const resolution_trace = @import("resolution_trace.zig");  // 🆕 --explain-overloads
+const ast_printer = @import("ast_printer.zig");  // 🆕 pawc ast --typed
+const messages = @import("messages.zig");  // 🆕 本地化的消息模板（--locale / PAW_LANG）
+const codes = @import("codes.zig");  // 🆕 诊断代码注册表
+const closure = @import("closure.zig");  // 🆕 lambda 捕获分析
 
 pub const TypeChecker = struct {
     allocator: std.mem.Allocator,
     arena: std.heap.ArenaAllocator,  // 🆕 Arena allocator for temporary types
-    errors: std.ArrayList([]const u8),  // 保留旧的错误列表用于兼容
     diagnostics: std.ArrayList(Diagnostic),  // 🆕 v0.1.8: 新的诊断系统
     symbol_table: std.StringHashMap(ast.Type),
     function_table: std.StringHashMap(ast.FunctionDecl),
     type_table: std.StringHashMap(ast.TypeDecl),  // 存储 type 声明
     trait_table: std.StringHashMap(TraitDef),      // 新增：存储 trait 定义
     type_methods: std.StringHashMap(TypeMethods),  // 新增：存储类型的方法
+    namespace_private: std.StringHashMap([]const u8),  // 🆕 命名空间导入的私有函数和常量：改名后的名字 -> 别名
     current_function_is_async: bool,  // 追踪当前函数是否异步
     generic_context: generics.GenericContext,  // 🆕 泛型上下文
     mutable_vars: std.StringHashMap(bool),  // 🆕 v0.1.6: 跟踪可变变量 (变量名 -> 是否可变)
-    source_file: []const u8,  // 🆕 v0.1.8: 当前处理的源文件名
     tokens: []Token,  // 🆕 v0.1.8: Token 数组用于位置查找
     identifier_tokens: std.StringHashMap(Token),  // 🆕 v0.1.8: 标识符名 -> Token 映射
+    export_index: ?*ExportIndex,  // 🆕 可导入名称索引（未知函数时建议 import）
+    source_map: ?*SourceMap,  // 🆕 源文件表（打印诊断时取代码片段）
+    max_errors: ?usize,  // 🆕 --max-errors: 收集到 N 个错误后停止
+    fail_fast: bool,  // 🆕 --fail-fast: 第一个出错的声明之后停止检查
+    loop_stack: std.ArrayList(LoopScope),  // 🆕 外层循环栈（标签、break 值类型）
+    current_function: ?ast.FunctionDecl,  // 🆕 正在检查的函数（return 的期望类型）
+    cancel_token: ?*const cancel.CancelToken,  // 🆕 取消令牌：取消后放弃检查，不打印过期的诊断
+    resolution_trace: ?*resolution_trace.ResolutionTrace,  // 🆕 --explain-overloads：记录调用解析过程
+    allow_asm: bool,  // 🆕 --allow-asm：允许 @asm 函数
+    expr_types: ?*ast_printer.ExprTypes,  // 🆕 pawc ast --typed：记录表达式和 let 绑定的类型
+    current_captures: []const ast.Capture,  // 🆕 正在检查的 lambda 捕获的变量（不能赋值）
 
-    pub fn init(allocator: std.mem.Allocator, source_file: []const u8, tokens: []Token) TypeChecker {
+    pub fn init(allocator: std.mem.Allocator, tokens: []Token) TypeChecker {
         return TypeChecker{
             .allocator = allocator,
             .arena = std.heap.ArenaAllocator.init(allocator),
-            .errors = std.ArrayList([]const u8){},
             .diagnostics = std.ArrayList(Diagnostic){},  // 🆕 v0.1.8
             .symbol_table = std.StringHashMap(ast.Type).init(allocator),
             .function_table = std.StringHashMap(ast.FunctionDecl).init(allocator),
             .type_table = std.StringHashMap(ast.TypeDecl).init(allocator),
             .trait_table = std.StringHashMap(TraitDef).init(allocator),
             .type_methods = std.StringHashMap(TypeMethods).init(allocator),
+            .namespace_private = std.StringHashMap([]const u8).init(allocator),
             .current_function_is_async = false,
             .generic_context = generics.GenericContext.init(allocator),  // 🆕 初始化泛型上下文
             .mutable_vars = std.StringHashMap(bool).init(allocator),  // 🆕 v0.1.6: 初始化可变变量表
-            .source_file = source_file,  // 🆕 v0.1.8
             .tokens = tokens,  // 🆕 v0.1.8
             .identifier_tokens = std.StringHashMap(Token).init(allocator),  // 🆕 v0.1.8
+            .export_index = null,
+            .source_map = null,
+            .max_errors = null,
+            .fail_fast = false,
+            .loop_stack = std.ArrayList(LoopScope){},
+            .current_function = null,
+            .cancel_token = null,
+            .resolution_trace = null,
+            .allow_asm = false,
+            .expr_types = null,
+            .current_captures = &[_]ast.Capture{},
         };
     }
 
     pub fn deinit(self: *TypeChecker) void {
-        // 🆕 v0.1.6: 释放错误消息内存
-        for (self.errors.items) |error_msg| {
-            self.allocator.free(error_msg);
-        }
-        self.errors.deinit(self.allocator);
-        
         // 🆕 v0.1.8: 释放诊断消息内存
         for (self.diagnostics.items) |diag| {
             self.allocator.free(diag.message);
@@ -84,11 +118,13 @@ pub const TypeChecker = struct {
         }
         self.diagnostics.deinit(self.allocator);
         self.identifier_tokens.deinit();
+        self.loop_stack.deinit(self.allocator);
         
         self.symbol_table.deinit();
         self.function_table.deinit();
         self.type_table.deinit();
         self.trait_table.deinit();
+        self.namespace_private.deinit();
         
         // 清理 type_methods
         var it = self.type_methods.iterator();
@@ -117,12 +153,61 @@ pub const TypeChecker = struct {
         }
         
         // 第一遍：收集所有类型、函数和 trait 声明
+        try self.collectDeclarations(program);
+
+        // 第二遍：类型检查
+        var stopped_early = false;
+        for (program.declarations) |decl| {
+            if (self.shouldStop()) {  // 🆕 --max-errors / --fail-fast
+                stopped_early = true;
+                break;
+            }
+            try cancel.checkOptional(self.cancel_token);
+            try self.checkDecl(decl);
+        }
+
+        if (!self.function_table.contains("main")) {
+            try self.reportError(.missing_main, try messages.format(self.allocator, .missing_main, .{}), null);
+        }
+        
+        // 🆕 无条件递归：运行时必然栈溢出，编译时给出警告
+        if (!stopped_early) try self.checkRecursionCycles(program);
+
+        // 🆕 v0.1.8: 打印增强的诊断消息（🆕 受 --max-errors 限制）
+        const total = self.diagnostics.items.len;
+        const limit = self.max_errors orelse total;
+        var shown: usize = 0;
+        if (self.diagnostics.items.len > 0) {
+            for (self.diagnostics.items) |diag| {
+                if (shown >= limit) break;
+                try diag.printWithSourceMap(self.allocator, self.source_map);
+                shown += 1;
+            }
+        }
+        
+        // 🆕 只有警告时检查仍然通过
+        if (self.errorCount() > 0) {
+            if (self.max_errors) |max| {
+                if (stopped_early or shown < total) {
+                    std.debug.print("\x1b[1;31merror\x1b[0m: too many errors, stopped after {d} (--max-errors {d})\n", .{shown, max});
+                }
+            }
+            return error.TypeCheckFailed;
+        }
+    }
+    
+    /// 🆕 收集类型、函数、trait 和常量声明（check 的第一遍；pawc query impls 只需要这一步）
+    pub fn collectDeclarations(self: *TypeChecker, program: ast.Program) !void {
         for (program.declarations) |decl| {
             switch (decl) {
                 .function => |func| {
                     try self.function_table.put(func.name, func);
+                    if (func.namespace) |alias| {
+                        if (!func.is_public) try self.namespace_private.put(func.name, alias);
+                    }
                 },
                 .type_decl => |td| {
+                    if (td.kind == .alias) continue;  // 🆕 别名在解析后已展开
                     try self.type_table.put(td.name, td);
                     try self.symbol_table.put(td.name, ast.Type{ .named = td.name });
                     
@@ -132,6 +217,8 @@ pub const TypeChecker = struct {
                             .name = td.name,
                             .methods = td.kind.trait_type.methods,
                             .type_params = td.type_params,
+                            .supertraits = td.kind.trait_type.supertraits,
+                            .assoc_types = td.kind.trait_type.assoc_types,
                         };
                         try self.trait_table.put(td.name, trait_def);
                     }
@@ -140,7 +227,7 @@ pub const TypeChecker = struct {
                     const methods: ?[]ast.FunctionDecl = switch (td.kind) {
                         .struct_type => |st| st.methods,
                         .enum_type => |et| et.methods,
-                        .trait_type => null,  // trait 只有签名，不收集到 type_methods
+                        .trait_type, .alias => null,  // trait 只有签名，不收集到 type_methods
                     };
                     
                     if (methods) |m| {
@@ -164,39 +251,76 @@ pub const TypeChecker = struct {
                 .enum_decl => |e| {
                     try self.symbol_table.put(e.name, ast.Type{ .named = e.name });
                 },
+                // 🆕 全局常量（初始化表达式已在编译期求值为字面量）
+                .const_decl => |cd| {
+                    const const_type: ast.Type = cd.type orelse switch (cd.value.*) {
+                        .float_literal => ast.Type.f64,
+                        .bool_literal => ast.Type.bool,
+                        .char_literal => ast.Type.char,
+                        .string_literal => ast.Type.string,
+                        .struct_init => |si| ast.Type{ .named = si.type_name },  // 🆕 结构体常量
+                        else => ast.Type.i32,
+                    };
+                    try self.symbol_table.put(cd.name, const_type);
+                    if (cd.namespace) |alias| {
+                        if (!cd.is_public) try self.namespace_private.put(cd.name, alias);
+                    }
+                },
                 else => {},
             }
         }
-
-        // 第二遍：类型检查
-        for (program.declarations) |decl| {
-            try self.checkDecl(decl);
-        }
-
-        if (!self.function_table.contains("main")) {
-            try self.errors.append(self.allocator, "Error: missing main function");
-        }
-
-        // 🆕 v0.1.8: 打印增强的诊断消息
-        if (self.diagnostics.items.len > 0) {
-            for (self.diagnostics.items) |diag| {
-                try diag.print(self.allocator);
+    }
+    
+    /// 🆕 对调用图中每个无条件递归的环给出警告
+    fn checkRecursionCycles(self: *TypeChecker, program: ast.Program) !void {
+        const cycles = try callgraph.findUnconditionalCycles(self.arena.allocator(), program);
+        for (cycles) |cycle| {
+            const first = cycle.functions[0];
+            const warning_msg = if (cycle.functions.len == 1)
+                try std.fmt.allocPrint(self.allocator, "function '{s}' calls itself on every path and will never return", .{first})
+            else
+                try std.fmt.allocPrint(self.allocator, "functions '{s}' and '{s}' call each other on every path and will never return", .{ first, cycle.functions[1] });
+            
+            var chain = std.ArrayList(u8){};
+            defer chain.deinit(self.allocator);
+            for (cycle.functions) |name| {
+                try chain.writer(self.allocator).print("'{s}' -> ", .{name});
             }
-            return error.TypeCheckFailed;
+            try chain.writer(self.allocator).print("'{s}'", .{first});
+            
+            const notes = try self.allocator.alloc([]const u8, 2);
+            notes[0] = try std.fmt.allocPrint(self.allocator, "call cycle: {s}", .{chain.items});
+            notes[1] = try self.allocator.dupe(u8, "the program will overflow the stack at runtime");
+            const help = try self.allocator.dupe(u8, "add a base case that returns without making the recursive call");
+            const span: ?Span = if (cycle.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Warning, warning_msg, span, notes, help).withCode(.infinite_recursion));
         }
-        
-        // 兼容：打印旧的简单错误
-        if (self.errors.items.len > 0) {
-            for (self.errors.items) |err| {
-                std.debug.print("{s}\n", .{err});
-            }
-            return error.TypeCheckFailed;
+    }
+    
+    /// 🆕 已收集的错误数量（不含警告）
+    fn errorCount(self: *TypeChecker) usize {
+        var count: usize = 0;
+        for (self.diagnostics.items) |diag| {
+            if (diag.level == .Error) count += 1;
         }
+        return count;
+    }
+    
+    /// 🆕 是否应停止继续检查
+    fn shouldStop(self: *TypeChecker) bool {
+        const count = self.errorCount();
+        if (self.fail_fast and count > 0) return true;
+        if (self.max_errors) |max| return count >= max;
+        return false;
     }
 
     fn checkDecl(self: *TypeChecker, decl: ast.TopLevelDecl) !void {
         switch (decl) {
             .function => |func| {
+                // 🆕 extern fn 只有签名，没有需要检查的函数体
+                if (func.is_extern) return;
+                if (func.asm_bodies.len > 0) return self.checkAsmFunction(func);
+                if (func.test_attr != null) try self.checkTestSignature(func);
                 try self.checkFunction(func);
             },
             .type_decl => |td| {
@@ -214,10 +338,124 @@ pub const TypeChecker = struct {
                     try self.checkFunction(method);
                 }
             },
+            .const_decl => |cd| try self.checkConstDecl(cd),
+            .impl_decl => |impl| try self.checkImplDecl(impl),
             else => {},
         }
     }
     
+    /// 🆕 impl Trait for Type：trait 已声明，trait（及 supertrait）的每个方法由 impl 块或类型自己定义。
+    /// 方法在解析时已并入目标类型，由 checkTypeDecl 检查
+    fn checkImplDecl(self: *TypeChecker, impl: ast.ImplDecl) !void {
+        const span: ?Span = if (impl.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        const target_name = switch (impl.target_type) {
+            .named => |n| n,
+            .generic_instance => |gi| gi.name,
+            else => return,
+        };
+        const trait_def = self.trait_table.get(impl.trait_name) orelse {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "'{s}' is not a trait", .{impl.trait_name});
+            const help = try std.fmt.allocPrint(self.allocator, "declare it: type {s} = trait {{ ... }}", .{impl.trait_name});
+            return self.reportErrorWithHelp(.unknown_trait, error_msg, span, help);
+        };
+        
+        var trait_methods = std.ArrayList(ast.FunctionSignature){};
+        defer trait_methods.deinit(self.allocator);
+        try self.collectTraitMethods(trait_def, &trait_methods);
+        const type_methods = self.type_methods.get(target_name);
+        for (trait_methods.items) |trait_method| {
+            if (type_methods) |tm| {
+                if (tm.methods.contains(trait_method.name)) continue;
+            }
+            const error_msg = try std.fmt.allocPrint(
+                self.allocator,
+                "impl of '{s}' for '{s}' is missing method '{s}'",
+                .{ impl.trait_name, target_name, trait_method.name },
+            );
+            const help = try std.fmt.allocPrint(self.allocator, "define `fn {s}(...)` in the impl block", .{trait_method.name});
+            try self.reportErrorWithHelp(.missing_impl_method, error_msg, span, help);
+        }
+    }
+    
+    /// 🆕 @asm 函数：需要 --allow-asm，目标架构要有对应的函数体，
+    /// 参数和返回值只能是按 C 调用约定放在寄存器中的标量
+    fn checkAsmFunction(self: *TypeChecker, func: ast.FunctionDecl) !void {
+        const span: ?Span = if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        
+        if (!self.allow_asm) {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "inline assembly in '{s}' is not enabled", .{func.name});
+            const notes = try self.allocator.alloc([]const u8, 1);
+            notes[0] = try self.allocator.dupe(u8, "@asm bodies are not checked by the compiler: a mistake corrupts registers or the stack");
+            const help = try self.allocator.dupe(u8, "pass --allow-asm to compile @asm functions");
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.asm_not_enabled));
+            return;
+        }
+        
+        const arch = @tagName(builtin.cpu.arch);
+        if (func.asmFor(arch) == null) {
+            var available = std.ArrayList(u8){};
+            defer available.deinit(self.allocator);
+            for (func.asm_bodies, 0..) |body, i| {
+                if (i > 0) try available.appendSlice(self.allocator, ", ");
+                try available.appendSlice(self.allocator, body.arch);
+            }
+            const error_msg = try std.fmt.allocPrint(self.allocator, "@asm function '{s}' has no body for the target architecture '{s}'", .{ func.name, arch });
+            const notes = try self.allocator.alloc([]const u8, 1);
+            notes[0] = try std.fmt.allocPrint(self.allocator, "bodies are given for: {s}", .{available.items});
+            const help = try std.fmt.allocPrint(self.allocator, "add @asm(\"{s}\", \"...\") to the declaration", .{arch});
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.asm_missing_target));
+        }
+        
+        for (func.params) |param| {
+            if (isAsmScalar(param.type)) continue;
+            const error_msg = try std.fmt.allocPrint(self.allocator, "parameter '{s}' of @asm function '{s}' has type '{s}'", .{ param.name, func.name, self.typeToString(param.type) });
+            const help = try self.allocator.dupe(u8, "@asm parameters must be integers (up to 64 bits), floats, bool, char or string (passed as a pointer)");
+            try self.reportErrorWithHelp(.asm_invalid_signature, error_msg, span, help);
+        }
+        if (!isAsmScalar(func.return_type) and func.return_type != .void and func.return_type != .never) {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "@asm function '{s}' returns '{s}'", .{ func.name, self.typeToString(func.return_type) });
+            const help = try self.allocator.dupe(u8, "@asm functions must return an integer (up to 64 bits), float, bool, char, string, void or !");
+            try self.reportErrorWithHelp(.asm_invalid_signature, error_msg, span, help);
+        }
+    }
+    
+    /// 🆕 @test / @property 函数由测试运行器直接调用：不能有参数、类型参数或返回值，也不能是 async
+    fn checkTestSignature(self: *TypeChecker, func: ast.FunctionDecl) !void {
+        if (func.params.len == 0 and func.type_params.len == 0 and func.return_type == .void and !func.is_async) return;
+        const span: ?Span = if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        const attr_name = if (func.test_attr.?.runs != null) "@property" else "@test";
+        const error_msg = try std.fmt.allocPrint(self.allocator, "{s} function '{s}' must be declared as `fn {s}()`", .{ attr_name, func.name, func.name });
+        const help = try self.allocator.dupe(u8, "a test takes no arguments and returns nothing; it fails by calling panic(...)");
+        try self.reportErrorWithHelp(.invalid_test_function, error_msg, span, help);
+    }
+    
+    /// 🆕 能放在一个寄存器中传递的类型（string 是指向字节的指针）
+    fn isAsmScalar(t: ast.Type) bool {
+        return switch (t) {
+            .i8, .i16, .i32, .i64, .u8, .u16, .u32, .u64, .f32, .f64, .bool, .char, .string => true,
+            else => false,
+        };
+    }
+    
+    /// 🆕 检查全局常量的值与声明的类型一致
+    fn checkConstDecl(self: *TypeChecker, cd: ast.ConstDecl) !void {
+        var scope = std.StringHashMap(ast.Type).init(self.allocator);
+        defer scope.deinit();
+        
+        const value_type = try self.checkExprExpected(cd.value.*, &scope, cd.type);
+        const declared = cd.type orelse value_type;
+        if (try self.checkLiteralRange(cd.value.*, declared)) return;
+        if (!self.isTypeCompatible(value_type, declared)) {
+            const error_msg = try std.fmt.allocPrint(
+                self.allocator,
+                "mismatched types in constant '{s}': expected '{s}', found '{s}'",
+                .{ cd.name, self.typeToString(declared), self.typeToString(value_type) },
+            );
+            const span: ?Span = if (cd.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, &[_][]const u8{}, null).withCode(.mismatched_types));
+        }
+    }
+    
     fn checkTypeDecl(self: *TypeChecker, type_decl: ast.TypeDecl) !void {
         switch (type_decl.kind) {
             .struct_type => |st| {
@@ -250,7 +488,14 @@ pub const TypeChecker = struct {
                     _ = sig;
                     // TODO: 验证方法签名类型
                 }
+                try self.checkSupertraits(type_decl, tt.supertraits);
+                // 🆕 签名中的 Self::Item 必须是 trait（或 supertrait）声明的关联类型
+                for (tt.methods) |sig| {
+                    for (sig.params) |param| try self.checkSelfProjection(type_decl, param.type);
+                    try self.checkSelfProjection(type_decl, sig.return_type);
+                }
             },
+            .alias => {},  // 🆕 已在解析后展开
         }
     }
 
@@ -260,8 +505,13 @@ pub const TypeChecker = struct {
         self.current_function_is_async = func.is_async;
         defer self.current_function_is_async = prev_async;
         
+        const prev_function = self.current_function;
+        self.current_function = func;
+        defer self.current_function = prev_function;
+        
         // 🆕 v0.1.6: 清空可变变量表（每个函数有自己的作用域）
         self.mutable_vars.clearRetainingCapacity();
+        self.loop_stack.clearRetainingCapacity();
         
         var local_scope = std.StringHashMap(ast.Type).init(self.allocator);
         defer local_scope.deinit();
@@ -271,16 +521,59 @@ pub const TypeChecker = struct {
             try local_scope.put(type_param, ast.Type{ .generic = type_param });
         }
 
+        // 🆕 签名中的关联类型投影（T::Item）
+        for (func.params) |param| try self.checkProjection(func, param.type);
+        try self.checkProjection(func, func.return_type);
+        
+        // 🆕 where T: Trait 中的 trait 必须已声明
+        for (func.trait_bounds) |tb| {
+            if (self.trait_table.contains(tb.trait_name)) continue;
+            const error_msg = try std.fmt.allocPrint(self.allocator, "bound '{s}: {s}' does not name a trait", .{ tb.param, tb.trait_name });
+            const help = try std.fmt.allocPrint(self.allocator, "declare it: type {s} = trait {{ ... }}", .{tb.trait_name});
+            const span: ?Span = if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+            try self.reportErrorWithHelp(.unknown_trait, error_msg, span, help);
+        }
+
         // 🆕 v0.1.6: 记录函数参数的可变性
         for (func.params) |param| {
             try local_scope.put(param.name, param.type);
             try self.mutable_vars.put(param.name, param.is_mut);  // 使用参数的 is_mut
         }
 
-        for (func.body) |stmt| {
-            try self.checkStmt(stmt, &local_scope);
+        var diverges = false;
+        for (func.body, 0..) |stmt, i| {
+            // 🆕 有返回值的函数中，最后一条表达式语句是返回值，不是被丢弃的值
+            const is_tail_value = i == func.body.len - 1 and stmt == .expr and
+                func.return_type != .void and func.return_type != .never;
+            if (is_tail_value) {
+                if ((try self.checkExprExpected(stmt.expr, &local_scope, self.expectedReturnType())) == .never) diverges = true;
+            } else if (try self.checkBlockStmt(stmt, &local_scope)) {
+                diverges = true;
+            }
+        }
+        
+        // 🆕 声明为 `-> !` 的函数不能正常结束
+        if (func.return_type == .never and !diverges) {
+            try self.reportNeverReturns(func, "its body can reach the end");
         }
     }
+    
+    /// 🆕 声明为 `-> !` 的函数会返回
+    fn reportNeverReturns(self: *TypeChecker, func: ast.FunctionDecl, reason: []const u8) !void {
+        const error_msg = try std.fmt.allocPrint(
+            self.allocator,
+            "function '{s}' is declared to never return ('!'), but {s}",
+            .{ func.name, reason },
+        );
+        const span: ?Span = if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        try self.diagnostics.append(self.allocator, Diagnostic.init(
+            .Error,
+            error_msg,
+            span,
+            &[_][]const u8{},
+            "end every path with a call to 'panic' or 'paw_exit', or an endless 'loop'",
+        ).withCode(.never_returns));
+    }
 
     // ============================================================================
     // Helper Functions
@@ -292,36 +585,86 @@ pub const TypeChecker = struct {
             .identifier => |name| {
                 // 检查变量是否存在
                 if (self.mutable_vars.get(name)) |is_mut| {
-                    if (!is_mut) {
-                        const error_msg = try std.fmt.allocPrint(
+                    if (!is_mut and self.isCaptured(name)) {
+                        // 🆕 lambda 按值捕获：赋值只会修改环境中的副本
+                        const error_msg = try std.fmt.allocPrint(self.allocator, "cannot assign to '{s}', which is captured by the enclosing lambda", .{name});
+                        try self.reportErrorWithHelp(
+                            .assign_to_capture,
+                            error_msg,
+                            self.identifierSpan(name),
+                            try self.allocator.dupe(u8, "captured variables are copied into the lambda; declare a local 'let mut' copy inside the lambda"),
+                        );
+                    } else if (!is_mut) {
+                        const error_msg = try messages.format(
                             self.allocator,
-                            "Error: Cannot assign to immutable variable '{s}'. Use 'let mut {s}' to make it mutable.",
+                            .assign_to_immutable,
                             .{name, name}
                         );
-                        try self.errors.append(self.allocator, error_msg);
+                        try self.reportError(.assign_to_immutable, error_msg, self.identifierSpan(name));
                     }
                 } else {
                     // 变量不存在（这应该在其他地方被捕获）
-                    const error_msg = try std.fmt.allocPrint(
+                    const error_msg = try messages.format(
                         self.allocator,
-                        "Error: Variable '{s}' not found.",
+                        .variable_not_found,
                         .{name}
                     );
-                    try self.errors.append(self.allocator, error_msg);
+                    try self.reportError(.undefined_variable, error_msg, self.identifierSpan(name));
                 }
             },
             .field_access => {
                 // 字段访问：暂时允许（将来可以添加结构体字段可变性检查）
             },
-            .array_index => {
-                // 数组索引：暂时允许（将来可以添加数组可变性检查）
+            .array_index => |ai| {
+                // 🆕 数组元素赋值：数组变量必须是可变的（数组是值，修改元素就是修改变量）
+                // 其他形式（如 self.items[i]）暂时允许
+                if (ai.array.* == .identifier) {
+                    const name = ai.array.identifier;
+                    if (self.mutable_vars.get(name)) |is_mut| {
+                        if (!is_mut) {
+                            const error_msg = try messages.format(
+                                self.allocator,
+                                .assign_to_immutable_element,
+                                .{name, name}
+                            );
+                            try self.reportError(.assign_to_immutable, error_msg, self.identifierSpan(name));
+                        }
+                    }
+                }
             },
             else => {
-                try self.errors.append(self.allocator, "Error: Invalid assignment target.");
+                try self.reportError(.invalid_assignment_target, try messages.format(self.allocator, .invalid_assignment_target, .{}), self.exprSpan(expr));
             },
         }
     }
     
+    /// 🆕 名字是正在检查的 lambda 捕获的变量
+    fn isCaptured(self: *TypeChecker, name: []const u8) bool {
+        for (self.current_captures) |capture| {
+            if (std.mem.eql(u8, capture.name, name)) return true;
+        }
+        return false;
+    }
+    
+    /// 🆕 常量索引（整数字面量）超出固定大小数组的长度时报错
+    fn checkConstantIndex(self: *TypeChecker, index: ast.Expr, len: usize) !void {
+        const value: i64 = switch (index) {
+            .int_literal => |literal| literal.value,
+            .unary => |un| if (un.op == .neg and un.operand.* == .int_literal) -un.operand.int_literal.value else return,
+            else => return,
+        };
+        const length: i64 = @intCast(len);
+        if (value >= 0 and value < length) return;
+        
+        const error_msg = try std.fmt.allocPrint(self.allocator, "index out of bounds: the length is {d} but the index is {d}", .{ length, value });
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = if (length == 0)
+            try self.allocator.dupe(u8, "the array is empty")
+        else
+            try std.fmt.allocPrint(self.allocator, "valid indices are 0 to {d}", .{length - 1});
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, null, notes, null).withCode(.index_out_of_bounds));
+    }
+    
     /// 创建子作用域（复制父作用域）
     fn createChildScope(self: *TypeChecker, parent: *std.StringHashMap(ast.Type)) !std.StringHashMap(ast.Type) {
         var child = std.StringHashMap(ast.Type).init(self.allocator);
@@ -336,20 +679,90 @@ pub const TypeChecker = struct {
     // Statement Checking
     // ============================================================================
     
+    /// 🆕 表达式语句 `expr;` 的值被丢弃：期望类型为 void，分支类型不必一致
+    /// 
+    /// 没有副作用的表达式（比较、算术、变量、字面量等）的值被丢弃时给出警告，
+    /// 例如把 `a = b;` 误写成 `a == b;`。确实要丢弃时写 `_ = expr;`。
+    fn checkExprStmt(self: *TypeChecker, expr: ast.Expr, scope: *std.StringHashMap(ast.Type)) (std.mem.Allocator.Error || error{TypeCheckFailed})!ast.Type {
+        const value_type = try self.checkExprExpected(expr, scope, ast.Type.void);
+        if (value_type != .void and value_type != .never and isPureExpr(expr)) {
+            try self.reportDiscardedValue(expr, value_type);
+        }
+        return value_type;
+    }
+    
+    /// 🆕 求值没有副作用、只为得到值的表达式
+    fn isPureExpr(expr: ast.Expr) bool {
+        return switch (expr) {
+            .int_literal, .float_literal, .string_literal, .char_literal, .byte_literal, .bool_literal,
+            .identifier, .unary, .field_access, .array_index, .as_expr, .type_query => true,
+            // `ok && f()` 常用于只在条件成立时执行 f，不算丢弃值
+            .binary => |bin| bin.op != .and_op and bin.op != .or_op,
+            else => false,
+        };
+    }
+    
+    /// 🆕 警告：表达式语句的值没有被使用
+    fn reportDiscardedValue(self: *TypeChecker, expr: ast.Expr, value_type: ast.Type) !void {
+        const is_comparison = expr == .binary and switch (expr.binary.op) {
+            .eq, .ne, .lt, .le, .gt, .ge => true,
+            else => false,
+        };
+        const warning_msg = if (is_comparison)
+            try self.allocator.dupe(u8, "unused comparison result")
+        else
+            try std.fmt.allocPrint(self.allocator, "unused value of type '{s}'", .{self.typeToString(value_type)});
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = try self.allocator.dupe(u8, "the value of an expression statement `expr;` is discarded");
+        const help = if (is_comparison and expr.binary.op == .eq)
+            try self.allocator.dupe(u8, "if you meant to assign, use `=`; to discard the value explicitly, write `_ = expr;`")
+        else
+            try self.allocator.dupe(u8, "to discard the value explicitly, write `_ = expr;`");
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Warning, warning_msg, self.exprSpan(expr), notes, help).withCode(.unused_value));
+    }
+    
+    /// 🆕 表达式的位置：运算符、调用和变量的位置；没有位置的表达式（字面量等）使用所在函数的位置
+    fn exprSpan(self: *TypeChecker, expr: ast.Expr) ?Span {
+        const location: ?ast.SourceLocation = switch (expr) {
+            .binary => |bin| bin.location,
+            .call => |call| if (call.callee.* == .identifier) return self.callSpan(call, call.callee.identifier) else call.location,
+            .identifier => |name| return self.identifierSpan(name),
+            .unary => |un| return self.exprSpan(un.operand.*),
+            .field_access => |access| return self.exprSpan(access.object.*),
+            .array_index => |ai| return self.exprSpan(ai.array.*),
+            .as_expr => |as_cast| return self.exprSpan(as_cast.value.*),
+            else => null,
+        };
+        const loc = location orelse (if (self.current_function) |func| func.location else null) orelse return null;
+        return Span.fromPosition(loc.file, loc.line, loc.column);
+    }
+    
+    /// 🆕 检查块中的一条语句，返回它之后的语句是否不可达（break / continue / return 或 never 类型的表达式）
+    fn checkBlockStmt(self: *TypeChecker, stmt: ast.Stmt, scope: *std.StringHashMap(ast.Type)) (std.mem.Allocator.Error || error{TypeCheckFailed})!bool {
+        if (stmt == .expr) return (try self.checkExprStmt(stmt.expr, scope)) == .never;
+        try self.checkStmt(stmt, scope);
+        return stmt.diverges();
+    }
+    
     fn checkStmt(self: *TypeChecker, stmt: ast.Stmt, scope: *std.StringHashMap(ast.Type)) (std.mem.Allocator.Error || error{TypeCheckFailed})!void {
         switch (stmt) {
-            .expr => |expr| {
-                _ = try self.checkExpr(expr, scope);
-            },
+            .expr => |expr| _ = try self.checkExprStmt(expr, scope),
             // 🆕 赋值语句
             .assign => |assign| {
+                // 🆕 `_ = expr;`：显式丢弃值，不产生警告
+                if (assign.target.isDiscardTarget()) {
+                    _ = try self.checkExpr(assign.value, scope);
+                    return;
+                }
                 // 🆕 v0.1.6: 检查目标是否可变
                 try self.checkMutability(assign.target);
                 
                 const target_type = try self.checkExpr(assign.target, scope);
-                const value_type = try self.checkExpr(assign.value, scope);
-                if (!target_type.eql(value_type)) {
-                    try self.errors.append(self.allocator, "Type error: assignment type mismatch");
+                const value_type = try self.checkExprExpected(assign.value, scope, target_type);
+                if (try self.checkLiteralRange(assign.value, target_type)) {
+                    // 🆕 整数字面量赋给更窄/更宽的整数类型：范围已检查
+                } else if (!target_type.eql(value_type)) {
+                    try self.reportTypeMismatch("assignment", target_type, value_type, self.exprSpan(assign.value));
                 }
             },
             // 🆕 复合赋值语句
@@ -357,11 +770,15 @@ pub const TypeChecker = struct {
                 // 🆕 v0.1.6: 检查目标是否可变
                 try self.checkMutability(ca.target);
                 
+                // 🆕 x += v 按 x = x + v 检查：字面量取目标的类型，类型参数需要 Num 约束
                 const target_type = try self.checkExpr(ca.target, scope);
-                const value_type = try self.checkExpr(ca.value, scope);
-                // 复合赋值要求类型匹配且支持相应运算
-                if (!target_type.eql(value_type)) {
-                    try self.errors.append(self.allocator, "Type error: compound assignment type mismatch");
+                const op = ca.op.binaryOp();
+                if (self.typeParamName(target_type)) |param| {
+                    try self.requireBound(param, .Num, binaryOpSymbol(op));
+                }
+                const value_type = try self.checkExprExpected(ca.value, scope, target_type);
+                if (!try self.checkLiteralRange(ca.value, target_type)) {
+                    try self.checkArithmeticOperands(target_type, value_type);
                 }
             },
             .let_decl => |let| {
@@ -369,34 +786,58 @@ pub const TypeChecker = struct {
                 try self.mutable_vars.put(let.name, let.is_mut);
                 
                 if (let.init) |init_expr| {
-                    const init_type = try self.checkExpr(init_expr, scope);
+                    // 🆕 声明的类型决定字面量的类型
+                    const init_type = try self.checkExprExpected(init_expr, scope, let.type);
                     
                     if (let.type) |declared_type| {
-                        // 🆕 改进类型兼容性检查
-                        if (!self.isTypeCompatible(init_type, declared_type)) {
-                            try self.errors.append(self.allocator, "Type error: variable type mismatch");
+                        // 🆕 整数字面量：检查值是否在声明类型的范围内
+                        if (try self.checkLiteralRange(init_expr, declared_type)) {
+                            // 范围错误已报告（或字面量合法），不再报告通用的类型不匹配
+                        } else if (!self.isTypeCompatible(init_type, declared_type)) {
+                            // 🆕 改进类型兼容性检查
+                            try self.reportTypeMismatch("initializer of 'let'", declared_type, init_type, self.exprSpan(init_expr));
                         }
                         try scope.put(let.name, declared_type);
                     } else {
+                        // 🆕 未标注类型的整数字面量默认 i32
+                        _ = try self.checkLiteralRange(init_expr, init_type);
                         try scope.put(let.name, init_type);
                     }
                 } else if (let.type) |declared_type| {
                     try scope.put(let.name, declared_type);
                 }
+                if (self.expr_types) |types| {
+                    if (scope.get(let.name)) |t| try types.recordBinding(let.name, t);
+                }
             },
             .return_stmt => |ret| {
+                if (self.current_function) |func| {
+                    if (func.return_type == .never) try self.reportNeverReturns(func, "it contains a 'return'");
+                }
                 if (ret) |expr| {
-                    _ = try self.checkExpr(expr, scope);
+                    // 🆕 返回类型作为期望类型流入返回值
+                    const expected = self.expectedReturnType();
+                    const value_type = try self.checkExprExpected(expr, scope, expected);
+                    if (expected) |want| {
+                        if (!try self.checkLiteralRange(expr, want)) {
+                            try self.checkReturnType(expr, value_type, want);
+                        }
+                    }
                 }
             },
-            .break_stmt, .continue_stmt => {},
+            .break_stmt => |brk| {
+                const value_type: ?ast.Type = if (brk.value) |value| try self.checkExpr(value, scope) else null;
+                try self.checkBreak(brk.label, value_type);
+            },
+            .continue_stmt => |label| _ = try self.findLoop("continue", label),
             .loop_stmt => |loop| {
+                try self.enterLoop(loop.label, false);
+                defer _ = self.loop_stack.pop();
+                
                 // 处理 loop 语句
                 if (loop.condition) |cond| {
                     const cond_type = try self.checkExpr(cond, scope);
-                    if (!cond_type.eql(ast.Type.bool)) {
-                        try self.errors.append(self.allocator, "Type error: loop condition must be Bool");
-                    }
+                    try self.checkBoolOperand("loop condition", cond, cond_type);
                 }
                 
                 // 处理 loop for 迭代器
@@ -425,9 +866,7 @@ pub const TypeChecker = struct {
             },
             .while_loop => |loop| {
                 const cond_type = try self.checkExpr(loop.condition, scope);
-                if (!cond_type.eql(ast.Type.bool)) {
-                    try self.errors.append(self.allocator, "Type error: while condition must be Bool");
-                }
+                try self.checkBoolOperand("while condition", loop.condition, cond_type);
                 
                 for (loop.body) |body_stmt| {
                     try self.checkStmt(body_stmt, scope);
@@ -440,9 +879,7 @@ pub const TypeChecker = struct {
                 
                 if (loop.condition) |cond| {
                     const cond_type = try self.checkExpr(cond, scope);
-                    if (!cond_type.eql(ast.Type.bool)) {
-                        try self.errors.append(self.allocator, "Type error: for condition must be Bool");
-                    }
+                    try self.checkBoolOperand("for condition", cond, cond_type);
                 }
                 
                 if (loop.step) |step| {
@@ -463,24 +900,16 @@ pub const TypeChecker = struct {
         // 完全相同的类型
         if (from_type.eql(to_type)) return true;
         
+        // 🆕 never（break / return 等）不产生值，可以出现在任何类型的位置
+        if (from_type == .never) return true;
+        
         // 🆕 泛型类型兼容：任何类型都可以赋值给泛型类型参数
         if (to_type == .generic) return true;
         if (from_type == .generic) return true;
+        // 🆕 类型参数上的关联类型在单态化时才确定
+        if (to_type == .projection or from_type == .projection) return true;
         
-        // 整数字面量（i32）可以兼容任何整数类型
-        const from_is_int = from_type == .i32;  // 字面量默认类型
-        const to_is_any_int = to_type == .i8 or to_type == .i16 or to_type == .i32 or 
-                             to_type == .i64 or to_type == .i128 or
-                             to_type == .u8 or to_type == .u16 or to_type == .u32 or 
-                             to_type == .u64 or to_type == .u128;
-        
-        if (from_is_int and to_is_any_int) return true;
-        
-        // 浮点字面量（f64）可以兼容任何浮点类型
-        const from_is_float = from_type == .f64;  // 字面量默认类型
-        const to_is_any_float = to_type == .f32 or to_type == .f64;
-        
-        if (from_is_float and to_is_any_float) return true;
+        // 🆕 字面量的类型由上下文决定（见 checkExprExpected），这里不再放宽整数/浮点类型
         
         // 数组类型兼容（已在 Type.eql 中处理）
         
@@ -499,20 +928,33 @@ pub const TypeChecker = struct {
         
         // 从每个参数推导类型
         for (func.params, call_args) |param, arg| {
-            const arg_type = try self.checkExpr(arg, scope);
+            // 🆕 C::Item 参数不参与推导，推导之后按具体的关联类型检查（见 checkProjectionArgs）
+            if (param.type == .projection) continue;
             
-            if (param.type == .generic) {
-                const type_param_name = param.type.generic;
-                
+            // 🆕 签名中的类型参数 T 解析为 named 类型
+            const param_name: ?[]const u8 = switch (param.type) {
+                .generic => |name| name,
+                .named => |name| for (func.type_params) |tp| {
+                    if (std.mem.eql(u8, tp, name)) break name;
+                } else null,
+                else => null,
+            };
+            // 已推导的类型参数作为期望类型（字面量随之取类型）
+            const arg_type = if (param_name) |name|
+                try self.checkExprExpected(arg, scope, type_map.get(name))
+            else
+                try self.checkExpr(arg, scope);
+            
+            if (param_name) |type_param_name| {
                 if (type_map.get(type_param_name)) |existing| {
                     // 类型参数已推导，检查一致性
                     if (!existing.eql(arg_type)) {
                         const err_msg = try std.fmt.allocPrint(
                             self.allocator,
-                            "Error: Type parameter '{s}' cannot be both {s} and {s}",
-                            .{type_param_name, @tagName(existing), @tagName(arg_type)}
+                            "type parameter '{s}' cannot be both '{s}' and '{s}'",
+                            .{type_param_name, self.typeToString(existing), self.typeToString(arg_type)}
                         );
-                        try self.errors.append(self.allocator, err_msg);
+                        try self.reportError(.conflicting_type_argument, err_msg, self.exprSpan(arg));
                     }
                 } else {
                     // 第一次推导此类型参数
@@ -535,6 +977,31 @@ pub const TypeChecker = struct {
         return inferred_types.toOwnedSlice(self.allocator);
     }
     
+    /// 🆕 类型为 C::Item 的参数：C 推导出来后替换为具体的关联类型，再检查实参
+    /// （C 是调用者自己的类型参数时投影保留到单态化，isTypeCompatible 接受它）
+    fn checkProjectionArgs(
+        self: *TypeChecker,
+        func: ast.FunctionDecl,
+        call_args: []ast.Expr,
+        inferred_types: []ast.Type,
+        scope: *std.StringHashMap(ast.Type),
+    ) !void {
+        for (func.params, call_args, 0..) |param, arg, i| {
+            if (param.type != .projection) continue;
+            const param_type = try self.substituteType(param.type, func.type_params, inferred_types);
+            const arg_type = try self.checkExprExpected(arg, scope, param_type);
+            if (try self.checkLiteralRange(arg, param_type)) continue;
+            if (!self.isTypeCompatible(arg_type, param_type)) {
+                const err_msg = try messages.format(
+                    self.allocator,
+                    .function_arg_mismatch,
+                    .{i + 1, func.name}
+                );
+                try self.reportArgumentMismatch(err_msg, param_type, arg_type, self.exprSpan(arg));
+            }
+        }
+    }
+    
     /// 🆕 将泛型类型参数替换为具体类型
     fn substituteType(
         self: *TypeChecker,
@@ -542,7 +1009,6 @@ pub const TypeChecker = struct {
         type_params: [][]const u8,
         type_args: []ast.Type
     ) !ast.Type {
-        _ = self;
         switch (ty) {
             .generic => |name| {
                 // 查找对应的类型参数
@@ -553,119 +1019,752 @@ pub const TypeChecker = struct {
                 }
                 return ty;
             },
+            // 🆕 T::Item：T 替换为具体类型后取该类型的关联类型
+            .projection => |proj| {
+                const base = switch (proj.base.*) {
+                    .generic, .named => |name| for (type_params, type_args) |param, arg| {
+                        if (std.mem.eql(u8, name, param)) break arg;
+                    } else return ty,
+                    else => return ty,
+                };
+                if (self.typeParamName(base) != null) {
+                    const new_base = try self.arena.allocator().create(ast.Type);
+                    new_base.* = base;
+                    return ast.Type{ .projection = .{ .base = new_base, .trait_name = proj.trait_name, .name = proj.name } };
+                }
+                return try self.resolveProjection(base, proj.trait_name, proj.name);
+            },
             else => return ty,
         }
     }
     
+    /// 🆕 具体类型的关联类型；类型没有定义它（或没有实现指定的 trait）时报告错误并返回 i32
+    fn resolveProjection(self: *TypeChecker, base: ast.Type, trait_name: ?[]const u8, name: []const u8) !ast.Type {
+        var type_name = self.typeToString(base);
+        var type_args: []const ast.Type = &.{};
+        if (base == .generic_instance) {
+            type_name = base.generic_instance.name;
+            type_args = base.generic_instance.type_args;
+        }
+        const span: ?Span = if (self.current_function) |func|
+            (if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null)
+        else
+            null;
+        
+        if (trait_name) |tn| {
+            if (self.trait_table.get(tn)) |trait_def| {
+                if (!self.typeImplementsTrait(type_name, trait_def)) {
+                    const error_msg = try std.fmt.allocPrint(self.allocator, "type '{s}' does not implement trait '{s}'", .{ type_name, tn });
+                    const help = try std.fmt.allocPrint(self.allocator, "give '{s}' every method and associated type of '{s}'", .{ type_name, tn });
+                    try self.reportErrorWithHelp(.unknown_associated_type, error_msg, span, help);
+                    return ast.Type.i32;
+                }
+            }
+        }
+        
+        if (self.type_table.get(type_name)) |td| {
+            if (td.assocType(name, type_args)) |target| return target;
+        }
+        const error_msg = try std.fmt.allocPrint(self.allocator, "type '{s}' has no associated type '{s}'", .{ type_name, name });
+        const help = try std.fmt.allocPrint(self.allocator, "add `type {s} = ...;` to '{s}'", .{ name, type_name });
+        try self.reportErrorWithHelp(.unknown_associated_type, error_msg, span, help);
+        return ast.Type.i32;
+    }
+    
+    /// 🆕 函数签名中的投影：base 必须是类型参数（具体类型的投影已展开），
+    /// 名字必须是指定 trait（未指定时任一 trait）声明的关联类型
+    fn checkProjection(self: *TypeChecker, func: ast.FunctionDecl, t: ast.Type) !void {
+        const proj = switch (t) {
+            .projection => |p| p,
+            .pointer => |inner| return self.checkProjection(func, inner.*),
+            .array => |arr| return self.checkProjection(func, arr.element.*),
+            else => return,
+        };
+        const span: ?Span = if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        const base_name = switch (proj.base.*) {
+            .generic, .named => |n| n,
+            else => self.typeToString(proj.base.*),
+        };
+        const is_type_param = for (func.type_params) |tp| {
+            if (std.mem.eql(u8, tp, base_name)) break true;
+        } else false;
+        
+        if (!is_type_param) {
+            // 具体类型（或方法中的 Self）上的投影在解析后已展开：类型没有定义它
+            const error_msg = try std.fmt.allocPrint(self.allocator, "type '{s}' has no associated type '{s}'", .{ base_name, proj.name });
+            const help = try std.fmt.allocPrint(self.allocator, "add `type {s} = ...;` to '{s}'", .{ proj.name, base_name });
+            try self.reportErrorWithHelp(.unknown_associated_type, error_msg, span, help);
+            return;
+        }
+        
+        if (proj.trait_name) |tn| {
+            const trait_def = self.trait_table.get(tn) orelse {
+                const error_msg = try std.fmt.allocPrint(self.allocator, "'{s}' in `<{s} as {s}>::{s}` is not a trait", .{ tn, base_name, tn, proj.name });
+                try self.reportErrorWithHelp(.unknown_associated_type, error_msg, span, try std.fmt.allocPrint(self.allocator, "declare it with `type {s} = trait {{ ... }}`", .{tn}));
+                return;
+            };
+            if (!self.traitDeclaresAssocType(trait_def, proj.name)) {
+                const error_msg = try std.fmt.allocPrint(self.allocator, "trait '{s}' has no associated type '{s}'", .{ tn, proj.name });
+                const help = try std.fmt.allocPrint(self.allocator, "declare it in the trait: `type {s};`", .{proj.name});
+                try self.reportErrorWithHelp(.unknown_associated_type, error_msg, span, help);
+            }
+            return;
+        }
+        
+        var it = self.trait_table.valueIterator();
+        while (it.next()) |trait_def| {
+            for (trait_def.assoc_types) |assoc| {
+                if (std.mem.eql(u8, assoc, proj.name)) return;
+            }
+        }
+        const error_msg = try std.fmt.allocPrint(self.allocator, "no trait declares an associated type '{s}' for '{s}::{s}'", .{ proj.name, base_name, proj.name });
+        const help = try std.fmt.allocPrint(self.allocator, "declare it in a trait: `type {s};`", .{proj.name});
+        try self.reportErrorWithHelp(.unknown_associated_type, error_msg, span, help);
+    }
+    
+    /// 🆕 trait 方法签名中的 Self::Item 必须由该 trait 或它的 supertrait 声明
+    fn checkSelfProjection(self: *TypeChecker, type_decl: ast.TypeDecl, t: ast.Type) !void {
+        const proj = switch (t) {
+            .projection => |p| p,
+            else => return,
+        };
+        if (proj.base.* != .named or !std.mem.eql(u8, proj.base.named, "Self")) return;
+        const trait_def = self.trait_table.get(type_decl.name) orelse return;
+        if (self.traitDeclaresAssocType(trait_def, proj.name)) return;
+        
+        const error_msg = try std.fmt.allocPrint(self.allocator, "trait '{s}' has no associated type '{s}'", .{ type_decl.name, proj.name });
+        const help = try std.fmt.allocPrint(self.allocator, "declare it in the trait: `type {s};`", .{proj.name});
+        const span: ?Span = if (type_decl.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        try self.reportErrorWithHelp(.unknown_associated_type, error_msg, span, help);
+    }
+    
+    /// 🆕 trait 或它的 supertrait 是否声明了关联类型 name
+    fn traitDeclaresAssocType(self: *TypeChecker, trait_def: TraitDef, name: []const u8) bool {
+        var assoc_types = std.ArrayList([]const u8){};
+        defer assoc_types.deinit(self.allocator);
+        self.collectTraitAssocTypes(trait_def, &assoc_types) catch return false;
+        for (assoc_types.items) |assoc| {
+            if (std.mem.eql(u8, assoc, name)) return true;
+        }
+        return false;
+    }
+    
     // ============================================================================
     // Expression Checking
     // ============================================================================
     
+    /// 不带期望类型的表达式检查（类型完全由表达式自身决定）
     fn checkExpr(self: *TypeChecker, expr: ast.Expr, scope: *std.StringHashMap(ast.Type)) (std.mem.Allocator.Error || error{TypeCheckFailed})!ast.Type {
-        return switch (expr) {
-            .int_literal => ast.Type.i32,      // 整数字面量默认 i32
-            .float_literal => ast.Type.f64,    // 浮点字面量默认 f64
-            .string_literal => ast.Type.string,
-            .char_literal => ast.Type.char,
-            .bool_literal => ast.Type.bool,
-            .static_method_call => |smc| blk: {
-                // 🆕 静态方法调用：Type<T>::method()
-                // 简化：返回泛型实例类型或 i32
-                if (smc.type_args.len > 0) {
-                    break :blk ast.Type{ .generic_instance = .{
-                        .name = smc.type_name,
-                        .type_args = smc.type_args,
-                    }};
-                }
-                break :blk ast.Type.i32;
-            },
-            .identifier => |name| blk: {
-                if (scope.get(name)) |var_type| {
-                    break :blk var_type;
-                } else if (self.symbol_table.get(name)) |sym_type| {
-                    break :blk sym_type;
-                } else {
-                    // 🆕 v0.1.8: Enhanced error message for undefined identifier
-                    if (self.identifier_tokens.get(name)) |token| {
-                        const error_msg = try std.fmt.allocPrint(
-                            self.allocator,
-                            "undefined variable '{s}'",
-                            .{name}
-                        );
-                        const span = Span.fromPosition(token.filename, token.line, token.column);
-                        const note_msg = try std.fmt.allocPrint(
-                            self.allocator,
-                            "variable '{s}' is not declared in this scope",
-                            .{name}
-                        );
-                        const notes = try self.allocator.alloc([]const u8, 1);
-                        notes[0] = note_msg;
-                        
-                        // 🆕 v0.1.8: Smart suggestion - find similar variable
-                        var help: ?[]const u8 = null;
-                        if (self.findSimilarVariable(name, scope)) |similar| {
-                            help = try std.fmt.allocPrint(
-                                self.allocator,
-                                "did you mean '{s}'?",
-                                .{similar}
-                            );
-                        }
-                        
-                        const diag = Diagnostic.init(.Error, error_msg, span, notes, help);
-                        try self.diagnostics.append(self.allocator, diag);
-                    } else {
-                        try self.errors.append(self.allocator, "Error: undefined identifier");
-                    }
-                    break :blk ast.Type.void;
-                }
-            },
-            .binary => |bin| blk: {
-                const left_type = try self.checkExpr(bin.left.*, scope);
-                const right_type = try self.checkExpr(bin.right.*, scope);
-                
-                switch (bin.op) {
-                    .add, .sub, .mul, .div, .mod => {
-                        if (!left_type.eql(right_type)) {
-                            // 🆕 v0.1.8: Enhanced error for type mismatch
-                            const error_msg = try std.fmt.allocPrint(
-                                self.allocator,
-                                "type mismatch: expected '{s}', found '{s}'",
-                                .{self.typeToString(left_type), self.typeToString(right_type)}
-                            );
-                            const note_msg = try std.fmt.allocPrint(
-                                self.allocator,
-                                "binary operator requires both operands to have the same type",
-                                .{}
-                            );
-                            const notes = try self.allocator.alloc([]const u8, 1);
-                            notes[0] = note_msg;
-                            const diag = Diagnostic.init(.Error, error_msg, null, notes, null);
-                            try self.diagnostics.append(self.allocator, diag);
-                        }
-                        break :blk left_type;
-                    },
-                    .eq, .ne, .lt, .le, .gt, .ge => {
-                        if (!left_type.eql(right_type)) {
-                            try self.errors.append(self.allocator, "Type error: comparison types must match");
-                        }
-                        break :blk ast.Type.bool;
-                    },
-                    .and_op, .or_op => {
-                        if (!left_type.eql(ast.Type.bool) or !right_type.eql(ast.Type.bool)) {
-                            try self.errors.append(self.allocator, "Type error: logical ops require Bool");
+        return self.checkExprExpected(expr, scope, null);
+    }
+    
+    // ============================================================================
+    // 🆕 内置约束（Num / Ord / Eq / Display）
+    // ============================================================================
+    
+    fn binaryOpSymbol(op: ast.BinaryOp) []const u8 {
+        return switch (op) {
+            .add => "+", .sub => "-", .mul => "*", .div => "/", .mod => "%",
+            .eq => "==", .ne => "!=", .lt => "<", .le => "<=", .gt => ">", .ge => ">=",
+            .and_op => "&&", .or_op => "||",
+        };
+    }
+    
+    /// 类型是否是当前函数的类型参数（参数类型 T 被解析为 named 类型）
+    fn typeParamName(self: *TypeChecker, t: ast.Type) ?[]const u8 {
+        const name = switch (t) {
+            .generic => |n| n,
+            .named => |n| n,
+            else => return null,
+        };
+        const func = self.current_function orelse return null;
+        for (func.type_params) |tp| {
+            if (std.mem.eql(u8, tp, name)) return name;
+        }
+        return null;
+    }
+    
+    /// 当前函数是否为类型参数 param 声明了蕴含 bound 的约束
+    fn hasBound(self: *TypeChecker, param: []const u8, bound: ast.BuiltinBound) bool {
+        const func = self.current_function orelse return false;
+        for (func.bounds) |b| {
+            if (std.mem.eql(u8, b.param, param) and b.bound.implies(bound)) return true;
+        }
+        return false;
+    }
+    
+    /// 在类型参数上使用运算符时检查约束
+    fn requireBound(self: *TypeChecker, param: []const u8, bound: ast.BuiltinBound, op: []const u8) !void {
+        if (self.hasBound(param, bound)) return;
+        
+        const error_msg = try std.fmt.allocPrint(
+            self.allocator,
+            "operator '{s}' cannot be applied to type parameter '{s}'",
+            .{ op, param },
+        );
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = try std.fmt.allocPrint(self.allocator, "'{s}' requires the bound '{s}'", .{ op, @tagName(bound) });
+        const help = try std.fmt.allocPrint(self.allocator, "add a bound: <{s}: {s}> or `where {s}: {s}`", .{ param, @tagName(bound), param, @tagName(bound) });
+        const span: ?Span = if (self.current_function) |func|
+            (if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null)
+        else
+            null;
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.missing_bound));
+    }
+    
+    /// 调用泛型函数时验证每个约束：具体类型必须满足，类型参数必须有蕴含它的约束
+    fn checkBoundsAtCall(self: *TypeChecker, func: ast.FunctionDecl, inferred_types: []const ast.Type, span: ?Span) !void {
+        for (func.bounds) |b| {
+            const index = for (func.type_params, 0..) |tp, i| {
+                if (std.mem.eql(u8, tp, b.param)) break i;
+            } else continue;
+            if (index >= inferred_types.len) continue;
+            const actual = inferred_types[index];
+            
+            const caller_param = self.typeParamName(actual);
+            if (self.boundHolds(b.bound, actual)) continue;
+            
+            const error_msg = try std.fmt.allocPrint(
+                self.allocator,
+                "the type '{s}' does not satisfy the bound '{s}'",
+                .{ self.typeToString(actual), @tagName(b.bound) },
+            );
+            const notes = try self.allocator.alloc([]const u8, 1);
+            notes[0] = try std.fmt.allocPrint(
+                self.allocator,
+                "required by the bound '{s}: {s}' on '{s}'",
+                .{ b.param, @tagName(b.bound), func.name },
+            );
+            const help: ?[]const u8 = if (caller_param) |param|
+                try std.fmt.allocPrint(self.allocator, "add the bound to the caller: <{s}: {s}>", .{ param, @tagName(b.bound) })
+            else
+                null;
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.missing_bound));
+        }
+    }
+    
+    /// 🆕 receiver.method(...)：receiver 是泛型类型的实例时，方法对类型参数的约束必须被类型实参满足
+    /// （`fn show(self) -> string where T: Display` 只在 Box<i32> 这样的实例上存在）。错误报告在调用处
+    fn checkReceiverBounds(self: *TypeChecker, receiver: ast.Expr, method_name: []const u8, span: ?Span, scope: *std.StringHashMap(ast.Type)) !void {
+        const receiver_type = try self.checkExpr(receiver, scope);
+        const instance = switch (receiver_type) {
+            .generic_instance => |gi| gi,
+            else => return,
+        };
+        const type_decl = self.type_table.get(instance.name) orelse return;
+        const methods = switch (type_decl.kind) {
+            .struct_type => |st| st.methods,
+            .enum_type => |et| et.methods,
+            else => return,
+        };
+        const method = for (methods) |m| {
+            if (std.mem.eql(u8, m.name, method_name)) break m;
+        } else return;
+        
+        for (method.bounds) |b| {
+            const actual = ast.typeArgFor(type_decl.type_params, instance.type_args, b.param) orelse continue;
+            if (self.boundHolds(b.bound, actual)) continue;
+            return self.reportUnmetReceiverBound(receiver_type, type_decl.name, method_name, b.param, @tagName(b.bound), actual, span);
+        }
+        // 🆕 where T: Trait（impl 块的约束）
+        for (method.trait_bounds) |tb| {
+            const actual = ast.typeArgFor(type_decl.type_params, instance.type_args, tb.param) orelse continue;
+            if (self.typeSatisfiesTrait(actual, tb.trait_name)) continue;
+            return self.reportUnmetReceiverBound(receiver_type, type_decl.name, method_name, tb.param, tb.trait_name, actual, span);
+        }
+    }
+    
+    fn reportUnmetReceiverBound(
+        self: *TypeChecker,
+        receiver_type: ast.Type,
+        type_name: []const u8,
+        method_name: []const u8,
+        param: []const u8,
+        bound_name: []const u8,
+        actual: ast.Type,
+        span: ?Span,
+    ) !void {
+        const error_msg = try std.fmt.allocPrint(
+            self.allocator,
+            "method '{s}' of '{s}' requires '{s}: {s}'",
+            .{ method_name, self.typeToString(receiver_type), param, bound_name },
+        );
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = try std.fmt.allocPrint(
+            self.allocator,
+            "'{s}' is '{s}', which does not satisfy '{s}'",
+            .{ param, self.typeToString(actual), bound_name },
+        );
+        const help = try std.fmt.allocPrint(
+            self.allocator,
+            "the method only exists on instances of '{s}' whose '{s}' satisfies '{s}'",
+            .{ type_name, param, bound_name },
+        );
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.missing_bound));
+    }
+    
+    /// 🆕 类型满足用户 trait 约束：具体类型（结构上）实现了 trait；泛型类型的实例还要求
+    /// trait 的方法在该实例上存在（impl 块的 where 约束被类型实参满足）；
+    /// 类型参数要由当前函数的 where T: Trait 保证。未声明的 trait 已在声明处报告
+    fn typeSatisfiesTrait(self: *TypeChecker, actual: ast.Type, trait_name: []const u8) bool {
+        const trait_def = self.trait_table.get(trait_name) orelse return true;
+        if (self.typeParamName(actual)) |param| {
+            const func = self.current_function orelse return false;
+            for (func.trait_bounds) |tb| {
+                if (std.mem.eql(u8, tb.param, param) and self.traitImplies(tb.trait_name, trait_name)) return true;
+            }
+            return false;
+        }
+        switch (actual) {
+            .named => |n| return self.typeImplementsTrait(n, trait_def),
+            .generic_instance => |gi| {
+                if (!self.typeImplementsTrait(gi.name, trait_def)) return false;
+                const type_decl = self.type_table.get(gi.name) orelse return false;
+                const type_methods = self.type_methods.get(gi.name) orelse return false;
+                var trait_methods = std.ArrayList(ast.FunctionSignature){};
+                defer trait_methods.deinit(self.allocator);
+                self.collectTraitMethods(trait_def, &trait_methods) catch return false;
+                for (trait_methods.items) |trait_method| {
+                    const method = type_methods.methods.get(trait_method.name) orelse return false;
+                    for (method.bounds) |b| {
+                        const arg = ast.typeArgFor(type_decl.type_params, gi.type_args, b.param) orelse continue;
+                        if (!self.boundHolds(b.bound, arg)) return false;
+                    }
+                    for (method.trait_bounds) |tb| {
+                        const arg = ast.typeArgFor(type_decl.type_params, gi.type_args, tb.param) orelse continue;
+                        if (!self.typeSatisfiesTrait(arg, tb.trait_name)) return false;
+                    }
+                }
+                return true;
+            },
+            else => return false,
+        }
+    }
+    
+    /// 🆕 trait have 是 want 本身或以 want 为（间接）supertrait
+    fn traitImplies(self: *TypeChecker, have: []const u8, want: []const u8) bool {
+        if (std.mem.eql(u8, have, want)) return true;
+        const have_def = self.trait_table.get(have) orelse return false;
+        var supertraits = std.ArrayList(TraitDef){};
+        defer supertraits.deinit(self.allocator);
+        var visited = std.StringHashMap(void).init(self.allocator);
+        defer visited.deinit();
+        supertraits.append(self.allocator, have_def) catch return false;
+        while (supertraits.pop()) |current| {
+            if (std.mem.eql(u8, current.name, want)) return true;
+            if ((visited.getOrPut(current.name) catch return false).found_existing) continue;
+            for (current.supertraits) |super| {
+                supertraits.append(self.allocator, self.trait_table.get(super.name) orelse continue) catch return false;
+            }
+        }
+        return false;
+    }
+    
+    /// 具体类型满足约束，或类型参数（调用者的）有蕴含它的约束
+    fn boundHolds(self: *TypeChecker, bound: ast.BuiltinBound, actual: ast.Type) bool {
+        return if (self.typeParamName(actual)) |param| self.hasBound(param, bound) else bound.satisfiedBy(actual);
+    }
+    
+    /// 🆕 --explain-overloads：记录泛型调用的替换和约束检查
+    fn traceGenericCall(self: *TypeChecker, func: ast.FunctionDecl, inferred_types: []const ast.Type, span: ?Span) !void {
+        const trace = self.resolution_trace orelse return;
+        const a = trace.allocator();
+        
+        var name = std.ArrayList(u8){};
+        var substitution = std.ArrayList(u8){};
+        try name.writer(a).print("{s}<", .{func.name});
+        for (func.type_params, 0..) |tp, i| {
+            if (i > 0) {
+                try name.appendSlice(a, ", ");
+                try substitution.appendSlice(a, ", ");
+            }
+            try name.appendSlice(a, tp);
+            const actual = if (i < inferred_types.len) self.typeToString(inferred_types[i]) else "?";
+            try substitution.writer(a).print("{s} = {s}", .{ tp, actual });
+        }
+        try name.append(a, '>');
+        
+        const checks = try a.alloc(resolution_trace.Check, func.bounds.len);
+        var count: usize = 0;
+        for (func.bounds) |b| {
+            const index = for (func.type_params, 0..) |tp, i| {
+                if (std.mem.eql(u8, tp, b.param)) break i;
+            } else continue;
+            if (index >= inferred_types.len) continue;
+            const actual = inferred_types[index];
+            checks[count] = .{
+                .description = try std.fmt.allocPrint(a, "{s}: {s} (where {s} = {s})", .{ b.param, @tagName(b.bound), b.param, self.typeToString(actual) }),
+                .passed = self.boundHolds(b.bound, actual),
+            };
+            count += 1;
+        }
+        
+        const candidates = try a.alloc(resolution_trace.Candidate, 1);
+        candidates[0] = .{ .name = name.items, .substitution = substitution.items, .checks = checks[0..count] };
+        try trace.record(.{
+            .callee = func.name,
+            .span = span,
+            .candidates = candidates,
+            .selected = if (candidates[0].isViable()) name.items else null,
+        });
+    }
+    
+    /// 🆕 调用处的位置（identifierSpan 是该名字最后一次出现的位置，不一定是这次调用）
+    fn callSpan(self: *TypeChecker, call: anytype, name: []const u8) ?Span {
+        const loc = call.location orelse return self.identifierSpan(name);
+        return Span.fromPosition(loc.file, loc.line, loc.column);
+    }
+    
+    fn identifierSpan(self: *TypeChecker, name: []const u8) ?Span {
+        const token = self.identifier_tokens.get(name) orelse return null;
+        return Span.fromPosition(token.file, token.line, token.column);
+    }
+    
+    /// 🆕 期望类型为 void：表达式的值被丢弃（表达式语句）
+    fn isDiscarded(expected: ?ast.Type) bool {
+        return if (expected) |t| t == .void else false;
+    }
+    
+    /// 🆕 未定类型的数值字面量（类型由上下文决定）
+    fn isUntypedLiteral(expr: ast.Expr) bool {
+        return switch (expr) {
+            .int_literal, .float_literal => true,
+            .unary => |un| un.op == .neg and isUntypedLiteral(un.operand.*),
+            else => false,
+        };
+    }
+    
+    fn isIntType(t: ast.Type) bool {
+        return switch (t) {
+            .i8, .i16, .i32, .i64, .i128, .u8, .u16, .u32, .u64, .u128 => true,
+            else => false,
+        };
+    }
+    
+    fn isUnsignedType(t: ast.Type) bool {
+        return switch (t) {
+            .u8, .u16, .u32, .u64, .u128 => true,
+            else => false,
+        };
+    }
+    
+    /// 🆕 当前函数的返回类型（作为 return 值的期望类型）
+    fn expectedReturnType(self: *TypeChecker) ?ast.Type {
+        const func = self.current_function orelse return null;
+        return switch (func.return_type) {
+            .void, .never => null,
+            .named => |name| if (std.mem.eql(u8, name, "Self")) null else func.return_type,
+            else => func.return_type,
+        };
+    }
+    
+    /// 标量类型：类型检查对它们的推导是可靠的
+    fn isScalarType(t: ast.Type) bool {
+        return isIntType(t) or switch (t) {
+            .f32, .f64, .bool, .char, .string => true,
+            else => false,
+        };
+    }
+    
+    /// 🆕 检查 return 值与声明的返回类型是否一致
+    fn checkReturnType(self: *TypeChecker, expr: ast.Expr, value_type: ast.Type, want: ast.Type) !void {
+        if (self.isTypeCompatible(value_type, want)) return;
+        // 只比较标量类型；i32 同时是推导不出类型时的占位类型（字段访问、未知调用），
+        // 非字面量的 i32 不作为证据
+        if (!isScalarType(want) or !isScalarType(value_type)) return;
+        if (value_type == .i32 and !isUntypedLiteral(expr)) return;
+        
+        const func = self.current_function.?;
+        const error_msg = try std.fmt.allocPrint(
+            self.allocator,
+            "mismatched types: expected '{s}', found '{s}'",
+            .{ self.typeToString(want), self.typeToString(value_type) },
+        );
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = if (func.location) |loc|
+            try std.fmt.allocPrint(
+                self.allocator,
+                "expected '{s}' because of the return type of '{s}' declared here: {s}:{d}:{d}",
+                .{ self.typeToString(want), func.name, loc.file.path(), loc.line, loc.column },
+            )
+        else
+            try std.fmt.allocPrint(
+                self.allocator,
+                "expected '{s}' because of the return type of '{s}'",
+                .{ self.typeToString(want), func.name },
+            );
+        const span: ?Span = if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        const help: ?[]const u8 = if (isScalarType(value_type) and value_type != .string and value_type != .bool and want != .string and want != .bool)
+            try std.fmt.allocPrint(self.allocator, "convert the value explicitly: `as {s}`", .{self.typeToString(want)})
+        else
+            null;
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.mismatched_types));
+    }
+    
+    /// 🆕 分支类型不一致
+    fn reportBranchMismatch(self: *TypeChecker, message: []const u8, expected: ast.Type, found: ast.Type, reason: []const u8) !void {
+        const error_msg = try std.fmt.allocPrint(
+            self.allocator,
+            "{s}: expected '{s}', found '{s}'",
+            .{ message, self.typeToString(expected), self.typeToString(found) },
+        );
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = try self.allocator.dupe(u8, reason);
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, null, notes, null).withCode(.mismatched_types));
+    }
+    
+    /// 🆕 m.name 引用命名空间导入的模块中的私有项：只有同一别名下的副本（模块自己的函数）可以引用
+    fn checkNamespaceAccess(self: *TypeChecker, name: []const u8, span: ?Span) !void {
+        const alias = self.namespace_private.get(name) orelse return;
+        if (self.current_function) |func| {
+            if (func.namespace) |current| {
+                if (std.mem.eql(u8, current, alias)) return;
+            }
+        }
+        const item = name[alias.len + 2 ..];
+        const error_msg = try std.fmt.allocPrint(self.allocator, "'{s}' is private to the module imported as '{s}'", .{ item, alias });
+        const help = try std.fmt.allocPrint(self.allocator, "mark '{s}' as `pub` in the module to use it as {s}.{s}", .{ item, alias, item });
+        try self.reportErrorWithHelp(.private_item, error_msg, span orelse self.identifierSpan(item), help);
+    }
+    
+    /// 🆕 赋值、let 初始值和数组元素的类型与期望的类型不一致
+    fn reportTypeMismatch(self: *TypeChecker, context: []const u8, expected: ast.Type, found: ast.Type, span: ?Span) !void {
+        const error_msg = try std.fmt.allocPrint(
+            self.allocator,
+            "mismatched types in {s}: expected '{s}', found '{s}'",
+            .{ context, self.typeToString(expected), self.typeToString(found) },
+        );
+        try self.reportError(.mismatched_types, error_msg, span);
+    }
+    
+    /// 🆕 实参类型与形参类型不一致：帮助信息给出两边的类型
+    fn reportArgumentMismatch(self: *TypeChecker, message: []const u8, param_type: ast.Type, arg_type: ast.Type, span: ?Span) !void {
+        const help = try std.fmt.allocPrint(self.allocator, "expected '{s}', found '{s}'", .{ self.typeToString(param_type), self.typeToString(arg_type) });
+        try self.reportErrorFull(.mismatched_types, message, span, &[_][]const u8{}, help);
+    }
+    
+    /// 🆕 条件、守卫和逻辑运算的操作数必须是 bool
+    fn checkBoolOperand(self: *TypeChecker, context: []const u8, expr: ast.Expr, actual: ast.Type) !void {
+        if (actual.eql(ast.Type.bool)) return;
+        const error_msg = try std.fmt.allocPrint(self.allocator, "{s} must be 'bool', found '{s}'", .{ context, self.typeToString(actual) });
+        try self.reportError(.mismatched_types, error_msg, self.exprSpan(expr));
+    }
+    
+    const OperandTypes = struct { left: ast.Type, right: ast.Type };
+    
+    /// 检查二元运算的一对操作数：字面量一侧取另一侧的类型
+    fn checkOperands(
+        self: *TypeChecker,
+        left: ast.Expr,
+        right: ast.Expr,
+        scope: *std.StringHashMap(ast.Type),
+        expected: ?ast.Type,
+    ) (std.mem.Allocator.Error || error{TypeCheckFailed})!OperandTypes {
+        if (isUntypedLiteral(left) and !isUntypedLiteral(right)) {
+            const right_type = try self.checkExprExpected(right, scope, expected);
+            return .{ .left = try self.checkExprExpected(left, scope, right_type), .right = right_type };
+        }
+        const left_type = try self.checkExprExpected(left, scope, expected);
+        return .{ .left = left_type, .right = try self.checkExprExpected(right, scope, left_type) };
+    }
+    
+    fn checkArithmeticOperands(self: *TypeChecker, left_type: ast.Type, right_type: ast.Type) !void {
+        if (left_type.eql(right_type)) return;
+        
+        // 🆕 v0.1.8: Enhanced error for type mismatch
+        const error_msg = try std.fmt.allocPrint(
+            self.allocator,
+            "type mismatch: expected '{s}', found '{s}'",
+            .{self.typeToString(left_type), self.typeToString(right_type)}
+        );
+        const note_msg = try std.fmt.allocPrint(
+            self.allocator,
+            "binary operator requires both operands to have the same type",
+            .{}
+        );
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = note_msg;
+        const diag = Diagnostic.init(.Error, error_msg, null, notes, null).withCode(.mismatched_types);
+        try self.diagnostics.append(self.allocator, diag);
+    }
+    
+    /// 🆕 双向类型检查：expected 是上下文期望的类型
+    ///
+    /// 期望类型从 let 标注、参数类型、返回类型流入表达式，并继续传给
+    /// if / is 分支、块的尾表达式和数组元素。数值字面量没有固定类型：
+    /// 期望整数（浮点）类型时直接取该类型，没有约束时退回默认的 i32 / f64。
+    /// 期望类型只用于推导，是否匹配由提供期望类型的一方检查并报告。
+    fn checkExprExpected(
+        self: *TypeChecker,
+        expr: ast.Expr,
+        scope: *std.StringHashMap(ast.Type),
+        expected: ?ast.Type,
+    ) (std.mem.Allocator.Error || error{TypeCheckFailed})!ast.Type {
+        const checked_type: ast.Type = switch (expr) {
+            .int_literal => if (expected != null and isIntType(expected.?)) expected.? else ast.Type.i32,
+            .float_literal => if (expected != null and (expected.? == .f32 or expected.? == .f64)) expected.? else ast.Type.f64,
+            .string_literal => ast.Type.string,
+            .char_literal => ast.Type.char,
+            .byte_literal => ast.Type.u8,  // 🆕 b'A'
+            .type_query => |tq| blk: {
+                // 🆕 size_of<T>() / align_of<T>()：泛型参数在单态化时求值
+                if (self.mentionsTypeParam(tq.type, scope)) break :blk ast.Type.i32;
+                _ = layout.ofType(tq.type, &self.type_table, layout.Bindings.empty) catch |err| {
+                    const name = if (tq.kind == .size_of) "size_of" else "align_of";
+                    const error_msg = try std.fmt.allocPrint(self.allocator, "cannot compute {s} for this type: {s}", .{name, layout.describeError(err)});
+                    try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, null, &[_][]const u8{}, null).withCode(.unknown_layout));
+                };
+                break :blk ast.Type.i32;
+            },
+            .byte_string_literal => |bytes| blk: {
+                // 🆕 b"..." 的类型是 [u8; N]
+                const elem = try self.arena.allocator().create(ast.Type);
+                elem.* = ast.Type.u8;
+                break :blk ast.Type{ .array = .{ .element = elem, .size = bytes.len } };
+            },
+            .bool_literal => ast.Type.bool,
+            .static_method_call => |smc| blk: {
+                // 🆕 静态方法调用：Type<T>::method()
+                // 简化：返回泛型实例类型或 i32
+                if (smc.type_args.len > 0) {
+                    break :blk ast.Type{ .generic_instance = .{
+                        .name = smc.type_name,
+                        .type_args = smc.type_args,
+                    }};
+                }
+                break :blk ast.Type.i32;
+            },
+            .identifier => |name| blk: {
+                if (scope.get(name)) |var_type| {
+                    break :blk var_type;
+                } else if (self.symbol_table.get(name)) |sym_type| {
+                    try self.checkNamespaceAccess(name, self.exprSpan(expr));
+                    break :blk sym_type;
+                } else if (self.function_table.get(name)) |func| {
+                    try self.checkNamespaceAccess(name, self.exprSpan(expr));
+                    // 🆕 函数名作为值：类型为 fn(参数类型...) -> 返回类型
+                    if (func.type_params.len > 0) {
+                        const error_msg = try std.fmt.allocPrint(self.allocator, "cannot use generic function '{s}' as a value", .{name});
+                        try self.reportErrorWithHelp(
+                            .generic_function_value,
+                            error_msg,
+                            self.identifierSpan(name),
+                            try self.allocator.dupe(u8, "wrap it in a non-generic function that calls it with concrete types"),
+                        );
+                        break :blk ast.Type.void;
+                    }
+                    // 🆕 @track_caller 函数的调用处位置只在直接调用时传入
+                    if (func.track_caller) {
+                        const error_msg = try std.fmt.allocPrint(self.allocator, "cannot use @track_caller function '{s}' as a value", .{name});
+                        try self.reportErrorWithHelp(
+                            .track_caller_function_value,
+                            error_msg,
+                            self.identifierSpan(name),
+                            try std.fmt.allocPrint(self.allocator, "call it directly, or wrap the call in a lambda: |...| {s}(...)", .{name}),
+                        );
+                        break :blk ast.Type.void;
+                    }
+                    break :blk try func.valueType(self.arena.allocator());
+                } else {
+                    // 🆕 v0.1.8: Enhanced error message for undefined identifier
+                    if (self.identifier_tokens.get(name)) |token| {
+                        const error_msg = try messages.format(
+                            self.allocator,
+                            .undefined_variable,
+                            .{name}
+                        );
+                        const span = Span.fromPosition(token.file, token.line, token.column);
+                        const note_msg = try messages.format(
+                            self.allocator,
+                            .undefined_variable_note,
+                            .{name}
+                        );
+                        const notes = try self.allocator.alloc([]const u8, 1);
+                        notes[0] = note_msg;
+                        
+                        // 🆕 v0.1.8: Smart suggestion - find similar variable
+                        var help: ?[]const u8 = null;
+                        if (self.findSimilarVariable(name, scope)) |similar| {
+                            help = try messages.format(
+                                self.allocator,
+                                .did_you_mean,
+                                .{similar}
+                            );
+                        }
+                        
+                        const diag = Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.undefined_variable);
+                        try self.diagnostics.append(self.allocator, diag);
+                    } else {
+                        try self.reportError(.undefined_variable, try messages.format(self.allocator, .undefined_identifier, .{}), self.identifierSpan(name));
+                    }
+                    break :blk ast.Type.void;
+                }
+            },
+            .binary => |bin| blk: {
+                // 🆕 字面量操作数取另一侧的类型（x + 1 中的 1 与 x 同类型）；
+                // 算术运算的结果与操作数同类型，期望类型继续传给操作数
+                const operand_expected = switch (bin.op) {
+                    .add, .sub, .mul, .div, .mod => expected,
+                    else => null,
+                };
+                const operands = try self.checkOperands(bin.left.*, bin.right.*, scope, operand_expected);
+                const left_type = operands.left;
+                const right_type = operands.right;
+                
+                // 🆕 类型参数上的运算符需要内置约束（Num / Ord / Eq）
+                if (self.typeParamName(left_type)) |param| {
+                    const required: ?ast.BuiltinBound = switch (bin.op) {
+                        .add, .sub, .mul, .div, .mod => .Num,
+                        .lt, .le, .gt, .ge => .Ord,
+                        .eq, .ne => .Eq,
+                        .and_op, .or_op => null,
+                    };
+                    if (required) |bound| try self.requireBound(param, bound, binaryOpSymbol(bin.op));
+                }
+                
+                // 🆕 用户类型上的运算符调用左操作数类型的方法（Add<T>、Equal<T>、Compare<T> 等）
+                if (try self.checkOperatorOverload(bin, left_type, right_type)) |result_type| break :blk result_type;
+                
+                switch (bin.op) {
+                    .add, .sub, .mul, .div, .mod => {
+                        try self.checkArithmeticOperands(left_type, right_type);
+                        break :blk left_type;
+                    },
+                    .eq, .ne, .lt, .le, .gt, .ge => {
+                        if (!left_type.eql(right_type)) {
+                            const error_msg = try std.fmt.allocPrint(self.allocator, "cannot compare '{s}' with '{s}'", .{ self.typeToString(left_type), self.typeToString(right_type) });
+                            try self.reportErrorWithHelp(.mismatched_types, error_msg, self.exprSpan(expr), try self.allocator.dupe(u8, "both sides of a comparison must have the same type; convert one side with `as`"));
                         }
                         break :blk ast.Type.bool;
                     },
+                    .and_op, .or_op => {
+                        const what = if (bin.op == .and_op) "operand of '&&'" else "operand of '||'";
+                        try self.checkBoolOperand(what, bin.left.*, left_type);
+                        try self.checkBoolOperand(what, bin.right.*, right_type);
+                        break :blk ast.Type.bool;
+                    },
                 }
             },
             .unary => |un| blk: {
-                const operand_type = try self.checkExpr(un.operand.*, scope);
+                const operand_type = try self.checkExprExpected(un.operand.*, scope, if (un.op == .neg) expected else null);
                 switch (un.op) {
-                    .neg => break :blk operand_type,
-                    .not => {
-                        if (!operand_type.eql(ast.Type.bool)) {
-                            try self.errors.append(self.allocator, "Type error: ! requires Bool");
+                    .neg => {
+                        if (self.typeParamName(operand_type)) |param| try self.requireBound(param, .Num, "-");
+                        // 🆕 无符号值不能取负（负的字面量由范围检查报告）
+                        if (isUnsignedType(operand_type) and literalValue(un.operand.*) == null) {
+                            const error_msg = try std.fmt.allocPrint(self.allocator, "cannot negate a value of unsigned type '{s}'", .{self.typeToString(operand_type)});
+                            try self.diagnostics.append(self.allocator, Diagnostic.init(
+                                .Error,
+                                error_msg,
+                                null,
+                                &[_][]const u8{},
+                                "convert it to a signed type first, e.g. `-(x as i64)`",
+                            ).withCode(.unsigned_negation));
                         }
+                        break :blk operand_type;
+                    },
+                    .not => {
+                        try self.checkBoolOperand("operand of '!'", un.operand.*, operand_type);
                         break :blk ast.Type.bool;
                     },
                 }
@@ -675,6 +1774,18 @@ pub const TypeChecker = struct {
                 if (call.callee.* == .identifier) {
                     const func_name = call.callee.identifier;
                     
+                    // 🆕 通过函数类型的变量调用：f(x)
+                    if (scope.get(func_name)) |var_type| {
+                        if (var_type == .function) {
+                            break :blk try self.checkFunctionValueCall(func_name, var_type, call, scope);
+                        }
+                        if (!self.function_table.contains(func_name) and var_type != .generic) {
+                            const error_msg = try std.fmt.allocPrint(self.allocator, "'{s}' is not a function: it has type '{s}'", .{ func_name, self.typeToString(var_type) });
+                            try self.reportError(.not_callable, error_msg, self.callSpan(call, func_name));
+                            break :blk ast.Type.void;
+                        }
+                    }
+                    
                     // 查找是否是enum variant
                     var type_iter = self.type_table.iterator();
                     while (type_iter.next()) |entry| {
@@ -691,14 +1802,17 @@ pub const TypeChecker = struct {
                     
                     // 不是enum构造器，检查是否是函数
                     if (self.function_table.get(func_name)) |func| {
+                        try self.checkNamespaceAccess(func_name, self.callSpan(call, func_name));
                         // 🆕 检查参数数量
                         if (call.args.len != func.params.len) {
-                            const err_msg = try std.fmt.allocPrint(
+                            // 🆕 @track_caller 函数的两个隐藏位置参数不计入
+                            const hidden: usize = if (func.track_caller) 2 else 0;
+                            const err_msg = try messages.format(
                                 self.allocator,
-                                "Error: Function '{s}' expects {d} arguments, but got {d}",
-                                .{func_name, func.params.len, call.args.len}
+                                .function_arg_count,
+                                .{func_name, func.params.len - hidden, call.args.len -| hidden}
                             );
-                            try self.errors.append(self.allocator, err_msg);
+                            try self.reportError(.wrong_argument_count, err_msg, self.callSpan(call, func_name));
                             break :blk ast.Type.void;
                         }
                         
@@ -707,6 +1821,15 @@ pub const TypeChecker = struct {
                             const inferred_types = try self.inferGenericTypes(func, call.args, scope);
                             defer self.allocator.free(inferred_types);
                             
+                            try self.checkProjectionArgs(func, call.args, inferred_types, scope);
+                            
+                            // 🆕 验证推导出的具体类型满足约束
+                            const call_span = self.callSpan(call, func_name);
+                            try self.checkBoundsAtCall(func, inferred_types, call_span);
+                            try self.traceGenericCall(func, inferred_types, call_span);
+                            // 🆕 记录推导结果，后端据此选择单态化实例
+                            call.resolved_type_args.* = try self.arena.allocator().dupe(ast.Type, inferred_types);
+                            
                             // 返回替换后的返回类型
                             const return_type = try self.substituteType(
                                 func.return_type,
@@ -717,22 +1840,56 @@ pub const TypeChecker = struct {
                         } else {
                             // 非泛型函数：检查参数类型
                             for (call.args, 0..) |arg, i| {
-                                const arg_type = try self.checkExpr(arg, scope);
                                 const param_type = func.params[i].type;
+                                const arg_type = try self.checkExprExpected(arg, scope, param_type);
                                 
+                                if (try self.checkLiteralRange(arg, param_type)) continue;
                                 if (!self.isTypeCompatible(arg_type, param_type)) {
-                                    const err_msg = try std.fmt.allocPrint(
+                                    const err_msg = try messages.format(
                                         self.allocator,
-                                        "Error: Argument {d} type mismatch in '{s}'",
+                                        .function_arg_mismatch,
                                         .{i + 1, func_name}
                                     );
-                                    try self.errors.append(self.allocator, err_msg);
+                                    try self.reportArgumentMismatch(err_msg, param_type, arg_type, self.exprSpan(arg));
                                 }
                             }
                             
                             break :blk func.return_type;
                         }
                     }
+                    
+                    // 🆕 直接调用 trait 方法：show(x) 等价于 x.show()
+                    if (scope.get(func_name) == null) {
+                        if (try self.resolveTraitMethodCall(func_name, call, scope)) |return_type| {
+                            break :blk return_type;
+                        }
+                    }
+                    
+                    // 🆕 into(x)：转换为期望的类型（与 x as T 相同的 From 转换）
+                    if (std.mem.eql(u8, func_name, ast.INTO_BUILTIN) and scope.get(func_name) == null) {
+                        break :blk try self.checkInto(call, scope, expected);
+                    }
+                    
+                    // 🆕 未知函数：报错并建议可导入的模块
+                    // （只针对本文件中出现的调用；导入模块的私有辅助函数不在 function_table 中）
+                    if (self.isUnknownFunctionCall(call, scope)) {
+                        try self.reportUnknownFunction(func_name, call.location);
+                        break :blk ast.Type.void;
+                    }
+                }
+                
+                // 🆕 泛型类型实例上的方法调用：方法对类型参数的 where 约束必须被实例满足
+                if (call.callee.* == .field_access) {
+                    const call_span: ?Span = if (call.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+                    try self.checkReceiverBounds(call.callee.field_access.object.*, call.callee.field_access.field, call_span, scope);
+                }
+                
+                // 🆕 调用表达式的值：make_adder()(x)、table[i](x)
+                if (call.callee.* != .field_access and call.callee.* != .identifier) {
+                    const callee_type = try self.checkExpr(call.callee.*, scope);
+                    if (callee_type == .function) {
+                        break :blk try self.checkFunctionValueCall(self.typeToString(callee_type), callee_type, call, scope);
+                    }
                 }
                 
                 // 默认返回 i32
@@ -749,31 +1906,52 @@ pub const TypeChecker = struct {
                 break :blk ast.Type{ .named = variant.enum_name };
             },
             .block => |stmts| blk: {
-                for (stmts) |stmt| {
-                    try self.checkStmt(stmt, scope);
+                // 🆕 块的值是尾表达式（最后一条表达式语句）的值
+                if (stmts.len == 0) break :blk ast.Type.void;
+                // 🆕 块中有 break / continue / return 时块不会产生值，类型为 never
+                var diverges = false;
+                for (stmts[0 .. stmts.len - 1]) |stmt| {
+                    if (try self.checkBlockStmt(stmt, scope)) diverges = true;
                 }
-                break :blk ast.Type.void;
+                const tail = stmts[stmts.len - 1];
+                if (tail == .expr) {
+                    const tail_type = try self.checkExprExpected(tail.expr, scope, expected);
+                    break :blk if (diverges) ast.Type.never else tail_type;
+                }
+                if (try self.checkBlockStmt(tail, scope)) diverges = true;
+                break :blk if (diverges) ast.Type.never else ast.Type.void;
             },
             .if_expr => |if_expr| blk: {
                 const cond_type = try self.checkExpr(if_expr.condition.*, scope);
-                if (!cond_type.eql(ast.Type.bool)) {
-                    try self.errors.append(self.allocator, "Type error: if condition must be Bool");
-                }
+                try self.checkBoolOperand("if condition", if_expr.condition.*, cond_type);
                 
-                const then_type = try self.checkExpr(if_expr.then_branch.*, scope);
+                // 🆕 两个分支都按期望类型检查；没有期望类型时 else 分支跟随 then 分支
+                const diagnostics_before = self.diagnostics.items.len;
+                const then_type = try self.checkExprExpected(if_expr.then_branch.*, scope, expected);
+                if (if_expr.dispatch_instance) |instance| try self.noteDispatchInstance(instance, diagnostics_before);
                 
                 if (if_expr.else_branch) |else_branch| {
-                    const else_type = try self.checkExpr(else_branch.*, scope);
-                    if (!then_type.eql(else_type)) {
-                        try self.errors.append(self.allocator, "Type error: if-else branches must match");
+                    // 🆕 不会产生值的分支（never，如 break）不参与类型统一
+                    const else_expected = expected orelse (if (then_type == .never) null else then_type);
+                    const else_type = try self.checkExprExpected(else_branch.*, scope, else_expected);
+                    if (then_type == .never) break :blk else_type;
+                    if (else_type != .never and !isDiscarded(expected) and !then_type.eql(else_type)) {
+                        try self.reportBranchMismatch("'if' and 'else' have incompatible types", then_type, else_type, "expected because of the type of the 'if' branch");
                     }
+                    break :blk then_type;
                 }
                 
-                break :blk then_type;
+                // 没有 else 时条件为假会继续执行，整个 if 不算 never
+                break :blk if (then_type == .never) ast.Type.void else then_type;
             },
             // 新增：is 表达式（模式匹配）
             .is_expr => |is_match| blk: {
-                _ = try self.checkExpr(is_match.value.*, scope);
+                const value_type = try self.checkExpr(is_match.value.*, scope);
+                // 🆕 匹配枚举值：变体模式按负载类型绑定，并检查是否覆盖了所有变体
+                const matched_enum = self.matchedEnum(value_type);
+                if (matched_enum) |info| try self.checkEnumArms(info, is_match.arms);
+                // 🆕 不可达的分支（警告）；bool 值必须覆盖 true 和 false
+                try self.checkArmReachability(is_match.arms, value_type, matched_enum);
                 
                 // 🆕 为每个arm创建新的scope，支持模式绑定
                 var result_type: ?ast.Type = null;
@@ -792,58 +1970,48 @@ pub const TypeChecker = struct {
                     // 🆕 根据pattern添加绑定
                     switch (arm.pattern) {
                         .identifier => |id| {
-                            // 标识符模式：绑定整个匹配值（暂时用i32）
-                            try arm_scope.put(id, ast.Type.i32);
+                            // 标识符模式：绑定整个匹配值
+                            try arm_scope.put(id, value_type);
                         },
                         .variant => |v| {
-                            // variant模式：为每个binding添加类型（暂时用i32）
-                            for (v.bindings) |binding| {
-                                try arm_scope.put(binding, ast.Type.i32);
+                            // variant模式：绑定变体的负载（🆕 类型来自枚举声明；未知时按 i32）
+                            const payload = if (matched_enum) |info| info.payload(v.name, v.bindings.len) else null;
+                            for (v.bindings, 0..) |binding, i| {
+                                try arm_scope.put(binding, if (payload) |fields| matched_enum.?.instantiate(fields[i]) else ast.Type.i32);
                             }
                         },
+                        // 🆕 值模式（字面量、范围、或模式）的类型必须与匹配值一致
+                        .literal, .range, .alternatives => if (matched_enum == null) try self.checkValuePattern(arm.pattern, value_type),
                         else => {},
                     }
                     
                     // 检查 guard 条件（如果有）
                     if (arm.guard) |guard| {
                         const guard_type = try self.checkExpr(guard, &arm_scope);
-                        if (!guard_type.eql(ast.Type.bool)) {
-                            try self.errors.append(self.allocator, "Type error: is guard must be Bool");
-                        }
+                        try self.checkBoolOperand("guard", guard, guard_type);
                     }
                     
-                    // 检查分支体的类型
-                    const arm_type = try self.checkExpr(arm.body, &arm_scope);
+                    // 检查分支体的类型（🆕 期望类型或第一个分支的类型流入后续分支）
+                    const arm_type = try self.checkExprExpected(arm.body, &arm_scope, expected orelse result_type);
+                    // 🆕 不会产生值的分支（never）不参与类型统一
+                    if (arm_type == .never) continue;
                     
-                    if (result_type) |rt| {
-                        if (!self.isTypeCompatible(arm_type, rt) and !self.isTypeCompatible(rt, arm_type)) {
-                            // 使用更宽松的类型兼容性检查
-                            // try self.errors.append(self.allocator, "Type error: is arms must have same type");
-                        }
-                    } else {
-                        result_type = arm_type;
-                    }
-                }
-                
-                // 验证模式覆盖完整性（简化版：检查是否有 _ 通配符）
-                var has_wildcard = false;
-                for (is_match.arms) |arm| {
-                    if (arm.pattern == .wildcard) {
-                        has_wildcard = true;
-                    }
-                }
-                
-                if (!has_wildcard and is_match.arms.len < 2) {
-                    // 警告：可能未覆盖所有情况
-                    // try self.errors.append(self.allocator, "Warning: is expression may not be exhaustive");
+                    if (result_type == null) result_type = arm_type;
                 }
                 
-                break :blk result_type orelse ast.Type.void;
+                // 🆕 所有分支都不会产生值时，整个表达式也是 never
+                break :blk result_type orelse (if (is_match.arms.len > 0) ast.Type.never else ast.Type.void);
             },
             // 新增：as 表达式（类型转换）
             .as_expr => |as_cast| blk: {
-                const from_type = try self.checkExpr(as_cast.value.*, scope);
                 const to_type = as_cast.target_type;
+                // 🆕 目标类型实现了 From 时，值按 from 的参数类型检查（字面量取该类型）
+                const from_method = self.fromMethod(to_type);
+                // 🆕 整数字面量转换为整数类型（包括类型后缀 42u32、-1i64）：字面量取目标类型，
+                // 超出目标类型的范围时报错（-1 as u32 不会悄悄回绕）
+                const literal_target: ?ast.Type = if (literalValue(as_cast.value.*) != null and isIntType(to_type)) to_type else null;
+                const from_type = try self.checkExprExpected(as_cast.value.*, scope, if (from_method) |m| m.params[0].type else literal_target);
+                if (literal_target) |target| _ = try self.checkLiteralRange(as_cast.value.*, target);
                 
                 // 🆕 v0.1.7: 改进的类型转换验证
                 const is_numeric_from = switch (from_type) {
@@ -862,18 +2030,27 @@ pub const TypeChecker = struct {
                 
                 if (!is_numeric_from or !is_numeric_to) {
                     // 只允许数值类型（包括 bool/char）之间转换
+                    // 🆕 以及目标类型的 From 转换：调用 to_type::from(value)
                     if (!from_type.eql(to_type)) {
-                        try self.errors.append(self.allocator, "Type error: invalid type conversion");
+                        if (self.acceptsFrom(from_method, from_type)) {
+                            as_cast.resolved_from.* = true;
+                        } else {
+                            try self.reportInvalidConversion(from_type, to_type, from_method);
+                        }
                     }
                 }
                 
+                // 🆕 常量转换为 char 时在编译期检查是否是 Unicode 标量值
+                if (to_type == .char) try self.checkCharLiteral(as_cast.value.*);
+                
                 break :blk as_cast.target_type;
             },
             // 新增：await 表达式
             .await_expr => |await_expr| blk: {
                 // 验证 await 只能在 async 函数中使用
                 if (!self.current_function_is_async) {
-                    try self.errors.append(self.allocator, "Type error: await can only be used in async functions");
+                    const error_msg = try self.allocator.dupe(u8, "await can only be used in async functions");
+                    try self.reportErrorWithHelp(.await_outside_async, error_msg, self.exprSpan(await_expr.*), try self.allocator.dupe(u8, "declare the enclosing function with `async fn`"));
                 }
                 
                 const expr_type = try self.checkExpr(await_expr.*, scope);
@@ -889,14 +2066,15 @@ pub const TypeChecker = struct {
                     break :blk ast.Type.void;
                 }
                 
-                // 检查第一个元素的类型
-                const first_type = try self.checkExpr(elements[0], scope);
+                // 检查第一个元素的类型（🆕 期望的数组元素类型流入元素）
+                const expected_elem: ?ast.Type = if (expected) |e| (if (e == .array) e.array.element.* else null) else null;
+                const first_type = try self.checkExprExpected(elements[0], scope, expected_elem);
                 
                 // 检查所有元素类型是否一致
                 for (elements[1..]) |elem| {
-                    const elem_type = try self.checkExpr(elem, scope);
+                    const elem_type = try self.checkExprExpected(elem, scope, first_type);
                     if (!elem_type.eql(first_type)) {
-                        try self.errors.append(self.allocator, "Type error: array elements must have same type");
+                        try self.reportTypeMismatch("array element", first_type, elem_type, self.exprSpan(elem));
                     }
                 }
                 
@@ -922,19 +2100,25 @@ pub const TypeChecker = struct {
                               index_type == .u32 or index_type == .u64;
                 
                 if (!is_int) {
-                    try self.errors.append(self.allocator, "Type error: array index must be integer");
+                    const error_msg = try std.fmt.allocPrint(self.allocator, "array index must be an integer, found '{s}'", .{self.typeToString(index_type)});
+                    try self.reportError(.mismatched_types, error_msg, self.exprSpan(ai.index.*));
                 }
                 
                 // 🆕 支持字符串索引：s[i] 返回 char
                 if (array_type == .string) {
+                    ai.length.* = null;
                     break :blk ast.Type.char;
                 }
                 
                 // 返回数组元素类型
                 if (array_type == .array) {
+                    // 🆕 固定大小数组的常量索引：越界在编译期报告（其余索引由后端按记录的长度运行时检查）
+                    ai.length.* = array_type.array.size;
+                    if (array_type.array.size) |len| try self.checkConstantIndex(ai.index.*, len);
                     break :blk array_type.array.element.*;
                 } else {
-                    try self.errors.append(self.allocator, "Type error: index on non-array type");
+                    const error_msg = try std.fmt.allocPrint(self.allocator, "cannot index a value of type '{s}'", .{self.typeToString(array_type)});
+                    try self.reportErrorWithHelp(.not_indexable, error_msg, self.exprSpan(ai.array.*), try self.allocator.dupe(u8, "only arrays and strings can be indexed"));
                     break :blk ast.Type.void;
                 }
             },
@@ -954,7 +2138,9 @@ pub const TypeChecker = struct {
                                   end_type == .u32 or end_type == .u64;
                 
                 if (!start_is_int or !end_is_int) {
-                    try self.errors.append(self.allocator, "Type error: range bounds must be integers");
+                    const bad_type = if (start_is_int) end_type else start_type;
+                    const error_msg = try std.fmt.allocPrint(self.allocator, "range bounds must be integers, found '{s}'", .{self.typeToString(bad_type)});
+                    try self.reportError(.mismatched_types, error_msg, self.exprSpan(if (start_is_int) r.end.* else r.start.*));
                 }
                 
                 // 范围表达式的类型暂定为 void（实际上是迭代器）
@@ -965,13 +2151,29 @@ pub const TypeChecker = struct {
                 // 检查所有表达式部分的类型
                 for (si.parts) |part| {
                     if (part == .expr) {
-                        _ = try self.checkExpr(part.expr, scope);
+                        const part_type = try self.checkExpr(part.expr, scope);
+                        // 🆕 插值类型参数的值需要 Display 约束
+                        if (self.typeParamName(part_type)) |param| try self.requireBound(param, .Display, "$");
                     }
                 }
                 // 字符串插值的结果类型是 string
                 break :blk ast.Type.string;
             },
             // 🆕 错误传播 (expr?)
+            // 🆕 loop 表达式：类型由 break 值决定（没有带值的 break 时为 void）
+            .loop_expr => |loop| blk: {
+                try self.enterLoop(loop.label, true);
+                defer _ = self.loop_stack.pop();
+                
+                for (loop.body) |stmt| {
+                    try self.checkStmt(stmt, scope);
+                }
+                
+                const result = self.loop_stack.items[self.loop_stack.items.len - 1].break_type orelse ast.Type.void;
+                loop.result_type.* = result;
+                break :blk result;
+            },
+            .lambda => |lambda| try self.checkLambda(lambda, scope),
             .try_expr => |inner| blk: {
                 const inner_type = try self.checkExpr(inner.*, scope);
                 
@@ -987,126 +2189,588 @@ pub const TypeChecker = struct {
                 
                 var result_type: ?ast.Type = null;
                 for (match.arms) |arm| {
-                    const arm_type = try self.checkExpr(arm.body, scope);
+                    const arm_type = try self.checkExprExpected(arm.body, scope, expected orelse result_type);
+                    if (arm_type == .never) continue;
                     if (result_type) |rt| {
-                        if (!rt.eql(arm_type)) {
-                            try self.errors.append(self.allocator, "Type error: match arms must have same type");
+                        if (!isDiscarded(expected) and !rt.eql(arm_type)) {
+                            try self.reportBranchMismatch("'match' arms have incompatible types", rt, arm_type, "expected because of the type of the first arm");
                         }
                     } else {
                         result_type = arm_type;
                     }
                 }
                 
-                break :blk result_type orelse ast.Type.void;
+                break :blk result_type orelse (if (match.arms.len > 0) ast.Type.never else ast.Type.void);
             },
         };
+        if (self.expr_types) |types| try types.record(expr, checked_type);
+        return checked_type;
+    }
+    
+    /// 🆕 lambda：计算捕获的变量，像函数一样检查函数体，类型为 fn(参数类型...) -> 返回类型
+    ///
+    /// 函数体在 lambda 自己的作用域中检查：外层的局部变量只能读取（按值捕获），
+    /// 外层的循环对 break / continue 不可见，return 从 lambda 返回。
+    fn checkLambda(self: *TypeChecker, lambda: ast.Lambda, scope: *std.StringHashMap(ast.Type)) (std.mem.Allocator.Error || error{TypeCheckFailed})!ast.Type {
+        const captures = try closure.freeVariables(self.arena.allocator(), lambda.params, lambda.body, scope);
+        lambda.captures.* = captures;
+        
+        // 提升后的 lambda 是普通的顶层函数：不能依赖泛型参数，也不能捕获 self（C 后端中是指针）
+        if (self.current_function) |func| {
+            if (func.type_params.len > 0) {
+                try self.reportErrorWithHelp(
+                    .unsupported_lambda,
+                    try std.fmt.allocPrint(self.allocator, "lambdas are not supported inside generic function '{s}'", .{func.name}),
+                    if (func.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null,
+                    try self.allocator.dupe(u8, "move the lambda into a non-generic function"),
+                );
+            }
+        }
+        var mentions_generic = self.mentionsTypeParam(lambda.return_type, scope);
+        for (lambda.params) |param| {
+            if (self.mentionsTypeParam(param.type, scope)) mentions_generic = true;
+        }
+        for (captures) |capture| {
+            if (self.mentionsTypeParam(capture.type, scope)) mentions_generic = true;
+            if (std.mem.eql(u8, capture.name, "self")) {
+                try self.reportErrorWithHelp(
+                    .unsupported_lambda,
+                    try self.allocator.dupe(u8, "a lambda cannot capture 'self'"),
+                    null,
+                    try self.allocator.dupe(u8, "copy the fields you need into local variables before the lambda"),
+                );
+            }
+            // 🆕 环境结构体按标量声明捕获的变量，数组无法按值复制进去
+            if (capture.type == .array) {
+                try self.reportErrorWithHelp(
+                    .unsupported_lambda,
+                    try std.fmt.allocPrint(self.allocator, "a lambda cannot capture the array '{s}'", .{capture.name}),
+                    self.identifierSpan(capture.name),
+                    try self.allocator.dupe(u8, "copy the elements you need into local variables before the lambda"),
+                );
+            }
+        }
+        if (mentions_generic) {
+            try self.reportError(
+                .unsupported_lambda,
+                try self.allocator.dupe(u8, "lambda parameters, return type and captured variables cannot use generic types"),
+                null,
+            );
+        }
+        
+        var lambda_scope = try self.createChildScope(scope);
+        defer lambda_scope.deinit();
+        
+        const saved_mutable = self.mutable_vars;
+        self.mutable_vars = std.StringHashMap(bool).init(self.allocator);
+        const saved_loops = self.loop_stack;
+        self.loop_stack = std.ArrayList(LoopScope){};
+        const saved_captures = self.current_captures;
+        self.current_captures = captures;
+        const saved_function = self.current_function;
+        const saved_async = self.current_function_is_async;
+        self.current_function_is_async = false;
+        defer {
+            self.mutable_vars.deinit();
+            self.mutable_vars = saved_mutable;
+            self.loop_stack.deinit(self.allocator);
+            self.loop_stack = saved_loops;
+            self.current_captures = saved_captures;
+            self.current_function = saved_function;
+            self.current_function_is_async = saved_async;
+        }
+        
+        for (captures) |capture| try self.mutable_vars.put(capture.name, false);
+        for (lambda.params) |param| {
+            try lambda_scope.put(param.name, param.type);
+            try self.mutable_vars.put(param.name, param.is_mut);
+        }
+        self.current_function = .{
+            .name = "lambda",
+            .type_params = &[_][]const u8{},
+            .params = lambda.params,
+            .return_type = lambda.return_type,
+            .body = lambda.body,
+            .is_public = false,
+            .is_async = false,
+            .location = if (saved_function) |func| func.location else null,
+            .captures = captures,
+            .namespace = if (saved_function) |func| func.namespace else null,
+        };
+        
+        // 与 checkFunction 相同：有返回值时最后一条表达式语句是返回值
+        for (lambda.body, 0..) |stmt, i| {
+            const is_tail_value = i == lambda.body.len - 1 and stmt == .expr and
+                lambda.return_type != .void and lambda.return_type != .never;
+            if (is_tail_value) {
+                _ = try self.checkExprExpected(stmt.expr, &lambda_scope, self.expectedReturnType());
+            } else {
+                _ = try self.checkBlockStmt(stmt, &lambda_scope);
+            }
+        }
+        
+        return lambda.valueType(self.arena.allocator());
     }
     
     // ==================== 新增：高级类型检查功能 ====================
     
-    /// 检查泛型约束是否满足
-    fn checkGenericConstraints(
+    /// 🆕 类型是否（结构上）实现了 trait：trait 及其所有 supertrait 的每个方法在类型上都有同名方法
+    fn typeImplementsTrait(self: *TypeChecker, type_name: []const u8, trait_def: TraitDef) bool {
+        const type_methods = self.type_methods.get(type_name) orelse return false;
+        var methods = std.ArrayList(ast.FunctionSignature){};
+        defer methods.deinit(self.allocator);
+        self.collectTraitMethods(trait_def, &methods) catch return false;
+        for (methods.items) |trait_method| {
+            if (!type_methods.methods.contains(trait_method.name)) return false;
+        }
+        // 🆕 还要定义 trait 声明的每个关联类型
+        var assoc_types = std.ArrayList([]const u8){};
+        defer assoc_types.deinit(self.allocator);
+        self.collectTraitAssocTypes(trait_def, &assoc_types) catch return false;
+        for (assoc_types.items) |assoc| {
+            const type_decl = self.type_table.get(type_name) orelse return false;
+            if (type_decl.assocType(assoc, &.{}) == null) return false;
+        }
+        return true;
+    }
+    
+    /// 🆕 trait 及其所有 supertrait 声明的关联类型（每个 trait 只访问一次）
+    fn collectTraitAssocTypes(self: *TypeChecker, trait_def: TraitDef, assoc_types: *std.ArrayList([]const u8)) !void {
+        var visited = std.StringHashMap(void).init(self.allocator);
+        defer visited.deinit();
+        var pending = std.ArrayList(TraitDef){};
+        defer pending.deinit(self.allocator);
+        try pending.append(self.allocator, trait_def);
+        while (pending.pop()) |current| {
+            if ((try visited.getOrPut(current.name)).found_existing) continue;
+            try assoc_types.appendSlice(self.allocator, current.assoc_types);
+            for (current.supertraits) |super| {
+                try pending.append(self.allocator, self.trait_table.get(super.name) orelse continue);
+            }
+        }
+    }
+    
+    /// 🆕 trait 自己的方法，然后是 supertrait 的方法（深度优先，同名的只保留第一个）
+    ///
+    /// 未知的 supertrait 跳过；循环的 supertrait 由 checkSupertraits 报告，这里每个 trait 只访问一次。
+    fn collectTraitMethods(self: *TypeChecker, trait_def: TraitDef, methods: *std.ArrayList(ast.FunctionSignature)) !void {
+        var visited = std.StringHashMap(void).init(self.allocator);
+        defer visited.deinit();
+        try self.collectTraitMethodsFrom(trait_def, methods, &visited);
+    }
+    
+    fn collectTraitMethodsFrom(
         self: *TypeChecker,
-        type_name: []const u8,
-        type_params: [][]const u8,
-        constraints: [][]const u8,
+        trait_def: TraitDef,
+        methods: *std.ArrayList(ast.FunctionSignature),
+        visited: *std.StringHashMap(void),
     ) !void {
-        // 简化版：检查类型参数是否满足 trait 约束
-        for (constraints) |constraint| {
-            if (!self.trait_table.contains(constraint)) {
-                const err_msg = try std.fmt.allocPrint(
+        if ((try visited.getOrPut(trait_def.name)).found_existing) return;
+        for (trait_def.methods) |sig| {
+            const seen = for (methods.items) |existing| {
+                if (std.mem.eql(u8, existing.name, sig.name)) break true;
+            } else false;
+            if (!seen) try methods.append(self.allocator, sig);
+        }
+        for (trait_def.supertraits) |super| {
+            const super_def = self.trait_table.get(super.name) orelse continue;
+            try self.collectTraitMethodsFrom(super_def, methods, visited);
+        }
+    }
+    
+    /// 🆕 supertrait 必须是已声明的 trait，类型实参个数与其类型参数一致，且不能（间接）继承自身
+    fn checkSupertraits(self: *TypeChecker, type_decl: ast.TypeDecl, supertraits: []const ast.TraitRef) !void {
+        const span: ?Span = if (type_decl.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        for (supertraits) |super| {
+            const super_def = self.trait_table.get(super.name) orelse {
+                const error_msg = try std.fmt.allocPrint(self.allocator, "supertrait '{s}' of '{s}' is not a trait", .{ super.name, type_decl.name });
+                const help = if (self.type_table.contains(super.name))
+                    try std.fmt.allocPrint(self.allocator, "'{s}' is a struct or enum; only traits can be supertraits", .{super.name})
+                else
+                    try std.fmt.allocPrint(self.allocator, "declare it with `type {s} = trait {{ ... }}`", .{super.name});
+                try self.reportErrorWithHelp(.unknown_supertrait, error_msg, span, help);
+                continue;
+            };
+            if (super.type_args.len != super_def.type_params.len) {
+                const error_msg = try std.fmt.allocPrint(
                     self.allocator,
-                    "Error: trait '{s}' not found for constraint",
-                    .{constraint}
+                    "supertrait '{s}' takes {d} type argument(s) but {d} were given",
+                    .{ super.name, super_def.type_params.len, super.type_args.len },
                 );
-                try self.errors.append(self.allocator, err_msg);
-                continue;
-            }
-            
-            // 检查类型是否实现了该 trait
-            if (self.type_methods.get(type_name)) |type_methods| {
-                const trait_def = self.trait_table.get(constraint).?;
-                
-                // 验证所有 trait 方法都被实现
-                for (trait_def.methods) |trait_method| {
-                    if (!type_methods.methods.contains(trait_method.name)) {
-                        const err_msg = try std.fmt.allocPrint(
-                            self.allocator,
-                            "Error: type '{s}' does not implement trait method '{s}' from '{s}'",
-                            .{type_name, trait_method.name, constraint}
-                        );
-                        try self.errors.append(self.allocator, err_msg);
-                    }
-                }
+                const help = try std.fmt.allocPrint(self.allocator, "write `trait: {s}<...>` with one type per parameter of '{s}'", .{ super.name, super.name });
+                try self.reportErrorWithHelp(.unknown_supertrait, error_msg, span, help);
             }
         }
         
-        _ = type_params;  // TODO: 实际验证泛型参数
+        if (try self.inheritsFrom(type_decl.name, supertraits)) {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "trait '{s}' is its own supertrait", .{type_decl.name});
+            try self.reportErrorWithHelp(.cyclic_supertrait, error_msg, span, try self.allocator.dupe(u8, "remove one of the supertraits that leads back to this trait"));
+        }
     }
     
-    /// 检查 trait 实现是否完整
-    fn checkTraitImpl(
+    /// supertraits（及它们的 supertrait）中是否有名为 name 的 trait
+    fn inheritsFrom(self: *TypeChecker, name: []const u8, supertraits: []const ast.TraitRef) !bool {
+        var visited = std.StringHashMap(void).init(self.allocator);
+        defer visited.deinit();
+        var pending = std.ArrayList([]const u8){};
+        defer pending.deinit(self.allocator);
+        for (supertraits) |super| try pending.append(self.allocator, super.name);
+        while (pending.pop()) |current| {
+            if (std.mem.eql(u8, current, name)) return true;
+            if ((try visited.getOrPut(current)).found_existing) continue;
+            const def = self.trait_table.get(current) orelse continue;
+            for (def.supertraits) |super| try pending.append(self.allocator, super.name);
+        }
+        return false;
+    }
+
+    /// 🆕 类型没有（正确）实现的 trait 方法
+    pub const MissingMethods = struct {
+        missing: []const []const u8,     // 类型上没有的方法
+        mismatched: []const []const u8,  // 同名但签名与 trait 不一致的方法
+    };
+
+    /// 🆕 逐个检查 trait（及其 supertrait）的方法（pawc query impls / missing-impls）
+    pub fn missingTraitMethods(
         self: *TypeChecker,
+        allocator: std.mem.Allocator,
         type_name: []const u8,
-        trait_name: []const u8,
-    ) !bool {
-        const trait_def = self.trait_table.get(trait_name) orelse {
-            const err_msg = try std.fmt.allocPrint(
+        trait_def: TraitDef,
+    ) !MissingMethods {
+        var missing = std.ArrayList([]const u8){};
+        errdefer missing.deinit(allocator);
+        var mismatched = std.ArrayList([]const u8){};
+        errdefer mismatched.deinit(allocator);
+
+        // 🆕 包括 supertrait 的方法
+        var trait_methods = std.ArrayList(ast.FunctionSignature){};
+        defer trait_methods.deinit(self.allocator);
+        try self.collectTraitMethods(trait_def, &trait_methods);
+        
+        const type_methods = self.type_methods.get(type_name);
+        for (trait_methods.items) |trait_method| {
+            const impl_method = if (type_methods) |tm| tm.methods.get(trait_method.name) else null;
+            if (impl_method) |method| {
+                if (!self.methodSignaturesMatch(type_name, trait_method, method)) try mismatched.append(allocator, trait_method.name);
+            } else {
+                try missing.append(allocator, trait_method.name);
+            }
+        }
+        
+        // 🆕 没有定义的关联类型列为 "type Item"
+        var assoc_types = std.ArrayList([]const u8){};
+        defer assoc_types.deinit(self.allocator);
+        try self.collectTraitAssocTypes(trait_def, &assoc_types);
+        const type_decl = self.type_table.get(type_name);
+        for (assoc_types.items) |assoc| {
+            const defined = if (type_decl) |td| td.assocType(assoc, &.{}) != null else false;
+            if (!defined) try missing.append(allocator, try std.fmt.allocPrint(allocator, "type {s}", .{assoc}));
+        }
+
+        return MissingMethods{
+            .missing = try missing.toOwnedSlice(allocator),
+            .mismatched = try mismatched.toOwnedSlice(allocator),
+        };
+    }
+
+    /// 🆕 把 name(x, ...) 解析为 trait 方法调用 x.name(...)
+    ///
+    /// 只有当 x 的类型实现的 trait 中恰好有一个提供 name 方法时才解析；
+    /// 多个 trait 都提供时报告歧义并列出候选 trait。
+    /// 返回方法的返回类型；不是 trait 方法调用时返回 null。解析成功时接收者的类型名记录在 call.resolved_receiver 上。
+    fn resolveTraitMethodCall(
+        self: *TypeChecker,
+        name: []const u8,
+        call: anytype,
+        scope: *std.StringHashMap(ast.Type),
+    ) (std.mem.Allocator.Error || error{TypeCheckFailed})!?ast.Type {
+        const args = call.args;
+        if (args.len == 0) return null;
+        
+        // 先确认有 trait 声明了这个方法，再检查接收者（避免重复检查参数）
+        var declared = false;
+        var trait_iter = self.trait_table.valueIterator();
+        while (trait_iter.next()) |trait_def| {
+            if (traitMethod(trait_def.*, name) != null) declared = true;
+        }
+        if (!declared) return null;
+        
+        const receiver_type = try self.checkExpr(args[0], scope);
+        if (receiver_type != .named) return null;
+        const type_name = receiver_type.named;
+        
+        var candidates = std.ArrayList([]const u8){};
+        defer candidates.deinit(self.allocator);
+        trait_iter = self.trait_table.valueIterator();
+        while (trait_iter.next()) |trait_def| {
+            const sig = traitMethod(trait_def.*, name) orelse continue;
+            if (sig.params.len == 0 or !std.mem.eql(u8, sig.params[0].name, "self")) continue;
+            if (self.typeImplementsTrait(type_name, trait_def.*)) {
+                try candidates.append(self.allocator, trait_def.name);
+            }
+        }
+        const span = self.callSpan(call, name);
+        try self.traceTraitMethodCall(name, type_name, candidates.items, span);
+        if (candidates.items.len == 0) return null;
+        
+        if (candidates.items.len > 1) {
+            std.mem.sort([]const u8, candidates.items, {}, struct {
+                fn lessThan(_: void, a: []const u8, b: []const u8) bool {
+                    return std.mem.lessThan(u8, a, b);
+                }
+            }.lessThan);
+            const error_msg = try std.fmt.allocPrint(
                 self.allocator,
-                "Error: trait '{s}' not found",
-                .{trait_name}
+                "ambiguous call to '{s}': multiple traits implemented by '{s}' provide it",
+                .{ name, type_name },
             );
-            try self.errors.append(self.allocator, err_msg);
-            return false;
-        };
+            const notes = try self.allocator.alloc([]const u8, candidates.items.len);
+            for (candidates.items, 0..) |trait_name, i| {
+                notes[i] = try std.fmt.allocPrint(self.allocator, "candidate: {s}::{s}", .{ trait_name, name });
+            }
+            const help = try std.fmt.allocPrint(self.allocator, "call it as a method to use the implementation on '{s}': value.{s}(...)", .{ type_name, name });
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.ambiguous_call));
+            return ast.Type.void;
+        }
         
-        const type_methods = self.type_methods.get(type_name) orelse {
-            const err_msg = try std.fmt.allocPrint(
+        // 唯一候选：按类型上的实现检查其余参数
+        call.resolved_receiver.* = type_name;
+        const method = self.type_methods.get(type_name).?.methods.get(name).?;
+        const rest = args[1..];
+        const method_params = if (method.params.len > 0) method.params[1..] else method.params;
+        if (rest.len != method_params.len) {
+            const err_msg = try messages.format(
                 self.allocator,
-                "Error: type '{s}' has no methods",
-                .{type_name}
+                .static_method_arg_count,
+                .{ type_name, name, method_params.len, rest.len },
             );
-            try self.errors.append(self.allocator, err_msg);
-            return false;
+            try self.reportError(.wrong_argument_count, err_msg, span);
+            return method.return_type;
+        }
+        for (rest, method_params) |arg, param| {
+            _ = try self.checkExprExpected(arg, scope, param.type);
+        }
+        return method.return_type;
+    }
+    
+    // ============================================================================
+    // 🆕 From 转换
+    // ============================================================================
+    
+    /// 类型 to_type 的 From 转换方法：静态方法 `fn from(value: A) -> to_type`
+    /// 方法按名字登记，一个类型只有一个 from，所以只能从一种源类型 A 转换
+    fn fromMethod(self: *TypeChecker, to_type: ast.Type) ?ast.FunctionDecl {
+        if (to_type != .named) return null;
+        const type_methods = self.type_methods.get(to_type.named) orelse return null;
+        const method = type_methods.methods.get(ast.FROM_METHOD) orelse return null;
+        if (method.params.len != 1 or std.mem.eql(u8, method.params[0].name, "self")) return null;
+        if (!method.return_type.eql(to_type)) return null;
+        return method;
+    }
+    
+    fn acceptsFrom(self: *TypeChecker, from_method: ?ast.FunctionDecl, from_type: ast.Type) bool {
+        const method = from_method orelse return false;
+        return self.isTypeCompatible(from_type, method.params[0].type);
+    }
+    
+    /// into(x)：目标类型来自上下文（let 的类型标注、参数类型、返回类型）。
+    /// 结果记录在调用上：resolved_type_args = [源类型, 目标类型]，后端生成 目标类型::from(x)
+    fn checkInto(
+        self: *TypeChecker,
+        call: anytype,
+        scope: *std.StringHashMap(ast.Type),
+        expected: ?ast.Type,
+    ) (std.mem.Allocator.Error || error{TypeCheckFailed})!ast.Type {
+        if (call.args.len != 1) {
+            const err_msg = try messages.format(self.allocator, .function_arg_count, .{ ast.INTO_BUILTIN, 1, call.args.len });
+            try self.reportError(.wrong_argument_count, err_msg, self.callSpan(call, ast.INTO_BUILTIN));
+            return ast.Type.void;
+        }
+        const target = if (expected != null and !isDiscarded(expected)) expected.? else {
+            _ = try self.checkExpr(call.args[0], scope);
+            const error_msg = try self.allocator.dupe(u8, "cannot infer the target type of 'into'");
+            const help = try self.allocator.dupe(u8, "annotate the destination, as in `let b: T = into(a);`, or write `a as T`");
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, self.identifierSpan(ast.INTO_BUILTIN), &[_][]const u8{}, help).withCode(.cannot_infer_into));
+            return ast.Type.void;
         };
         
-        var all_implemented = true;
+        const from_method = self.fromMethod(target);
+        const from_type = try self.checkExprExpected(call.args[0], scope, if (from_method) |m| m.params[0].type else target);
+        if (!from_type.eql(target) and !self.acceptsFrom(from_method, from_type)) {
+            try self.reportInvalidConversion(from_type, target, from_method);
+            return target;
+        }
         
-        // 检查每个 trait 方法是否都被实现
-        for (trait_def.methods) |trait_method| {
-            if (type_methods.methods.get(trait_method.name)) |impl_method| {
-                // 检查方法签名是否匹配
-                if (!self.methodSignaturesMatch(trait_method, impl_method)) {
-                    const err_msg = try std.fmt.allocPrint(
-                        self.allocator,
-                        "Error: method '{s}' signature mismatch in type '{s}' (trait: '{s}')",
-                        .{trait_method.name, type_name, trait_name}
-                    );
-                    try self.errors.append(self.allocator, err_msg);
-                    all_implemented = false;
-                }
-            } else {
-                const err_msg = try std.fmt.allocPrint(
-                    self.allocator,
-                    "Error: missing trait method '{s}' in type '{s}' (required by trait '{s}')",
-                    .{trait_method.name, type_name, trait_name}
-                );
-                try self.errors.append(self.allocator, err_msg);
-                all_implemented = false;
+        const types = try self.arena.allocator().alloc(ast.Type, 2);
+        types[0] = from_type;
+        types[1] = target;
+        call.resolved_type_args.* = types;
+        call.resolved_into.* = true;
+        return target;
+    }
+    
+    /// 无法转换：目标是用户类型时说明如何实现 From
+    fn reportInvalidConversion(self: *TypeChecker, from_type: ast.Type, to_type: ast.Type, from_method: ?ast.FunctionDecl) !void {
+        if (to_type != .named or !self.type_table.contains(to_type.named)) {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "cannot convert '{s}' to '{s}'", .{ self.typeToString(from_type), self.typeToString(to_type) });
+            return self.reportError(.invalid_conversion, error_msg, null);
+        }
+        const type_name = to_type.named;
+        const error_msg = try std.fmt.allocPrint(self.allocator, "cannot convert '{s}' to '{s}'", .{ self.typeToString(from_type), type_name });
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = if (from_method) |method|
+            try std.fmt.allocPrint(self.allocator, "'{s}::from' converts from '{s}'", .{ type_name, self.typeToString(method.params[0].type) })
+        else
+            try std.fmt.allocPrint(self.allocator, "'{s}' does not implement From", .{type_name});
+        const help = try std.fmt.allocPrint(
+            self.allocator,
+            "add `fn from(value: {s}) -> {s}` to '{s}' to allow the conversion",
+            .{ self.typeToString(from_type), type_name, type_name },
+        );
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, null, notes, help).withCode(.invalid_conversion));
+    }
+    
+    // ============================================================================
+    // 🆕 运算符重载
+    // ============================================================================
+    
+    /// 左操作数是用户类型（struct / enum）时，运算符由它的方法实现（方法名见 ast.BinaryOp.overloadMethod）：
+    /// a + b 调用 a.add(b)，a == b 调用 a.eq(b)，a < b 即 a.cmp(b) < 0。
+    /// 结果记录在 bin.resolved_operator 中；左操作数不是用户类型时返回 null（按内置运算符检查）
+    fn checkOperatorOverload(self: *TypeChecker, bin: anytype, left_type: ast.Type, right_type: ast.Type) !?ast.Type {
+        const type_name = switch (left_type) {
+            .named => |name| name,
+            else => return null,
+        };
+        const type_decl = self.type_table.get(type_name) orelse return null;
+        if (type_decl.kind != .struct_type and type_decl.kind != .enum_type) return null;
+        const method_name = bin.op.overloadMethod() orelse return null;
+        const symbol = binaryOpSymbol(bin.op);
+        const trait_name = bin.op.overloadTrait().?;
+        const span: ?Span = if (bin.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        
+        const method = if (self.type_methods.get(type_name)) |type_methods| type_methods.methods.get(method_name) else null;
+        const operator = method orelse {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "operator '{s}' cannot be applied to type '{s}'", .{ symbol, type_name });
+            const notes = try self.allocator.alloc([]const u8, 1);
+            notes[0] = try std.fmt.allocPrint(self.allocator, "'{s}' does not implement {s}<T>", .{ type_name, trait_name });
+            const help = try std.fmt.allocPrint(self.allocator, "add `fn {s}(self, other: {s}) -> {s}` to '{s}'", .{
+                method_name,
+                self.typeToString(right_type),
+                switch (bin.op) {
+                    .eq, .ne => "bool",
+                    .lt, .le, .gt, .ge => "i32",
+                    else => type_name,
+                },
+                type_name,
+            });
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.missing_operator_method));
+            return ast.Type.void;
+        };
+        if (operator.params.len != 2 or !std.mem.eql(u8, operator.params[0].name, "self")) {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "method '{s}' of '{s}' cannot implement operator '{s}'", .{ method_name, type_name, symbol });
+            const help = try std.fmt.allocPrint(self.allocator, "an operator method takes self and one other operand: `fn {s}(self, other: T)`", .{method_name});
+            try self.reportErrorWithHelp(.missing_operator_method, error_msg, span, help);
+            return ast.Type.void;
+        }
+        
+        // 右操作数：方法的参数类型
+        const param_type = operator.params[1].type;
+        if (!(try self.checkLiteralRange(bin.right.*, param_type)) and !self.isTypeCompatible(right_type, param_type)) {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "operator '{s}' of '{s}' expects a right operand of type '{s}', found '{s}'", .{
+                symbol,
+                type_name,
+                self.typeToString(param_type),
+                self.typeToString(right_type),
+            });
+            try self.reportError(.mismatched_types, error_msg, span);
+        }
+        
+        // == / != 需要返回 bool 的 eq，比较运算符需要返回 i32 的 cmp（负数、0、正数）
+        const required_return: ?ast.Type = switch (bin.op) {
+            .eq, .ne => ast.Type.bool,
+            .lt, .le, .gt, .ge => ast.Type.i32,
+            else => null,
+        };
+        if (required_return) |required| {
+            if (!operator.return_type.eql(required)) {
+                const error_msg = try std.fmt.allocPrint(self.allocator, "method '{s}' of '{s}' must return '{s}' to implement operator '{s}'", .{
+                    method_name,
+                    type_name,
+                    self.typeToString(required),
+                    symbol,
+                });
+                try self.reportError(.missing_operator_method, error_msg, span);
             }
         }
         
-        return all_implemented;
+        bin.resolved_operator.* = type_name;
+        return if (required_return != null) ast.Type.bool else operator.return_type;
+    }
+    
+    /// 🆕 dispatch 的体对每个类型各检查一次：这一次产生的诊断注明类型参数的替换
+    fn noteDispatchInstance(self: *TypeChecker, instance: []const u8, diagnostics_before: usize) !void {
+        for (self.diagnostics.items[diagnostics_before..]) |*diag| {
+            const notes = try self.allocator.alloc([]const u8, diag.notes.len + 1);
+            @memcpy(notes[0..diag.notes.len], diag.notes);
+            notes[diag.notes.len] = try std.fmt.allocPrint(self.allocator, "in dispatch instance {s}", .{instance});
+            if (diag.notes.len > 0) self.allocator.free(diag.notes);
+            diag.notes = notes;
+        }
+    }
+    
+    /// 🆕 --explain-overloads：记录 name(x, ...) 考虑过的每个 trait
+    fn traceTraitMethodCall(self: *TypeChecker, name: []const u8, type_name: []const u8, viable: []const []const u8, span: ?Span) !void {
+        const trace = self.resolution_trace orelse return;
+        const a = trace.allocator();
+        
+        var candidates = std.ArrayList(resolution_trace.Candidate){};
+        var trait_iter = self.trait_table.valueIterator();
+        while (trait_iter.next()) |trait_def| {
+            const sig = traitMethod(trait_def.*, name) orelse continue;
+            const checks = try a.alloc(resolution_trace.Check, 2);
+            checks[0] = .{
+                .description = "the method takes 'self'",
+                .passed = sig.params.len > 0 and std.mem.eql(u8, sig.params[0].name, "self"),
+            };
+            checks[1] = .{
+                .description = try std.fmt.allocPrint(a, "'{s}' implements {s}", .{ type_name, trait_def.name }),
+                .passed = self.typeImplementsTrait(type_name, trait_def.*),
+            };
+            try candidates.append(a, .{
+                .name = try std.fmt.allocPrint(a, "{s}::{s}", .{ trait_def.name, name }),
+                .substitution = try std.fmt.allocPrint(a, "Self = {s}", .{type_name}),
+                .checks = checks,
+            });
+        }
+        std.mem.sort(resolution_trace.Candidate, candidates.items, {}, struct {
+            fn lessThan(_: void, x: resolution_trace.Candidate, y: resolution_trace.Candidate) bool {
+                return std.mem.lessThan(u8, x.name, y.name);
+            }
+        }.lessThan);
+        
+        try trace.record(.{
+            .callee = name,
+            .span = span,
+            .candidates = candidates.items,
+            .selected = if (viable.len == 1) try std.fmt.allocPrint(a, "{s}::{s}", .{ viable[0], name }) else null,
+        });
+    }
+    
+    fn traitMethod(trait_def: TraitDef, name: []const u8) ?ast.FunctionSignature {
+        for (trait_def.methods) |sig| {
+            if (std.mem.eql(u8, sig.name, name)) return sig;
+        }
+        return null;
     }
     
     /// 检查两个方法签名是否匹配
     fn methodSignaturesMatch(
         self: *TypeChecker,
+        type_name: []const u8,
         trait_sig: ast.FunctionSignature,
         impl_func: ast.FunctionDecl,
     ) bool {
-        _ = self;
-        
         // 检查参数数量
         if (trait_sig.params.len != impl_func.params.len) {
             return false;
@@ -1114,183 +2778,364 @@ pub const TypeChecker = struct {
         
         // 检查参数类型
         for (trait_sig.params, impl_func.params) |trait_param, impl_param| {
-            if (!trait_param.type.eql(impl_param.type)) {
+            if (!self.selfTypeFor(type_name, trait_param.type).eql(impl_param.type)) {
                 return false;
             }
         }
         
         // 检查返回类型
-        if (!trait_sig.return_type.eql(impl_func.return_type)) {
+        if (!self.selfTypeFor(type_name, trait_sig.return_type).eql(impl_func.return_type)) {
             return false;
         }
         
         return true;
     }
     
-    /// 检查方法调用是否有效（增强版）
-    fn checkMethodCallEnhanced(
-        self: *TypeChecker,
-        receiver_type: ast.Type,
-        method_name: []const u8,
-        args: []ast.Expr,
-        scope: *std.StringHashMap(ast.Type),
-    ) !ast.Type {
-        // 获取接收者的类型名
-        const type_name = switch (receiver_type) {
-            .named => |name| name,
-            else => {
-                try self.errors.append(self.allocator, "Error: cannot call method on non-named type");
-                return ast.Type.void;
-            },
-        };
+    /// 🆕 trait 签名中的 Self::Item 在类型 type_name 上是它定义的关联类型
+    /// （类型自己的方法签名在解析后已展开）
+    fn selfTypeFor(self: *TypeChecker, type_name: []const u8, t: ast.Type) ast.Type {
+        if (t != .projection or t.projection.base.* != .named) return t;
+        if (!std.mem.eql(u8, t.projection.base.named, "Self")) return t;
+        const type_decl = self.type_table.get(type_name) orelse return t;
+        return type_decl.assocType(t.projection.name, &.{}) orelse t;
+    }
+    
+    /// 🆕 字符串字面量模式与 string 值：两者必须同时出现
+    fn checkStringPattern(self: *TypeChecker, pattern: ast.Expr, value_type: ast.Type) !void {
+        const is_string_pattern = pattern == .string_literal;
+        const is_string_value = value_type == .string;
+        if (is_string_pattern == is_string_value) return;
+        if (is_string_pattern) {
+            const msg = try std.fmt.allocPrint(self.allocator, "a string pattern cannot match a value of type '{s}'", .{self.typeToString(value_type)});
+            return self.reportError(.invalid_pattern, msg, null);
+        }
+        const msg = try self.allocator.dupe(u8, "a string can only be matched against string literal patterns");
+        const help = try self.allocator.dupe(u8, "use a `_ => ...` or identifier arm for the remaining cases");
+        try self.reportErrorWithHelp(.invalid_pattern, msg, null, help);
+    }
+    
+    /// 🆕 被匹配的枚举：声明和（泛型枚举的）类型实参
+    const MatchedEnum = struct {
+        name: []const u8,
+        decl: ast.TypeDecl,
+        type_args: []ast.Type,
         
-        // 查找类型的方法
-        if (self.type_methods.get(type_name)) |type_methods| {
-            if (type_methods.methods.get(method_name)) |method| {
-                // 检查参数数量（-1 因为 self 参数）
-                const expected_args = if (method.params.len > 0) method.params.len - 1 else 0;
-                if (args.len != expected_args) {
-                    const err_msg = try std.fmt.allocPrint(
-                        self.allocator,
-                        "Error: method '{s}' expects {d} arguments, got {d}",
-                        .{method_name, expected_args, args.len}
-                    );
-                    try self.errors.append(self.allocator, err_msg);
-                }
-                
-                // 检查参数类型
-                var arg_idx: usize = 0;
-                for (method.params, 0..) |param, i| {
-                    // 跳过 self 参数
-                    if (i == 0 and (std.mem.eql(u8, param.name, "self") or 
-                                    std.mem.eql(u8, param.name, "mut self"))) {
-                        continue;
-                    }
-                    
-                    if (arg_idx < args.len) {
-                        const arg_type = try self.checkExpr(args[arg_idx], scope);
-                        if (!arg_type.eql(param.type)) {
-                            const err_msg = try std.fmt.allocPrint(
-                                self.allocator,
-                                "Error: argument {d} type mismatch in method '{s}'",
-                                .{arg_idx + 1, method_name}
-                            );
-                            try self.errors.append(self.allocator, err_msg);
-                        }
-                        arg_idx += 1;
-                    }
-                }
-                
-                return method.return_type;
+        fn variant(self: MatchedEnum, name: []const u8) ?ast.EnumVariant {
+            for (self.decl.kind.enum_type.variants) |v| {
+                if (std.mem.eql(u8, v.name, name)) return v;
             }
+            return null;
         }
         
-        const err_msg = try std.fmt.allocPrint(
-            self.allocator,
-            "Error: method '{s}' not found on type '{s}'",
-            .{method_name, type_name}
-        );
-        try self.errors.append(self.allocator, err_msg);
-        return ast.Type.void;
-    }
+        /// 变体的负载类型；变体不存在或绑定数不符时返回 null
+        fn payload(self: MatchedEnum, name: []const u8, binding_count: usize) ?[]ast.Type {
+            const v = self.variant(name) orelse return null;
+            if (v.fields.len != binding_count) return null;
+            return v.fields;
+        }
+        
+        /// 泛型枚举：负载中的类型参数替换为类型实参（T 可能被解析为 generic 或 named）
+        fn instantiate(self: MatchedEnum, field: ast.Type) ast.Type {
+            const param_name = switch (field) {
+                .generic, .named => |n| n,
+                else => return field,
+            };
+            if (self.type_args.len != self.decl.type_params.len) return field;
+            for (self.decl.type_params, self.type_args) |param, arg| {
+                if (std.mem.eql(u8, param, param_name)) return arg;
+            }
+            return field;
+        }
+    };
     
-    /// 检查字段访问是否有效（增强版）
-    fn checkFieldAccessEnhanced(
-        self: *TypeChecker,
-        receiver_type: ast.Type,
-        field_name: []const u8,
-    ) !ast.Type {
-        const type_name = switch (receiver_type) {
-            .named => |name| name,
-            else => {
-                try self.errors.append(self.allocator, "Error: cannot access field on non-named type");
-                return ast.Type.void;
+    fn matchedEnum(self: *TypeChecker, value_type: ast.Type) ?MatchedEnum {
+        var info = MatchedEnum{ .name = undefined, .decl = undefined, .type_args = &[_]ast.Type{} };
+        switch (value_type) {
+            .named => |n| info.name = n,
+            .generic_instance => |gi| {
+                info.name = gi.name;
+                info.type_args = gi.type_args;
             },
-        };
-        
-        // 查找类型定义
-        if (self.type_table.get(type_name)) |type_decl| {
-            switch (type_decl.kind) {
-                .struct_type => |st| {
-                    // 查找字段
-                    for (st.fields) |field| {
-                        if (std.mem.eql(u8, field.name, field_name)) {
-                            // TODO: 检查可见性（pub）
-                            return field.field_type;
+            else => return null,
+        }
+        info.decl = self.type_table.get(info.name) orelse return null;
+        if (info.decl.kind != .enum_type) return null;
+        return info;
+    }
+    
+    /// 🆕 检查枚举匹配的分支：变体必须属于该枚举、绑定数与负载一致，
+    /// 且没有兜底分支（_ 或不带 guard 的标识符）时必须覆盖所有变体
+    ///
+    /// 无负载变体也可以不带括号（`None =>`），这里把它改写为变体模式。
+    fn checkEnumArms(self: *TypeChecker, info: MatchedEnum, arms: []ast.IsArm) !void {
+        var has_catch_all = false;
+        for (arms) |*arm| {
+            switch (arm.pattern) {
+                .identifier => |id| {
+                    if (info.variant(id)) |v| {
+                        if (v.fields.len == 0) {
+                            arm.pattern = .{ .variant = .{ .name = id, .bindings = &[_][]const u8{} } };
+                            continue;
                         }
                     }
+                    if (arm.guard == null) has_catch_all = true;
+                },
+                .wildcard => if (arm.guard == null) {
+                    has_catch_all = true;
                 },
-                .enum_type => {
-                    // 枚举不能直接访问字段
-                    try self.errors.append(self.allocator, "Error: cannot access fields on enum type");
-                    return ast.Type.void;
+                .variant => |v| try self.checkVariantPattern(info, v.name, v.bindings.len),
+                .literal, .range => {
+                    const msg = try std.fmt.allocPrint(self.allocator, "a literal pattern cannot match a value of enum '{s}'", .{info.name});
+                    try self.reportError(.invalid_pattern, msg, null);
                 },
-                .trait_type => {
-                    try self.errors.append(self.allocator, "Error: cannot access fields on trait type");
-                    return ast.Type.void;
+                // 🆕 或模式：每个子模式都是不带绑定的变体
+                .alternatives => |alts| for (alts) |*alt| {
+                    if (alt.* == .identifier) {
+                        if (info.variant(alt.identifier)) |v| {
+                            if (v.fields.len == 0) alt.* = .{ .variant = .{ .name = alt.identifier, .bindings = &[_][]const u8{} } };
+                        }
+                    }
+                    if (alt.* != .variant) {
+                        const msg = try std.fmt.allocPrint(self.allocator, "alternatives of an or-pattern on enum '{s}' must be variants", .{info.name});
+                        try self.reportError(.invalid_pattern, msg, null);
+                        continue;
+                    }
+                    try self.checkVariantPattern(info, alt.variant.name, alt.variant.bindings.len);
+                    if (alt.variant.bindings.len > 0) {
+                        const msg = try std.fmt.allocPrint(self.allocator, "variant '{s}' cannot bind values inside an or-pattern", .{alt.variant.name});
+                        const help = try self.allocator.dupe(u8, "give this variant its own arm to use its payload");
+                        try self.reportErrorWithHelp(.invalid_pattern, msg, null, help);
+                    }
                 },
             }
         }
+        if (has_catch_all) return;
         
-        const err_msg = try std.fmt.allocPrint(
+        // 没有兜底分支：每个变体都需要一个不带 guard 的分支
+        var missing = std.ArrayList(u8){};
+        defer missing.deinit(self.allocator);
+        var missing_count: usize = 0;
+        for (info.decl.kind.enum_type.variants) |variant| {
+            const covered = for (arms) |arm| {
+                if (arm.guard == null and patternCovers(arm.pattern, variant.name)) break true;
+            } else false;
+            if (covered) continue;
+            if (missing_count > 0) try missing.appendSlice(self.allocator, ", ");
+            try missing.appendSlice(self.allocator, variant.name);
+            missing_count += 1;
+        }
+        if (missing_count == 0) return;
+        
+        const msg = try std.fmt.allocPrint(
             self.allocator,
-            "Error: field '{s}' not found on type '{s}'",
-            .{field_name, type_name}
+            "non-exhaustive match on enum '{s}': {s} {s} not covered",
+            .{ info.name, missing.items, if (missing_count == 1) "is" else "are" },
         );
-        try self.errors.append(self.allocator, err_msg);
-        return ast.Type.void;
+        const help = try self.allocator.dupe(u8, "add an arm for each missing variant, or a `_ => ...` arm to handle the rest");
+        try self.reportErrorWithHelp(.non_exhaustive_match, msg, null, help);
     }
     
-    /// 检查 is 表达式的完整性（穷尽性检查）
-    fn checkIsExhaustivenness(
-        self: *TypeChecker,
-        value_type: ast.Type,
-        arms: []ast.IsArm,
-    ) !void {
-        // 简化版：检查是否有通配符 _
-        var has_wildcard = false;
+    /// 🆕 前面不带 guard 的分支已经匹配的值
+    const ArmCoverage = struct {
+        catch_all: bool = false,
+        ranges: std.ArrayList([2]i64) = .{},        // 整数 / 字符 / bool（0 和 1）的闭区间
+        strings: std.ArrayList([]const u8) = .{},
+        variants: std.ArrayList([]const u8) = .{},
         
-        for (arms) |arm| {
-            if (arm.pattern == .wildcard) {
-                has_wildcard = true;
-                break;
+        fn deinit(self: *ArmCoverage, allocator: std.mem.Allocator) void {
+            self.ranges.deinit(allocator);
+            self.strings.deinit(allocator);
+            self.variants.deinit(allocator);
+        }
+        
+        /// 模式能匹配的每个值都已被前面的分支匹配
+        fn covers(self: *const ArmCoverage, pattern: ast.Pattern) bool {
+            if (self.catch_all) return true;
+            return switch (pattern) {
+                .wildcard, .identifier => false,
+                .literal => |lit| if (lit == .string_literal)
+                    containsName(self.strings.items, lit.string_literal)
+                else if (patternValue(lit)) |v|
+                    self.coversRange(v, v)
+                else
+                    false,
+                .range => |r| if (rangeBounds(r.start, r.end, r.inclusive)) |b| self.coversRange(b[0], b[1]) else false,
+                .variant => |v| containsName(self.variants.items, v.name),
+                .alternatives => |alts| for (alts) |alt| {
+                    if (!self.covers(alt)) break false;
+                } else true,
+            };
+        }
+        
+        fn coversRange(self: *const ArmCoverage, lo: i64, hi: i64) bool {
+            for (self.ranges.items) |r| {
+                if (r[0] <= lo and hi <= r[1]) return true;
             }
+            return false;
         }
         
-        // 如果匹配的是枚举类型，检查是否覆盖所有变体
-        if (value_type == .named) {
-            const type_name = value_type.named;
+        fn add(self: *ArmCoverage, allocator: std.mem.Allocator, pattern: ast.Pattern) !void {
+            switch (pattern) {
+                .wildcard, .identifier => self.catch_all = true,
+                .literal => |lit| if (lit == .string_literal) {
+                    try self.strings.append(allocator, lit.string_literal);
+                } else if (patternValue(lit)) |v| {
+                    try self.ranges.append(allocator, .{ v, v });
+                },
+                .range => |r| if (rangeBounds(r.start, r.end, r.inclusive)) |b| try self.ranges.append(allocator, b),
+                .variant => |v| try self.variants.append(allocator, v.name),
+                .alternatives => |alts| for (alts) |alt| try self.add(allocator, alt),
+            }
+        }
+    };
+    
+    /// 🆕 分支可达性：已被前面的分支完全匹配的分支永远不会执行（警告）；
+    /// 匹配 bool 且没有兜底分支时必须同时覆盖 true 和 false（枚举由 checkEnumArms 检查）
+    fn checkArmReachability(self: *TypeChecker, arms: []const ast.IsArm, value_type: ast.Type, matched_enum: ?MatchedEnum) !void {
+        var coverage = ArmCoverage{};
+        defer coverage.deinit(self.allocator);
+        
+        for (arms, 1..) |arm, number| {
+            if (coverage.covers(arm.pattern)) {
+                try self.reportUnreachableArm(arm, number, coverage.catch_all);
+                continue;
+            }
+            if (arm.guard != null) continue;
+            try coverage.add(self.allocator, arm.pattern);
             
-            if (self.type_table.get(type_name)) |type_decl| {
-                if (type_decl.kind == .enum_type) {
-                    const enum_type = type_decl.kind.enum_type;
-                    
-                    if (!has_wildcard) {
-                        // 检查是否所有变体都被覆盖
-                        var covered_variants = std.StringHashMap(bool).init(self.allocator);
-                        defer covered_variants.deinit();
-                        
-                        for (arms) |arm| {
-                            if (arm.pattern == .enum_variant) {
-                                try covered_variants.put(arm.pattern.enum_variant.variant_name, true);
-                            }
-                        }
-                        
-                        // 检查是否有未覆盖的变体
-                        for (enum_type.variants) |variant| {
-                            if (!covered_variants.contains(variant.name)) {
-                                const err_msg = try std.fmt.allocPrint(
-                                    self.allocator,
-                                    "Warning: is expression not exhaustive, missing pattern for '{s}'",
-                                    .{variant.name}
-                                );
-                                try self.errors.append(self.allocator, err_msg);
-                            }
-                        }
-                    }
-                }
+            // 覆盖了所有变体 / true 和 false 之后，后面的分支（包括 `_`）都不可达
+            if (matched_enum) |info| {
+                coverage.catch_all = for (info.decl.kind.enum_type.variants) |variant| {
+                    if (!containsName(coverage.variants.items, variant.name)) break false;
+                } else true;
+            } else if (value_type == .bool) {
+                coverage.catch_all = coverage.coversRange(0, 0) and coverage.coversRange(1, 1);
             }
         }
+        
+        if (value_type != .bool or coverage.catch_all) return;
+        const missing = if (coverage.coversRange(1, 1)) "false is" else if (coverage.coversRange(0, 0)) "true is" else "true, false are";
+        const msg = try std.fmt.allocPrint(self.allocator, "non-exhaustive match on 'bool': {s} not covered", .{missing});
+        const help = try self.allocator.dupe(u8, "add an arm for each missing value, or a `_ => ...` arm to handle the rest");
+        try self.reportErrorWithHelp(.non_exhaustive_match, msg, null, help);
+    }
+    
+    fn reportUnreachableArm(self: *TypeChecker, arm: ast.IsArm, number: usize, after_catch_all: bool) !void {
+        const warning_msg = try std.fmt.allocPrint(self.allocator, "unreachable match arm: arm {d} can never be taken", .{number});
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = try self.allocator.dupe(u8, if (after_catch_all)
+            "an earlier arm without a guard already matches every value"
+        else
+            "every value this pattern matches is already matched by an earlier arm");
+        const help = try self.allocator.dupe(u8, "remove this arm, or move it above the arm that shadows it");
+        const span: ?Span = if (arm.location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Warning, warning_msg, span, notes, help).withCode(.unreachable_match_arm));
+    }
+    
+    fn containsName(names: []const []const u8, name: []const u8) bool {
+        for (names) |n| {
+            if (std.mem.eql(u8, n, name)) return true;
+        }
+        return false;
+    }
+    
+    /// 模式中的整数、字符、字节或 bool 字面量的值
+    fn patternValue(expr: ast.Expr) ?i64 {
+        if (expr == .bool_literal) return @intFromBool(expr.bool_literal);
+        return rangeBound(expr);
+    }
+    
+    /// 范围模式的闭区间（a..b 为 [a, b-1]）；端点无效或为空时返回 null
+    fn rangeBounds(start: ast.Expr, end: ast.Expr, inclusive: bool) ?[2]i64 {
+        const lo = rangeBound(start) orelse return null;
+        const hi = (rangeBound(end) orelse return null) - @intFromBool(!inclusive);
+        return if (lo <= hi) .{ lo, hi } else null;
+    }
+    
+    /// 变体模式：变体必须属于该枚举，绑定数与负载一致
+    fn checkVariantPattern(self: *TypeChecker, info: MatchedEnum, name: []const u8, binding_count: usize) !void {
+        const variant = info.variant(name) orelse {
+            const msg = try std.fmt.allocPrint(self.allocator, "enum '{s}' has no variant '{s}'", .{ info.name, name });
+            const help = try self.variantList(info, "the variants are: ");
+            return self.reportErrorWithHelp(.unknown_variant, msg, null, help);
+        };
+        if (variant.fields.len != binding_count) {
+            const msg = try std.fmt.allocPrint(
+                self.allocator,
+                "pattern '{s}' binds {d} value(s), but variant '{s}::{s}' carries {d}",
+                .{ name, binding_count, info.name, name, variant.fields.len },
+            );
+            try self.reportError(.pattern_arity, msg, null);
+        }
+    }
+    
+    /// 模式（不带 guard 时）是否覆盖了该变体
+    fn patternCovers(pattern: ast.Pattern, variant_name: []const u8) bool {
+        return switch (pattern) {
+            .variant => |v| std.mem.eql(u8, v.name, variant_name),
+            .alternatives => |alts| for (alts) |alt| {
+                if (patternCovers(alt, variant_name)) break true;
+            } else false,
+            else => false,
+        };
+    }
+    
+    /// 🆕 非枚举值的模式：字面量、范围，以及由它们组成的或模式
+    fn checkValuePattern(self: *TypeChecker, pattern: ast.Pattern, value_type: ast.Type) !void {
+        switch (pattern) {
+            .literal => |lit| try self.checkStringPattern(lit, value_type),
+            .range => |r| try self.checkRangePattern(r.start, r.end, r.inclusive, value_type),
+            .alternatives => |alts| for (alts) |alt| {
+                if (alt == .literal or alt == .range) {
+                    try self.checkValuePattern(alt, value_type);
+                } else {
+                    const msg = try self.allocator.dupe(u8, "alternatives of an or-pattern must be literals or ranges");
+                    const help = try self.allocator.dupe(u8, "bindings and `_` need an arm of their own");
+                    try self.reportErrorWithHelp(.invalid_pattern, msg, null, help);
+                }
+            },
+            else => {},
+        }
+    }
+    
+    /// 🆕 范围模式：端点是整数 / 字符字面量，匹配整数或 char，且至少包含一个值
+    fn checkRangePattern(self: *TypeChecker, start: ast.Expr, end: ast.Expr, inclusive: bool, value_type: ast.Type) !void {
+        const lo = rangeBound(start) orelse return self.reportRangeBoundError();
+        const hi = rangeBound(end) orelse return self.reportRangeBoundError();
+        if (!isIntType(value_type) and value_type != .char) {
+            const msg = try std.fmt.allocPrint(self.allocator, "a range pattern cannot match a value of type '{s}'", .{self.typeToString(value_type)});
+            return self.reportError(.invalid_pattern, msg, null);
+        }
+        if (lo > hi or (lo == hi and !inclusive)) {
+            const msg = try std.fmt.allocPrint(self.allocator, "range pattern {d}{s}{d} matches no value", .{ lo, if (inclusive) "..=" else "..", hi });
+            const help = try self.allocator.dupe(u8, "the start of a range pattern must be below its end (or equal, with ..=)");
+            try self.reportErrorWithHelp(.empty_range_pattern, msg, null, help);
+        }
+    }
+    
+    fn reportRangeBoundError(self: *TypeChecker) !void {
+        const msg = try self.allocator.dupe(u8, "range pattern bounds must be integer or character literals");
+        try self.reportError(.invalid_pattern, msg, null);
+    }
+    
+    fn rangeBound(expr: ast.Expr) ?i64 {
+        return switch (expr) {
+            .int_literal => |literal| literal.value,
+            .char_literal => |v| v,
+            .byte_literal => |v| v,
+            else => null,
+        };
+    }
+    
+    fn variantList(self: *TypeChecker, info: MatchedEnum, prefix: []const u8) ![]const u8 {
+        var list = std.ArrayList(u8){};
+        errdefer list.deinit(self.allocator);
+        try list.appendSlice(self.allocator, prefix);
+        for (info.decl.kind.enum_type.variants, 0..) |variant, i| {
+            if (i > 0) try list.appendSlice(self.allocator, ", ");
+            try list.appendSlice(self.allocator, variant.name);
+        }
+        return list.toOwnedSlice(self.allocator);
     }
     
     // ============================================================================
@@ -1298,7 +3143,7 @@ pub const TypeChecker = struct {
     // ============================================================================
     
     /// 将类型转换为字符串（用于错误消息）
-    fn typeToString(_: *TypeChecker, t: ast.Type) []const u8 {
+    fn typeToString(self: *TypeChecker, t: ast.Type) []const u8 {
         return switch (t) {
             .i8 => "i8",
             .i16 => "i16",
@@ -1316,12 +3161,32 @@ pub const TypeChecker = struct {
             .char => "char",
             .string => "string",
             .void => "void",
+            .never => "!",
             .generic => |name| name,
             .named => |name| name,
+            .function => |func| self.functionTypeToString(func.params, func.return_type.*) catch "fn(...)",
+            .projection => |proj| std.fmt.allocPrint(self.allocator, "{s}::{s}", .{ self.typeToString(proj.base.*), proj.name }) catch proj.name,
             else => "unknown",
         };
     }
     
+    /// 🆕 函数类型的源码写法：fn(i32, i32) -> i32
+    fn functionTypeToString(self: *TypeChecker, params: []const ast.Type, return_type: ast.Type) std.mem.Allocator.Error![]const u8 {
+        var buf = std.ArrayList(u8){};
+        const allocator = self.arena.allocator();
+        try buf.appendSlice(allocator, "fn(");
+        for (params, 0..) |param, i| {
+            if (i > 0) try buf.appendSlice(allocator, ", ");
+            try buf.appendSlice(allocator, self.typeToString(param));
+        }
+        try buf.appendSlice(allocator, ")");
+        if (return_type != .void) {
+            try buf.appendSlice(allocator, " -> ");
+            try buf.appendSlice(allocator, self.typeToString(return_type));
+        }
+        return buf.toOwnedSlice(allocator);
+    }
+    
     /// 🆕 v0.1.8: 计算 Levenshtein 距离（用于相似变量名建议）
     fn levenshteinDistance(_: *TypeChecker, s1: []const u8, s2: []const u8) usize {
         if (s1.len == 0) return s2.len;
@@ -1382,42 +3247,342 @@ pub const TypeChecker = struct {
         return best_match;
     }
     
+    /// 🆕 类型中是否引用了当前作用域的泛型参数
+    fn mentionsTypeParam(self: *TypeChecker, t: ast.Type, scope: *std.StringHashMap(ast.Type)) bool {
+        return switch (t) {
+            .generic => true,
+            .named => |name| if (scope.get(name)) |bound| bound == .generic else false,
+            .array => |arr| self.mentionsTypeParam(arr.element.*, scope),
+            .generic_instance => |gi| blk: {
+                for (gi.type_args) |arg| {
+                    if (self.mentionsTypeParam(arg, scope)) break :blk true;
+                }
+                break :blk false;
+            },
+            .function => |func| blk: {
+                for (func.params) |param| {
+                    if (self.mentionsTypeParam(param, scope)) break :blk true;
+                }
+                break :blk self.mentionsTypeParam(func.return_type.*, scope);
+            },
+            .projection => |proj| self.mentionsTypeParam(proj.base.*, scope),
+            else => false,
+        };
+    }
+    
+    /// 🆕 进入循环：记录标签，同名标签不能嵌套
+    fn enterLoop(self: *TypeChecker, label: ?[]const u8, is_expr: bool) !void {
+        if (label) |name| {
+            for (self.loop_stack.items) |outer| {
+                if (outer.label != null and std.mem.eql(u8, outer.label.?, name)) {
+                    const error_msg = try std.fmt.allocPrint(self.allocator, "label '{s} shadows a label of an enclosing loop", .{name});
+                    const help = try self.allocator.dupe(u8, "rename the inner loop's label");
+                    try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, self.labelSpan(name), &[_][]const u8{}, help).withCode(.shadowed_label));
+                    break;
+                }
+            }
+        }
+        try self.loop_stack.append(self.allocator, LoopScope{ .label = label, .is_expr = is_expr, .break_type = null });
+    }
+    
+    /// 🆕 查找 break / continue 的目标循环：必须在循环内，标签必须属于某个外层循环
+    fn findLoop(self: *TypeChecker, keyword: []const u8, label: ?[]const u8) !?*LoopScope {
+        if (self.loop_stack.items.len == 0) {
+            const error_msg = try std.fmt.allocPrint(self.allocator, "'{s}' outside of a loop", .{keyword});
+            const span: ?Span = if (label) |name| self.labelSpan(name) else null;
+            try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, &[_][]const u8{}, null).withCode(.break_outside_loop));
+            return null;
+        }
+        
+        const name = label orelse return &self.loop_stack.items[self.loop_stack.items.len - 1];
+        var i = self.loop_stack.items.len;
+        while (i > 0) {
+            i -= 1;
+            const outer = &self.loop_stack.items[i];
+            if (outer.label != null and std.mem.eql(u8, outer.label.?, name)) return outer;
+        }
+        
+        const error_msg = try std.fmt.allocPrint(self.allocator, "use of undeclared label '{s}", .{name});
+        const help = try std.fmt.allocPrint(self.allocator, "label an enclosing loop: '{s}: loop {{ ... }}", .{name});
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, self.labelSpan(name), &[_][]const u8{}, help).withCode(.undeclared_label));
+        return null;
+    }
+    
+    /// 🆕 检查 break：带值的 break 只能用于 loop 表达式，所有 break 值的类型必须一致
+    fn checkBreak(self: *TypeChecker, label: ?[]const u8, value_type: ?ast.Type) !void {
+        const loop = (try self.findLoop("break", label)) orelse return;
+        
+        if (!loop.is_expr) {
+            if (value_type != null) {
+                const error_msg = try self.allocator.dupe(u8, "'break' with a value is only allowed inside a 'loop' expression");
+                const help = try self.allocator.dupe(u8, "use the loop as an expression: let result = loop { ... break value; };");
+                try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, null, &[_][]const u8{}, help).withCode(.break_value_outside_loop_expr));
+            }
+            return;
+        }
+        
+        const actual = value_type orelse ast.Type.void;
+        if (loop.break_type) |expected| {
+            if (!expected.eql(actual)) {
+                const error_msg = try std.fmt.allocPrint(
+                    self.allocator,
+                    "mismatched types in loop breaks: expected '{s}', found '{s}'",
+                    .{ self.typeToString(expected), self.typeToString(actual) },
+                );
+                const notes = try self.allocator.alloc([]const u8, 1);
+                notes[0] = try self.allocator.dupe(u8, "the type of a loop expression is the type of the first 'break' value");
+                try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, null, notes, null).withCode(.mismatched_types));
+            }
+        } else {
+            loop.break_type = actual;
+        }
+    }
+    
+    // ============================================================================
+    // 🆕 整数字面量范围检查
+    // ============================================================================
+    
+    const IntRange = struct { min: i128, max: i128 };
+    
+    /// 整数类型的取值范围（非整数类型返回 null）
+    fn intRange(t: ast.Type) ?IntRange {
+        return switch (t) {
+            .i8 => .{ .min = std.math.minInt(i8), .max = std.math.maxInt(i8) },
+            .i16 => .{ .min = std.math.minInt(i16), .max = std.math.maxInt(i16) },
+            .i32 => .{ .min = std.math.minInt(i32), .max = std.math.maxInt(i32) },
+            .i64 => .{ .min = std.math.minInt(i64), .max = std.math.maxInt(i64) },
+            .i128 => .{ .min = std.math.minInt(i128), .max = std.math.maxInt(i128) },
+            .u8 => .{ .min = 0, .max = std.math.maxInt(u8) },
+            .u16 => .{ .min = 0, .max = std.math.maxInt(u16) },
+            .u32 => .{ .min = 0, .max = std.math.maxInt(u32) },
+            .u64 => .{ .min = 0, .max = std.math.maxInt(u64) },
+            .u128 => .{ .min = 0, .max = std.math.maxInt(i128) },  // i128 能表示的部分
+            else => null,
+        };
+    }
+    
+    /// 表达式是整数字面量（或取负的整数字面量）时返回它的值
+    fn literalValue(expr: ast.Expr) ?i128 {
+        return switch (expr) {
+            .int_literal => |literal| literal.value,
+            .unary => |un| if (un.op == .neg and un.operand.* == .int_literal) -@as(i128, un.operand.int_literal.value) else null,
+            else => null,
+        };
+    }
+    
+    /// 能容纳 value 的最窄的同符号类型（用于建议）
+    fn widerTypeFor(value: i128, unsigned: bool) []const u8 {
+        const candidates = if (unsigned and value >= 0)
+            [_]ast.Type{ .u8, .u16, .u32, .u64, .u128 }
+        else
+            [_]ast.Type{ .i8, .i16, .i32, .i64, .i128 };
+        for (candidates) |candidate| {
+            const range = intRange(candidate).?;
+            if (value >= range.min and value <= range.max) {
+                return switch (candidate) {
+                    .u8 => "u8", .u16 => "u16", .u32 => "u32", .u64 => "u64",
+                    .i8 => "i8", .i16 => "i16", .i32 => "i32", .i64 => "i64",
+                    else => if (unsigned) "u128" else "i128",
+                };
+            }
+        }
+        return "i128";
+    }
+    
+    /// 检查整数字面量能否放进 target 类型
+    ///
+    /// 返回 true 表示 expr 是整数字面量且 target 是整数类型（无论是否越界，
+    /// 越界时已报告专门的诊断），调用方不必再做通用的类型兼容检查。
+    fn checkLiteralRange(self: *TypeChecker, expr: ast.Expr, target: ast.Type) !bool {
+        const value = literalValue(expr) orelse return false;
+        const range = intRange(target) orelse return false;
+        if (value >= range.min and value <= range.max) return true;
+        
+        const type_name = self.typeToString(target);
+        const unsigned = range.min == 0;
+        
+        const error_msg = try std.fmt.allocPrint(self.allocator, "literal out of range for '{s}'", .{type_name});
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = try std.fmt.allocPrint(
+            self.allocator,
+            "the literal `{d}` does not fit into the type `{s}` whose range is `{d}..={d}`",
+            .{ value, type_name, range.min, range.max },
+        );
+        const help = if (unsigned and value < 0)
+            try std.fmt.allocPrint(self.allocator, "`{s}` cannot hold negative values; consider using `{s}` instead", .{ type_name, widerTypeFor(value, false) })
+        else
+            try std.fmt.allocPrint(self.allocator, "consider using the type `{s}` instead", .{widerTypeFor(value, unsigned)});
+        
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, self.literalSpan(expr), notes, help).withCode(.literal_out_of_range));
+        return true;
+    }
+    
+    /// 🆕 `<整数字面量> as char`：字面量必须是 Unicode 标量值（运行时的值由后端检查）
+    fn checkCharLiteral(self: *TypeChecker, expr: ast.Expr) !void {
+        const value = literalValue(expr) orelse return;
+        if (ast.isValidChar(value)) return;
+        
+        const error_msg = try std.fmt.allocPrint(self.allocator, "invalid char value {d}", .{value});
+        const notes = try self.allocator.alloc([]const u8, 1);
+        notes[0] = if (value >= 0xD800 and value <= 0xDFFF)
+            try std.fmt.allocPrint(self.allocator, "`0x{X}` is a UTF-16 surrogate code point, not a Unicode scalar value", .{value})
+        else
+            try std.fmt.allocPrint(self.allocator, "a char must be in the range `0..=0x10FFFF`, excluding surrogates `0xD800..=0xDFFF`", .{});
+        try self.diagnostics.append(self.allocator, Diagnostic.init(
+            .Error,
+            error_msg,
+            self.literalSpan(expr),
+            notes,
+            "use `is_valid_char(n)` to check a value before converting it",
+        ).withCode(.invalid_char_value));
+    }
+    
+    /// 整数字面量（或取负的整数字面量）的位置；编译器生成的字面量没有位置，退回到 exprSpan
+    fn literalSpan(self: *TypeChecker, expr: ast.Expr) ?Span {
+        const literal = if (expr == .unary) expr.unary.operand.* else expr;
+        const loc = (if (literal == .int_literal) literal.int_literal.location else null) orelse return self.exprSpan(expr);
+        return Span.fromPosition(loc.file, loc.line, loc.column);
+    }
+    
+    /// 🆕 标签在源码中第一次出现的位置
+    fn labelSpan(self: *TypeChecker, name: []const u8) ?Span {
+        for (self.tokens) |token| {
+            if (token.type == .label and std.mem.eql(u8, token.lexeme[1..], name)) {
+                return Span.fromPosition(token.file, token.line, token.column - token.lexeme.len);
+            }
+        }
+        return null;
+    }
+    
+    /// 🆕 通过函数值调用：按函数类型检查实参的个数和类型，返回函数类型的返回类型
+    fn checkFunctionValueCall(
+        self: *TypeChecker,
+        name: []const u8,
+        func_type: ast.Type,
+        call: anytype,
+        scope: *std.StringHashMap(ast.Type),
+    ) (std.mem.Allocator.Error || error{TypeCheckFailed})!ast.Type {
+        const func = func_type.function;
+        const span = self.callSpan(call, name);
+        if (call.args.len != func.params.len) {
+            const err_msg = try messages.format(self.allocator, .function_arg_count, .{ name, func.params.len, call.args.len });
+            try self.reportError(.wrong_argument_count, err_msg, span);
+            return func.return_type.*;
+        }
+        for (call.args, func.params, 0..) |arg, param_type, i| {
+            const arg_type = try self.checkExprExpected(arg, scope, param_type);
+            if (try self.checkLiteralRange(arg, param_type)) continue;
+            if (!self.isTypeCompatible(arg_type, param_type)) {
+                const err_msg = try messages.format(self.allocator, .function_arg_mismatch, .{ i + 1, name });
+                try self.reportArgumentMismatch(err_msg, param_type, arg_type, span);
+            }
+        }
+        return func.return_type.*;
+    }
+    
+    /// 🆕 报告未知函数，并从导出索引中查找可以导入它的模块
+    fn reportUnknownFunction(self: *TypeChecker, name: []const u8, location: ?ast.SourceLocation) !void {
+        const error_msg = try messages.format(self.allocator, .unknown_function, .{name});
+        const span: ?Span = if (location) |loc| Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        
+        const candidates: []const []const u8 = if (self.export_index) |index| index.lookup(name) else &[_][]const u8{};
+        
+        var notes: [][]const u8 = &[_][]const u8{};
+        var help: ?[]const u8 = null;
+        if (candidates.len > 0) {
+            help = try self.formatImportLine(candidates[0], name);
+            if (candidates.len > 1) {
+                notes = try self.allocator.alloc([]const u8, candidates.len - 1);
+                for (candidates[1..], 0..) |candidate, i| {
+                    const line = try self.formatImportLine(candidate, name);
+                    defer self.allocator.free(line);
+                    notes[i] = try messages.format(self.allocator, .also_exported_by, .{line});
+                }
+            }
+        } else if (self.findSimilarFunction(name)) |similar| {
+            help = try messages.format(self.allocator, .did_you_mean, .{similar});
+        }
+        
+        try self.diagnostics.append(self.allocator, Diagnostic.init(.Error, error_msg, span, notes, help).withCode(.unknown_function));
+    }
+    
+    /// 🆕 name(...) 确实找不到：不是局部变量、函数（含 prelude）、类型、常量或由后端处理的内置函数，
+    /// 并且调用写在正在检查的文件中（导入模块里的调用由模块自己的检查负责）
+    fn isUnknownFunctionCall(self: *TypeChecker, call: anytype, scope: *std.StringHashMap(ast.Type)) bool {
+        const name = call.callee.identifier;
+        if (scope.get(name) != null or self.symbol_table.contains(name)) return false;
+        if (self.function_table.contains(name) or self.type_table.contains(name)) return false;
+        for (BACKEND_BUILTINS) |builtin| {
+            if (std.mem.eql(u8, builtin, name)) return false;
+        }
+        const loc = call.location orelse return false;
+        if (self.tokens.len == 0) return false;
+        return loc.file == self.tokens[0].file;
+    }
+    
+    /// 由后端直接生成的内置函数（prelude 中有同名的占位声明）
+    const BACKEND_BUILTINS = [_][]const u8{ "paw_write", "paw_write_f64", "paw_log", "gen_int", "gen_string", ast.INTO_BUILTIN };
+    
+    /// math/vec + add -> "import math.vec.add;"
+    fn formatImportLine(self: *TypeChecker, module_path: []const u8, name: []const u8) ![]const u8 {
+        const dotted = try self.allocator.dupe(u8, module_path);
+        defer self.allocator.free(dotted);
+        std.mem.replaceScalar(u8, dotted, '/', '.');
+        return std.fmt.allocPrint(self.allocator, "import {s}.{s};", .{dotted, name});
+    }
+    
+    /// 在函数表中查找相似的函数名
+    fn findSimilarFunction(self: *TypeChecker, name: []const u8) ?[]const u8 {
+        var best_match: ?[]const u8 = null;
+        var best_distance: usize = 3;
+        var it = self.function_table.keyIterator();
+        while (it.next()) |key| {
+            if (key.len >= 100 or name.len >= 100) continue;
+            const distance = self.levenshteinDistance(name, key.*);
+            if (distance < best_distance) {
+                best_distance = distance;
+                best_match = key.*;
+            }
+        }
+        return best_match;
+    }
+    
     /// 报告一个简单错误（使用新的诊断系统）
+    ///
+    /// 位置总是来自 token / 声明位置的 Span，带有它实际所在的文件：
+    /// 导入模块中的错误指向模块文件，而不是入口文件
     fn reportError(
         self: *TypeChecker,
+        code: codes.Code,
         message: []const u8,
-        line: usize,
-        col: usize,
+        span: ?Span,
     ) !void {
-        const span = Span.init(self.source_file, line, col, line, col);
-        const diag = Diagnostic.init(.Error, message, span, &[_][]const u8{}, null);
+        const diag = Diagnostic.init(.Error, message, span, &[_][]const u8{}, null).withCode(code);
         try self.diagnostics.append(self.allocator, diag);
     }
     
     /// 报告错误并附带帮助信息
     fn reportErrorWithHelp(
         self: *TypeChecker,
+        code: codes.Code,
         message: []const u8,
-        line: usize,
-        col: usize,
+        span: ?Span,
         help: []const u8,
     ) !void {
-        const span = Span.init(self.source_file, line, col, line, col);
-        const diag = Diagnostic.init(.Error, message, span, &[_][]const u8{}, help);
+        const diag = Diagnostic.init(.Error, message, span, &[_][]const u8{}, help).withCode(code);
         try self.diagnostics.append(self.allocator, diag);
     }
     
     /// 报告错误并附带注释和帮助
     fn reportErrorFull(
         self: *TypeChecker,
+        code: codes.Code,
         message: []const u8,
-        line: usize,
-        col: usize,
+        span: ?Span,
         notes: []const []const u8,
         help: ?[]const u8,
     ) !void {
-        const span = Span.init(self.source_file, line, col, line, col);
-        const diag = Diagnostic.init(.Error, message, span, notes, help);
+        const diag = Diagnostic.init(.Error, message, span, notes, help).withCode(code);
         try self.diagnostics.append(self.allocator, diag);
     }
 };
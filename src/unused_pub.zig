@@ -0,0 +1,276 @@
+//! UnusedPub - 库中未被使用的公开项（pawc unused-pub <lib.paw> [tests...]）
+//!
+//! 库的 pub 项都是导出的根，普通的死代码消除不会删除它们。这里反过来问：
+//! 一个 pub 项是否被库中其他的公开项（直接或经过私有项）或测试用到？
+//! 都没有用到的 pub 项是"死 API"的候选，以警告报告。
+//!
+//! 引用图按名字构建：声明中出现的标识符与某个顶层项同名即视为引用
+//! （调用、类型、常量都算）。同名的局部变量或方法会被当作引用，
+//! 所以这里只会漏报，不会误报。
+//!
+//! impl 块中方法的引用属于目标类型（类型被用到时方法也被用到）；目标类型不是库中的项时
+//! （如 impl Show for i32），方法中的引用都视为根。
+//!
+//! 测试是命令行给出的 .paw 文件或目录（递归），其中提到的 pub 项都算被用到；
+//! 库中的 main 也视为根。对单个声明可以用 @allow(unused_pub) 关闭检查。
+//! --deny-warnings 时报告了任何项都以退出码 1 结束。
+
+const std = @import("std");
+const ast = @import("ast.zig");
+const Token = @import("token.zig").Token;
+const Lexer = @import("lexer.zig").Lexer;
+const Parser = @import("parser.zig").Parser;
+const SourceMap = @import("source_map.zig").SourceMap;
+const selftest = @import("selftest.zig");
+const diagnostic = @import("diagnostic.zig");
+const Diagnostic = diagnostic.Diagnostic;
+
+/// 一个顶层项
+const Item = struct {
+    kind: []const u8,  // function / type / constant（用于消息）
+    is_public: bool,
+    allowed: bool,     // @allow(unused_pub)
+    location: ?ast.SourceLocation,
+    /// 声明中引用的其他顶层项
+    references: std.ArrayList([]const u8),
+};
+
+/// 检查库文件，打印未被使用的 pub 项；返回报告的数量
+pub fn run(allocator: std.mem.Allocator, library_file: []const u8, test_paths: []const []const u8) !usize {
+    var arena_state = std.heap.ArenaAllocator.init(allocator);
+    defer arena_state.deinit();
+    const arena = arena_state.allocator();
+
+    var source_map = SourceMap.init(allocator);
+    defer source_map.deinit();
+    const file_id = source_map.loadFile(library_file) catch |err| {
+        std.debug.print("Error: Cannot read file {s}: {any}\n", .{ library_file, err });
+        return error.InvalidInput;
+    };
+    const source = source_map.get(file_id).contents;
+
+    var lexer = Lexer.init(allocator, source, library_file);
+    defer lexer.deinit();
+    const tokens = try lexer.tokenize();
+
+    var parser = Parser.init(allocator, tokens);
+    defer parser.deinit();
+    const program = try parser.parse();
+
+    // 1. 顶层项
+    var items = std.StringArrayHashMap(Item).init(arena);
+    for (program.declarations) |decl| {
+        const entry: struct { name: []const u8, item: Item } = switch (decl) {
+            .function => |func| .{ .name = func.name, .item = newItem("function", func.is_public, func.allow, func.location) },
+            .type_decl => |td| .{ .name = td.name, .item = newItem("type", td.is_public, td.allow, td.location) },
+            .const_decl => |cd| .{ .name = cd.name, .item = newItem("constant", cd.is_public, cd.allow, cd.location) },
+            else => continue,
+        };
+        try items.put(entry.name, entry.item);
+    }
+
+    // 2. 引用：每个顶层声明的 token 范围中出现的其他项
+    var impl_roots = std.ArrayList([]const u8){};
+    var start: usize = 0;
+    while (start < tokens.len and tokens[start].type != .eof) {
+        const end = declEnd(tokens, start);
+        const decl_tokens = tokens[start..end];
+        start = end;
+
+        if (implTarget(decl_tokens)) |target_index| {
+            const target = decl_tokens[target_index].lexeme;
+            const owner = items.getPtr(target);
+            for (decl_tokens) |token| {
+                if (token.type != .identifier or !items.contains(token.lexeme)) continue;
+                if (owner) |item| {
+                    if (!std.mem.eql(u8, token.lexeme, target)) try item.references.append(arena, token.lexeme);
+                } else {
+                    try impl_roots.append(arena, token.lexeme);
+                }
+            }
+            continue;
+        }
+
+        const name_index = declName(decl_tokens) orelse continue;
+        const item = items.getPtr(decl_tokens[name_index].lexeme) orelse continue;
+        for (decl_tokens, 0..) |token, i| {
+            if (i == name_index or token.type != .identifier) continue;
+            if (items.contains(token.lexeme)) try item.references.append(arena, token.lexeme);
+        }
+    }
+
+    // 3. 根：测试提到的 pub 项和 main；从每个公开项出发能到达的其他项
+    var used = std.StringHashMap(void).init(arena);
+    var test_files = std.ArrayList([]const u8){};
+    for (test_paths) |path| {
+        const is_dir = if (std.fs.cwd().statFile(path)) |stat| stat.kind == .directory else |_| false;
+        if (is_dir) {
+            try selftest.collectPrograms(arena, path, &test_files);
+        } else {
+            try test_files.append(arena, path);
+        }
+    }
+    for (test_files.items) |path| {
+        try markTestReferences(arena, path, &items, &used);
+    }
+    if (items.contains("main")) try markReachable(arena, &items, "main", &used, true);
+    for (impl_roots.items) |name| try markReachable(arena, &items, name, &used, true);
+
+    var it = items.iterator();
+    while (it.next()) |entry| {
+        if (entry.value_ptr.is_public) try markReachable(arena, &items, entry.key_ptr.*, &used, false);
+    }
+
+    // 4. 报告（按声明顺序）
+    var reported: usize = 0;
+    it = items.iterator();
+    while (it.next()) |entry| {
+        const name = entry.key_ptr.*;
+        const item = entry.value_ptr.*;
+        if (!item.is_public or item.allowed or used.contains(name)) continue;
+
+        const message = try std.fmt.allocPrint(arena, "public {s} '{s}' is not used by any other public item or test", .{ item.kind, name });
+        const notes = try arena.alloc([]const u8, 1);
+        notes[0] = "it is part of the library API, but nothing in the library or its tests exercises it";
+        const help = try std.fmt.allocPrint(arena, "remove `pub` if '{s}' is not meant to be API, or add @allow(unused_pub) to keep it", .{name});
+        const span: ?diagnostic.Span = if (item.location) |loc| diagnostic.Span.fromPosition(loc.file, loc.line, loc.column) else null;
+        try Diagnostic.init(.Warning, message, span, notes, help).withCode(.unused_pub).printWithSourceMap(allocator, &source_map);
+        reported += 1;
+    }
+
+    if (reported == 0) {
+        std.debug.print("✅ No unused public items in {s}\n", .{library_file});
+    } else {
+        std.debug.print("\x1b[1;33mwarning\x1b[0m: {d} unused public item(s) in {s}\n", .{ reported, library_file });
+    }
+    return reported;
+}
+
+fn newItem(kind: []const u8, is_public: bool, allow: ast.LintSet, location: ?ast.SourceLocation) Item {
+    return Item{
+        .kind = kind,
+        .is_public = is_public,
+        .allowed = allow.contains(.unused_pub),
+        .location = location,
+        .references = std.ArrayList([]const u8){},
+    };
+}
+
+/// 从 root 出发沿引用能到达的项都标记为已使用；include_root 为 false 时 root 本身不算
+/// （公开项递归调用自己不代表它被用到）
+fn markReachable(
+    arena: std.mem.Allocator,
+    items: *const std.StringArrayHashMap(Item),
+    root: []const u8,
+    used: *std.StringHashMap(void),
+    include_root: bool,
+) !void {
+    var visited = std.StringHashMap(void).init(arena);
+    defer visited.deinit();
+    var stack = std.ArrayList([]const u8){};
+    defer stack.deinit(arena);
+
+    try visited.put(root, {});
+    if (include_root) try used.put(root, {});
+    try stack.append(arena, root);
+    while (stack.pop()) |name| {
+        const item = items.get(name) orelse continue;
+        for (item.references.items) |ref| {
+            if (visited.contains(ref)) continue;
+            try visited.put(ref, {});
+            try used.put(ref, {});
+            try stack.append(arena, ref);
+        }
+    }
+}
+
+/// 测试文件中提到的 pub 项（及其引用的项）都算被用到
+fn markTestReferences(
+    arena: std.mem.Allocator,
+    path: []const u8,
+    items: *const std.StringArrayHashMap(Item),
+    used: *std.StringHashMap(void),
+) !void {
+    const source = std.fs.cwd().readFileAlloc(arena, path, 10 * 1024 * 1024) catch |err| {
+        std.debug.print("Warning: Cannot read test file {s}: {any}\n", .{ path, err });
+        return;
+    };
+    var lexer = Lexer.init(arena, source, path);
+    defer lexer.deinit();
+    const tokens = lexer.tokenize() catch return;  // 词法错误已经打印
+
+    for (tokens) |token| {
+        if (token.type != .identifier) continue;
+        const item = items.get(token.lexeme) orelse continue;
+        if (item.is_public and !used.contains(token.lexeme)) {
+            try markReachable(arena, items, token.lexeme, used, true);
+        }
+    }
+}
+
+/// 顶层声明的结束位置：深度为 0 的 `;`，或使深度回到 0 的 `}`
+/// （声明前的属性 @allow(...) 属于同一个声明）
+fn declEnd(tokens: []const Token, start: usize) usize {
+    var depth: usize = 0;
+    var i = start;
+    while (i < tokens.len and tokens[i].type != .eof) : (i += 1) {
+        switch (tokens[i].type) {
+            .lparen, .lbrace, .lbracket => depth += 1,
+            .rparen, .rbracket => depth -|= 1,
+            .rbrace => {
+                depth -|= 1;
+                if (depth == 0) return i + 1;
+            },
+            .semicolon => if (depth == 0) return i + 1,
+            else => {},
+        }
+    }
+    return i;
+}
+
+/// 🆕 impl 块的目标类型 token 的下标：`impl Trait for Type`（impl 和 for 是上下文关键字）
+fn implTarget(decl: []const Token) ?usize {
+    var i: usize = 0;
+    // 跳过声明前的属性
+    while (i < decl.len and decl[i].type == .at) : (i = declAttributeEnd(decl, i)) {}
+    if (i >= decl.len or decl[i].type != .identifier or !std.mem.eql(u8, decl[i].lexeme, "impl")) return null;
+    while (i + 1 < decl.len and decl[i].type != .lbrace) : (i += 1) {
+        if (decl[i].type == .identifier and std.mem.eql(u8, decl[i].lexeme, "for") and decl[i + 1].type == .identifier) return i + 1;
+    }
+    return null;
+}
+
+/// 属性 @name(...) 之后的下标
+fn declAttributeEnd(decl: []const Token, start: usize) usize {
+    var i = start + 1;
+    if (i < decl.len and decl[i].type == .identifier) i += 1;
+    if (i >= decl.len or decl[i].type != .lparen) return i;
+    var depth: usize = 0;
+    while (i < decl.len) : (i += 1) {
+        switch (decl[i].type) {
+            .lparen => depth += 1,
+            .rparen => {
+                depth -= 1;
+                if (depth == 0) return i + 1;
+            },
+            else => {},
+        }
+    }
+    return i;
+}
+
+/// 声明名 token 的下标：深度为 0 的 fn / type / const 之后的标识符
+fn declName(decl: []const Token) ?usize {
+    var depth: usize = 0;
+    for (decl, 0..) |token, i| {
+        switch (token.type) {
+            .lparen, .lbrace, .lbracket => depth += 1,
+            .rparen, .rbrace, .rbracket => depth -|= 1,
+            .keyword_fn, .keyword_type, .keyword_const => {
+                if (depth == 0 and i + 1 < decl.len and decl[i + 1].type == .identifier) return i + 1;
+            },
+            else => {},
+        }
+    }
+    return null;
+}
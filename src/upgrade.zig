@@ -0,0 +1,205 @@
+//! Upgrade - 编译器自我更新（pawc upgrade）
+//!
+//! pawc upgrade [--dry-run] [--proxy <url>]
+//!
+//! 1. 下载发布索引 <endpoint>/latest.txt（endpoint 默认 DEFAULT_ENDPOINT，可通过 PAW_RELEASES 覆盖）：
+//!      version 0.2.0
+//!      x86_64-linux pawc-0.2.0-x86_64-linux 3f5a...（文件名相对于 endpoint，SHA-256）
+//!      aarch64-macos pawc-0.2.0-aarch64-macos 9c1e...
+//! 2. 版本不比当前编译器新，或索引中没有本机三元组（<arch>-<os>）时停止
+//! 3. 下载到可执行文件所在目录的临时文件，校验 SHA-256，然后原子地替换当前可执行文件
+//!
+//! --dry-run 只检查并报告将要安装的版本。下载使用 curl：代理取自 --proxy，
+//! 否则取自 HTTPS_PROXY / HTTP_PROXY / NO_PROXY 环境变量（curl 自行处理）。
+
+const std = @import("std");
+const builtin = @import("builtin");
+const Sha256 = std.crypto.hash.sha2.Sha256;
+
+/// 默认发布地址（可通过 PAW_RELEASES 覆盖）
+pub const DEFAULT_ENDPOINT = "https://releases.pawlang.dev/pawc";
+const INDEX_FILE = "latest.txt";
+
+pub const Options = struct {
+    dry_run: bool = false,       // 只检查，不下载
+    proxy: ?[]const u8 = null,   // 传给 curl --proxy
+};
+
+/// 本机三元组（与发布索引中的写法一致）
+pub const HOST_TRIPLE = @tagName(builtin.cpu.arch) ++ "-" ++ @tagName(builtin.os.tag);
+
+/// 发布索引中本机可用的构建
+const Release = struct {
+    version: []const u8,
+    file: []const u8,
+    sha256: [Sha256.digest_length]u8,
+};
+
+/// 检查并安装新版本；出错时返回 false
+pub fn run(allocator: std.mem.Allocator, current_version: []const u8, options: Options) !bool {
+    const endpoint = std.process.getEnvVarOwned(allocator, "PAW_RELEASES") catch
+        try allocator.dupe(u8, DEFAULT_ENDPOINT);
+    defer allocator.free(endpoint);
+    const base = std.mem.trimRight(u8, endpoint, "/");
+
+    const index_url = try std.fmt.allocPrint(allocator, "{s}/{s}", .{ base, INDEX_FILE });
+    defer allocator.free(index_url);
+    const index = download(allocator, index_url, null, options.proxy) catch {
+        std.debug.print("❌ Cannot fetch the release index from {s}\n", .{index_url});
+        std.debug.print("💡 Behind a proxy? Pass --proxy <url> or set HTTPS_PROXY\n", .{});
+        return false;
+    };
+    defer allocator.free(index);
+
+    const release = parseIndex(index, HOST_TRIPLE) catch |err| {
+        switch (err) {
+            error.NoBuildForHost => std.debug.print("❌ The latest release has no build for {s}\n", .{HOST_TRIPLE}),
+            error.InvalidIndex => std.debug.print("❌ Malformed release index at {s}\n", .{index_url}),
+        }
+        return false;
+    };
+
+    if (compareVersions(release.version, current_version) != .gt) {
+        std.debug.print("✅ pawc {s} is up to date (latest release: {s})\n", .{ current_version, release.version });
+        return true;
+    }
+
+    const file_url = try std.fmt.allocPrint(allocator, "{s}/{s}", .{ base, release.file });
+    defer allocator.free(file_url);
+    if (options.dry_run) {
+        std.debug.print("🔍 pawc {s} -> {s} ({s})\n", .{ current_version, release.version, HOST_TRIPLE });
+        std.debug.print("   would download {s}\n", .{file_url});
+        return true;
+    }
+
+    const self_exe = try std.fs.selfExePathAlloc(allocator);
+    defer allocator.free(self_exe);
+    const exe_dir = std.fs.path.dirname(self_exe) orelse ".";
+    // 临时文件与可执行文件在同一目录，重命名才是原子的
+    const tmp_path = try std.fs.path.join(allocator, &.{ exe_dir, ".pawc-upgrade.tmp" });
+    defer allocator.free(tmp_path);
+    defer std.fs.deleteFileAbsolute(tmp_path) catch {};
+
+    std.debug.print("📥 Downloading pawc {s} for {s}...\n", .{ release.version, HOST_TRIPLE });
+    _ = download(allocator, file_url, tmp_path, options.proxy) catch {
+        std.debug.print("❌ Failed to download {s}\n", .{file_url});
+        return false;
+    };
+
+    if (!try verifyChecksum(allocator, tmp_path, release.sha256)) {
+        std.debug.print("❌ Checksum mismatch for {s}; the current compiler was left unchanged\n", .{file_url});
+        return false;
+    }
+
+    replaceExecutable(allocator, tmp_path, self_exe) catch |err| {
+        std.debug.print("❌ Cannot replace {s}: {s}\n", .{ self_exe, @errorName(err) });
+        std.debug.print("💡 Check that you can write to {s}\n", .{exe_dir});
+        return false;
+    };
+    std.debug.print("✅ Upgraded pawc {s} -> {s}\n", .{ current_version, release.version });
+    return true;
+}
+
+/// 在索引中查找本机的构建
+fn parseIndex(index: []const u8, triple: []const u8) error{ InvalidIndex, NoBuildForHost }!Release {
+    var version: ?[]const u8 = null;
+    var found: ?Release = null;
+
+    var lines = std.mem.splitScalar(u8, index, '\n');
+    while (lines.next()) |raw_line| {
+        const line = std.mem.trim(u8, raw_line, " \t\r");
+        if (line.len == 0 or line[0] == '#') continue;
+        var fields = std.mem.tokenizeAny(u8, line, " \t");
+        const first = fields.next().?;
+        if (std.mem.eql(u8, first, "version")) {
+            version = fields.next() orelse return error.InvalidIndex;
+            continue;
+        }
+        if (!std.mem.eql(u8, first, triple)) continue;
+        const file = fields.next() orelse return error.InvalidIndex;
+        const hex = fields.next() orelse return error.InvalidIndex;
+        var release = Release{ .version = "", .file = file, .sha256 = undefined };
+        if (hex.len != Sha256.digest_length * 2) return error.InvalidIndex;
+        _ = std.fmt.hexToBytes(&release.sha256, hex) catch return error.InvalidIndex;
+        found = release;
+    }
+
+    var release = found orelse return error.NoBuildForHost;
+    release.version = version orelse return error.InvalidIndex;
+    return release;
+}
+
+/// 比较 major.minor.patch；版本号相同时，预发布版本（0.1.9-dev）比正式版本旧
+fn compareVersions(a: []const u8, b: []const u8) std.math.Order {
+    const a_core, const a_pre = splitPreRelease(a);
+    const b_core, const b_pre = splitPreRelease(b);
+    var a_parts = std.mem.splitScalar(u8, a_core, '.');
+    var b_parts = std.mem.splitScalar(u8, b_core, '.');
+    for (0..3) |_| {
+        const x = std.fmt.parseInt(u64, a_parts.next() orelse "0", 10) catch 0;
+        const y = std.fmt.parseInt(u64, b_parts.next() orelse "0", 10) catch 0;
+        if (x != y) return std.math.order(x, y);
+    }
+    if (a_pre == b_pre) return .eq;
+    return if (a_pre) .lt else .gt;
+}
+
+fn splitPreRelease(version: []const u8) struct { []const u8, bool } {
+    const v = std.mem.trimLeft(u8, version, "v");
+    if (std.mem.indexOfScalar(u8, v, '-')) |dash| return .{ v[0..dash], true };
+    return .{ v, false };
+}
+
+/// 用 curl 下载：output 为 null 时返回内容（调用者释放），否则写入文件并返回空切片
+fn download(allocator: std.mem.Allocator, url: []const u8, output: ?[]const u8, proxy: ?[]const u8) ![]const u8 {
+    var argv = std.ArrayList([]const u8){};
+    defer argv.deinit(allocator);
+    try argv.appendSlice(allocator, &.{ "curl", "-fsSL" });
+    if (proxy) |p| try argv.appendSlice(allocator, &.{ "--proxy", p });
+    if (output) |path| try argv.appendSlice(allocator, &.{ "-o", path });
+    try argv.append(allocator, url);
+
+    const result = try std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = argv.items,
+        .max_output_bytes = 1024 * 1024,
+    });
+    defer allocator.free(result.stderr);
+    errdefer allocator.free(result.stdout);
+
+    if (result.term != .Exited or result.term.Exited != 0) {
+        if (result.stderr.len > 0) std.debug.print("{s}", .{result.stderr});
+        return error.DownloadFailed;
+    }
+    if (output != null) {
+        allocator.free(result.stdout);
+        return "";
+    }
+    return result.stdout;
+}
+
+fn verifyChecksum(allocator: std.mem.Allocator, path: []const u8, expected: [Sha256.digest_length]u8) !bool {
+    const data = try std.fs.cwd().readFileAlloc(allocator, path, 1024 * 1024 * 1024);
+    defer allocator.free(data);
+    var digest: [Sha256.digest_length]u8 = undefined;
+    Sha256.hash(data, &digest, .{});
+    return std.mem.eql(u8, &digest, &expected);
+}
+
+/// 原子替换：同一目录内重命名。Windows 不能覆盖正在运行的可执行文件，
+/// 先把它移到 pawc.old（下次升级时覆盖）
+fn replaceExecutable(allocator: std.mem.Allocator, new_path: []const u8, exe_path: []const u8) !void {
+    if (builtin.os.tag == .windows) {
+        const old_path = try std.fmt.allocPrint(allocator, "{s}.old", .{exe_path});
+        defer allocator.free(old_path);
+        std.fs.deleteFileAbsolute(old_path) catch {};
+        try std.fs.renameAbsolute(exe_path, old_path);
+        errdefer std.fs.renameAbsolute(old_path, exe_path) catch {};
+        try std.fs.renameAbsolute(new_path, exe_path);
+        return;
+    }
+    const file = try std.fs.openFileAbsolute(new_path, .{});
+    defer file.close();
+    try file.chmod(0o755);
+    try std.fs.renameAbsolute(new_path, exe_path);
+}